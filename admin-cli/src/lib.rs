@@ -1 +1,2 @@
+pub mod provision;
 pub mod tui;
\ No newline at end of file
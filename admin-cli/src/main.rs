@@ -1,6 +1,7 @@
-mod tui;
-
+use admin_cli::provision::{self, ProvisionJob, Secrets};
+use admin_cli::tui;
 use clap::{Parser, Subcommand};
+use std::path::PathBuf;
 
 #[derive(Parser)]
 #[command(name = "linguabridge-admin")]
@@ -13,7 +14,38 @@ struct Cli {
 #[derive(Subcommand)]
 enum Commands {
     /// Launch the Terminal User Interface
-    Tui,
+    Tui {
+        /// Watch the wallet in config (`wallet.address`) without signing or
+        /// broadcasting any transaction
+        #[arg(long)]
+        read_only: bool,
+    },
+    /// Send the bot its secrets over the admin endpoint
+    Provision {
+        /// Bot admin endpoint, e.g. http://localhost:9999
+        #[arg(long)]
+        bot_url: String,
+        /// Path the bot serves its admin endpoints under (its
+        /// `admin.path_prefix`), e.g. /bot-admin
+        #[arg(long)]
+        path_prefix: Option<String>,
+        /// Discord bot token
+        #[arg(long)]
+        discord_token: String,
+        /// Admin Ed25519 private key file
+        #[arg(long, default_value = "admin.key")]
+        admin_key: PathBuf,
+        /// Hugging Face token for the inference service
+        #[arg(long)]
+        hf_token: Option<String>,
+        /// Extra secret as KEY=VALUE (repeatable)
+        #[arg(long, value_parser = provision::parse_custom)]
+        custom: Vec<(String, String)>,
+        /// Check reachability, keys and signing, and list what would be
+        /// sent, without sending it
+        #[arg(long)]
+        dry_run: bool,
+    },
 }
 
 #[tokio::main]
@@ -21,8 +53,31 @@ async fn main() -> anyhow::Result<()> {
     let cli = Cli::parse();
 
     match cli.command {
-        Commands::Tui => {
-            tui::run_tui().await
+        Commands::Tui { read_only } => {
+            tui::run_tui(read_only).await
+        }
+        Commands::Provision {
+            bot_url,
+            path_prefix,
+            discord_token,
+            admin_key,
+            hf_token,
+            custom,
+            dry_run,
+        } => {
+            let job = ProvisionJob {
+                bot_url,
+                path_prefix,
+                admin_key,
+                secrets: Secrets {
+                    discord_token,
+                    hf_token,
+                    custom: custom.into_iter().collect(),
+                },
+                dry_run,
+            };
+            print!("{}", provision::run(&job).await?);
+            Ok(())
         }
     }
 }
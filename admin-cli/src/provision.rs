@@ -0,0 +1,706 @@
+//! Client side of the bot's admin provisioning endpoints.
+//!
+//! The bot serves `GET /status`, `GET /pubkey` and `POST /provision` on its
+//! admin port. Provisioning fetches the bot's ephemeral X25519 key, derives a
+//! shared secret with a fresh key of our own, encrypts the secrets with
+//! ChaCha20-Poly1305 and signs `our_x25519_public || ciphertext || nonce`
+//! with the admin Ed25519 key the bot was configured with.
+//!
+//! A dropped connection while posting leaves it unclear whether the bot got
+//! the secrets, so [`provision`] asks `/status` before trying again rather
+//! than running into the bot's "already provisioned" answer.
+//!
+//! [`run`] is the entry point: the `provision` command and the TUI's Discord
+//! setup screen both describe a [`ProvisionJob`] and hand it over, so there is
+//! one implementation of the flow to audit.
+
+use anyhow::{anyhow, bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Nonce};
+use ed25519_dalek::{Signer, SigningKey, Verifier};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use x25519_dalek::{EphemeralSecret, PublicKey};
+use zeroize::Zeroize;
+
+/// How long each admin endpoint request may take
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Attempts at sending the secrets when the connection keeps dropping
+const MAX_ATTEMPTS: u32 = 3;
+
+/// Pause before checking on the bot after a dropped connection
+const RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// Secrets sent to the bot; mirrors the bot's `SecretsPayload`.
+#[derive(Debug, Default, Serialize)]
+pub struct Secrets {
+    pub discord_token: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hf_token: Option<String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub custom: BTreeMap<String, String>,
+}
+
+impl Secrets {
+    /// Name and length of every secret that would be sent, never the values
+    pub fn summary(&self) -> Vec<(String, usize)> {
+        let mut entries = vec![("discord_token".to_string(), self.discord_token.len())];
+        if let Some(hf_token) = &self.hf_token {
+            entries.push(("hf_token".to_string(), hf_token.len()));
+        }
+        for (key, value) in &self.custom {
+            entries.push((format!("custom.{}", key), value.len()));
+        }
+        entries
+    }
+}
+
+impl Drop for Secrets {
+    fn drop(&mut self) {
+        self.discord_token.zeroize();
+        if let Some(token) = &mut self.hf_token {
+            token.zeroize();
+        }
+        for value in self.custom.values_mut() {
+            value.zeroize();
+        }
+    }
+}
+
+/// Parse a `KEY=VALUE` custom secret
+pub fn parse_custom(entry: &str) -> Result<(String, String)> {
+    match entry.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => bail!("custom secrets must look like KEY=VALUE, got {:?}", entry.split('=').next().unwrap_or_default()),
+    }
+}
+
+/// Body of `POST /provision`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProvisionRequest {
+    pub admin_x25519_public: String,
+    pub ciphertext: String,
+    pub nonce: String,
+    pub signature: String,
+}
+
+/// Read the admin Ed25519 signing key: a base64-encoded 32-byte seed.
+pub fn load_signing_key(path: &Path) -> Result<SigningKey> {
+    let mut contents = std::fs::read_to_string(path)
+        .with_context(|| format!("reading admin key {}", path.display()))?;
+    let decoded = BASE64.decode(contents.trim());
+    contents.zeroize();
+    let mut bytes = decoded.with_context(|| format!("{} is not base64", path.display()))?;
+    let seed: [u8; 32] = bytes.as_slice().try_into().map_err(|_| {
+        anyhow!("{} must hold a 32-byte key, found {} bytes", path.display(), bytes.len())
+    })?;
+    bytes.zeroize();
+    Ok(SigningKey::from_bytes(&seed))
+}
+
+/// Base64 public half of `signing_key`, as configured on the bot in
+/// `LINGUABRIDGE_ADMIN__PUBLIC_KEY`
+pub fn admin_public_key(signing_key: &SigningKey) -> String {
+    BASE64.encode(signing_key.verifying_key().to_bytes())
+}
+
+/// Encrypt `secrets` to the bot's `bot_public` key and sign the result.
+pub fn seal(secrets: &Secrets, bot_public: &PublicKey, signing_key: &SigningKey) -> Result<ProvisionRequest> {
+    let ours = EphemeralSecret::random_from_rng(OsRng);
+    let our_public = PublicKey::from(&ours);
+    let shared = ours.diffie_hellman(bot_public);
+
+    let mut plaintext = serde_json::to_vec(secrets)?;
+    let mut nonce = [0u8; 12];
+    OsRng.fill_bytes(&mut nonce);
+    let cipher = ChaCha20Poly1305::new_from_slice(shared.as_bytes())
+        .map_err(|_| anyhow!("shared secret is not a valid key"))?;
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), plaintext.as_slice())
+        .map_err(|_| anyhow!("encrypting secrets failed"));
+    plaintext.zeroize();
+    let ciphertext = ciphertext?;
+
+    let mut message = Vec::with_capacity(32 + ciphertext.len() + nonce.len());
+    message.extend_from_slice(our_public.as_bytes());
+    message.extend_from_slice(&ciphertext);
+    message.extend_from_slice(&nonce);
+    let signature = signing_key.sign(&message);
+    // Catch a broken key before the bot has to
+    signing_key
+        .verifying_key()
+        .verify(&message, &signature)
+        .map_err(|_| anyhow!("signature did not verify against the admin key"))?;
+
+    Ok(ProvisionRequest {
+        admin_x25519_public: BASE64.encode(our_public.as_bytes()),
+        ciphertext: BASE64.encode(&ciphertext),
+        nonce: BASE64.encode(nonce),
+        signature: BASE64.encode(signature.to_bytes()),
+    })
+}
+
+#[derive(Deserialize)]
+struct StatusBody {
+    status: String,
+}
+
+#[derive(Deserialize)]
+struct PublicKeyBody {
+    public_key: String,
+}
+
+#[derive(Deserialize)]
+struct ErrorBody {
+    error: String,
+}
+
+/// Why posting the secrets failed
+#[derive(Debug)]
+pub enum SendError {
+    /// No answer; the bot may or may not have stored the secrets
+    Connection(reqwest::Error),
+    /// The bot answered and refused them
+    Rejected(anyhow::Error),
+}
+
+impl std::fmt::Display for SendError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Connection(e) => write!(f, "connection failed: {}", e),
+            Self::Rejected(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for SendError {}
+
+/// Base URL of a bot's admin endpoints: `bot_url` followed by the bot's
+/// `admin.path_prefix`, if it serves them under one.
+pub fn admin_base_url(bot_url: &str, path_prefix: Option<&str>) -> String {
+    let bot_url = bot_url.trim_end_matches('/');
+    match path_prefix.map(|prefix| prefix.trim().trim_matches('/')) {
+        Some(prefix) if !prefix.is_empty() => format!("{}/{}", bot_url, prefix),
+        _ => bot_url.to_string(),
+    }
+}
+
+/// HTTP client for one bot's admin endpoints.
+pub struct BotAdminClient {
+    http: reqwest::Client,
+    bot_url: String,
+    retry_delay: Duration,
+}
+
+impl BotAdminClient {
+    pub fn new(bot_url: &str) -> Result<Self> {
+        Self::with_timeout(bot_url, REQUEST_TIMEOUT)
+    }
+
+    /// Client whose requests give up after `timeout`
+    pub fn with_timeout(bot_url: &str, timeout: Duration) -> Result<Self> {
+        let http = reqwest::Client::builder().timeout(timeout).build()?;
+        Ok(Self {
+            http,
+            bot_url: bot_url.trim_end_matches('/').to_string(),
+            retry_delay: RETRY_DELAY,
+        })
+    }
+
+    /// Wait `delay` before checking on the bot after a dropped connection
+    pub fn with_retry_delay(mut self, delay: Duration) -> Self {
+        self.retry_delay = delay;
+        self
+    }
+
+    /// Whether the bot already holds its secrets
+    pub async fn is_provisioned(&self) -> Result<bool> {
+        let response = self
+            .http
+            .get(format!("{}/status", self.bot_url))
+            .send()
+            .await
+            .with_context(|| format!("bot unreachable at {}", self.bot_url))?;
+        let body: StatusBody = checked(response).await?.json().await?;
+        Ok(body.status == "provisioned")
+    }
+
+    /// The bot's ephemeral X25519 key for this boot
+    pub async fn public_key(&self) -> Result<PublicKey> {
+        let response = self.http.get(format!("{}/pubkey", self.bot_url)).send().await?;
+        let body: PublicKeyBody = checked(response).await?.json().await?;
+        let bytes: [u8; 32] = BASE64
+            .decode(&body.public_key)
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| anyhow!("bot returned a malformed public key"))?;
+        Ok(PublicKey::from(bytes))
+    }
+
+    /// Send sealed secrets to the bot
+    pub async fn provision(&self, request: &ProvisionRequest) -> Result<(), SendError> {
+        let response = self
+            .http
+            .post(format!("{}/provision", self.bot_url))
+            .json(request)
+            .send()
+            .await
+            .map_err(SendError::Connection)?;
+        checked(response).await.map_err(SendError::Rejected)?;
+        Ok(())
+    }
+}
+
+/// Turn an error status into an error carrying the bot's explanation
+async fn checked(response: reqwest::Response) -> Result<reqwest::Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    match response.json::<ErrorBody>().await {
+        Ok(body) => bail!("bot responded {}: {}", status, body.error),
+        Err(_) => bail!("bot responded {}", status),
+    }
+}
+
+/// What a dry run found, without anything having been sent.
+#[derive(Debug)]
+pub struct DryRunReport {
+    pub bot_url: String,
+    pub admin_public_key: String,
+    /// Name and length of each secret that would be sent
+    pub secrets: Vec<(String, usize)>,
+    pub ciphertext_bytes: usize,
+}
+
+impl std::fmt::Display for DryRunReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Dry run: nothing was sent to {}", self.bot_url)?;
+        writeln!(f, "  bot reachable:      yes, waiting for provisioning")?;
+        writeln!(f, "  bot public key:     fetched")?;
+        writeln!(f, "  admin public key:   {}", self.admin_public_key)?;
+        writeln!(f, "                      (must match LINGUABRIDGE_ADMIN__PUBLIC_KEY on the bot)")?;
+        writeln!(f, "  encrypt and sign:   ok, {} byte ciphertext", self.ciphertext_bytes)?;
+        writeln!(f, "  would send:")?;
+        for (name, len) in &self.secrets {
+            writeln!(f, "    {:<20} {} chars", name, len)?;
+        }
+        Ok(())
+    }
+}
+
+/// Run every provisioning step except the final POST.
+pub async fn dry_run(client: &BotAdminClient, secrets: &Secrets, signing_key: &SigningKey) -> Result<DryRunReport> {
+    if client.is_provisioned().await? {
+        bail!("bot at {} is already provisioned; restart it to provision again", client.bot_url);
+    }
+    let bot_public = client.public_key().await.context("fetching bot public key")?;
+    let request = seal(secrets, &bot_public, signing_key)?;
+    Ok(DryRunReport {
+        bot_url: client.bot_url.clone(),
+        admin_public_key: admin_public_key(signing_key),
+        secrets: secrets.summary(),
+        ciphertext_bytes: BASE64.decode(&request.ciphertext)?.len(),
+    })
+}
+
+/// How a provisioning run ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProvisionOutcome {
+    /// The bot accepted the secrets
+    Provisioned { attempts: u32 },
+    /// The connection dropped, but the bot then reported itself provisioned
+    ConfirmedByStatus { attempts: u32 },
+}
+
+impl std::fmt::Display for ProvisionOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Provisioned { attempts: 1 } => write!(f, "provisioned"),
+            Self::Provisioned { attempts } => write!(f, "provisioned (after {} attempts)", attempts),
+            Self::ConfirmedByStatus { .. } => write!(
+                f,
+                "provisioned (the connection dropped, but the bot reports it has its secrets)"
+            ),
+        }
+    }
+}
+
+/// Fetch the bot's key, seal `secrets` and send them, retrying when the
+/// connection drops and the bot turns out not to have them.
+pub async fn provision(
+    client: &BotAdminClient,
+    secrets: &Secrets,
+    signing_key: &SigningKey,
+) -> Result<ProvisionOutcome> {
+    if client.is_provisioned().await? {
+        bail!(
+            "bot at {} is already provisioned; nothing was sent (restart it to provision again)",
+            client.bot_url
+        );
+    }
+
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        // The bot discards its key on every request it receives, so each
+        // attempt needs a fresh one
+        let bot_public = client.public_key().await.with_context(|| {
+            if attempts > 1 {
+                "the bot received an earlier attempt but didn't store the secrets; restart it and provision again"
+            } else {
+                "fetching bot public key"
+            }
+        })?;
+        let request = seal(secrets, &bot_public, signing_key)?;
+
+        let dropped = match client.provision(&request).await {
+            Ok(()) => return Ok(ProvisionOutcome::Provisioned { attempts }),
+            Err(SendError::Rejected(e)) => return Err(e.context("bot rejected the secrets")),
+            Err(SendError::Connection(e)) => e,
+        };
+
+        tokio::time::sleep(client.retry_delay).await;
+        match client.is_provisioned().await {
+            Ok(true) => return Ok(ProvisionOutcome::ConfirmedByStatus { attempts }),
+            Ok(false) if attempts < MAX_ATTEMPTS => continue,
+            Ok(false) => bail!(
+                "not provisioned: the connection dropped on all {} attempts (last error: {})",
+                attempts,
+                dropped
+            ),
+            Err(e) => bail!(
+                "unknown whether the bot was provisioned: the connection dropped ({}) and /status \
+                 is unreachable ({:#}); check it with the TUI or `curl {}/status`",
+                dropped,
+                e,
+                client.bot_url
+            ),
+        }
+    }
+}
+
+/// One provisioning run, as described by the CLI flags or the TUI form.
+pub struct ProvisionJob {
+    /// Bot admin endpoint, e.g. http://localhost:9999
+    pub bot_url: String,
+    /// The bot's `admin.path_prefix`, if it serves its endpoints under one
+    pub path_prefix: Option<String>,
+    /// Admin Ed25519 private key file
+    pub admin_key: PathBuf,
+    pub secrets: Secrets,
+    /// Check reachability, keys and signing without sending anything
+    pub dry_run: bool,
+}
+
+/// What a [`run`] did.
+#[derive(Debug)]
+pub enum JobReport {
+    DryRun(DryRunReport),
+    Sent { bot_url: String, outcome: ProvisionOutcome },
+}
+
+impl std::fmt::Display for JobReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::DryRun(report) => report.fmt(f),
+            Self::Sent { bot_url, outcome } => writeln!(f, "Bot at {}: {}", bot_url, outcome),
+        }
+    }
+}
+
+/// Load the admin key and provision the bot, or dry-run it.
+pub async fn run(job: &ProvisionJob) -> Result<JobReport> {
+    let bot_url = admin_base_url(&job.bot_url, job.path_prefix.as_deref());
+    run_with_client(job, &BotAdminClient::new(&bot_url)?).await
+}
+
+async fn run_with_client(job: &ProvisionJob, client: &BotAdminClient) -> Result<JobReport> {
+    let signing_key = load_signing_key(&job.admin_key)?;
+    if job.dry_run {
+        return Ok(JobReport::DryRun(dry_run(client, &job.secrets, &signing_key).await?));
+    }
+    let outcome = provision(client, &job.secrets, &signing_key).await?;
+    Ok(JobReport::Sent {
+        bot_url: client.bot_url.clone(),
+        outcome,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::routing::{get, post};
+    use axum::{Json, Router};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    fn secrets() -> Secrets {
+        Secrets {
+            discord_token: "discord-secret".to_string(),
+            hf_token: Some("hf-secret".to_string()),
+            custom: [("API".to_string(), "xyz".to_string())].into(),
+        }
+    }
+
+    #[test]
+    fn test_sealed_request_opens_on_the_bot_side() {
+        let signing_key = SigningKey::generate(&mut OsRng);
+        let bot_secret = EphemeralSecret::random_from_rng(OsRng);
+        let bot_public = PublicKey::from(&bot_secret);
+
+        let request = seal(&secrets(), &bot_public, &signing_key).unwrap();
+
+        // What the bot does on POST /provision
+        let ours: [u8; 32] = BASE64.decode(&request.admin_x25519_public).unwrap().try_into().unwrap();
+        let ciphertext = BASE64.decode(&request.ciphertext).unwrap();
+        let nonce = BASE64.decode(&request.nonce).unwrap();
+        let signature: [u8; 64] = BASE64.decode(&request.signature).unwrap().try_into().unwrap();
+        let message = [ours.as_slice(), &ciphertext, &nonce].concat();
+        signing_key
+            .verifying_key()
+            .verify(&message, &ed25519_dalek::Signature::from_bytes(&signature))
+            .unwrap();
+
+        let shared = bot_secret.diffie_hellman(&PublicKey::from(ours));
+        let plaintext = ChaCha20Poly1305::new_from_slice(shared.as_bytes())
+            .unwrap()
+            .decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+            .unwrap();
+        let payload: serde_json::Value = serde_json::from_slice(&plaintext).unwrap();
+        assert_eq!(payload["discord_token"], "discord-secret");
+        assert_eq!(payload["custom"]["API"], "xyz");
+    }
+
+    #[test]
+    fn test_summary_lists_lengths_not_values() {
+        let summary = secrets().summary();
+        assert_eq!(
+            summary,
+            vec![
+                ("discord_token".to_string(), 14),
+                ("hf_token".to_string(), 9),
+                ("custom.API".to_string(), 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_custom() {
+        assert_eq!(parse_custom("A=b=c").unwrap(), ("A".to_string(), "b=c".to_string()));
+        assert!(parse_custom("=x").is_err());
+        assert!(parse_custom("novalue").is_err());
+    }
+
+    /// How the mock bot answers POST /provision
+    #[derive(Clone, Copy)]
+    enum OnPost {
+        Accept,
+        /// Store the secrets but never answer
+        StoreThenHang,
+        /// Hang without storing on the first request, then accept
+        HangOnce,
+    }
+
+    #[derive(Default)]
+    struct MockBot {
+        provisioned: std::sync::atomic::AtomicBool,
+        posts: AtomicUsize,
+    }
+
+    /// Bot stand-in recording POST /provision calls
+    async fn mock_bot(provisioned: bool, on_post: OnPost) -> (String, Arc<MockBot>) {
+        let bot = Arc::new(MockBot::default());
+        bot.provisioned.store(provisioned, Ordering::SeqCst);
+        let bot_public = PublicKey::from(&EphemeralSecret::random_from_rng(OsRng));
+        let status_bot = bot.clone();
+        let post_bot = bot.clone();
+        let app = Router::new()
+            .route(
+                "/status",
+                get(move || async move {
+                    let status = if status_bot.provisioned.load(Ordering::SeqCst) {
+                        "provisioned"
+                    } else {
+                        "waiting_for_provisioning"
+                    };
+                    Json(serde_json::json!({ "status": status }))
+                }),
+            )
+            .route(
+                "/pubkey",
+                get(move || async move {
+                    Json(serde_json::json!({ "public_key": BASE64.encode(bot_public.as_bytes()) }))
+                }),
+            )
+            .route(
+                "/provision",
+                post(move || async move {
+                    let previous = post_bot.posts.fetch_add(1, Ordering::SeqCst);
+                    let hang = match on_post {
+                        OnPost::Accept => false,
+                        OnPost::StoreThenHang => {
+                            post_bot.provisioned.store(true, Ordering::SeqCst);
+                            true
+                        }
+                        OnPost::HangOnce => previous == 0,
+                    };
+                    if hang {
+                        tokio::time::sleep(Duration::from_secs(60)).await;
+                    }
+                    post_bot.provisioned.store(true, Ordering::SeqCst);
+                    Json(serde_json::json!({ "success": true }))
+                }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (url, bot)
+    }
+
+    fn impatient_client(url: &str) -> BotAdminClient {
+        BotAdminClient::with_timeout(url, Duration::from_millis(300))
+            .unwrap()
+            .with_retry_delay(Duration::from_millis(10))
+    }
+
+    #[test]
+    fn test_admin_base_url_appends_prefix() {
+        assert_eq!(admin_base_url("http://bot:9999/", None), "http://bot:9999");
+        assert_eq!(admin_base_url("http://bot:9999", Some("")), "http://bot:9999");
+        assert_eq!(admin_base_url("https://bot.example", Some("/admin/")), "https://bot.example/admin");
+        assert_eq!(admin_base_url("https://bot.example/", Some("admin")), "https://bot.example/admin");
+    }
+
+    #[tokio::test]
+    async fn test_client_reaches_prefixed_endpoints() {
+        let app = Router::new().nest(
+            "/bot-admin",
+            Router::new().route(
+                "/status",
+                get(|| async { Json(serde_json::json!({ "status": "provisioned" })) }),
+            ),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let client = BotAdminClient::new(&admin_base_url(&url, Some("/bot-admin"))).unwrap();
+        assert!(client.is_provisioned().await.unwrap());
+        assert!(BotAdminClient::new(&url).unwrap().is_provisioned().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_never_posts() {
+        let (url, bot) = mock_bot(false, OnPost::Accept).await;
+        let client = BotAdminClient::new(&url).unwrap();
+        let signing_key = SigningKey::generate(&mut OsRng);
+
+        let report = dry_run(&client, &secrets(), &signing_key).await.unwrap();
+        assert_eq!(bot.posts.load(Ordering::SeqCst), 0);
+        assert_eq!(report.admin_public_key, admin_public_key(&signing_key));
+        let shown = report.to_string();
+        assert!(shown.contains("discord_token"));
+        assert!(!shown.contains("discord-secret"));
+
+        let outcome = provision(&client, &secrets(), &signing_key).await.unwrap();
+        assert_eq!(outcome, ProvisionOutcome::Provisioned { attempts: 1 });
+        assert_eq!(bot.posts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_dropped_connection_after_delivery_counts_as_success() {
+        let (url, bot) = mock_bot(false, OnPost::StoreThenHang).await;
+        let outcome = provision(&impatient_client(&url), &secrets(), &SigningKey::generate(&mut OsRng))
+            .await
+            .unwrap();
+        assert_eq!(outcome, ProvisionOutcome::ConfirmedByStatus { attempts: 1 });
+        // Not sent a second time
+        assert_eq!(bot.posts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_dropped_connection_before_delivery_is_retried() {
+        let (url, bot) = mock_bot(false, OnPost::HangOnce).await;
+        let outcome = provision(&impatient_client(&url), &secrets(), &SigningKey::generate(&mut OsRng))
+            .await
+            .unwrap();
+        assert_eq!(outcome, ProvisionOutcome::Provisioned { attempts: 2 });
+        assert_eq!(bot.posts.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_already_provisioned_bot_is_left_alone() {
+        let (url, bot) = mock_bot(true, OnPost::Accept).await;
+        let err = provision(&impatient_client(&url), &secrets(), &SigningKey::generate(&mut OsRng))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("nothing was sent"));
+        assert_eq!(bot.posts.load(Ordering::SeqCst), 0);
+    }
+
+    /// A job for `url` whose admin key is `signing_key`, written to a file
+    /// the caller removes
+    fn job(url: &str, signing_key: &SigningKey, dry_run: bool) -> ProvisionJob {
+        let admin_key = std::env::temp_dir().join(format!("linguabridge-admin-{}.key", rand::random::<u64>()));
+        std::fs::write(&admin_key, BASE64.encode(signing_key.to_bytes())).unwrap();
+        ProvisionJob {
+            bot_url: url.to_string(),
+            path_prefix: None,
+            admin_key,
+            secrets: secrets(),
+            dry_run,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_job_dry_runs_then_sends() {
+        let (url, bot) = mock_bot(false, OnPost::Accept).await;
+        let signing_key = SigningKey::generate(&mut OsRng);
+
+        let dry = job(&url, &signing_key, true);
+        let report = run(&dry).await.unwrap();
+        std::fs::remove_file(&dry.admin_key).unwrap();
+        match report {
+            JobReport::DryRun(report) => {
+                assert_eq!(report.admin_public_key, admin_public_key(&signing_key))
+            }
+            other => panic!("expected a dry run, got {:?}", other),
+        }
+        assert_eq!(bot.posts.load(Ordering::SeqCst), 0);
+
+        let send = job(&url, &signing_key, false);
+        let report = run(&send).await.unwrap();
+        std::fs::remove_file(&send.admin_key).unwrap();
+        assert_eq!(report.to_string(), format!("Bot at {}: provisioned\n", url));
+        assert_eq!(bot.posts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_job_without_admin_key_sends_nothing() {
+        let (url, bot) = mock_bot(false, OnPost::Accept).await;
+        let mut missing = job(&url, &SigningKey::generate(&mut OsRng), false);
+        std::fs::remove_file(&missing.admin_key).unwrap();
+        missing.admin_key = missing.admin_key.with_extension("missing");
+
+        let err = run(&missing).await.unwrap_err();
+        assert!(err.to_string().contains("reading admin key"));
+        assert_eq!(bot.posts.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_reports_provisioned_bot() {
+        let (url, _) = mock_bot(true, OnPost::Accept).await;
+        let client = BotAdminClient::new(&url).unwrap();
+        let err = dry_run(&client, &secrets(), &SigningKey::generate(&mut OsRng))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("already provisioned"));
+    }
+}
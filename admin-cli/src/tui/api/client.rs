@@ -2,7 +2,44 @@ use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
 use linguabridge_types::cosmos::bank::v1beta1::{
     query_client::QueryClient as BankQueryClient, QueryBalanceRequest,
 };
+use linguabridge_types::cosmos::base::query::v1beta1::{PageRequest, PageResponse};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use crate::tui::config::schema::DEFAULT_DENOM;
+
+/// Default number of retries per endpoint for idempotent queries.
+pub const DEFAULT_QUERY_RETRIES: u32 = 2;
+
+/// Longest wait for one HTTP or gRPC request, so a hung endpoint fails over
+/// instead of stalling the query.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Base delay for query retry backoff (doubles per attempt).
+const RETRY_BASE_DELAY_MS: u64 = 250;
+
+/// Page size requested from the LCD for list queries.
+const PAGE_LIMIT: u64 = 100;
+
+/// Most pages fetched for one list query; a wallet with more entries than
+/// `PAGE_LIMIT * MAX_PAGES` gets a truncated list (see [`Paged::is_truncated`]).
+const MAX_PAGES: usize = 20;
+
+/// Results of a paginated list query.
+#[derive(Debug, Clone)]
+pub struct Paged<T> {
+    pub items: Vec<T>,
+    /// Total number of matching entries reported by the chain
+    pub total: u64,
+}
+
+impl<T> Paged<T> {
+    /// Whether the page cap was hit before every entry was fetched.
+    pub fn is_truncated(&self) -> bool {
+        (self.items.len() as u64) < self.total
+    }
+}
 
 /// Deployment info from chain queries.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -12,6 +49,16 @@ pub struct DeploymentInfo {
     pub state: String,
 }
 
+/// Escrow account funding a deployment's leases.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EscrowInfo {
+    pub dseq: u64,
+    /// Unspent deposit in base-denom units, fractions dropped
+    pub balance: u64,
+    pub denom: String,
+    pub state: String,
+}
+
 /// Bid info from market queries.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BidInfo {
@@ -79,9 +126,30 @@ struct LcdCoin {
     amount: String,
 }
 
+/// LCD JSON form of `PageResponse`: the key is base64 and the total a string.
+#[derive(Deserialize)]
+struct LcdPagination {
+    next_key: Option<String>,
+    total: Option<String>,
+}
+
+impl LcdPagination {
+    fn into_page_response(self) -> Result<PageResponse, Box<dyn std::error::Error>> {
+        let next_key = match self.next_key.as_deref() {
+            Some(key) if !key.is_empty() => BASE64.decode(key)?,
+            _ => Vec::new(),
+        };
+        Ok(PageResponse {
+            next_key,
+            total: self.total.and_then(|t| t.parse().ok()).unwrap_or(0),
+        })
+    }
+}
+
 #[derive(Deserialize)]
 struct LcdDeploymentsResp {
     deployments: Option<Vec<LcdDeploymentEntry>>,
+    pagination: Option<LcdPagination>,
 }
 
 #[derive(Deserialize)]
@@ -101,9 +169,23 @@ struct LcdDeploymentId {
     dseq: String,
 }
 
+#[derive(Deserialize)]
+struct LcdDeploymentInfoResp {
+    escrow_account: LcdEscrowAccount,
+}
+
+#[derive(Deserialize)]
+struct LcdEscrowAccount {
+    state: String,
+    balance: LcdDecCoin,
+    /// Deposits from other depositors, spent before `balance`
+    funds: Option<LcdDecCoin>,
+}
+
 #[derive(Deserialize)]
 struct LcdBidsResp {
     bids: Option<Vec<LcdBidEntry>>,
+    pagination: Option<LcdPagination>,
 }
 
 #[derive(Deserialize)]
@@ -135,6 +217,7 @@ struct LcdDecCoin {
 #[derive(Deserialize)]
 struct LcdLeasesResp {
     leases: Option<Vec<LcdLeaseEntry>>,
+    pagination: Option<LcdPagination>,
 }
 
 #[derive(Deserialize)]
@@ -158,6 +241,33 @@ struct LcdLeaseId {
     provider: String,
 }
 
+#[derive(Deserialize)]
+struct LcdProviderResp {
+    provider: LcdProvider,
+}
+
+#[derive(Deserialize)]
+struct LcdProvider {
+    host_uri: String,
+}
+
+#[derive(Deserialize)]
+struct LcdCertificatesResp {
+    certificates: Option<Vec<LcdCertificateEntry>>,
+    pagination: Option<LcdPagination>,
+}
+
+#[derive(Deserialize)]
+struct LcdCertificateEntry {
+    certificate: LcdCertificate,
+}
+
+#[derive(Deserialize)]
+struct LcdCertificate {
+    /// Base64 of the PEM bytes
+    cert: String,
+}
+
 #[derive(Serialize)]
 struct BroadcastTxReq {
     tx_bytes: String,
@@ -177,9 +287,20 @@ struct TxResp {
 }
 
 /// REST + gRPC client for Akash Network queries.
+///
+/// Read-only queries are retried with exponential backoff and rotate through
+/// fallback endpoints. Transaction broadcasts are never retried automatically.
 pub struct AkashClient {
     pub base_url: String,
     pub grpc_url: String,
+    fallback_urls: Vec<String>,
+    fallback_grpc_urls: Vec<String>,
+    max_retries: u32,
+    /// Index of the last endpoint that answered, tried first next time
+    preferred: AtomicUsize,
+    preferred_grpc: AtomicUsize,
+    /// Base denom balances and fee grant limits are read in
+    denom: String,
     http: reqwest::Client,
 }
 
@@ -188,17 +309,157 @@ impl AkashClient {
         Self {
             base_url: base_url.trim_end_matches('/').to_string(),
             grpc_url: grpc_url.trim_end_matches('/').to_string(),
-            http: reqwest::Client::new(),
+            fallback_urls: Vec::new(),
+            fallback_grpc_urls: Vec::new(),
+            max_retries: DEFAULT_QUERY_RETRIES,
+            preferred: AtomicUsize::new(0),
+            preferred_grpc: AtomicUsize::new(0),
+            denom: DEFAULT_DENOM.to_string(),
+            http: reqwest::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
         }
     }
 
+    /// Read balances and fee grant limits in `denom` instead of `uakt`.
+    pub fn with_denom(mut self, denom: &str) -> Self {
+        self.denom = denom.to_string();
+        self
+    }
+
+    /// Add fallback REST and gRPC endpoints used when the primary ones fail.
+    pub fn with_fallbacks(mut self, urls: &[String], grpc_urls: &[String]) -> Self {
+        self.fallback_urls = urls.iter().map(|u| u.trim_end_matches('/').to_string()).collect();
+        self.fallback_grpc_urls = grpc_urls
+            .iter()
+            .map(|u| u.trim_end_matches('/').to_string())
+            .collect();
+        self
+    }
+
+    /// Override how many times each endpoint is retried for queries.
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// REST endpoints in the order they should be tried.
+    fn endpoints(&self) -> Vec<&str> {
+        rotate_endpoints(&self.base_url, &self.fallback_urls, self.preferred.load(Ordering::Relaxed))
+    }
+
+    /// gRPC endpoints in the order they should be tried.
+    fn grpc_endpoints(&self) -> Vec<&str> {
+        rotate_endpoints(
+            &self.grpc_url,
+            &self.fallback_grpc_urls,
+            self.preferred_grpc.load(Ordering::Relaxed),
+        )
+    }
+
+    /// GET a JSON document from the LCD, retrying transient failures across endpoints.
+    async fn get_json<T: DeserializeOwned>(
+        &self,
+        path: &str,
+    ) -> Result<T, Box<dyn std::error::Error>> {
+        let endpoints = self.endpoints();
+        let mut last_error = String::from("no endpoints configured");
+
+        for attempt in 0..=self.max_retries {
+            if attempt > 0 {
+                tokio::time::sleep(retry_delay(attempt)).await;
+            }
+            for endpoint in &endpoints {
+                let url = format!("{}{}", endpoint, path);
+                match self.http.get(&url).send().await {
+                    Ok(resp) if is_retryable_status(resp.status()) => {
+                        last_error = format!("{} returned {}", endpoint, resp.status());
+                    }
+                    Ok(resp) => {
+                        let parsed = resp.json::<T>().await?;
+                        self.mark_preferred(&self.preferred, &self.base_url, &self.fallback_urls, endpoint);
+                        return Ok(parsed);
+                    }
+                    Err(e) => {
+                        last_error = format!("{}: {}", endpoint, e);
+                    }
+                }
+            }
+        }
+
+        Err(format!(
+            "query failed after {} attempts: {}",
+            self.max_retries + 1,
+            last_error
+        )
+        .into())
+    }
+
+    /// GET every page of an LCD list query, following `next_key` until the
+    /// chain reports no more pages or `MAX_PAGES` is reached.
+    ///
+    /// `path` must already carry a query string; `split` pulls the entries
+    /// and pagination out of each page.
+    async fn get_paged<R, T>(
+        &self,
+        path: &str,
+        split: impl Fn(R) -> (Vec<T>, Option<LcdPagination>),
+    ) -> Result<Paged<T>, Box<dyn std::error::Error>>
+    where
+        R: DeserializeOwned,
+    {
+        let mut request = PageRequest {
+            limit: PAGE_LIMIT,
+            count_total: true,
+            ..Default::default()
+        };
+        let mut items = Vec::new();
+        let mut total = 0;
+
+        for _ in 0..MAX_PAGES {
+            let resp: R = self
+                .get_json(&format!("{}&{}", path, page_query(&request)))
+                .await?;
+            let (page, pagination) = split(resp);
+            items.extend(page);
+
+            let page_resp = match pagination {
+                Some(p) => p.into_page_response()?,
+                None => break,
+            };
+            // Only the first page carries the total
+            if request.key.is_empty() {
+                total = page_resp.total;
+            }
+            if page_resp.next_key.is_empty() {
+                break;
+            }
+            request.key = page_resp.next_key;
+            request.count_total = false;
+        }
+
+        let total = total.max(items.len() as u64);
+        Ok(Paged { items, total })
+    }
+
+    fn mark_preferred(&self, slot: &AtomicUsize, primary: &str, fallbacks: &[String], endpoint: &str) {
+        // Index into the same deduplicated list `rotate_endpoints` rotates
+        let index = unique_endpoints(primary, fallbacks)
+            .iter()
+            .position(|e| *e == endpoint)
+            .unwrap_or(0);
+        slot.store(index, Ordering::Relaxed);
+    }
+
     /// Query account info (account_number, sequence) for tx signing.
     pub async fn get_account_info(
         &self,
         address: &str,
     ) -> Result<AccountInfo, Box<dyn std::error::Error>> {
-        let url = format!("{}/cosmos/auth/v1beta1/accounts/{}", self.base_url, address);
-        let resp: LcdAccountResp = self.http.get(&url).send().await?.json().await?;
+        let resp: LcdAccountResp = self
+            .get_json(&format!("/cosmos/auth/v1beta1/accounts/{}", address))
+            .await?;
         let account = resp.account.ok_or("account not found")?;
         let base = account.base_account.as_deref().unwrap_or(&account);
         Ok(AccountInfo {
@@ -217,20 +478,73 @@ impl AkashClient {
         &self,
         address: &str,
     ) -> Result<Balance, Box<dyn std::error::Error>> {
-        let channel = tonic::transport::Channel::from_shared(self.grpc_url.clone())?
+        let endpoints = self.grpc_endpoints();
+        let mut last_error = String::from("no endpoints configured");
+
+        for attempt in 0..=self.max_retries {
+            if attempt > 0 {
+                tokio::time::sleep(retry_delay(attempt)).await;
+            }
+            for endpoint in &endpoints {
+                match Self::query_balance_at(endpoint, address, &self.denom).await {
+                    Ok(balance) => {
+                        self.mark_preferred(
+                            &self.preferred_grpc,
+                            &self.grpc_url,
+                            &self.fallback_grpc_urls,
+                            endpoint,
+                        );
+                        return Ok(balance);
+                    }
+                    Err(e) => last_error = format!("{}: {}", endpoint, e),
+                }
+            }
+        }
+
+        Err(format!(
+            "balance query failed after {} attempts: {}",
+            self.max_retries + 1,
+            last_error
+        )
+        .into())
+    }
+
+    async fn query_balance_at(
+        grpc_url: &str,
+        address: &str,
+        denom: &str,
+    ) -> Result<Balance, String> {
+        // Covers connecting as well as the call; a node can accept the
+        // connection and then never answer
+        tokio::time::timeout(REQUEST_TIMEOUT, Self::fetch_balance(grpc_url, address, denom))
+            .await
+            .map_err(|_| format!("no reply within {}s", REQUEST_TIMEOUT.as_secs()))?
+    }
+
+    async fn fetch_balance(
+        grpc_url: &str,
+        address: &str,
+        denom: &str,
+    ) -> Result<Balance, String> {
+        let channel = tonic::transport::Channel::from_shared(grpc_url.to_string())
+            .map_err(|e| e.to_string())?
+            .connect_timeout(REQUEST_TIMEOUT)
+            .timeout(REQUEST_TIMEOUT)
             .connect()
-            .await?;
+            .await
+            .map_err(|e| e.to_string())?;
         let mut client = BankQueryClient::new(channel);
         let resp = client
             .balance(QueryBalanceRequest {
                 address: address.to_string(),
-                denom: "uakt".to_string(),
+                denom: denom.to_string(),
             })
-            .await?;
+            .await
+            .map_err(|e| e.to_string())?;
         let coin = resp.into_inner().balance.unwrap_or_default();
         Ok(Balance {
             denom: if coin.denom.is_empty() {
-                "uakt".to_string()
+                denom.to_string()
             } else {
                 coin.denom
             },
@@ -246,22 +560,96 @@ impl AkashClient {
     pub async fn query_deployments(
         &self,
         owner: &str,
-    ) -> Result<Vec<DeploymentInfo>, Box<dyn std::error::Error>> {
-        let url = format!(
-            "{}/akash/deployment/v1beta3/deployments/list?filters.owner={}",
-            self.base_url, owner
-        );
-        let resp: LcdDeploymentsResp = self.http.get(&url).send().await?.json().await?;
-        Ok(resp
-            .deployments
-            .unwrap_or_default()
-            .into_iter()
-            .map(|e| DeploymentInfo {
-                owner: e.deployment.deployment_id.owner,
-                dseq: e.deployment.deployment_id.dseq.parse().unwrap_or(0),
-                state: e.deployment.state,
+    ) -> Result<Paged<DeploymentInfo>, Box<dyn std::error::Error>> {
+        self.get_paged(
+            &format!(
+                "/akash/deployment/v1beta3/deployments/list?filters.owner={}",
+                owner
+            ),
+            |resp: LcdDeploymentsResp| {
+                let deployments = resp
+                    .deployments
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|e| DeploymentInfo {
+                        owner: e.deployment.deployment_id.owner,
+                        dseq: e.deployment.deployment_id.dseq.parse().unwrap_or(0),
+                        state: e.deployment.state,
+                    })
+                    .collect();
+                (deployments, resp.pagination)
+            },
+        )
+        .await
+    }
+
+    /// Query the escrow account of deployment `dseq`.
+    pub async fn query_escrow(
+        &self,
+        owner: &str,
+        dseq: u64,
+    ) -> Result<EscrowInfo, Box<dyn std::error::Error>> {
+        let resp: LcdDeploymentInfoResp = self
+            .get_json(&format!(
+                "/akash/deployment/v1beta3/deployments/info?id.owner={}&id.dseq={}",
+                owner, dseq
+            ))
+            .await?;
+        let escrow = resp.escrow_account;
+        let funds = escrow.funds.as_ref().map_or(0, |f| dec_coin_units(&f.amount));
+        Ok(EscrowInfo {
+            dseq,
+            balance: dec_coin_units(&escrow.balance.amount) + funds,
+            denom: escrow.balance.denom,
+            state: escrow.state,
+        })
+    }
+
+    /// Base URL of a provider's API, from its on-chain registration. Leases
+    /// and bids name providers by account address, which isn't reachable.
+    pub async fn query_provider_host(&self, provider: &str) -> Result<String, Box<dyn std::error::Error>> {
+        let resp: LcdProviderResp = self
+            .get_json(&format!("/akash/provider/v1beta3/providers/{}", provider))
+            .await?;
+        let host_uri = resp.provider.host_uri.trim_end_matches('/').to_string();
+        if host_uri.is_empty() {
+            return Err(format!("provider {} has no host URI registered", provider).into());
+        }
+        Ok(host_uri)
+    }
+
+    /// PEM certificates `owner` has published that the cert module still
+    /// considers valid. Providers serve one of these for TLS, and clients
+    /// must have theirs here before providers accept it.
+    pub async fn query_certificates(
+        &self,
+        owner: &str,
+    ) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let paged = self
+            .get_paged(
+                &format!(
+                    "/akash/cert/v1beta3/certificates/list?filter.owner={}&filter.state=valid",
+                    owner
+                ),
+                |resp: LcdCertificatesResp| {
+                    let certs = resp
+                        .certificates
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|e| e.certificate.cert)
+                        .collect();
+                    (certs, resp.pagination)
+                },
+            )
+            .await?;
+        paged
+            .items
+            .iter()
+            .map(|encoded| {
+                let pem = BASE64.decode(encoded)?;
+                Ok(String::from_utf8(pem)?)
             })
-            .collect())
+            .collect()
     }
 
     /// Query bids for a specific deployment.
@@ -269,56 +657,66 @@ impl AkashClient {
         &self,
         owner: &str,
         dseq: u64,
-    ) -> Result<Vec<BidInfo>, Box<dyn std::error::Error>> {
-        let url = format!(
-            "{}/akash/market/v1beta4/bids/list?filters.owner={}&id.dseq={}",
-            self.base_url, owner, dseq
-        );
-        let resp: LcdBidsResp = self.http.get(&url).send().await?.json().await?;
-        Ok(resp
-            .bids
-            .unwrap_or_default()
-            .into_iter()
-            .map(|e| BidInfo {
-                provider: e.bid.bid_id.provider,
-                dseq: e.bid.bid_id.dseq.parse().unwrap_or(0),
-                gseq: e.bid.bid_id.gseq,
-                oseq: e.bid.bid_id.oseq,
-                price_amount: e.bid.price.amount,
-                price_denom: e.bid.price.denom,
-                state: e.bid.state,
-            })
-            .collect())
+    ) -> Result<Paged<BidInfo>, Box<dyn std::error::Error>> {
+        self.get_paged(
+            &format!(
+                "/akash/market/v1beta4/bids/list?filters.owner={}&id.dseq={}",
+                owner, dseq
+            ),
+            |resp: LcdBidsResp| {
+                let bids = resp
+                    .bids
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|e| BidInfo {
+                        provider: e.bid.bid_id.provider,
+                        dseq: e.bid.bid_id.dseq.parse().unwrap_or(0),
+                        gseq: e.bid.bid_id.gseq,
+                        oseq: e.bid.bid_id.oseq,
+                        price_amount: e.bid.price.amount,
+                        price_denom: e.bid.price.denom,
+                        state: e.bid.state,
+                    })
+                    .collect();
+                (bids, resp.pagination)
+            },
+        )
+        .await
     }
 
     /// Query active leases for an address.
     pub async fn query_leases(
         &self,
         owner: &str,
-    ) -> Result<Vec<LeaseInfo>, Box<dyn std::error::Error>> {
-        let url = format!(
-            "{}/akash/market/v1beta4/leases/list?filters.owner={}",
-            self.base_url, owner
-        );
-        let resp: LcdLeasesResp = self.http.get(&url).send().await?.json().await?;
-        Ok(resp
-            .leases
-            .unwrap_or_default()
-            .into_iter()
-            .map(|e| LeaseInfo {
-                owner: e.lease.lease_id.owner,
-                dseq: e.lease.lease_id.dseq.parse().unwrap_or(0),
-                gseq: e.lease.lease_id.gseq,
-                oseq: e.lease.lease_id.oseq,
-                provider: e.lease.lease_id.provider,
-                price_amount: e.lease.price.amount,
-                price_denom: e.lease.price.denom,
-                state: e.lease.state,
-            })
-            .collect())
+    ) -> Result<Paged<LeaseInfo>, Box<dyn std::error::Error>> {
+        self.get_paged(
+            &format!("/akash/market/v1beta4/leases/list?filters.owner={}", owner),
+            |resp: LcdLeasesResp| {
+                let leases = resp
+                    .leases
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|e| LeaseInfo {
+                        owner: e.lease.lease_id.owner,
+                        dseq: e.lease.lease_id.dseq.parse().unwrap_or(0),
+                        gseq: e.lease.lease_id.gseq,
+                        oseq: e.lease.lease_id.oseq,
+                        provider: e.lease.lease_id.provider,
+                        price_amount: e.lease.price.amount,
+                        price_denom: e.lease.price.denom,
+                        state: e.lease.state,
+                    })
+                    .collect();
+                (leases, resp.pagination)
+            },
+        )
+        .await
     }
 
     /// Broadcast a signed transaction (BROADCAST_MODE_SYNC).
+    ///
+    /// Only the primary endpoint is used and failures are not retried, so a
+    /// transaction is never submitted twice behind the caller's back.
     pub async fn broadcast_tx(
         &self,
         tx_bytes: &[u8],
@@ -341,11 +739,9 @@ impl AkashClient {
 
     /// Get the latest block height (useful for dseq).
     pub async fn get_block_height(&self) -> Result<u64, Box<dyn std::error::Error>> {
-        let url = format!(
-            "{}/cosmos/base/tendermint/v1beta1/blocks/latest",
-            self.base_url
-        );
-        let resp: serde_json::Value = self.http.get(&url).send().await?.json().await?;
+        let resp: serde_json::Value = self
+            .get_json("/cosmos/base/tendermint/v1beta1/blocks/latest")
+            .await?;
         let height = resp["block"]["header"]["height"]
             .as_str()
             .unwrap_or("0")
@@ -382,6 +778,31 @@ impl AkashClient {
             tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
         }
     }
+
+    /// Wait for the certificate tx `txhash` to land, then check the cert
+    /// module lists `cert_pem` as valid for `owner`. A broadcast accepted by
+    /// CheckTx can still fail in the block or never be included at all.
+    pub async fn confirm_certificate(
+        &self,
+        txhash: &str,
+        owner: &str,
+        cert_pem: &str,
+        timeout_secs: u64,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let included = self.wait_for_tx(txhash, timeout_secs).await?;
+        if included.code != 0 {
+            return Err(format!(
+                "tx {} failed (code {}): {}",
+                txhash, included.code, included.raw_log
+            )
+            .into());
+        }
+        let published = self.query_certificates(owner).await?;
+        if !published.iter().any(|pem| pem.trim() == cert_pem.trim()) {
+            return Err(format!("tx {} landed but the certificate is not on chain", txhash).into());
+        }
+        Ok(())
+    }
 }
 
 /// Fee grant allowance info.
@@ -417,11 +838,9 @@ impl AkashClient {
         &self,
         grantee: &str,
     ) -> Result<Vec<FeeAllowanceInfo>, Box<dyn std::error::Error>> {
-        let url = format!(
-            "{}/cosmos/feegrant/v1beta1/allowances/{}",
-            self.base_url, grantee
-        );
-        let resp: LcdAllowancesResp = self.http.get(&url).send().await?.json().await?;
+        let resp: LcdAllowancesResp = self
+            .get_json(&format!("/cosmos/feegrant/v1beta1/allowances/{}", grantee))
+            .await?;
         Ok(resp
             .allowances
             .unwrap_or_default()
@@ -436,7 +855,7 @@ impl AkashClient {
 
                 let spend_limit = inner
                     .and_then(|a| a.spend_limit.as_ref())
-                    .and_then(|coins| coins.iter().find(|c| c.denom == "uakt"))
+                    .and_then(|coins| coins.iter().find(|c| c.denom == self.denom))
                     .map(|c| Balance {
                         amount: c.amount.clone(),
                         denom: c.denom.clone(),
@@ -454,6 +873,58 @@ impl AkashClient {
     }
 }
 
+/// Whole base-denom units of a `DecCoin` amount such as `"4999.5000"`.
+fn dec_coin_units(amount: &str) -> u64 {
+    amount.split('.').next().and_then(|units| units.parse().ok()).unwrap_or(0)
+}
+
+/// Order endpoints starting from the last known-good one, primary first otherwise.
+fn rotate_endpoints<'a>(primary: &'a str, fallbacks: &'a [String], start: usize) -> Vec<&'a str> {
+    let mut all = unique_endpoints(primary, fallbacks);
+    let start = start.min(all.len().saturating_sub(1));
+    all.rotate_left(start);
+    all
+}
+
+/// The primary endpoint then each fallback, skipping repeats wherever they are.
+fn unique_endpoints<'a>(primary: &'a str, fallbacks: &'a [String]) -> Vec<&'a str> {
+    let mut all: Vec<&str> = Vec::new();
+    for endpoint in std::iter::once(primary).chain(fallbacks.iter().map(String::as_str)) {
+        if !all.contains(&endpoint) {
+            all.push(endpoint);
+        }
+    }
+    all
+}
+
+/// Rate limits and server errors are worth retrying; other statuses are final.
+fn is_retryable_status(status: reqwest::StatusCode) -> bool {
+    status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// Backoff before retry round `attempt` (1-based).
+fn retry_delay(attempt: u32) -> Duration {
+    Duration::from_millis(RETRY_BASE_DELAY_MS * 2u64.pow(attempt.saturating_sub(1)))
+}
+
+/// LCD query parameters for a `PageRequest`. The key is base64, so its
+/// `+`, `/` and `=` need escaping in the URL.
+fn page_query(request: &PageRequest) -> String {
+    let mut query = format!(
+        "pagination.limit={}&pagination.count_total={}",
+        request.limit, request.count_total
+    );
+    if !request.key.is_empty() {
+        let key = BASE64
+            .encode(&request.key)
+            .replace('+', "%2B")
+            .replace('/', "%2F")
+            .replace('=', "%3D");
+        query.push_str(&format!("&pagination.key={}", key));
+    }
+    query
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -467,4 +938,95 @@ mod tests {
         assert_eq!(client.base_url, "https://api.akashnet.net");
         assert_eq!(client.grpc_url, "https://grpc.akashnet.net:443");
     }
+
+    #[test]
+    fn endpoints_rotate_from_last_healthy() {
+        let client = AkashClient::new("https://a/".to_string(), "https://g".to_string())
+            .with_fallbacks(&["https://b".to_string(), "https://c/".to_string()], &[]);
+        assert_eq!(client.endpoints(), vec!["https://a", "https://b", "https://c"]);
+
+        client.mark_preferred(&client.preferred, &client.base_url, &client.fallback_urls, "https://c");
+        assert_eq!(client.endpoints(), vec!["https://c", "https://a", "https://b"]);
+        assert_eq!(client.grpc_endpoints(), vec!["https://g"]);
+    }
+
+    #[test]
+    fn duplicate_endpoints_prefer_the_one_that_answered() {
+        let fallbacks = ["https://b".to_string(), "https://a".to_string(), "https://c".to_string()];
+        let client = AkashClient::new("https://a".to_string(), "https://g".to_string())
+            .with_fallbacks(&fallbacks, &[]);
+        assert_eq!(client.endpoints(), vec!["https://a", "https://b", "https://c"]);
+
+        client.mark_preferred(&client.preferred, &client.base_url, &client.fallback_urls, "https://c");
+        assert_eq!(client.endpoints(), vec!["https://c", "https://a", "https://b"]);
+    }
+
+    #[test]
+    fn retryable_statuses() {
+        assert!(is_retryable_status(reqwest::StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_retryable_status(reqwest::StatusCode::BAD_GATEWAY));
+        assert!(!is_retryable_status(reqwest::StatusCode::NOT_FOUND));
+        assert!(!is_retryable_status(reqwest::StatusCode::OK));
+    }
+
+    #[test]
+    fn retry_delay_doubles() {
+        assert_eq!(retry_delay(1), Duration::from_millis(250));
+        assert_eq!(retry_delay(2), Duration::from_millis(500));
+        assert_eq!(retry_delay(3), Duration::from_millis(1000));
+    }
+
+    #[test]
+    fn dec_coin_units_drop_fractions() {
+        assert_eq!(dec_coin_units("4999.999999000000000000"), 4999);
+        assert_eq!(dec_coin_units("12"), 12);
+        assert_eq!(dec_coin_units(""), 0);
+    }
+
+    #[test]
+    fn page_query_escapes_key() {
+        let mut request = PageRequest {
+            limit: 100,
+            count_total: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            page_query(&request),
+            "pagination.limit=100&pagination.count_total=true"
+        );
+
+        request.key = vec![0xfb, 0xff];
+        request.count_total = false;
+        assert_eq!(
+            page_query(&request),
+            "pagination.limit=100&pagination.count_total=false&pagination.key=%2B%2F8%3D"
+        );
+    }
+
+    #[test]
+    fn lcd_pagination_decodes_key_and_total() {
+        let page = LcdPagination {
+            next_key: Some("AAE=".to_string()),
+            total: Some("50".to_string()),
+        }
+        .into_page_response()
+        .unwrap();
+        assert_eq!(page.next_key, vec![0, 1]);
+        assert_eq!(page.total, 50);
+
+        let last = LcdPagination { next_key: None, total: Some("0".to_string()) }
+            .into_page_response()
+            .unwrap();
+        assert!(last.next_key.is_empty());
+    }
+
+    #[tokio::test]
+    async fn query_fails_over_after_retries() {
+        // Nothing listens on these ports, so every attempt fails fast
+        let client = AkashClient::new("http://127.0.0.1:9".to_string(), "http://127.0.0.1:9".to_string())
+            .with_fallbacks(&["http://127.0.0.1:10".to_string()], &[])
+            .with_max_retries(1);
+        let err = client.query_leases("akash1owner").await.unwrap_err();
+        assert!(err.to_string().contains("after 2 attempts"));
+    }
 }
@@ -1,5 +1,5 @@
 pub mod client;
 pub mod provider;
 
-pub use client::{AkashClient, Balance, BidInfo, BroadcastResult, DeploymentInfo, FeeAllowanceInfo, LeaseInfo};
-pub use provider::{ProviderClient, ServiceStatus};
+pub use client::{AkashClient, Balance, BidInfo, BroadcastResult, DeploymentInfo, EscrowInfo, FeeAllowanceInfo, LeaseInfo, Paged};
+pub use provider::{ProviderClient, ProviderUnresponsive, ServiceStatus};
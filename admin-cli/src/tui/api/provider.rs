@@ -1,4 +1,31 @@
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::tui::wallet::cert::ProviderCertificate;
+
+/// Default timeout for a single provider API request.
+pub const DEFAULT_PROVIDER_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A provider didn't answer within the client's timeout; kept apart from
+/// other failures so callers can tell a hung provider from a rejected request.
+#[derive(Debug, Clone)]
+pub struct ProviderUnresponsive {
+    pub provider_url: String,
+    pub timeout: Duration,
+}
+
+impl std::fmt::Display for ProviderUnresponsive {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "provider unresponsive: no reply from {} within {}s",
+            self.provider_url,
+            self.timeout.as_secs()
+        )
+    }
+}
+
+impl std::error::Error for ProviderUnresponsive {}
 
 /// Service status from a provider.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,17 +54,71 @@ pub struct LogEntry {
 }
 
 /// Client for interacting with Akash provider REST APIs.
+///
+/// Providers serve self-signed certificates published on chain rather than
+/// ones issued by a public CA, so a client only reaches a provider once it
+/// has been [pinned](Self::pinned_to) to that provider's certificates.
 pub struct ProviderClient {
     http: reqwest::Client,
+    timeout: Duration,
+    /// Client certificate presented for mTLS, kept to rebuild pinned clients
+    identity: Option<reqwest::Identity>,
 }
 
 impl ProviderClient {
+    /// Client without a client certificate; authenticated endpoints will
+    /// reject it.
     pub fn new() -> Self {
         Self {
-            http: reqwest::Client::new(),
+            http: reqwest::Client::builder()
+                .build()
+                .expect("provider HTTP client config is static"),
+            timeout: DEFAULT_PROVIDER_TIMEOUT,
+            identity: None,
         }
     }
 
+    /// Client that authenticates as the lease owner with `cert` (mTLS).
+    pub fn with_certificate(cert: &ProviderCertificate) -> Result<Self, reqwest::Error> {
+        let identity = cert.identity()?;
+        Ok(Self {
+            http: reqwest::Client::builder().identity(identity.clone()).build()?,
+            timeout: DEFAULT_PROVIDER_TIMEOUT,
+            identity: Some(identity),
+        })
+    }
+
+    /// Give up on each request after `timeout` instead of the default.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Client that only trusts a server presenting one of `server_certs`,
+    /// the PEM certificates the provider published on chain (see
+    /// `AkashClient::query_certificates`). The client certificate carries over.
+    pub fn pinned_to(&self, server_certs: &[String]) -> Result<Self, Box<dyn std::error::Error>> {
+        if server_certs.is_empty() {
+            return Err("provider has no valid certificate on chain".into());
+        }
+        let mut builder = reqwest::Client::builder()
+            .tls_built_in_root_certs(false)
+            // The pinned certificate is the trust anchor; providers don't
+            // necessarily list their API host among its names
+            .danger_accept_invalid_hostnames(true);
+        for pem in server_certs {
+            builder = builder.add_root_certificate(reqwest::Certificate::from_pem(pem.as_bytes())?);
+        }
+        if let Some(identity) = &self.identity {
+            builder = builder.identity(identity.clone());
+        }
+        Ok(Self {
+            http: builder.build()?,
+            timeout: self.timeout,
+            identity: self.identity.clone(),
+        })
+    }
+
     /// Send the deployment manifest to the provider after lease creation.
     pub async fn send_manifest(
         &self,
@@ -50,7 +131,14 @@ impl ProviderClient {
             provider_url.trim_end_matches('/'),
             dseq
         );
-        let resp = self.http.put(&url).json(manifest_json).send().await?;
+        let resp = self
+            .http
+            .put(&url)
+            .json(manifest_json)
+            .timeout(self.timeout)
+            .send()
+            .await
+            .map_err(|e| self.classify(provider_url, e))?;
         if !resp.status().is_success() {
             let status = resp.status();
             let body = resp.text().await.unwrap_or_default();
@@ -72,7 +160,7 @@ impl ProviderClient {
             provider_url.trim_end_matches('/'),
             dseq, gseq, oseq
         );
-        let resp: serde_json::Value = self.http.get(&url).send().await?.json().await?;
+        let resp = self.get_json(provider_url, &url).await?;
         let mut services = Vec::new();
 
         if let Some(svcs) = resp.get("services") {
@@ -112,7 +200,7 @@ impl ProviderClient {
             provider_url.trim_end_matches('/'),
             dseq, gseq, oseq
         );
-        let resp: serde_json::Value = self.http.get(&url).send().await?.json().await?;
+        let resp = self.get_json(provider_url, &url).await?;
         let mut ports = Vec::new();
 
         if let Some(fwd) = resp.get("forwarded_ports") {
@@ -150,14 +238,20 @@ impl ProviderClient {
             provider_url.trim_end_matches('/'),
             dseq, gseq, oseq, service_name, tail
         );
-        let resp = self.http.get(&url).send().await?;
+        let resp = self
+            .http
+            .get(&url)
+            .timeout(self.timeout)
+            .send()
+            .await
+            .map_err(|e| self.classify(provider_url, e))?;
         if !resp.status().is_success() {
             let status = resp.status();
             let body = resp.text().await.unwrap_or_default();
             return Err(format!("failed to get logs ({}): {}", status, body).into());
         }
 
-        let body = resp.text().await?;
+        let body = resp.text().await.map_err(|e| self.classify(provider_url, e))?;
         let entries: Vec<LogEntry> = body
             .lines()
             .map(|line| LogEntry {
@@ -168,6 +262,33 @@ impl ProviderClient {
 
         Ok(entries)
     }
+
+    async fn get_json(
+        &self,
+        provider_url: &str,
+        url: &str,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+        let resp = self
+            .http
+            .get(url)
+            .timeout(self.timeout)
+            .send()
+            .await
+            .map_err(|e| self.classify(provider_url, e))?;
+        resp.json().await.map_err(|e| self.classify(provider_url, e))
+    }
+
+    /// Timeouts become [`ProviderUnresponsive`]; other errors pass through.
+    fn classify(&self, provider_url: &str, error: reqwest::Error) -> Box<dyn std::error::Error> {
+        if error.is_timeout() {
+            Box::new(ProviderUnresponsive {
+                provider_url: provider_url.to_string(),
+                timeout: self.timeout,
+            })
+        } else {
+            Box::new(error)
+        }
+    }
 }
 
 impl Default for ProviderClient {
@@ -184,4 +305,54 @@ mod tests {
     fn provider_client_creation() {
         let _client = ProviderClient::new();
     }
+
+    #[tokio::test]
+    async fn hung_provider_times_out_as_unresponsive() {
+        // Accepts connections but never answers
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut held = Vec::new();
+            while let Ok((socket, _)) = listener.accept().await {
+                held.push(socket);
+            }
+        });
+
+        let client = ProviderClient::new().with_timeout(Duration::from_millis(200));
+        let started = std::time::Instant::now();
+        let err = client
+            .get_logs(&format!("http://{}", addr), 1, 1, 1, "web", 10)
+            .await
+            .unwrap_err();
+        assert!(started.elapsed() < Duration::from_secs(5));
+        let unresponsive = err.downcast_ref::<ProviderUnresponsive>().expect("timeout error");
+        assert_eq!(unresponsive.timeout, Duration::from_millis(200));
+        assert!(err.to_string().starts_with("provider unresponsive"));
+    }
+
+    #[test]
+    fn provider_client_with_certificate() {
+        use crate::tui::wallet::keygen::KeyGenerator;
+
+        let gen = KeyGenerator::new();
+        let keypair = gen.derive_keypair(&gen.generate_mnemonic().unwrap()).unwrap();
+        let cert = ProviderCertificate::generate(&keypair, "akash1owner").unwrap();
+        assert!(ProviderClient::with_certificate(&cert).is_ok());
+    }
+
+    #[test]
+    fn pinning_requires_an_on_chain_certificate() {
+        use crate::tui::wallet::keygen::KeyGenerator;
+
+        let client = ProviderClient::new();
+        let err = client.pinned_to(&[]).err().expect("no certificate to pin");
+        assert!(err.to_string().contains("no valid certificate"));
+
+        let gen = KeyGenerator::new();
+        let keypair = gen.derive_keypair(&gen.generate_mnemonic().unwrap()).unwrap();
+        let server = ProviderCertificate::generate(&keypair, "akash1provider").unwrap();
+        let pinned = client.pinned_to(&[server.cert_pem]).unwrap();
+        assert_eq!(pinned.timeout, client.timeout);
+        assert!(client.pinned_to(&["not a certificate".to_string()]).is_err());
+    }
 }
@@ -1,13 +1,27 @@
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::time::Duration;
 use tokio::sync::mpsc;
 
-use crate::tui::api::{AkashClient, BidInfo, FeeAllowanceInfo, LeaseInfo, ProviderClient};
+use crate::provision::{self, JobReport, ProvisionJob, Secrets};
+use crate::tui::api::{
+    AkashClient, BidInfo, FeeAllowanceInfo, LeaseInfo, ProviderClient, ProviderUnresponsive,
+};
 use crate::tui::config::{AppConfig, ConfigStore};
+use crate::tui::escrow::{self, EscrowHealth, EscrowMonitor, EscrowStatus};
 use crate::tui::event::AppEvent;
 use crate::tui::gpu::GpuCatalog;
 use crate::tui::input::InputMode;
+use crate::tui::refresh::AutoRefresh;
 use crate::tui::sdl::SdlFile;
+use crate::tui::tx::{self, DepositDenom, PendingTx, TxAction, DEFAULT_GAS_LIMIT, DEPLOYMENT_GAS_LIMIT};
+use crate::tui::wallet::cert::ProviderCertificate;
 use crate::tui::wallet::keygen::KeyGenerator;
+use crate::tui::wallet::offline::{SignedTx, UnsignedTx, SIGNED_TX_FILE, UNSIGNED_TX_FILE};
+use crate::tui::wallet::signer::TransactionSigner;
 use crate::tui::wallet::Wallet;
 use crate::tui::widgets::{Form, LogViewer, Popup, PopupType, Spinner};
 
@@ -43,12 +57,39 @@ pub enum Screen {
     Deployments, // New: deployed bots dashboard
 }
 
+impl Screen {
+    /// What `key` would sign or broadcast on this screen, if anything;
+    /// read-only mode refuses these.
+    pub fn spending_action(self, key: KeyCode) -> Option<&'static str> {
+        match (self, key) {
+            (Screen::Wallet, KeyCode::Char('g')) => Some("Generating a wallet"),
+            (Screen::Wallet, KeyCode::Char('i')) => Some("Importing a mnemonic"),
+            (Screen::Wallet, KeyCode::Char('s')) => Some("Saving the wallet"),
+            (Screen::Wallet, KeyCode::Char('o')) => Some("Signing offline"),
+            (Screen::Wallet, KeyCode::Char('p')) => Some("Publishing a certificate"),
+            (Screen::FeeGrant, KeyCode::Char('r')) => Some("Requesting a fee grant"),
+            (Screen::Deployment, KeyCode::Char('d')) => Some("Deploying"),
+            (Screen::Deployment, KeyCode::Char('o')) => Some("Broadcasting a signed tx"),
+            (Screen::Bids, KeyCode::Enter) => Some("Accepting a bid"),
+            (Screen::Leases, KeyCode::Char('u')) => Some("Updating a deployment"),
+            (Screen::Deployments, KeyCode::Char('d')) => Some("Depositing to escrow"),
+            _ => None,
+        }
+    }
+}
+
 impl Default for Screen {
     fn default() -> Self {
         Screen::Splash
     }
 }
 
+/// Admin key file the Discord setup form starts with, as `provision` does
+const DEFAULT_ADMIN_KEY_FILE: &str = "admin.key";
+
+/// How long to wait for a broadcast tx to be included in a block
+const TX_CONFIRM_TIMEOUT_SECS: u64 = 60;
+
 /// Main application state
 pub struct App {
     pub current_screen: Screen,
@@ -72,9 +113,17 @@ pub struct App {
     pub popup: Option<Popup>,
     pub spinner: Spinner,
     pub status_message: Option<(String, bool)>, // (message, is_error)
+    /// Transaction shown in the TxConfirm popup, sent only on Enter
+    pub pending_tx: Option<PendingTx>,
 
     // Config
     pub config: AppConfig,
+    pub auto_refresh: AutoRefresh,
+    /// Escrow scans of active deployments; unlike `auto_refresh` these run
+    /// while the user is away
+    pub escrow_refresh: AutoRefresh,
+    /// Queries only: nothing is signed or broadcast (`tui --read-only`)
+    pub read_only: bool,
 }
 
 // --- Per-screen state ---
@@ -90,12 +139,12 @@ pub struct WalletState {
     pub import_text: String,
 }
 
-/// Minimum balance (in uakt) needed to deploy without a fee grant
-const MIN_DEPLOY_BALANCE_UAKT: u64 = 5_000_000; // 5 AKT
-
 pub struct FeeGrantState {
     pub balance: Option<String>,
     pub balance_uakt: u64,
+    /// Last balance seen per base denom, including deposit denoms other
+    /// than the network's
+    pub denom_balances: HashMap<String, u64>,
     pub fee_grant_status: String,
     pub allowance: Option<String>,
     pub allowances: Vec<FeeAllowanceInfo>,
@@ -142,17 +191,34 @@ pub struct DeploymentState {
     // Deploy confirmation
     pub confirm_pending: bool,
     pub readiness: Option<DeployReadiness>,
+    /// Coin the escrow deposit is paid in
+    pub deposit_denom: DepositDenom,
 }
 
 pub struct BidsState {
     pub bids: Vec<BidInfo>,
+    /// Bid count reported by the chain
+    pub total: u64,
     pub selected_index: usize,
     pub dseq: Option<u64>,
     pub loading: bool,
+    /// Providers that timed out this session; their bids are listed last
+    pub unresponsive_providers: HashSet<String>,
+}
+
+impl BidsState {
+    /// Move bids from unresponsive providers to the end, keeping the
+    /// chain's order otherwise.
+    fn deprioritize_unresponsive(&mut self) {
+        let unresponsive = &self.unresponsive_providers;
+        self.bids.sort_by_key(|bid| unresponsive.contains(&bid.provider));
+    }
 }
 
 pub struct LeasesState {
     pub leases: Vec<LeaseInfo>,
+    /// Lease count reported by the chain
+    pub total: u64,
     pub selected_index: usize,
     pub service_uris: Vec<String>,
     pub log_viewer: LogViewer,
@@ -172,6 +238,8 @@ pub struct DeploymentsState {
     pub deployments: Vec<DeploymentRecord>,
     pub selected_index: usize,
     pub loading: bool,
+    /// Escrow runway per DSeq from the latest scan
+    pub escrow: EscrowMonitor,
 }
 
 /// A stored deployment record
@@ -222,12 +290,19 @@ impl App {
         discord_form.add_field("Bot Token", "Discord bot token");
         discord_form.add_field("HF Token", "HuggingFace API token (for inference)");
         discord_form.add_field("Bot URL", "Service URI from active lease");
+        discord_form.add_field("Admin Key", "Admin Ed25519 private key file");
+        if let Some(admin_key) = discord_form.fields.last_mut() {
+            admin_key.value = DEFAULT_ADMIN_KEY_FILE.to_string();
+        }
 
         let config = ConfigStore::new()
             .ok()
             .and_then(|store| store.load_config().ok())
             .unwrap_or_default();
 
+        let auto_refresh = AutoRefresh::new(config.auto_refresh_secs);
+        let escrow_refresh = AutoRefresh::unattended(config.escrow.scan_secs);
+
         // Always start with Wallet tab (required before deployment)
         let initial_tab = MainTab::Wallet;
 
@@ -253,6 +328,7 @@ impl App {
             fee_grant_state: FeeGrantState {
                 balance: None,
                 balance_uakt: 0,
+                denom_balances: HashMap::new(),
                 fee_grant_status: "Not checked".to_string(),
                 allowance: None,
                 allowances: Vec::new(),
@@ -277,15 +353,19 @@ impl App {
                 gpu_filter_min_memory: 0,
                 confirm_pending: false,
                 readiness: None,
+                deposit_denom: DepositDenom::default(),
             },
             bids_state: BidsState {
                 bids: Vec::new(),
+                total: 0,
                 selected_index: 0,
                 dseq: None,
                 loading: false,
+                unresponsive_providers: HashSet::new(),
             },
             leases_state: LeasesState {
                 leases: Vec::new(),
+                total: 0,
                 selected_index: 0,
                 service_uris: Vec::new(),
                 log_viewer: LogViewer::new(500),
@@ -302,15 +382,29 @@ impl App {
                 deployments: Vec::new(),
                 selected_index: 0,
                 loading: false,
+                escrow: EscrowMonitor::default(),
             },
 
             popup: None,
             spinner: Spinner::new("Loading...".to_string()),
             status_message: None,
+            pending_tx: None,
             config,
+            auto_refresh,
+            escrow_refresh,
+            read_only: false,
         }
     }
 
+    /// Switch to read-only mode: the configured `wallet.address` is watched
+    /// without a mnemonic, and keys that sign or broadcast are refused.
+    pub fn into_read_only(mut self) -> Self {
+        self.read_only = true;
+        self.wallet_state.wallet.address = self.config.wallet.address.clone();
+        self.wallet_state.wallet.public_key = self.config.wallet.public_key.clone();
+        self
+    }
+
     pub fn set_sender(&mut self, tx: mpsc::UnboundedSender<AppEvent>) {
         self.tx = Some(tx);
     }
@@ -323,6 +417,7 @@ impl App {
                 false
             }
             AppEvent::Key(key) => {
+                self.auto_refresh.record_activity(std::time::Instant::now());
                 // Ctrl-C always quits
                 if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
                     self.should_quit = true;
@@ -338,9 +433,30 @@ impl App {
             }
             AppEvent::Tick => {
                 self.spinner.tick();
+                let now = std::time::Instant::now();
+                if self.auto_refresh.poll(now) {
+                    self.background_refresh();
+                }
+                if self.escrow_refresh.poll(now) && !self.deployments_state.loading {
+                    self.scan_escrow(true);
+                }
+                true
+            }
+            AppEvent::FocusChanged { focused } => {
+                self.auto_refresh.set_focused(focused);
                 true
             }
             // Async results
+            AppEvent::WalletGenerated { address, public_key, .. } if self.read_only => {
+                // Loaded from the encrypted store; keep the address, not the keys
+                self.wallet_state.wallet.address = Some(address);
+                self.wallet_state.wallet.public_key = Some(public_key);
+                self.wallet_state.loading = false;
+                self.spinner.stop();
+                self.status_message = Some(("Wallet address loaded (read-only)".to_string(), false));
+                self.refresh_balance();
+                true
+            }
             AppEvent::WalletGenerated { mnemonic, address, public_key } => {
                 self.wallet_state.wallet.mnemonic = Some(mnemonic.clone());
                 self.wallet_state.wallet.address = Some(address.clone());
@@ -379,13 +495,18 @@ impl App {
                 true
             }
             AppEvent::BalanceUpdated { amount, denom } => {
-                let balance_str = format!("{} {}", amount, denom);
                 let balance_uakt = amount.parse::<u64>().unwrap_or(0);
+                self.fee_grant_state.denom_balances.insert(denom.clone(), balance_uakt);
+                if denom != self.config.network.denom {
+                    // A deposit denom balance; the wallet shows the network denom's
+                    return true;
+                }
+                let balance_str = self.config.network.format_coin(&amount, &denom);
                 self.wallet_state.balance = Some(balance_str.clone());
                 self.fee_grant_state.balance = Some(balance_str);
                 self.fee_grant_state.balance_uakt = balance_uakt;
                 // Update fee grant status based on balance
-                if balance_uakt >= MIN_DEPLOY_BALANCE_UAKT {
+                if balance_uakt >= self.required_deposit_uakt() {
                     self.fee_grant_state.fee_grant_status = "Not needed (sufficient balance)".to_string();
                 } else if self.fee_grant_state.has_fee_grant {
                     self.fee_grant_state.fee_grant_status = "Active".to_string();
@@ -395,20 +516,29 @@ impl App {
                 self.wallet_state.loading = false;
                 self.fee_grant_state.loading = false;
                 self.spinner.stop();
+                self.auto_refresh.record_success();
                 true
             }
-            AppEvent::BidsReceived { bids } => {
+            AppEvent::BidsReceived { bids, total } => {
                 self.bids_state.bids = bids;
+                self.bids_state.deprioritize_unresponsive();
+                self.bids_state.total = total;
                 self.bids_state.selected_index = 0;
                 self.bids_state.loading = false;
                 self.spinner.stop();
                 true
             }
-            AppEvent::LeasesReceived { leases } => {
+            AppEvent::LeasesReceived { leases, total } => {
+                // Keep the selection stable across background refreshes
+                self.leases_state.selected_index = self
+                    .leases_state
+                    .selected_index
+                    .min(leases.len().saturating_sub(1));
                 self.leases_state.leases = leases;
-                self.leases_state.selected_index = 0;
+                self.leases_state.total = total;
                 self.leases_state.loading = false;
                 self.spinner.stop();
+                self.auto_refresh.record_success();
                 true
             }
             AppEvent::TxBroadcast { txhash, success, message } => {
@@ -425,6 +555,25 @@ impl App {
                 self.status_message = Some((message, is_error));
                 true
             }
+            AppEvent::BackgroundRefreshFailed { message } => {
+                // Leave whatever the user is reading alone after the first report
+                if self.auto_refresh.record_failure() {
+                    self.status_message =
+                        Some((format!("{} (showing stale data, retrying)", message), true));
+                }
+                true
+            }
+            AppEvent::ProviderUnresponsive { provider, message } => {
+                self.bids_state.unresponsive_providers.insert(provider);
+                self.bids_state.deprioritize_unresponsive();
+                self.leases_state.loading = false;
+                self.spinner.stop();
+                self.status_message = Some((
+                    format!("{}; its bids will be listed last", message),
+                    true,
+                ));
+                true
+            }
             AppEvent::LogsReceived { lines } => {
                 self.leases_state.log_viewer.clear();
                 for line in lines {
@@ -439,13 +588,13 @@ impl App {
                 if let Some(first) = allowances.first() {
                     let limit_str = first.spend_limit
                         .as_ref()
-                        .map(|b| format!("{} {}", b.amount, b.denom))
+                        .map(|b| self.config.network.format_coin(&b.amount, &b.denom))
                         .unwrap_or_else(|| "unlimited".to_string());
                     self.fee_grant_state.allowance = Some(limit_str);
                     self.fee_grant_state.fee_grant_status = "Active".to_string();
                 } else {
                     self.fee_grant_state.allowance = None;
-                    if self.fee_grant_state.balance_uakt < MIN_DEPLOY_BALANCE_UAKT {
+                    if self.fee_grant_state.balance_uakt < self.required_deposit_uakt() {
                         self.fee_grant_state.fee_grant_status = "Needed (low balance)".to_string();
                     }
                 }
@@ -460,6 +609,10 @@ impl App {
                 true
             }
             AppEvent::DeploymentCreated { dseq, txhash } => {
+                // Baseline for later in-place updates
+                if let (Some(sdl), Ok(store)) = (&self.deployment_state.sdl, ConfigStore::new()) {
+                    let _ = store.save_deployed_sdl(dseq, &sdl.render_yaml());
+                }
                 self.deployment_state.dseq = Some(dseq);
                 self.deployment_state.status = format!("Deployed (DSeq: {})", dseq);
                 self.deployment_state.loading = false;
@@ -472,12 +625,82 @@ impl App {
                 self.sync_screen_from_tab();
                 true
             }
+            AppEvent::DeploymentUpdated { dseq, txhash, manifest_errors } => {
+                self.deployment_state.loading = false;
+                self.spinner.stop();
+                if manifest_errors.is_empty() {
+                    self.deployment_state.status = format!("Updated (DSeq: {})", dseq);
+                    self.status_message = Some((format!("Deployment {} updated! TX: {}", dseq, txhash), false));
+                } else {
+                    let mut popup = Popup::new(
+                        PopupType::Error,
+                        "Manifest Rejected".to_string(),
+                        format!("Update {} is on chain, but providers refused the new manifest:", txhash),
+                    )
+                    .with_details(manifest_errors);
+                    popup.show();
+                    self.popup = Some(popup);
+                }
+                true
+            }
+            AppEvent::UnsignedTxExported { path, dseq, qr } => {
+                self.deployment_state.loading = false;
+                self.deployment_state.status = format!("Awaiting offline signature (DSeq: {})", dseq);
+                self.spinner.stop();
+
+                let mut details = vec![
+                    format!("Sign {} on the offline machine (Wallet tab, 'o'),", UNSIGNED_TX_FILE),
+                    format!("then copy {} back next to it and press 'o' here.", SIGNED_TX_FILE),
+                    String::new(),
+                ];
+                match qr {
+                    Some(rows) => details.extend(rows),
+                    None => details.push("SignDoc too large for a QR code; transfer the file instead.".to_string()),
+                }
+                let mut popup = Popup::new(
+                    PopupType::OfflineTx,
+                    "Unsigned Transaction Exported".to_string(),
+                    path,
+                )
+                .with_details(details);
+                popup.show();
+                self.popup = Some(popup);
+                true
+            }
+            AppEvent::ProvisionFinished { dry_run, result } => {
+                self.discord_state.loading = false;
+                self.spinner.stop();
+                let (status, message, is_error) = match (dry_run, result) {
+                    (true, Ok(summary)) => ("Dry run ok".to_string(), summary, false),
+                    (false, Ok(summary)) => ("Provisioned".to_string(), summary, false),
+                    (true, Err(e)) => ("Dry run failed".to_string(), e, true),
+                    (false, Err(e)) => ("Provisioning failed".to_string(), e, true),
+                };
+                self.discord_state.deploy_status = status;
+                self.status_message = Some((message, is_error));
+                true
+            }
+            AppEvent::EscrowScanned { result } => {
+                self.deployments_state.loading = false;
+                self.spinner.stop();
+                match result {
+                    Ok(statuses) => self.apply_escrow_scan(statuses),
+                    Err(e) => self.status_message = Some((format!("Escrow scan failed: {}", e), true)),
+                }
+                true
+            }
+            AppEvent::EscrowDeposited { dseq, txhash, automatic } => {
+                self.spinner.stop();
+                let kind = if automatic { "Automatic top-up" } else { "Deposit" };
+                self.status_message = Some((format!("{} for DSeq {} sent: TX {}", kind, dseq, txhash), false));
+                true
+            }
         }
     }
 
     fn handle_popup_key(&mut self, key: KeyEvent) {
         let popup_type = self.popup.as_ref().map(|p| match p.popup_type {
-            PopupType::DeployConfirm => "deploy_confirm",
+            PopupType::TxConfirm => "tx_confirm",
             PopupType::FeeGrantNeeded => "fee_grant_needed",
             PopupType::Mnemonic => "mnemonic",
             _ => "generic",
@@ -513,21 +736,21 @@ impl App {
                     }
                 }
             }
-            Some("deploy_confirm") => {
+            Some("tx_confirm") => {
+                self.popup = None;
                 match key.code {
                     KeyCode::Enter => {
-                        self.popup = None;
-                        self.confirm_deployment();
+                        if let Some(pending) = self.pending_tx.take() {
+                            self.execute_tx(pending);
+                        }
                     }
                     KeyCode::Esc | KeyCode::Char('q') => {
-                        self.popup = None;
-                        self.deployment_state.confirm_pending = false;
-                        self.status_message = Some(("Deployment cancelled".to_string(), false));
+                        self.cancel_tx();
+                        self.status_message = Some(("Transaction cancelled".to_string(), false));
                     }
                     _ => {
-                        // Dismiss on any other key
-                        self.popup = None;
-                        self.deployment_state.confirm_pending = false;
+                        // Anything but Enter aborts; never broadcast by accident
+                        self.cancel_tx();
                     }
                 }
             }
@@ -674,6 +897,12 @@ impl App {
     }
 
     fn handle_screen_key(&mut self, key: KeyEvent) {
+        if self.read_only {
+            if let Some(action) = self.current_screen.spending_action(key.code) {
+                self.status_message = Some((format!("{} is disabled in read-only mode", action), true));
+                return;
+            }
+        }
         match self.current_screen {
             Screen::Splash => {
                 // Any key advances past splash to the detected tab
@@ -694,6 +923,7 @@ impl App {
                 }
                 KeyCode::Char('r') => self.refresh_deployments(),
                 KeyCode::Char('l') => self.fetch_deployment_logs(),
+                KeyCode::Char('d') => self.deposit_selected(),
                 _ => {}
             },
             Screen::Wallet => match key.code {
@@ -703,6 +933,8 @@ impl App {
                 KeyCode::Char('s') => self.save_wallet_encrypted(),
                 KeyCode::Char('l') => self.load_wallet_encrypted(),
                 KeyCode::Char('r') => self.refresh_balance(),
+                KeyCode::Char('o') => self.review_offline_tx(),
+                KeyCode::Char('p') => self.publish_provider_certificate(),
                 _ => {}
             },
             Screen::FeeGrant => match key.code {
@@ -843,6 +1075,9 @@ impl App {
                     }
                 }
                 KeyCode::Char('d') => self.submit_deployment(),
+                KeyCode::Char('m') if !self.deployment_state.gpu_picker_open => self.cycle_deposit_denom(),
+                KeyCode::Char('e') if !self.deployment_state.gpu_picker_open => self.export_unsigned_tx(),
+                KeyCode::Char('o') if !self.deployment_state.gpu_picker_open => self.import_signed_tx(),
                 _ => {}
             },
             Screen::Bids => match key.code {
@@ -873,6 +1108,7 @@ impl App {
                 }
                 KeyCode::Char('l') => self.fetch_logs(),
                 KeyCode::Char('r') => self.refresh_leases(),
+                KeyCode::Char('u') => self.update_deployment(),
                 _ => {}
             },
             Screen::DiscordConfig => match key.code {
@@ -904,10 +1140,8 @@ impl App {
                     // Auto-populate Bot URL from active lease
                     self.auto_populate_bot_url();
                 }
-                KeyCode::Char('t') => {
-                    // Test provision endpoint
-                    self.test_provision_endpoint();
-                }
+                KeyCode::Char('t') => self.run_provisioning(true),
+                KeyCode::Char('P') => self.run_provisioning(false),
                 KeyCode::Char('j') | KeyCode::Down => {
                     self.discord_state.form.next_field();
                 }
@@ -1112,21 +1346,76 @@ impl App {
         }
     }
 
+    /// Chain client for the configured network, including fallback endpoints.
+    fn akash_client(&self) -> AkashClient {
+        AkashClient::new(
+            self.config.network.rpc_url.clone(),
+            self.config.network.grpc_url.clone(),
+        )
+        .with_fallbacks(
+            &self.config.network.fallback_rpc_urls,
+            &self.config.network.fallback_grpc_urls,
+        )
+        .with_denom(&self.config.network.denom)
+    }
+
+    /// Provider API client, authenticating with the wallet's published
+    /// provider certificate when there is one.
+    fn provider_client(&mut self) -> ProviderClient {
+        let timeout = Duration::from_secs(self.config.provider_timeout_secs);
+        let client = match self.provider_certificate() {
+            Some(cert) => ProviderClient::with_certificate(&cert).unwrap_or_else(|e| {
+                self.status_message = Some((format!("Provider certificate unusable: {}", e), true));
+                ProviderClient::new()
+            }),
+            None => ProviderClient::new(),
+        };
+        client.with_timeout(timeout)
+    }
+
+    /// Certificate this machine published for the loaded wallet, if any.
+    fn provider_certificate(&self) -> Option<ProviderCertificate> {
+        let wallet = &self.wallet_state.wallet;
+        let (mnemonic, address) = (wallet.mnemonic.as_ref()?, wallet.address.as_ref()?);
+        let cert_pem = ConfigStore::new().ok()?.load_provider_cert(address)?;
+        let keypair = KeyGenerator::new().derive_keypair(mnemonic).ok()?;
+        ProviderCertificate::from_pem(&keypair, cert_pem).ok()
+    }
+
+    /// Refresh on-screen chain state without a spinner; driven by `AutoRefresh`.
+    fn background_refresh(&mut self) {
+        if self.popup.is_some() || self.input_mode != InputMode::Normal {
+            return;
+        }
+        match self.current_screen {
+            Screen::Wallet | Screen::FeeGrant if !self.wallet_state.loading => {
+                self.query_balance(true);
+            }
+            Screen::Leases if !self.leases_state.loading => {
+                self.query_leases(true);
+            }
+            _ => {}
+        }
+    }
+
     fn refresh_balance(&mut self) {
+        self.query_balance(false);
+    }
+
+    fn query_balance(&mut self, background: bool) {
         let address = self.wallet_state.wallet.address.clone();
         if let (Some(tx), Some(addr)) = (&self.tx, address) {
-            self.wallet_state.loading = true;
-            self.spinner.start();
-            self.spinner.message = "Fetching balance...".to_string();
+            if !background {
+                self.wallet_state.loading = true;
+                self.spinner.start();
+                self.spinner.message = "Fetching balance...".to_string();
+            }
             let tx = tx.clone();
-            let rpc_url = self.config.network.rpc_url.clone();
-            let grpc_url = self.config.network.grpc_url.clone();
+            let client = self.akash_client();
             tokio::spawn(async move {
-                let client = AkashClient::new(rpc_url, grpc_url);
-
-                // Add timeout to prevent stuck loading
+                // Add timeout to prevent stuck loading (covers retries across endpoints)
                 let balance_query = tokio::time::timeout(
-                    std::time::Duration::from_secs(10),
+                    std::time::Duration::from_secs(30),
                     client.query_balance(&addr)
                 );
 
@@ -1138,22 +1427,59 @@ impl App {
                         });
                     }
                     Ok(Err(e)) => {
-                        let _ = tx.send(AppEvent::StatusMessage {
-                            message: format!("Balance query failed: {}", e),
-                            is_error: true,
-                        });
+                        let _ = tx.send(query_failed(
+                            background,
+                            format!("Balance query failed: {}", e),
+                        ));
                     }
                     Err(_) => {
-                        let _ = tx.send(AppEvent::StatusMessage {
-                            message: "Balance query timed out after 10 seconds".to_string(),
-                            is_error: true,
-                        });
+                        let _ = tx.send(query_failed(
+                            background,
+                            "Balance query timed out after 30 seconds".to_string(),
+                        ));
                     }
                 }
             });
-        } else {
+        } else if !background {
             self.status_message = Some(("No wallet loaded".to_string(), true));
         }
+        self.query_deposit_balance(background);
+    }
+
+    /// Switch the coin the deployment deposit is paid in.
+    fn cycle_deposit_denom(&mut self) {
+        let denom = self.deployment_state.deposit_denom.next();
+        self.deployment_state.deposit_denom = denom;
+        self.deployment_state.readiness = None;
+        self.status_message = Some((format!("Deployment deposit will be paid in {}", denom.label()), false));
+        self.query_deposit_balance(false);
+    }
+
+    /// Fetch the wallet's balance in the deposit denom when that isn't the
+    /// network denom, which the regular balance query already covers.
+    fn query_deposit_balance(&mut self, background: bool) {
+        let deposit_denom = self.deployment_state.deposit_denom;
+        let denom = deposit_denom.base_denom(&self.config.network).to_string();
+        if denom == self.config.network.denom {
+            return;
+        }
+        if let (Some(tx), Some(addr)) = (&self.tx, self.wallet_state.wallet.address.clone()) {
+            let tx = tx.clone();
+            let client = self.akash_client().with_denom(&denom);
+            tokio::spawn(async move {
+                let event = match client.query_balance(&addr).await {
+                    Ok(balance) => AppEvent::BalanceUpdated {
+                        amount: balance.amount,
+                        denom: balance.denom,
+                    },
+                    Err(e) => query_failed(
+                        background,
+                        format!("{} balance query failed: {}", deposit_denom.label(), e),
+                    ),
+                };
+                let _ = tx.send(event);
+            });
+        }
     }
 
     fn request_fee_grant(&mut self) {
@@ -1173,13 +1499,14 @@ impl App {
             self.spinner.start();
             self.spinner.message = "Fetching bids...".to_string();
             let tx = tx.clone();
-            let rpc_url = self.config.network.rpc_url.clone();
-            let grpc_url = self.config.network.grpc_url.clone();
+            let client = self.akash_client();
             tokio::spawn(async move {
-                let client = AkashClient::new(rpc_url, grpc_url);
                 match client.query_bids(&addr, dseq).await {
-                    Ok(bids) => {
-                        let _ = tx.send(AppEvent::BidsReceived { bids });
+                    Ok(page) => {
+                        let _ = tx.send(AppEvent::BidsReceived {
+                            bids: page.items,
+                            total: page.total,
+                        });
                     }
                     Err(e) => {
                         let _ = tx.send(AppEvent::StatusMessage {
@@ -1195,42 +1522,68 @@ impl App {
     }
 
     fn accept_bid(&mut self) {
-        if let Some(bid) = self.bids_state.bids.get(self.bids_state.selected_index) {
-            self.status_message = Some((
-                format!("Accepting bid from {}...", &bid.provider[..20.min(bid.provider.len())]),
-                false,
-            ));
-            // In a real flow, this would create and broadcast MsgCreateLease
-            self.bids_state.loading = true;
-            self.spinner.start();
-            self.spinner.message = "Creating lease...".to_string();
+        if let Some(bid) = self.bids_state.bids.get(self.bids_state.selected_index).cloned() {
+            let pending = PendingTx::new(TxAction::CreateLease { bid: bid.clone() }, "Accept Bid")
+                .param("Provider", bid.provider.clone())
+                .param("DSeq", bid.dseq.to_string())
+                .param("Price", format!("{} {} / block", bid.price_amount, bid.price_denom))
+                .with_fee_grant(self.fee_grant_state.has_fee_grant);
+            self.request_tx(pending);
         }
     }
 
+    fn create_lease(&mut self, bid: BidInfo) {
+        self.status_message = Some((
+            format!("Accepting bid from {}...", &bid.provider[..20.min(bid.provider.len())]),
+            false,
+        ));
+        // In a real flow, this would create and broadcast MsgCreateLease
+        self.bids_state.loading = true;
+        self.spinner.start();
+        self.spinner.message = "Creating lease...".to_string();
+    }
+
     fn refresh_leases(&mut self) {
+        self.query_leases(false);
+    }
+
+    fn query_leases(&mut self, background: bool) {
         let address = self.wallet_state.wallet.address.clone();
         if let (Some(tx), Some(addr)) = (&self.tx, address) {
-            self.leases_state.loading = true;
-            self.spinner.start();
-            self.spinner.message = "Fetching leases...".to_string();
+            if !background {
+                self.leases_state.loading = true;
+                self.spinner.start();
+                self.spinner.message = "Fetching leases...".to_string();
+            }
             let tx = tx.clone();
-            let rpc_url = self.config.network.rpc_url.clone();
-            let grpc_url = self.config.network.grpc_url.clone();
+            let client = self.akash_client();
             tokio::spawn(async move {
-                let client = AkashClient::new(rpc_url, grpc_url);
                 match client.query_leases(&addr).await {
-                    Ok(leases) => {
-                        let _ = tx.send(AppEvent::LeasesReceived { leases });
+                    Ok(page) => {
+                        if page.is_truncated() && !background {
+                            let _ = tx.send(AppEvent::StatusMessage {
+                                message: format!(
+                                    "Showing {} of {} leases",
+                                    page.items.len(),
+                                    page.total
+                                ),
+                                is_error: true,
+                            });
+                        }
+                        let _ = tx.send(AppEvent::LeasesReceived {
+                            leases: page.items,
+                            total: page.total,
+                        });
                     }
                     Err(e) => {
-                        let _ = tx.send(AppEvent::StatusMessage {
-                            message: format!("Lease query failed: {}", e),
-                            is_error: true,
-                        });
+                        let _ = tx.send(query_failed(
+                            background,
+                            format!("Lease query failed: {}", e),
+                        ));
                     }
                 }
             });
-        } else {
+        } else if !background {
             self.status_message = Some(("No wallet loaded".to_string(), true));
         }
     }
@@ -1242,22 +1595,40 @@ impl App {
                 self.spinner.start();
                 self.spinner.message = "Fetching logs...".to_string();
                 let tx = tx.clone();
-                let provider_url = lease.provider.clone();
+                let provider = lease.provider.clone();
                 let dseq = lease.dseq;
                 let gseq = lease.gseq;
                 let oseq = lease.oseq;
+                let chain = self.akash_client();
+                let client = self.provider_client();
                 tokio::spawn(async move {
-                    let client = ProviderClient::new();
+                    let (provider_url, client) = match connect_provider(&chain, &client, &provider).await {
+                        Ok(connected) => connected,
+                        Err(e) => {
+                            let _ = tx.send(AppEvent::StatusMessage {
+                                message: format!("Provider lookup failed: {}", e),
+                                is_error: true,
+                            });
+                            return;
+                        }
+                    };
                     match client.get_logs(&provider_url, dseq, gseq, oseq, "web", 100).await {
                         Ok(entries) => {
                             let lines: Vec<String> = entries.into_iter().map(|e| e.message).collect();
                             let _ = tx.send(AppEvent::LogsReceived { lines });
                         }
                         Err(e) => {
-                            let _ = tx.send(AppEvent::StatusMessage {
-                                message: format!("Log fetch failed: {}", e),
-                                is_error: true,
-                            });
+                            let event = match e.downcast_ref::<ProviderUnresponsive>() {
+                                Some(unresponsive) => AppEvent::ProviderUnresponsive {
+                                    provider: provider.clone(),
+                                    message: format!("Log fetch failed: {}", unresponsive),
+                                },
+                                None => AppEvent::StatusMessage {
+                                    message: format!("Log fetch failed: {}", e),
+                                    is_error: true,
+                                },
+                            };
+                            let _ = tx.send(event);
                         }
                     }
                 });
@@ -1276,8 +1647,16 @@ impl App {
     fn check_deploy_readiness(&mut self) {
         let wallet_ready = self.wallet_state.wallet.address.is_some();
         let balance_uakt = self.fee_grant_state.balance_uakt;
-        let balance_sufficient = balance_uakt >= MIN_DEPLOY_BALANCE_UAKT;
+        let required_uakt = self.required_deposit_uakt();
         let has_fee_grant = self.fee_grant_state.has_fee_grant;
+        // A deposit in another denom comes from that balance, so the
+        // network balance only has to cover the fee
+        let deposit_shortfall = self.deposit_shortfall();
+        let balance_sufficient = if deposit_shortfall.is_some() || self.deposit_in_network_denom() {
+            balance_uakt >= required_uakt
+        } else {
+            balance_uakt >= tx::estimate_fee_uakt(DEPLOYMENT_GAS_LIMIT)
+        };
 
         let sdl_ready = self.deployment_state.sdl.is_some()
             && self.deployment_state.sdl_error.is_none();
@@ -1303,10 +1682,13 @@ impl App {
                 issues.push(format!("Unfilled variables: {}", unfilled.join(", ")));
             }
         }
+        if let Some(shortfall) = &deposit_shortfall {
+            issues.push(shortfall.clone());
+        }
         if !balance_sufficient && !has_fee_grant {
             issues.push(format!(
-                "Balance too low ({} uakt) and no fee grant — request fee grant first",
-                balance_uakt
+                "Balance too low ({}) and no fee grant — request fee grant first",
+                self.config.network.format_amount(balance_uakt)
             ));
         }
 
@@ -1325,6 +1707,10 @@ impl App {
             // Show issues as error popup or redirect to fee grant
             if !wallet_ready {
                 self.status_message = Some((issues[0].clone(), true));
+            } else if let Some(shortfall) = deposit_shortfall {
+                // A fee grant can't pay a deposit, so don't send them there
+                self.status_message = Some((shortfall, true));
+                self.query_deposit_balance(false);
             } else if !balance_sufficient && !has_fee_grant {
                 // Show fee grant needed popup
                 let mut popup = Popup::new(
@@ -1333,8 +1719,8 @@ impl App {
                     "Your wallet balance is too low to cover deployment fees.".to_string(),
                 );
                 popup.details = vec![
-                    format!("Current balance: {} uakt ({:.3} AKT)", balance_uakt, balance_uakt as f64 / 1_000_000.0),
-                    format!("Minimum required: {} uakt ({:.1} AKT)", MIN_DEPLOY_BALANCE_UAKT, MIN_DEPLOY_BALANCE_UAKT as f64 / 1_000_000.0),
+                    format!("Current balance: {}", self.config.network.format_amount(balance_uakt)),
+                    format!("Estimated deposit: {}", self.config.network.format_amount(required_uakt)),
                     String::new(),
                     "Press Tab to go to Fee Grant step, or any key to dismiss.".to_string(),
                 ];
@@ -1350,9 +1736,41 @@ impl App {
         }
     }
 
+    /// Escrow deposit the loaded SDL needs, or the chain minimum without one.
+    pub fn required_deposit_uakt(&self) -> u64 {
+        self.deployment_state
+            .sdl
+            .as_ref()
+            .and_then(|sdl| tx::estimate_deposit_uakt(sdl).ok())
+            .unwrap_or(tx::MIN_DEPLOYMENT_DEPOSIT_UAKT)
+    }
+
+    fn deposit_in_network_denom(&self) -> bool {
+        self.deployment_state.deposit_denom.base_denom(&self.config.network) == self.config.network.denom
+    }
+
+    /// Why the wallet can't pay the deposit in a denom other than the
+    /// network's; network denom deposits are checked with the fee.
+    fn deposit_shortfall(&self) -> Option<String> {
+        if self.deposit_in_network_denom() {
+            return None;
+        }
+        let denom = self.deployment_state.deposit_denom;
+        let balance = self
+            .fee_grant_state
+            .denom_balances
+            .get(denom.base_denom(&self.config.network))
+            .copied();
+        tx::deposit_shortfall(denom, self.required_deposit_uakt(), balance, &self.config.network)
+    }
+
     fn show_deploy_confirm(&mut self) {
         let balance_uakt = self.fee_grant_state.balance_uakt;
-        let fee_source = if self.fee_grant_state.has_fee_grant {
+        let deposit_uakt = self.required_deposit_uakt();
+        let deposit_denom = self.deployment_state.deposit_denom;
+        let network = &self.config.network;
+        let has_fee_grant = self.fee_grant_state.has_fee_grant;
+        let fee_source = if has_fee_grant {
             "Fee Grant (granter pays gas)"
         } else {
             "Wallet Balance"
@@ -1370,23 +1788,66 @@ impl App {
             .map(|s| s.services.len())
             .unwrap_or(0);
 
+        let pending = PendingTx::new(TxAction::CreateDeployment, "Confirm Deployment")
+            .param("Services", service_count.to_string())
+            .param("GPU Models", gpu_text)
+            .param("Fee Source", fee_source)
+            .param("Deposit", format!("{} (escrow, refundable)", deposit_denom.format_amount(deposit_uakt, network)))
+            .param("Balance", network.format_amount(balance_uakt));
+        let pending = match self.fee_grant_state.denom_balances.get(deposit_denom.base_denom(network)) {
+            Some(&deposit_balance) if !self.deposit_in_network_denom() => pending.param(
+                format!("{} Balance", deposit_denom.label()),
+                deposit_denom.format_amount(deposit_balance, network),
+            ),
+            _ => pending,
+        }
+        .with_fee_grant(has_fee_grant);
+        self.request_tx(pending);
+        self.deployment_state.confirm_pending = true;
+    }
+
+    /// Show the confirmation popup for a transaction. Every action that
+    /// broadcasts must go through here; `execute_tx` runs only on Enter.
+    fn request_tx(&mut self, pending: PendingTx) {
+        if self.read_only {
+            self.status_message = Some((format!("{} is disabled in read-only mode", pending.title), true));
+            return;
+        }
         let mut popup = Popup::new(
-            PopupType::DeployConfirm,
-            "Confirm Deployment".to_string(),
-            "Ready to submit deployment to Akash Network.".to_string(),
-        );
-        popup.details = vec![
-            format!("Services: {}", service_count),
-            format!("GPU Models: {}", gpu_text),
-            format!("Fee Source: {}", fee_source),
-            format!("Balance: {:.3} AKT", balance_uakt as f64 / 1_000_000.0),
-            String::new(),
-            "Press Enter to confirm, Esc to cancel.".to_string(),
-        ];
-        popup.buttons = vec!["Confirm".to_string(), "Cancel".to_string()];
+            PopupType::TxConfirm,
+            pending.title.clone(),
+            "Review this transaction before it is signed and broadcast.".to_string(),
+        )
+        .with_details(pending.detail_lines())
+        .with_buttons(vec!["Confirm".to_string(), "Cancel".to_string()]);
         popup.show();
         self.popup = Some(popup);
-        self.deployment_state.confirm_pending = true;
+        self.pending_tx = Some(pending);
+    }
+
+    /// Run a transaction the user confirmed in the TxConfirm popup.
+    fn execute_tx(&mut self, pending: PendingTx) {
+        match pending.action {
+            TxAction::CreateDeployment => self.confirm_deployment(),
+            TxAction::CreateLease { bid } => self.create_lease(bid),
+            TxAction::UpdateDeployment { dseq } => self.broadcast_update(dseq),
+            TxAction::SignOffline { unsigned } => self.sign_offline_tx(unsigned),
+            TxAction::BroadcastSigned { signed, messages, dseq } => {
+                self.broadcast_signed_tx(signed, &messages, dseq)
+            }
+            TxAction::PublishCertificate { cert_pem } => self.broadcast_certificate(cert_pem),
+            TxAction::DepositDeployment { dseq, amount, denom } => {
+                self.broadcast_deposit(dseq, amount, denom, false)
+            }
+        }
+    }
+
+    fn cancel_tx(&mut self) {
+        if let Some(pending) = self.pending_tx.take() {
+            if matches!(pending.action, TxAction::CreateDeployment) {
+                self.deployment_state.confirm_pending = false;
+            }
+        }
     }
 
     /// Actually execute the deployment after confirmation
@@ -1399,12 +1860,10 @@ impl App {
 
         if let Some(tx) = &self.tx {
             let tx = tx.clone();
-            let rpc_url = self.config.network.rpc_url.clone();
-            let grpc_url = self.config.network.grpc_url.clone();
+            let client = self.akash_client();
             let _address = self.wallet_state.wallet.address.clone().unwrap_or_default();
             tokio::spawn(async move {
                 // Get block height for dseq
-                let client = AkashClient::new(rpc_url, grpc_url);
                 match client.get_block_height().await {
                     Ok(height) => {
                         // In full implementation, we would build and broadcast MsgCreateDeployment
@@ -1425,6 +1884,611 @@ impl App {
         }
     }
 
+    /// Apply the edited SDL to the selected lease's deployment without
+    /// closing it. Only manifest-level changes (image, env, ports) qualify.
+    fn update_deployment(&mut self) {
+        let dseq = match self.leases_state.leases.get(self.leases_state.selected_index) {
+            Some(lease) => lease.dseq,
+            None => {
+                self.status_message = Some(("No lease selected".to_string(), true));
+                return;
+            }
+        };
+        let sdl = match &self.deployment_state.sdl {
+            Some(sdl) => sdl,
+            None => {
+                self.status_message = Some(("SDL not loaded".to_string(), true));
+                return;
+            }
+        };
+        let deployed = match ConfigStore::new().ok().and_then(|store| store.load_deployed_sdl(dseq)) {
+            Some(yaml) => SdlFile::parse(&yaml),
+            None => {
+                self.status_message = Some((
+                    format!("No SDL recorded for DSeq {}; cannot check the update is compatible", dseq),
+                    true,
+                ));
+                return;
+            }
+        };
+        let deployed = match deployed {
+            Ok(deployed) => deployed,
+            Err(e) => {
+                self.status_message = Some((format!("Recorded SDL for DSeq {} is invalid: {}", dseq, e), true));
+                return;
+            }
+        };
+
+        if let Err(problems) = sdl.check_update_compatible(&deployed) {
+            let mut popup = Popup::new(
+                PopupType::Error,
+                "Incompatible Update".to_string(),
+                "Akash cannot apply these changes to a running deployment. Close it and redeploy instead."
+                    .to_string(),
+            )
+            .with_details(problems.iter().map(|p| p.to_string()).collect());
+            popup.show();
+            self.popup = Some(popup);
+            return;
+        }
+
+        let version = match (sdl.version(), deployed.version()) {
+            (Ok(new), Ok(old)) if new == old => {
+                self.status_message = Some(("SDL unchanged — nothing to update".to_string(), false));
+                return;
+            }
+            (Ok(new), Ok(_)) => new,
+            (Err(e), _) | (_, Err(e)) => {
+                self.status_message = Some((format!("Cannot compute version: {}", e), true));
+                return;
+            }
+        };
+
+        let providers = self.lease_providers(dseq);
+        let pending = PendingTx::new(TxAction::UpdateDeployment { dseq }, "Update Deployment")
+            .param("DSeq", dseq.to_string())
+            .param("New Version", version.iter().take(8).map(|b| format!("{:02x}", b)).collect::<String>())
+            .param("Manifest To", format!("{} provider(s)", providers.len()))
+            .with_gas_limit(DEPLOYMENT_GAS_LIMIT)
+            .with_fee_grant(self.fee_grant_state.has_fee_grant);
+        self.request_tx(pending);
+    }
+
+    /// Distinct providers holding a lease on deployment `dseq`.
+    fn lease_providers(&self, dseq: u64) -> Vec<String> {
+        let mut providers: Vec<String> = self
+            .leases_state
+            .leases
+            .iter()
+            .filter(|lease| lease.dseq == dseq)
+            .map(|lease| lease.provider.clone())
+            .collect();
+        providers.dedup();
+        providers
+    }
+
+    /// Sign and broadcast MsgUpdateDeployment, then push the new manifest to
+    /// every provider leasing the deployment.
+    fn broadcast_update(&mut self, dseq: u64) {
+        let (mnemonic, address) = match (
+            self.wallet_state.wallet.mnemonic.clone(),
+            self.wallet_state.wallet.address.clone(),
+        ) {
+            (Some(mnemonic), Some(address)) => (mnemonic, address),
+            _ => {
+                self.status_message = Some(("No wallet loaded".to_string(), true));
+                return;
+            }
+        };
+        let prepared = self.deployment_state.sdl.as_ref().ok_or_else(|| "SDL not loaded".to_string()).and_then(|sdl| {
+            let msg = tx::update_deployment_msg(&address, dseq, sdl)?;
+            let manifest = sdl.manifest_json()?;
+            Ok((msg, manifest, sdl.render_yaml()))
+        });
+        let (msg, manifest, rendered) = match prepared {
+            Ok(prepared) => prepared,
+            Err(e) => {
+                self.status_message = Some((format!("Cannot build update: {}", e), true));
+                return;
+            }
+        };
+        let providers = self.lease_providers(dseq);
+
+        if let Some(tx) = &self.tx {
+            self.deployment_state.loading = true;
+            self.spinner.start();
+            self.spinner.message = "Updating deployment...".to_string();
+            let tx = tx.clone();
+            let client = self.akash_client();
+            let provider_client = self.provider_client();
+            let chain_id = self.config.network.chain_id.clone();
+            tokio::spawn(async move {
+                let account = match client.get_account_info(&address).await {
+                    Ok(account) => account,
+                    Err(e) => {
+                        let _ = tx.send(AppEvent::StatusMessage {
+                            message: format!("Account query failed: {}", e),
+                            is_error: true,
+                        });
+                        return;
+                    }
+                };
+                let signed = KeyGenerator::new()
+                    .derive_keypair(&mnemonic)
+                    .and_then(|keypair| {
+                        let any = TransactionSigner::encode_msg(&msg)?;
+                        TransactionSigner::new(keypair).create_signed_tx(
+                            vec![any],
+                            &chain_id,
+                            account.account_number,
+                            account.sequence,
+                            DEPLOYMENT_GAS_LIMIT,
+                            tx::estimate_fee_uakt(DEPLOYMENT_GAS_LIMIT) as u128,
+                            "",
+                        )
+                    })
+                    .map_err(|e| e.to_string());
+                let tx_bytes = match signed {
+                    Ok(bytes) => bytes,
+                    Err(e) => {
+                        let _ = tx.send(AppEvent::StatusMessage {
+                            message: format!("Signing failed: {}", e),
+                            is_error: true,
+                        });
+                        return;
+                    }
+                };
+
+                let txhash = match client.broadcast_tx(&tx_bytes).await {
+                    Ok(result) if result.code == 0 => result.txhash,
+                    Ok(result) => {
+                        let _ = tx.send(AppEvent::TxBroadcast {
+                            txhash: result.txhash,
+                            success: false,
+                            message: result.raw_log,
+                        });
+                        return;
+                    }
+                    Err(e) => {
+                        let _ = tx.send(AppEvent::StatusMessage {
+                            message: format!("Broadcast failed: {}", e),
+                            is_error: true,
+                        });
+                        return;
+                    }
+                };
+
+                // The chain now expects the new version; providers only redeploy
+                // once they receive a manifest hashing to it.
+                let mut manifest_errors = Vec::new();
+                for provider in &providers {
+                    let connected = connect_provider(&client, &provider_client, provider).await;
+                    let sent = match connected {
+                        Ok((host_uri, provider_client)) => provider_client
+                            .send_manifest(&host_uri, dseq, &manifest)
+                            .await
+                            .map_err(|e| e.to_string()),
+                        Err(e) => Err(e),
+                    };
+                    if let Err(e) = sent {
+                        manifest_errors.push(format!("{}: {}", provider, e));
+                    }
+                }
+                if let Ok(store) = ConfigStore::new() {
+                    let _ = store.save_deployed_sdl(dseq, &rendered);
+                }
+
+                let _ = tx.send(AppEvent::DeploymentUpdated { dseq, txhash, manifest_errors });
+            });
+        }
+    }
+
+    /// Issue a provider mTLS certificate for the loaded wallet and ask to
+    /// publish it; providers only accept certificates they can find on chain.
+    fn publish_provider_certificate(&mut self) {
+        let wallet = &self.wallet_state.wallet;
+        let (mnemonic, address) = match (&wallet.mnemonic, &wallet.address) {
+            (Some(mnemonic), Some(address)) => (mnemonic, address.clone()),
+            _ => {
+                self.status_message = Some(("No wallet loaded".to_string(), true));
+                return;
+            }
+        };
+        let cert = KeyGenerator::new()
+            .derive_keypair(mnemonic)
+            .and_then(|keypair| ProviderCertificate::generate(&keypair, &address));
+        match cert {
+            Ok(cert) => {
+                let pending = PendingTx::new(
+                    TxAction::PublishCertificate { cert_pem: cert.cert_pem },
+                    "Publish Provider Certificate",
+                )
+                .param("Owner", address);
+                self.request_tx(pending);
+            }
+            Err(e) => {
+                self.status_message = Some((format!("Certificate generation failed: {}", e), true));
+            }
+        }
+    }
+
+    fn broadcast_certificate(&mut self, cert_pem: String) {
+        let (mnemonic, address) = match (
+            self.wallet_state.wallet.mnemonic.clone(),
+            self.wallet_state.wallet.address.clone(),
+        ) {
+            (Some(mnemonic), Some(address)) => (mnemonic, address),
+            _ => {
+                self.status_message = Some(("No wallet loaded".to_string(), true));
+                return;
+            }
+        };
+
+        if let Some(tx) = &self.tx {
+            self.spinner.start();
+            self.spinner.message = "Publishing certificate...".to_string();
+            let tx = tx.clone();
+            let client = self.akash_client();
+            let chain_id = self.config.network.chain_id.clone();
+            tokio::spawn(async move {
+                let account = match client.get_account_info(&address).await {
+                    Ok(account) => account,
+                    Err(e) => {
+                        let _ = tx.send(AppEvent::StatusMessage {
+                            message: format!("Account query failed: {}", e),
+                            is_error: true,
+                        });
+                        return;
+                    }
+                };
+                let signed = KeyGenerator::new()
+                    .derive_keypair(&mnemonic)
+                    .and_then(|keypair| {
+                        let msg = ProviderCertificate::from_pem(&keypair, cert_pem.clone())?
+                            .create_msg(&address)?;
+                        let any = TransactionSigner::encode_msg(&msg)?;
+                        TransactionSigner::new(keypair).create_signed_tx(
+                            vec![any],
+                            &chain_id,
+                            account.account_number,
+                            account.sequence,
+                            DEFAULT_GAS_LIMIT,
+                            tx::estimate_fee_uakt(DEFAULT_GAS_LIMIT) as u128,
+                            "",
+                        )
+                    })
+                    .map_err(|e| e.to_string());
+                let tx_bytes = match signed {
+                    Ok(bytes) => bytes,
+                    Err(e) => {
+                        let _ = tx.send(AppEvent::StatusMessage {
+                            message: format!("Signing failed: {}", e),
+                            is_error: true,
+                        });
+                        return;
+                    }
+                };
+
+                let broadcast = client.broadcast_tx(&tx_bytes).await.map_err(|e| e.to_string());
+                match broadcast {
+                    Ok(result) if result.code == 0 => {
+                        // CheckTx passing says nothing about the block; only
+                        // keep the cert once the cert module lists it
+                        let confirmed = client
+                            .confirm_certificate(
+                                &result.txhash,
+                                &address,
+                                &cert_pem,
+                                TX_CONFIRM_TIMEOUT_SECS,
+                            )
+                            .await
+                            .map_err(|e| e.to_string());
+                        if let Err(e) = confirmed {
+                            let _ = tx.send(AppEvent::TxBroadcast {
+                                txhash: result.txhash,
+                                success: false,
+                                message: e,
+                            });
+                            return;
+                        }
+                        let saved = ConfigStore::new()
+                            .and_then(|store| store.save_provider_cert(&address, &cert_pem));
+                        let _ = tx.send(match saved {
+                            Ok(()) => AppEvent::TxBroadcast {
+                                txhash: result.txhash,
+                                success: true,
+                                message: String::new(),
+                            },
+                            Err(e) => AppEvent::StatusMessage {
+                                message: format!(
+                                    "Certificate published ({}) but not saved locally: {}",
+                                    result.txhash, e
+                                ),
+                                is_error: true,
+                            },
+                        });
+                    }
+                    Ok(result) => {
+                        let _ = tx.send(AppEvent::TxBroadcast {
+                            txhash: result.txhash,
+                            success: false,
+                            message: result.raw_log,
+                        });
+                    }
+                    Err(e) => {
+                        let _ = tx.send(AppEvent::StatusMessage {
+                            message: format!("Broadcast failed: {}", e),
+                            is_error: true,
+                        });
+                    }
+                }
+            });
+        }
+    }
+
+    /// Address and raw public key to build transactions for. Without a loaded
+    /// mnemonic this falls back to the watch-only signer in config.
+    fn signer_identity(&self) -> Option<(String, Vec<u8>)> {
+        let wallet = &self.wallet_state.wallet;
+        let (address, public_key) = match (&wallet.address, &wallet.public_key) {
+            (Some(address), Some(public_key)) => (address.clone(), public_key.clone()),
+            _ => (
+                self.config.wallet.address.clone()?,
+                self.config.wallet.public_key.clone()?,
+            ),
+        };
+        let public_key = BASE64.decode(public_key).ok()?;
+        Some((address, public_key))
+    }
+
+    /// Build the deployment tx without signing it and write it out for an
+    /// air-gapped machine to sign.
+    fn export_unsigned_tx(&mut self) {
+        let (address, public_key) = match self.signer_identity() {
+            Some(identity) => identity,
+            None => {
+                self.status_message = Some((
+                    "No signer — load a wallet or set wallet.address and wallet.public_key in config".to_string(),
+                    true,
+                ));
+                return;
+            }
+        };
+        // Build the message up front so SDL problems show before any network calls
+        let msg = match &self.deployment_state.sdl {
+            Some(sdl) if !sdl.all_variables_filled() => {
+                self.status_message = Some(("Fill all SDL variables before exporting".to_string(), true));
+                return;
+            }
+            Some(sdl) => tx::create_deployment_msg(
+                &address,
+                0,
+                sdl,
+                self.deployment_state.deposit_denom.base_denom(&self.config.network),
+            ),
+            None => Err("SDL not loaded".to_string()),
+        };
+        let mut msg = match msg {
+            Ok(msg) => msg,
+            Err(e) => {
+                self.status_message = Some((format!("Cannot build deployment: {}", e), true));
+                return;
+            }
+        };
+        let path = match offline_tx_path(UNSIGNED_TX_FILE) {
+            Some(path) => path,
+            None => {
+                self.status_message = Some(("Config directory unavailable".to_string(), true));
+                return;
+            }
+        };
+
+        if let Some(tx) = &self.tx {
+            self.deployment_state.loading = true;
+            self.spinner.start();
+            self.spinner.message = "Building unsigned transaction...".to_string();
+            let tx = tx.clone();
+            let client = self.akash_client();
+            let chain_id = self.config.network.chain_id.clone();
+            tokio::spawn(async move {
+                let account = match client.get_account_info(&address).await {
+                    Ok(account) => account,
+                    Err(e) => {
+                        let _ = tx.send(AppEvent::StatusMessage {
+                            message: format!("Account query failed: {}", e),
+                            is_error: true,
+                        });
+                        return;
+                    }
+                };
+                let dseq = match client.get_block_height().await {
+                    Ok(height) => height,
+                    Err(e) => {
+                        let _ = tx.send(AppEvent::StatusMessage {
+                            message: format!("Block height query failed: {}", e),
+                            is_error: true,
+                        });
+                        return;
+                    }
+                };
+                if let Some(id) = msg.id.as_mut() {
+                    id.dseq = dseq;
+                }
+
+                let exported = TransactionSigner::encode_msg(&msg)
+                    .and_then(|any| {
+                        UnsignedTx::build(
+                            vec![any],
+                            &address,
+                            &public_key,
+                            &chain_id,
+                            account.account_number,
+                            account.sequence,
+                            DEPLOYMENT_GAS_LIMIT,
+                            tx::estimate_fee_uakt(DEPLOYMENT_GAS_LIMIT) as u128,
+                            "",
+                        )
+                    })
+                    .and_then(|unsigned| {
+                        let unsigned = unsigned.with_dseq(dseq);
+                        unsigned.save(&path)?;
+                        unsigned.qr_lines()
+                    })
+                    .map_err(|e| e.to_string());
+
+                let _ = match exported {
+                    Ok(qr) => tx.send(AppEvent::UnsignedTxExported {
+                        path: path.display().to_string(),
+                        dseq,
+                        qr,
+                    }),
+                    Err(e) => tx.send(AppEvent::StatusMessage {
+                        message: format!("Export failed: {}", e),
+                        is_error: true,
+                    }),
+                };
+            });
+        }
+    }
+
+    /// Air-gapped side: show the exported tx for review before signing it.
+    fn review_offline_tx(&mut self) {
+        if self.wallet_state.wallet.mnemonic.is_none() {
+            self.status_message = Some(("Load the signing wallet first".to_string(), true));
+            return;
+        }
+        let unsigned = match offline_tx_path(UNSIGNED_TX_FILE).map(|path| UnsignedTx::load(&path)) {
+            Some(Ok(unsigned)) => unsigned,
+            Some(Err(e)) => {
+                self.status_message = Some((format!("No exported transaction: {}", e), true));
+                return;
+            }
+            None => {
+                self.status_message = Some(("Config directory unavailable".to_string(), true));
+                return;
+            }
+        };
+
+        let contents = match unsigned.review_params() {
+            Ok(contents) => contents,
+            Err(e) => {
+                self.status_message = Some((format!("Cannot decode exported transaction: {}", e), true));
+                return;
+            }
+        };
+
+        let mut pending = PendingTx::new(
+            TxAction::SignOffline { unsigned: unsigned.clone() },
+            "Sign Offline Transaction",
+        )
+        .param("Signer", unsigned.signer.clone())
+        .param("Chain", unsigned.chain_id.clone())
+        .param("Account / Sequence", format!("{} / {}", unsigned.account_number, unsigned.sequence))
+        .with_gas_limit(unsigned.gas_limit);
+        pending.params.extend(contents);
+        self.request_tx(pending);
+    }
+
+    /// Air-gapped side: sign the reviewed tx and write the signed file.
+    fn sign_offline_tx(&mut self, unsigned: UnsignedTx) {
+        let (mnemonic, signed_path) = match (
+            self.wallet_state.wallet.mnemonic.clone(),
+            offline_tx_path(SIGNED_TX_FILE),
+        ) {
+            (Some(mnemonic), Some(path)) => (mnemonic, path),
+            _ => {
+                self.status_message = Some(("Signing wallet or config directory unavailable".to_string(), true));
+                return;
+            }
+        };
+        let signed = KeyGenerator::new()
+            .derive_keypair(&mnemonic)
+            .and_then(|keypair| TransactionSigner::new(keypair).sign_unsigned(&unsigned))
+            .and_then(|signed| signed.save(&signed_path));
+        self.status_message = Some(match signed {
+            Ok(()) => (format!("Signed transaction written to {}", signed_path.display()), false),
+            Err(e) => (format!("Offline signing failed: {}", e), true),
+        });
+    }
+
+    /// Online side: load the signed tx, check it matches the export, then
+    /// confirm before broadcasting.
+    fn import_signed_tx(&mut self) {
+        let paths = offline_tx_path(UNSIGNED_TX_FILE).zip(offline_tx_path(SIGNED_TX_FILE));
+        let loaded = match paths {
+            Some((unsigned_path, signed_path)) => UnsignedTx::load(&unsigned_path).and_then(|unsigned| {
+                let signed = SignedTx::load(&signed_path)?;
+                signed.verify_matches(&unsigned)?;
+                let contents = unsigned.review_params()?;
+                Ok((unsigned, signed, contents))
+            }),
+            None => Err("config directory unavailable".into()),
+        };
+
+        match loaded {
+            Ok((unsigned, signed, contents)) => {
+                let mut pending = PendingTx::new(
+                    TxAction::BroadcastSigned {
+                        signed,
+                        messages: unsigned.messages.clone(),
+                        dseq: unsigned.dseq,
+                    },
+                    "Broadcast Signed Transaction",
+                )
+                .param("Signer", unsigned.signer.clone())
+                .param("Sequence", unsigned.sequence.to_string())
+                .with_gas_limit(unsigned.gas_limit);
+                pending.params.extend(contents);
+                self.request_tx(pending);
+            }
+            Err(e) => {
+                self.status_message = Some((format!("Signed tx import failed: {}", e), true));
+            }
+        }
+    }
+
+    fn broadcast_signed_tx(&mut self, signed: SignedTx, messages: &[String], dseq: Option<u64>) {
+        let tx_bytes = match signed.tx_bytes() {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                self.status_message = Some((format!("Signed tx unreadable: {}", e), true));
+                return;
+            }
+        };
+        let created_dseq = dseq.filter(|_| {
+            messages.iter().any(|m| m == &TxAction::CreateDeployment.msg_type())
+        });
+
+        if let Some(tx) = &self.tx {
+            self.spinner.start();
+            self.spinner.message = "Broadcasting signed transaction...".to_string();
+            let tx = tx.clone();
+            let client = self.akash_client();
+            tokio::spawn(async move {
+                let event = match client.broadcast_tx(&tx_bytes).await {
+                    Ok(result) if result.code == 0 => match created_dseq {
+                        Some(dseq) => AppEvent::DeploymentCreated { dseq, txhash: result.txhash },
+                        None => AppEvent::TxBroadcast {
+                            txhash: result.txhash,
+                            success: true,
+                            message: String::new(),
+                        },
+                    },
+                    Ok(result) => AppEvent::TxBroadcast {
+                        txhash: result.txhash,
+                        success: false,
+                        message: result.raw_log,
+                    },
+                    Err(e) => AppEvent::StatusMessage {
+                        message: format!("Broadcast failed: {}", e),
+                        is_error: true,
+                    },
+                };
+                let _ = tx.send(event);
+            });
+        }
+    }
+
     /// Fetch fee grant allowances for the current wallet
     fn check_fee_allowances(&mut self) {
         let address = self.wallet_state.wallet.address.clone();
@@ -1433,10 +2497,8 @@ impl App {
             self.spinner.start();
             self.spinner.message = "Checking fee grants...".to_string();
             let tx = tx.clone();
-            let rpc_url = self.config.network.rpc_url.clone();
-            let grpc_url = self.config.network.grpc_url.clone();
+            let client = self.akash_client();
             tokio::spawn(async move {
-                let client = AkashClient::new(rpc_url, grpc_url);
                 match client.query_fee_allowances(&addr).await {
                     Ok(allowances) => {
                         let _ = tx.send(AppEvent::FeeAllowanceReceived { allowances });
@@ -1494,45 +2556,63 @@ impl App {
         }
     }
 
-    fn test_provision_endpoint(&mut self) {
-        let bot_url = self.discord_state.form.get_value("Bot URL").to_string();
-        if bot_url.is_empty() {
-            self.status_message = Some(("Bot URL not set — press 'u' to auto-populate".to_string(), true));
+    /// Send the form's secrets to the bot, or with `dry_run` check every
+    /// step without sending, through the same code as `provision`.
+    fn run_provisioning(&mut self, dry_run: bool) {
+        let form = &self.discord_state.form;
+        let bot_url = form.get_value("Bot URL").trim().to_string();
+        let discord_token = form.get_value("Bot Token").trim().to_string();
+        let admin_key = form.get_value("Admin Key").trim().to_string();
+        let missing = if bot_url.is_empty() {
+            Some("Bot URL not set — press 'u' to auto-populate")
+        } else if discord_token.is_empty() {
+            Some("Bot Token not set")
+        } else if admin_key.is_empty() {
+            Some("Admin Key not set")
+        } else {
+            None
+        };
+        if let Some(missing) = missing {
+            self.status_message = Some((missing.to_string(), true));
             return;
         }
+        let hf_token = Some(form.get_value("HF Token").trim().to_string()).filter(|t| !t.is_empty());
 
         if let Some(tx) = &self.tx {
             self.discord_state.loading = true;
             self.spinner.start();
-            self.spinner.message = "Testing endpoint...".to_string();
+            self.spinner.message = if dry_run { "Checking provisioning..." } else { "Provisioning bot..." }.to_string();
             let tx = tx.clone();
-            let url = bot_url.clone();
+            // Provision wherever the deployed bot serves its admin endpoints
+            let path_prefix = self.deployment_state.sdl.as_ref().and_then(|sdl| sdl.admin_path_prefix());
+            let job = ProvisionJob {
+                bot_url,
+                path_prefix,
+                admin_key: PathBuf::from(admin_key),
+                secrets: Secrets {
+                    discord_token,
+                    hf_token,
+                    custom: Default::default(),
+                },
+                dry_run,
+            };
             tokio::spawn(async move {
-                let client = reqwest::Client::new();
-                match client.get(&format!("{}/health", url))
-                    .timeout(std::time::Duration::from_secs(5))
-                    .send()
+                let result = provision::run(&job)
                     .await
-                {
-                    Ok(resp) => {
-                        let status = resp.status();
-                        let msg = if status.is_success() {
-                            format!("Endpoint healthy ({})", status)
-                        } else {
-                            format!("Endpoint responded with {}", status)
-                        };
-                        let _ = tx.send(AppEvent::StatusMessage {
-                            message: msg,
-                            is_error: !status.is_success(),
-                        });
-                    }
-                    Err(e) => {
-                        let _ = tx.send(AppEvent::StatusMessage {
-                            message: format!("Endpoint unreachable: {}", e),
-                            is_error: true,
-                        });
-                    }
-                }
+                    .map(|report| match report {
+                        JobReport::DryRun(report) => format!(
+                            "Dry run ok, nothing sent; would send {}",
+                            report
+                                .secrets
+                                .iter()
+                                .map(|(name, _)| name.as_str())
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        ),
+                        sent => sent.to_string().trim_end().to_string(),
+                    })
+                    .map_err(|e| format!("{:#}", e));
+                let _ = tx.send(AppEvent::ProvisionFinished { dry_run, result });
             });
         }
     }
@@ -1659,8 +2739,15 @@ impl App {
         };
 
         let entering_fee_grant = new_screen == Screen::FeeGrant && self.current_screen != Screen::FeeGrant;
+        let entering_deployments =
+            new_screen == Screen::Deployments && self.current_screen != Screen::Deployments;
         self.current_screen = new_screen;
 
+        // Show escrow badges without waiting for the next scan
+        if entering_deployments && !self.deployments_state.loading {
+            self.scan_escrow(true);
+        }
+
         // Auto-check balance and fee grants when entering the FeeGrant step
         if entering_fee_grant && self.wallet_state.wallet.address.is_some() {
             if self.fee_grant_state.balance.is_none() {
@@ -1690,6 +2777,205 @@ impl App {
             }
         }).collect();
         self.status_message = Some(("Deployments refreshed".to_string(), false));
+        self.scan_escrow(false);
+    }
+
+    /// Fetch the escrow balance and lease prices of every active deployment.
+    fn scan_escrow(&mut self, background: bool) {
+        let address = self.wallet_state.wallet.address.clone();
+        let (Some(tx), Some(owner)) = (&self.tx, address) else {
+            if !background {
+                self.status_message = Some(("No wallet loaded".to_string(), true));
+            }
+            return;
+        };
+        self.deployments_state.loading = true;
+        if !background {
+            self.spinner.start();
+            self.spinner.message = "Checking escrow...".to_string();
+        }
+        let tx = tx.clone();
+        let client = self.akash_client();
+        tokio::spawn(async move {
+            let scan = async {
+                let deployments = client.query_deployments(&owner).await?.items;
+                let leases = client.query_leases(&owner).await?.items;
+                let mut statuses = Vec::new();
+                for deployment in deployments.iter().filter(|d| d.state == "active") {
+                    let info = client.query_escrow(&owner, deployment.dseq).await?;
+                    statuses.push(EscrowStatus::new(info, &leases));
+                }
+                Ok::<_, Box<dyn std::error::Error>>(statuses)
+            };
+            let result = scan.await.map_err(|e| e.to_string());
+            let _ = tx.send(AppEvent::EscrowScanned { result });
+        });
+    }
+
+    /// Record a scan, warn about deployments that just became at risk and
+    /// send any top-ups the user opted in to.
+    fn apply_escrow_scan(&mut self, statuses: Vec<EscrowStatus>) {
+        let outcome = self.deployments_state.escrow.update(statuses, &self.config.escrow);
+        if !outcome.newly_at_risk.is_empty() {
+            let warnings: Vec<String> = outcome
+                .newly_at_risk
+                .iter()
+                .filter_map(|dseq| self.deployments_state.escrow.statuses.get(dseq))
+                .map(|status| match status.health(self.config.escrow.warn_hours) {
+                    EscrowHealth::Depleted => format!("DSeq {} is out of escrow", status.dseq),
+                    _ => format!(
+                        "DSeq {} has ~{} of escrow left",
+                        status.dseq,
+                        status.hours_left().map(escrow::format_runway).unwrap_or_default()
+                    ),
+                })
+                .collect();
+            self.status_message = Some((
+                format!("{} — press d on the Bots tab to deposit", warnings.join(", ")),
+                true,
+            ));
+        }
+        for dseq in outcome.topups {
+            let Some(denom) = self.deployments_state.escrow.statuses.get(&dseq).map(|s| s.denom.clone()) else {
+                continue;
+            };
+            if self.read_only || self.wallet_state.wallet.mnemonic.is_none() {
+                self.status_message = Some((
+                    format!("Automatic top-up of DSeq {} skipped: no wallet to sign with", dseq),
+                    true,
+                ));
+                continue;
+            }
+            // `escrow.auto_topup` is the user's standing confirmation, so this
+            // skips the TxConfirm popup
+            self.broadcast_deposit(dseq, self.config.escrow.topup_amount, denom, true);
+        }
+    }
+
+    /// A base-denom amount of `denom`, in display units when it is one of the
+    /// deposit denoms.
+    pub fn format_escrow_amount(&self, amount: u64, denom: &str) -> String {
+        let network = &self.config.network;
+        match tx::DepositDenom::from_base_denom(denom, network) {
+            Some(deposit_denom) => deposit_denom.format_amount(amount, network),
+            None => format!("{} {}", amount, denom),
+        }
+    }
+
+    /// Ask to deposit into the selected deployment's escrow, in the denom it
+    /// was funded with.
+    fn deposit_selected(&mut self) {
+        let Some(record) = self.deployments_state.deployments.get(self.deployments_state.selected_index) else {
+            self.status_message = Some(("No deployments".to_string(), true));
+            return;
+        };
+        let Some(status) = self.deployments_state.escrow.statuses.get(&record.dseq) else {
+            self.status_message = Some((
+                format!("No escrow found for DSeq {} — press r to rescan", record.dseq),
+                true,
+            ));
+            return;
+        };
+        let amount = match self.config.escrow.topup_amount {
+            0 => tx::MIN_DEPLOYMENT_DEPOSIT_UAKT,
+            amount => amount,
+        };
+        let runway = status
+            .hours_left()
+            .map(escrow::format_runway)
+            .unwrap_or_else(|| "no active lease".to_string());
+        let pending = PendingTx::new(
+            TxAction::DepositDeployment { dseq: record.dseq, amount, denom: status.denom.clone() },
+            "Deposit to Escrow",
+        )
+        .param("DSeq", record.dseq.to_string())
+        .param("Escrow Balance", self.format_escrow_amount(status.balance, &status.denom))
+        .param("Runway", runway)
+        .param("Deposit", self.format_escrow_amount(amount, &status.denom))
+        .with_fee_grant(self.fee_grant_state.has_fee_grant);
+        self.request_tx(pending);
+    }
+
+    /// Sign and broadcast MsgDepositDeployment. Only `deposit_selected`
+    /// (through the TxConfirm popup) and opted-in top-ups call this.
+    fn broadcast_deposit(&mut self, dseq: u64, amount: u64, denom: String, automatic: bool) {
+        let (mnemonic, address) = match (
+            self.wallet_state.wallet.mnemonic.clone(),
+            self.wallet_state.wallet.address.clone(),
+        ) {
+            (Some(mnemonic), Some(address)) => (mnemonic, address),
+            _ => {
+                self.status_message = Some(("No wallet loaded".to_string(), true));
+                return;
+            }
+        };
+
+        if let Some(tx) = &self.tx {
+            if !automatic {
+                self.spinner.start();
+                self.spinner.message = "Depositing to escrow...".to_string();
+            }
+            let tx = tx.clone();
+            let client = self.akash_client();
+            let chain_id = self.config.network.chain_id.clone();
+            tokio::spawn(async move {
+                let account = match client.get_account_info(&address).await {
+                    Ok(account) => account,
+                    Err(e) => {
+                        let _ = tx.send(AppEvent::StatusMessage {
+                            message: format!("Account query failed: {}", e),
+                            is_error: true,
+                        });
+                        return;
+                    }
+                };
+                let msg = tx::deposit_deployment_msg(&address, dseq, amount, &denom);
+                let signed = KeyGenerator::new()
+                    .derive_keypair(&mnemonic)
+                    .and_then(|keypair| {
+                        let any = TransactionSigner::encode_msg(&msg)?;
+                        TransactionSigner::new(keypair).create_signed_tx(
+                            vec![any],
+                            &chain_id,
+                            account.account_number,
+                            account.sequence,
+                            DEFAULT_GAS_LIMIT,
+                            tx::estimate_fee_uakt(DEFAULT_GAS_LIMIT) as u128,
+                            "",
+                        )
+                    })
+                    .map_err(|e| e.to_string());
+                let tx_bytes = match signed {
+                    Ok(bytes) => bytes,
+                    Err(e) => {
+                        let _ = tx.send(AppEvent::StatusMessage {
+                            message: format!("Signing failed: {}", e),
+                            is_error: true,
+                        });
+                        return;
+                    }
+                };
+
+                match client.broadcast_tx(&tx_bytes).await {
+                    Ok(result) if result.code == 0 => {
+                        let _ = tx.send(AppEvent::EscrowDeposited { dseq, txhash: result.txhash, automatic });
+                    }
+                    Ok(result) => {
+                        let _ = tx.send(AppEvent::TxBroadcast {
+                            txhash: result.txhash,
+                            success: false,
+                            message: result.raw_log,
+                        });
+                    }
+                    Err(e) => {
+                        let _ = tx.send(AppEvent::StatusMessage {
+                            message: format!("Broadcast failed: {}", e),
+                            is_error: true,
+                        });
+                    }
+                }
+            });
+        }
     }
 
     fn fetch_deployment_logs(&mut self) {
@@ -1705,3 +2991,32 @@ impl App {
         self.sync_screen_from_tab();
     }
 }
+
+/// Location of an air-gapped signing file in the config directory.
+fn offline_tx_path(file: &str) -> Option<PathBuf> {
+    let store = ConfigStore::new().ok()?;
+    store.config_dir().map(|dir| dir.join(file))
+}
+
+/// API address of `provider` and a client pinned to the certificates it
+/// published on chain, so nothing else can answer in its place.
+async fn connect_provider(
+    chain: &AkashClient,
+    client: &ProviderClient,
+    provider: &str,
+) -> Result<(String, ProviderClient), String> {
+    let host_uri = chain.query_provider_host(provider).await.map_err(|e| e.to_string())?;
+    let certs = chain.query_certificates(provider).await.map_err(|e| e.to_string())?;
+    let pinned = client.pinned_to(&certs).map_err(|e| e.to_string())?;
+    Ok((host_uri, pinned))
+}
+
+/// Error event for a failed chain query; background refreshes report
+/// through `AutoRefresh` so a down node doesn't flood the status line.
+fn query_failed(background: bool, message: String) -> AppEvent {
+    if background {
+        AppEvent::BackgroundRefreshFailed { message }
+    } else {
+        AppEvent::StatusMessage { message, is_error: true }
+    }
+}
@@ -8,6 +8,80 @@ pub struct AppConfig {
     pub network: NetworkConfig,
     pub wallet: WalletConfig,
     pub deployments: Vec<SavedDeployment>,
+    /// Seconds between background balance/lease refreshes (0 disables).
+    #[serde(default = "default_auto_refresh_secs")]
+    pub auto_refresh_secs: u64,
+    /// Seconds to wait for a provider API reply before treating the provider
+    /// as unresponsive.
+    #[serde(default = "default_provider_timeout_secs")]
+    pub provider_timeout_secs: u64,
+    /// When to warn about, and optionally top up, deployments running low
+    /// on escrow.
+    #[serde(default)]
+    pub escrow: EscrowConfig,
+}
+
+/// Default background refresh interval for balances and leases.
+pub const DEFAULT_AUTO_REFRESH_SECS: u64 = 30;
+
+fn default_auto_refresh_secs() -> u64 {
+    DEFAULT_AUTO_REFRESH_SECS
+}
+
+/// Default wait for a provider API reply.
+pub const DEFAULT_PROVIDER_TIMEOUT_SECS: u64 = 10;
+
+fn default_provider_timeout_secs() -> u64 {
+    DEFAULT_PROVIDER_TIMEOUT_SECS
+}
+
+/// Default seconds between escrow scans of active deployments.
+pub const DEFAULT_ESCROW_SCAN_SECS: u64 = 300;
+
+/// Default runway, in hours, below which a deployment is flagged.
+pub const DEFAULT_ESCROW_WARN_HOURS: u64 = 24;
+
+/// Escrow monitoring for active deployments. Automatic deposits spend from
+/// the wallet, so they stay off unless `auto_topup` is set and an amount
+/// is configured.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EscrowConfig {
+    /// Seconds between escrow scans (0 disables them).
+    #[serde(default = "default_escrow_scan_secs")]
+    pub scan_secs: u64,
+    /// Grace period: flag deployments with less runway than this.
+    #[serde(default = "default_escrow_warn_hours")]
+    pub warn_hours: u64,
+    /// Deposit without asking when a deployment drops below
+    /// `topup_below_hours`.
+    #[serde(default)]
+    pub auto_topup: bool,
+    /// Runway, in hours, below which an automatic deposit is made.
+    #[serde(default)]
+    pub topup_below_hours: u64,
+    /// Base-denom amount of each deposit; also the default for manual ones.
+    #[serde(default)]
+    pub topup_amount: u64,
+}
+
+fn default_escrow_scan_secs() -> u64 {
+    DEFAULT_ESCROW_SCAN_SECS
+}
+
+fn default_escrow_warn_hours() -> u64 {
+    DEFAULT_ESCROW_WARN_HOURS
+}
+
+impl Default for EscrowConfig {
+    fn default() -> Self {
+        Self {
+            scan_secs: DEFAULT_ESCROW_SCAN_SECS,
+            warn_hours: DEFAULT_ESCROW_WARN_HOURS,
+            auto_topup: false,
+            topup_below_hours: 0,
+            topup_amount: 0,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -16,6 +90,87 @@ pub struct NetworkConfig {
     pub rpc_url: String,
     pub grpc_url: String,
     pub provider_url: String,
+    /// Extra REST endpoints tried when `rpc_url` is unreachable or rate limited.
+    #[serde(default)]
+    pub fallback_rpc_urls: Vec<String>,
+    /// Extra gRPC endpoints tried when `grpc_url` is unreachable.
+    #[serde(default)]
+    pub fallback_grpc_urls: Vec<String>,
+    /// Base denom balances are held in on chain, e.g. `uakt` or an `ibc/…`
+    /// denom.
+    #[serde(default = "default_denom")]
+    pub denom: String,
+    /// Decimal places between the base denom and the unit shown in the UI.
+    #[serde(default = "default_decimals")]
+    pub decimals: u32,
+    /// Unit shown in the UI, e.g. `AKT`. Derived from `denom` when unset.
+    #[serde(default)]
+    pub display_denom: Option<String>,
+    /// IBC denom of USDC on this chain, used for USDC deployment deposits.
+    #[serde(default = "default_usdc_denom")]
+    pub usdc_denom: String,
+}
+
+/// Base denom of Akash mainnet.
+pub const DEFAULT_DENOM: &str = "uakt";
+
+/// Decimal places of AKT.
+pub const DEFAULT_DECIMALS: u32 = 6;
+
+/// IBC denom of Noble USDC on Akash mainnet.
+pub const DEFAULT_USDC_DENOM: &str =
+    "ibc/170C677610AC31DF0904FFE09CD3B5C657492170E7E52372E48756B71E56F2AC";
+
+fn default_denom() -> String {
+    DEFAULT_DENOM.to_string()
+}
+
+fn default_decimals() -> u32 {
+    DEFAULT_DECIMALS
+}
+
+fn default_usdc_denom() -> String {
+    DEFAULT_USDC_DENOM.to_string()
+}
+
+impl NetworkConfig {
+    /// Unit amounts are shown in: the configured display denom, else the
+    /// base denom without its `u` (micro, 6 decimals) or `a` (atto, 18
+    /// decimals) prefix, else the base denom itself.
+    pub fn display_denom(&self) -> String {
+        if let Some(display) = self.display_denom.as_deref().filter(|d| !d.is_empty()) {
+            return display.to_string();
+        }
+        let unprefixed = match self.decimals {
+            6 => self.denom.strip_prefix('u'),
+            18 => self.denom.strip_prefix('a'),
+            _ => None,
+        };
+        match unprefixed {
+            Some(name) if !name.is_empty() && !name.contains('/') => name.to_uppercase(),
+            _ => self.denom.clone(),
+        }
+    }
+
+    /// A coin as returned by the chain, in display units when it is in the
+    /// base denom and as-is otherwise.
+    pub fn format_coin(&self, amount: &str, denom: &str) -> String {
+        match amount.parse::<u64>() {
+            Ok(amount) if denom == self.denom => self.format_amount(amount),
+            _ => format!("{} {}", amount, denom),
+        }
+    }
+
+    /// A base-denom amount in display units, e.g. `1500000` → `1.500 AKT`.
+    pub fn format_amount(&self, amount: u64) -> String {
+        let value = amount as f64 / 10f64.powi(self.decimals as i32);
+        format!(
+            "{:.*} {}",
+            self.decimals.min(3) as usize,
+            value,
+            self.display_denom()
+        )
+    }
 }
 
 /// Default gRPC endpoint for Akash mainnet queries.
@@ -29,6 +184,12 @@ impl Default for NetworkConfig {
             rpc_url: "https://rpc.akashnet.net:443".to_string(),
             grpc_url: DEFAULT_GRPC_URL.to_string(),
             provider_url: "https://provider.akashnet.net".to_string(),
+            fallback_rpc_urls: vec![],
+            fallback_grpc_urls: vec![],
+            denom: default_denom(),
+            decimals: DEFAULT_DECIMALS,
+            display_denom: None,
+            usdc_denom: default_usdc_denom(),
         }
     }
 }
@@ -37,6 +198,10 @@ impl Default for NetworkConfig {
 pub struct WalletConfig {
     pub encrypted_mnemonic: Option<Vec<u8>>,
     pub address: Option<String>,
+    /// Base64 secp256k1 public key of an offline signer; with `address` this
+    /// lets the TUI export unsigned txs without holding the mnemonic.
+    #[serde(default)]
+    pub public_key: Option<String>,
 }
 
 impl Default for WalletConfig {
@@ -44,6 +209,7 @@ impl Default for WalletConfig {
         Self {
             encrypted_mnemonic: None,
             address: None,
+            public_key: None,
         }
     }
 }
@@ -62,6 +228,55 @@ impl Default for AppConfig {
             network: NetworkConfig::default(),
             wallet: WalletConfig::default(),
             deployments: vec![],
+            auto_refresh_secs: DEFAULT_AUTO_REFRESH_SECS,
+            provider_timeout_secs: DEFAULT_PROVIDER_TIMEOUT_SECS,
+            escrow: EscrowConfig::default(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn network(denom: &str, decimals: u32, display_denom: Option<&str>) -> NetworkConfig {
+        NetworkConfig {
+            denom: denom.to_string(),
+            decimals,
+            display_denom: display_denom.map(str::to_string),
+            ..NetworkConfig::default()
+        }
+    }
+
+    #[test]
+    fn format_amount_uses_denom_and_decimals() {
+        assert_eq!(NetworkConfig::default().format_amount(1_500_000), "1.500 AKT");
+        assert_eq!(network("utest", 6, None).format_amount(250_000), "0.250 TEST");
+        assert_eq!(network("aevmos", 18, None).format_amount(2_000_000_000_000_000_000), "2.000 EVMOS");
+        assert_eq!(network("stake", 0, None).format_amount(42), "42 stake");
+    }
+
+    #[test]
+    fn format_coin_leaves_other_denoms_alone() {
+        let config = NetworkConfig::default();
+        assert_eq!(config.format_coin("5000000", "uakt"), "5.000 AKT");
+        assert_eq!(config.format_coin("12", "uusdc"), "12 uusdc");
+    }
+
+    #[test]
+    fn ibc_denoms_need_an_explicit_display_denom() {
+        let ibc = "ibc/170C677610AC31DF0904FFE09CD3B5C657492170E7E52372E48756B71E56F2F1";
+        assert_eq!(network(ibc, 6, None).display_denom(), ibc);
+        assert_eq!(network(ibc, 6, Some("USDC")).format_amount(1_000_000), "1.000 USDC");
+    }
+
+    #[test]
+    fn older_configs_default_to_akt() {
+        let config: NetworkConfig = serde_json::from_str(
+            r#"{"chain_id":"akashnet-2","rpc_url":"r","grpc_url":"g","provider_url":"p"}"#,
+        )
+        .unwrap();
+        assert_eq!(config.denom, DEFAULT_DENOM);
+        assert_eq!(config.display_denom(), "AKT");
+    }
+}
@@ -105,6 +105,51 @@ impl ConfigStore {
     pub fn wallet_path(&self) -> &std::path::Path {
         &self.encrypted_path
     }
+
+    /// Record the SDL a deployment is running, the baseline later updates
+    /// are checked against.
+    pub fn save_deployed_sdl(&self, dseq: u64, yaml: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let path = self.deployed_sdl_path(dseq);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, yaml)?;
+        Ok(())
+    }
+
+    /// SDL recorded for a deployment, if this machine created or updated it.
+    pub fn load_deployed_sdl(&self, dseq: u64) -> Option<String> {
+        fs::read_to_string(self.deployed_sdl_path(dseq)).ok()
+    }
+
+    /// Record the provider certificate published on chain for `address`.
+    /// Only the public certificate is stored; its key is re-derived from
+    /// the wallet.
+    pub fn save_provider_cert(&self, address: &str, cert_pem: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let path = self.provider_cert_path(address);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, cert_pem)?;
+        Ok(())
+    }
+
+    /// Provider certificate published for `address` from this machine.
+    pub fn load_provider_cert(&self, address: &str) -> Option<String> {
+        fs::read_to_string(self.provider_cert_path(address)).ok()
+    }
+
+    fn provider_cert_path(&self, address: &str) -> PathBuf {
+        self.config_path
+            .with_file_name("certs")
+            .join(format!("{}.pem", address))
+    }
+
+    fn deployed_sdl_path(&self, dseq: u64) -> PathBuf {
+        self.config_path
+            .with_file_name("deployments")
+            .join(format!("{}.yaml", dseq))
+    }
 }
 
 /// Derive an AES-256-GCM cipher from a password using HKDF-SHA256.
@@ -152,6 +197,54 @@ mod tests {
         let _ = fs::remove_file(&store.config_path);
     }
 
+    #[test]
+    fn load_config_fills_defaults_for_missing_fields() {
+        let store = temp_store();
+        let legacy = r#"{
+            "network": {
+                "chain_id": "akashnet-2",
+                "rpc_url": "https://rpc.example",
+                "grpc_url": "https://grpc.example",
+                "provider_url": "https://provider.example"
+            },
+            "wallet": { "encrypted_mnemonic": null, "address": null },
+            "deployments": []
+        }"#;
+        fs::write(&store.config_path, legacy).unwrap();
+
+        let loaded = store.load_config().unwrap();
+        assert!(loaded.network.fallback_rpc_urls.is_empty());
+        assert_eq!(loaded.auto_refresh_secs, super::super::schema::DEFAULT_AUTO_REFRESH_SECS);
+        assert_eq!(
+            loaded.provider_timeout_secs,
+            super::super::schema::DEFAULT_PROVIDER_TIMEOUT_SECS
+        );
+        // Automatic deposits stay off unless configured
+        assert!(!loaded.escrow.auto_topup);
+        assert_eq!(loaded.escrow.warn_hours, super::super::schema::DEFAULT_ESCROW_WARN_HOURS);
+
+        // cleanup
+        let _ = fs::remove_file(&store.config_path);
+    }
+
+    #[test]
+    fn provider_cert_roundtrip_per_address() {
+        let store = temp_store();
+        // lib and bin test binaries share temp dirs; keep addresses unique
+        let owner = format!("akash1owner{}", std::process::id());
+        assert!(store.load_provider_cert(&owner).is_none());
+
+        store.save_provider_cert(&owner, "-----BEGIN CERTIFICATE-----").unwrap();
+        assert_eq!(
+            store.load_provider_cert(&owner).as_deref(),
+            Some("-----BEGIN CERTIFICATE-----")
+        );
+        assert!(store.load_provider_cert("akash1other").is_none());
+
+        // cleanup
+        let _ = fs::remove_file(store.provider_cert_path(&owner));
+    }
+
     #[test]
     fn encrypt_decrypt_wallet_roundtrip() {
         let store = temp_store();
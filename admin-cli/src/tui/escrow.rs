@@ -0,0 +1,280 @@
+//! Escrow runway of active deployments.
+//!
+//! Leases are paid per block out of their deployment's escrow account, and a
+//! provider closes the lease once the account runs dry. The TUI scans active
+//! deployments on `EscrowConfig::scan_secs`, flags those with less than the
+//! configured grace period left, and (only when opted in) deposits more.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::tui::api::{EscrowInfo, LeaseInfo};
+use crate::tui::config::schema::EscrowConfig;
+
+/// Average Akash block time, used to turn blocks into hours.
+pub const SECS_PER_BLOCK: f64 = 6.0;
+
+/// Escrow balance and spend rate of one deployment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EscrowStatus {
+    pub dseq: u64,
+    /// Unspent deposit in base-denom units
+    pub balance: u64,
+    pub denom: String,
+    /// Combined price of the deployment's active leases, per block
+    pub per_block: f64,
+    /// Escrow account state on chain (`open`, `overdrawn`, `closed`)
+    pub state: String,
+}
+
+impl EscrowStatus {
+    /// Escrow `info` spent by whichever of `leases` belong to its deployment.
+    pub fn new(info: EscrowInfo, leases: &[LeaseInfo]) -> Self {
+        let per_block = leases
+            .iter()
+            .filter(|lease| lease.dseq == info.dseq && lease.state == "active")
+            .filter_map(|lease| lease.price_amount.parse::<f64>().ok())
+            .sum();
+        Self {
+            dseq: info.dseq,
+            balance: info.balance,
+            denom: info.denom,
+            per_block,
+            state: info.state,
+        }
+    }
+
+    /// Hours until the balance runs out, or None while nothing is spending it.
+    pub fn hours_left(&self) -> Option<f64> {
+        (self.per_block > 0.0)
+            .then(|| self.balance as f64 / self.per_block * SECS_PER_BLOCK / 3600.0)
+    }
+
+    pub fn health(&self, warn_hours: u64) -> EscrowHealth {
+        if self.state == "overdrawn" || (self.balance == 0 && self.per_block > 0.0) {
+            return EscrowHealth::Depleted;
+        }
+        match self.hours_left() {
+            Some(hours) if hours < warn_hours as f64 => EscrowHealth::Low,
+            _ => EscrowHealth::Healthy,
+        }
+    }
+}
+
+/// How close a deployment is to losing its leases.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EscrowHealth {
+    Healthy,
+    /// Within the grace period
+    Low,
+    /// Out of funds; providers may already be closing leases
+    Depleted,
+}
+
+impl EscrowHealth {
+    pub fn is_at_risk(self) -> bool {
+        self != Self::Healthy
+    }
+}
+
+/// A runway such as `2d 5h`, `7h` or `<1h`.
+pub fn format_runway(hours: f64) -> String {
+    let whole = hours.floor() as u64;
+    match whole {
+        0 => "<1h".to_string(),
+        1..=47 => format!("{}h", whole),
+        _ => format!("{}d {}h", whole / 24, whole % 24),
+    }
+}
+
+/// What a scan turned up that the user should hear about.
+#[derive(Debug, Default, PartialEq)]
+pub struct ScanOutcome {
+    /// Deployments that just entered the grace period (or ran dry)
+    pub newly_at_risk: Vec<u64>,
+    /// Deployments to deposit `EscrowConfig::topup_amount` into
+    pub topups: Vec<u64>,
+}
+
+/// Escrow state across scans. Each deployment is warned about, and topped
+/// up, once per dip below the thresholds, so a failed or pending deposit
+/// isn't retried every scan.
+#[derive(Debug, Default)]
+pub struct EscrowMonitor {
+    pub statuses: HashMap<u64, EscrowStatus>,
+    warned: HashSet<u64>,
+    topped_up: HashSet<u64>,
+}
+
+impl EscrowMonitor {
+    pub fn health(&self, dseq: u64, config: &EscrowConfig) -> Option<EscrowHealth> {
+        self.statuses.get(&dseq).map(|status| status.health(config.warn_hours))
+    }
+
+    /// Replace the statuses with a new scan's.
+    pub fn update(&mut self, statuses: Vec<EscrowStatus>, config: &EscrowConfig) -> ScanOutcome {
+        let mut outcome = ScanOutcome::default();
+        self.statuses = statuses.into_iter().map(|status| (status.dseq, status)).collect();
+        let mut dseqs: Vec<u64> = self.statuses.keys().copied().collect();
+        dseqs.sort_unstable();
+
+        for dseq in dseqs {
+            let status = &self.statuses[&dseq];
+            if status.health(config.warn_hours).is_at_risk() {
+                if self.warned.insert(dseq) {
+                    outcome.newly_at_risk.push(dseq);
+                }
+            } else {
+                self.warned.remove(&dseq);
+            }
+            if wants_topup(status, config) {
+                if self.topped_up.insert(dseq) {
+                    outcome.topups.push(dseq);
+                }
+            } else {
+                self.topped_up.remove(&dseq);
+            }
+        }
+        // Closed deployments drop out of the scan
+        self.warned.retain(|dseq| self.statuses.contains_key(dseq));
+        self.topped_up.retain(|dseq| self.statuses.contains_key(dseq));
+        outcome
+    }
+}
+
+/// Whether the user opted in to an automatic deposit for `status`.
+fn wants_topup(status: &EscrowStatus, config: &EscrowConfig) -> bool {
+    if !config.auto_topup || config.topup_amount == 0 || status.state == "closed" {
+        return false;
+    }
+    status
+        .hours_left()
+        .is_some_and(|hours| hours < config.topup_below_hours as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Blocks in an hour at `SECS_PER_BLOCK`
+    const BLOCKS_PER_HOUR: u64 = 600;
+
+    fn status(dseq: u64, hours: u64) -> EscrowStatus {
+        EscrowStatus {
+            dseq,
+            balance: hours * BLOCKS_PER_HOUR * 10,
+            denom: "uakt".to_string(),
+            per_block: 10.0,
+            state: "open".to_string(),
+        }
+    }
+
+    fn lease(dseq: u64, price: &str, state: &str) -> LeaseInfo {
+        LeaseInfo {
+            owner: "akash1owner".to_string(),
+            dseq,
+            gseq: 1,
+            oseq: 1,
+            provider: "akash1provider".to_string(),
+            price_amount: price.to_string(),
+            price_denom: "uakt".to_string(),
+            state: state.to_string(),
+        }
+    }
+
+    fn opted_in() -> EscrowConfig {
+        EscrowConfig {
+            auto_topup: true,
+            topup_below_hours: 6,
+            topup_amount: 5_000_000,
+            ..EscrowConfig::default()
+        }
+    }
+
+    #[test]
+    fn burn_rate_sums_active_leases() {
+        let info = EscrowInfo {
+            dseq: 100,
+            balance: 36_000,
+            denom: "uakt".to_string(),
+            state: "open".to_string(),
+        };
+        let leases = [
+            lease(100, "12.5", "active"),
+            lease(100, "2.5", "active"),
+            lease(100, "99", "closed"),
+            lease(101, "99", "active"),
+        ];
+        let status = EscrowStatus::new(info, &leases);
+        assert_eq!(status.per_block, 15.0);
+        // 2400 blocks of 6s
+        assert_eq!(status.hours_left(), Some(4.0));
+    }
+
+    #[test]
+    fn health_follows_the_grace_period() {
+        assert_eq!(status(100, 48).health(24), EscrowHealth::Healthy);
+        assert_eq!(status(100, 12).health(24), EscrowHealth::Low);
+        assert_eq!(status(100, 0).health(24), EscrowHealth::Depleted);
+
+        let mut overdrawn = status(100, 48);
+        overdrawn.state = "overdrawn".to_string();
+        assert_eq!(overdrawn.health(24), EscrowHealth::Depleted);
+
+        // Without a lease nothing is spent
+        let mut idle = status(100, 0);
+        idle.per_block = 0.0;
+        assert_eq!(idle.hours_left(), None);
+        assert_eq!(idle.health(24), EscrowHealth::Healthy);
+    }
+
+    #[test]
+    fn runway_formatting() {
+        assert_eq!(format_runway(0.4), "<1h");
+        assert_eq!(format_runway(7.9), "7h");
+        assert_eq!(format_runway(53.0), "2d 5h");
+    }
+
+    #[test]
+    fn warns_once_per_dip() {
+        let config = EscrowConfig::default();
+        let mut monitor = EscrowMonitor::default();
+
+        let outcome = monitor.update(vec![status(100, 12), status(101, 72)], &config);
+        assert_eq!(outcome.newly_at_risk, vec![100]);
+        assert_eq!(monitor.health(100, &config), Some(EscrowHealth::Low));
+        assert!(monitor.update(vec![status(100, 11), status(101, 71)], &config).newly_at_risk.is_empty());
+
+        // Topped up by hand, then low again
+        monitor.update(vec![status(100, 48)], &config);
+        assert_eq!(monitor.update(vec![status(100, 3)], &config).newly_at_risk, vec![100]);
+    }
+
+    #[test]
+    fn topups_need_opt_in() {
+        let mut monitor = EscrowMonitor::default();
+        let outcome = monitor.update(vec![status(100, 2)], &EscrowConfig::default());
+        assert!(outcome.topups.is_empty());
+
+        let no_amount = EscrowConfig { topup_amount: 0, ..opted_in() };
+        assert!(monitor.update(vec![status(100, 2)], &no_amount).topups.is_empty());
+    }
+
+    #[test]
+    fn topups_fire_once_below_threshold() {
+        let config = opted_in();
+        let mut monitor = EscrowMonitor::default();
+
+        // In the grace period but above the top-up threshold
+        let outcome = monitor.update(vec![status(100, 12)], &config);
+        assert_eq!(outcome.newly_at_risk, vec![100]);
+        assert!(outcome.topups.is_empty());
+
+        assert_eq!(monitor.update(vec![status(100, 5)], &config).topups, vec![100]);
+        // The deposit hasn't landed yet; don't send another
+        assert!(monitor.update(vec![status(100, 4)], &config).topups.is_empty());
+
+        // Once it has, a later dip tops up again
+        monitor.update(vec![status(100, 30)], &config);
+        assert_eq!(monitor.update(vec![status(100, 5)], &config).topups, vec![100]);
+    }
+}
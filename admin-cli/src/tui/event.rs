@@ -4,6 +4,7 @@ use std::time::Duration;
 use tokio::sync::mpsc;
 
 use crate::tui::api::{BidInfo, FeeAllowanceInfo, LeaseInfo};
+use crate::tui::escrow::EscrowStatus;
 
 /// Application events
 #[derive(Debug, Clone)]
@@ -11,17 +12,35 @@ pub enum AppEvent {
     Key(KeyEvent),
     Tick,
     Quit,
+    /// Terminal gained or lost focus (pauses background refresh)
+    FocusChanged { focused: bool },
     // Async operation results
     WalletGenerated { mnemonic: String, address: String, public_key: String },
     WalletImported { mnemonic: String, address: String, public_key: String },
     BalanceUpdated { amount: String, denom: String },
-    BidsReceived { bids: Vec<BidInfo> },
-    LeasesReceived { leases: Vec<LeaseInfo> },
+    /// `total` is the chain's count, which exceeds the list if it was capped
+    BidsReceived { bids: Vec<BidInfo>, total: u64 },
+    LeasesReceived { leases: Vec<LeaseInfo>, total: u64 },
     TxBroadcast { txhash: String, success: bool, message: String },
     StatusMessage { message: String, is_error: bool },
+    /// A background refresh failed; reported once until one succeeds
+    BackgroundRefreshFailed { message: String },
     LogsReceived { lines: Vec<String> },
+    /// A provider request timed out; `provider` is deprioritized in bids
+    ProviderUnresponsive { provider: String, message: String },
     FeeAllowanceReceived { allowances: Vec<FeeAllowanceInfo> },
     DeploymentCreated { dseq: u64, txhash: String },
+    /// MsgUpdateDeployment landed; `manifest_errors` lists providers that
+    /// rejected the new manifest
+    DeploymentUpdated { dseq: u64, txhash: String, manifest_errors: Vec<String> },
+    /// Unsigned tx written for offline signing; `qr` is None when too large
+    UnsignedTxExported { path: String, dseq: u64, qr: Option<Vec<String>> },
+    /// A provisioning run from the Discord setup screen finished
+    ProvisionFinished { dry_run: bool, result: Result<String, String> },
+    /// Escrow balances of the wallet's active deployments
+    EscrowScanned { result: Result<Vec<EscrowStatus>, String> },
+    /// MsgDepositDeployment landed; `automatic` for opted-in top-ups
+    EscrowDeposited { dseq: u64, txhash: String, automatic: bool },
 }
 
 /// Event handler for the TUI
@@ -60,6 +79,12 @@ impl EventHandler {
                                 }
                             }
                             Some(Ok(CrosstermEvent::Resize(_, _))) => {}
+                            Some(Ok(focus @ (CrosstermEvent::FocusGained | CrosstermEvent::FocusLost))) => {
+                                let focused = matches!(focus, CrosstermEvent::FocusGained);
+                                if tx_clone.send(AppEvent::FocusChanged { focused }).is_err() {
+                                    break;
+                                }
+                            }
                             Some(Err(_)) => {
                                 if tx_clone.send(AppEvent::Quit).is_err() {
                                     break;
@@ -1,5 +1,6 @@
 use anyhow::Result;
 use crossterm::{
+    event::{DisableFocusChange, EnableFocusChange},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
@@ -7,12 +8,15 @@ use ratatui::prelude::*;
 use std::io;
 
 mod app;
+mod escrow;
 mod event;
 pub mod gpu;
 mod input;
+mod refresh;
 mod screens;
 pub mod sdl;
 mod theme;
+pub mod tx;
 mod ui;
 pub mod widgets;
 
@@ -23,22 +27,26 @@ pub mod wallet;
 pub use app::App;
 pub use event::EventHandler;
 
-/// Run the TUI application
-pub async fn run_tui() -> Result<()> {
+/// Run the TUI application; `read_only` watches the configured wallet
+/// address without being able to sign or broadcast.
+pub async fn run_tui(read_only: bool) -> Result<()> {
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen)?;
+    execute!(stdout, EnterAlternateScreen, EnableFocusChange)?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
     // Create app and run it
     let mut app = App::new();
+    if read_only {
+        app = app.into_read_only();
+    }
     let res = run_app(&mut terminal, &mut app).await;
 
     // Restore terminal
     disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen, DisableFocusChange)?;
     terminal.show_cursor()?;
 
     if let Err(err) = res {
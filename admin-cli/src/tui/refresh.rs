@@ -0,0 +1,163 @@
+//! Background refresh scheduling for chain state shown in the TUI.
+//!
+//! Driven from the app's tick event: polls only while the terminal is focused
+//! and the user has been active recently, so an idle TUI does not hammer RPC nodes.
+//! Escrow scans run unattended instead: they exist to warn an absent user.
+
+use std::time::{Duration, Instant};
+
+/// Stop polling after this long without a key press.
+pub const IDLE_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Lower bound on the poll interval to keep public RPC nodes happy.
+pub const MIN_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Decides when the next background refresh should run.
+#[derive(Debug, Clone)]
+pub struct AutoRefresh {
+    interval: Option<Duration>,
+    last_run: Instant,
+    last_activity: Instant,
+    focused: bool,
+    /// Keep polling while idle or unfocused
+    unattended: bool,
+    /// The last background refresh failed
+    failing: bool,
+}
+
+impl AutoRefresh {
+    /// `interval_secs == 0` disables background refresh.
+    pub fn new(interval_secs: u64) -> Self {
+        let now = Instant::now();
+        Self {
+            interval: (interval_secs > 0)
+                .then(|| Duration::from_secs(interval_secs).max(MIN_INTERVAL)),
+            last_run: now,
+            last_activity: now,
+            focused: true,
+            unattended: false,
+            failing: false,
+        }
+    }
+
+    /// Like `new`, but keeps polling while the user is away; for checks
+    /// whose point is to catch problems nobody is watching for.
+    pub fn unattended(interval_secs: u64) -> Self {
+        Self {
+            unattended: true,
+            ..Self::new(interval_secs)
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.interval.is_some()
+    }
+
+    /// Record user input; resumes polling after an idle pause.
+    pub fn record_activity(&mut self, now: Instant) {
+        self.last_activity = now;
+    }
+
+    /// Track terminal focus; polling pauses while unfocused.
+    pub fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    pub fn is_paused(&self, now: Instant) -> bool {
+        if self.unattended {
+            return false;
+        }
+        !self.focused || now.duration_since(self.last_activity) >= IDLE_TIMEOUT
+    }
+
+    /// Returns true (and restarts the interval) when a refresh should run now.
+    pub fn poll(&mut self, now: Instant) -> bool {
+        let Some(interval) = self.interval else {
+            return false;
+        };
+        if self.is_paused(now) || now.duration_since(self.last_run) < interval {
+            return false;
+        }
+        self.last_run = now;
+        true
+    }
+
+    /// Record a successful refresh, re-arming failure reporting.
+    pub fn record_success(&mut self) {
+        self.failing = false;
+    }
+
+    /// Record a failed background refresh. Returns true only for the first
+    /// failure since the last success, so a down node is reported once
+    /// rather than on every tick.
+    pub fn record_failure(&mut self) -> bool {
+        !std::mem::replace(&mut self.failing, true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disabled_never_fires() {
+        let mut refresh = AutoRefresh::new(0);
+        assert!(!refresh.is_enabled());
+        assert!(!refresh.poll(Instant::now() + Duration::from_secs(3600)));
+    }
+
+    #[test]
+    fn fires_once_per_interval() {
+        let mut refresh = AutoRefresh::new(30);
+        let start = Instant::now();
+        refresh.record_activity(start);
+        assert!(!refresh.poll(start + Duration::from_secs(10)));
+        assert!(refresh.poll(start + Duration::from_secs(31)));
+        assert!(!refresh.poll(start + Duration::from_secs(40)));
+        assert!(refresh.poll(start + Duration::from_secs(62)));
+    }
+
+    #[test]
+    fn interval_is_clamped() {
+        let mut refresh = AutoRefresh::new(1);
+        let start = Instant::now();
+        assert!(!refresh.poll(start + Duration::from_secs(2)));
+        assert!(refresh.poll(start + MIN_INTERVAL + Duration::from_millis(1)));
+    }
+
+    #[test]
+    fn pauses_when_idle_or_unfocused() {
+        let mut refresh = AutoRefresh::new(30);
+        let start = Instant::now();
+        refresh.record_activity(start);
+
+        let idle = start + IDLE_TIMEOUT + Duration::from_secs(1);
+        assert!(!refresh.poll(idle));
+        refresh.record_activity(idle);
+        assert!(refresh.poll(idle));
+
+        refresh.set_focused(false);
+        assert!(!refresh.poll(idle + Duration::from_secs(60)));
+        refresh.set_focused(true);
+        assert!(refresh.poll(idle + Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn failures_reported_once_until_success() {
+        let mut refresh = AutoRefresh::new(30);
+        assert!(refresh.record_failure());
+        assert!(!refresh.record_failure());
+        refresh.record_success();
+        assert!(refresh.record_failure());
+    }
+
+    #[test]
+    fn unattended_ignores_idle_and_focus() {
+        let mut refresh = AutoRefresh::unattended(300);
+        let start = Instant::now();
+        refresh.set_focused(false);
+        let later = start + IDLE_TIMEOUT + Duration::from_secs(1);
+        assert!(!refresh.is_paused(later));
+        assert!(refresh.poll(later));
+    }
+}
@@ -49,16 +49,24 @@ pub fn render(frame: &mut Frame, theme: &AkashTheme, app: &App, area: Rect) {
                 bid.provider.clone()
             };
             let price = format!("{} {}", bid.price_amount, bid.price_denom);
+            let unresponsive = app.bids_state.unresponsive_providers.contains(&bid.provider);
             let style = if i == app.bids_state.selected_index {
                 Style::default().fg(theme.primary).bold()
+            } else if unresponsive {
+                theme.text_dim_style()
             } else {
                 theme.text_primary_style()
             };
+            let state = if unresponsive {
+                format!("{} (unresponsive)", bid.state)
+            } else {
+                bid.state.clone()
+            };
             let marker = if i == app.bids_state.selected_index { ">" } else { " " };
             Row::new(vec![
                 Cell::from(format!("{} {}", marker, provider_short)),
                 Cell::from(price),
-                Cell::from(bid.state.clone()),
+                Cell::from(state),
             ])
             .style(style)
         }).collect();
@@ -79,7 +87,10 @@ pub fn render(frame: &mut Frame, theme: &AkashTheme, app: &App, area: Rect) {
         .block(
             Block::default()
                 .title(Span::styled(
-                    format!(" Available Bids ({}) ", app.bids_state.bids.len()),
+                    format!(
+                        " Available Bids ({}) ",
+                        super::count_label(app.bids_state.bids.len(), app.bids_state.total)
+                    ),
                     theme.primary_style(),
                 ))
                 .borders(Borders::ALL)
@@ -263,7 +263,11 @@ pub fn render(frame: &mut Frame, theme: &AkashTheme, app: &App, area: Rect) {
     let dseq_text = app.deployment_state.dseq
         .map(|d| format!(" | DSeq: {}", d))
         .unwrap_or_default();
-    let status_line = Paragraph::new(format!("{}{}{}", app.deployment_state.status, dseq_text, gpu_text))
+    let deposit_text = format!(" | Deposit: {}", app.deployment_state.deposit_denom.label());
+    let status_line = Paragraph::new(format!(
+        "{}{}{}{}",
+        app.deployment_state.status, dseq_text, deposit_text, gpu_text
+    ))
         .style(status_style)
         .alignment(Alignment::Center)
         .block(Block::default().borders(Borders::ALL).border_style(theme.primary_style()));
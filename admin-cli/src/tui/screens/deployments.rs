@@ -1,6 +1,7 @@
 use ratatui::prelude::*;
 use ratatui::widgets::*;
 use crate::tui::app::{App, DeploymentStatus};
+use crate::tui::escrow::{self, EscrowHealth};
 use crate::tui::theme::AkashTheme;
 
 pub fn render(frame: &mut Frame, theme: &AkashTheme, app: &App, area: Rect) {
@@ -66,11 +67,21 @@ pub fn render(frame: &mut Frame, theme: &AkashTheme, app: &App, area: Rect) {
             theme.text_primary_style()
         };
 
-        list_lines.push(Line::from(vec![
+        let mut spans = vec![
             Span::styled(format!("{} ", marker), name_style),
             Span::styled(&dep.name, name_style),
             Span::styled(format!(" [{}]", dep.status.as_str()), status_style),
-        ]));
+        ];
+        match app.deployments_state.escrow.health(dep.dseq, &app.config.escrow) {
+            Some(EscrowHealth::Low) => {
+                spans.push(Span::styled(" ⚠ LOW ESCROW", Style::default().fg(theme.warning).bold()));
+            }
+            Some(EscrowHealth::Depleted) => {
+                spans.push(Span::styled(" ⚠ OUT OF ESCROW", Style::default().fg(theme.error).bold()));
+            }
+            _ => {}
+        }
+        list_lines.push(Line::from(spans));
         list_lines.push(Line::from(Span::styled(
             format!("  DSeq: {} | {}", dep.dseq, dep.created_at),
             theme.text_dim_style(),
@@ -111,6 +122,39 @@ pub fn render(frame: &mut Frame, theme: &AkashTheme, app: &App, area: Rect) {
             Span::styled(&dep.created_at, theme.text_primary_style()),
         ]));
 
+        if let Some(status) = app.deployments_state.escrow.statuses.get(&dep.dseq) {
+            let runway_style = match status.health(app.config.escrow.warn_hours) {
+                EscrowHealth::Healthy => Style::default().fg(theme.success),
+                EscrowHealth::Low => Style::default().fg(theme.warning),
+                EscrowHealth::Depleted => Style::default().fg(theme.error),
+            };
+            let runway = status
+                .hours_left()
+                .map(|hours| format!("~{}", escrow::format_runway(hours)))
+                .unwrap_or_else(|| "no active lease".to_string());
+            detail_lines.push(Line::from(vec![
+                Span::styled("Escrow: ", theme.text_dim_style()),
+                Span::styled(
+                    app.format_escrow_amount(status.balance, &status.denom),
+                    theme.text_primary_style(),
+                ),
+            ]));
+            detail_lines.push(Line::from(vec![
+                Span::styled("Runway: ", theme.text_dim_style()),
+                Span::styled(runway, runway_style),
+            ]));
+            if app.config.escrow.auto_topup {
+                detail_lines.push(Line::from(Span::styled(
+                    format!(
+                        "  Auto top-up: {} below {}h",
+                        app.format_escrow_amount(app.config.escrow.topup_amount, &status.denom),
+                        app.config.escrow.topup_below_hours
+                    ),
+                    theme.text_dim_style(),
+                )));
+            }
+        }
+
         if !dep.services.is_empty() {
             detail_lines.push(Line::from(""));
             detail_lines.push(Line::from(Span::styled("Services", theme.text_primary_style().bold())));
@@ -18,7 +18,7 @@ const GUIDE_STEPS: &[(&str, &str)] = &[
     ("Set Bot URL",
      "Enter the service URI from your active lease.\nPress 'u' to auto-populate from lease.\nOr enter manually from Leases screen."),
     ("Test & Submit",
-     "Press 't' to test the endpoint health.\nPress Enter to save configuration.\nThe bot will connect via the provision API."),
+     "Set Admin Key to your admin key file.\nPress 't' for a dry run (nothing is sent).\nPress 'P' to send the secrets to the bot."),
 ];
 
 pub fn render(frame: &mut Frame, theme: &AkashTheme, app: &App, area: Rect) {
@@ -33,7 +33,7 @@ pub fn render(frame: &mut Frame, theme: &AkashTheme, app: &App, area: Rect) {
     // Title
     let mode_hint = match app.input_mode {
         InputMode::Insert => " [INSERT - Tab: next, Enter: submit, Esc: cancel]",
-        _ => " [i: edit, j/k: field, x: clear, u: URL, t: test]",
+        _ => " [i: edit, j/k: field, x: clear, u: URL, t: dry run, P: provision]",
     };
     let title = Paragraph::new(format!("Discord Bot Setup{}", mode_hint))
         .style(theme.primary_style().bold())
@@ -55,7 +55,7 @@ pub fn render(frame: &mut Frame, theme: &AkashTheme, app: &App, area: Rect) {
         .direction(Direction::Vertical)
         .constraints([
             Constraint::Min(1),
-            Constraint::Length(10), // 3 fields * 2 lines + borders + padding
+            Constraint::Length(12), // 4 fields * 2 lines + borders + padding
         ])
         .split(content_layout[0]);
 
@@ -179,6 +179,7 @@ pub fn render(frame: &mut Frame, theme: &AkashTheme, app: &App, area: Rect) {
     // Status panel
     let status_style = match app.discord_state.deploy_status.as_str() {
         "Not configured" => theme.text_dim_style(),
+        "Provisioned" | "Dry run ok" => Style::default().fg(theme.success),
         s if s.contains("Configured") || s.contains("healthy") => Style::default().fg(theme.success),
         s if s.contains("failed") || s.contains("error") || s.contains("Incomplete") => Style::default().fg(theme.warning),
         _ => Style::default().fg(theme.info),
@@ -3,9 +3,6 @@ use ratatui::widgets::*;
 use crate::tui::app::App;
 use crate::tui::theme::AkashTheme;
 
-/// Minimum balance threshold for display
-const MIN_DEPLOY_BALANCE_UAKT: u64 = 5_000_000;
-
 pub fn render(frame: &mut Frame, theme: &AkashTheme, app: &App, area: Rect) {
     let layout = Layout::default()
         .direction(Direction::Vertical)
@@ -26,7 +23,8 @@ pub fn render(frame: &mut Frame, theme: &AkashTheme, app: &App, area: Rect) {
     // Status banner (conditional)
     let balance_uakt = app.fee_grant_state.balance_uakt;
     let has_grant = app.fee_grant_state.has_fee_grant;
-    let (banner_text, banner_style) = if balance_uakt >= MIN_DEPLOY_BALANCE_UAKT {
+    let required_uakt = app.required_deposit_uakt();
+    let (banner_text, banner_style) = if balance_uakt >= required_uakt {
         ("✓ Sufficient balance for deployment — fee grant optional", Style::default().fg(theme.success))
     } else if has_grant {
         ("✓ Fee grant active — deployment fees covered by granter", Style::default().fg(theme.success))
@@ -42,7 +40,7 @@ pub fn render(frame: &mut Frame, theme: &AkashTheme, app: &App, area: Rect) {
         .style(banner_style)
         .alignment(Alignment::Center)
         .block(Block::default().borders(Borders::ALL).border_style(
-            if balance_uakt >= MIN_DEPLOY_BALANCE_UAKT || has_grant {
+            if balance_uakt >= required_uakt || has_grant {
                 Style::default().fg(theme.success)
             } else {
                 Style::default().fg(theme.warning)
@@ -70,7 +68,7 @@ pub fn render(frame: &mut Frame, theme: &AkashTheme, app: &App, area: Rect) {
         Line::from(Span::styled("     Query active grants for your address", theme.text_dim_style())),
         Line::from(""),
         Line::from(Span::styled("  b  Refresh wallet balance", theme.text_primary_style())),
-        Line::from(Span::styled("     Check current uAKT balance on-chain", theme.text_dim_style())),
+        Line::from(Span::styled("     Check current balance on-chain", theme.text_dim_style())),
     ];
 
     if app.fee_grant_state.loading {
@@ -108,7 +106,7 @@ pub fn render(frame: &mut Frame, theme: &AkashTheme, app: &App, area: Rect) {
         app.fee_grant_state.balance.as_deref().unwrap_or("Not checked").to_string()
     };
 
-    let balance_style = if balance_uakt >= MIN_DEPLOY_BALANCE_UAKT {
+    let balance_style = if balance_uakt >= required_uakt {
         Style::default().fg(theme.success)
     } else if balance_uakt > 0 {
         Style::default().fg(theme.warning)
@@ -144,7 +142,10 @@ pub fn render(frame: &mut Frame, theme: &AkashTheme, app: &App, area: Rect) {
             if let Some(ref limit) = grant.spend_limit {
                 right_lines.push(Line::from(vec![
                     Span::styled("  Limit: ", theme.text_dim_style()),
-                    Span::styled(format!("{} {}", limit.amount, limit.denom), theme.text_primary_style()),
+                    Span::styled(
+                        app.config.network.format_coin(&limit.amount, &limit.denom),
+                        theme.text_primary_style(),
+                    ),
                 ]));
             } else {
                 right_lines.push(Line::from(vec![
@@ -171,7 +172,7 @@ pub fn render(frame: &mut Frame, theme: &AkashTheme, app: &App, area: Rect) {
     right_lines.push(Line::from(""));
     right_lines.push(Line::from(Span::styled("Deploy Readiness", theme.text_primary_style().bold())));
     right_lines.push(Line::from(""));
-    if balance_uakt >= MIN_DEPLOY_BALANCE_UAKT || has_grant {
+    if balance_uakt >= required_uakt || has_grant {
         right_lines.push(Line::from(Span::styled("  ✓ Ready to deploy", Style::default().fg(theme.success))));
     } else {
         right_lines.push(Line::from(Span::styled("  ✗ Need fee grant or balance", Style::default().fg(theme.error))));
@@ -80,7 +80,10 @@ pub fn render(frame: &mut Frame, theme: &AkashTheme, app: &App, area: Rect) {
         .block(
             Block::default()
                 .title(Span::styled(
-                    format!(" Active Leases ({}) ", app.leases_state.leases.len()),
+                    format!(
+                        " Active Leases ({}) ",
+                        super::count_label(app.leases_state.leases.len(), app.leases_state.total)
+                    ),
                     theme.primary_style(),
                 ))
                 .borders(Borders::ALL)
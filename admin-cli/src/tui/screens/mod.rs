@@ -42,3 +42,12 @@ pub fn render_discord_config(frame: &mut Frame, theme: &AkashTheme, app: &App, a
 pub fn render_deployments(frame: &mut Frame, theme: &AkashTheme, app: &App, area: Rect) {
     deployments::render(frame, theme, app, area);
 }
+
+/// "12", or "2000 of 2350" when a list query hit its page cap.
+fn count_label(shown: usize, total: u64) -> String {
+    if (shown as u64) < total {
+        format!("{} of {}", shown, total)
+    } else {
+        shown.to_string()
+    }
+}
@@ -65,6 +65,18 @@ pub fn render(frame: &mut Frame, theme: &AkashTheme, app: &App, area: Rect) {
             "  r  Refresh on-chain balance",
             theme.text_primary_style(),
         )),
+        Line::from(""),
+        Line::from(Span::styled("Providers", theme.text_primary_style().bold())),
+        Line::from(Span::styled(
+            "  p  Publish provider certificate (logs, manifests)",
+            theme.text_primary_style(),
+        )),
+        Line::from(""),
+        Line::from(Span::styled("Air-gapped", theme.text_primary_style().bold())),
+        Line::from(Span::styled(
+            "  o  Sign exported transaction",
+            theme.text_primary_style(),
+        )),
     ];
 
     if app.wallet_state.loading {
@@ -1,4 +1,13 @@
+use linguabridge_types::akash::base::v1beta3::{
+    endpoint, Attribute, Cpu, Endpoint, Gpu, Memory, PlacementRequirements, ResourceValue,
+    Resources, Storage,
+};
+use linguabridge_types::akash::deployment::v1beta3::{GroupSpec, ResourceUnit};
+use linguabridge_types::akash::manifest::v2beta2 as manifest;
+use linguabridge_types::cosmos::base::v1beta1::DecCoin;
+use serde_json::{json, Value as JsonValue};
 use serde_yaml::Value;
+use sha2::{Digest, Sha256};
 use std::collections::BTreeMap;
 use std::path::Path;
 
@@ -147,6 +156,17 @@ impl SdlFile {
         self.variables.iter().filter(|v| v.value.is_empty()).collect()
     }
 
+    /// The bot's `admin.path_prefix`, when a service's env sets
+    /// `LINGUABRIDGE_ADMIN__PATH_PREFIX`
+    pub fn admin_path_prefix(&self) -> Option<String> {
+        self.services
+            .iter()
+            .flat_map(|svc| &svc.env_vars)
+            .find(|env| env.key == "LINGUABRIDGE_ADMIN__PATH_PREFIX")
+            .map(|env| env.value.trim().to_string())
+            .filter(|prefix| !prefix.is_empty())
+    }
+
     /// Regenerate the YAML with variable substitutions applied.
     pub fn render_yaml(&self) -> String {
         let mut output = self.raw.clone();
@@ -182,6 +202,258 @@ impl SdlFile {
         output
     }
 
+    /// Deployment version sent on chain: SHA-256 of the manifest's canonical
+    /// JSON, the same bytes Akash's `Manifest.Version()` hashes, so providers
+    /// accept the manifest and only changes they see bump the version.
+    pub fn version(&self) -> Result<Vec<u8>, String> {
+        let canonical = canonical_json(&self.manifest_json()?);
+        Ok(Sha256::digest(canonical.as_bytes()).to_vec())
+    }
+
+    /// The manifest as providers parse it: Akash's JSON field names
+    /// (`externalPort`, `httpOptions`, `size`) and decimal-string quantities.
+    pub fn manifest_json(&self) -> Result<JsonValue, String> {
+        Ok(JsonValue::Array(self.manifest()?.iter().map(group_json).collect()))
+    }
+
+    fn rendered_doc(&self) -> Result<Value, String> {
+        serde_yaml::from_str(&self.render_yaml()).map_err(|e| format!("YAML parse error: {}", e))
+    }
+
+    /// Build the on-chain group specs from the rendered SDL: one group per
+    /// placement, one resource unit per service deployed to it.
+    pub fn group_specs(&self) -> Result<Vec<GroupSpec>, String> {
+        let doc = self.rendered_doc()?;
+        let mut groups: Vec<GroupSpec> = Vec::new();
+        for entry in deployment_entries(&doc)? {
+            let index = match groups.iter().position(|g| g.name == entry.placement_name) {
+                Some(i) => i,
+                None => {
+                    groups.push(GroupSpec {
+                        name: entry.placement_name.to_string(),
+                        requirements: Some(PlacementRequirements {
+                            signed_by: None,
+                            attributes: flatten_attributes(entry.placement.get("attributes")),
+                        }),
+                        resources: Vec::new(),
+                    });
+                    groups.len() - 1
+                }
+            };
+            let group = &mut groups[index];
+            let id = group.resources.len() as u32 + 1;
+            group.resources.push(ResourceUnit {
+                resource: Some(Self::resource_spec(id, entry.resources, entry.service)?),
+                count: entry.count,
+                price: Some(dec_coin(entry.price)?),
+            });
+        }
+
+        Ok(groups)
+    }
+
+    /// Build the manifest sent to the provider: the same groups as
+    /// `group_specs`, plus the images, env, ports and mounts the chain never sees.
+    pub fn manifest(&self) -> Result<Vec<manifest::Group>, String> {
+        let doc = self.rendered_doc()?;
+        let mut groups: Vec<manifest::Group> = Vec::new();
+        for entry in deployment_entries(&doc)? {
+            let index = match groups.iter().position(|g| g.name == entry.placement_name) {
+                Some(i) => i,
+                None => {
+                    groups.push(manifest::Group {
+                        name: entry.placement_name.to_string(),
+                        services: Vec::new(),
+                    });
+                    groups.len() - 1
+                }
+            };
+            let group = &mut groups[index];
+            let id = group.services.len() as u32 + 1;
+            let svc = entry.service;
+
+            let storage_params: Vec<manifest::StorageParams> = svc
+                .get("params")
+                .and_then(|p| p.get("storage"))
+                .and_then(|s| s.as_mapping())
+                .map(|volumes| {
+                    volumes
+                        .iter()
+                        .map(|(name, volume)| manifest::StorageParams {
+                            name: name.as_str().unwrap_or_default().to_string(),
+                            mount: volume.get("mount").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+                            read_only: volume.get("readOnly").and_then(|v| v.as_bool()).unwrap_or(false),
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            group.services.push(manifest::Service {
+                name: entry.service_name.to_string(),
+                image: svc.get("image").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+                command: string_list(svc.get("command")),
+                args: string_list(svc.get("args")),
+                env: string_list(svc.get("env")),
+                resources: Some(Self::resource_spec(id, entry.resources, svc)?),
+                count: entry.count,
+                expose: manifest_exposes(svc),
+                params: (!storage_params.is_empty())
+                    .then_some(manifest::ServiceParams { storage: storage_params }),
+                credentials: svc.get("credentials").map(|c| manifest::ServiceImageCredentials {
+                    host: c.get("host").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+                    email: c.get("email").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+                    username: c.get("username").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+                    password: c.get("password").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+                }),
+            });
+        }
+
+        Ok(groups)
+    }
+
+    /// Check that this SDL can replace `deployed` through MsgUpdateDeployment.
+    /// Akash keeps the on-chain groups fixed, so only what lives in the
+    /// manifest (images, env, commands, ports) may change.
+    pub fn check_update_compatible(&self, deployed: &SdlFile) -> Result<(), Vec<IncompatibleUpdate>> {
+        let specs = |sdl: &SdlFile| {
+            sdl.group_specs()
+                .map_err(|e| vec![IncompatibleUpdate::Invalid(e)])
+        };
+        let before = specs(deployed)?;
+        let after = specs(self)?;
+
+        let mut problems = Vec::new();
+        for old in &before {
+            if !after.iter().any(|g| g.name == old.name) {
+                problems.push(IncompatibleUpdate::GroupRemoved(old.name.clone()));
+            }
+        }
+        for new in &after {
+            let old = match before.iter().find(|g| g.name == new.name) {
+                Some(old) => old,
+                None => {
+                    problems.push(IncompatibleUpdate::GroupAdded(new.name.clone()));
+                    continue;
+                }
+            };
+            if old.requirements != new.requirements {
+                problems.push(IncompatibleUpdate::PlacementChanged(new.name.clone()));
+            }
+            if old.resources.len() != new.resources.len() {
+                problems.push(IncompatibleUpdate::ServiceCountChanged {
+                    group: new.name.clone(),
+                    before: old.resources.len(),
+                    after: new.resources.len(),
+                });
+                continue;
+            }
+            for (old_unit, new_unit) in old.resources.iter().zip(&new.resources) {
+                let (a, b) = (old_unit.resource.as_ref(), new_unit.resource.as_ref());
+                let id = b.map(|r| r.id).unwrap_or_default();
+                let changed = [
+                    ("cpu", a.map(|r| &r.cpu) != b.map(|r| &r.cpu)),
+                    ("memory", a.map(|r| &r.memory) != b.map(|r| &r.memory)),
+                    ("storage", a.map(|r| &r.storage) != b.map(|r| &r.storage)),
+                    ("gpu", a.map(|r| &r.gpu) != b.map(|r| &r.gpu)),
+                    ("endpoints", a.map(|r| &r.endpoints) != b.map(|r| &r.endpoints)),
+                ];
+                for (resource, _) in changed.iter().filter(|(_, differs)| *differs) {
+                    problems.push(IncompatibleUpdate::ResourcesChanged {
+                        group: new.name.clone(),
+                        id,
+                        resource,
+                    });
+                }
+                if old_unit.count != new_unit.count {
+                    problems.push(IncompatibleUpdate::ReplicasChanged {
+                        group: new.name.clone(),
+                        id,
+                        before: old_unit.count,
+                        after: new_unit.count,
+                    });
+                }
+                if old_unit.price != new_unit.price {
+                    problems.push(IncompatibleUpdate::PriceChanged { group: new.name.clone(), id });
+                }
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems)
+        }
+    }
+
+    fn resource_spec(id: u32, res: &Value, service: &Value) -> Result<Resources, String> {
+        let cpu_units = res.get("cpu")
+            .and_then(|v| v.get("units"))
+            .map(parse_cpu_millis)
+            .transpose()?
+            .unwrap_or(1000);
+        let memory = res.get("memory")
+            .and_then(|v| v.get("size"))
+            .and_then(|v| v.as_str())
+            .map(parse_size)
+            .transpose()?
+            .unwrap_or(512 * 1024 * 1024);
+
+        let storage_entries: Vec<&Value> = match res.get("storage") {
+            Some(Value::Sequence(seq)) => seq.iter().collect(),
+            Some(v) => vec![v],
+            None => Vec::new(),
+        };
+        let mut storage = Vec::new();
+        for entry in storage_entries {
+            let size = entry.get("size")
+                .and_then(|v| v.as_str())
+                .ok_or("storage entry has no size")?;
+            storage.push(Storage {
+                name: entry.get("name").and_then(|v| v.as_str()).unwrap_or("default").to_string(),
+                quantity: Some(resource_value(parse_size(size)?)),
+                attributes: flatten_attributes(entry.get("attributes")),
+            });
+        }
+
+        let gpu_units = res.get("gpu")
+            .and_then(|v| v.get("units"))
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0);
+        let gpu_attributes = res.get("gpu")
+            .and_then(|v| v.get("attributes"))
+            .map(gpu_model_attributes)
+            .unwrap_or_default();
+
+        // Every globally exposed port becomes a shared HTTP endpoint
+        let endpoints = service
+            .get("expose")
+            .and_then(|v| v.as_sequence())
+            .map(|exposes| {
+                exposes
+                    .iter()
+                    .filter(|e| {
+                        e.get("to").and_then(|t| t.as_sequence()).is_some_and(|to| {
+                            to.iter().any(|t| t.get("global").and_then(|g| g.as_bool()) == Some(true))
+                        })
+                    })
+                    .map(|_| Endpoint {
+                        kind: endpoint::Kind::SharedHttp as i32,
+                        sequence_number: 0,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(Resources {
+            id,
+            cpu: Some(Cpu { units: Some(resource_value(cpu_units)), attributes: Vec::new() }),
+            memory: Some(Memory { quantity: Some(resource_value(memory)), attributes: Vec::new() }),
+            storage,
+            gpu: Some(Gpu { units: Some(resource_value(gpu_units)), attributes: gpu_attributes }),
+            endpoints,
+        })
+    }
+
     fn extract_services(doc: &Value) -> Result<Vec<SdlService>, String> {
         let mut services = Vec::new();
 
@@ -279,6 +551,449 @@ impl SdlFile {
     }
 }
 
+/// Why an edited SDL cannot be applied to a running deployment in place.
+#[derive(Debug, Clone, PartialEq)]
+pub enum IncompatibleUpdate {
+    /// The SDL no longer converts to group specs at all
+    Invalid(String),
+    GroupAdded(String),
+    GroupRemoved(String),
+    PlacementChanged(String),
+    ServiceCountChanged { group: String, before: usize, after: usize },
+    ResourcesChanged { group: String, id: u32, resource: &'static str },
+    ReplicasChanged { group: String, id: u32, before: u32, after: u32 },
+    PriceChanged { group: String, id: u32 },
+}
+
+impl std::fmt::Display for IncompatibleUpdate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Invalid(e) => write!(f, "SDL is invalid: {}", e),
+            Self::GroupAdded(g) => write!(f, "placement group '{}' was added", g),
+            Self::GroupRemoved(g) => write!(f, "placement group '{}' was removed", g),
+            Self::PlacementChanged(g) => write!(f, "group '{}': placement attributes changed", g),
+            Self::ServiceCountChanged { group, before, after } => write!(
+                f,
+                "group '{}': services changed from {} to {}",
+                group, before, after
+            ),
+            Self::ResourcesChanged { group, id, resource } => write!(
+                f,
+                "group '{}' service #{}: {} changed",
+                group, id, resource
+            ),
+            Self::ReplicasChanged { group, id, before, after } => write!(
+                f,
+                "group '{}' service #{}: count changed from {} to {}",
+                group, id, before, after
+            ),
+            Self::PriceChanged { group, id } => {
+                write!(f, "group '{}' service #{}: pricing changed", group, id)
+            }
+        }
+    }
+}
+
+/// One `deployment.<service>.<placement>` entry with its profiles resolved.
+struct DeploymentEntry<'a> {
+    service_name: &'a str,
+    service: &'a Value,
+    placement_name: &'a str,
+    placement: &'a Value,
+    resources: &'a Value,
+    price: &'a Value,
+    count: u32,
+}
+
+fn deployment_entries(doc: &Value) -> Result<Vec<DeploymentEntry<'_>>, String> {
+    let deployment = doc.get("deployment")
+        .and_then(|v| v.as_mapping())
+        .ok_or("no 'deployment' section in SDL")?;
+    let compute = doc.get("profiles").and_then(|v| v.get("compute"));
+    let placements = doc.get("profiles").and_then(|v| v.get("placement"));
+
+    let mut entries = Vec::new();
+    for (svc_name, svc_deploy) in deployment {
+        let service_name = svc_name.as_str().unwrap_or("unknown");
+        let service = doc.get("services")
+            .and_then(|s| s.get(service_name))
+            .ok_or_else(|| format!("service '{}' is deployed but not defined", service_name))?;
+        let svc_deploy = svc_deploy
+            .as_mapping()
+            .ok_or_else(|| format!("deployment.{} must be a mapping", service_name))?;
+
+        for (placement_name, entry) in svc_deploy {
+            let placement_name = placement_name.as_str().unwrap_or("unknown");
+            let profile = entry.get("profile")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| format!("deployment.{}.{} has no profile", service_name, placement_name))?;
+            let resources = compute
+                .and_then(|c| c.get(profile))
+                .and_then(|p| p.get("resources"))
+                .ok_or_else(|| format!("compute profile '{}' not found", profile))?;
+            let placement = placements
+                .and_then(|p| p.get(placement_name))
+                .ok_or_else(|| format!("placement '{}' not found", placement_name))?;
+            let price = placement.get("pricing")
+                .and_then(|p| p.get(profile))
+                .ok_or_else(|| format!("placement '{}' has no pricing for '{}'", placement_name, profile))?;
+
+            entries.push(DeploymentEntry {
+                service_name,
+                service,
+                placement_name,
+                placement,
+                resources,
+                price,
+                count: entry.get("count").and_then(|v| v.as_u64()).unwrap_or(1) as u32,
+            });
+        }
+    }
+    Ok(entries)
+}
+
+/// A YAML sequence of scalars as strings (env, command, args).
+fn string_list(v: Option<&Value>) -> Vec<String> {
+    v.and_then(|v| v.as_sequence())
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| match item {
+                    Value::String(s) => Some(s.clone()),
+                    Value::Number(n) => Some(n.to_string()),
+                    Value::Bool(b) => Some(b.to_string()),
+                    _ => None,
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// One manifest expose entry per `to` target of each exposed port.
+fn manifest_exposes(svc: &Value) -> Vec<manifest::ServiceExpose> {
+    let mut out = Vec::new();
+    for expose in svc.get("expose").and_then(|v| v.as_sequence()).into_iter().flatten() {
+        let port = expose.get("port").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
+        let external_port = expose.get("as").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
+        let proto = expose.get("proto")
+            .and_then(|v| v.as_str())
+            .unwrap_or("TCP")
+            .to_uppercase();
+        for target in expose.get("to").and_then(|v| v.as_sequence()).into_iter().flatten() {
+            out.push(manifest::ServiceExpose {
+                port,
+                external_port,
+                proto: proto.clone(),
+                service: target.get("service").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+                global: target.get("global").and_then(|v| v.as_bool()).unwrap_or(false),
+                hosts: string_list(expose.get("accept")),
+                http_options: Some(http_options(expose.get("http_options"))),
+                ip: target.get("ip").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+                endpoint_sequence_number: 0,
+            });
+        }
+    }
+    out
+}
+
+/// `value` the way Go's `encoding/json` writes it after Cosmos' `SortJSON`:
+/// compact, keys in byte order, and `<`, `>`, `&`, U+2028/9 escaped.
+fn canonical_json(value: &JsonValue) -> String {
+    let mut out = String::new();
+    write_canonical(value, &mut out);
+    out
+}
+
+fn write_canonical(value: &JsonValue, out: &mut String) {
+    match value {
+        JsonValue::Null => out.push_str("null"),
+        JsonValue::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        JsonValue::Number(n) => out.push_str(&n.to_string()),
+        JsonValue::String(s) => write_go_string(s, out),
+        JsonValue::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        JsonValue::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            out.push('{');
+            for (i, (key, item)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_go_string(key, out);
+                out.push(':');
+                write_canonical(item, out);
+            }
+            out.push('}');
+        }
+    }
+}
+
+fn write_go_string(s: &str, out: &mut String) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '<' | '>' | '&' | '\u{2028}' | '\u{2029}' => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Go marshals an empty slice field as `null`.
+fn json_list<T>(items: &[T], f: impl Fn(&T) -> JsonValue) -> JsonValue {
+    if items.is_empty() {
+        JsonValue::Null
+    } else {
+        JsonValue::Array(items.iter().map(f).collect())
+    }
+}
+
+fn group_json(group: &manifest::Group) -> JsonValue {
+    json!({
+        "name": group.name,
+        "services": json_list(&group.services, service_json),
+    })
+}
+
+fn service_json(svc: &manifest::Service) -> JsonValue {
+    let string = |s: &String| json!(s);
+    json!({
+        "name": svc.name,
+        "image": svc.image,
+        "command": json_list(&svc.command, string),
+        "args": json_list(&svc.args, string),
+        "env": json_list(&svc.env, string),
+        "resources": resources_json(&svc.resources.clone().unwrap_or_default()),
+        "count": svc.count,
+        "expose": json_list(&svc.expose, expose_json),
+        "params": svc.params.as_ref().map(|params| json!({
+            "storage": json_list(&params.storage, |s| json!({
+                "name": s.name,
+                "mount": s.mount,
+                "readOnly": s.read_only,
+            })),
+        })),
+        "credentials": svc.credentials.as_ref().map(|c| json!({
+            "host": c.host,
+            "email": c.email,
+            "username": c.username,
+            "password": c.password,
+        })),
+    })
+}
+
+fn expose_json(expose: &manifest::ServiceExpose) -> JsonValue {
+    let http = expose.http_options.clone().unwrap_or_default();
+    json!({
+        "port": expose.port,
+        "externalPort": expose.external_port,
+        "proto": expose.proto,
+        "service": expose.service,
+        "global": expose.global,
+        "hosts": json_list(&expose.hosts, |h| json!(h)),
+        "httpOptions": {
+            "maxBodySize": http.max_body_size,
+            "readTimeout": http.read_timeout,
+            "sendTimeout": http.send_timeout,
+            "nextTries": http.next_tries,
+            "nextTimeout": http.next_timeout,
+            "nextCases": json_list(&http.next_cases, |c| json!(c)),
+        },
+        "ip": expose.ip,
+        "endpointSequenceNumber": expose.endpoint_sequence_number,
+    })
+}
+
+fn resources_json(resources: &Resources) -> JsonValue {
+    json!({
+        "id": resources.id,
+        "cpu": resources.cpu.as_ref().map(|cpu| with_attributes(
+            json!({ "units": quantity_json(cpu.units.as_ref()) }),
+            &cpu.attributes,
+        )),
+        "memory": resources.memory.as_ref().map(|memory| with_attributes(
+            json!({ "size": quantity_json(memory.quantity.as_ref()) }),
+            &memory.attributes,
+        )),
+        "storage": json_list(&resources.storage, |storage| with_attributes(
+            json!({ "name": storage.name, "size": quantity_json(storage.quantity.as_ref()) }),
+            &storage.attributes,
+        )),
+        "gpu": resources.gpu.as_ref().map(|gpu| with_attributes(
+            json!({ "units": quantity_json(gpu.units.as_ref()) }),
+            &gpu.attributes,
+        )),
+        "endpoints": json_list(&resources.endpoints, |endpoint| {
+            let mut out = json!({ "sequence_number": endpoint.sequence_number });
+            if endpoint.kind != 0 {
+                out["kind"] = json!(endpoint.kind);
+            }
+            out
+        }),
+    })
+}
+
+/// Resource attributes are `omitempty` in the manifest.
+fn with_attributes(mut unit: JsonValue, attributes: &[Attribute]) -> JsonValue {
+    if !attributes.is_empty() {
+        unit["attributes"] = attributes
+            .iter()
+            .map(|a| json!({ "key": a.key, "value": a.value }))
+            .collect();
+    }
+    unit
+}
+
+fn quantity_json(value: Option<&ResourceValue>) -> JsonValue {
+    let val = value.map(|v| String::from_utf8_lossy(&v.val).into_owned());
+    json!({ "val": val.unwrap_or_else(|| "0".to_string()) })
+}
+
+/// An expose's `http_options`, with the defaults Akash's SDL parser fills in.
+fn http_options(v: Option<&Value>) -> manifest::ServiceExposeHttpOptions {
+    let number = |key: &str, default: u32| {
+        v.and_then(|v| v.get(key))
+            .and_then(|n| n.as_u64())
+            .map_or(default, |n| n as u32)
+    };
+    let next_cases = match v.and_then(|v| v.get("next_cases")) {
+        Some(cases) => string_list(Some(cases)),
+        None => vec!["error".to_string(), "timeout".to_string()],
+    };
+    manifest::ServiceExposeHttpOptions {
+        max_body_size: number("max_body_size", 1_048_576),
+        read_timeout: number("read_timeout", 60_000),
+        send_timeout: number("send_timeout", 60_000),
+        next_tries: number("next_tries", 3),
+        next_timeout: number("next_timeout", 0),
+        next_cases,
+    }
+}
+
+/// Resource quantities are encoded on chain as decimal strings.
+fn resource_value(n: u64) -> ResourceValue {
+    ResourceValue { val: n.to_string().into_bytes() }
+}
+
+/// CPU units in millicores: `1`, `0.5` or `500m`.
+fn parse_cpu_millis(v: &Value) -> Result<u64, String> {
+    if let Some(n) = v.as_u64() {
+        return Ok(n * 1000);
+    }
+    if let Some(f) = v.as_f64() {
+        return Ok((f * 1000.0).round() as u64);
+    }
+    let s = v.as_str().ok_or("cpu units must be a number")?;
+    match s.strip_suffix('m') {
+        Some(millis) => millis.parse().map_err(|_| format!("invalid cpu units: {}", s)),
+        None => s
+            .parse::<f64>()
+            .map(|f| (f * 1000.0).round() as u64)
+            .map_err(|_| format!("invalid cpu units: {}", s)),
+    }
+}
+
+/// Parse a Kubernetes-style size (`512Mi`, `50Gi`, `1G`) into bytes.
+pub fn parse_size(s: &str) -> Result<u64, String> {
+    const UNITS: &[(&str, u64)] = &[
+        ("Ki", 1 << 10),
+        ("Mi", 1 << 20),
+        ("Gi", 1 << 30),
+        ("Ti", 1 << 40),
+        ("k", 1_000),
+        ("K", 1_000),
+        ("M", 1_000_000),
+        ("G", 1_000_000_000),
+        ("T", 1_000_000_000_000),
+    ];
+    let s = s.trim();
+    let (number, multiplier) = UNITS
+        .iter()
+        .find_map(|(suffix, mult)| s.strip_suffix(suffix).map(|n| (n, *mult)))
+        .unwrap_or((s, 1));
+    number
+        .trim()
+        .parse::<u64>()
+        .map(|n| n * multiplier)
+        .map_err(|_| format!("invalid size: {}", s))
+}
+
+/// Pricing entry (`denom`, `amount`) as a DecCoin with 18-digit fixed precision.
+fn dec_coin(price: &Value) -> Result<DecCoin, String> {
+    let denom = price.get("denom").and_then(|v| v.as_str()).unwrap_or("uakt");
+    let amount = match price.get("amount") {
+        Some(Value::Number(n)) => n.to_string(),
+        Some(Value::String(s)) => s.clone(),
+        _ => return Err("pricing entry has no amount".to_string()),
+    };
+    let (whole, frac) = amount.split_once('.').unwrap_or((&amount, ""));
+    if whole.is_empty()
+        || frac.len() > 18
+        || !whole.chars().chain(frac.chars()).all(|c| c.is_ascii_digit())
+    {
+        return Err(format!("invalid price amount: {}", amount));
+    }
+    let scaled = format!("{}{:0<18}", whole, frac);
+    let trimmed = scaled.trim_start_matches('0');
+    Ok(DecCoin {
+        denom: denom.to_string(),
+        amount: if trimmed.is_empty() { "0".to_string() } else { trimmed.to_string() },
+    })
+}
+
+/// Flatten a nested attribute mapping into `key: value` pairs, joining nested
+/// keys with `/` (e.g. `persistent: true`, `class: beta3`).
+fn flatten_attributes(attrs: Option<&Value>) -> Vec<Attribute> {
+    let mut out = Vec::new();
+    if let Some(map) = attrs.and_then(|v| v.as_mapping()) {
+        for (key, value) in map {
+            let key = key.as_str().unwrap_or_default().to_string();
+            let value = match value {
+                Value::String(s) => s.clone(),
+                Value::Bool(b) => b.to_string(),
+                Value::Number(n) => n.to_string(),
+                _ => continue,
+            };
+            out.push(Attribute { key, value });
+        }
+    }
+    out
+}
+
+/// GPU attributes in Akash's `vendor/<vendor>/model/<model>` form.
+fn gpu_model_attributes(attrs: &Value) -> Vec<Attribute> {
+    let mut out = Vec::new();
+    if let Some(vendors) = attrs.get("vendor").and_then(|v| v.as_mapping()) {
+        for (vendor, models) in vendors {
+            let vendor = vendor.as_str().unwrap_or_default();
+            for model in models.as_sequence().into_iter().flatten() {
+                if let Some(name) = model.get("model").and_then(|m| m.as_str()) {
+                    out.push(Attribute {
+                        key: format!("vendor/{}/model/{}", vendor, name),
+                        value: "true".to_string(),
+                    });
+                }
+            }
+        }
+    }
+    out
+}
+
 impl Default for ServiceResources {
     fn default() -> Self {
         Self {
@@ -289,3 +1004,163 @@ impl Default for ServiceResources {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_size_handles_binary_and_decimal_units() {
+        assert_eq!(parse_size("512Mi").unwrap(), 512 * 1024 * 1024);
+        assert_eq!(parse_size("1G").unwrap(), 1_000_000_000);
+        assert_eq!(parse_size("4096").unwrap(), 4096);
+        assert!(parse_size("lots").is_err());
+    }
+
+    #[test]
+    fn bundled_sdl_builds_one_group_with_both_services() {
+        let sdl = SdlFile::parse(DEFAULT_SDL).unwrap();
+        let groups = sdl.group_specs().unwrap();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].name, "dcloud");
+        assert_eq!(groups[0].resources.len(), 2);
+
+        let inference = groups[0].resources[0].resource.as_ref().unwrap();
+        let cpu = inference.cpu.as_ref().unwrap().units.as_ref().unwrap();
+        assert_eq!(cpu.val, b"8000");
+        let gpu = inference.gpu.as_ref().unwrap();
+        assert_eq!(gpu.units.as_ref().unwrap().val, b"2");
+        assert!(gpu.attributes.iter().any(|a| a.key == "vendor/nvidia/model/a100"));
+        assert_eq!(inference.storage.len(), 2);
+
+        let bot = &groups[0].resources[1];
+        assert_eq!(bot.price.as_ref().unwrap().amount, "50000000000000000000000");
+        assert_eq!(bot.resource.as_ref().unwrap().endpoints.len(), 2);
+    }
+
+    /// One web service; its env needs Go's HTML escaping.
+    const MINIMAL_SDL: &str = r#"---
+version: "2.0"
+services:
+  web:
+    image: nginx:1.25
+    env:
+      - GREETING=<hi> & bye
+    expose:
+      - port: 80
+        as: 80
+        to:
+          - global: true
+profiles:
+  compute:
+    web:
+      resources:
+        cpu:
+          units: 0.5
+        memory:
+          size: 512Mi
+        storage:
+          size: 1Gi
+  placement:
+    dcloud:
+      pricing:
+        web:
+          denom: uakt
+          amount: 1000
+deployment:
+  web:
+    dcloud:
+      profile: web
+      count: 1
+"#;
+
+    /// What akash's `Manifest.Version()` hashes for `MINIMAL_SDL`: Go field
+    /// tags, sorted keys, `\u003c`-style escapes. The hash below was taken
+    /// over this literal with Python's hashlib, not with our encoder.
+    const MINIMAL_CANONICAL: &str = r#"[{"name":"dcloud","services":[{"args":null,"command":null,"count":1,"credentials":null,"env":["GREETING=\u003chi\u003e \u0026 bye"],"expose":[{"endpointSequenceNumber":0,"externalPort":80,"global":true,"hosts":null,"httpOptions":{"maxBodySize":1048576,"nextCases":["error","timeout"],"nextTimeout":0,"nextTries":3,"readTimeout":60000,"sendTimeout":60000},"ip":"","port":80,"proto":"TCP","service":""}],"image":"nginx:1.25","name":"web","params":null,"resources":{"cpu":{"units":{"val":"500"}},"endpoints":[{"sequence_number":0}],"gpu":{"units":{"val":"0"}},"id":1,"memory":{"size":{"val":"536870912"}},"storage":[{"name":"default","size":{"val":"1073741824"}}]}}]}]"#;
+
+    #[test]
+    fn version_hashes_the_canonical_manifest() {
+        let sdl = SdlFile::parse(MINIMAL_SDL).unwrap();
+        assert_eq!(canonical_json(&sdl.manifest_json().unwrap()), MINIMAL_CANONICAL);
+        let hex: String = sdl.version().unwrap().iter().map(|b| format!("{:02x}", b)).collect();
+        assert_eq!(hex, "10ca2efdaa11cb91f5f21ec2d061c2948353ef76c4e52b7812efc4d51c84e450");
+    }
+
+    #[test]
+    fn version_tracks_variable_values() {
+        let mut sdl = SdlFile::parse(DEFAULT_SDL).unwrap();
+        let before = sdl.version().unwrap();
+        sdl.variables[0].value = "filled".to_string();
+        assert_ne!(before, sdl.version().unwrap());
+        assert_eq!(before.len(), 32);
+    }
+
+    #[test]
+    fn manifest_carries_images_env_and_exposes() {
+        let sdl = SdlFile::parse(DEFAULT_SDL).unwrap();
+        let groups = sdl.manifest().unwrap();
+        let bot = groups[0].services.iter().find(|s| s.name == "bot").unwrap();
+        assert!(bot.image.starts_with("ghcr.io/permissionlessweb/linguabridge-bot"));
+        assert!(bot.env.iter().any(|e| e == "LINGUABRIDGE_WEB__PORT=3000"));
+        assert!(bot.expose.iter().any(|e| e.port == 3000 && e.external_port == 80 && e.global));
+        assert_eq!(bot.params.as_ref().unwrap().storage[0].mount, "/data");
+    }
+
+    #[test]
+    fn admin_path_prefix_comes_from_the_bot_env() {
+        let sdl = SdlFile::parse(DEFAULT_SDL).unwrap();
+        assert_eq!(sdl.admin_path_prefix(), None);
+
+        let prefixed = SdlFile::parse(&DEFAULT_SDL.replace(
+            "- \"LINGUABRIDGE_ADMIN__PORT=9999\"",
+            "- \"LINGUABRIDGE_ADMIN__PORT=9999\"\n      - \"LINGUABRIDGE_ADMIN__PATH_PREFIX=/bot-admin\"",
+        ))
+        .unwrap();
+        assert_eq!(prefixed.admin_path_prefix().as_deref(), Some("/bot-admin"));
+    }
+
+    #[test]
+    fn env_and_image_changes_are_compatible_updates() {
+        let deployed = SdlFile::parse(DEFAULT_SDL).unwrap();
+        let updated = SdlFile::parse(
+            &DEFAULT_SDL
+                .replace("linguabridge-bot:v0.3.1", "linguabridge-bot:v0.3.2")
+                .replace("RUST_LOG=linguabridge=info", "RUST_LOG=linguabridge=debug"),
+        )
+        .unwrap();
+        assert!(updated.check_update_compatible(&deployed).is_ok());
+        assert_ne!(updated.version().unwrap(), deployed.version().unwrap());
+    }
+
+    #[test]
+    fn resource_and_pricing_changes_are_rejected() {
+        let deployed = SdlFile::parse(DEFAULT_SDL).unwrap();
+        let updated = SdlFile::parse(
+            &DEFAULT_SDL
+                .replace("size: 512Mi", "size: 1Gi")
+                .replace("amount: 50000", "amount: 60000"),
+        )
+        .unwrap();
+        let problems = updated.check_update_compatible(&deployed).unwrap_err();
+        assert!(problems.contains(&IncompatibleUpdate::ResourcesChanged {
+            group: "dcloud".to_string(),
+            id: 2,
+            resource: "memory",
+        }));
+        assert!(problems.contains(&IncompatibleUpdate::PriceChanged {
+            group: "dcloud".to_string(),
+            id: 2,
+        }));
+        assert_eq!(problems[0].to_string(), "group 'dcloud' service #2: memory changed");
+    }
+
+    #[test]
+    fn renamed_placement_is_rejected() {
+        let deployed = SdlFile::parse(DEFAULT_SDL).unwrap();
+        let updated = SdlFile::parse(&DEFAULT_SDL.replace("dcloud", "akash")).unwrap();
+        let problems = updated.check_update_compatible(&deployed).unwrap_err();
+        assert!(problems.contains(&IncompatibleUpdate::GroupRemoved("dcloud".to_string())));
+        assert!(problems.contains(&IncompatibleUpdate::GroupAdded("akash".to_string())));
+    }
+}
@@ -0,0 +1,429 @@
+//! Pending chain transactions awaiting explicit user confirmation.
+//!
+//! Every TUI action that would broadcast a transaction builds a [`PendingTx`]
+//! and hands it to `App::request_tx`, which shows a `TxConfirm` popup. Nothing
+//! is signed or sent until the user presses Enter on that popup.
+
+use linguabridge_types::akash::base::v1beta3::ResourceValue;
+use linguabridge_types::akash::deployment::v1beta3::{
+    DeploymentId, MsgCreateDeployment, MsgDepositDeployment, MsgUpdateDeployment,
+};
+use linguabridge_types::cosmos::base::v1beta1::Coin;
+
+use crate::tui::api::BidInfo;
+use crate::tui::config::schema::NetworkConfig;
+use crate::tui::sdl::SdlFile;
+use crate::tui::wallet::offline::{SignedTx, UnsignedTx};
+
+/// Default gas limit for single-message transactions.
+pub const DEFAULT_GAS_LIMIT: u64 = 200_000;
+
+/// Gas limit for MsgCreateDeployment and MsgUpdateDeployment, which carry
+/// every group.
+pub const DEPLOYMENT_GAS_LIMIT: u64 = 500_000;
+
+/// Gas price used for fee estimates, in uakt per gas unit.
+pub const GAS_PRICE_UAKT: f64 = 0.025;
+
+/// Smallest escrow deposit the chain accepts for a deployment, in uakt.
+/// The chain asks for the same 5 USDC minimum from USDC deposits.
+pub const MIN_DEPLOYMENT_DEPOSIT_UAKT: u64 = 5_000_000;
+
+/// Blocks of runtime the deposit is sized to fund (about a day at 6s blocks).
+pub const DEPOSIT_DURATION_BLOCKS: u64 = 14_400;
+
+/// Rough provider rates in uakt per block, used only to size the deposit.
+const CPU_RATE_PER_CORE: f64 = 10.0;
+const MEMORY_RATE_PER_GIB: f64 = 2.0;
+const STORAGE_RATE_PER_GIB: f64 = 1.0;
+const GPU_RATE_PER_UNIT: f64 = 1_000.0;
+
+const GIB: f64 = (1u64 << 30) as f64;
+
+/// Estimated fee in uakt for the given gas limit.
+pub fn estimate_fee_uakt(gas_limit: u64) -> u64 {
+    (gas_limit as f64 * GAS_PRICE_UAKT).ceil() as u64
+}
+
+/// Escrow deposit in uakt for the SDL: every service's resources times its
+/// replica count, priced with the rate table for `DEPOSIT_DURATION_BLOCKS`,
+/// and never below the chain minimum.
+pub fn estimate_deposit_uakt(sdl: &SdlFile) -> Result<u64, String> {
+    let mut per_block = 0.0;
+    for unit in sdl.group_specs()?.iter().flat_map(|g| &g.resources) {
+        let Some(res) = &unit.resource else { continue };
+        let cpu_millis = quantity(res.cpu.as_ref().and_then(|c| c.units.as_ref()));
+        let memory = quantity(res.memory.as_ref().and_then(|m| m.quantity.as_ref()));
+        let storage: u64 = res.storage.iter().map(|s| quantity(s.quantity.as_ref())).sum();
+        let gpus = quantity(res.gpu.as_ref().and_then(|g| g.units.as_ref()));
+
+        let unit_rate = cpu_millis as f64 / 1000.0 * CPU_RATE_PER_CORE
+            + memory as f64 / GIB * MEMORY_RATE_PER_GIB
+            + storage as f64 / GIB * STORAGE_RATE_PER_GIB
+            + gpus as f64 * GPU_RATE_PER_UNIT;
+        per_block += unit_rate * unit.count as f64;
+    }
+    let deposit = (per_block * DEPOSIT_DURATION_BLOCKS as f64).ceil() as u64;
+    Ok(deposit.max(MIN_DEPLOYMENT_DEPOSIT_UAKT))
+}
+
+/// Coin a deployment's escrow deposit is paid in. Fees are always paid in
+/// the network denom.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DepositDenom {
+    /// The network's own denom (`uakt` on mainnet)
+    #[default]
+    Akt,
+    /// USDC bridged over IBC, for wallets holding stablecoins
+    Usdc,
+}
+
+impl DepositDenom {
+    /// The other choice; the deployment screen toggles between the two.
+    pub fn next(self) -> Self {
+        match self {
+            Self::Akt => Self::Usdc,
+            Self::Usdc => Self::Akt,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Akt => "AKT",
+            Self::Usdc => "USDC",
+        }
+    }
+
+    /// The choice whose base denom on `network` is `denom`, if any.
+    pub fn from_base_denom(denom: &str, network: &NetworkConfig) -> Option<Self> {
+        [Self::Akt, Self::Usdc]
+            .into_iter()
+            .find(|choice| choice.base_denom(network) == denom)
+    }
+
+    /// Base denom the deposit coin is sent in on `network`.
+    pub fn base_denom(self, network: &NetworkConfig) -> &str {
+        match self {
+            Self::Akt => &network.denom,
+            Self::Usdc => &network.usdc_denom,
+        }
+    }
+
+    /// A micro-unit amount, e.g. `5000000` → `5.000 USDC`. Both denoms
+    /// have 6 decimals.
+    pub fn format_amount(self, amount: u64, network: &NetworkConfig) -> String {
+        match self {
+            Self::Akt => network.format_amount(amount),
+            Self::Usdc => format!("{:.3} USDC", amount as f64 / 1_000_000.0),
+        }
+    }
+}
+
+/// Why the wallet can't fund a deposit of `deposit` micro-units in a
+/// non-network denom, or None if it can. The fee grant can't help here: it
+/// only pays gas, so the balance in that denom has to cover the deposit.
+pub fn deposit_shortfall(
+    denom: DepositDenom,
+    deposit: u64,
+    balance: Option<u64>,
+    network: &NetworkConfig,
+) -> Option<String> {
+    match balance {
+        None => Some(format!("{} balance not loaded yet — try again in a moment", denom.label())),
+        Some(balance) if balance < deposit => Some(format!(
+            "{} balance too low for the deposit ({} held, {} needed)",
+            denom.label(),
+            denom.format_amount(balance, network),
+            denom.format_amount(deposit, network)
+        )),
+        Some(_) => None,
+    }
+}
+
+/// On-chain resource quantities are decimal strings.
+fn quantity(value: Option<&ResourceValue>) -> u64 {
+    value
+        .and_then(|v| std::str::from_utf8(&v.val).ok())
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0)
+}
+
+/// What the transaction does once confirmed.
+#[derive(Debug, Clone)]
+pub enum TxAction {
+    /// MsgCreateDeployment from the current SDL
+    CreateDeployment,
+    /// MsgCreateLease accepting a provider bid
+    CreateLease { bid: BidInfo },
+    /// MsgUpdateDeployment moving a running deployment to the edited SDL
+    UpdateDeployment { dseq: u64 },
+    /// Sign an exported transaction on the air-gapped machine (no broadcast)
+    SignOffline { unsigned: UnsignedTx },
+    /// A transaction signed on an offline machine and imported from file
+    BroadcastSigned { signed: SignedTx, messages: Vec<String>, dseq: Option<u64> },
+    /// MsgCreateCertificate publishing the wallet's provider mTLS certificate
+    PublishCertificate { cert_pem: String },
+    /// MsgDepositDeployment adding `amount` of `denom` to a deployment's escrow
+    DepositDeployment { dseq: u64, amount: u64, denom: String },
+}
+
+impl TxAction {
+    /// Protobuf type URL of the message this action broadcasts.
+    pub fn msg_type(&self) -> String {
+        match self {
+            Self::CreateDeployment => "/akash.deployment.v1beta3.MsgCreateDeployment".to_string(),
+            Self::CreateLease { .. } => "/akash.market.v1beta4.MsgCreateLease".to_string(),
+            Self::UpdateDeployment { .. } => "/akash.deployment.v1beta3.MsgUpdateDeployment".to_string(),
+            Self::SignOffline { unsigned } => unsigned.messages.join(", "),
+            Self::BroadcastSigned { messages, .. } => messages.join(", "),
+            Self::PublishCertificate { .. } => "/akash.cert.v1beta3.MsgCreateCertificate".to_string(),
+            Self::DepositDeployment { .. } => "/akash.deployment.v1beta3.MsgDepositDeployment".to_string(),
+        }
+    }
+}
+
+/// MsgCreateDeployment for the SDL, owned and funded by `owner`, with the
+/// escrow deposit in `deposit_denom`. The rate table is too rough to tell
+/// AKT from USDC pricing, so both deposit the same micro-unit amount.
+pub fn create_deployment_msg(
+    owner: &str,
+    dseq: u64,
+    sdl: &SdlFile,
+    deposit_denom: &str,
+) -> Result<MsgCreateDeployment, String> {
+    Ok(MsgCreateDeployment {
+        id: Some(DeploymentId { owner: owner.to_string(), dseq }),
+        groups: sdl.group_specs()?,
+        version: sdl.version()?,
+        deposit: Some(Coin {
+            denom: deposit_denom.to_string(),
+            amount: estimate_deposit_uakt(sdl)?.to_string(),
+        }),
+        depositor: owner.to_string(),
+    })
+}
+
+/// MsgUpdateDeployment pointing deployment `dseq` at the SDL's new version.
+/// Callers must check `SdlFile::check_update_compatible` first.
+pub fn update_deployment_msg(
+    owner: &str,
+    dseq: u64,
+    sdl: &SdlFile,
+) -> Result<MsgUpdateDeployment, String> {
+    Ok(MsgUpdateDeployment {
+        id: Some(DeploymentId { owner: owner.to_string(), dseq }),
+        version: sdl.version()?,
+    })
+}
+
+/// MsgDepositDeployment topping up the escrow of deployment `dseq`. The
+/// deposit has to be in the denom the deployment was created with.
+pub fn deposit_deployment_msg(owner: &str, dseq: u64, amount: u64, denom: &str) -> MsgDepositDeployment {
+    MsgDepositDeployment {
+        id: Some(DeploymentId { owner: owner.to_string(), dseq }),
+        amount: Some(Coin {
+            denom: denom.to_string(),
+            amount: amount.to_string(),
+        }),
+        depositor: owner.to_string(),
+    }
+}
+
+/// A transaction waiting on the confirmation popup.
+#[derive(Debug, Clone)]
+pub struct PendingTx {
+    pub action: TxAction,
+    pub title: String,
+    /// Key parameters shown to the user as `label: value`
+    pub params: Vec<(String, String)>,
+    pub gas_limit: u64,
+    /// Whether a fee grant pays the fee instead of the wallet
+    pub fee_granted: bool,
+}
+
+impl PendingTx {
+    pub fn new(action: TxAction, title: impl Into<String>) -> Self {
+        Self {
+            action,
+            title: title.into(),
+            params: Vec::new(),
+            gas_limit: DEFAULT_GAS_LIMIT,
+            fee_granted: false,
+        }
+    }
+
+    pub fn param(mut self, label: impl Into<String>, value: impl Into<String>) -> Self {
+        self.params.push((label.into(), value.into()));
+        self
+    }
+
+    pub fn with_gas_limit(mut self, gas_limit: u64) -> Self {
+        self.gas_limit = gas_limit;
+        self
+    }
+
+    pub fn with_fee_grant(mut self, fee_granted: bool) -> Self {
+        self.fee_granted = fee_granted;
+        self
+    }
+
+    pub fn estimated_fee_uakt(&self) -> u64 {
+        estimate_fee_uakt(self.gas_limit)
+    }
+
+    /// Lines rendered in the confirmation popup.
+    pub fn detail_lines(&self) -> Vec<String> {
+        let mut lines = vec![format!("Message: {}", self.action.msg_type())];
+        lines.extend(
+            self.params
+                .iter()
+                .map(|(label, value)| format!("{}: {}", label, value)),
+        );
+        // Pre-built transactions list the fee they were signed with in params
+        if !matches!(self.action, TxAction::SignOffline { .. } | TxAction::BroadcastSigned { .. }) {
+            lines.push(format!(
+                "Estimated fee: {} uakt (gas {}){}",
+                self.estimated_fee_uakt(),
+                self.gas_limit,
+                if self.fee_granted { " — paid by fee grant" } else { "" }
+            ));
+        }
+        lines.push(String::new());
+        lines.push(match self.action {
+            TxAction::SignOffline { .. } => "Press Enter to sign (nothing is broadcast), Esc to cancel.",
+            TxAction::BroadcastSigned { .. } => "Press Enter to broadcast, Esc to cancel.",
+            _ => "Press Enter to sign and broadcast, Esc to cancel.",
+        }.to_string());
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fee_estimate_rounds_up() {
+        assert_eq!(estimate_fee_uakt(DEFAULT_GAS_LIMIT), 5_000);
+        assert_eq!(estimate_fee_uakt(1), 1);
+    }
+
+    #[test]
+    fn detail_lines_include_type_params_and_fee() {
+        let pending = PendingTx::new(TxAction::CreateDeployment, "Confirm Deployment")
+            .param("Services", "2")
+            .with_fee_grant(true);
+        let lines = pending.detail_lines();
+        assert_eq!(lines[0], "Message: /akash.deployment.v1beta3.MsgCreateDeployment");
+        assert_eq!(lines[1], "Services: 2");
+        assert!(lines[2].starts_with("Estimated fee: 5000 uakt"));
+        assert!(lines[2].ends_with("paid by fee grant"));
+        assert!(lines.last().unwrap().starts_with("Press Enter"));
+    }
+
+    #[test]
+    fn deployment_msg_carries_owner_groups_and_deposit() {
+        let sdl = SdlFile::parse(crate::tui::sdl::DEFAULT_SDL).unwrap();
+        let msg = create_deployment_msg("akash1owner", 42, &sdl, "uakt").unwrap();
+        let id = msg.id.unwrap();
+        assert_eq!((id.owner.as_str(), id.dseq), ("akash1owner", 42));
+        assert_eq!(msg.depositor, "akash1owner");
+        assert_eq!(msg.version.len(), 32);
+        assert!(!msg.groups.is_empty());
+        let deposit = msg.deposit.unwrap();
+        assert_eq!(deposit.denom, "uakt");
+        assert_eq!(deposit.amount, estimate_deposit_uakt(&sdl).unwrap().to_string());
+    }
+
+    #[test]
+    fn usdc_deposit_uses_the_configured_ibc_denom() {
+        let network = NetworkConfig::default();
+        let sdl = SdlFile::parse(crate::tui::sdl::DEFAULT_SDL).unwrap();
+        let denom = DepositDenom::Usdc.base_denom(&network);
+        assert!(denom.starts_with("ibc/"));
+
+        let deposit = create_deployment_msg("akash1owner", 42, &sdl, denom)
+            .unwrap()
+            .deposit
+            .unwrap();
+        assert_eq!(deposit.denom, network.usdc_denom);
+        assert_eq!(DepositDenom::Akt.base_denom(&network), "uakt");
+        assert_eq!(DepositDenom::Usdc.next(), DepositDenom::Akt);
+    }
+
+    #[test]
+    fn escrow_deposit_keeps_the_deployment_denom() {
+        let network = NetworkConfig::default();
+        let msg = deposit_deployment_msg("akash1owner", 42, 5_000_000, &network.usdc_denom);
+        let id = msg.id.unwrap();
+        assert_eq!((id.owner.as_str(), id.dseq), ("akash1owner", 42));
+        assert_eq!(msg.depositor, "akash1owner");
+        let amount = msg.amount.unwrap();
+        assert_eq!((amount.amount.as_str(), amount.denom.as_str()), ("5000000", network.usdc_denom.as_str()));
+
+        assert_eq!(DepositDenom::from_base_denom(&network.usdc_denom, &network), Some(DepositDenom::Usdc));
+        assert_eq!(DepositDenom::from_base_denom("uakt", &network), Some(DepositDenom::Akt));
+        assert_eq!(DepositDenom::from_base_denom("uatom", &network), None);
+    }
+
+    #[test]
+    fn deposit_shortfall_checks_the_chosen_denom() {
+        let network = NetworkConfig::default();
+        let usdc = DepositDenom::Usdc;
+        assert!(deposit_shortfall(usdc, 5_000_000, Some(5_000_000), &network).is_none());
+        assert_eq!(
+            deposit_shortfall(usdc, 5_000_000, Some(1_000_000), &network).unwrap(),
+            "USDC balance too low for the deposit (1.000 USDC held, 5.000 USDC needed)"
+        );
+        assert!(deposit_shortfall(usdc, 5_000_000, None, &network)
+            .unwrap()
+            .contains("not loaded"));
+    }
+
+    #[test]
+    fn deposit_scales_with_resources_and_replicas() {
+        let sdl = SdlFile::parse(crate::tui::sdl::DEFAULT_SDL).unwrap();
+        let deposit = estimate_deposit_uakt(&sdl).unwrap();
+        // Two GPUs dominate the default SDL, well past the chain minimum
+        assert!(deposit > MIN_DEPLOYMENT_DEPOSIT_UAKT * 4);
+
+        let doubled = SdlFile::parse(
+            &crate::tui::sdl::DEFAULT_SDL.replace("profile: inference\n      count: 1", "profile: inference\n      count: 2"),
+        )
+        .unwrap();
+        assert!(estimate_deposit_uakt(&doubled).unwrap() > deposit);
+    }
+
+    #[test]
+    fn small_deployments_pay_the_chain_minimum() {
+        let yaml = r#"
+version: "2.0"
+services:
+  web:
+    image: nginx
+profiles:
+  compute:
+    web:
+      resources:
+        cpu:
+          units: 0.5
+        memory:
+          size: 256Mi
+        storage:
+          size: 1Gi
+  placement:
+    dcloud:
+      pricing:
+        web:
+          denom: uakt
+          amount: 100
+deployment:
+  web:
+    dcloud:
+      profile: web
+      count: 1
+"#;
+        let sdl = SdlFile::parse(yaml).unwrap();
+        assert_eq!(estimate_deposit_uakt(&sdl).unwrap(), MIN_DEPLOYMENT_DEPOSIT_UAKT);
+    }
+}
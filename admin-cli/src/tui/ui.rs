@@ -72,8 +72,15 @@ fn render_header(frame: &mut Frame, theme: &AkashTheme, app: &App, area: Rect) {
         Span::raw("")
     };
 
+    let read_only = if app.read_only {
+        Span::styled(" [READ-ONLY]", Style::default().fg(theme.warning).bold())
+    } else {
+        Span::raw("")
+    };
+
     let title_line = Line::from(vec![
         Span::styled("LinguaBridge Admin TUI", theme.primary_style().bold()),
+        read_only,
         status,
     ]);
 
@@ -163,13 +170,25 @@ fn render_footer(frame: &mut Frame, theme: &AkashTheme, app: &App, area: Rect) {
 
     let help_text = match app.current_screen {
         Screen::Splash => "Press any key to continue",
-        Screen::Wallet => "g: Gen | i: Import | c: Copy | s: Save | l: Load | r: Balance",
+        Screen::Wallet => "g: Gen | i: Import | c: Copy | s: Save | l: Load | r: Balance | o: Sign Offline",
         Screen::FeeGrant => "r: Request | c: Check Grants | b: Balance | Tab/BackTab: Nav",
-        Screen::Deployment => "v: Panel | i: Edit | j/k: Nav | g: GPU | d: Deploy",
+        Screen::Deployment => "v: Panel | i: Edit | j/k: Nav | g: GPU | m: Deposit Denom | d: Deploy | e: Export | o: Import Signed",
         Screen::Bids => "j/k: Navigate | Enter: Accept | r: Refresh",
-        Screen::Leases => "j/k: Navigate | l: Logs | r: Refresh",
-        Screen::DiscordConfig => "i: Edit | j/k: Field | x/X: Clear | u: URL | t: Test | n/p: Guide",
-        Screen::Deployments => "j/k: Navigate | r: Refresh | l: Logs | 2: New Deploy",
+        Screen::Leases => "j/k: Navigate | l: Logs | r: Refresh | u: Update Deployment",
+        Screen::DiscordConfig => "i: Edit | j/k: Field | x/X: Clear | u: URL | t: Dry Run | P: Provision | n/p: Guide",
+        Screen::Deployments => "j/k: Navigate | r: Refresh | l: Logs | d: Deposit | 2: New Deploy",
+    };
+    let help_text = if app.read_only {
+        match app.current_screen {
+            Screen::Wallet => "c: Copy | l: Load Address | r: Balance",
+            Screen::FeeGrant => "c: Check Grants | b: Balance | Tab/BackTab: Nav",
+            Screen::Deployment => "v: Panel | i: Edit | j/k: Nav | g: GPU | e: Export",
+            Screen::Bids => "j/k: Navigate | r: Refresh",
+            Screen::Leases => "j/k: Navigate | l: Logs | r: Refresh",
+            _ => help_text,
+        }
+    } else {
+        help_text
     };
 
     let footer_line = Line::from(vec![
@@ -224,7 +243,11 @@ fn render_spinner(frame: &mut Frame, theme: &AkashTheme, app: &App) {
 fn render_popup(frame: &mut Frame, theme: &AkashTheme, app: &App) {
     if let Some(popup) = &app.popup {
         let area = frame.area();
-        let popup_area = centered_rect(60, 50, area);
+        // QR codes need most of the terminal to stay scannable
+        let popup_area = match popup.popup_type {
+            crate::tui::widgets::PopupType::OfflineTx => centered_rect(90, 95, area),
+            _ => centered_rect(60, 50, area),
+        };
 
         frame.render_widget(Clear, popup_area);
 
@@ -261,8 +284,8 @@ fn render_popup(frame: &mut Frame, theme: &AkashTheme, app: &App) {
                     theme.text_dim_style(),
                 )));
             }
-            crate::tui::widgets::PopupType::DeployConfirm => {
-                // Show cost breakdown details
+            crate::tui::widgets::PopupType::TxConfirm => {
+                // Show message type, parameters and fee estimate
                 for detail in &popup.details {
                     if detail.is_empty() {
                         lines.push(Line::from(""));
@@ -273,6 +296,15 @@ fn render_popup(frame: &mut Frame, theme: &AkashTheme, app: &App) {
                     }
                 }
             }
+            crate::tui::widgets::PopupType::OfflineTx => {
+                for detail in &popup.details {
+                    lines.push(Line::from(Span::styled(detail.as_str(), theme.text_primary_style())));
+                }
+                lines.push(Line::from(Span::styled(
+                    "Press any key to dismiss",
+                    theme.text_dim_style(),
+                )));
+            }
             crate::tui::widgets::PopupType::FeeGrantNeeded => {
                 // Show fee grant needed details with warning styling
                 for detail in &popup.details {
@@ -288,6 +320,12 @@ fn render_popup(frame: &mut Frame, theme: &AkashTheme, app: &App) {
                 }
             }
             _ => {
+                for detail in &popup.details {
+                    lines.push(Line::from(Span::styled(detail.as_str(), theme.text_primary_style())));
+                }
+                if !popup.details.is_empty() {
+                    lines.push(Line::from(""));
+                }
                 lines.push(Line::from(Span::styled(
                     "Press any key to dismiss",
                     theme.text_dim_style(),
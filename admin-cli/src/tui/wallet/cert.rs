@@ -0,0 +1,172 @@
+//! Client certificates for authenticating to Akash provider APIs (mTLS).
+//!
+//! Providers only serve logs, lease status and manifests to the lease owner,
+//! identified by a self-signed certificate whose subject CN is the owner's
+//! address and which has been published on chain with `MsgCreateCertificate`.
+//! The certificate's P-256 key is derived from the wallet key, so only the
+//! public certificate needs to be kept on disk.
+
+use hkdf::Hkdf;
+use p256::pkcs8::der::pem::LineEnding;
+use p256::pkcs8::{DecodePrivateKey, EncodePrivateKey, EncodePublicKey};
+use rcgen::{
+    CertificateParams, DistinguishedName, DnType, ExtendedKeyUsagePurpose, KeyUsagePurpose,
+    SerialNumber,
+};
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+use super::KeyPair;
+
+const HKDF_SALT: &[u8] = b"linguabridge-provider-cert-v1";
+const HKDF_INFO: &[u8] = b"akash-mtls-p256";
+
+/// Subject attribute the Akash CLI stamps on client certificates.
+const AUTH_VERSION_OID: &[u64] = &[2, 23, 133, 2, 6];
+const AUTH_VERSION: &str = "v0.0.1";
+
+/// How long a newly generated certificate stays valid.
+const CERT_VALIDITY_DAYS: i64 = 365;
+
+/// `akash.cert.v1beta3.MsgCreateCertificate`. linguabridge-types doesn't
+/// export the cert package, so the message is mirrored here.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct MsgCreateCertificate {
+    #[prost(string, tag = "1")]
+    pub owner: String,
+    /// PEM-encoded certificate
+    #[prost(bytes = "vec", tag = "2")]
+    pub cert: Vec<u8>,
+    /// PEM-encoded (`EC PUBLIC KEY`) public key
+    #[prost(bytes = "vec", tag = "3")]
+    pub pubkey: Vec<u8>,
+}
+
+impl prost::Name for MsgCreateCertificate {
+    const NAME: &'static str = "MsgCreateCertificate";
+    const PACKAGE: &'static str = "akash.cert.v1beta3";
+}
+
+/// A provider client certificate and the wallet-derived key it was issued for.
+pub struct ProviderCertificate {
+    pub cert_pem: String,
+    key_pem: Zeroizing<String>,
+}
+
+impl ProviderCertificate {
+    /// Issue a new certificate for `owner`. It must be published on chain
+    /// before providers accept it.
+    pub fn generate(keypair: &KeyPair, owner: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let key_pem = derive_key_pem(keypair)?;
+        let signing_key = rcgen::KeyPair::from_pem(&key_pem)?;
+
+        let now = time::OffsetDateTime::now_utc();
+        let mut params = CertificateParams::new(Vec::<String>::new())?;
+        let mut name = DistinguishedName::new();
+        name.push(DnType::CommonName, owner);
+        name.push(DnType::CustomDnType(AUTH_VERSION_OID.to_vec()), AUTH_VERSION);
+        params.distinguished_name = name;
+        // Akash identifies certificates by (owner, serial); like the Akash
+        // CLI, use the issue time in nanoseconds
+        params.serial_number = Some(SerialNumber::from(now.unix_timestamp_nanos() as u64));
+        params.not_before = now;
+        params.not_after = now + time::Duration::days(CERT_VALIDITY_DAYS);
+        params.key_usages = vec![
+            KeyUsagePurpose::KeyEncipherment,
+            KeyUsagePurpose::DataEncipherment,
+        ];
+        params.extended_key_usages = vec![ExtendedKeyUsagePurpose::ClientAuth];
+
+        let cert = params.self_signed(&signing_key)?;
+        Ok(Self {
+            cert_pem: cert.pem(),
+            key_pem,
+        })
+    }
+
+    /// Pair a previously issued certificate with the wallet's derived key.
+    pub fn from_pem(keypair: &KeyPair, cert_pem: String) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(Self {
+            cert_pem,
+            key_pem: derive_key_pem(keypair)?,
+        })
+    }
+
+    /// Identity for reqwest to present during the TLS handshake.
+    pub fn identity(&self) -> Result<reqwest::Identity, reqwest::Error> {
+        reqwest::Identity::from_pkcs8_pem(self.cert_pem.as_bytes(), self.key_pem.as_bytes())
+    }
+
+    /// Public key in the `EC PUBLIC KEY` PEM form the cert module stores.
+    pub fn public_key_pem(&self) -> Result<String, Box<dyn std::error::Error>> {
+        let secret = p256::SecretKey::from_pkcs8_pem(&self.key_pem)?;
+        let der = secret.public_key().to_public_key_der()?;
+        p256::pkcs8::der::pem::encode_string("EC PUBLIC KEY", LineEnding::LF, der.as_bytes())
+            .map_err(|e| format!("failed to encode public key: {}", e).into())
+    }
+
+    /// MsgCreateCertificate publishing this certificate for `owner`.
+    pub fn create_msg(&self, owner: &str) -> Result<MsgCreateCertificate, Box<dyn std::error::Error>> {
+        Ok(MsgCreateCertificate {
+            owner: owner.to_string(),
+            cert: self.cert_pem.clone().into_bytes(),
+            pubkey: self.public_key_pem()?.into_bytes(),
+        })
+    }
+}
+
+/// PKCS#8 PEM of the P-256 key derived from the wallet's secp256k1 key.
+fn derive_key_pem(keypair: &KeyPair) -> Result<Zeroizing<String>, Box<dyn std::error::Error>> {
+    let hk = Hkdf::<Sha256>::new(Some(HKDF_SALT), &keypair.private_key);
+    let mut scalar = Zeroizing::new([0u8; 32]);
+    hk.expand(HKDF_INFO, scalar.as_mut())
+        .map_err(|e| format!("provider key derivation failed: {}", e))?;
+    let secret = p256::SecretKey::from_slice(scalar.as_ref())
+        .map_err(|e| format!("derived provider key is invalid: {}", e))?;
+    Ok(secret.to_pkcs8_pem(LineEnding::LF)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tui::wallet::keygen::KeyGenerator;
+
+    fn keypair() -> KeyPair {
+        let gen = KeyGenerator::new();
+        let mnemonic = gen.generate_mnemonic().unwrap();
+        gen.derive_keypair(&mnemonic).unwrap()
+    }
+
+    #[test]
+    fn generated_certificate_builds_tls_identity() {
+        let keypair = keypair();
+        let cert = ProviderCertificate::generate(&keypair, "akash1owner").unwrap();
+        assert!(cert.cert_pem.starts_with("-----BEGIN CERTIFICATE-----"));
+        assert!(cert.identity().is_ok());
+    }
+
+    #[test]
+    fn key_is_derived_deterministically_from_wallet() {
+        let keypair = keypair();
+        let issued = ProviderCertificate::generate(&keypair, "akash1owner").unwrap();
+        let issued_key = issued.public_key_pem().unwrap();
+        let reloaded = ProviderCertificate::from_pem(&keypair, issued.cert_pem.clone()).unwrap();
+        assert_eq!(issued_key, reloaded.public_key_pem().unwrap());
+        assert!(reloaded.identity().is_ok());
+
+        let other = ProviderCertificate::from_pem(&self::keypair(), issued.cert_pem).unwrap();
+        assert_ne!(issued_key, other.public_key_pem().unwrap());
+    }
+
+    #[test]
+    fn create_msg_carries_pem_cert_and_pubkey() {
+        let keypair = keypair();
+        let cert = ProviderCertificate::generate(&keypair, "akash1owner").unwrap();
+        let msg = cert.create_msg("akash1owner").unwrap();
+        assert_eq!(msg.owner, "akash1owner");
+        assert_eq!(msg.cert, cert.cert_pem.as_bytes());
+        assert!(String::from_utf8(msg.pubkey)
+            .unwrap()
+            .starts_with("-----BEGIN EC PUBLIC KEY-----"));
+    }
+}
@@ -1,4 +1,6 @@
+pub mod cert;
 pub mod keygen;
+pub mod offline;
 pub mod signer;
 
 use zeroize::Zeroize;
@@ -0,0 +1,416 @@
+//! Air-gapped signing.
+//!
+//! The online TUI builds an [`UnsignedTx`] (the SignDoc fields plus enough
+//! context to review it) and writes it to a file. An offline machine holding
+//! the mnemonic signs it with `TransactionSigner::sign_unsigned`, producing a
+//! [`SignedTx`] file that is carried back and broadcast from the online TUI.
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use cosmrs::crypto::PublicKey;
+use cosmrs::proto::cosmos::tx::v1beta1::{AuthInfo, TxBody, TxRaw};
+use cosmrs::tx::{Body, Fee, Raw, SignDoc, SignerInfo};
+use cosmrs::{Any, Coin};
+use linguabridge_types::akash::deployment::v1beta3::{
+    DeploymentId, MsgCreateDeployment, MsgDepositDeployment, MsgUpdateDeployment,
+};
+use linguabridge_types::cosmos::bank::v1beta1::MsgSend;
+use linguabridge_types::cosmos::base::v1beta1::Coin as ProtoCoin;
+use prost::{Message, Name};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// File name of the exported unsigned transaction in the config directory.
+pub const UNSIGNED_TX_FILE: &str = "unsigned-tx.json";
+/// File name the offline machine writes the signed transaction to.
+pub const SIGNED_TX_FILE: &str = "signed-tx.json";
+
+/// Largest SignDoc (in bytes) rendered as a QR code; a version 40 code with
+/// low error correction holds 2953 bytes, base64 needs 4/3 of that.
+pub const MAX_QR_SIGN_DOC_BYTES: usize = 2_200;
+
+/// A transaction built online and waiting for an offline signature.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnsignedTx {
+    /// Address that must sign this transaction
+    pub signer: String,
+    pub chain_id: String,
+    pub account_number: u64,
+    pub sequence: u64,
+    pub gas_limit: u64,
+    /// Message type URLs, shown for review on the offline machine
+    pub messages: Vec<String>,
+    /// Base64 protobuf `TxBody`
+    pub body_bytes: String,
+    /// Base64 protobuf `AuthInfo`
+    pub auth_info_bytes: String,
+    /// Deployment sequence the transaction creates or acts on, if any
+    #[serde(default)]
+    pub dseq: Option<u64>,
+}
+
+impl UnsignedTx {
+    /// Build the SignDoc for `messages`, signed by the holder of `public_key`
+    /// (33-byte compressed secp256k1).
+    #[allow(clippy::too_many_arguments)]
+    pub fn build(
+        messages: Vec<Any>,
+        signer: &str,
+        public_key: &[u8],
+        chain_id: &str,
+        account_number: u64,
+        sequence: u64,
+        gas_limit: u64,
+        fee_amount: u128,
+        memo: &str,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let public_key: PublicKey = cosmrs::tendermint::PublicKey::from_raw_secp256k1(public_key)
+            .ok_or("invalid secp256k1 public key")?
+            .into();
+
+        let fee_coin = Coin {
+            denom: "uakt"
+                .parse()
+                .map_err(|_| "invalid fee denom")?,
+            amount: fee_amount,
+        };
+
+        let type_urls = messages.iter().map(|m| m.type_url.clone()).collect();
+        let tx_body = Body::new(messages, memo, 0u32);
+
+        let auth_info = SignerInfo::single_direct(Some(public_key), sequence)
+            .auth_info(Fee::from_amount_and_gas(fee_coin, gas_limit));
+
+        let chain_id_parsed = chain_id
+            .parse()
+            .map_err(|_| format!("invalid chain ID: {}", chain_id))?;
+
+        let sign_doc = SignDoc::new(&tx_body, &auth_info, &chain_id_parsed, account_number)
+            .map_err(|e| format!("failed to build sign doc: {}", e))?;
+
+        Ok(Self {
+            signer: signer.to_string(),
+            chain_id: chain_id.to_string(),
+            account_number,
+            sequence,
+            gas_limit,
+            messages: type_urls,
+            body_bytes: BASE64.encode(&sign_doc.body_bytes),
+            auth_info_bytes: BASE64.encode(&sign_doc.auth_info_bytes),
+            dseq: None,
+        })
+    }
+
+    pub fn with_dseq(mut self, dseq: u64) -> Self {
+        self.dseq = Some(dseq);
+        self
+    }
+
+    /// Reassemble the SignDoc from the exported fields.
+    pub fn sign_doc(&self) -> Result<SignDoc, Box<dyn std::error::Error>> {
+        Ok(SignDoc {
+            body_bytes: BASE64.decode(&self.body_bytes)?,
+            auth_info_bytes: BASE64.decode(&self.auth_info_bytes)?,
+            chain_id: self.chain_id.clone(),
+            account_number: self.account_number,
+        })
+    }
+
+    /// Canonical SignDoc bytes: exactly what the offline key signs.
+    pub fn sign_doc_bytes(&self) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let bytes = self
+            .sign_doc()?
+            .into_bytes()
+            .map_err(|e| format!("failed to encode sign doc: {}", e))?;
+        Ok(bytes)
+    }
+
+    /// What the SignDoc actually commits to, as `label: value` pairs for the
+    /// review popup. Decoded from the body and auth info bytes, not from
+    /// `messages` or `dseq`, which the file could state falsely.
+    pub fn review_params(&self) -> Result<Vec<(String, String)>, Box<dyn std::error::Error>> {
+        let body: TxBody =
+            cosmrs::proto::prost::Message::decode(BASE64.decode(&self.body_bytes)?.as_slice())?;
+        let auth_info: AuthInfo = cosmrs::proto::prost::Message::decode(
+            BASE64.decode(&self.auth_info_bytes)?.as_slice(),
+        )?;
+        let type_urls: Vec<&str> = body.messages.iter().map(|m| m.type_url.as_str()).collect();
+        if type_urls != self.messages {
+            return Err("listed messages do not match the transaction body".into());
+        }
+
+        let mut params = Vec::new();
+        for msg in &body.messages {
+            params.extend(describe_msg(&msg.type_url, &msg.value)?);
+        }
+        if !body.memo.is_empty() {
+            params.push(("Memo".to_string(), body.memo));
+        }
+        let fee = auth_info.fee.unwrap_or_default();
+        let fee_coins: Vec<String> = fee
+            .amount
+            .iter()
+            .map(|c| format!("{} {}", c.amount, c.denom))
+            .collect();
+        params.push((
+            "Fee".to_string(),
+            format!("{} (gas {})", fee_coins.join(", "), fee.gas_limit),
+        ));
+        if !fee.granter.is_empty() {
+            params.push(("Fee granter".to_string(), fee.granter));
+        }
+        Ok(params)
+    }
+
+    /// Render the base64 SignDoc as a terminal QR code, one string per row.
+    /// Returns `None` when the SignDoc is too large to scan reliably.
+    pub fn qr_lines(&self) -> Result<Option<Vec<String>>, Box<dyn std::error::Error>> {
+        let bytes = self.sign_doc_bytes()?;
+        if bytes.len() > MAX_QR_SIGN_DOC_BYTES {
+            return Ok(None);
+        }
+        let code = qrcode::QrCode::with_error_correction_level(
+            BASE64.encode(&bytes),
+            qrcode::EcLevel::L,
+        )
+        .map_err(|e| format!("QR encoding failed: {}", e))?;
+        let rendered = code
+            .render::<qrcode::render::unicode::Dense1x2>()
+            .quiet_zone(true)
+            .build();
+        Ok(Some(rendered.lines().map(str::to_string).collect()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let data = std::fs::read_to_string(path)
+            .map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+        Ok(serde_json::from_str(&data)?)
+    }
+}
+
+/// A transaction signed offline, ready for broadcast.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignedTx {
+    pub signer: String,
+    /// Base64 protobuf `TxRaw`
+    pub tx_bytes: String,
+}
+
+impl SignedTx {
+    pub fn from_raw(signer: &str, raw: &Raw) -> Result<Self, Box<dyn std::error::Error>> {
+        let bytes = raw
+            .to_bytes()
+            .map_err(|e| format!("failed to encode tx: {}", e))?;
+        Ok(Self {
+            signer: signer.to_string(),
+            tx_bytes: BASE64.encode(bytes),
+        })
+    }
+
+    /// Decoded tx bytes; fails if the file does not hold a valid `TxRaw`.
+    pub fn tx_bytes(&self) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let bytes = BASE64.decode(&self.tx_bytes)?;
+        Raw::from_bytes(&bytes).map_err(|e| format!("not a valid signed tx: {}", e))?;
+        Ok(bytes)
+    }
+
+    /// Check this is a signature over `unsigned`, so a stale or swapped file
+    /// is rejected before it reaches the chain.
+    pub fn verify_matches(&self, unsigned: &UnsignedTx) -> Result<(), Box<dyn std::error::Error>> {
+        if self.signer != unsigned.signer {
+            return Err(format!(
+                "signed by {}, but the export expects {}",
+                self.signer, unsigned.signer
+            )
+            .into());
+        }
+        let raw: TxRaw = Raw::from_bytes(&BASE64.decode(&self.tx_bytes)?)
+            .map_err(|e| format!("not a valid signed tx: {}", e))?
+            .into();
+        if raw.body_bytes != BASE64.decode(&unsigned.body_bytes)?
+            || raw.auth_info_bytes != BASE64.decode(&unsigned.auth_info_bytes)?
+        {
+            return Err("signed tx does not match the exported transaction".into());
+        }
+        if raw.signatures.iter().all(|sig| sig.is_empty()) {
+            return Err("signed tx carries no signature".into());
+        }
+        Ok(())
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let data = std::fs::read_to_string(path)
+            .map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+        Ok(serde_json::from_str(&data)?)
+    }
+}
+
+/// Review lines for one message. Messages the TUI never builds are shown
+/// by type only, flagged so the reviewer knows the contents weren't checked.
+fn describe_msg(
+    type_url: &str,
+    value: &[u8],
+) -> Result<Vec<(String, String)>, Box<dyn std::error::Error>> {
+    let name = type_url.rsplit('.').next().unwrap_or(type_url).to_string();
+    let mut params = Vec::new();
+    if type_url == MsgCreateDeployment::type_url() {
+        let msg = MsgCreateDeployment::decode(value)?;
+        params.push(("Message".to_string(), name));
+        params.extend(deployment_id(msg.id.as_ref()));
+        params.push(("Groups".to_string(), msg.groups.len().to_string()));
+        params.push(("Deposit".to_string(), coin(msg.deposit.as_ref())));
+        params.push(("Depositor".to_string(), msg.depositor));
+    } else if type_url == MsgUpdateDeployment::type_url() {
+        let msg = MsgUpdateDeployment::decode(value)?;
+        params.push(("Message".to_string(), name));
+        params.extend(deployment_id(msg.id.as_ref()));
+    } else if type_url == MsgDepositDeployment::type_url() {
+        let msg = MsgDepositDeployment::decode(value)?;
+        params.push(("Message".to_string(), name));
+        params.extend(deployment_id(msg.id.as_ref()));
+        params.push(("Deposit".to_string(), coin(msg.amount.as_ref())));
+        params.push(("Depositor".to_string(), msg.depositor));
+    } else if type_url == MsgSend::type_url() {
+        let msg = MsgSend::decode(value)?;
+        params.push(("Message".to_string(), name));
+        params.push(("From".to_string(), msg.from_address));
+        params.push(("To".to_string(), msg.to_address));
+        let amounts: Vec<String> = msg.amount.iter().map(|c| coin(Some(c))).collect();
+        params.push(("Amount".to_string(), amounts.join(", ")));
+    } else {
+        params.push(("Message".to_string(), format!("{} (contents not decoded)", type_url)));
+    }
+    Ok(params)
+}
+
+fn deployment_id(id: Option<&DeploymentId>) -> Vec<(String, String)> {
+    let id = id.cloned().unwrap_or_default();
+    vec![
+        ("Owner".to_string(), id.owner),
+        ("DSeq".to_string(), id.dseq.to_string()),
+    ]
+}
+
+fn coin(coin: Option<&ProtoCoin>) -> String {
+    coin.map(|c| format!("{} {}", c.amount, c.denom))
+        .unwrap_or_else(|| "none".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tui::wallet::keygen::KeyGenerator;
+    use crate::tui::wallet::signer::TransactionSigner;
+
+    fn export(signer: &TransactionSigner, public_key: &[u8], sequence: u64) -> UnsignedTx {
+        let address = signer.address().unwrap();
+        UnsignedTx::build(
+            vec![], &address, public_key, "akashnet-2", 7, sequence, 200_000, 5000, "offline",
+        )
+        .unwrap()
+    }
+
+    fn signer_and_export() -> (TransactionSigner, UnsignedTx) {
+        let gen = KeyGenerator::new();
+        let mnemonic = gen.generate_mnemonic().unwrap();
+        let keypair = gen.derive_keypair(&mnemonic).unwrap();
+        let public_key = keypair.public_key.clone();
+        let signer = TransactionSigner::new(keypair);
+        let unsigned = export(&signer, &public_key, 3);
+        (signer, unsigned)
+    }
+
+    #[test]
+    fn offline_round_trip_matches_online_signing() {
+        let (signer, unsigned) = signer_and_export();
+        let signed = signer.sign_unsigned(&unsigned).unwrap();
+        signed.verify_matches(&unsigned).unwrap();
+
+        // Same SignDoc signed in one step online must give identical bytes
+        let online = signer
+            .create_signed_tx(vec![], "akashnet-2", 7, 3, 200_000, 5000, "offline")
+            .unwrap();
+        assert_eq!(signed.tx_bytes().unwrap(), online);
+    }
+
+    #[test]
+    fn files_round_trip() {
+        let (signer, unsigned) = signer_and_export();
+        let dir = std::env::temp_dir().join(format!("lb-offline-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        unsigned.save(&dir.join(UNSIGNED_TX_FILE)).unwrap();
+        let loaded = UnsignedTx::load(&dir.join(UNSIGNED_TX_FILE)).unwrap();
+        assert_eq!(loaded, unsigned);
+
+        signer.sign_unsigned(&loaded).unwrap().save(&dir.join(SIGNED_TX_FILE)).unwrap();
+        let signed = SignedTx::load(&dir.join(SIGNED_TX_FILE)).unwrap();
+        signed.verify_matches(&unsigned).unwrap();
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn wrong_wallet_refuses_to_sign() {
+        let (_, unsigned) = signer_and_export();
+        let (other, _) = signer_and_export();
+        assert!(other.sign_unsigned(&unsigned).is_err());
+    }
+
+    #[test]
+    fn signature_over_different_tx_is_rejected() {
+        let gen = KeyGenerator::new();
+        let keypair = gen.derive_keypair(&gen.generate_mnemonic().unwrap()).unwrap();
+        let public_key = keypair.public_key.clone();
+        let signer = TransactionSigner::new(keypair);
+        let unsigned = export(&signer, &public_key, 3);
+        let signed = signer.sign_unsigned(&export(&signer, &public_key, 4)).unwrap();
+        assert!(signed.verify_matches(&unsigned).is_err());
+    }
+
+    #[test]
+    fn review_shows_decoded_contents_and_fee() {
+        let gen = KeyGenerator::new();
+        let keypair = gen.derive_keypair(&gen.generate_mnemonic().unwrap()).unwrap();
+        let public_key = keypair.public_key.clone();
+        let signer = TransactionSigner::new(keypair);
+        let address = signer.address().unwrap();
+        let msg = crate::tui::tx::deposit_deployment_msg(&address, 42, 5_000_000, "uakt");
+        let any = TransactionSigner::encode_msg(&msg).unwrap();
+        let mut unsigned = UnsignedTx::build(
+            vec![any], &address, &public_key, "akashnet-2", 7, 3, 200_000, 5000, "",
+        )
+        .unwrap();
+
+        let params = unsigned.review_params().unwrap();
+        let value = |label: &str| {
+            params.iter().find(|(l, _)| l == label).map(|(_, v)| v.as_str()).unwrap()
+        };
+        assert_eq!(value("Message"), "MsgDepositDeployment");
+        assert_eq!(value("DSeq"), "42");
+        assert_eq!(value("Deposit"), "5000000 uakt");
+        assert_eq!(value("Depositor"), address);
+        assert_eq!(value("Fee"), "5000 uakt (gas 200000)");
+
+        // A file claiming different messages than its body is refused
+        unsigned.messages = vec!["/cosmos.bank.v1beta1.MsgSend".to_string()];
+        assert!(unsigned.review_params().is_err());
+    }
+
+    #[test]
+    fn small_sign_doc_renders_as_qr() {
+        let (_, unsigned) = signer_and_export();
+        let lines = unsigned.qr_lines().unwrap().unwrap();
+        assert!(!lines.is_empty());
+    }
+}
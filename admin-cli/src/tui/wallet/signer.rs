@@ -1,7 +1,7 @@
+use super::offline::{SignedTx, UnsignedTx};
 use super::KeyPair;
 use cosmrs::crypto::secp256k1::SigningKey;
-use cosmrs::tx::{Body, Fee, SignDoc, SignerInfo};
-use cosmrs::{Any, Coin};
+use cosmrs::Any;
 
 const AKASH_BECH32_PREFIX: &str = "akash";
 
@@ -61,27 +61,20 @@ impl TransactionSigner {
         memo: &str,
     ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
         let sk = self.signing_key()?;
-
-        let fee_coin = Coin {
-            denom: "uakt"
-                .parse()
-                .map_err(|_| "invalid fee denom")?,
-            amount: fee_amount,
-        };
-
-        let tx_body = Body::new(messages, memo, 0u32);
-
-        let auth_info = SignerInfo::single_direct(Some(sk.public_key()), sequence)
-            .auth_info(Fee::from_amount_and_gas(fee_coin, gas_limit));
-
-        let chain_id_parsed = chain_id
-            .parse()
-            .map_err(|_| format!("invalid chain ID: {}", chain_id))?;
-
-        let sign_doc = SignDoc::new(&tx_body, &auth_info, &chain_id_parsed, account_number)
-            .map_err(|e| format!("failed to build sign doc: {}", e))?;
-
-        let tx_signed = sign_doc
+        let unsigned = UnsignedTx::build(
+            messages,
+            &self.address()?,
+            &self.keypair.public_key,
+            chain_id,
+            account_number,
+            sequence,
+            gas_limit,
+            fee_amount,
+            memo,
+        )?;
+
+        let tx_signed = unsigned
+            .sign_doc()?
             .sign(&sk)
             .map_err(|e| format!("signing failed: {}", e))?;
 
@@ -92,6 +85,26 @@ impl TransactionSigner {
         Ok(tx_bytes)
     }
 
+    /// Sign a transaction exported from the online TUI (air-gapped flow).
+    /// Refuses exports addressed to a different wallet.
+    pub fn sign_unsigned(&self, unsigned: &UnsignedTx) -> Result<SignedTx, Box<dyn std::error::Error>> {
+        let address = self.address()?;
+        if address != unsigned.signer {
+            return Err(format!(
+                "transaction expects signer {}, but this wallet is {}",
+                unsigned.signer, address
+            )
+            .into());
+        }
+
+        let sk = self.signing_key()?;
+        let raw = unsigned
+            .sign_doc()?
+            .sign(&sk)
+            .map_err(|e| format!("signing failed: {}", e))?;
+        SignedTx::from_raw(&address, &raw)
+    }
+
     /// Encode a prost Message into a Cosmos SDK `Any` for use in transactions.
     pub fn encode_msg<M: prost::Message + prost::Name>(msg: &M) -> Result<Any, Box<dyn std::error::Error>> {
         let type_url = M::type_url();
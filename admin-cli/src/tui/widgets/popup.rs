@@ -7,7 +7,8 @@ pub enum PopupType {
     Error,
     Info,
     Mnemonic,
-    DeployConfirm,   // Deployment confirmation with cost breakdown
+    TxConfirm,       // Chain transaction confirmation (message, params, fee)
+    OfflineTx,       // Exported unsigned tx: file path and SignDoc QR code
     FeeGrantNeeded,  // Balance too low, suggest fee grant
 }
 
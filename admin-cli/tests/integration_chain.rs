@@ -0,0 +1,871 @@
+//! Integration tests for the admin TUI's chain-facing code.
+//!
+//! `AkashClient` talks to an LCD (REST) endpoint for most queries and to a
+//! gRPC endpoint for balances. These tests run it against `MockAkashNode`,
+//! which serves canned responses for both, so queries and broadcasts are
+//! exercised end-to-end without a live node.
+
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use cosmrs::tx::Tx;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio::sync::Mutex;
+use tonic::{Request, Response as GrpcResponse, Status};
+
+use admin_cli::tui::api::AkashClient;
+use admin_cli::tui::sdl::{SdlFile, DEFAULT_SDL};
+use admin_cli::tui::tx::{create_deployment_msg, update_deployment_msg};
+use admin_cli::tui::wallet::keygen::KeyGenerator;
+use admin_cli::tui::wallet::offline::UnsignedTx;
+use admin_cli::tui::wallet::signer::TransactionSigner;
+use linguabridge_types::cosmos::bank::v1beta1::query_server::{Query as BankQuery, QueryServer};
+use linguabridge_types::cosmos::bank::v1beta1::*;
+use linguabridge_types::cosmos::base::v1beta1::Coin;
+
+const OWNER: &str = "akash1mockowner";
+const PROVIDER: &str = "akash1mockprovider";
+/// API address the mock provider registered on chain
+const PROVIDER_HOST_URI: &str = "https://provider.mock.example:8443";
+/// Certificate the mock provider published with the cert module
+const PROVIDER_CERT_PEM: &str = "-----BEGIN CERTIFICATE-----\nMOCK\n-----END CERTIFICATE-----\n";
+const CHAIN_ID: &str = "akashnet-2";
+const ACCOUNT_NUMBER: u64 = 7;
+const SEQUENCE: u64 = 3;
+const BLOCK_HEIGHT: u64 = 1_234_567;
+const BALANCE_UAKT: &str = "25000000";
+/// Leases owned by `OWNER`, served across several pages
+const LEASE_COUNT: usize = 50;
+/// Largest page the mock serves, whatever limit the client asks for
+const MOCK_PAGE_SIZE: usize = 20;
+
+/// State shared between the mock's handlers and the test.
+#[derive(Default)]
+struct NodeState {
+    /// Decoded tx bytes of every broadcast, in order
+    broadcasts: Mutex<Vec<Vec<u8>>>,
+    /// Query string of every LCD list request, keyed by path
+    queries: Mutex<HashMap<String, Vec<HashMap<String, String>>>>,
+    /// Remaining LCD requests to answer with 503 before recovering
+    failures: AtomicUsize,
+    /// `code` returned for broadcasts (0 = accepted)
+    broadcast_code: AtomicUsize,
+}
+
+/// Mock Akash node for testing.
+///
+/// Mimics the endpoints the TUI uses on a real node:
+/// - LCD: accounts, deployments (list and escrow info), bids, leases,
+///   providers, certificates, fee allowances, latest block, tx broadcast
+///   and tx lookup
+/// - gRPC: `cosmos.bank.v1beta1.Query/Balance`
+struct MockAkashNode {
+    state: Arc<NodeState>,
+    /// LCD base URL
+    url: String,
+    /// gRPC endpoint URL
+    grpc_url: String,
+    _lcd_task: tokio::task::JoinHandle<()>,
+    _grpc_task: tokio::task::JoinHandle<()>,
+}
+
+impl MockAkashNode {
+    /// Start both servers on random ports
+    async fn start() -> Self {
+        let state = Arc::new(NodeState::default());
+
+        let lcd = Router::new()
+            .route("/cosmos/auth/v1beta1/accounts/{address}", get(account))
+            .route("/akash/deployment/v1beta3/deployments/list", get(deployments))
+            .route("/akash/deployment/v1beta3/deployments/info", get(deployment_info))
+            .route("/akash/market/v1beta4/bids/list", get(bids))
+            .route("/akash/market/v1beta4/leases/list", get(leases))
+            .route("/akash/provider/v1beta3/providers/{owner}", get(provider))
+            .route("/akash/cert/v1beta3/certificates/list", get(certificates))
+            .route("/cosmos/feegrant/v1beta1/allowances/{grantee}", get(allowances))
+            .route("/cosmos/base/tendermint/v1beta1/blocks/latest", get(latest_block))
+            .route("/cosmos/tx/v1beta1/txs", post(broadcast))
+            .route("/cosmos/tx/v1beta1/txs/{hash}", get(tx_by_hash))
+            .with_state(Arc::clone(&state));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let lcd_task = tokio::spawn(async move {
+            let _ = axum::serve(listener, lcd).await;
+        });
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let grpc_url = format!("http://{}", listener.local_addr().unwrap());
+        let grpc_task = tokio::spawn(async move {
+            let _ = tonic::transport::Server::builder()
+                .add_service(QueryServer::new(MockBank))
+                .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+                .await;
+        });
+
+        Self {
+            state,
+            url,
+            grpc_url,
+            _lcd_task: lcd_task,
+            _grpc_task: grpc_task,
+        }
+    }
+
+    fn client(&self) -> AkashClient {
+        AkashClient::new(self.url.clone(), self.grpc_url.clone())
+    }
+
+    /// Answer the next `count` LCD requests with 503 Service Unavailable
+    fn fail_next(&self, count: usize) {
+        self.state.failures.store(count, Ordering::SeqCst);
+    }
+
+    /// Reject subsequent broadcasts with the given ABCI code
+    fn reject_broadcasts(&self, code: usize) {
+        self.state.broadcast_code.store(code, Ordering::SeqCst);
+    }
+
+    async fn broadcasts(&self) -> Vec<Vec<u8>> {
+        self.state.broadcasts.lock().await.clone()
+    }
+
+    async fn queries(&self, path: &str) -> Vec<HashMap<String, String>> {
+        self.state
+            .queries
+            .lock()
+            .await
+            .get(path)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+/// Consume one pending injected failure, if any.
+fn injected_failure(state: &NodeState) -> Option<Response> {
+    state
+        .failures
+        .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+        .ok()
+        .map(|_| StatusCode::SERVICE_UNAVAILABLE.into_response())
+}
+
+async fn record_query(state: &NodeState, path: &str, params: HashMap<String, String>) {
+    state
+        .queries
+        .lock()
+        .await
+        .entry(path.to_string())
+        .or_default()
+        .push(params);
+}
+
+fn tx_hash(tx_bytes: &[u8]) -> String {
+    Sha256::digest(tx_bytes)
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect()
+}
+
+async fn account(State(state): State<Arc<NodeState>>, Path(address): Path<String>) -> Response {
+    if let Some(failure) = injected_failure(&state) {
+        return failure;
+    }
+    if address != OWNER {
+        return (StatusCode::NOT_FOUND, Json(json!({"code": 5, "message": "account not found"})))
+            .into_response();
+    }
+    // Vesting-style wrapper, like Akash returns for some accounts
+    Json(json!({
+        "account": {
+            "@type": "/cosmos.vesting.v1beta1.DelayedVestingAccount",
+            "base_account": {
+                "address": OWNER,
+                "account_number": ACCOUNT_NUMBER.to_string(),
+                "sequence": SEQUENCE.to_string()
+            }
+        }
+    }))
+    .into_response()
+}
+
+async fn deployments(
+    State(state): State<Arc<NodeState>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Response {
+    if let Some(failure) = injected_failure(&state) {
+        return failure;
+    }
+    record_query(&state, "deployments", params).await;
+    Json(json!({
+        "deployments": [
+            {"deployment": {"deployment_id": {"owner": OWNER, "dseq": "100"}, "state": "active"}},
+            {"deployment": {"deployment_id": {"owner": OWNER, "dseq": "101"}, "state": "closed"}}
+        ],
+        "pagination": {"next_key": null, "total": "2"}
+    }))
+    .into_response()
+}
+
+async fn deployment_info(
+    State(state): State<Arc<NodeState>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Response {
+    if let Some(failure) = injected_failure(&state) {
+        return failure;
+    }
+    record_query(&state, "deployment_info", params.clone()).await;
+    if params.get("id.dseq").map(String::as_str) != Some("100") {
+        return (StatusCode::NOT_FOUND, Json(json!({"code": 5, "message": "deployment not found"})))
+            .into_response();
+    }
+    Json(json!({
+        "deployment": {"deployment_id": {"owner": OWNER, "dseq": "100"}, "state": "active"},
+        "groups": [],
+        "escrow_account": {
+            "id": {"scope": "deployment", "xid": format!("{}/100", OWNER)},
+            "owner": OWNER,
+            "state": "open",
+            "balance": {"denom": "uakt", "amount": "4000000.750000000000000000"},
+            "transferred": {"denom": "uakt", "amount": "1000000.000000000000000000"},
+            "settled_at": "1234000",
+            "depositor": OWNER,
+            "funds": {"denom": "uakt", "amount": "500000.000000000000000000"}
+        }
+    }))
+    .into_response()
+}
+
+async fn bids(
+    State(state): State<Arc<NodeState>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Response {
+    if let Some(failure) = injected_failure(&state) {
+        return failure;
+    }
+    record_query(&state, "bids", params).await;
+    Json(json!({
+        "bids": [{
+            "bid": {
+                "bid_id": {"owner": OWNER, "dseq": "100", "gseq": 1, "oseq": 1, "provider": PROVIDER},
+                "state": "open",
+                "price": {"denom": "uakt", "amount": "12.500000000000000000"}
+            },
+            "escrow_account": {}
+        }]
+    }))
+    .into_response()
+}
+
+async fn leases(
+    State(state): State<Arc<NodeState>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Response {
+    if let Some(failure) = injected_failure(&state) {
+        return failure;
+    }
+    // Keys are the base64 offset of the next page, like an opaque store key
+    let offset: usize = params
+        .get("pagination.key")
+        .and_then(|k| BASE64.decode(k).ok())
+        .and_then(|k| String::from_utf8(k).ok())
+        .and_then(|k| k.parse().ok())
+        .unwrap_or(0);
+    let limit = params
+        .get("pagination.limit")
+        .and_then(|l| l.parse().ok())
+        .unwrap_or(MOCK_PAGE_SIZE)
+        .min(MOCK_PAGE_SIZE);
+    let count_total = params.get("pagination.count_total").is_some_and(|c| c == "true");
+    record_query(&state, "leases", params).await;
+
+    let end = (offset + limit).min(LEASE_COUNT);
+    let leases: Vec<Value> = (offset..end)
+        .map(|i| {
+            json!({
+                "lease": {
+                    "lease_id": {"owner": OWNER, "dseq": (100 + i).to_string(), "gseq": 1, "oseq": 1, "provider": PROVIDER},
+                    "state": "active",
+                    "price": {"denom": "uakt", "amount": "12.500000000000000000"}
+                },
+                "escrow_payment": {}
+            })
+        })
+        .collect();
+    let next_key = (end < LEASE_COUNT).then(|| BASE64.encode(end.to_string()));
+    let total = if count_total { LEASE_COUNT } else { 0 };
+    Json(json!({
+        "leases": leases,
+        "pagination": {"next_key": next_key, "total": total.to_string()}
+    }))
+    .into_response()
+}
+
+async fn provider(State(state): State<Arc<NodeState>>, Path(owner): Path<String>) -> Response {
+    if let Some(failure) = injected_failure(&state) {
+        return failure;
+    }
+    if owner != PROVIDER {
+        return (StatusCode::NOT_FOUND, Json(json!({"code": 5, "message": "provider not found"})))
+            .into_response();
+    }
+    Json(json!({
+        "provider": {
+            "owner": PROVIDER,
+            "host_uri": PROVIDER_HOST_URI,
+            "attributes": [{"key": "region", "value": "us-west"}],
+            "info": {"email": "", "website": ""}
+        }
+    }))
+    .into_response()
+}
+
+async fn certificates(
+    State(state): State<Arc<NodeState>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Response {
+    if let Some(failure) = injected_failure(&state) {
+        return failure;
+    }
+    record_query(&state, "certificates", params.clone()).await;
+    let certificates: Vec<Value> = if params.get("filter.owner").map(String::as_str) == Some(PROVIDER) {
+        vec![json!({
+            "certificate": {
+                "state": "valid",
+                "cert": BASE64.encode(PROVIDER_CERT_PEM),
+                "pubkey": BASE64.encode("mock-pubkey")
+            },
+            "serial": "1"
+        })]
+    } else {
+        Vec::new()
+    };
+    Json(json!({
+        "certificates": certificates,
+        "pagination": {"next_key": null, "total": certificates.len().to_string()}
+    }))
+    .into_response()
+}
+
+async fn allowances(State(state): State<Arc<NodeState>>, Path(grantee): Path<String>) -> Response {
+    if let Some(failure) = injected_failure(&state) {
+        return failure;
+    }
+    if grantee != OWNER {
+        return Json(json!({"allowances": []})).into_response();
+    }
+    Json(json!({
+        "allowances": [
+            {
+                "granter": "akash1basicgranter",
+                "grantee": OWNER,
+                "allowance": {
+                    "@type": "/cosmos.feegrant.v1beta1.BasicAllowance",
+                    "spend_limit": [{"denom": "uakt", "amount": "1000000"}],
+                    "expiration": "2030-01-01T00:00:00Z"
+                }
+            },
+            {
+                "granter": "akash1filteredgranter",
+                "grantee": OWNER,
+                "allowance": {
+                    "@type": "/cosmos.feegrant.v1beta1.AllowedMsgAllowance",
+                    "allowance": {
+                        "@type": "/cosmos.feegrant.v1beta1.BasicAllowance",
+                        "spend_limit": [
+                            {"denom": "uact", "amount": "9"},
+                            {"denom": "uakt", "amount": "500"}
+                        ],
+                        "expiration": null
+                    },
+                    "allowed_messages": ["/akash.market.v1beta4.MsgCreateLease"]
+                }
+            }
+        ]
+    }))
+    .into_response()
+}
+
+async fn latest_block(State(state): State<Arc<NodeState>>) -> Response {
+    if let Some(failure) = injected_failure(&state) {
+        return failure;
+    }
+    Json(json!({"block": {"header": {"chain_id": CHAIN_ID, "height": BLOCK_HEIGHT.to_string()}}}))
+        .into_response()
+}
+
+async fn broadcast(State(state): State<Arc<NodeState>>, Json(req): Json<Value>) -> Response {
+    assert_eq!(req["mode"], "BROADCAST_MODE_SYNC");
+    let tx_bytes = match req["tx_bytes"].as_str().map(|b| BASE64.decode(b)) {
+        Some(Ok(bytes)) => bytes,
+        _ => return (StatusCode::BAD_REQUEST, "invalid tx_bytes").into_response(),
+    };
+    let txhash = tx_hash(&tx_bytes);
+    state.broadcasts.lock().await.push(tx_bytes);
+
+    let code = state.broadcast_code.load(Ordering::SeqCst);
+    let raw_log = if code == 0 { "" } else { "insufficient funds" };
+    Json(json!({"tx_response": {"txhash": txhash, "code": code, "raw_log": raw_log}}))
+        .into_response()
+}
+
+async fn tx_by_hash(State(state): State<Arc<NodeState>>, Path(hash): Path<String>) -> Response {
+    let included = state
+        .broadcasts
+        .lock()
+        .await
+        .iter()
+        .any(|tx| tx_hash(tx) == hash);
+    if !included {
+        return (StatusCode::NOT_FOUND, Json(json!({"code": 5, "message": "tx not found"})))
+            .into_response();
+    }
+    Json(json!({"tx_response": {"txhash": hash, "height": BLOCK_HEIGHT.to_string(), "code": 0, "raw_log": ""}}))
+        .into_response()
+}
+
+/// Bank query service answering `Balance`; everything else is unimplemented.
+struct MockBank;
+
+fn unimplemented<T>() -> Result<GrpcResponse<T>, Status> {
+    Err(Status::unimplemented("not served by MockAkashNode"))
+}
+
+#[tonic::async_trait]
+impl BankQuery for MockBank {
+    async fn balance(
+        &self,
+        request: Request<QueryBalanceRequest>,
+    ) -> Result<GrpcResponse<QueryBalanceResponse>, Status> {
+        let req = request.into_inner();
+        let amount = if req.address == OWNER { BALANCE_UAKT } else { "" };
+        Ok(GrpcResponse::new(QueryBalanceResponse {
+            balance: Some(Coin {
+                denom: req.denom,
+                amount: amount.to_string(),
+            }),
+        }))
+    }
+
+    async fn all_balances(
+        &self,
+        _: Request<QueryAllBalancesRequest>,
+    ) -> Result<GrpcResponse<QueryAllBalancesResponse>, Status> {
+        unimplemented()
+    }
+
+    async fn spendable_balances(
+        &self,
+        _: Request<QuerySpendableBalancesRequest>,
+    ) -> Result<GrpcResponse<QuerySpendableBalancesResponse>, Status> {
+        unimplemented()
+    }
+
+    async fn spendable_balance_by_denom(
+        &self,
+        _: Request<QuerySpendableBalanceByDenomRequest>,
+    ) -> Result<GrpcResponse<QuerySpendableBalanceByDenomResponse>, Status> {
+        unimplemented()
+    }
+
+    async fn total_supply(
+        &self,
+        _: Request<QueryTotalSupplyRequest>,
+    ) -> Result<GrpcResponse<QueryTotalSupplyResponse>, Status> {
+        unimplemented()
+    }
+
+    async fn supply_of(
+        &self,
+        _: Request<QuerySupplyOfRequest>,
+    ) -> Result<GrpcResponse<QuerySupplyOfResponse>, Status> {
+        unimplemented()
+    }
+
+    async fn params(
+        &self,
+        _: Request<QueryParamsRequest>,
+    ) -> Result<GrpcResponse<QueryParamsResponse>, Status> {
+        unimplemented()
+    }
+
+    async fn denom_metadata(
+        &self,
+        _: Request<QueryDenomMetadataRequest>,
+    ) -> Result<GrpcResponse<QueryDenomMetadataResponse>, Status> {
+        unimplemented()
+    }
+
+    async fn denoms_metadata(
+        &self,
+        _: Request<QueryDenomsMetadataRequest>,
+    ) -> Result<GrpcResponse<QueryDenomsMetadataResponse>, Status> {
+        unimplemented()
+    }
+
+    async fn denom_owners(
+        &self,
+        _: Request<QueryDenomOwnersRequest>,
+    ) -> Result<GrpcResponse<QueryDenomOwnersResponse>, Status> {
+        unimplemented()
+    }
+
+    async fn send_enabled(
+        &self,
+        _: Request<QuerySendEnabledRequest>,
+    ) -> Result<GrpcResponse<QuerySendEnabledResponse>, Status> {
+        unimplemented()
+    }
+}
+
+/// A freshly generated wallet and its compressed public key.
+fn signer() -> (TransactionSigner, Vec<u8>) {
+    let gen = KeyGenerator::new();
+    let keypair = gen
+        .derive_keypair(&gen.generate_mnemonic().unwrap())
+        .unwrap();
+    let public_key = keypair.public_key.clone();
+    (TransactionSigner::new(keypair), public_key)
+}
+
+// ============================================================================
+// Query paths
+// ============================================================================
+
+#[tokio::test]
+async fn account_info_unwraps_base_account() {
+    let node = MockAkashNode::start().await;
+    let account = node.client().get_account_info(OWNER).await.unwrap();
+    assert_eq!(account.account_number, ACCOUNT_NUMBER);
+    assert_eq!(account.sequence, SEQUENCE);
+
+    assert!(node.client().get_account_info("akash1unknown").await.is_err());
+}
+
+#[tokio::test]
+async fn balance_is_queried_over_grpc() {
+    let node = MockAkashNode::start().await;
+    let balance = node.client().query_balance(OWNER).await.unwrap();
+    assert_eq!(balance.denom, "uakt");
+    assert_eq!(balance.amount, BALANCE_UAKT);
+
+    // Empty amounts from the node are normalised to zero
+    let empty = node.client().query_balance("akash1empty").await.unwrap();
+    assert_eq!(empty.amount, "0");
+}
+
+#[tokio::test]
+async fn deployments_are_filtered_by_owner() {
+    let node = MockAkashNode::start().await;
+    let deployments = node.client().query_deployments(OWNER).await.unwrap();
+    assert_eq!(deployments.total, 2);
+    let deployments = deployments.items;
+    assert_eq!(deployments.len(), 2);
+    assert_eq!(deployments[0].dseq, 100);
+    assert_eq!(deployments[0].state, "active");
+    assert_eq!(deployments[1].state, "closed");
+
+    let queries = node.queries("deployments").await;
+    assert_eq!(queries[0]["filters.owner"], OWNER);
+}
+
+#[tokio::test]
+async fn escrow_balance_includes_depositor_funds() {
+    let node = MockAkashNode::start().await;
+    let escrow = node.client().query_escrow(OWNER, 100).await.unwrap();
+    assert_eq!(escrow.dseq, 100);
+    assert_eq!(escrow.balance, 4_500_000);
+    assert_eq!(escrow.denom, "uakt");
+    assert_eq!(escrow.state, "open");
+
+    let queries = node.queries("deployment_info").await;
+    assert_eq!(queries[0]["id.owner"], OWNER);
+    assert_eq!(queries[0]["id.dseq"], "100");
+
+    assert!(node.client().query_escrow(OWNER, 999).await.is_err());
+}
+
+#[tokio::test]
+async fn provider_host_uri_is_resolved_from_its_address() {
+    let node = MockAkashNode::start().await;
+    let host_uri = node.client().query_provider_host(PROVIDER).await.unwrap();
+    assert_eq!(host_uri, PROVIDER_HOST_URI);
+
+    assert!(node.client().query_provider_host("akash1unknown").await.is_err());
+}
+
+#[tokio::test]
+async fn provider_certificates_are_decoded_from_the_cert_module() {
+    let node = MockAkashNode::start().await;
+    let certs = node.client().query_certificates(PROVIDER).await.unwrap();
+    assert_eq!(certs, vec![PROVIDER_CERT_PEM.to_string()]);
+
+    let queries = node.queries("certificates").await;
+    assert_eq!(queries[0]["filter.state"], "valid");
+
+    assert!(node.client().query_certificates("akash1unknown").await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn certificate_is_confirmed_only_once_listed_on_chain() {
+    let node = MockAkashNode::start().await;
+    let client = node.client();
+    let (signer, _) = signer();
+    let tx_bytes = signer
+        .create_signed_tx(vec![], CHAIN_ID, ACCOUNT_NUMBER, SEQUENCE, 200_000, 5_000, "")
+        .unwrap();
+    let result = client.broadcast_tx(&tx_bytes).await.unwrap();
+
+    client
+        .confirm_certificate(&result.txhash, PROVIDER, PROVIDER_CERT_PEM, 5)
+        .await
+        .unwrap();
+    let missing = client
+        .confirm_certificate(&result.txhash, "akash1unknown", PROVIDER_CERT_PEM, 5)
+        .await
+        .unwrap_err();
+    assert!(missing.to_string().contains("not on chain"));
+}
+
+#[tokio::test]
+async fn bids_are_parsed_for_a_deployment() {
+    let node = MockAkashNode::start().await;
+    let bids = node.client().query_bids(OWNER, 100).await.unwrap().items;
+    assert_eq!(bids.len(), 1);
+    let bid = &bids[0];
+    assert_eq!(bid.provider, PROVIDER);
+    assert_eq!((bid.dseq, bid.gseq, bid.oseq), (100, 1, 1));
+    assert_eq!(bid.price_denom, "uakt");
+    assert_eq!(bid.price_amount, "12.500000000000000000");
+    assert_eq!(bid.state, "open");
+
+    let queries = node.queries("bids").await;
+    assert_eq!(queries[0]["filters.owner"], OWNER);
+    assert_eq!(queries[0]["id.dseq"], "100");
+}
+
+#[tokio::test]
+async fn leases_are_parsed() {
+    let node = MockAkashNode::start().await;
+    let leases = node.client().query_leases(OWNER).await.unwrap().items;
+    assert_eq!(leases[0].owner, OWNER);
+    assert_eq!(leases[0].provider, PROVIDER);
+    assert_eq!(leases[0].dseq, 100);
+    assert_eq!(leases[0].state, "active");
+}
+
+#[tokio::test]
+async fn leases_are_collected_across_pages() {
+    let node = MockAkashNode::start().await;
+    let leases = node.client().query_leases(OWNER).await.unwrap();
+    assert_eq!(leases.total, LEASE_COUNT as u64);
+    assert!(!leases.is_truncated());
+    let dseqs: Vec<u64> = leases.items.iter().map(|l| l.dseq).collect();
+    assert_eq!(dseqs, (100..100 + LEASE_COUNT as u64).collect::<Vec<_>>());
+
+    // The first page asks for a total; later pages follow next_key
+    let queries = node.queries("leases").await;
+    assert_eq!(queries.len(), 3);
+    assert_eq!(queries[0]["pagination.count_total"], "true");
+    assert!(!queries[0].contains_key("pagination.key"));
+    assert_eq!(BASE64.decode(&queries[2]["pagination.key"]).unwrap(), b"40");
+}
+
+#[tokio::test]
+async fn fee_allowances_unwrap_allowed_msg_allowance() {
+    let node = MockAkashNode::start().await;
+    let allowances = node.client().query_fee_allowances(OWNER).await.unwrap();
+    assert_eq!(allowances.len(), 2);
+
+    assert_eq!(allowances[0].granter, "akash1basicgranter");
+    assert_eq!(allowances[0].spend_limit.as_ref().unwrap().amount, "1000000");
+    assert_eq!(allowances[0].expiration.as_deref(), Some("2030-01-01T00:00:00Z"));
+
+    // Nested BasicAllowance: only the uakt limit is picked up
+    assert_eq!(allowances[1].granter, "akash1filteredgranter");
+    assert_eq!(allowances[1].spend_limit.as_ref().unwrap().amount, "500");
+    assert!(allowances[1].expiration.is_none());
+
+    let none = node.client().query_fee_allowances("akash1nogrant").await.unwrap();
+    assert!(none.is_empty());
+}
+
+#[tokio::test]
+async fn block_height_is_parsed() {
+    let node = MockAkashNode::start().await;
+    assert_eq!(node.client().get_block_height().await.unwrap(), BLOCK_HEIGHT);
+}
+
+#[tokio::test]
+async fn queries_retry_through_transient_failures() {
+    let node = MockAkashNode::start().await;
+    node.fail_next(2);
+    let leases = node.client().with_max_retries(2).query_leases(OWNER).await.unwrap();
+    assert_eq!(leases.items.len(), LEASE_COUNT);
+
+    node.fail_next(10);
+    assert!(node.client().with_max_retries(1).query_leases(OWNER).await.is_err());
+}
+
+#[tokio::test]
+async fn queries_fail_over_to_fallback_node() {
+    let node = MockAkashNode::start().await;
+    // Primary endpoints refuse connections; the mock is only a fallback
+    let client = AkashClient::new("http://127.0.0.1:9".to_string(), "http://127.0.0.1:9".to_string())
+        .with_fallbacks(std::slice::from_ref(&node.url), std::slice::from_ref(&node.grpc_url))
+        .with_max_retries(0);
+
+    assert_eq!(client.get_block_height().await.unwrap(), BLOCK_HEIGHT);
+    assert_eq!(client.query_balance(OWNER).await.unwrap().amount, BALANCE_UAKT);
+}
+
+// ============================================================================
+// Broadcast paths
+// ============================================================================
+
+#[tokio::test]
+async fn deployment_tx_is_signed_and_broadcast() {
+    let node = MockAkashNode::start().await;
+    let client = node.client();
+    let (signer, _) = signer();
+    let address = signer.address().unwrap();
+
+    let account = client.get_account_info(OWNER).await.unwrap();
+    let dseq = client.get_block_height().await.unwrap();
+    let sdl = SdlFile::parse(DEFAULT_SDL).unwrap();
+    let msg = create_deployment_msg(&address, dseq, &sdl, "uakt").unwrap();
+    let tx_bytes = signer
+        .create_signed_tx(
+            vec![TransactionSigner::encode_msg(&msg).unwrap()],
+            CHAIN_ID,
+            account.account_number,
+            account.sequence,
+            500_000,
+            12_500,
+            "",
+        )
+        .unwrap();
+
+    let result = client.broadcast_tx(&tx_bytes).await.unwrap();
+    assert_eq!(result.code, 0);
+    assert_eq!(result.txhash, tx_hash(&tx_bytes));
+
+    // The node received exactly what was signed, with the queried sequence
+    let broadcasts = node.broadcasts().await;
+    assert_eq!(broadcasts, vec![tx_bytes]);
+    let tx = Tx::from_bytes(&broadcasts[0]).unwrap();
+    assert_eq!(tx.body.messages.len(), 1);
+    assert_eq!(
+        tx.body.messages[0].type_url,
+        "/akash.deployment.v1beta3.MsgCreateDeployment"
+    );
+    assert_eq!(tx.auth_info.signer_infos[0].sequence, SEQUENCE);
+    assert_eq!(tx.auth_info.fee.gas_limit, 500_000);
+    assert_eq!(tx.signatures.len(), 1);
+
+    let included = client.wait_for_tx(&result.txhash, 5).await.unwrap();
+    assert_eq!(included.txhash, result.txhash);
+    assert_eq!(included.code, 0);
+}
+
+#[tokio::test]
+async fn update_tx_is_broadcast() {
+    let node = MockAkashNode::start().await;
+    let client = node.client();
+    let (signer, _) = signer();
+    let address = signer.address().unwrap();
+
+    let sdl = SdlFile::parse(DEFAULT_SDL).unwrap();
+    let msg = update_deployment_msg(&address, 100, &sdl).unwrap();
+    let tx_bytes = signer
+        .create_signed_tx(
+            vec![TransactionSigner::encode_msg(&msg).unwrap()],
+            CHAIN_ID,
+            ACCOUNT_NUMBER,
+            SEQUENCE,
+            200_000,
+            5_000,
+            "",
+        )
+        .unwrap();
+
+    let result = client.broadcast_tx(&tx_bytes).await.unwrap();
+    assert_eq!(result.code, 0);
+    let tx = Tx::from_bytes(&node.broadcasts().await[0]).unwrap();
+    assert_eq!(
+        tx.body.messages[0].type_url,
+        "/akash.deployment.v1beta3.MsgUpdateDeployment"
+    );
+}
+
+#[tokio::test]
+async fn rejected_broadcast_surfaces_code_and_log() {
+    let node = MockAkashNode::start().await;
+    node.reject_broadcasts(5);
+    let (signer, _) = signer();
+    let tx_bytes = signer
+        .create_signed_tx(vec![], CHAIN_ID, ACCOUNT_NUMBER, SEQUENCE, 200_000, 5_000, "")
+        .unwrap();
+
+    let result = node.client().broadcast_tx(&tx_bytes).await.unwrap();
+    assert_eq!(result.code, 5);
+    assert_eq!(result.raw_log, "insufficient funds");
+}
+
+#[tokio::test]
+async fn broadcast_is_not_retried() {
+    let node = MockAkashNode::start().await;
+    // Primary refuses connections; a fallback must never receive the tx
+    let client = AkashClient::new("http://127.0.0.1:9".to_string(), node.grpc_url.clone())
+        .with_fallbacks(std::slice::from_ref(&node.url), &[]);
+    let (signer, _) = signer();
+    let tx_bytes = signer
+        .create_signed_tx(vec![], CHAIN_ID, ACCOUNT_NUMBER, SEQUENCE, 200_000, 5_000, "")
+        .unwrap();
+
+    assert!(client.broadcast_tx(&tx_bytes).await.is_err());
+    assert!(node.broadcasts().await.is_empty());
+}
+
+#[tokio::test]
+async fn offline_signed_tx_is_broadcast() {
+    let node = MockAkashNode::start().await;
+    let client = node.client();
+    let (signer, public_key) = signer();
+    let address = signer.address().unwrap();
+
+    // Online side: watch-only export using chain state from the node
+    let account = client.get_account_info(OWNER).await.unwrap();
+    let sdl = SdlFile::parse(DEFAULT_SDL).unwrap();
+    let msg = create_deployment_msg(&address, 100, &sdl, "uakt").unwrap();
+    let unsigned = UnsignedTx::build(
+        vec![TransactionSigner::encode_msg(&msg).unwrap()],
+        &address,
+        &public_key,
+        CHAIN_ID,
+        account.account_number,
+        account.sequence,
+        500_000,
+        12_500,
+        "",
+    )
+    .unwrap()
+    .with_dseq(100);
+
+    // Offline side signs; online side verifies and broadcasts
+    let signed = signer.sign_unsigned(&unsigned).unwrap();
+    signed.verify_matches(&unsigned).unwrap();
+    let tx_bytes = signed.tx_bytes().unwrap();
+    let result = client.broadcast_tx(&tx_bytes).await.unwrap();
+
+    assert_eq!(result.code, 0);
+    assert_eq!(node.broadcasts().await, vec![tx_bytes]);
+}
@@ -20,6 +20,20 @@ SUPPORTED_MODELS = {
     "google/translategemma-27b-it",
 }
 
+# Target languages with a formal/informal register (T-V pronouns or
+# honorific verb forms) that the prompt can ask for
+FORMALITY_LANGUAGES = [
+    "bg", "ca", "cs", "de", "el", "es", "fa", "fr", "hi", "hr", "hu", "id",
+    "it", "ja", "ko", "mk", "nl", "pl", "pt", "pt-BR", "ro", "ru", "sk",
+    "sl", "sr", "tr", "uk",
+]
+
+# Instruction placed ahead of the source text for a requested register
+FORMALITY_INSTRUCTIONS = {
+    "formal": "Use a formal, polite register (e.g. formal pronouns and honorifics).",
+    "informal": "Use an informal, casual register (e.g. familiar pronouns, no honorifics).",
+}
+
 # Map common dtype strings to torch dtypes
 DTYPE_MAP = {
     "bfloat16": torch.bfloat16,
@@ -105,6 +119,7 @@ class TranslateGemmaTranslator:
         text: str,
         source_lang: str,
         target_lang: str,
+        formality: str = "default",
     ) -> str:
         """
         Translate text from source language to target language.
@@ -113,6 +128,8 @@ class TranslateGemmaTranslator:
             text: Text to translate
             source_lang: Source language code (ISO 639-1, e.g., 'en', 'es', 'zh')
             target_lang: Target language code (ISO 639-1)
+            formality: 'default', 'formal' or 'informal'; ignored for
+                targets outside FORMALITY_LANGUAGES
 
         Returns:
             Translated text
@@ -140,13 +157,17 @@ class TranslateGemmaTranslator:
         ]
 
         # Process input
-        inputs = self.processor.apply_chat_template(
-            messages,
-            tokenize=True,
-            add_generation_prompt=True,
-            return_dict=True,
-            return_tensors="pt",
-        )
+        instruction = FORMALITY_INSTRUCTIONS.get(formality)
+        if instruction and self.supports_formality(target_lang):
+            inputs = self._prompt_with_instruction(messages, text, instruction)
+        else:
+            inputs = self.processor.apply_chat_template(
+                messages,
+                tokenize=True,
+                add_generation_prompt=True,
+                return_dict=True,
+                return_tensors="pt",
+            )
 
         # Move to model device
         inputs = {
@@ -173,6 +194,26 @@ class TranslateGemmaTranslator:
 
         return translated.strip()
 
+    def _prompt_with_instruction(self, messages: list, text: str, instruction: str):
+        """
+        Tokenized chat prompt with `instruction` placed right before the
+        source text, since TranslateGemma's template has no slot for it.
+        """
+        prompt = self.processor.apply_chat_template(
+            messages,
+            tokenize=False,
+            add_generation_prompt=True,
+        )
+        at = prompt.rfind(text)
+        if at != -1:
+            prompt = f"{prompt[:at]}{instruction}\n\n{prompt[at:]}"
+        else:
+            logger.warning("Source text not found in prompt; translating without register")
+        # The rendered template already starts with <bos>
+        return dict(
+            self.processor(text=prompt, add_special_tokens=False, return_tensors="pt")
+        )
+
     def translate_batch(
         self,
         texts: list[str],
@@ -206,8 +247,19 @@ class TranslateGemmaTranslator:
             "it", "ja", "kn", "ko", "lv", "lt", "mk", "ms", "ml", "mr",
             "no", "fa", "pl", "pt", "pa", "ro", "ru", "sr", "sk", "sl",
             "es", "sv", "ta", "te", "th", "tr", "uk", "ur", "vi",
+            "zh-Hans", "zh-Hant", "pt-BR",
         ]
 
+    @property
+    def formality_languages(self) -> list[str]:
+        """Return target language codes whose register can be requested."""
+        return FORMALITY_LANGUAGES
+
+    def supports_formality(self, lang_code: str) -> bool:
+        """Check if a formal/informal register can be requested for a target."""
+        base_code = lang_code.split("_")[0].split("-")[0].lower()
+        return lang_code in FORMALITY_LANGUAGES or base_code in FORMALITY_LANGUAGES
+
     def is_language_supported(self, lang_code: str) -> bool:
         """Check if a language is supported."""
         # Handle regional variants (en_US -> en)
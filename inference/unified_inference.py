@@ -16,16 +16,17 @@ import logging
 import os
 import time
 from contextlib import asynccontextmanager
-from typing import Optional
+from typing import Dict, Literal, Optional
 
 import numpy as np
 from dotenv import load_dotenv
 from fastapi import FastAPI, HTTPException, WebSocket, WebSocketDisconnect
 from fastapi.middleware.cors import CORSMiddleware
+from fastapi.responses import StreamingResponse
 from pydantic import BaseModel, Field
 
 from voice.stt import SpeechToText
-from voice.tts import TextToSpeech, audio_to_base64
+from voice.tts import TextToSpeech, encode_tts_audio
 from translator import TranslateGemmaTranslator
 from detector import LanguageDetector
 from voice_protocol import (
@@ -34,7 +35,10 @@ from voice_protocol import (
     create_result_response,
     create_error_response,
     create_pong_response,
+    strip_overlap,
     VoiceProtocolError,
+    PROTOCOL_VERSION,
+    PROTOCOL_MISMATCH_CODE,
 )
 
 # Load environment variables
@@ -53,6 +57,7 @@ STT_MODEL = os.getenv("STT_MODEL", "distil-large-v3")
 TTS_MODEL = os.getenv("TTS_MODEL", "CosyVoice2-0.5B")
 DEVICE = os.getenv("DEVICE", "cuda")
 TORCH_DTYPE = os.getenv("TORCH_DTYPE", "bfloat16")
+ENABLE_TRANSLATION = os.getenv("ENABLE_TRANSLATION", "true").lower() == "true"
 ENABLE_STT = os.getenv("ENABLE_STT", "true").lower() == "true"
 ENABLE_TTS = os.getenv("ENABLE_TTS", "true").lower() == "true"
 ENABLE_DIARIZATION = os.getenv("ENABLE_DIARIZATION", "false").lower() == "true"
@@ -61,6 +66,37 @@ HF_TOKEN = os.getenv("HF_TOKEN", "")
 # Discord audio format
 DISCORD_SAMPLE_RATE = 48000
 
+# Longest text accepted by /translate and /detect, in characters
+MAX_INPUT_CHARS = 2000
+# Most texts accepted by one /translate/stream request
+MAX_STREAM_BATCH = 1000
+# Longest translation context hint, in characters (matches the bot's limit)
+MAX_CONTEXT_CHARS = 200
+
+# TranslateGemma supports 55 languages
+SUPPORTED_LANGUAGES = [
+    ("ar", "Arabic"), ("bn", "Bengali"), ("bg", "Bulgarian"),
+    ("ca", "Catalan"), ("zh", "Chinese"),
+    ("zh-Hans", "Chinese (Simplified)"), ("zh-Hant", "Chinese (Traditional)"),
+    ("hr", "Croatian"),
+    ("cs", "Czech"), ("da", "Danish"), ("nl", "Dutch"),
+    ("en", "English"), ("et", "Estonian"), ("fi", "Finnish"),
+    ("fr", "French"), ("de", "German"), ("el", "Greek"),
+    ("gu", "Gujarati"), ("he", "Hebrew"), ("hi", "Hindi"),
+    ("hu", "Hungarian"), ("id", "Indonesian"), ("it", "Italian"),
+    ("ja", "Japanese"), ("kn", "Kannada"), ("ko", "Korean"),
+    ("lv", "Latvian"), ("lt", "Lithuanian"), ("mk", "Macedonian"),
+    ("ms", "Malay"), ("ml", "Malayalam"), ("mr", "Marathi"),
+    ("no", "Norwegian"), ("fa", "Persian"), ("pl", "Polish"),
+    ("pt", "Portuguese"), ("pt-BR", "Portuguese (Brazil)"),
+    ("pa", "Punjabi"), ("ro", "Romanian"),
+    ("ru", "Russian"), ("sr", "Serbian"), ("sk", "Slovak"),
+    ("sl", "Slovenian"), ("es", "Spanish"), ("sv", "Swedish"),
+    ("ta", "Tamil"), ("te", "Telugu"), ("th", "Thai"),
+    ("tr", "Turkish"), ("uk", "Ukrainian"), ("ur", "Urdu"),
+    ("vi", "Vietnamese"),
+]
+
 # Global model instances
 translator: Optional[TranslateGemmaTranslator] = None
 detector: Optional[LanguageDetector] = None
@@ -78,26 +114,35 @@ async def lifespan(app: FastAPI):
     logger.info(f"STT Model: {STT_MODEL} (enabled: {ENABLE_STT})")
     logger.info(f"TTS Model: {TTS_MODEL} (enabled: {ENABLE_TTS})")
     logger.info(f"Device: {DEVICE}, Dtype: {TORCH_DTYPE}")
-
-    # Load translation model (shared by both REST and voice)
-    try:
-        translator = TranslateGemmaTranslator(
-            model_id=TRANSLATE_MODEL,
-            device=DEVICE,
-            torch_dtype=TORCH_DTYPE
+    if not HF_TOKEN:
+        logger.warning(
+            "HF_TOKEN not set: speaker diarization is disabled and gated models "
+            f"({TRANSLATE_MODEL}) load only if already in the local cache"
         )
-        logger.info("TranslateGemma model loaded successfully")
-    except Exception as e:
-        logger.error(f"Failed to load TranslateGemma model: {e}")
-        translator = None
 
-    # Load language detector (for REST API)
-    try:
-        detector = LanguageDetector()
-        logger.info("Language detector loaded successfully")
-    except Exception as e:
-        logger.error(f"Failed to load language detector: {e}")
-        detector = None
+    # Load translation model (shared by both REST and voice). A replica
+    # serving only /tts skips it with ENABLE_TRANSLATION=false.
+    if ENABLE_TRANSLATION:
+        try:
+            translator = TranslateGemmaTranslator(
+                model_id=TRANSLATE_MODEL,
+                device=DEVICE,
+                torch_dtype=TORCH_DTYPE
+            )
+            logger.info("TranslateGemma model loaded successfully")
+        except Exception as e:
+            logger.error(f"Failed to load TranslateGemma model: {e}")
+            translator = None
+
+        # Load language detector (for REST API)
+        try:
+            detector = LanguageDetector()
+            logger.info("Language detector loaded successfully")
+        except Exception as e:
+            logger.error(f"Failed to load language detector: {e}")
+            detector = None
+    else:
+        logger.info("Translation disabled by configuration")
 
     # Load STT model (for voice)
     if ENABLE_STT:
@@ -106,7 +151,7 @@ async def lifespan(app: FastAPI):
                 model_size=STT_MODEL,
                 device=DEVICE,
                 compute_type="float16" if DEVICE == "cuda" else "int8",
-                enable_diarization=ENABLE_DIARIZATION,
+                enable_diarization=ENABLE_DIARIZATION and bool(HF_TOKEN),
                 hf_token=HF_TOKEN if HF_TOKEN else None,
             )
             stt.load()
@@ -168,7 +213,21 @@ app.add_middleware(
 # ============================================================================
 
 class TranslateRequest(BaseModel):
-    text: str = Field(..., description="Text to translate", max_length=2000)
+    text: str = Field(..., description="Text to translate", max_length=MAX_INPUT_CHARS)
+    source_lang: str = Field(..., description="Source language code (ISO 639-1)")
+    target_lang: str = Field(..., description="Target language code (ISO 639-1)")
+    # TranslateGemma's chat template has no slot for a domain hint, so this
+    # model accepts and ignores it; backends that support it can use it
+    context: Optional[str] = Field(
+        None, description="Domain hint, e.g. 'casual gaming chat'", max_length=MAX_CONTEXT_CHARS
+    )
+    formality: Literal["default", "formal", "informal"] = Field(
+        "default", description="Register, for target languages in formality_languages"
+    )
+
+
+class TranslateBatchRequest(BaseModel):
+    texts: list[str] = Field(..., description="Texts to translate", max_length=MAX_STREAM_BATCH)
     source_lang: str = Field(..., description="Source language code (ISO 639-1)")
     target_lang: str = Field(..., description="Target language code (ISO 639-1)")
 
@@ -178,10 +237,12 @@ class TranslateResponse(BaseModel):
     source_lang: str
     target_lang: str
     confidence: Optional[float] = None
+    # Model that produced the translation (unset when the text was passed through)
+    model: Optional[str] = None
 
 
 class DetectRequest(BaseModel):
-    text: str = Field(..., description="Text to detect language for", max_length=2000)
+    text: str = Field(..., description="Text to detect language for", max_length=MAX_INPUT_CHARS)
 
 
 class DetectResponse(BaseModel):
@@ -189,6 +250,18 @@ class DetectResponse(BaseModel):
     confidence: float
 
 
+class TTSRequest(BaseModel):
+    text: str = Field(..., description="Text to speak", max_length=MAX_INPUT_CHARS)
+    language: str = Field(..., description="Language code of the text")
+    format: Optional[str] = Field(None, description="Audio encoding: wav, pcm, opus or mp3")
+
+
+class TTSResponse(BaseModel):
+    audio: str
+    format: str
+    duration: float
+
+
 class HealthResponse(BaseModel):
     status: str
     model: str
@@ -198,6 +271,12 @@ class HealthResponse(BaseModel):
     tts_loaded: bool
     stt_model: str
     tts_model: str
+    supported_languages: list[str]
+    # Target languages whose formality the model can control
+    formality_languages: list[str] = []
+    max_input_chars: int
+    protocol_version: int
+    hf_token_configured: bool
 
 
 class LanguageInfo(BaseModel):
@@ -212,7 +291,10 @@ class LanguageInfo(BaseModel):
 @app.get("/health", response_model=HealthResponse)
 async def health_check():
     """Check service health and model status."""
-    all_critical_loaded = translator is not None and detector is not None
+    if ENABLE_TRANSLATION:
+        all_critical_loaded = translator is not None and detector is not None
+    else:
+        all_critical_loaded = tts is not None
     return HealthResponse(
         status="ok" if all_critical_loaded else "degraded",
         model=TRANSLATE_MODEL,
@@ -222,6 +304,11 @@ async def health_check():
         tts_loaded=tts is not None,
         stt_model=STT_MODEL,
         tts_model=TTS_MODEL,
+        supported_languages=[code for code, _ in SUPPORTED_LANGUAGES],
+        formality_languages=translator.formality_languages if translator else [],
+        max_input_chars=MAX_INPUT_CHARS,
+        protocol_version=PROTOCOL_VERSION,
+        hf_token_configured=bool(HF_TOKEN),
     )
 
 
@@ -246,12 +333,14 @@ async def translate(request: TranslateRequest):
         result = translator.translate(
             text=request.text,
             source_lang=request.source_lang,
-            target_lang=request.target_lang
+            target_lang=request.target_lang,
+            formality=request.formality,
         )
         return TranslateResponse(
             translated_text=result,
             source_lang=request.source_lang,
-            target_lang=request.target_lang
+            target_lang=request.target_lang,
+            model=TRANSLATE_MODEL,
         )
     except ValueError as e:
         raise HTTPException(status_code=400, detail=str(e))
@@ -260,6 +349,48 @@ async def translate(request: TranslateRequest):
         raise HTTPException(status_code=500, detail="Translation failed")
 
 
+@app.post("/translate/stream")
+async def translate_stream(request: TranslateBatchRequest):
+    """Translate a batch, streaming one NDJSON line per text as it finishes.
+
+    Each line is `{"index": i, "translated_text": ...}` or, when that text
+    failed, `{"index": i, "error": ...}`, so one bad input doesn't sink the
+    rest of the batch.
+    """
+    if translator is None:
+        raise HTTPException(
+            status_code=503,
+            detail="Translation model not loaded. Please try again later."
+        )
+    for text in request.texts:
+        if len(text) > MAX_INPUT_CHARS:
+            raise HTTPException(status_code=422, detail=f"Text longer than {MAX_INPUT_CHARS} characters")
+
+    async def lines():
+        for index, text in enumerate(request.texts):
+            line = {"index": index}
+            if request.source_lang == request.target_lang:
+                line["translated_text"] = text
+            else:
+                try:
+                    # Off the event loop so each line is flushed as it's ready
+                    line["translated_text"] = await asyncio.to_thread(
+                        translator.translate,
+                        text=text,
+                        source_lang=request.source_lang,
+                        target_lang=request.target_lang,
+                    )
+                    line["model"] = TRANSLATE_MODEL
+                except ValueError as e:
+                    line["error"] = str(e)
+                except Exception as e:
+                    logger.error(f"Translation error in batch item {index}: {e}")
+                    line["error"] = "Translation failed"
+            yield json.dumps(line) + "\n"
+
+    return StreamingResponse(lines(), media_type="application/x-ndjson")
+
+
 @app.post("/detect", response_model=DetectResponse)
 async def detect_language(request: DetectRequest):
     """Detect the language of input text."""
@@ -280,30 +411,32 @@ async def detect_language(request: DetectRequest):
         raise HTTPException(status_code=500, detail="Language detection failed")
 
 
+@app.post("/tts", response_model=TTSResponse)
+async def synthesize_speech(request: TTSRequest):
+    """Synthesize speech, for bots that run TTS as its own service."""
+    if tts is None:
+        raise HTTPException(
+            status_code=503,
+            detail="TTS model not loaded. Please try again later."
+        )
+    if not request.text.strip():
+        raise HTTPException(status_code=400, detail="Text is empty")
+
+    try:
+        result = await tts.synthesize(request.text, language=request.language)
+        audio, audio_format = encode_tts_audio(
+            result.audio, result.sample_rate, request.format
+        )
+        return TTSResponse(audio=audio, format=audio_format, duration=result.duration)
+    except Exception as e:
+        logger.error(f"TTS error: {e}")
+        raise HTTPException(status_code=500, detail="Speech synthesis failed")
+
+
 @app.get("/languages", response_model=list[LanguageInfo])
 async def list_languages():
     """List all supported languages."""
-    # TranslateGemma supports 55 languages
-    languages = [
-        ("ar", "Arabic"), ("bn", "Bengali"), ("bg", "Bulgarian"),
-        ("ca", "Catalan"), ("zh", "Chinese"), ("hr", "Croatian"),
-        ("cs", "Czech"), ("da", "Danish"), ("nl", "Dutch"),
-        ("en", "English"), ("et", "Estonian"), ("fi", "Finnish"),
-        ("fr", "French"), ("de", "German"), ("el", "Greek"),
-        ("gu", "Gujarati"), ("he", "Hebrew"), ("hi", "Hindi"),
-        ("hu", "Hungarian"), ("id", "Indonesian"), ("it", "Italian"),
-        ("ja", "Japanese"), ("kn", "Kannada"), ("ko", "Korean"),
-        ("lv", "Latvian"), ("lt", "Lithuanian"), ("mk", "Macedonian"),
-        ("ms", "Malay"), ("ml", "Malayalam"), ("mr", "Marathi"),
-        ("no", "Norwegian"), ("fa", "Persian"), ("pl", "Polish"),
-        ("pt", "Portuguese"), ("pa", "Punjabi"), ("ro", "Romanian"),
-        ("ru", "Russian"), ("sr", "Serbian"), ("sk", "Slovak"),
-        ("sl", "Slovenian"), ("es", "Spanish"), ("sv", "Swedish"),
-        ("ta", "Tamil"), ("te", "Telugu"), ("th", "Thai"),
-        ("tr", "Turkish"), ("uk", "Ukrainian"), ("ur", "Urdu"),
-        ("vi", "Vietnamese"),
-    ]
-    return [LanguageInfo(code=code, name=name) for code, name in languages]
+    return [LanguageInfo(code=code, name=name) for code, name in SUPPORTED_LANGUAGES]
 
 
 # ============================================================================
@@ -322,6 +455,9 @@ async def voice_websocket(websocket: WebSocket):
         [header_length bytes: JSON header]
         [remaining bytes: raw PCM i16 LE samples]
 
+    The client's first message is {"type": "Hello", "protocol_version": N};
+    mismatched versions get a PROTOCOL_MISMATCH error and the socket is closed.
+
     Text frame format (JSON, backward compatible):
         {
             "type": "Audio",
@@ -331,11 +467,18 @@ async def voice_websocket(websocket: WebSocket):
             "username": "User",
             "audio_base64": "<base64 PCM i16 48kHz mono>",
             "sample_rate": 48000,
+            "source_language": null,
             "target_language": "en",
             "generate_tts": false,
-            "audio_hash": 12345678901234567890
+            "tts_format": "opus",
+            "audio_hash": 12345678901234567890,
+            "overlap_samples": 14400
         }
 
+    ``overlap_samples`` counts leading samples repeated from the speaker's
+    previous streamed chunk; words already transcribed from them are dropped
+    before translation.
+
     Response format (JSON text frame):
         {
             "type": "Result",
@@ -347,7 +490,8 @@ async def voice_websocket(websocket: WebSocket):
             "translated_text": "Hello world",
             "source_language": "es",
             "target_language": "en",
-            "tts_audio": "<base64 WAV>",
+            "tts_audio": "<base64 audio>",
+            "tts_format": "opus",
             "latency_ms": 450,
             "audio_hash": 12345678901234567890
         }
@@ -360,8 +504,12 @@ async def voice_websocket(websocket: WebSocket):
         "type": "Ready",
         "stt_models": [STT_MODEL] if stt else [],
         "tts_models": [TTS_MODEL] if tts else [],
+        "protocol_version": PROTOCOL_VERSION,
     })
 
+    # Last transcript per speaker, to drop words repeated by chunk overlap
+    last_transcripts: Dict[str, str] = {}
+
     try:
         while True:
             # Receive message (can be text or binary)
@@ -382,12 +530,17 @@ async def voice_websocket(websocket: WebSocket):
                         start_time = time.time()
 
                         try:
-                            result = await process_audio_binary(header, samples)
+                            result = await process_audio_binary(header, samples, last_transcripts)
                             result["latency_ms"] = int((time.time() - start_time) * 1000)
                             await websocket.send_text(result)
                         except Exception as e:
-                            logger.error(f"Audio processing error: {e}", exc_info=True)
-                            error_response = create_error_response(str(e), "PROCESSING_ERROR")
+                            trace_id = header.get("trace_id")
+                            logger.error(
+                                f"Audio processing error: {e}, trace={trace_id}", exc_info=True
+                            )
+                            error_response = create_error_response(
+                                str(e), "PROCESSING_ERROR", trace_id
+                            )
                             await websocket.send_text(error_response)
                     else:
                         logger.warning(f"Unknown binary message type: {msg_type}")
@@ -400,6 +553,22 @@ async def voice_websocket(websocket: WebSocket):
                     message = parse_text_frame(text_data)
                     msg_type = message.get("type")
 
+                    if msg_type == "Hello":
+                        client_version = message.get("protocol_version")
+                        if client_version != PROTOCOL_VERSION:
+                            logger.error(
+                                f"Rejecting voice client speaking protocol v{client_version}; "
+                                f"this service speaks v{PROTOCOL_VERSION}"
+                            )
+                            await websocket.send_text(create_error_response(
+                                f"Unsupported protocol version {client_version}, "
+                                f"expected {PROTOCOL_VERSION}",
+                                PROTOCOL_MISMATCH_CODE,
+                            ))
+                            await websocket.close(code=1002)
+                            return
+                        continue
+
                     if msg_type == "Ping":
                         await websocket.send_text(create_pong_response())
                         continue
@@ -414,15 +583,19 @@ async def voice_websocket(websocket: WebSocket):
                         start_time = time.time()
 
                         try:
-                            result = await process_audio_text(message)
+                            result = await process_audio_text(message, last_transcripts)
                             result["latency_ms"] = int((time.time() - start_time) * 1000)
                             await websocket.send_json(result)
                         except Exception as e:
-                            logger.error(f"Audio processing error: {e}", exc_info=True)
+                            trace_id = message.get("trace_id")
+                            logger.error(
+                                f"Audio processing error: {e}, trace={trace_id}", exc_info=True
+                            )
                             await websocket.send_json({
                                 "type": "Error",
                                 "message": str(e),
                                 "code": "PROCESSING_ERROR",
+                                "trace_id": trace_id,
                             })
                 else:
                     logger.warning(f"Unknown WebSocket message format: {raw_message.keys()}")
@@ -438,13 +611,32 @@ async def voice_websocket(websocket: WebSocket):
         logger.error(f"Voice WebSocket error: {e}", exc_info=True)
 
 
-async def process_audio_binary(header: dict, samples: np.ndarray) -> str:
+def _overlap_context(message: dict, last_transcripts: Dict[str, str]) -> Optional[str]:
+    """Previous transcript from this speaker if the chunk starts with overlap."""
+    if not message.get("overlap_samples"):
+        return None
+    return last_transcripts.get(f"{message['guild_id']}:{message['user_id']}")
+
+
+def _remember_transcript(message: dict, result: dict, last_transcripts: Dict[str, str]) -> None:
+    """Keep the newest transcript for the speaker's next overlapping chunk."""
+    key = f"{message['guild_id']}:{message['user_id']}"
+    if result["original_text"]:
+        last_transcripts[key] = result["original_text"]
+    elif not message.get("overlap_samples"):
+        last_transcripts.pop(key, None)
+
+
+async def process_audio_binary(
+    header: dict, samples: np.ndarray, last_transcripts: Dict[str, str]
+) -> str:
     """
     Process incoming binary audio frame and return translation result.
 
     Args:
         header: Parsed JSON header with metadata
         samples: Raw PCM samples (i16)
+        last_transcripts: Per-connection transcripts, for overlap dedupe
 
     Returns:
         JSON string response (use send_text, not send_json)
@@ -454,9 +646,15 @@ async def process_audio_binary(header: dict, samples: np.ndarray) -> str:
     user_id = header["user_id"]
     username = header["username"]
     sample_rate = header.get("sample_rate", DISCORD_SAMPLE_RATE)
+    source_language = header.get("source_language")
     target_language = header.get("target_language", "en")
     generate_tts = header.get("generate_tts", False)
     audio_hash = header.get("audio_hash", 0)  # CRITICAL: Must echo back
+    started_at = header.get("started_at")
+    tts_format = header.get("tts_format")
+    stages = header.get("stages")
+    formality = header.get("formality", "default")
+    trace_id = header.get("trace_id")
 
     # Convert i16 samples to float32 for processing
     audio_float = samples.astype(np.float32) / 32768.0
@@ -464,7 +662,7 @@ async def process_audio_binary(header: dict, samples: np.ndarray) -> str:
     logger.info(
         f"Processing binary audio: {len(samples)} samples, "
         f"{len(samples) / sample_rate:.2f}s from {username}, "
-        f"hash={audio_hash}"
+        f"hash={audio_hash}, trace={trace_id}"
     )
 
     # Process audio (transcribe + translate + TTS)
@@ -475,9 +673,16 @@ async def process_audio_binary(header: dict, samples: np.ndarray) -> str:
         channel_id=channel_id,
         user_id=user_id,
         username=username,
+        source_language=source_language,
         target_language=target_language,
         generate_tts=generate_tts,
+        tts_format=tts_format,
+        overlap_context=_overlap_context(header, last_transcripts),
+        stages=stages,
+        formality=formality,
+        trace_id=trace_id,
     )
+    _remember_transcript(header, result, last_transcripts)
 
     # Create response JSON (echo back audio_hash for cache)
     return create_result_response(
@@ -492,15 +697,20 @@ async def process_audio_binary(header: dict, samples: np.ndarray) -> str:
         tts_audio=result["tts_audio"],
         latency_ms=result.get("latency_ms", 0),
         audio_hash=audio_hash,  # Echo back for cache correlation
+        confidence=result.get("confidence"),
+        started_at=started_at,
+        tts_format=result.get("tts_format"),
+        trace_id=trace_id,
     )
 
 
-async def process_audio_text(message: dict) -> dict:
+async def process_audio_text(message: dict, last_transcripts: Dict[str, str]) -> dict:
     """
     Process incoming text audio frame (legacy base64 format).
 
     Args:
         message: Parsed JSON message with base64 audio
+        last_transcripts: Per-connection transcripts, for overlap dedupe
 
     Returns:
         Dict response (use send_json)
@@ -511,9 +721,14 @@ async def process_audio_text(message: dict) -> dict:
     username = message["username"]
     audio_base64 = message["audio_base64"]
     sample_rate = message.get("sample_rate", DISCORD_SAMPLE_RATE)
+    source_language = message.get("source_language")
     target_language = message.get("target_language", "en")
     generate_tts = message.get("generate_tts", False)
     audio_hash = message.get("audio_hash", 0)  # Optional for text frames
+    tts_format = message.get("tts_format")
+    stages = message.get("stages")
+    formality = message.get("formality", "default")
+    trace_id = message.get("trace_id")
 
     # Decode audio
     audio_bytes = base64.b64decode(audio_base64)
@@ -522,7 +737,7 @@ async def process_audio_text(message: dict) -> dict:
 
     logger.info(
         f"Processing text audio: {len(audio)} samples, "
-        f"{len(audio) / sample_rate:.2f}s from {username}"
+        f"{len(audio) / sample_rate:.2f}s from {username}, trace={trace_id}"
     )
 
     # Process audio (transcribe + translate + TTS)
@@ -533,13 +748,23 @@ async def process_audio_text(message: dict) -> dict:
         channel_id=channel_id,
         user_id=user_id,
         username=username,
+        source_language=source_language,
         target_language=target_language,
         generate_tts=generate_tts,
+        tts_format=tts_format,
+        overlap_context=_overlap_context(message, last_transcripts),
+        stages=stages,
+        formality=formality,
+        trace_id=trace_id,
     )
+    _remember_transcript(message, result, last_transcripts)
 
     # Add audio_hash if provided (for cache correlation)
     if audio_hash:
         result["audio_hash"] = audio_hash
+    if message.get("started_at"):
+        result["started_at"] = message["started_at"]
+    result["trace_id"] = trace_id
 
     return result
 
@@ -551,8 +776,14 @@ async def _process_audio_internal(
     channel_id: str,
     user_id: str,
     username: str,
+    source_language: Optional[str],
     target_language: str,
     generate_tts: bool,
+    tts_format: Optional[str] = None,
+    overlap_context: Optional[str] = None,
+    stages: Optional[list] = None,
+    formality: str = "default",
+    trace_id: Optional[str] = None,
 ) -> dict:
     """
     Internal audio processing logic shared by binary and text handlers.
@@ -564,22 +795,43 @@ async def _process_audio_internal(
         channel_id: Discord channel ID
         user_id: Discord user ID
         username: Discord username
+        source_language: Spoken language code, or None/"auto" to detect it
         target_language: Target language code
         generate_tts: Whether to generate TTS audio
+        tts_format: Requested TTS encoding (wav, pcm, opus, mp3)
+        overlap_context: Previous chunk's transcript when this chunk starts
+            with its audio; repeated leading words are dropped
+        stages: Stages the client asked for ("transcribe", "translate",
+            "tts"); None runs them all, with TTS per generate_tts
+        formality: Register of the translation ("default", "formal",
+            "informal"), where the target language has one
+        trace_id: Per-segment trace id from the bot, added to each stage's log
 
     Returns:
         Dict with transcription/translation results
     """
+    if stages is not None:
+        generate_tts = "tts" in stages
+    translate = stages is None or "translate" in stages
+
     # Step 1: Transcribe
     if stt is None:
         raise RuntimeError("STT model not loaded")
 
-    transcription = stt.transcribe(audio_float, sample_rate=sample_rate)
+    language_hint = None if source_language in (None, "", "auto") else source_language
+    transcription = stt.transcribe(
+        audio_float, sample_rate=sample_rate, language=language_hint
+    )
     original_text = transcription.text
     source_language = transcription.language
+    if overlap_context:
+        deduped = strip_overlap(overlap_context, original_text)
+        if deduped != original_text:
+            logger.debug(f"Dropped overlap: {original_text!r} -> {deduped!r}")
+        original_text = deduped
 
     if not original_text.strip():
-        logger.info("No speech detected in audio")
+        logger.info(f"No speech detected in audio, trace={trace_id}")
         return {
             "type": "Result",
             "guild_id": guild_id,
@@ -591,37 +843,46 @@ async def _process_audio_internal(
             "source_language": source_language,
             "target_language": target_language,
             "tts_audio": None,
+            "confidence": transcription.confidence,
         }
 
-    logger.info(f"Transcribed ({source_language}): {original_text}")
+    logger.info(f"Transcribed ({source_language}), trace={trace_id}: {original_text}")
 
     # Step 2: Translate (if needed)
     translated_text = original_text
-    if source_language != target_language and translator is not None:
+    if not translate and source_language != target_language:
+        # Transcript only: label it with the language it is actually in
+        logger.debug("Translation not requested, returning transcript")
+        target_language = source_language
+    elif source_language != target_language and translator is not None:
         try:
             translated_text = translator.translate(
                 original_text,
                 source_lang=source_language,
                 target_lang=target_language,
+                formality=formality,
             )
-            logger.info(f"Translated ({target_language}): {translated_text}")
+            logger.info(f"Translated ({target_language}), trace={trace_id}: {translated_text}")
         except Exception as e:
-            logger.error(f"Translation failed: {e}")
+            logger.error(f"Translation failed: {e}, trace={trace_id}")
             # Fall back to original text
             translated_text = original_text
 
     # Step 3: Synthesize TTS (if requested)
     tts_audio = None
+    tts_audio_format = None
     if generate_tts and tts is not None and translated_text.strip():
         try:
             tts_result = await tts.synthesize(
                 translated_text,
                 language=target_language,
             )
-            tts_audio = audio_to_base64(tts_result.audio, tts_result.sample_rate)
-            logger.info(f"Generated TTS: {tts_result.duration:.2f}s")
+            tts_audio, tts_audio_format = encode_tts_audio(
+                tts_result.audio, tts_result.sample_rate, tts_format
+            )
+            logger.info(f"Generated TTS: {tts_result.duration:.2f}s, trace={trace_id}")
         except Exception as e:
-            logger.error(f"TTS failed: {e}")
+            logger.error(f"TTS failed: {e}, trace={trace_id}")
 
     return {
         "type": "Result",
@@ -634,6 +895,8 @@ async def _process_audio_internal(
         "source_language": source_language,
         "target_language": target_language,
         "tts_audio": tts_audio,
+        "tts_format": tts_audio_format,
+        "confidence": transcription.confidence,
     }
 
 
@@ -647,5 +910,6 @@ if __name__ == "__main__":
         "unified_inference:app",
         host=host,
         port=port,
+        ws_max_size=int(os.getenv("WS_MAX_SIZE", str(16 * 1024 * 1024))),
         reload=os.getenv("DEBUG", "false").lower() == "true"
     )
@@ -38,6 +38,14 @@ class TranscriptionResult:
     duration: float
     processing_time_ms: int
 
+    @property
+    def confidence(self) -> Optional[float]:
+        """Mean per-segment token probability (0.0-1.0), or None if unknown."""
+        scores = [s.confidence for s in self.segments if s.confidence is not None]
+        if not scores:
+            return None
+        return float(np.mean(np.exp(scores)))
+
 
 class SpeechToText:
     """
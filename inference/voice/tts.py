@@ -18,6 +18,11 @@ logger = logging.getLogger(__name__)
 OUTPUT_SAMPLE_RATE = 24000  # CosyVoice outputs 24kHz
 
 
+def base_language(code: str) -> str:
+    """Primary subtag of a language code (zh-Hant -> zh, pt-BR -> pt)."""
+    return code.replace("_", "-").split("-")[0].lower()
+
+
 @dataclass
 class TTSResult:
     """Text-to-speech synthesis result."""
@@ -158,7 +163,7 @@ class TextToSpeech:
                 pass
         else:
             # Standard synthesis
-            voice = voice_map.get(language, "English")
+            voice = voice_map.get(base_language(language), "English")
             for audio_chunk in self.model.inference_sft(text, voice):
                 pass
 
@@ -186,10 +191,13 @@ class TextToSpeech:
             "ja": "ja-JP-NanamiNeural",
             "ko": "ko-KR-SunHiNeural",
             "zh": "zh-CN-XiaoxiaoNeural",
+            "zh-Hant": "zh-TW-HsiaoChenNeural",
             "ru": "ru-RU-SvetlanaNeural",
         }
 
-        voice = voice_map.get(language, "en-US-AriaNeural")
+        voice = voice_map.get(language) or voice_map.get(
+            base_language(language), "en-US-AriaNeural"
+        )
 
         communicate = edge_tts.Communicate(text, voice)
 
@@ -247,6 +255,47 @@ def audio_to_base64(audio: np.ndarray, sample_rate: int) -> str:
     return base64.b64encode(buffer.read()).decode('utf-8')
 
 
+# Raw PCM has no header, so the bot assumes this rate for it
+PCM_SAMPLE_RATE = 24000
+
+
+def encode_tts_audio(
+    audio: np.ndarray, sample_rate: int, audio_format: Optional[str] = None
+) -> Tuple[str, str]:
+    """
+    Encode TTS audio as base64 in the requested format.
+
+    Supports "wav", "pcm" (headerless int16, 24kHz only), "opus" (Ogg) and
+    "mp3". Opus and MP3 need a libsndfile build with those codecs; anything
+    that can't be produced falls back to WAV.
+
+    Returns:
+        (base64 audio, format actually produced)
+    """
+    import base64
+
+    audio_format = (audio_format or "wav").lower()
+
+    if audio_format == "pcm" and sample_rate == PCM_SAMPLE_RATE:
+        audio_int16 = (np.clip(audio, -1.0, 1.0) * 32767).astype("<i2")
+        return base64.b64encode(audio_int16.tobytes()).decode("utf-8"), "pcm"
+
+    if audio_format in ("opus", "mp3"):
+        try:
+            import soundfile as sf
+
+            buffer = io.BytesIO()
+            if audio_format == "opus":
+                sf.write(buffer, audio, sample_rate, format="OGG", subtype="OPUS")
+            else:
+                sf.write(buffer, audio, sample_rate, format="MP3")
+            return base64.b64encode(buffer.getvalue()).decode("utf-8"), audio_format
+        except Exception as e:
+            logger.warning(f"Cannot encode TTS as {audio_format}, sending WAV: {e}")
+
+    return audio_to_base64(audio, sample_rate), "wav"
+
+
 def base64_to_audio(b64_string: str) -> Tuple[np.ndarray, int]:
     """Decode base64 WAV to audio array."""
     import base64
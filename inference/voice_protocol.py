@@ -8,15 +8,29 @@ Binary frame format:
     [header_length bytes: JSON header as UTF-8]
     [remaining bytes: raw PCM samples as i16 little-endian]
 
-Text frames are still JSON (ping/pong/configure commands).
+Text frames are still JSON (hello/ping/pong/configure commands).
+
+The client opens with {"type": "Hello", "protocol_version": N}; the server
+announces its own version in Ready and rejects mismatched clients.
 """
 
 import json
+import re
 import struct
 from typing import Dict, Any, Tuple, Optional
 import numpy as np
 
 
+# Bump together with PROTOCOL_VERSION in src/voice/types.rs whenever the
+# binary frame layout or message shapes change incompatibly.
+PROTOCOL_VERSION = 1
+
+PROTOCOL_MISMATCH_CODE = "PROTOCOL_MISMATCH"
+
+# Most words a chunk overlap (a few hundred ms of audio) can repeat.
+MAX_OVERLAP_WORDS = 6
+
+
 class VoiceProtocolError(Exception):
     """Raised when binary frame parsing fails."""
     pass
@@ -109,6 +123,10 @@ def create_result_response(
     tts_audio: Optional[str],
     latency_ms: int,
     audio_hash: int,  # CRITICAL: Echo back for cache correlation
+    confidence: Optional[float] = None,
+    started_at: Optional[str] = None,
+    tts_format: Optional[str] = None,
+    trace_id: Optional[str] = None,
 ) -> str:
     """
     Create a Result response message (JSON text frame).
@@ -125,9 +143,16 @@ def create_result_response(
         translated_text: Translated text (target language)
         source_language: Detected source language code
         target_language: Target language code
-        tts_audio: Base64-encoded TTS audio (WAV), or None
+        tts_audio: Base64-encoded TTS audio, or None
         latency_ms: Total processing latency in milliseconds
         audio_hash: Audio hash from request (MUST echo back)
+        confidence: Transcription confidence (0.0-1.0), or None if unknown
+        started_at: RFC 3339 speech start time from the request header, echoed
+            back so the bot can align transcripts to wall-clock time
+        tts_format: Encoding of tts_audio (wav, pcm, opus, mp3); may differ
+            from the requested one if the encoder isn't available
+        trace_id: Per-segment trace id from the request header, echoed back
+            so bot and service logs for one utterance can be matched up
 
     Returns:
         JSON string ready to send over WebSocket
@@ -145,17 +170,56 @@ def create_result_response(
         'tts_audio': tts_audio,
         'latency_ms': latency_ms,
         'audio_hash': audio_hash,  # Echo back for cache correlation
+        'confidence': confidence,
+        'started_at': started_at,
+        'tts_format': tts_format,
+        'trace_id': trace_id,
     }
     return json.dumps(response)
 
 
-def create_error_response(message: str, code: Optional[str] = None) -> str:
+def strip_overlap(previous: str, current: str, max_words: int = MAX_OVERLAP_WORDS) -> str:
+    """
+    Drop the words at the start of a streamed chunk's transcript that repeat
+    the end of the previous chunk's transcript.
+
+    Streamed chunks start with the tail of the previous chunk's audio
+    (``overlap_samples`` in the header), so the same words are usually
+    transcribed twice. Words are compared case- and punctuation-insensitively
+    and the longest repeat of up to ``max_words`` words is removed.
+
+    Args:
+        previous: Transcript of the previous chunk from the same speaker
+        current: Transcript of the chunk that starts with the overlap
+        max_words: Longest repeat to look for
+
+    Returns:
+        ``current`` without the repeated leading words
+    """
+    def normalize(word: str) -> str:
+        return re.sub(r"[^\w]", "", word).lower()
+
+    prev_words = [normalize(w) for w in previous.split()]
+    words = current.split()
+    cur_words = [normalize(w) for w in words]
+
+    longest = min(max_words, len(prev_words), len(cur_words))
+    for n in range(longest, 0, -1):
+        if prev_words[-n:] == cur_words[:n]:
+            return " ".join(words[n:])
+    return current
+
+
+def create_error_response(
+    message: str, code: Optional[str] = None, trace_id: Optional[str] = None
+) -> str:
     """
     Create an Error response message (JSON text frame).
 
     Args:
         message: Error message
         code: Optional error code
+        trace_id: Trace id of the segment that failed, if the error is about one
 
     Returns:
         JSON string ready to send over WebSocket
@@ -164,6 +228,7 @@ def create_error_response(message: str, code: Optional[str] = None) -> str:
         'type': 'Error',
         'message': message,
         'code': code,
+        'trace_id': trace_id,
     }
     return json.dumps(response)
 
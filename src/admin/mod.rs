@@ -24,7 +24,8 @@
 //! )?;
 //!
 //! // Start admin server and wait for provisioning
-//! let admin_router = admin::transport::admin_router(Arc::new(admin_state));
+//! let admin_router =
+//!     admin::transport::admin_router(Arc::new(admin_state), &config.admin.mount_path());
 //! // ... start server ...
 //!
 //! // Wait for secrets
@@ -39,5 +40,5 @@ pub mod secrets;
 pub mod transport;
 
 pub use crypto::{CryptoError, EphemeralKeyPair};
-pub use secrets::{create_secret_store, ProvisioningStatus, SecretsPayload, SecretStore, SharedSecretStore};
+pub use secrets::{create_secret_store, InferenceTokens, ProvisioningStatus, HF_TOKEN_FEATURES, SecretsPayload, SecretStore, SharedSecretStore};
 pub use transport::{admin_router, AdminState};
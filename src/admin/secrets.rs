@@ -7,6 +7,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::{Notify, RwLock};
+use tracing::warn;
 use zeroize::Zeroize;
 
 /// Secrets payload sent by admin during provisioning.
@@ -38,6 +39,89 @@ impl Drop for SecretsPayload {
     }
 }
 
+/// Features that need a Hugging Face token. The inference service reads its
+/// own `HF_TOKEN`; deployments that provision no `hf_token` run without these.
+pub const HF_TOKEN_FEATURES: &[&str] = &[
+    "speaker diarization (pyannote/speaker-diarization-3.1)",
+    "downloading gated models such as TranslateGemma that aren't already cached",
+];
+
+/// Custom secret holding the inference service token for every guild
+pub const INFERENCE_TOKEN_KEY: &str = "inference_token";
+
+#[derive(Default)]
+struct TokenMap {
+    default: Option<String>,
+    guilds: HashMap<u64, String>,
+}
+
+impl Drop for TokenMap {
+    fn drop(&mut self) {
+        if let Some(ref mut token) = self.default {
+            token.zeroize();
+        }
+        for token in self.guilds.values_mut() {
+            token.zeroize();
+        }
+    }
+}
+
+/// Bearer tokens presented to a multi-tenant inference service.
+///
+/// Provisioned as custom secrets: `inference_token` is used for every guild,
+/// and `inference_token.<guild_id>` overrides it for one guild so that guild
+/// authenticates as its own tenant.
+#[derive(Clone, Default)]
+pub struct InferenceTokens(Arc<TokenMap>);
+
+impl InferenceTokens {
+    /// Pick the inference tokens out of the provisioned custom secrets
+    pub fn from_custom_secrets(custom: &HashMap<String, String>) -> Self {
+        let mut tokens = TokenMap::default();
+        for (key, value) in custom {
+            if value.is_empty() {
+                continue;
+            }
+            if key == INFERENCE_TOKEN_KEY {
+                tokens.default = Some(value.clone());
+            } else if let Some(guild) = key
+                .strip_prefix(INFERENCE_TOKEN_KEY)
+                .and_then(|rest| rest.strip_prefix('.'))
+            {
+                match guild.parse() {
+                    Ok(guild_id) => {
+                        tokens.guilds.insert(guild_id, value.clone());
+                    }
+                    Err(_) => warn!(key = %key, "Ignoring inference token for an invalid guild ID"),
+                }
+            }
+        }
+        Self(Arc::new(tokens))
+    }
+
+    /// Token to present for `guild_id`, or the shared one outside a guild
+    pub fn for_guild(&self, guild_id: Option<u64>) -> Option<&str> {
+        guild_id
+            .and_then(|id| self.0.guilds.get(&id))
+            .or(self.0.default.as_ref())
+            .map(String::as_str)
+    }
+
+    /// Number of guilds with a token of their own
+    pub fn guild_count(&self) -> usize {
+        self.0.guilds.len()
+    }
+}
+
+impl std::fmt::Debug for InferenceTokens {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("InferenceTokens")
+            .field("default", &self.0.default.as_ref().map(|_| "<redacted>"))
+            .field("guilds", &self.guild_count())
+            .finish()
+    }
+}
+
 /// Current provisioning status of the bot.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 #[serde(rename_all = "snake_case")]
@@ -133,6 +217,26 @@ impl SecretStore {
             .and_then(|s| s.hf_token.clone())
     }
 
+    /// Whether a non-empty Hugging Face token was provisioned.
+    pub async fn has_hf_token(&self) -> bool {
+        self.secrets
+            .read()
+            .await
+            .as_ref()
+            .and_then(|s| s.hf_token.as_deref())
+            .is_some_and(|token| !token.is_empty())
+    }
+
+    /// Inference service tokens from the custom secrets.
+    pub async fn inference_tokens(&self) -> InferenceTokens {
+        self.secrets
+            .read()
+            .await
+            .as_ref()
+            .map(|s| InferenceTokens::from_custom_secrets(&s.custom))
+            .unwrap_or_default()
+    }
+
     /// Get a custom secret by key.
     pub async fn custom_secret(&self, key: &str) -> Option<String> {
         self.secrets
@@ -201,5 +305,48 @@ mod tests {
 
         // Token unchanged
         assert_eq!(store.discord_token().await, Some("test-token".to_string()));
+        assert!(!store.has_hf_token().await);
+    }
+
+    #[tokio::test]
+    async fn test_empty_hf_token_counts_as_missing() {
+        for (token, expected) in [(Some(""), false), (Some("hf_abc"), true)] {
+            let store = SecretStore::new();
+            store
+                .provision(SecretsPayload {
+                    discord_token: "test-token".to_string(),
+                    hf_token: token.map(str::to_string),
+                    custom: Default::default(),
+                })
+                .await;
+            assert_eq!(store.has_hf_token().await, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_inference_tokens_fall_back_to_shared_token() {
+        let store = SecretStore::new();
+        assert_eq!(store.inference_tokens().await.for_guild(Some(1)), None);
+
+        let custom = HashMap::from([
+            ("inference_token".to_string(), "shared".to_string()),
+            ("inference_token.42".to_string(), "tenant-42".to_string()),
+            ("inference_token.not-a-guild".to_string(), "ignored".to_string()),
+            ("other".to_string(), "unrelated".to_string()),
+        ]);
+        store
+            .provision(SecretsPayload {
+                discord_token: "test-token".to_string(),
+                hf_token: None,
+                custom,
+            })
+            .await;
+
+        let tokens = store.inference_tokens().await;
+        assert_eq!(tokens.for_guild(Some(42)), Some("tenant-42"));
+        assert_eq!(tokens.for_guild(Some(7)), Some("shared"));
+        assert_eq!(tokens.for_guild(None), Some("shared"));
+        assert_eq!(tokens.guild_count(), 1);
+        assert!(!format!("{:?}", tokens).contains("tenant-42"));
     }
 }
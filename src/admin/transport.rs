@@ -65,6 +65,9 @@ pub struct ErrorResponse {
 }
 
 /// Admin transport errors.
+///
+/// Each provisioning step has its own variant so a failed request says which
+/// step rejected it.
 #[derive(Debug, thiserror::Error)]
 pub enum AdminError {
     #[error("Cryptographic error: {0}")]
@@ -76,6 +79,24 @@ pub enum AdminError {
     #[error("Already provisioned")]
     AlreadyProvisioned,
 
+    #[error("{field} is not valid base64: {reason}")]
+    BadBase64 { field: &'static str, reason: String },
+
+    #[error("admin_x25519_public must decode to {expected} bytes, got {actual}")]
+    BadPublicKeyLength { expected: usize, actual: usize },
+
+    #[error("nonce must decode to {expected} bytes, got {actual}")]
+    BadNonceLength { expected: usize, actual: usize },
+
+    #[error("signature must decode to {expected} bytes, got {actual}")]
+    BadSignatureLength { expected: usize, actual: usize },
+
+    #[error("Signature verification failed: request was not signed by the configured admin key")]
+    SignatureRejected,
+
+    #[error("Decryption failed: ciphertext is corrupt or was encrypted to a different bot public key (fetch /pubkey again after a restart)")]
+    DecryptionFailed,
+
     #[error("Secrets deserialization failed: {0}")]
     DeserializationFailed(String),
 }
@@ -83,10 +104,16 @@ pub enum AdminError {
 impl IntoResponse for AdminError {
     fn into_response(self) -> Response {
         let status = match &self {
-            AdminError::Crypto(_) => StatusCode::BAD_REQUEST,
-            AdminError::InvalidRequest(_) => StatusCode::BAD_REQUEST,
             AdminError::AlreadyProvisioned => StatusCode::CONFLICT,
-            AdminError::DeserializationFailed(_) => StatusCode::BAD_REQUEST,
+            AdminError::Crypto(_)
+            | AdminError::InvalidRequest(_)
+            | AdminError::BadBase64 { .. }
+            | AdminError::BadPublicKeyLength { .. }
+            | AdminError::BadNonceLength { .. }
+            | AdminError::BadSignatureLength { .. }
+            | AdminError::SignatureRejected
+            | AdminError::DecryptionFailed
+            | AdminError::DeserializationFailed(_) => StatusCode::BAD_REQUEST,
         };
 
         let body = Json(ErrorResponse {
@@ -97,6 +124,50 @@ impl IntoResponse for AdminError {
     }
 }
 
+/// Decode a base64 request field, naming the field if it is malformed.
+fn decode_field(field: &'static str, value: &str) -> Result<Vec<u8>, AdminError> {
+    BASE64.decode(value).map_err(|e| AdminError::BadBase64 {
+        field,
+        reason: e.to_string(),
+    })
+}
+
+/// Decode a base64 request field that must be exactly `expected` bytes long.
+fn decode_fixed(
+    field: &'static str,
+    value: &str,
+    expected: usize,
+    bad_length: fn(usize, usize) -> AdminError,
+) -> Result<Vec<u8>, AdminError> {
+    let bytes = decode_field(field, value)?;
+    if bytes.len() != expected {
+        return Err(bad_length(expected, bytes.len()));
+    }
+    Ok(bytes)
+}
+
+/// Step-by-step log of one provision request, enabled by
+/// `admin.debug_provisioning`. Only lengths and outcomes are recorded.
+struct ProvisionTrace {
+    enabled: bool,
+}
+
+impl ProvisionTrace {
+    fn step(&self, step: &str, outcome: std::fmt::Arguments<'_>) {
+        if self.enabled {
+            info!(step, "provision: {}", outcome);
+        }
+    }
+
+    /// Record a failed step and hand the error back.
+    fn fail(&self, step: &str, err: AdminError) -> AdminError {
+        if self.enabled {
+            warn!(step, "provision: failed: {}", err);
+        }
+        err
+    }
+}
+
 /// Shared state for admin endpoints.
 pub struct AdminState {
     /// Bot's ephemeral keypair
@@ -105,6 +176,8 @@ pub struct AdminState {
     pub admin_public_key: VerifyingKey,
     /// Secret store to provision
     pub secret_store: SharedSecretStore,
+    /// Log each provisioning step (see `admin.debug_provisioning`)
+    pub debug: bool,
 }
 
 impl AdminState {
@@ -127,8 +200,15 @@ impl AdminState {
             keypair: RwLock::new(Some(keypair)),
             admin_public_key,
             secret_store,
+            debug: false,
         })
     }
+
+    /// Enable or disable step-by-step provisioning logs.
+    pub fn with_debug(mut self, debug: bool) -> Self {
+        self.debug = debug;
+        self
+    }
 }
 
 /// Handler: GET /admin/pubkey
@@ -173,17 +253,40 @@ async fn provision(
     };
 
     info!("Processing provision request...");
+    let trace = ProvisionTrace {
+        enabled: state.debug,
+    };
 
     // Parse admin's X25519 public key
-    let admin_x25519_public = parse_x25519_public_key(&request.admin_x25519_public)?;
+    let admin_x25519_bytes = decode_fixed(
+        "admin_x25519_public",
+        &request.admin_x25519_public,
+        32,
+        |expected, actual| AdminError::BadPublicKeyLength { expected, actual },
+    )
+    .map_err(|e| trace.fail("key_parse", e))?;
+    let admin_x25519_public = parse_x25519_public_key(&request.admin_x25519_public)
+        .map_err(|e| trace.fail("key_parse", e.into()))?;
+    trace.step(
+        "key_parse",
+        format_args!("admin X25519 key ok ({} bytes)", admin_x25519_bytes.len()),
+    );
 
     // Decode ciphertext and nonce for signature verification
-    let ciphertext_bytes = BASE64
-        .decode(&request.ciphertext)
-        .map_err(|e| AdminError::Crypto(CryptoError::Base64(e)))?;
-    let nonce_bytes = BASE64
-        .decode(&request.nonce)
-        .map_err(|e| AdminError::Crypto(CryptoError::Base64(e)))?;
+    let ciphertext_bytes =
+        decode_field("ciphertext", &request.ciphertext).map_err(|e| trace.fail("decode", e))?;
+    let nonce_bytes = decode_fixed("nonce", &request.nonce, 12, |expected, actual| {
+        AdminError::BadNonceLength { expected, actual }
+    })
+    .map_err(|e| trace.fail("decode", e))?;
+    trace.step(
+        "decode",
+        format_args!(
+            "ciphertext {} bytes, nonce {} bytes",
+            ciphertext_bytes.len(),
+            nonce_bytes.len()
+        ),
+    );
 
     // Build message that was signed
     let message = build_signature_message(
@@ -193,18 +296,44 @@ async fn provision(
     );
 
     // Parse and verify signature
-    let signature = parse_signature(&request.signature)?;
-    verify_signature(&state.admin_public_key, &message, &signature)?;
+    decode_fixed("signature", &request.signature, 64, |expected, actual| {
+        AdminError::BadSignatureLength { expected, actual }
+    })
+    .map_err(|e| trace.fail("signature_verify", e))?;
+    let signature =
+        parse_signature(&request.signature).map_err(|e| trace.fail("signature_verify", e.into()))?;
+    verify_signature(&state.admin_public_key, &message, &signature)
+        .map_err(|_| trace.fail("signature_verify", AdminError::SignatureRejected))?;
     info!("Signature verified successfully");
+    trace.step(
+        "signature_verify",
+        format_args!("valid over {} byte message", message.len()),
+    );
 
     // Compute shared secret and decrypt
     let shared_secret = keypair.diffie_hellman(&admin_x25519_public);
-    let plaintext = decrypt_payload(&shared_secret, &request.nonce, &request.ciphertext)?;
+    trace.step("dh", format_args!("shared secret derived"));
+    let plaintext = decrypt_payload(&shared_secret, &request.nonce, &request.ciphertext)
+        .map_err(|_| trace.fail("decrypt", AdminError::DecryptionFailed))?;
     info!("Decryption successful");
+    trace.step("decrypt", format_args!("plaintext {} bytes", plaintext.len()));
 
     // Parse secrets
-    let secrets: SecretsPayload = serde_json::from_slice(&plaintext)
-        .map_err(|e| AdminError::DeserializationFailed(e.to_string()))?;
+    let secrets: SecretsPayload = serde_json::from_slice(&plaintext).map_err(|e| {
+        // serde_json's message can quote the offending value, which may be a
+        // token, so only the error category and position are reported
+        let reason = format!("{:?} error at line {} column {}", e.classify(), e.line(), e.column());
+        trace.fail("deserialize", AdminError::DeserializationFailed(reason))
+    })?;
+    trace.step(
+        "deserialize",
+        format_args!(
+            "discord_token {} chars, hf_token {}, {} custom entries",
+            secrets.discord_token.len(),
+            if secrets.hf_token.is_some() { "set" } else { "unset" },
+            secrets.custom.len()
+        ),
+    );
 
     // Store secrets
     if !state.secret_store.provision(secrets).await {
@@ -213,6 +342,7 @@ async fn provision(
     }
 
     info!("Secrets provisioned successfully!");
+    trace.step("store", format_args!("secrets stored"));
 
     Ok(Json(ProvisionResponse {
         success: true,
@@ -220,13 +350,20 @@ async fn provision(
     }))
 }
 
-/// Create the admin router.
-pub fn admin_router(state: Arc<AdminState>) -> Router {
-    Router::new()
+/// Create the admin router, serving its endpoints under `path_prefix`
+/// (normalized as by [`AdminConfig::mount_path`](crate::config::AdminConfig::mount_path);
+/// empty for the root).
+pub fn admin_router(state: Arc<AdminState>, path_prefix: &str) -> Router {
+    let router = Router::new()
         .route("/pubkey", get(get_public_key))
         .route("/status", get(get_status))
         .route("/provision", post(provision))
-        .with_state(state)
+        .with_state(state);
+    if path_prefix.is_empty() {
+        router
+    } else {
+        Router::new().nest(path_prefix, router)
+    }
 }
 
 #[cfg(test)]
@@ -263,6 +400,13 @@ mod tests {
         let deser = AdminError::DeserializationFailed("parse error".to_string());
         let resp = deser.into_response();
         assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        let nonce = AdminError::BadNonceLength {
+            expected: 12,
+            actual: 8,
+        };
+        assert_eq!(nonce.to_string(), "nonce must decode to 12 bytes, got 8");
+        assert_eq!(nonce.into_response().status(), StatusCode::BAD_REQUEST);
     }
 
     #[test]
@@ -302,6 +446,23 @@ mod tests {
         assert_eq!(result.0.status, ProvisioningStatus::WaitingForProvisioning);
     }
 
+    #[tokio::test]
+    async fn test_router_serves_under_path_prefix() {
+        let (_, public_key_base64) = generate_admin_keys();
+        let state = Arc::new(AdminState::new(&public_key_base64, create_secret_store()).unwrap());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            axum::serve(listener, admin_router(state, "/bot-admin")).await.unwrap();
+        });
+
+        let client = reqwest::Client::new();
+        let prefixed = client.get(format!("{}/bot-admin/status", base)).send().await.unwrap();
+        assert_eq!(prefixed.status(), StatusCode::OK);
+        let root = client.get(format!("{}/status", base)).send().await.unwrap();
+        assert_eq!(root.status(), StatusCode::NOT_FOUND);
+    }
+
     #[tokio::test]
     async fn test_provision_invalid_signature() {
         let (_, public_key_base64) = generate_admin_keys();
@@ -320,7 +481,50 @@ mod tests {
         };
 
         let result = provision(State(state), Json(request)).await;
-        assert!(result.is_err());
+        assert!(matches!(result, Err(AdminError::SignatureRejected)));
+    }
+
+    #[tokio::test]
+    async fn test_provision_reports_bad_lengths() {
+        let (_, public_key_base64) = generate_admin_keys();
+        let admin_x25519_public = BASE64.encode([7u8; 32]);
+
+        let cases = [
+            (BASE64.encode([7u8; 31]), BASE64.encode([0u8; 12]), BASE64.encode([0u8; 64])),
+            (admin_x25519_public.clone(), BASE64.encode([0u8; 8]), BASE64.encode([0u8; 64])),
+            (admin_x25519_public.clone(), BASE64.encode([0u8; 12]), BASE64.encode([0u8; 63])),
+            (admin_x25519_public, "not base64!".to_string(), BASE64.encode([0u8; 64])),
+        ];
+
+        let mut errors = Vec::new();
+        for (key, nonce, signature) in cases {
+            let state = Arc::new(
+                AdminState::new(&public_key_base64, create_secret_store())
+                    .unwrap()
+                    .with_debug(true),
+            );
+            let request = ProvisionRequest {
+                admin_x25519_public: key,
+                ciphertext: BASE64.encode(b"fake ciphertext"),
+                nonce,
+                signature,
+            };
+            errors.push(provision(State(state), Json(request)).await.unwrap_err());
+        }
+
+        assert!(matches!(
+            errors[0],
+            AdminError::BadPublicKeyLength { expected: 32, actual: 31 }
+        ));
+        assert!(matches!(
+            errors[1],
+            AdminError::BadNonceLength { expected: 12, actual: 8 }
+        ));
+        assert!(matches!(
+            errors[2],
+            AdminError::BadSignatureLength { expected: 64, actual: 63 }
+        ));
+        assert!(matches!(errors[3], AdminError::BadBase64 { field: "nonce", .. }));
     }
 
     #[tokio::test]
@@ -0,0 +1,175 @@
+//! Catching up on recent conversation when a channel is first enabled.
+//!
+//! `/channels enable backfill:N` fetches the channel's last N messages and
+//! posts their translations, oldest first, in a thread so the catch-up
+//! doesn't interleave with live chat. Each message goes through the same
+//! origin, opt-out, length and rate checks as a live one; once the guild's
+//! per-minute budget runs out the rest are skipped.
+
+use crate::bot::discord_limits::{self, MESSAGE_CONTENT};
+use crate::bot::handler::{
+    channel_target_languages, is_opted_out, passes_message_filter, translate_message, MessageOrigin,
+};
+use crate::bot::message_limits::MessageRejected;
+use crate::bot::Data;
+use crate::db::GuildSettings;
+use crate::translation::{TranslationHints, TranslationResult};
+use poise::serenity_prelude::{
+    self as serenity, ChannelId, ChannelType, CreateAllowedMentions, CreateMessage, CreateThread,
+    GetMessages, Http, UserId,
+};
+use std::time::Instant;
+use tracing::warn;
+
+/// Most messages one backfill may fetch
+pub const MAX_BACKFILL_MESSAGES: u8 = 50;
+
+/// Name of the thread the translations are posted in
+const THREAD_NAME: &str = "Catch-up translations";
+
+/// What a backfill did
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct BackfillReport {
+    /// Messages whose translations were posted
+    pub translated: usize,
+    /// Messages left out for their length or the guild's rate limit
+    pub skipped: usize,
+    /// Thread holding the translations, if any were posted
+    pub thread: Option<ChannelId>,
+}
+
+/// Translate up to `count` recent messages in `channel_id` into a new thread
+pub async fn backfill_channel(
+    http: &Http,
+    data: &Data,
+    channel_id: ChannelId,
+    own_id: UserId,
+    settings: &GuildSettings,
+    count: u8,
+) -> serenity::Result<BackfillReport> {
+    let limit = count.min(MAX_BACKFILL_MESSAGES);
+    let mut messages = channel_id.messages(http, GetMessages::new().limit(limit)).await?;
+    // Discord returns newest first
+    messages.reverse();
+
+    let mut report = BackfillReport::default();
+    let allowed = match data.settings.allowed_languages(&channel_id.to_string()).await {
+        Ok(allowed) => allowed,
+        Err(e) => {
+            warn!(channel_id = %channel_id, "Skipping backfill, couldn't load allowed languages: {}", e);
+            return Ok(report);
+        }
+    };
+    let filter = match data.settings.message_filter(&channel_id.to_string()).await {
+        Ok(filter) => filter,
+        Err(e) => {
+            warn!(channel_id = %channel_id, "Skipping backfill, couldn't load channel filter: {}", e);
+            return Ok(report);
+        }
+    };
+    let context = match data.settings.translation_context(&channel_id.to_string()).await {
+        Ok(context) => context.or_else(|| settings.translation_context.clone()),
+        Err(e) => {
+            warn!(channel_id = %channel_id, "Couldn't load translation context: {}", e);
+            settings.translation_context.clone()
+        }
+    };
+    let hints = TranslationHints {
+        context: context.as_deref(),
+        formality: settings.formality,
+    };
+    let target_langs = channel_target_languages(settings, &allowed);
+    let guild_key = settings.guild_id.parse().unwrap_or_default();
+
+    let mut entries = Vec::new();
+    for (i, msg) in messages.iter().enumerate() {
+        let origin = MessageOrigin::of(msg, own_id, &data.webhooks);
+        if msg.content.trim().is_empty()
+            || !origin.is_translated(settings)
+            || is_opted_out(&msg.content, settings)
+            || !passes_message_filter(filter.as_ref(), &msg.content)
+        {
+            continue;
+        }
+
+        let chars = msg.content.chars().count();
+        match data
+            .message_guard
+            .check(guild_key, settings.subscription_tier, chars, Instant::now())
+        {
+            Ok(()) => {}
+            Err(MessageRejected::TooLong { .. }) => {
+                report.skipped += 1;
+                continue;
+            }
+            Err(MessageRejected::RateLimited) => {
+                report.skipped += messages.len() - i;
+                break;
+            }
+        }
+
+        let translations: Vec<TranslationResult> =
+            translate_message(&data.translator, &msg.content, &target_langs, settings, hints)
+                .await
+                .into_iter()
+                .filter_map(Result::ok)
+                .collect();
+        if translations.is_empty() {
+            continue;
+        }
+        report.translated += 1;
+        entries.extend(
+            translations
+                .iter()
+                .map(|t| entry_line(msg.author.display_name(), t)),
+        );
+    }
+
+    if entries.is_empty() {
+        return Ok(report);
+    }
+
+    let thread = channel_id
+        .create_thread(
+            http,
+            CreateThread::new(THREAD_NAME).kind(ChannelType::PublicThread),
+        )
+        .await?;
+    for chunk in discord_limits::split(&entries.join("\n"), MESSAGE_CONTENT) {
+        let message = CreateMessage::new()
+            .content(chunk)
+            // The originals already pinged whoever they mention
+            .allowed_mentions(CreateAllowedMentions::new());
+        thread.id.send_message(http, message).await?;
+    }
+    report.thread = Some(thread.id);
+    Ok(report)
+}
+
+/// One translated message, e.g. "**Ana** (ES → EN): Hello everyone"
+fn entry_line(author: &str, translation: &TranslationResult) -> String {
+    format!(
+        "**{}** ({} → {}): {}",
+        author,
+        translation.source_lang.to_uppercase(),
+        translation.target_lang.to_uppercase(),
+        translation.translated_text
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entry_line_format() {
+        let translation = TranslationResult {
+            original_text: "Hola a todos".to_string(),
+            translated_text: "Hello everyone".to_string(),
+            source_lang: "es".to_string(),
+            target_lang: "en".to_string(),
+            cached: false,
+        };
+        assert_eq!(entry_line("Ana", &translation), "**Ana** (ES → EN): Hello everyone");
+    }
+}
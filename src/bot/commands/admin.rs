@@ -0,0 +1,364 @@
+use crate::bot::discord_limits::{truncate, EMBED_DESCRIPTION, EMBED_FIELD_VALUE};
+use crate::bot::selftest::{self, StageReport, VOICE_TIMEOUT};
+use crate::bot::Data;
+use crate::db::{GuildDataSummary, GuildMigrationReport, GuildMigrationRepo};
+use crate::error::AppResult;
+use crate::translation::recent_errors::{summarize, RECENT_ERRORS_PER_GUILD};
+use crate::translation::{FailureRecord, Language, TranslateResponse};
+use poise::serenity_prelude as serenity;
+use std::time::{Duration, Instant};
+
+type Error = Box<dyn std::error::Error + Send + Sync>;
+type Context<'a> = poise::Context<'a, Data, Error>;
+
+/// Operator tools for checking this LinguaBridge deployment
+#[poise::command(
+    slash_command,
+    guild_only,
+    required_permissions = "ADMINISTRATOR",
+    subcommands("admin_selftest", "admin_translate", "admin_migrate", "admin_errors"),
+    subcommand_required
+)]
+pub async fn admin(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Run a test phrase (and optionally test audio) through the inference service
+#[poise::command(slash_command, guild_only, rename = "selftest")]
+pub async fn admin_selftest(
+    ctx: Context<'_>,
+    #[description = "Also send test audio through the voice pipeline (default: true)"] voice: Option<bool>,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+    let started = Instant::now();
+
+    let mut stages = selftest::check_translation(&ctx.data().translator).await;
+    if voice.unwrap_or(true) {
+        match ctx.data().voice.as_ref() {
+            Some(voice_manager) => {
+                stages.push(
+                    selftest::check_voice(&voice_manager.inference_client(), VOICE_TIMEOUT).await,
+                );
+            }
+            None => stages.push(StageReport {
+                name: "Voice pipeline",
+                outcome: Err("voice support is not initialized".to_string()),
+                latency: Default::default(),
+            }),
+        }
+    }
+
+    let passed = stages.iter().filter(|s| s.passed()).count();
+    let all_passed = passed == stages.len();
+    let body = stages.iter().map(StageReport::line).collect::<Vec<_>>().join("\n");
+
+    let embed = serenity::CreateEmbed::default()
+        .title(if all_passed { "Self-test passed" } else { "Self-test failed" })
+        .description(body)
+        .footer(serenity::CreateEmbedFooter::new(format!(
+            "{}/{} stages passed in {} ms",
+            passed,
+            stages.len(),
+            started.elapsed().as_millis()
+        )))
+        .color(if all_passed { 0x57F287 } else { 0xED4245 });
+
+    ctx.send(poise::CreateReply::default().embed(embed).ephemeral(true)).await?;
+    Ok(())
+}
+
+/// Translate one exact language pair and show the raw result and diagnostics
+#[poise::command(slash_command, guild_only, rename = "translate")]
+pub async fn admin_translate(
+    ctx: Context<'_>,
+    #[description = "Text to translate"] text: String,
+    #[description = "Source language code (no auto-detection)"] source: String,
+    #[description = "Target language code"] target: String,
+) -> Result<(), Error> {
+    let source = Language::from_code(&source).ok_or_else(|| format!("Unknown source language: {}", source))?;
+    let target = Language::from_code(&target).ok_or_else(|| format!("Unknown target language: {}", target))?;
+    ctx.defer_ephemeral().await?;
+
+    let translator = &ctx.data().translator;
+    let started = Instant::now();
+    let result = translator
+        .translate_uncached(&text, source.code(), target.code())
+        .await;
+    let latency = started.elapsed();
+
+    let embed = serenity::CreateEmbed::default()
+        .title(format!(
+            "{} → {}: {}",
+            source.code(),
+            target.code(),
+            if result.is_ok() { "ok" } else { "failed" }
+        ))
+        .fields(
+            pair_test_fields(&result, latency, translator.base_url())
+                .into_iter()
+                .map(|(name, value)| (name, value, false)),
+        )
+        .color(if result.is_ok() { 0x57F287 } else { 0xED4245 });
+
+    ctx.send(poise::CreateReply::default().embed(embed).ephemeral(true)).await?;
+    Ok(())
+}
+
+/// Copy configuration stranded under an old server ID to this server
+#[poise::command(slash_command, guild_only, rename = "migrate")]
+pub async fn admin_migrate(
+    ctx: Context<'_>,
+    #[description = "ID of the deleted server, or the one the bot was removed from"] old_guild_id: String,
+    #[description = "Replace the configuration this server already has (default: false)"] overwrite: Option<bool>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("This command can only be used in a server")?;
+    let old_id = old_guild_id
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .filter(|&id| id != 0)
+        .ok_or_else(|| format!("`{}` is not a server ID", old_guild_id))?;
+    if old_id == guild_id.get() {
+        return Err("That is this server's own ID".into());
+    }
+    ctx.defer_ephemeral().await?;
+
+    // Only stranded configuration can be claimed; while the bot is still in
+    // the old server, it belongs to that server's admins
+    if ctx.http().get_guild(serenity::GuildId::new(old_id)).await.is_ok() {
+        return Err("The bot is still in that server, so its configuration can't be moved here".into());
+    }
+
+    let pool = &ctx.data().pool;
+    let (old_key, new_key) = (old_id.to_string(), guild_id.to_string());
+    let stored = GuildMigrationRepo::summarize(pool, &old_key).await?;
+    if stored.is_empty() {
+        return Err(format!("No configuration is stored for server {}", old_id).into());
+    }
+
+    let existing = GuildMigrationRepo::summarize(pool, &new_key).await?;
+    if !existing.is_empty() && !overwrite.unwrap_or(false) {
+        let embed = serenity::CreateEmbed::default()
+            .title("This server is already configured")
+            .description(format!(
+                "Migrating would replace:\n{}\n\nRun `/admin migrate` again with `overwrite: True` to continue.",
+                summary_lines(&existing)
+            ))
+            .color(0xFEE75C);
+        ctx.send(poise::CreateReply::default().embed(embed).ephemeral(true)).await?;
+        return Ok(());
+    }
+
+    let channels: Vec<String> = guild_id
+        .channels(ctx.http())
+        .await?
+        .keys()
+        .map(|id| id.to_string())
+        .collect();
+    let name = ctx.guild().map(|g| g.name.clone()).unwrap_or_default();
+    let report = GuildMigrationRepo::migrate(pool, &old_key, &new_key, &name, &channels).await?;
+
+    let data = ctx.data();
+    data.settings.invalidate_guild(&new_key);
+    data.settings.invalidate_guild_users(&new_key);
+    data.prefixes.invalidate(guild_id.get());
+
+    let embed = serenity::CreateEmbed::default()
+        .title("Configuration migrated")
+        .description(report_lines(&report))
+        .footer(serenity::CreateEmbedFooter::new(format!("From server {}", old_id)))
+        .color(0x57F287);
+    ctx.send(poise::CreateReply::default().embed(embed).ephemeral(true)).await?;
+    Ok(())
+}
+
+/// Show this server's most recent translation failures
+#[poise::command(slash_command, guild_only, rename = "errors")]
+pub async fn admin_errors(
+    ctx: Context<'_>,
+    #[description = "How many to show (default: 5, max: 20)"] count: Option<usize>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("This command can only be used in a server")?;
+    let limit = count.unwrap_or(5).clamp(1, RECENT_ERRORS_PER_GUILD);
+    let failures = ctx.data().translator.recent_errors(guild_id.get(), limit);
+
+    let embed = if failures.is_empty() {
+        serenity::CreateEmbed::default()
+            .title("No recent translation errors")
+            .description("No translation has failed in this server since the bot started.")
+            .color(0x57F287)
+    } else {
+        serenity::CreateEmbed::default()
+            .title(format!("Last {}", summarize(&failures)))
+            .description(truncate(&failure_lines(&failures), EMBED_DESCRIPTION))
+            .footer(serenity::CreateEmbedFooter::new(
+                "Inputs are shown as hashes; the same hash means the same message",
+            ))
+            .color(0xED4245)
+    };
+    ctx.send(poise::CreateReply::default().embed(embed).ephemeral(true)).await?;
+    Ok(())
+}
+
+/// One line per failure, newest first
+fn failure_lines(failures: &[FailureRecord]) -> String {
+    failures
+        .iter()
+        .map(|failure| {
+            let target = match &failure.target_lang {
+                Some(lang) => format!("→ {}", lang),
+                None => "detection".to_string(),
+            };
+            format!(
+                "<t:{}:R> **{}** ({}, `{}`): {}",
+                failure.at.timestamp(),
+                failure.reason,
+                target,
+                failure.input_hash,
+                truncate(&failure.message, 120)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// One line per kind of stored configuration
+fn summary_lines(summary: &GuildDataSummary) -> String {
+    let mut lines = Vec::new();
+    if summary.custom_settings {
+        lines.push("• Server settings".to_string());
+    }
+    for (count, what) in [
+        (summary.user_preferences, "member language preferences"),
+        (summary.channels, "channel settings"),
+        (summary.voice_channels, "voice channel settings"),
+        (summary.transcript_channels, "voice transcript settings"),
+    ] {
+        if count > 0 {
+            lines.push(format!("• {} {}", count, what));
+        }
+    }
+    lines.join("\n")
+}
+
+/// What a migration copied, and what it had to leave behind
+fn report_lines(report: &GuildMigrationReport) -> String {
+    let mut lines = vec![format!(
+        "• Server settings: {}",
+        if report.settings { "copied" } else { "none stored" }
+    )];
+    for (count, what) in [
+        (report.user_preferences, "member language preferences"),
+        (report.channels, "channel settings"),
+        (report.voice_channels, "voice channel settings"),
+        (report.transcript_channels, "voice transcript settings"),
+    ] {
+        lines.push(format!("• {} {}", count, what));
+    }
+    if report.skipped_channels > 0 {
+        lines.push(format!(
+            "\n{} channel setting(s) were for channels that aren't in this server and were left behind; set them up again with `/channels enable` and the `/voice` commands.",
+            report.skipped_channels
+        ));
+    }
+    lines.join("\n")
+}
+
+/// Embed fields for a language pair test: the raw output (or error), how
+/// long the service took and what served it
+fn pair_test_fields(
+    result: &AppResult<TranslateResponse>,
+    latency: Duration,
+    service_url: &str,
+) -> Vec<(&'static str, String)> {
+    let raw_len = EMBED_FIELD_VALUE - "```\n\n```".len();
+    let mut fields = Vec::new();
+    match result {
+        Ok(response) => {
+            fields.push(("Result", format!("```\n{}\n```", truncate(&response.translated_text, raw_len))));
+            if let Some(confidence) = response.confidence {
+                fields.push(("Confidence", format!("{:.0}%", confidence * 100.0)));
+            }
+        }
+        Err(e) => fields.push(("Error", truncate(&e.to_string(), EMBED_FIELD_VALUE))),
+    }
+    fields.push(("Latency", format!("{} ms (uncached)", latency.as_millis())));
+
+    let model = match result {
+        Ok(TranslateResponse { model: Some(model), .. }) => format!(" ({})", model),
+        _ => String::new(),
+    };
+    fields.push(("Provider", truncate(&format!("{}{}", service_url, model), EMBED_FIELD_VALUE)));
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::AppError;
+
+    #[test]
+    fn test_pair_test_fields_success() {
+        let result = Ok(TranslateResponse {
+            translated_text: "こんにちは".to_string(),
+            source_lang: "de".to_string(),
+            target_lang: "ja".to_string(),
+            confidence: None,
+            model: Some("google/translategemma-4b-it".to_string()),
+        });
+        let fields = pair_test_fields(&result, Duration::from_millis(412), "http://inference:8000");
+
+        assert_eq!(fields[0], ("Result", "```\nこんにちは\n```".to_string()));
+        assert_eq!(fields[1], ("Latency", "412 ms (uncached)".to_string()));
+        assert_eq!(
+            fields[2],
+            ("Provider", "http://inference:8000 (google/translategemma-4b-it)".to_string())
+        );
+    }
+
+    #[test]
+    fn test_pair_test_fields_failure() {
+        let result = Err(AppError::Translation("unsupported pair".to_string()));
+        let fields = pair_test_fields(&result, Duration::from_millis(5), "http://inference:8000");
+
+        assert_eq!(fields[0].0, "Error");
+        assert!(fields[0].1.contains("unsupported pair"));
+        assert_eq!(fields[2], ("Provider", "http://inference:8000".to_string()));
+    }
+
+    #[test]
+    fn test_failure_lines_show_reason_target_and_hash() {
+        let errors = crate::translation::recent_errors::RecentErrors::new();
+        errors.record(1, "bonjour", Some("en"), &AppError::RateLimitExceeded);
+        errors.record(1, "hola", None, &AppError::InferenceUnavailable);
+        let failures = errors.recent(1, 5);
+
+        let lines = failure_lines(&failures);
+        let lines: Vec<&str> = lines.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].contains("**service unreachable** (detection, `"));
+        assert!(lines[1].contains("**rate-limited** (→ en, `"));
+        assert!(lines[1].ends_with(": Rate limit exceeded"));
+        assert!(!lines.concat().contains("bonjour"));
+    }
+
+    #[test]
+    fn test_migration_report_mentions_skipped_channels() {
+        let report = GuildMigrationReport {
+            settings: true,
+            user_preferences: 12,
+            voice_channels: 1,
+            skipped_channels: 2,
+            ..Default::default()
+        };
+        let text = report_lines(&report);
+        assert!(text.starts_with("• Server settings: copied\n• 12 member language preferences"));
+        assert!(text.contains("2 channel setting(s) were for channels that aren't in this server"));
+
+        let summary = GuildDataSummary {
+            user_preferences: 3,
+            ..Default::default()
+        };
+        assert_eq!(summary_lines(&summary), "• 3 member language preferences");
+    }
+}
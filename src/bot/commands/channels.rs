@@ -0,0 +1,337 @@
+use crate::bot::backfill::{backfill_channel, BackfillReport};
+use crate::bot::handler::{compile_message_filter, MAX_MESSAGE_FILTER_LEN};
+use crate::bot::Data;
+use crate::db::{ChannelRepo, GuildRepo};
+use crate::translation::{parse_context, Language};
+use poise::serenity_prelude as serenity;
+
+type Error = Box<dyn std::error::Error + Send + Sync>;
+type Context<'a> = poise::Context<'a, Data, Error>;
+
+/// Most channels listed before the rest are summarised
+const MAX_LISTED_CHANNELS: usize = 50;
+
+/// See and change which channels are translated
+#[poise::command(
+    slash_command,
+    guild_only,
+    required_permissions = "MANAGE_CHANNELS",
+    subcommands(
+        "channels_list",
+        "channels_enable",
+        "channels_disable",
+        "channels_languages",
+        "channels_filter",
+        "channels_context"
+    ),
+    subcommand_required
+)]
+pub async fn channels(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// List the channels where messages are translated
+#[poise::command(slash_command, guild_only, rename = "list")]
+pub async fn channels_list(ctx: Context<'_>) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Must be used in a guild")?.to_string();
+    let Some(settings) = GuildRepo::get_settings(&ctx.data().pool, &guild_id).await? else {
+        ctx.say("LinguaBridge is not configured for this server. Run `/setup init` to get started.")
+            .await?;
+        return Ok(());
+    };
+
+    let embed = serenity::CreateEmbed::default()
+        .title(format!("Translated Channels ({})", settings.enabled_channels.len()))
+        .description(channel_list(&settings.enabled_channels));
+
+    ctx.send(poise::CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+    Ok(())
+}
+
+/// Translate messages in a channel
+#[poise::command(slash_command, guild_only, rename = "enable")]
+pub async fn channels_enable(
+    ctx: Context<'_>,
+    #[description = "Channel to translate (default: this channel)"] channel: Option<serenity::GuildChannel>,
+    #[description = "Also translate this many recent messages into a thread (max 50)"]
+    #[max = 50]
+    backfill: Option<u8>,
+) -> Result<(), Error> {
+    set_enabled(ctx, channel, true, backfill.unwrap_or(0)).await
+}
+
+/// Stop translating messages in a channel
+#[poise::command(slash_command, guild_only, rename = "disable")]
+pub async fn channels_disable(
+    ctx: Context<'_>,
+    #[description = "Channel to stop translating (default: this channel)"] channel: Option<serenity::GuildChannel>,
+) -> Result<(), Error> {
+    set_enabled(ctx, channel, false, 0).await
+}
+
+async fn set_enabled(
+    ctx: Context<'_>,
+    channel: Option<serenity::GuildChannel>,
+    enable: bool,
+    backfill: u8,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Must be used in a guild")?.to_string();
+    let channel_id = channel.map(|c| c.id).unwrap_or_else(|| ctx.channel_id());
+    let pool = &ctx.data().pool;
+
+    let Some(settings) = GuildRepo::get_settings(pool, &guild_id).await? else {
+        ctx.say("Please run `/setup init` first to initialize LinguaBridge.").await?;
+        return Ok(());
+    };
+
+    let was_enabled = settings.enabled_channels.contains(&channel_id.to_string());
+    let message = match (enable, was_enabled) {
+        (true, true) => format!("<#{}> is already translated.", channel_id),
+        (false, false) => format!("<#{}> isn't translated.", channel_id),
+        (true, false) => {
+            GuildRepo::enable_channel(pool, &guild_id, &channel_id.to_string()).await?;
+            ctx.data().settings.invalidate_guild(&guild_id);
+            let mut message = format!("Translation enabled in <#{}>", channel_id);
+            if backfill > 0 {
+                // Fetching and translating can outlast the 3s reply window
+                ctx.defer().await?;
+                let report = backfill_channel(
+                    ctx.http(),
+                    ctx.data(),
+                    channel_id,
+                    ctx.framework().bot_id,
+                    &settings,
+                    backfill,
+                )
+                .await?;
+                message.push_str(&format!("\n{}", backfill_summary(&report)));
+            }
+            message
+        }
+        (false, true) => {
+            GuildRepo::disable_channel(pool, &guild_id, &channel_id.to_string()).await?;
+            ctx.data().settings.invalidate_guild(&guild_id);
+            ctx.data().broadcast.forget_text_channel(&channel_id.to_string());
+            format!("Translation disabled in <#{}>", channel_id)
+        }
+    };
+
+    ctx.say(message).await?;
+    Ok(())
+}
+
+/// Only translate a channel into approved languages, whatever the server's targets
+#[poise::command(slash_command, guild_only, rename = "languages")]
+pub async fn channels_languages(
+    ctx: Context<'_>,
+    #[description = "Allowed languages (comma-separated, e.g. 'en,fr'); leave empty to allow all"]
+    languages: Option<String>,
+    #[description = "Channel to restrict (default: this channel)"] channel: Option<serenity::GuildChannel>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Must be used in a guild")?.to_string();
+    let channel_id = channel.map(|c| c.id).unwrap_or_else(|| ctx.channel_id()).to_string();
+    let pool = &ctx.data().pool;
+
+    let settings = GuildRepo::get_settings(pool, &guild_id)
+        .await?
+        .ok_or("Please run `/setup init` first")?;
+
+    let (allowed, unknown) = parse_languages(languages.as_deref().unwrap_or_default());
+    if allowed.is_empty() && !unknown.is_empty() {
+        return Err(format!("Unknown languages: {}", unknown.join(", ")).into());
+    }
+    ChannelRepo::set_allowed_languages(pool, &guild_id, &channel_id, &allowed).await?;
+    ctx.data().settings.invalidate_channel(&channel_id);
+
+    let mut message = if allowed.is_empty() {
+        format!("<#{}> is translated into all of the server's languages.", channel_id)
+    } else {
+        format!("<#{}> is only translated into **{}**.", channel_id, allowed.join(", "))
+    };
+    if !unknown.is_empty() {
+        message.push_str(&format!("\nUnknown languages ignored: {}", unknown.join(", ")));
+    }
+    let targets = crate::bot::handler::channel_target_languages(&settings, &allowed);
+    if targets.is_empty() {
+        message.push_str(
+            "\nNone of the server's target languages are allowed, so nothing here will be translated. \
+            Add them with `/setup languages`.",
+        );
+    }
+
+    ctx.say(message).await?;
+    Ok(())
+}
+
+/// Only translate messages in a channel that match a regular expression
+#[poise::command(slash_command, guild_only, rename = "filter")]
+pub async fn channels_filter(
+    ctx: Context<'_>,
+    #[description = "Regex messages must match, e.g. '\\?\\s*$' for questions; leave empty to translate all"]
+    #[max_length = 200]
+    pattern: Option<String>,
+    #[description = "Channel to filter (default: this channel)"] channel: Option<serenity::GuildChannel>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Must be used in a guild")?.to_string();
+    let channel_id = channel.map(|c| c.id).unwrap_or_else(|| ctx.channel_id()).to_string();
+    let pool = &ctx.data().pool;
+
+    GuildRepo::get_settings(pool, &guild_id)
+        .await?
+        .ok_or("Please run `/setup init` first")?;
+
+    let pattern = pattern.filter(|p| !p.trim().is_empty());
+    if let Some(pattern) = &pattern {
+        compile_message_filter(pattern).map_err(|e| {
+            format!(
+                "That filter isn't a valid regular expression (max {} characters): {}",
+                MAX_MESSAGE_FILTER_LEN, e
+            )
+        })?;
+    }
+    ChannelRepo::set_message_filter(pool, &guild_id, &channel_id, pattern.as_deref()).await?;
+    ctx.data().settings.invalidate_channel(&channel_id);
+
+    let message = match pattern {
+        Some(pattern) => format!(
+            "Only messages in <#{}> matching `{}` are translated.",
+            channel_id,
+            pattern.replace('`', "'")
+        ),
+        None => format!("Every message in <#{}> is translated.", channel_id),
+    };
+    ctx.say(message).await?;
+    Ok(())
+}
+
+/// Give a channel its own hint for the translation model
+#[poise::command(slash_command, guild_only, rename = "context")]
+pub async fn channels_context(
+    ctx: Context<'_>,
+    #[description = "E.g. 'customer support, formal'; leave empty to use the server's context"]
+    #[max_length = 200]
+    context: Option<String>,
+    #[description = "Channel to set it for (default: this channel)"] channel: Option<serenity::GuildChannel>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Must be used in a guild")?.to_string();
+    let channel_id = channel.map(|c| c.id).unwrap_or_else(|| ctx.channel_id()).to_string();
+    let pool = &ctx.data().pool;
+
+    GuildRepo::get_settings(pool, &guild_id)
+        .await?
+        .ok_or("Please run `/setup init` first")?;
+
+    let context = parse_context(context.as_deref().unwrap_or_default())?;
+    ChannelRepo::set_translation_context(pool, &guild_id, &channel_id, context.as_deref()).await?;
+    ctx.data().settings.invalidate_channel(&channel_id);
+
+    let message = match context {
+        Some(context) => format!("Messages in <#{}> are translated with the context: *{}*", channel_id, context),
+        None => format!("<#{}> uses the server's translation context.", channel_id),
+    };
+    ctx.say(message).await?;
+    Ok(())
+}
+
+/// Canonical codes for a comma-separated language list, and the entries that
+/// aren't supported languages
+fn parse_languages(input: &str) -> (Vec<String>, Vec<String>) {
+    let mut allowed = Vec::new();
+    let mut unknown = Vec::new();
+    for lang in input.split(',').map(|s| s.trim().to_lowercase()).filter(|s| !s.is_empty()) {
+        match Language::from_code(&lang) {
+            Some(language) => {
+                let code = language.code().to_string();
+                if !allowed.contains(&code) {
+                    allowed.push(code);
+                }
+            }
+            None => unknown.push(lang),
+        }
+    }
+    (allowed, unknown)
+}
+
+/// One line describing how a backfill went
+fn backfill_summary(report: &BackfillReport) -> String {
+    let mut summary = match report.thread {
+        Some(thread) => format!(
+            "Translated {} recent message{} in <#{}>.",
+            report.translated,
+            if report.translated == 1 { "" } else { "s" },
+            thread
+        ),
+        None => "No recent messages needed translating.".to_string(),
+    };
+    if report.skipped > 0 {
+        summary.push_str(&format!(
+            " Skipped {} that were too long or over the rate limit.",
+            report.skipped
+        ));
+    }
+    summary
+}
+
+/// Enabled channels as mentions, one per line
+fn channel_list(channel_ids: &[String]) -> String {
+    if channel_ids.is_empty() {
+        return "No channels are translated yet. Use `/channels enable` in a channel to start."
+            .to_string();
+    }
+
+    let mut lines: Vec<String> = channel_ids
+        .iter()
+        .take(MAX_LISTED_CHANNELS)
+        .map(|id| format!("<#{}>", id))
+        .collect();
+    if channel_ids.len() > MAX_LISTED_CHANNELS {
+        lines.push(format!("…and {} more", channel_ids.len() - MAX_LISTED_CHANNELS));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_channel_list_uses_mentions() {
+        let ids = vec!["1".to_string(), "2".to_string()];
+        assert_eq!(channel_list(&ids), "<#1>\n<#2>");
+    }
+
+    #[test]
+    fn test_empty_channel_list_explains_how_to_enable() {
+        assert!(channel_list(&[]).contains("/channels enable"));
+    }
+
+    #[test]
+    fn test_long_channel_list_is_truncated() {
+        let ids: Vec<String> = (0..60).map(|i| i.to_string()).collect();
+        let list = channel_list(&ids);
+        assert_eq!(list.lines().count(), MAX_LISTED_CHANNELS + 1);
+        assert!(list.ends_with("…and 10 more"));
+    }
+
+    #[test]
+    fn test_parse_languages_canonicalizes_and_reports_unknown() {
+        let (allowed, unknown) = parse_languages(" EN, fr,en, klingon,");
+        assert_eq!(allowed, vec!["en", "fr"]);
+        assert_eq!(unknown, vec!["klingon"]);
+        assert_eq!(parse_languages(""), (vec![], vec![]));
+    }
+
+    #[test]
+    fn test_backfill_summary_mentions_thread_and_skips() {
+        let report = BackfillReport {
+            translated: 1,
+            skipped: 2,
+            thread: Some(serenity::ChannelId::new(42)),
+        };
+        assert_eq!(
+            backfill_summary(&report),
+            "Translated 1 recent message in <#42>. Skipped 2 that were too long or over the rate limit."
+        );
+    }
+}
@@ -0,0 +1,185 @@
+use crate::bot::Data;
+use crate::db::{NewTranslationFeedback, TranslationFeedbackRepo, TranslationMessageRepo};
+use poise::serenity_prelude as serenity;
+
+type Error = Box<dyn std::error::Error + Send + Sync>;
+type Context<'a> = poise::Context<'a, Data, Error>;
+
+/// Report a wrong translation and suggest a better one
+#[poise::command(slash_command, guild_only)]
+pub async fn feedback(
+    ctx: Context<'_>,
+    #[description = "Link or ID of the translation message"] message: String,
+    #[description = "Corrected translation"] correction: String,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Must be used in a guild")?;
+
+    let correction = correction.trim().to_string();
+    if correction.is_empty() {
+        return Err("The correction can't be empty.".into());
+    }
+
+    let (link_guild, channel_id, message_id) = parse_message_reference(&message)
+        .ok_or("Give a message link (Copy Message Link) or a message ID from this channel.")?;
+    if link_guild.is_some_and(|g| g != guild_id) {
+        return Err("That message is from another server.".into());
+    }
+    let channel_id = channel_id.unwrap_or_else(|| ctx.channel_id());
+
+    let not_a_translation = "That message isn't a LinguaBridge translation.";
+    let original_gone = "The original message for this translation is gone.";
+    let record = TranslationMessageRepo::get(&ctx.data().pool, &message_id.to_string()).await?;
+    let (source_lang, target_lang, machine_translation, original) = match record {
+        // Posted since translations were recorded, in any output mode
+        Some(record) if record.guild_id == guild_id.to_string() => {
+            let original_channel = record.original_channel_id.parse().map(serenity::ChannelId::new)?;
+            let original_id = record.original_message_id.parse().map(serenity::MessageId::new)?;
+            let original = original_channel
+                .message(ctx.http(), original_id)
+                .await
+                .map_err(|_| original_gone)?;
+            (record.source_lang, record.target_lang, record.translated_text, original)
+        }
+        Some(_) => return Err(not_a_translation.into()),
+        // Older replies and thread posts: read the embed the bot posted
+        None => {
+            let translation_msg = channel_id.message(ctx.http(), message_id).await?;
+            if translation_msg.author.id != ctx.framework().bot_id {
+                return Err(not_a_translation.into());
+            }
+            let embed = translation_msg.embeds.first().ok_or(not_a_translation)?;
+            let machine_translation = embed.description.clone().ok_or(not_a_translation)?;
+            let (source_lang, target_lang) = embed
+                .footer
+                .as_ref()
+                .and_then(|footer| parse_language_footer(&footer.text))
+                .ok_or(not_a_translation)?;
+
+            // Translation replies reference the message they translate;
+            // thread posts have no reference, but their thread started from it
+            let original = match translation_msg.referenced_message.as_deref() {
+                Some(original) => original.clone(),
+                None => match translation_msg.message_reference.as_ref().and_then(|r| r.message_id) {
+                    Some(reference) => channel_id.message(ctx.http(), reference).await?,
+                    None => thread_starter(ctx, channel_id).await.ok_or(original_gone)?,
+                },
+            };
+            (source_lang, target_lang, machine_translation, original)
+        }
+    };
+
+    TranslationFeedbackRepo::create(
+        &ctx.data().pool,
+        NewTranslationFeedback {
+            guild_id: guild_id.to_string(),
+            channel_id: channel_id.to_string(),
+            message_id: message_id.to_string(),
+            user_id: ctx.author().id.to_string(),
+            source_lang,
+            target_lang,
+            original_text: original.content,
+            machine_translation,
+            correction,
+        },
+    )
+    .await?;
+
+    ctx.send(
+        poise::CreateReply::default()
+            .content("Thanks! Your correction has been recorded and will help improve translations.")
+            .ephemeral(true),
+    )
+    .await?;
+    Ok(())
+}
+
+/// The message a thread was started from. A thread shares its starter
+/// message's ID, and that message lives in the parent channel.
+async fn thread_starter(ctx: Context<'_>, channel_id: serenity::ChannelId) -> Option<serenity::Message> {
+    let thread = channel_id.to_channel(ctx.serenity_context()).await.ok()?.guild()?;
+    let parent = thread.parent_id?;
+    parent
+        .message(ctx.http(), serenity::MessageId::new(channel_id.get()))
+        .await
+        .ok()
+}
+
+/// Parse a Discord message link (`.../channels/<guild>/<channel>/<message>`)
+/// or a bare message ID.
+fn parse_message_reference(
+    input: &str,
+) -> Option<(Option<serenity::GuildId>, Option<serenity::ChannelId>, serenity::MessageId)> {
+    let input = input.trim();
+    let id = |s: &str| s.parse::<u64>().ok().filter(|id| *id != 0);
+
+    if let Some(message_id) = id(input) {
+        return Some((None, None, serenity::MessageId::new(message_id)));
+    }
+
+    let path = input.split_once("/channels/")?.1;
+    let mut parts = path.trim_end_matches('/').split('/');
+    let (guild, channel, message) = (parts.next()?, parts.next()?, parts.next()?);
+    if parts.next().is_some() {
+        return None;
+    }
+    Some((
+        Some(serenity::GuildId::new(id(guild)?)),
+        Some(serenity::ChannelId::new(id(channel)?)),
+        serenity::MessageId::new(id(message)?),
+    ))
+}
+
+/// Parse the "EN → ES" footer of a translation reply into lowercase codes.
+fn parse_language_footer(footer: &str) -> Option<(String, String)> {
+    let (source, target) = footer.split_once('→')?;
+    let target = target.split_whitespace().next()?;
+    let source = source.trim();
+    if source.is_empty() {
+        return None;
+    }
+    Some((source.to_lowercase(), target.to_lowercase()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_message_link() {
+        let (guild, channel, message) =
+            parse_message_reference("https://discord.com/channels/111/222/333").unwrap();
+        assert_eq!(guild, Some(serenity::GuildId::new(111)));
+        assert_eq!(channel, Some(serenity::ChannelId::new(222)));
+        assert_eq!(message, serenity::MessageId::new(333));
+
+        assert!(parse_message_reference("https://ptb.discord.com/channels/1/2/3").is_some());
+    }
+
+    #[test]
+    fn test_parse_message_id() {
+        let (guild, channel, message) = parse_message_reference(" 333 ").unwrap();
+        assert!(guild.is_none() && channel.is_none());
+        assert_eq!(message, serenity::MessageId::new(333));
+    }
+
+    #[test]
+    fn test_parse_message_reference_rejects_garbage() {
+        assert!(parse_message_reference("hello").is_none());
+        assert!(parse_message_reference("0").is_none());
+        assert!(parse_message_reference("https://discord.com/channels/1/2").is_none());
+        assert!(parse_message_reference("https://discord.com/channels/1/2/x").is_none());
+    }
+
+    #[test]
+    fn test_parse_language_footer() {
+        assert_eq!(
+            parse_language_footer("EN → ES"),
+            Some(("en".to_string(), "es".to_string()))
+        );
+        assert_eq!(
+            parse_language_footer("JA → EN (cached)"),
+            Some(("ja".to_string(), "en".to_string()))
+        );
+        assert!(parse_language_footer("no arrow here").is_none());
+    }
+}
@@ -1,10 +1,16 @@
+pub mod admin;
+pub mod channels;
+pub mod feedback;
 pub mod mylang;
 pub mod setup;
 pub mod translate;
 pub mod voice;
 pub mod webview;
 
-pub use mylang::{mylang, mypreferences};
+pub use admin::admin;
+pub use channels::channels;
+pub use feedback::feedback;
+pub use mylang::{mylang, mypreferences, whoami};
 pub use setup::setup;
 pub use translate::{languages, translate};
 pub use voice::{voice, voiceconfig};
@@ -18,12 +24,45 @@ type Error = Box<dyn std::error::Error + Send + Sync>;
 pub fn all_commands() -> Vec<poise::Command<Data, Error>> {
     vec![
         setup(),
+        channels(),
         translate(),
         languages(),
+        feedback(),
         mylang(),
         mypreferences(),
+        whoami(),
         webview(),
         voice(),
         voiceconfig(),
+        admin(),
     ]
 }
+
+/// Top-level commands that need a working voice stack
+const VOICE_COMMANDS: &[&str] = &["voice", "voiceconfig"];
+
+/// Commands to register, leaving out voice commands when voice is unavailable
+pub fn enabled_commands(voice_enabled: bool) -> Vec<poise::Command<Data, Error>> {
+    all_commands()
+        .into_iter()
+        .filter(|command| voice_enabled || !VOICE_COMMANDS.contains(&command.name.as_str()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_voice_commands_disabled_without_voice() {
+        let names = |commands: Vec<poise::Command<Data, Error>>| {
+            commands.into_iter().map(|c| c.name).collect::<Vec<_>>()
+        };
+
+        let text_only = names(enabled_commands(false));
+        assert!(!text_only.iter().any(|n| VOICE_COMMANDS.contains(&n.as_str())));
+        assert!(text_only.contains(&"translate".to_string()));
+
+        assert_eq!(enabled_commands(true).len(), all_commands().len());
+    }
+}
@@ -1,18 +1,24 @@
+use crate::bot::commands::translate::check_formality;
+use crate::bot::handler::channel_target_languages;
 use crate::bot::Data;
-use crate::db::UserPreferenceRepo;
-use crate::translation::Language;
+use crate::db::{UserGlobalPreferenceRepo, UserPreferenceRepo};
+use crate::translation::{Formality, Language};
 use poise::serenity_prelude as serenity;
 
 type Error = Box<dyn std::error::Error + Send + Sync>;
 type Context<'a> = poise::Context<'a, Data, Error>;
 
 /// Set your preferred language for translations
-#[poise::command(slash_command, guild_only)]
+///
+/// In a DM with the bot this sets the language direct messages are
+/// translated into, which applies outside every server.
+#[poise::command(slash_command)]
 pub async fn mylang(
     ctx: Context<'_>,
     #[description = "Your preferred language code (e.g., 'en', 'es', 'fr')"] language: String,
+    #[description = "Formal or informal wording for your /translate results, where the language has both"]
+    formality: Option<Formality>,
 ) -> Result<(), Error> {
-    let guild_id = ctx.guild_id().ok_or("Must be used in a guild")?.to_string();
     let user_id = ctx.author().id.to_string();
 
     // Validate language
@@ -24,15 +30,41 @@ pub async fn mylang(
         )
     })?;
 
+    if let Some(formality) = formality {
+        if ctx.guild_id().is_none() {
+            return Err("Formality is set per server; run `/mylang` there to choose one.".into());
+        }
+        let codes = [lang.code().to_string()];
+        check_formality(ctx.data().translator.capabilities().as_ref(), formality, &codes)?;
+    }
+
+    let Some(guild_id) = ctx.guild_id().map(|id| id.to_string()) else {
+        UserGlobalPreferenceRepo::set_language(&ctx.data().pool, &user_id, lang.code()).await?;
+        ctx.say(format!(
+            "Messages you DM me will be translated into **{}** ({}).",
+            lang.name(),
+            lang.code()
+        ))
+        .await?;
+        return Ok(());
+    };
+
     UserPreferenceRepo::set_language(&ctx.data().pool, &user_id, &guild_id, lang.code()).await?;
+    if let Some(formality) = formality {
+        UserPreferenceRepo::set_formality(&ctx.data().pool, &user_id, &guild_id, formality).await?;
+    }
+    ctx.data().settings.invalidate_user(&user_id, &guild_id);
 
-    ctx.say(format!(
+    let mut message = format!(
         "Your preferred language has been set to **{}** ({}).\n\
         Translations will be delivered in this language when available.",
         lang.name(),
         lang.code()
-    ))
-    .await?;
+    );
+    if let Some(formality) = formality {
+        message.push_str(&format!("\nYour `/translate` results use **{}** wording.", formality));
+    }
+    ctx.say(message).await?;
 
     Ok(())
 }
@@ -63,6 +95,7 @@ pub async fn mypreferences(ctx: Context<'_>) -> Result<(), Error> {
                     if p.auto_translate { "Enabled" } else { "Disabled" },
                     true,
                 )
+                .field("Formality", Formality::from(p.formality.as_str()).as_str(), true)
                 .color(0x5865F2);
 
             ctx.send(poise::CreateReply::default().embed(embed)).await?;
@@ -78,3 +111,129 @@ pub async fn mypreferences(ctx: Context<'_>) -> Result<(), Error> {
 
     Ok(())
 }
+
+/// See which languages you get translations in here, and why
+#[poise::command(slash_command, guild_only)]
+pub async fn whoami(ctx: Context<'_>) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Must be used in a guild")?.to_string();
+    let channel_id = ctx.channel_id().to_string();
+    let user_id = ctx.author().id.to_string();
+    let cache = &ctx.data().settings;
+
+    let Some(settings) = cache.guild_settings(&guild_id).await? else {
+        ctx.send(
+            poise::CreateReply::default()
+                .content("LinguaBridge is not configured for this server. Run `/setup init` to get started.")
+                .ephemeral(true),
+        )
+        .await?;
+        return Ok(());
+    };
+    let pref = cache.user_preference(&user_id, &guild_id).await?;
+    let allowed = cache.allowed_languages(&channel_id).await?;
+    let targets = channel_target_languages(&settings, &allowed);
+    let channel_enabled = settings.enabled_channels.contains(&channel_id);
+
+    let embed = serenity::CreateEmbed::default()
+        .title("Your Effective Settings")
+        .description(explain_languages(
+            pref.as_ref().map(|p| p.preferred_language.as_str()),
+            &settings.default_language,
+            &targets,
+            channel_enabled,
+        ))
+        .field(
+            "Your Preference",
+            pref.as_ref()
+                .map_or("Not set".to_string(), |p| language_label(&p.preferred_language)),
+            true,
+        )
+        .field(
+            "Auto-Translate",
+            match &pref {
+                Some(p) if !p.auto_translate => "Disabled",
+                _ => "Enabled",
+            },
+            true,
+        )
+        .field("Server Default", language_label(&settings.default_language), true)
+        .field(
+            "This Channel's Targets",
+            if targets.is_empty() {
+                "None".to_string()
+            } else {
+                targets.iter().map(|t| format!("`{}`", t)).collect::<Vec<_>>().join(", ")
+            },
+            false,
+        )
+        .color(0x5865F2);
+
+    ctx.send(poise::CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+    Ok(())
+}
+
+fn language_label(code: &str) -> String {
+    let name = Language::from_code(code).map_or("Unknown", |l| l.name());
+    format!("{} (`{}`)", name, code)
+}
+
+/// One line saying which language a user sees translations in and which
+/// link of the chain (their preference, the channel's targets, the server
+/// default) decided it
+fn explain_languages(
+    preferred: Option<&str>,
+    default_language: &str,
+    targets: &[String],
+    channel_enabled: bool,
+) -> String {
+    let list = targets.iter().map(|t| format!("**{}**", t)).collect::<Vec<_>>().join(", ");
+    if !channel_enabled {
+        return "Messages in this channel aren't translated; `/channels enable` turns it on.".to_string();
+    }
+    if targets.is_empty() {
+        return "This channel's language whitelist excludes every server target, so nothing is translated here."
+            .to_string();
+    }
+    match preferred {
+        Some(lang) if targets.iter().any(|t| t == lang) => {
+            format!("You see translations in **{}** because your preference is `{}`.", lang, lang)
+        }
+        Some(lang) => format!(
+            "Your preference is `{}`, but this channel only translates into {}, so you see those.",
+            lang, list
+        ),
+        None if targets.len() == 1 && targets[0] == default_language => format!(
+            "You see translations in **{}** because you haven't set a preference and it is the server default.",
+            default_language
+        ),
+        None => format!(
+            "You haven't set a preference with `/mylang`, so you see this channel's targets: {}.",
+            list
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn langs(codes: &[&str]) -> Vec<String> {
+        codes.iter().map(|c| c.to_string()).collect()
+    }
+
+    #[test]
+    fn test_explain_languages_names_the_deciding_setting() {
+        let targets = langs(&["es", "fr"]);
+        assert_eq!(
+            explain_languages(Some("es"), "en", &targets, true),
+            "You see translations in **es** because your preference is `es`."
+        );
+        assert!(explain_languages(Some("de"), "en", &targets, true)
+            .starts_with("Your preference is `de`, but this channel only translates into **es**, **fr**"));
+        assert!(explain_languages(None, "en", &langs(&["en"]), true).contains("server default"));
+        assert!(explain_languages(None, "en", &targets, true).contains("this channel's targets"));
+        assert!(explain_languages(Some("es"), "en", &targets, false).contains("aren't translated"));
+        assert!(explain_languages(Some("es"), "en", &[], true).contains("whitelist"));
+    }
+}
@@ -1,6 +1,12 @@
+use crate::bot::discord_limits::{join_fitting, truncate, EMBED_DESCRIPTION, EMBED_FIELD_VALUE};
+use crate::bot::commands::translate::check_formality;
+use crate::bot::prefix::validate_prefix;
 use crate::bot::Data;
-use crate::db::{GuildRepo, NewGuild};
-use crate::translation::Language;
+use crate::db::{
+    ConfigHistoryRepo, ConfigSetting, GuildRepo, IgnoreListRepo, IgnoredKind, NewGuild,
+    OutputMode, ProgressReaction, WebSessionRepo,
+};
+use crate::translation::{parse_context, Formality, Language};
 use poise::serenity_prelude as serenity;
 
 type Error = Box<dyn std::error::Error + Send + Sync>;
@@ -11,7 +17,24 @@ type Context<'a> = poise::Context<'a, Data, Error>;
     slash_command,
     guild_only,
     required_permissions = "ADMINISTRATOR",
-    subcommands("setup_init", "setup_channel", "setup_languages", "setup_status")
+    subcommands(
+        "setup_init",
+        "setup_channel",
+        "setup_languages",
+        "setup_foreign",
+        "setup_skipprefix",
+        "setup_bots",
+        "setup_ignore",
+        "setup_output",
+        "setup_progress",
+        "setup_prefix",
+        "setup_context",
+        "setup_formality",
+        "setup_undo",
+        "setup_status",
+        "setup_sessions",
+        "setup_revoke"
+    )
 )]
 pub async fn setup(_ctx: Context<'_>) -> Result<(), Error> {
     Ok(())
@@ -31,6 +54,7 @@ pub async fn setup_init(ctx: Context<'_>) -> Result<(), Error> {
     };
 
     GuildRepo::upsert(&ctx.data().pool, new_guild).await?;
+    ctx.data().settings.invalidate_guild(&guild_id);
 
     ctx.say(format!(
         "LinguaBridge initialized for **{}**!\n\n\
@@ -64,9 +88,12 @@ pub async fn setup_channel(
 
     if enable {
         GuildRepo::enable_channel(&ctx.data().pool, &guild_id, &channel_id).await?;
+        ctx.data().settings.invalidate_guild(&guild_id);
         ctx.say(format!("Translation enabled in <#{}>", channel.id)).await?;
     } else {
         GuildRepo::disable_channel(&ctx.data().pool, &guild_id, &channel_id).await?;
+        ctx.data().settings.invalidate_guild(&guild_id);
+        ctx.data().broadcast.forget_text_channel(&channel_id);
         ctx.say(format!("Translation disabled in <#{}>", channel.id)).await?;
     }
 
@@ -98,8 +125,12 @@ pub async fn setup_languages(
     let mut invalid_langs = Vec::new();
 
     for lang in &langs {
-        if Language::from_code(lang).is_some() {
-            valid_langs.push(lang.clone());
+        if let Some(language) = Language::from_code(lang) {
+            // Store the canonical code so `zh-tw` and `zh-Hant` are one language
+            let code = language.code().to_string();
+            if !valid_langs.contains(&code) {
+                valid_langs.push(code);
+            }
         } else {
             invalid_langs.push(lang.clone());
         }
@@ -118,6 +149,7 @@ pub async fn setup_languages(
     }
 
     GuildRepo::set_target_languages(&ctx.data().pool, &guild_id, &valid_langs).await?;
+    ctx.data().settings.invalidate_guild(&guild_id);
 
     let mut response = format!(
         "Target languages set: **{}**",
@@ -135,6 +167,430 @@ pub async fn setup_languages(
     Ok(())
 }
 
+/// Only translate messages written outside the server's default language
+#[poise::command(slash_command, guild_only, rename = "foreign")]
+pub async fn setup_foreign(
+    ctx: Context<'_>,
+    #[description = "Skip messages already in the default language"] enable: bool,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Must be used in a guild")?.to_string();
+
+    // Ensure guild exists
+    let settings = GuildRepo::get_settings(&ctx.data().pool, &guild_id)
+        .await?
+        .ok_or("Please run `/setup init` first")?;
+
+    GuildRepo::set_translate_only_foreign(&ctx.data().pool, &guild_id, enable).await?;
+    ctx.data().settings.invalidate_guild(&guild_id);
+
+    if enable {
+        ctx.say(format!(
+            "Only messages not written in **{}** will be translated.",
+            settings.default_language
+        ))
+        .await?;
+    } else {
+        ctx.say("All messages in enabled channels will be translated.").await?;
+    }
+
+    Ok(())
+}
+
+/// Choose whether messages from other bots and webhooks are translated
+#[poise::command(slash_command, guild_only, rename = "bots")]
+pub async fn setup_bots(
+    ctx: Context<'_>,
+    #[description = "Translate messages posted by other bots"] bots: Option<bool>,
+    #[description = "Translate messages posted through webhooks (bridged chats)"] webhooks: Option<bool>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Must be used in a guild")?.to_string();
+
+    // Ensure guild exists
+    let settings = GuildRepo::get_settings(&ctx.data().pool, &guild_id)
+        .await?
+        .ok_or("Please run `/setup init` first")?;
+
+    let bots = bots.unwrap_or(settings.translate_bot_messages);
+    let webhooks = webhooks.unwrap_or(settings.translate_webhooks);
+    GuildRepo::set_automated_sources(&ctx.data().pool, &guild_id, bots, webhooks).await?;
+    ctx.data().settings.invalidate_guild(&guild_id);
+
+    let describe = |enabled: bool| if enabled { "translated" } else { "ignored" };
+    ctx.say(format!(
+        "Messages from other bots are **{}**; webhook messages are **{}**.",
+        describe(bots),
+        describe(webhooks)
+    ))
+    .await?;
+
+    Ok(())
+}
+
+/// Exclude users or roles from text and voice translation
+#[poise::command(
+    slash_command,
+    guild_only,
+    rename = "ignore",
+    subcommands("setup_ignore_add", "setup_ignore_remove"),
+    subcommand_required
+)]
+pub async fn setup_ignore(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Stop translating a user's or role's messages and speech
+#[poise::command(slash_command, guild_only, rename = "add")]
+pub async fn setup_ignore_add(
+    ctx: Context<'_>,
+    #[description = "User to ignore, e.g. a music bot"] user: Option<serenity::User>,
+    #[description = "Role to ignore"] role: Option<serenity::Role>,
+) -> Result<(), Error> {
+    update_ignore_list(ctx, user, role, true).await
+}
+
+/// Translate an ignored user or role again
+#[poise::command(slash_command, guild_only, rename = "remove")]
+pub async fn setup_ignore_remove(
+    ctx: Context<'_>,
+    #[description = "User to translate again"] user: Option<serenity::User>,
+    #[description = "Role to translate again"] role: Option<serenity::Role>,
+) -> Result<(), Error> {
+    update_ignore_list(ctx, user, role, false).await
+}
+
+async fn update_ignore_list(
+    ctx: Context<'_>,
+    user: Option<serenity::User>,
+    role: Option<serenity::Role>,
+    ignore: bool,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Must be used in a guild")?.to_string();
+    GuildRepo::get_settings(&ctx.data().pool, &guild_id)
+        .await?
+        .ok_or("Please run `/setup init` first")?;
+
+    let mut entries = Vec::new();
+    if let Some(user) = user {
+        entries.push((IgnoredKind::User, user.id.to_string(), format!("<@{}>", user.id)));
+    }
+    if let Some(role) = role {
+        entries.push((IgnoredKind::Role, role.id.to_string(), format!("<@&{}>", role.id)));
+    }
+    if entries.is_empty() {
+        return Err("Choose a user or a role".into());
+    }
+
+    let pool = &ctx.data().pool;
+    let mut lines = Vec::new();
+    for (kind, id, mention) in entries {
+        let changed = if ignore {
+            IgnoreListRepo::add(pool, &guild_id, kind, &id).await?
+        } else {
+            IgnoreListRepo::remove(pool, &guild_id, kind, &id).await?
+        };
+        lines.push(match (ignore, changed) {
+            (true, true) => format!("{} is now ignored.", mention),
+            (true, false) => format!("{} was already ignored.", mention),
+            (false, true) => format!("{} will be translated again.", mention),
+            (false, false) => format!("{} wasn't ignored.", mention),
+        });
+    }
+    ctx.data().settings.invalidate_ignore_list(&guild_id);
+
+    // Mentions in the reply shouldn't ping anyone
+    ctx.send(
+        poise::CreateReply::default()
+            .content(lines.join("\n"))
+            .allowed_mentions(serenity::CreateAllowedMentions::new()),
+    )
+    .await?;
+    Ok(())
+}
+
+/// How translations are posted, as offered to admins
+#[derive(Debug, poise::ChoiceParameter)]
+pub enum OutputChoice {
+    #[name = "Reply to the message"]
+    Reply,
+    #[name = "Thread on the message"]
+    Thread,
+    #[name = "Post as the author (webhook)"]
+    Webhook,
+}
+
+impl From<OutputChoice> for OutputMode {
+    fn from(choice: OutputChoice) -> Self {
+        match choice {
+            OutputChoice::Reply => OutputMode::Reply,
+            OutputChoice::Thread => OutputMode::Thread,
+            OutputChoice::Webhook => OutputMode::Webhook,
+        }
+    }
+}
+
+/// Choose how translations are posted in this server
+#[poise::command(slash_command, guild_only, rename = "output")]
+pub async fn setup_output(
+    ctx: Context<'_>,
+    #[description = "Where translations appear"] mode: OutputChoice,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Must be used in a guild")?.to_string();
+
+    // Ensure guild exists
+    if GuildRepo::get_by_guild_id(&ctx.data().pool, &guild_id)
+        .await?
+        .is_none()
+    {
+        ctx.say("Please run `/setup init` first to initialize LinguaBridge.").await?;
+        return Ok(());
+    }
+
+    let mode = OutputMode::from(mode);
+    GuildRepo::set_output_mode(&ctx.data().pool, &guild_id, mode).await?;
+    ctx.data().settings.invalidate_guild(&guild_id);
+
+    let message = match mode {
+        OutputMode::Reply => "Translations will be posted as replies.",
+        OutputMode::Thread => {
+            "Translations will be posted in a thread on each message. I need the **Create Public Threads** permission."
+        }
+        OutputMode::Webhook => {
+            "Translations will be posted under the author's name and avatar. I need the **Manage Webhooks** permission."
+        }
+    };
+    ctx.say(message).await?;
+
+    Ok(())
+}
+
+/// Progress reactions, as offered to admins
+#[derive(Debug, poise::ChoiceParameter)]
+pub enum ProgressChoice {
+    #[name = "Off"]
+    Off,
+    #[name = "While translating"]
+    Pending,
+    #[name = "While translating, then ✅ or ❌"]
+    Outcome,
+}
+
+impl From<ProgressChoice> for ProgressReaction {
+    fn from(choice: ProgressChoice) -> Self {
+        match choice {
+            ProgressChoice::Off => ProgressReaction::Off,
+            ProgressChoice::Pending => ProgressReaction::Pending,
+            ProgressChoice::Outcome => ProgressReaction::Outcome,
+        }
+    }
+}
+
+/// React to messages while they're being translated
+#[poise::command(slash_command, guild_only, rename = "progress")]
+pub async fn setup_progress(
+    ctx: Context<'_>,
+    #[description = "Which reactions to show"] mode: ProgressChoice,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Must be used in a guild")?.to_string();
+
+    // Ensure guild exists
+    if GuildRepo::get_by_guild_id(&ctx.data().pool, &guild_id)
+        .await?
+        .is_none()
+    {
+        ctx.say("Please run `/setup init` first to initialize LinguaBridge.").await?;
+        return Ok(());
+    }
+
+    let mode = ProgressReaction::from(mode);
+    GuildRepo::set_progress_reaction(&ctx.data().pool, &guild_id, mode).await?;
+    ctx.data().settings.invalidate_guild(&guild_id);
+
+    let message = match mode {
+        ProgressReaction::Off => "Messages won't get progress reactions.",
+        ProgressReaction::Pending => {
+            "Messages will show 🔄 while they're being translated. I need the **Add Reactions** permission."
+        }
+        ProgressReaction::Outcome => {
+            "Messages will show 🔄 while they're being translated, then ✅ or ❌. I need the **Add Reactions** permission."
+        }
+    };
+    ctx.say(message).await?;
+
+    Ok(())
+}
+
+/// Set the prefix that keeps a message from being translated
+#[poise::command(slash_command, guild_only, rename = "skipprefix")]
+pub async fn setup_skipprefix(
+    ctx: Context<'_>,
+    #[description = "Prefix to skip translation (leave empty to disable)"] prefix: Option<String>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Must be used in a guild")?.to_string();
+
+    // Ensure guild exists
+    if GuildRepo::get_by_guild_id(&ctx.data().pool, &guild_id)
+        .await?
+        .is_none()
+    {
+        ctx.say("Please run `/setup init` first to initialize LinguaBridge.").await?;
+        return Ok(());
+    }
+
+    let prefix = prefix.map(|p| p.trim().to_string()).unwrap_or_default();
+    GuildRepo::set_skip_prefix(&ctx.data().pool, &guild_id, &prefix).await?;
+    ctx.data().settings.invalidate_guild(&guild_id);
+
+    if prefix.is_empty() {
+        ctx.say("Skip prefix disabled. All messages in enabled channels will be translated.")
+            .await?;
+    } else {
+        ctx.say(format!(
+            "Messages starting with `{}` will not be translated.",
+            prefix
+        ))
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Set the prefix for text commands in this server
+#[poise::command(slash_command, guild_only, rename = "prefix")]
+pub async fn setup_prefix(
+    ctx: Context<'_>,
+    #[description = "Text command prefix (leave empty to use the default)"] prefix: Option<String>,
+) -> Result<(), Error> {
+    let guild = ctx.guild_id().ok_or("Must be used in a guild")?;
+    let guild_id = guild.to_string();
+    let pool = &ctx.data().pool;
+
+    if GuildRepo::get_by_guild_id(pool, &guild_id).await?.is_none() {
+        ctx.say("Please run `/setup init` first to initialize LinguaBridge.").await?;
+        return Ok(());
+    }
+
+    let prefix = prefix.filter(|p| !p.is_empty());
+    if let Some(prefix) = &prefix {
+        validate_prefix(prefix)?;
+    }
+    GuildRepo::set_command_prefix(pool, &guild_id, prefix.as_deref()).await?;
+    ctx.data().prefixes.invalidate(guild.get());
+
+    match prefix {
+        Some(prefix) => {
+            ctx.say(format!(
+                "Text commands now use `{}` (e.g. `{}languages`).",
+                prefix, prefix
+            ))
+            .await?
+        }
+        None => {
+            ctx.say(format!(
+                "Text commands use the default prefix `{}` again.",
+                ctx.data().prefixes.default_prefix()
+            ))
+            .await?
+        }
+    };
+
+    Ok(())
+}
+
+/// Tell the translation model what this server's chat is like
+#[poise::command(slash_command, guild_only, rename = "context")]
+pub async fn setup_context(
+    ctx: Context<'_>,
+    #[description = "E.g. 'casual gaming chat, keep slang' or 'formal business'; leave empty to remove"]
+    #[max_length = 200]
+    context: Option<String>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Must be used in a guild")?.to_string();
+    let pool = &ctx.data().pool;
+
+    if GuildRepo::get_by_guild_id(pool, &guild_id).await?.is_none() {
+        ctx.say("Please run `/setup init` first to initialize LinguaBridge.").await?;
+        return Ok(());
+    }
+
+    let context = parse_context(context.as_deref().unwrap_or_default())?;
+    GuildRepo::set_translation_context(pool, &guild_id, context.as_deref()).await?;
+    ctx.data().settings.invalidate_guild(&guild_id);
+
+    let message = match context {
+        Some(context) => format!(
+            "Translations are now made with the context: *{}*\nChannels with their own `/channels context` keep theirs.",
+            context
+        ),
+        None => "Translations no longer carry a server-wide context.".to_string(),
+    };
+    ctx.say(message).await?;
+    Ok(())
+}
+
+/// Choose formal or informal wording for message translations
+#[poise::command(slash_command, guild_only, rename = "formality")]
+pub async fn setup_formality(
+    ctx: Context<'_>,
+    #[description = "Formal (e.g. German 'Sie'), informal ('du'), or whatever the model picks"]
+    formality: Formality,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Must be used in a guild")?.to_string();
+    let pool = &ctx.data().pool;
+
+    let Some(settings) = GuildRepo::get_settings(pool, &guild_id).await? else {
+        ctx.say("Please run `/setup init` first to initialize LinguaBridge.").await?;
+        return Ok(());
+    };
+    let unsupported = check_formality(
+        ctx.data().translator.capabilities().as_ref(),
+        formality,
+        &settings.target_languages,
+    )?;
+
+    GuildRepo::set_formality(pool, &guild_id, formality).await?;
+    ctx.data().settings.invalidate_guild(&guild_id);
+
+    let mut message = format!("Message translations now use **{}** wording.", formality);
+    if !unsupported.is_empty() {
+        message.push_str(&format!(
+            "\nTranslations into {} keep their usual wording; the translation service has no formality for them.",
+            unsupported.join(", ")
+        ));
+    }
+    ctx.say(message).await?;
+    Ok(())
+}
+
+/// Revert the last language change
+#[poise::command(slash_command, guild_only, rename = "undo")]
+pub async fn setup_undo(ctx: Context<'_>) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Must be used in a guild")?.to_string();
+
+    let Some(change) = ConfigHistoryRepo::undo_last(&ctx.data().pool, &guild_id).await? else {
+        ctx.say("Nothing to undo.").await?;
+        return Ok(());
+    };
+    ctx.data().settings.invalidate_guild(&guild_id);
+
+    let message = match change.setting() {
+        ConfigSetting::DefaultLanguage => {
+            format!("Default language reverted to **{}**", change.previous_value)
+        }
+        ConfigSetting::TargetLanguages => {
+            let languages: Vec<String> =
+                serde_json::from_str(&change.previous_value).unwrap_or_default();
+            if languages.is_empty() {
+                "Target languages reverted to **none**".to_string()
+            } else {
+                format!("Target languages reverted to **{}**", languages.join(", "))
+            }
+        }
+    };
+    ctx.say(message).await?;
+
+    Ok(())
+}
+
 /// Show current LinguaBridge configuration
 #[poise::command(slash_command, guild_only, rename = "status")]
 pub async fn setup_status(ctx: Context<'_>) -> Result<(), Error> {
@@ -149,15 +605,33 @@ pub async fn setup_status(ctx: Context<'_>) -> Result<(), Error> {
         }
     };
 
+    let command_prefix = match GuildRepo::get_command_prefix(&ctx.data().pool, &guild_id).await? {
+        Some(prefix) => format!("`{}`", prefix),
+        None => format!("`{}` (default)", ctx.data().prefixes.default_prefix()),
+    };
+
     let channels_str = if settings.enabled_channels.is_empty() {
         "None".to_string()
     } else {
-        settings
+        let mentions: Vec<String> = settings
             .enabled_channels
             .iter()
             .map(|c| format!("<#{}>", c))
-            .collect::<Vec<_>>()
-            .join(", ")
+            .collect();
+        join_fitting(&mentions, ", ", EMBED_FIELD_VALUE)
+    };
+
+    let ignores = IgnoreListRepo::get(&ctx.data().pool, &guild_id).await?;
+    let ignored_str = if ignores.is_empty() {
+        "None".to_string()
+    } else {
+        let mentions: Vec<String> = ignores
+            .users
+            .iter()
+            .map(|u| format!("<@{}>", u))
+            .chain(ignores.roles.iter().map(|r| format!("<@&{}>", r)))
+            .collect();
+        join_fitting(&mentions, ", ", EMBED_FIELD_VALUE)
     };
 
     let embed = serenity::CreateEmbed::default()
@@ -166,12 +640,124 @@ pub async fn setup_status(ctx: Context<'_>) -> Result<(), Error> {
         .field("Subscription", settings.subscription_tier.as_str(), true)
         .field(
             "Target Languages",
-            settings.target_languages.join(", "),
+            join_fitting(&settings.target_languages, ", ", EMBED_FIELD_VALUE),
             false,
         )
         .field("Enabled Channels", channels_str, false)
+        .field(
+            "Only Foreign Messages",
+            if settings.translate_only_foreign { "Yes" } else { "No" },
+            true,
+        )
+        .field(
+            "Skip Prefix",
+            if settings.skip_prefix.is_empty() {
+                "Disabled".to_string()
+            } else {
+                format!("`{}`", truncate(&settings.skip_prefix, EMBED_FIELD_VALUE - 2))
+            },
+            true,
+        )
+        .field("Command Prefix", command_prefix, true)
+        .field("Output", settings.output_mode.as_str(), true)
+        .field("Progress Reactions", settings.progress_reaction.as_str(), true)
+        .field("Formality", settings.formality.as_str(), true)
+        .field(
+            "Bot Messages",
+            if settings.translate_bot_messages { "Translated" } else { "Ignored" },
+            true,
+        )
+        .field(
+            "Webhook Messages",
+            if settings.translate_webhooks { "Translated" } else { "Ignored" },
+            true,
+        )
+        .field("Ignored Users and Roles", ignored_str, false)
+        .field(
+            "Translation Context",
+            settings.translation_context.as_deref().unwrap_or("None"),
+            false,
+        )
         .color(0x5865F2);
 
     ctx.send(poise::CreateReply::default().embed(embed)).await?;
     Ok(())
 }
+
+/// List active web view sessions for this server
+#[poise::command(slash_command, guild_only, rename = "sessions")]
+pub async fn setup_sessions(ctx: Context<'_>) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Must be used in a guild")?.to_string();
+    let sessions = WebSessionRepo::list_for_guild(&ctx.data().pool, &guild_id).await?;
+
+    let description = if sessions.is_empty() {
+        "No active web view sessions.".to_string()
+    } else {
+        let lines: Vec<String> = sessions
+            .iter()
+            .map(|s| {
+                let channel = s
+                    .channel_id
+                    .as_ref()
+                    .map(|c| format!("<#{}>", c))
+                    .unwrap_or_else(|| "all channels".to_string());
+                format!(
+                    "`#{}` <@{}> — {} — expires <t:{}:R>",
+                    s.id,
+                    s.user_id,
+                    channel,
+                    s.expires_at.timestamp()
+                )
+            })
+            .collect();
+        join_fitting(&lines, "\n", EMBED_DESCRIPTION)
+    };
+
+    let embed = serenity::CreateEmbed::default()
+        .title("Active Web Sessions")
+        .description(description)
+        .footer(serenity::CreateEmbedFooter::new(
+            "Use /setup revoke to end a session",
+        ))
+        .color(0x5865F2);
+
+    ctx.send(poise::CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+    Ok(())
+}
+
+/// Revoke web view sessions so their viewers are disconnected
+#[poise::command(slash_command, guild_only, rename = "revoke")]
+pub async fn setup_revoke(
+    ctx: Context<'_>,
+    #[description = "Session number from /setup sessions"] session: Option<i64>,
+    #[description = "Revoke all sessions of this user"] user: Option<serenity::User>,
+    #[description = "Revoke every session in this server"] all: Option<bool>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Must be used in a guild")?.to_string();
+    let pool = &ctx.data().pool;
+
+    let message = match (session, user, all.unwrap_or(false)) {
+        (Some(id), None, false) => {
+            if WebSessionRepo::revoke(pool, &guild_id, id).await? {
+                format!("Revoked session `#{}`.", id)
+            } else {
+                format!("No active session `#{}` in this server.", id)
+            }
+        }
+        (None, Some(user), false) => {
+            let user_id = user.id.to_string();
+            let count = WebSessionRepo::revoke_for_guild(pool, &guild_id, Some(&user_id)).await?;
+            format!("Revoked {} session(s) of <@{}>.", count, user.id)
+        }
+        (None, None, true) => {
+            let count = WebSessionRepo::revoke_for_guild(pool, &guild_id, None).await?;
+            format!("Revoked all {} session(s) in this server.", count)
+        }
+        _ => return Err("Specify exactly one of `session`, `user` or `all:true`.".into()),
+    };
+
+    ctx.send(poise::CreateReply::default().content(message).ephemeral(true))
+        .await?;
+    Ok(())
+}
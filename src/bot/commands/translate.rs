@@ -1,21 +1,37 @@
+use crate::bot::discord_limits::{split, truncate, EMBED_FIELD_VALUE, EMBED_TOTAL, MESSAGE_CONTENT};
 use crate::bot::Data;
-use crate::translation::Language;
+use crate::db::UserPreferenceRepo;
+use crate::translation::{Capabilities, Formality, Language, TranslationHints};
 use poise::serenity_prelude as serenity;
 
 type Error = Box<dyn std::error::Error + Send + Sync>;
 type Context<'a> = poise::Context<'a, Data, Error>;
 
-/// Translate text to a specific language
+/// Most target languages a single `/translate` may fan out to
+const MAX_TARGET_LANGUAGES: usize = 5;
+
+/// Room left in the embed for its title and footer
+const EMBED_FRAME_RESERVE: usize = 200;
+
+/// Field text for a translation too long to show in the embed
+const POSTED_BELOW: &str = "Too long for an embed, posted below.";
+
+/// Translate text to one or more languages
 #[poise::command(slash_command, guild_only)]
 pub async fn translate(
     ctx: Context<'_>,
     #[description = "Text to translate"] text: String,
     #[description = "Target language code (e.g., 'es', 'fr', 'ja')"] target: String,
     #[description = "Source language (auto-detect if not specified)"] source: Option<String>,
+    #[description = "More target languages, comma or space separated (e.g., 'de,ja')"] languages: Option<String>,
+    #[description = "Formal or informal wording, where the language has both (default: your /mylang setting)"]
+    formality: Option<Formality>,
 ) -> Result<(), Error> {
-    // Validate target language
-    let target_lang = Language::from_code(&target)
-        .ok_or_else(|| format!("Unknown language: {}. Use ISO 639-1 codes like 'en', 'es', 'fr'.", target))?;
+    let targets = parse_target_languages(&target, languages.as_deref())?;
+    let codes: Vec<String> = targets.iter().map(|l| l.code().to_string()).collect();
+    if let Some(formality) = formality {
+        check_formality(ctx.data().translator.capabilities().as_ref(), formality, &codes)?;
+    }
 
     // Validate source language if provided
     if let Some(ref src) = source {
@@ -27,36 +43,175 @@ pub async fn translate(
     // Defer response since translation may take time
     ctx.defer().await?;
 
-    let result = if let Some(src_lang) = source {
-        ctx.data()
-            .translator
-            .translate(&text, &src_lang, target_lang.code())
-            .await?
-    } else {
-        ctx.data()
-            .translator
-            .translate_auto(&text, target_lang.code())
-            .await?
+    let translator = &ctx.data().translator;
+    let guild_id = ctx.guild_id().map(|id| id.get());
+    let formality = match formality {
+        Some(formality) => formality,
+        None => default_formality(ctx).await?,
     };
+    let source_lang = match source {
+        Some(src_lang) => src_lang,
+        None => translator.detect_language(&text, guild_id).await?.language,
+    };
+    let hints = TranslationHints {
+        formality,
+        ..Default::default()
+    };
+    let results = translator
+        .translate_to_multiple(&text, &source_lang, &codes, guild_id, hints)
+        .await;
 
-    let embed = serenity::CreateEmbed::default()
-        .title("Translation")
-        .field("Original", &result.original_text, false)
-        .field(target_lang.name(), &result.translated_text, false)
+    let mut all_cached = true;
+    let mut translations = Vec::new();
+    for (lang, result) in targets.iter().zip(results) {
+        match result {
+            Ok(result) => {
+                all_cached &= result.cached;
+                translations.push((lang.name().to_string(), result.translated_text));
+            }
+            Err(e) => {
+                all_cached = false;
+                translations.push((lang.name().to_string(), format!("⚠️ Translation failed: {}", e)));
+            }
+        }
+    }
+    let (fields, follow_ups) = layout_translations(&text, translations);
+
+    let mut embed = serenity::CreateEmbed::default().title("Translation");
+    for (name, value) in fields {
+        embed = embed.field(name, value, false);
+    }
+
+    let target_codes: Vec<String> = codes.iter().map(|c| c.to_uppercase()).collect();
+    let embed = embed
         .footer(serenity::CreateEmbedFooter::new(format!(
             "{} → {} {}",
-            result.source_lang.to_uppercase(),
-            result.target_lang.to_uppercase(),
-            if result.cached { "(cached)" } else { "" }
+            source_lang.to_uppercase(),
+            target_codes.join(", "),
+            if all_cached { "(cached)" } else { "" }
         )))
         .color(0x5865F2);
 
     ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    for part in follow_ups {
+        ctx.send(
+            poise::CreateReply::default()
+                .content(part)
+                .allowed_mentions(serenity::CreateAllowedMentions::new()),
+        )
+        .await?;
+    }
     Ok(())
 }
 
+/// The caller's own formality, else their server's
+async fn default_formality(ctx: Context<'_>) -> Result<Formality, Error> {
+    let Some(guild_id) = ctx.guild_id().map(|id| id.to_string()) else {
+        return Ok(Formality::Default);
+    };
+    let data = ctx.data();
+    let user_id = ctx.author().id.to_string();
+    let own = UserPreferenceRepo::get(&data.pool, &user_id, &guild_id)
+        .await?
+        .map(|pref| Formality::from(pref.formality.as_str()))
+        .unwrap_or_default();
+    if !own.is_default() {
+        return Ok(own);
+    }
+    Ok(data
+        .settings
+        .guild_settings(&guild_id)
+        .await?
+        .map(|settings| settings.formality)
+        .unwrap_or_default())
+}
+
+/// Check that the inference backend can apply `formality` to at least one of
+/// `targets`, returning the targets it can't apply it to
+pub(crate) fn check_formality(
+    capabilities: Option<&Capabilities>,
+    formality: Formality,
+    targets: &[String],
+) -> Result<Vec<String>, String> {
+    if formality.is_default() {
+        return Ok(Vec::new());
+    }
+    let Some(capabilities) = capabilities else {
+        return Err("The translation service hasn't reported what it supports yet; try again shortly.".to_string());
+    };
+    let (supported, unsupported): (Vec<&String>, Vec<&String>) = targets
+        .iter()
+        .partition(|code| capabilities.supports_formality(code));
+    if supported.is_empty() {
+        return Err(format!(
+            "The translation service can't make {} translations into {}.",
+            formality,
+            targets.join(", ")
+        ));
+    }
+    Ok(unsupported.into_iter().cloned().collect())
+}
+
+/// Split the reply into embed fields and follow-up messages.
+///
+/// The original is truncated to one field. Each translation gets a field if
+/// it fits there and in what's left of the embed; longer ones are posted in
+/// full as follow-up messages instead, so nothing is cut off.
+fn layout_translations(
+    original: &str,
+    translations: Vec<(String, String)>,
+) -> (Vec<(String, String)>, Vec<String>) {
+    let original = truncate(original, EMBED_FIELD_VALUE);
+    let mut used = EMBED_FRAME_RESERVE + "Original".len() + original.chars().count();
+    let mut fields = vec![("Original".to_string(), original)];
+    let mut follow_ups = Vec::new();
+
+    for (name, value) in translations {
+        let value_len = value.chars().count();
+        let name_len = name.chars().count();
+        if value_len <= EMBED_FIELD_VALUE && used + name_len + value_len <= EMBED_TOTAL {
+            used += name_len + value_len;
+            fields.push((name, value));
+        } else {
+            follow_ups.extend(split(&format!("**{}**\n{}", name, value), MESSAGE_CONTENT));
+            used += name_len + POSTED_BELOW.chars().count();
+            fields.push((name, POSTED_BELOW.to_string()));
+        }
+    }
+    (fields, follow_ups)
+}
+
+/// Resolve `target` plus the `extra` codes into a deduplicated list of at
+/// most [`MAX_TARGET_LANGUAGES`] languages. Both accept codes separated by
+/// commas, spaces or both, so `es fr` and `es, fr` work like `es,fr`.
+fn parse_target_languages(target: &str, extra: Option<&str>) -> Result<Vec<Language>, String> {
+    let mut languages = Vec::new();
+    let codes = std::iter::once(target)
+        .chain(extra)
+        .flat_map(|list| list.split(|c: char| c == ',' || c.is_whitespace()));
+    for code in codes.filter(|c| !c.is_empty()) {
+        let lang = Language::from_code(code).ok_or_else(|| {
+            format!("Unknown language: {}. Use ISO 639-1 codes like 'en', 'es', 'fr'.", code)
+        })?;
+        if !languages.contains(&lang) {
+            languages.push(lang);
+        }
+    }
+
+    if languages.is_empty() {
+        return Err("Give at least one target language.".to_string());
+    }
+    if languages.len() > MAX_TARGET_LANGUAGES {
+        return Err(format!(
+            "You can translate into at most {} languages at once.",
+            MAX_TARGET_LANGUAGES
+        ));
+    }
+    Ok(languages)
+}
+
 /// List all supported languages
-#[poise::command(slash_command)]
+#[poise::command(slash_command, prefix_command)]
 pub async fn languages(ctx: Context<'_>) -> Result<(), Error> {
     let langs: Vec<String> = Language::all()
         .iter()
@@ -81,3 +236,108 @@ pub async fn languages(ctx: Context<'_>) -> Result<(), Error> {
     ctx.send(poise::CreateReply::default().embed(embed)).await?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_formality_against_capabilities() {
+        let capabilities = Capabilities {
+            model: "m".to_string(),
+            supported_languages: Vec::new(),
+            formality_languages: vec!["de".to_string(), "ja".to_string()],
+            tts_available: false,
+            stt_available: false,
+            max_input_chars: None,
+        };
+        let targets = |codes: &[&str]| codes.iter().map(|c| c.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(
+            check_formality(Some(&capabilities), Formality::Formal, &targets(&["de", "en"])),
+            Ok(vec!["en".to_string()])
+        );
+        assert!(check_formality(Some(&capabilities), Formality::Informal, &targets(&["en"])).is_err());
+        assert!(check_formality(None, Formality::Formal, &targets(&["de"])).is_err());
+        // Nothing to check without a formality
+        assert_eq!(check_formality(None, Formality::Default, &targets(&["en"])), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_parse_target_languages() {
+        let langs = parse_target_languages("en", Some("es, ja,en")).unwrap();
+        assert_eq!(
+            langs.iter().map(|l| l.code()).collect::<Vec<_>>(),
+            vec!["en", "es", "ja"]
+        );
+
+        assert_eq!(parse_target_languages("fr", None).unwrap().len(), 1);
+        assert_eq!(parse_target_languages("fr", Some(" , ")).unwrap().len(), 1);
+
+        // Spaces separate codes like commas do, in either field
+        let langs = parse_target_languages("es fr", Some("de  ja, it")).unwrap();
+        assert_eq!(
+            langs.iter().map(|l| l.code()).collect::<Vec<_>>(),
+            vec!["es", "fr", "de", "ja", "it"]
+        );
+    }
+
+    #[test]
+    fn test_parse_target_languages_rejects_unknown_and_too_many() {
+        let err = parse_target_languages("en", Some("es,xx")).unwrap_err();
+        assert!(err.contains("xx"));
+
+        assert!(parse_target_languages("", None).is_err());
+        assert!(parse_target_languages("en", Some("es,fr,de,it,ja")).is_err());
+        assert!(parse_target_languages("en es", Some("fr de it ja")).is_err());
+
+        // A space-joined list is checked code by code, not as one unknown code
+        let err = parse_target_languages("en", Some("es xx")).unwrap_err();
+        assert!(err.contains("Unknown language: xx."));
+    }
+
+    #[test]
+    fn test_layout_keeps_short_translations_in_embed() {
+        let (fields, follow_ups) = layout_translations(
+            "hola",
+            vec![("English".to_string(), "hello".to_string())],
+        );
+        assert_eq!(fields.len(), 2);
+        assert_eq!(fields[1].1, "hello");
+        assert!(follow_ups.is_empty());
+    }
+
+    #[test]
+    fn test_layout_moves_long_translations_to_follow_ups() {
+        let long = "palabra ".repeat(500);
+        let (fields, follow_ups) = layout_translations(
+            &long,
+            vec![
+                ("English".to_string(), "word ".repeat(500)),
+                ("French".to_string(), "mot".to_string()),
+            ],
+        );
+
+        assert!(fields[0].1.chars().count() <= EMBED_FIELD_VALUE);
+        assert_eq!(fields[1].1, POSTED_BELOW);
+        assert_eq!(fields[2].1, "mot");
+        assert_eq!(follow_ups.len(), 2);
+        assert!(follow_ups[0].starts_with("**English**\n"));
+        assert!(follow_ups.iter().all(|m| m.chars().count() <= MESSAGE_CONTENT));
+    }
+
+    #[test]
+    fn test_layout_respects_embed_total() {
+        let translations = (0..5)
+            .map(|i| (format!("Language {}", i), "x".repeat(EMBED_FIELD_VALUE)))
+            .collect();
+        let (fields, follow_ups) = layout_translations(&"y".repeat(2000), translations);
+
+        let total: usize = fields
+            .iter()
+            .map(|(name, value)| name.chars().count() + value.chars().count())
+            .sum();
+        assert!(total + EMBED_FRAME_RESERVE <= EMBED_TOTAL);
+        assert!(!follow_ups.is_empty());
+    }
+}
@@ -1,12 +1,19 @@
 //! Voice channel translation commands.
 
+use crate::bot::discord_limits::{join_fitting, truncate, EMBED_FIELD_VALUE};
 use crate::bot::Data;
-use crate::db::{NewVoiceTranscriptSettings, VoiceTranscriptRepo};
-use crate::translation::Language;
-use crate::voice::{VoiceClientConfig, VoiceManager};
+use crate::db::{
+    DbPool, GuildRepo, NewVoiceChannelSettings, NewVoiceTranscriptSettings, TtsMode, UserPreferenceRepo,
+    VoiceChannelRepo, VoiceHistoryRepo, VoiceTranscriptRepo, AUTO_DETECT_LANGUAGE,
+};
+use crate::bot::commands::translate::check_formality;
+use crate::translation::{Formality, Language};
+use crate::voice::export::{self, ExportFormat};
+use crate::bot::transcript_threads;
+use crate::bot::voice_session::{self, default_tts_mode};
 use poise::serenity_prelude as serenity;
-use std::sync::Arc;
-use tracing::{error, info};
+use std::collections::HashSet;
+use tracing::error;
 
 // Re-export for convenience
 
@@ -17,7 +24,7 @@ type Context<'a> = poise::Context<'a, Data, Error>;
 #[poise::command(
     slash_command,
     guild_only,
-    subcommands("join", "leave", "status", "cachestats", "url", "transcript"),
+    subcommands("join", "leave", "status", "cachestats", "url", "transcript", "language", "names", "anonymize", "timelimit"),
     subcommand_required
 )]
 pub async fn voice(_ctx: Context<'_>) -> Result<(), Error> {
@@ -57,70 +64,45 @@ pub async fn join(
 
     ctx.defer().await?;
 
-    // Get Songbird manager
+    // Check if already in this channel
     let manager = songbird::get(ctx.serenity_context())
         .await
         .ok_or("Voice client not initialized")?;
-
-    // Check if already in a channel
     if let Some(call) = manager.get(guild_id) {
-        let current_channel = call.lock().await.current_channel();
-        if current_channel.is_some() {
-            let current_id = current_channel.unwrap();
-            if current_id.0.get() == channel_id.get() {
-                return Err("Already in this voice channel!".into());
-            }
-            // Leave current channel first
-            manager.remove(guild_id).await?;
+        if call.lock().await.current_channel().map(|c| c.0.get()) == Some(channel_id.get()) {
+            return Err("Already in this voice channel!".into());
         }
     }
 
-    // Join the channel
-    let call = manager.join(guild_id, channel_id).await.map_err(|e| {
-        error!(error = %e, "Failed to join voice channel");
-        format!("Failed to join voice channel: {}", e)
-    })?;
-
-    // Set up voice receive handler
     let config = crate::config::AppConfig::get();
-    let voice_config = VoiceClientConfig {
-        url: config.voice.url.clone(),
-        ..Default::default()
-    };
-
-    let voice_manager = Arc::new(VoiceManager::new(manager.clone(), voice_config));
-    let handler = voice_manager.get_or_create_handler(guild_id.get(), channel_id.get());
-
-    // Register event handler for receiving audio
-    // We need to use Arc::unwrap_or_clone to get the handler since songbird expects ownership
+    let voice_manager = ctx
+        .data()
+        .voice
+        .as_ref()
+        .ok_or("Voice translation not initialized")?;
+    let spoken_language = match voice_session::connect(
+        ctx.serenity_context(),
+        &ctx.data().pool,
+        voice_manager,
+        guild_id,
+        channel_id,
+    )
+    .await
     {
-        let mut call_lock = call.lock().await;
-
-        // Create separate handlers for each event type since songbird takes ownership
-        let handler1 = (*handler).clone();
-        let handler2 = (*handler).clone();
-        let handler3 = (*handler).clone();
-
-        // Enable receiving audio
-        call_lock.add_global_event(
-            songbird::CoreEvent::SpeakingStateUpdate.into(),
-            handler1,
-        );
-        call_lock.add_global_event(
-            songbird::CoreEvent::VoiceTick.into(),
-            handler2,
-        );
-        call_lock.add_global_event(
-            songbird::CoreEvent::ClientDisconnect.into(),
-            handler3,
-        );
-    }
-
-    info!(
-        guild_id = guild_id.get(),
-        channel_id = channel_id.get(),
-        "Joined voice channel for translation"
-    );
+        Ok(spoken_language) => spoken_language,
+        Err(e) => {
+            let Some(failure) = e.downcast_ref::<voice_session::JoinFailure>() else {
+                return Err(e);
+            };
+            let embed = serenity::CreateEmbed::default()
+                .title("Couldn't Join Voice")
+                .description(failure.to_string())
+                .field("How to fix it", failure.fix(), false)
+                .color(0xED4245);
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
 
     let embed = serenity::CreateEmbed::default()
         .title("Voice Translation Active")
@@ -131,11 +113,14 @@ pub async fn join(
         ))
         .field(
             "TTS Playback",
-            if config.voice.enable_tts_playback {
-                "Enabled"
-            } else {
-                "Disabled"
-            },
+            tts_mode_label(
+                channel_tts_mode(&ctx.data().pool, guild_id.get(), channel_id.get()).await,
+            ),
+            true,
+        )
+        .field(
+            "Spoken Language",
+            source_language_label(spoken_language.as_deref()),
             true,
         )
         .footer(serenity::CreateEmbedFooter::new(
@@ -152,17 +137,14 @@ pub async fn join(
 pub async fn leave(ctx: Context<'_>) -> Result<(), Error> {
     let guild_id = ctx.guild_id().ok_or("Must be used in a server")?;
 
-    let manager = songbird::get(ctx.serenity_context())
-        .await
-        .ok_or("Voice client not initialized")?;
-
-    if manager.get(guild_id).is_none() {
-        return Err("Not in a voice channel".into());
-    }
-
-    manager.remove(guild_id).await?;
-
-    info!(guild_id = guild_id.get(), "Left voice channel");
+    voice_session::disconnect(
+        ctx.serenity_context(),
+        &ctx.data().pool,
+        ctx.data().voice.as_deref(),
+        &ctx.data().broadcast,
+        guild_id,
+    )
+    .await?;
 
     let embed = serenity::CreateEmbed::default()
         .title("Voice Translation Stopped")
@@ -183,8 +165,7 @@ pub async fn status(ctx: Context<'_>) -> Result<(), Error> {
         .ok_or("Voice client not initialized")?;
 
     let embed = if let Some(call) = manager.get(guild_id) {
-        let call = call.lock().await;
-        let channel = call.current_channel();
+        let channel = call.lock().await.current_channel();
 
         if let Some(channel_id) = channel {
             let config = crate::config::AppConfig::get();
@@ -199,16 +180,15 @@ pub async fn status(ctx: Context<'_>) -> Result<(), Error> {
                 )
                 .field(
                     "TTS Playback",
-                    if config.voice.enable_tts_playback {
-                        "Enabled"
-                    } else {
-                        "Disabled"
-                    },
+                    tts_mode_label(
+                        channel_tts_mode(&ctx.data().pool, guild_id.get(), channel_id.0.get())
+                            .await,
+                    ),
                     true,
                 )
                 .field(
                     "Inference Service",
-                    &config.voice.url,
+                    truncate(&config.voice.url, EMBED_FIELD_VALUE),
                     false,
                 )
                 .color(0x57F287)
@@ -228,10 +208,27 @@ pub async fn status(ctx: Context<'_>) -> Result<(), Error> {
             .color(0xFEE75C)
     };
 
+    let embed = match ctx.data().voice.as_ref() {
+        Some(voice_manager) => embed.field(
+            "Servers Using Voice",
+            capacity_label(voice_manager.active_guild_count(), voice_manager.max_guilds()),
+            true,
+        ),
+        None => embed,
+    };
+
     ctx.send(poise::CreateReply::default().embed(embed)).await?;
     Ok(())
 }
 
+/// Active voice guilds against the configured cap, e.g. "3 / 10"
+fn capacity_label(active: usize, max: Option<usize>) -> String {
+    match max {
+        Some(max) => format!("{} / {}", active, max),
+        None => format!("{} (no limit)", active),
+    }
+}
+
 /// View translation cache statistics
 #[poise::command(slash_command, guild_only)]
 pub async fn cachestats(ctx: Context<'_>) -> Result<(), Error> {
@@ -273,15 +270,60 @@ pub async fn cachestats(ctx: Context<'_>) -> Result<(), Error> {
     Ok(())
 }
 
+/// Where TTS audio should be played, as offered to users
+#[derive(Debug, poise::ChoiceParameter)]
+pub enum TtsChoice {
+    #[name = "Off"]
+    Off,
+    #[name = "Web view only"]
+    WebOnly,
+    #[name = "Voice channel only"]
+    Voice,
+    #[name = "Voice channel and web view"]
+    Both,
+}
+
+impl From<TtsChoice> for TtsMode {
+    fn from(choice: TtsChoice) -> Self {
+        match choice {
+            TtsChoice::Off => TtsMode::Off,
+            TtsChoice::WebOnly => TtsMode::WebOnly,
+            TtsChoice::Voice => TtsMode::Voice,
+            TtsChoice::Both => TtsMode::Both,
+        }
+    }
+}
+
 /// Configure voice translation settings for this server
 #[poise::command(slash_command, guild_only)]
 pub async fn voiceconfig(
     ctx: Context<'_>,
     #[description = "Target language for translations (e.g., 'en', 'es', 'ja')"]
     target_language: Option<String>,
-    #[description = "Enable TTS playback of translations"] enable_tts: Option<bool>,
+    #[description = "Where to play TTS audio of translations"] tts: Option<TtsChoice>,
+    #[description = "Formal or informal spoken translations, where the language has both"]
+    formality: Option<Formality>,
+    #[description = "Only speak these target languages (e.g., 'es,ja'), or 'all'"]
+    tts_languages: Option<String>,
+    #[description = "Voice channel to configure (uses your current channel if not specified)"]
+    channel: Option<serenity::GuildChannel>,
 ) -> Result<(), Error> {
-    let _guild_id = ctx.guild_id().ok_or("Must be used in a server")?;
+    let guild_id = ctx.guild_id().ok_or("Must be used in a server")?;
+    let pool = &ctx.data().pool;
+    let config = crate::config::AppConfig::get();
+
+    let channel_id = match channel {
+        Some(ch) if ch.kind != serenity::ChannelType::Voice => {
+            return Err("Please specify a voice channel".into());
+        }
+        Some(ch) => Some(ch.id),
+        None => ctx.guild().and_then(|guild| {
+            guild
+                .voice_states
+                .get(&ctx.author().id)
+                .and_then(|vs| vs.channel_id)
+        }),
+    };
 
     let mut updates = Vec::new();
 
@@ -296,15 +338,83 @@ pub async fn voiceconfig(
         updates.push(format!("Target language: **{}**", lang.to_uppercase()));
     }
 
-    if let Some(tts) = enable_tts {
+    if let Some(tts) = tts {
+        let channel_id = channel_id
+            .ok_or("Join a voice channel or specify one to change its TTS playback.")?;
+        let mode = TtsMode::from(tts);
+        let (guild, channel) = (guild_id.to_string(), channel_id.to_string());
+
+        if VoiceChannelRepo::get_settings(pool, &guild, &channel).await?.is_some() {
+            VoiceChannelRepo::set_tts_mode(pool, &guild, &channel, mode).await?;
+        } else {
+            VoiceChannelRepo::upsert(
+                pool,
+                NewVoiceChannelSettings {
+                    guild_id: guild,
+                    voice_channel_id: channel,
+                    target_language: config.voice.default_target_language.clone(),
+                    tts_mode: mode,
+                },
+            )
+            .await?;
+        }
         updates.push(format!(
-            "TTS playback: **{}**",
-            if tts { "Enabled" } else { "Disabled" }
+            "TTS playback in <#{}>: **{}**",
+            channel_id,
+            tts_mode_label(mode)
         ));
     }
 
+    if let Some(formality) = formality {
+        let channel_id = channel_id
+            .ok_or("Join a voice channel or specify one to change its formality.")?;
+        let (guild, channel) = (guild_id.to_string(), channel_id.to_string());
+        voice_session::ensure_channel_settings(pool, &guild, &channel).await?;
+        let target = VoiceChannelRepo::get_settings(pool, &guild, &channel)
+            .await?
+            .map(|settings| settings.target_language)
+            .unwrap_or_else(|| config.voice.default_target_language.clone());
+        check_formality(ctx.data().translator.capabilities().as_ref(), formality, &[target])?;
+        VoiceChannelRepo::set_formality(pool, &guild, &channel, formality).await?;
+
+        // Apply right away if the bot is already listening in this channel
+        if let Some(handler) = ctx
+            .data()
+            .voice
+            .as_ref()
+            .and_then(|vm| vm.handler(guild_id.get()))
+        {
+            if handler.channel_id() == channel_id.get() {
+                handler.set_formality(formality).await;
+            }
+        }
+        updates.push(format!("Formality in <#{}>: **{}**", channel_id, formality));
+    }
+
+    if let Some(tts_languages) = &tts_languages {
+        let channel_id = channel_id
+            .ok_or("Join a voice channel or specify one to change its TTS languages.")?;
+        let languages = parse_tts_languages(tts_languages)?;
+        let (guild, channel) = (guild_id.to_string(), channel_id.to_string());
+        voice_session::ensure_channel_settings(pool, &guild, &channel).await?;
+        VoiceChannelRepo::set_tts_languages(pool, &guild, &channel, &languages).await?;
+        sync_transcript_audio(ctx, guild_id, channel_id).await;
+
+        let label = if languages.is_empty() {
+            "All languages".to_string()
+        } else {
+            languages.join(", ").to_uppercase()
+        };
+        updates.push(format!("TTS languages in <#{}>: **{}**", channel_id, label));
+    }
+
     if updates.is_empty() {
-        let config = crate::config::AppConfig::get();
+        let tts_label = match channel_id {
+            Some(channel_id) => {
+                tts_mode_label(channel_tts_mode(pool, guild_id.get(), channel_id.get()).await)
+            }
+            None => tts_mode_label(default_tts_mode()),
+        };
         let embed = serenity::CreateEmbed::default()
             .title("Voice Configuration")
             .description("Current settings for this server:")
@@ -313,15 +423,7 @@ pub async fn voiceconfig(
                 config.voice.default_target_language.to_uppercase(),
                 true,
             )
-            .field(
-                "TTS Playback",
-                if config.voice.enable_tts_playback {
-                    "Enabled"
-                } else {
-                    "Disabled"
-                },
-                true,
-            )
+            .field("TTS Playback", tts_label, true)
             .footer(serenity::CreateEmbedFooter::new(
                 "Use /voiceconfig with options to change settings",
             ))
@@ -329,7 +431,7 @@ pub async fn voiceconfig(
 
         ctx.send(poise::CreateReply::default().embed(embed)).await?;
     } else {
-        // TODO: Save to database per-guild
+        // TODO: Save target language to database per-guild
         let embed = serenity::CreateEmbed::default()
             .title("Voice Configuration Updated")
             .description(updates.join("\n"))
@@ -344,6 +446,221 @@ pub async fn voiceconfig(
     Ok(())
 }
 
+/// Parse a comma-separated list of language codes; `all` or an empty list
+/// clears the limit
+fn parse_tts_languages(input: &str) -> Result<Vec<String>, String> {
+    let mut languages = Vec::new();
+    for code in input.split(',').map(|code| code.trim().to_lowercase()) {
+        if code.is_empty() || code == "all" {
+            continue;
+        }
+        if Language::from_code(&code).is_none() {
+            return Err(format!(
+                "Unknown language: {}. Use ISO 639-1 codes like 'en', 'es', 'fr'.",
+                code
+            ));
+        }
+        if !languages.contains(&code) {
+            languages.push(code);
+        }
+    }
+    Ok(languages)
+}
+
+/// Effective TTS mode for a voice channel
+async fn channel_tts_mode(pool: &DbPool, guild_id: u64, channel_id: u64) -> TtsMode {
+    match VoiceChannelRepo::get_settings(pool, &guild_id.to_string(), &channel_id.to_string()).await
+    {
+        Ok(Some(settings)) => TtsMode::from(settings.tts_mode.as_str()),
+        _ => default_tts_mode(),
+    }
+}
+
+fn tts_mode_label(mode: TtsMode) -> &'static str {
+    match mode {
+        TtsMode::Off => "Disabled",
+        TtsMode::WebOnly => "Web view only",
+        TtsMode::Voice => "Voice channel only",
+        TtsMode::Both => "Voice channel and web view",
+    }
+}
+
+/// Set the spoken language of a voice channel, or let it be detected per utterance
+#[poise::command(slash_command, guild_only)]
+pub async fn language(
+    ctx: Context<'_>,
+    #[description = "Language spoken in the channel (e.g., 'en'), or 'auto' for mixed-language channels"]
+    language: String,
+    #[description = "Voice channel to configure (uses your current channel if not specified)"]
+    channel: Option<serenity::GuildChannel>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Must be used in a server")?;
+    let pool = &ctx.data().pool;
+
+    let channel_id = match channel {
+        Some(ch) if ch.kind != serenity::ChannelType::Voice => {
+            return Err("Please specify a voice channel".into());
+        }
+        Some(ch) => ch.id,
+        None => ctx
+            .guild()
+            .and_then(|guild| {
+                guild
+                    .voice_states
+                    .get(&ctx.author().id)
+                    .and_then(|vs| vs.channel_id)
+            })
+            .ok_or("Join a voice channel or specify one to set its language.")?,
+    };
+
+    let language = language.trim().to_lowercase();
+    let language = if language == AUTO_DETECT_LANGUAGE {
+        language
+    } else {
+        match Language::from_code(&language) {
+            Some(lang) => lang.code().to_string(),
+            None => {
+                return Err(format!(
+                    "Unknown language: {}. Use ISO 639-1 codes like 'en', 'es', 'fr', or 'auto'.",
+                    language
+                )
+                .into())
+            }
+        }
+    };
+
+    let (guild, channel) = (guild_id.to_string(), channel_id.to_string());
+    voice_session::ensure_channel_settings(pool, &guild, &channel).await?;
+    VoiceChannelRepo::set_source_language(pool, &guild, &channel, &language).await?;
+
+    let fixed = (language != AUTO_DETECT_LANGUAGE).then_some(language.as_str());
+
+    // Apply right away if the bot is already listening in this channel
+    if let Some(handler) = ctx
+        .data()
+        .voice
+        .as_ref()
+        .and_then(|vm| vm.handler(guild_id.get()))
+    {
+        if handler.state().read().await.channel_id == channel_id.get() {
+            handler.set_source_language(fixed.map(Into::into)).await;
+        }
+    }
+
+    let embed = serenity::CreateEmbed::default()
+        .title("Voice Language Updated")
+        .description(format!(
+            "Spoken language in <#{}>: **{}**",
+            channel_id,
+            source_language_label(fixed)
+        ))
+        .color(0x57F287);
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
+/// Choose whether transcripts name speakers by server nickname or username
+#[poise::command(slash_command, guild_only)]
+pub async fn names(
+    ctx: Context<'_>,
+    #[description = "Show server nicknames (off shows usernames)"] nicknames: bool,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Must be used in a server")?;
+    let guild = guild_id.to_string();
+
+    GuildRepo::get_settings(&ctx.data().pool, &guild)
+        .await?
+        .ok_or("Please run `/setup init` first")?;
+    GuildRepo::set_use_nicknames(&ctx.data().pool, &guild, nicknames).await?;
+    ctx.data().settings.invalidate_guild(&guild);
+
+    // Relabel speakers in an ongoing session from their next utterance
+    if let Some(handler) = ctx
+        .data()
+        .voice
+        .as_ref()
+        .and_then(|vm| vm.handler(guild_id.get()))
+    {
+        handler.forget_speaker_names();
+    }
+
+    ctx.say(if nicknames {
+        "Voice transcripts now show speakers' **server nicknames**."
+    } else {
+        "Voice transcripts now show speakers' **usernames**."
+    })
+    .await?;
+    Ok(())
+}
+
+/// Show voice speakers as "Speaker N" and keep their user IDs out of history
+#[poise::command(slash_command, guild_only)]
+pub async fn anonymize(
+    ctx: Context<'_>,
+    #[description = "Hide who is speaking"] enabled: bool,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Must be used in a server")?;
+    let guild = guild_id.to_string();
+
+    GuildRepo::get_settings(&ctx.data().pool, &guild)
+        .await?
+        .ok_or("Please run `/setup init` first")?;
+    GuildRepo::set_anonymize_speakers(&ctx.data().pool, &guild, enabled).await?;
+    ctx.data().settings.invalidate_guild(&guild);
+
+    // Numbering starts over with the next person to speak
+    if let Some(handler) = ctx
+        .data()
+        .voice
+        .as_ref()
+        .and_then(|vm| vm.handler(guild_id.get()))
+    {
+        handler.forget_speaker_names();
+    }
+
+    ctx.say(if enabled {
+        "Voice speakers are now shown as **Speaker 1, Speaker 2, …** and their user IDs are no longer stored."
+    } else {
+        "Voice speakers are shown **by name** again."
+    })
+    .await?;
+    Ok(())
+}
+
+/// Leave voice on my own after this long in a channel
+#[poise::command(slash_command, guild_only)]
+pub async fn timelimit(
+    ctx: Context<'_>,
+    #[description = "Minutes before I leave (0: no limit; leave out to use the bot's default)"] minutes: Option<u32>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Must be used in a server")?;
+    let guild = guild_id.to_string();
+
+    GuildRepo::get_settings(&ctx.data().pool, &guild)
+        .await?
+        .ok_or("Please run `/setup init` first")?;
+    let secs = minutes.map(|m| i64::from(m) * 60);
+    GuildRepo::set_max_voice_session_secs(&ctx.data().pool, &guild, secs).await?;
+    ctx.data().settings.invalidate_guild(&guild);
+
+    let default_secs = crate::config::AppConfig::get().voice.max_session_secs;
+    let limit = match voice_session::session_limit(default_secs, secs) {
+        Some(limit) => format!("I'll leave voice channels after **{}**", voice_session::describe_session_limit(limit)),
+        None => "Voice sessions have **no time limit**".to_string(),
+    };
+    let source = if secs.is_some() { "in this server" } else { "(the bot's default)" };
+    ctx.say(format!("{} {}. This counts from when I joined.", limit, source)).await?;
+    Ok(())
+}
+
+fn source_language_label(language: Option<&str>) -> String {
+    match language {
+        Some(code) => code.to_uppercase(),
+        None => "Auto-detect per utterance".to_string(),
+    }
+}
+
 /// Get the public web URL for a voice channel
 #[poise::command(slash_command, guild_only)]
 pub async fn url(
@@ -408,9 +725,26 @@ pub async fn url(
     Ok(())
 }
 
+/// Voice transcript threads and exports
+#[poise::command(
+    slash_command,
+    guild_only,
+    subcommands(
+        "transcript_configure",
+        "transcript_audio",
+        "transcript_export",
+        "transcript_optout",
+        "transcript_repair"
+    ),
+    subcommand_required
+)]
+pub async fn transcript(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
 /// Enable or disable transcript posting to Discord threads
-#[poise::command(slash_command, guild_only)]
-pub async fn transcript(
+#[poise::command(slash_command, guild_only, rename = "configure")]
+pub async fn transcript_configure(
     ctx: Context<'_>,
     #[description = "Enable transcript posting"] enable: bool,
     #[description = "Text channel to post transcripts in"] text_channel: Option<serenity::GuildChannel>,
@@ -446,6 +780,7 @@ pub async fn transcript(
         )
         .await
         .ok(); // Ignore error if settings don't exist
+        sync_transcript_audio(ctx, guild_id, voice_channel_id).await;
 
         let embed = serenity::CreateEmbed::default()
             .title("Voice Transcripts Disabled")
@@ -467,16 +802,22 @@ pub async fn transcript(
         .map(|s| s.trim().to_lowercase())
         .collect::<Vec<_>>();
 
-    // Validate languages
+    // Validate languages and store their canonical codes
+    let mut canonical = Vec::with_capacity(lang_list.len());
     for lang in &lang_list {
-        if crate::translation::Language::from_code(lang).is_none() {
+        let Some(language) = crate::translation::Language::from_code(lang) else {
             return Err(format!(
                 "Unknown language: {}. Use ISO 639-1 codes like 'en', 'es', 'fr'.",
                 lang
             )
             .into());
+        };
+        let code = language.code().to_string();
+        if !canonical.contains(&code) {
+            canonical.push(code);
         }
     }
+    let lang_list = canonical;
 
     // Determine text channel for transcripts
     let transcript_channel = if let Some(ch) = text_channel {
@@ -504,43 +845,18 @@ pub async fn transcript(
     // Create threads for each language
     let mut thread_names = Vec::new();
     for lang in &lang_list {
-        let lang_name = Language::from_code(lang)
-            .map(|l| l.name())
-            .unwrap_or(lang.as_str());
-        let thread_name = format!("Voice Translation - {}", lang_name);
-
-        // Create a thread in the text channel
-        let thread_builder = serenity::CreateThread::new(thread_name.clone())
-            .kind(serenity::ChannelType::PublicThread)
-            .auto_archive_duration(serenity::AutoArchiveDuration::OneDay);
-
-        match transcript_channel
-            .create_thread(ctx.http(), thread_builder)
-            .await
+        match transcript_threads::create_thread(
+            ctx.http(),
+            pool,
+            &guild_id.to_string(),
+            &voice_channel_id.to_string(),
+            &transcript_channel.to_string(),
+            lang,
+        )
+        .await
         {
             Ok(thread) => {
-                // Store thread ID in database
-                VoiceTranscriptRepo::set_thread_id(
-                    pool,
-                    &guild_id.to_string(),
-                    &voice_channel_id.to_string(),
-                    lang,
-                    &thread.id.to_string(),
-                )
-                .await
-                .ok();
-
-                thread_names.push(format!("<#{}> ({})", thread.id, lang.to_uppercase()));
-
-                // Send initial message to thread
-                let welcome_msg = format!(
-                    "This thread will receive real-time voice transcripts translated to **{}**.\n\nTranscripts from <#{}> will appear here.",
-                    lang_name, voice_channel_id
-                );
-                let _ = thread
-                    .id
-                    .send_message(ctx.http(), serenity::CreateMessage::new().content(welcome_msg))
-                    .await;
+                thread_names.push(format!("<#{}> ({})", thread, lang.to_uppercase()));
             }
             Err(e) => {
                 error!(error = %e, lang = %lang, "Failed to create transcript thread");
@@ -549,13 +865,10 @@ pub async fn transcript(
         }
     }
 
-    let lang_display = lang_list
-        .iter()
-        .map(|l| l.to_uppercase())
-        .collect::<Vec<_>>()
-        .join(", ");
+    let lang_codes: Vec<String> = lang_list.iter().map(|l| l.to_uppercase()).collect();
+    let lang_display = join_fitting(&lang_codes, ", ", EMBED_FIELD_VALUE);
 
-    let threads_display = thread_names.join("\n");
+    let threads_display = join_fitting(&thread_names, "\n", EMBED_FIELD_VALUE);
 
     let embed = serenity::CreateEmbed::default()
         .title("Voice Transcripts Enabled")
@@ -572,5 +885,224 @@ pub async fn transcript(
         .color(0x57F287);
 
     ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    sync_transcript_audio(ctx, guild_id, voice_channel_id).await;
+    Ok(())
+}
+
+/// Attach spoken audio to the transcripts posted in one language's thread
+#[poise::command(slash_command, guild_only, rename = "audio")]
+pub async fn transcript_audio(
+    ctx: Context<'_>,
+    #[description = "Transcript language (e.g., 'es')"] language: String,
+    #[description = "Attach TTS audio clips to this language's transcripts"] enabled: bool,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Must be used in a server")?;
+    let voice_channel_id = ctx
+        .guild()
+        .and_then(|guild| {
+            guild
+                .voice_states
+                .get(&ctx.author().id)
+                .and_then(|vs| vs.channel_id)
+        })
+        .ok_or("You must be in a voice channel to configure transcripts.")?;
+
+    let pool = &ctx.data().pool;
+    let (guild, channel) = (guild_id.to_string(), voice_channel_id.to_string());
+    let settings = VoiceTranscriptRepo::get_settings(pool, &guild, &channel)
+        .await?
+        .ok_or("Transcripts aren't set up for this channel. Run `/voice transcript configure enable:true` first.")?;
+
+    let language = Language::from_code(language.trim())
+        .ok_or_else(|| format!("Unknown language: {}.", language))?;
+    let code = language.code();
+    if !settings.get_languages().iter().any(|lang| lang == code) {
+        return Err(format!(
+            "<#{}> has no {} transcript thread. Add it with `/voice transcript configure`.",
+            voice_channel_id,
+            language.name()
+        )
+        .into());
+    }
+
+    VoiceTranscriptRepo::set_language_tts(pool, &guild, &channel, code, enabled).await?;
+    sync_transcript_audio(ctx, guild_id, voice_channel_id).await;
+
+    let message = if enabled {
+        format!(
+            "{} transcripts from <#{}> will include spoken audio.",
+            language.name(),
+            voice_channel_id
+        )
+    } else {
+        format!(
+            "{} transcripts from <#{}> will be text only.",
+            language.name(),
+            voice_channel_id
+        )
+    };
+    ctx.say(message).await?;
+    Ok(())
+}
+
+/// Recreate transcript threads that were deleted
+#[poise::command(slash_command, guild_only, rename = "repair")]
+pub async fn transcript_repair(ctx: Context<'_>) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Must be used in a server")?;
+    let voice_channel_id = ctx
+        .guild()
+        .and_then(|guild| {
+            guild
+                .voice_states
+                .get(&ctx.author().id)
+                .and_then(|vs| vs.channel_id)
+        })
+        .ok_or("You must be in a voice channel to repair its transcripts.")?;
+
+    let pool = &ctx.data().pool;
+    let settings = VoiceTranscriptRepo::get_settings(pool, &guild_id.to_string(), &voice_channel_id.to_string())
+        .await?
+        .filter(|settings| settings.enabled)
+        .ok_or("Transcripts aren't set up for this channel. Run `/voice transcript configure enable:true` first.")?;
+
+    // Checking and creating threads can outlast the 3s reply window
+    ctx.defer().await?;
+    let recreated = transcript_threads::repair(ctx.http(), pool, &settings).await?;
+
+    let message = if recreated.is_empty() {
+        format!("All transcript threads for <#{}> are in place.", voice_channel_id)
+    } else {
+        let threads: Vec<String> = recreated
+            .iter()
+            .map(|(lang, thread)| format!("<#{}> ({})", thread, lang.to_uppercase()))
+            .collect();
+        format!(
+            "Recreated transcript threads for <#{}>:\n{}",
+            voice_channel_id,
+            join_fitting(&threads, "\n", EMBED_FIELD_VALUE)
+        )
+    };
+    ctx.say(message).await?;
+    Ok(())
+}
+
+/// Update TTS requests for the bot's session in `voice_channel_id`, if any
+async fn sync_transcript_audio(
+    ctx: Context<'_>,
+    guild_id: serenity::GuildId,
+    voice_channel_id: serenity::ChannelId,
+) {
+    let Some(handler) = ctx
+        .data()
+        .voice
+        .as_ref()
+        .and_then(|voice| voice.handler(guild_id.get()))
+        .filter(|handler| handler.channel_id() == voice_channel_id.get())
+    else {
+        return;
+    };
+    voice_session::sync_transcript_audio(
+        &ctx.data().pool,
+        &handler,
+        guild_id.get(),
+        voice_channel_id.get(),
+    )
+    .await;
+}
+
+/// Export the stored transcript of a voice channel as a file
+#[poise::command(slash_command, guild_only, rename = "export")]
+pub async fn transcript_export(
+    ctx: Context<'_>,
+    #[description = "Voice channel to export (your current channel if not specified)"]
+    channel: Option<serenity::GuildChannel>,
+    #[description = "File format (plain text if not specified)"] format: Option<ExportFormat>,
+    #[description = "Only include the last N minutes (the latest session if not specified)"]
+    #[min = 1]
+    #[max = 10080]
+    minutes: Option<u32>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Must be used in a server")?;
+    let pool = &ctx.data().pool;
+
+    let voice_channel_id = match channel {
+        Some(ch) if ch.kind != serenity::ChannelType::Voice => {
+            return Err("Please specify a voice channel".into());
+        }
+        Some(ch) => ch.id,
+        None => ctx
+            .guild()
+            .and_then(|guild| {
+                guild
+                    .voice_states
+                    .get(&ctx.author().id)
+                    .and_then(|vs| vs.channel_id)
+            })
+            .ok_or("Join a voice channel or specify one to export its transcript.")?,
+    };
+
+    ctx.defer().await?;
+
+    let now = chrono::Utc::now();
+    let since = now - chrono::Duration::minutes(i64::from(minutes.unwrap_or(24 * 60)));
+    let (guild, channel) = (guild_id.to_string(), voice_channel_id.to_string());
+    let entries = VoiceHistoryRepo::list(pool, &guild, &channel, since, now).await?;
+    let entries = match minutes {
+        Some(_) => entries.as_slice(),
+        None => export::latest_session(&entries, export::SESSION_GAP),
+    };
+
+    let opted_out: HashSet<String> = UserPreferenceRepo::transcript_opt_outs(pool, &guild)
+        .await?
+        .into_iter()
+        .collect();
+    let entries = export::without_opted_out(entries, &opted_out);
+
+    let Some(first) = entries.first() else {
+        ctx.say(format!("No stored transcript for <#{}> in that time range.", voice_channel_id))
+            .await?;
+        return Ok(());
+    };
+
+    let format = format.unwrap_or(ExportFormat::Txt);
+    let attachment = serenity::CreateAttachment::bytes(
+        export::render(&entries, format).into_bytes(),
+        export::file_name(&channel, first.spoken_at, format),
+    );
+
+    ctx.send(
+        poise::CreateReply::default()
+            .content(format!(
+                "Transcript of <#{}>: {} utterances since <t:{}:f>",
+                voice_channel_id,
+                entries.len(),
+                first.spoken_at.timestamp()
+            ))
+            .attachment(attachment),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Leave your speech out of exported transcripts in this server
+#[poise::command(slash_command, guild_only, rename = "optout")]
+pub async fn transcript_optout(
+    ctx: Context<'_>,
+    #[description = "Leave your speech out of transcript exports"] enabled: bool,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Must be used in a server")?.to_string();
+    let user_id = ctx.author().id.to_string();
+
+    UserPreferenceRepo::set_transcript_opt_out(&ctx.data().pool, &user_id, &guild_id, enabled)
+        .await?;
+    ctx.data().settings.invalidate_user(&user_id, &guild_id);
+
+    let message = if enabled {
+        "Your speech will be left out of exported voice transcripts."
+    } else {
+        "Your speech will be included in exported voice transcripts again."
+    };
+    ctx.send(poise::CreateReply::default().content(message).ephemeral(true))
+        .await?;
     Ok(())
 }
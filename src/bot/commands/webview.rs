@@ -54,6 +54,13 @@ pub async fn webview(
         session.session_id
     );
 
+    let dashboard_url = format!(
+        "{}/guild/{}?session={}",
+        config.web.public_url.trim_end_matches('/'),
+        guild_id,
+        session.session_id
+    );
+
     let channel_mention = channel_id
         .as_ref()
         .map(|id| format!("<#{}>", id))
@@ -63,10 +70,12 @@ pub async fn webview(
         .title("Web Translation View")
         .description(format!(
             "View live translations for {} in your browser.\n\n\
-            **[Click here to open]({})** \n\n\
+            **[Click here to open]({})** \n\
+            [Voice channels dashboard]({})\n\n\
             This link expires in {} hours.",
             channel_mention,
             web_url,
+            dashboard_url,
             config.web.session_expiry_hours
         ))
         .field("Session ID", &session.session_id[..8], true)
@@ -0,0 +1,162 @@
+//! Per-user, per-command cooldowns for expensive slash commands.
+//!
+//! Checked from the framework's `command_check` hook before any command runs;
+//! a hit surfaces as a [`CooldownHit`] error that `on_error` turns into an
+//! ephemeral reply.
+
+use dashmap::DashMap;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Prune expired entries once the map grows past this many users/commands
+const PRUNE_THRESHOLD: usize = 10_000;
+
+/// Returned from the command check when a user is still cooling down
+#[derive(Debug)]
+pub struct CooldownHit {
+    pub command: String,
+    pub remaining: Duration,
+}
+
+impl std::fmt::Display for CooldownHit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Round up so "0s" is never shown while the command is still blocked
+        let secs = self.remaining.as_secs() + u64::from(self.remaining.subsec_nanos() > 0);
+        write!(
+            f,
+            "`/{}` is on cooldown. Try again in {}s.",
+            self.command, secs
+        )
+    }
+}
+
+impl std::error::Error for CooldownHit {}
+
+/// Tracks when each user last ran each rate-limited command
+#[derive(Debug)]
+pub struct CommandCooldowns {
+    /// Cooldown per qualified command name (e.g. "voice join")
+    limits: HashMap<String, Duration>,
+    last_used: DashMap<(u64, String), Instant>,
+}
+
+impl CommandCooldowns {
+    /// `limits` maps qualified command names to cooldown seconds; commands
+    /// not listed (or set to 0) are never limited.
+    pub fn new(limits: &HashMap<String, u64>) -> Self {
+        Self {
+            limits: limits
+                .iter()
+                .filter(|(_, secs)| **secs > 0)
+                .map(|(name, secs)| (name.to_lowercase(), Duration::from_secs(*secs)))
+                .collect(),
+            last_used: DashMap::new(),
+        }
+    }
+
+    /// Cooldown configured for `command`, if any
+    pub fn limit(&self, command: &str) -> Option<Duration> {
+        self.limits.get(&command.to_lowercase()).copied()
+    }
+
+    /// Record a use of `command` by `user_id`, or return how long they must wait.
+    pub fn check(&self, user_id: u64, command: &str, now: Instant) -> Result<(), Duration> {
+        let limit = match self.limit(command) {
+            Some(limit) => limit,
+            None => return Ok(()),
+        };
+
+        let key = (user_id, command.to_lowercase());
+        if let Some(last) = self.last_used.get(&key) {
+            let elapsed = now.saturating_duration_since(*last);
+            if elapsed < limit {
+                return Err(limit - elapsed);
+            }
+        }
+
+        self.last_used.insert(key, now);
+        if self.last_used.len() > PRUNE_THRESHOLD {
+            self.prune(now);
+        }
+        Ok(())
+    }
+
+    /// Drop entries whose cooldown has already expired
+    pub fn prune(&self, now: Instant) {
+        self.last_used.retain(|(_, command), last| {
+            self.limits
+                .get(command)
+                .is_some_and(|limit| now.saturating_duration_since(*last) < *limit)
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cooldowns() -> CommandCooldowns {
+        CommandCooldowns::new(&HashMap::from([
+            ("voice join".to_string(), 15),
+            ("translate".to_string(), 5),
+            ("languages".to_string(), 0),
+        ]))
+    }
+
+    #[test]
+    fn test_second_use_within_cooldown_is_blocked() {
+        let cooldowns = cooldowns();
+        let start = Instant::now();
+        assert!(cooldowns.check(1, "voice join", start).is_ok());
+
+        let remaining = cooldowns
+            .check(1, "voice join", start + Duration::from_secs(5))
+            .unwrap_err();
+        assert_eq!(remaining, Duration::from_secs(10));
+
+        assert!(cooldowns
+            .check(1, "voice join", start + Duration::from_secs(15))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_cooldown_is_per_user_and_per_command() {
+        let cooldowns = cooldowns();
+        let now = Instant::now();
+        assert!(cooldowns.check(1, "voice join", now).is_ok());
+        assert!(cooldowns.check(2, "voice join", now).is_ok());
+        assert!(cooldowns.check(1, "translate", now).is_ok());
+        assert!(cooldowns.check(1, "translate", now).is_err());
+    }
+
+    #[test]
+    fn test_unlisted_and_zero_cooldowns_never_block() {
+        let cooldowns = cooldowns();
+        let now = Instant::now();
+        for _ in 0..3 {
+            assert!(cooldowns.check(1, "voice status", now).is_ok());
+            assert!(cooldowns.check(1, "languages", now).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_prune_drops_expired_entries() {
+        let cooldowns = cooldowns();
+        let start = Instant::now();
+        cooldowns.check(1, "translate", start).unwrap();
+        cooldowns.check(1, "voice join", start).unwrap();
+
+        cooldowns.prune(start + Duration::from_secs(10));
+        assert_eq!(cooldowns.last_used.len(), 1);
+        assert!(cooldowns.check(1, "voice join", start + Duration::from_secs(10)).is_err());
+    }
+
+    #[test]
+    fn test_cooldown_hit_message_rounds_up() {
+        let hit = CooldownHit {
+            command: "voice join".to_string(),
+            remaining: Duration::from_millis(2_100),
+        };
+        assert_eq!(hit.to_string(), "`/voice join` is on cooldown. Try again in 3s.");
+    }
+}
@@ -0,0 +1,151 @@
+//! Discord's length limits on what the bot sends.
+//!
+//! The API rejects a message or embed outright when any part is too long,
+//! so text that can grow without bound (translations, transcripts, lists of
+//! channels or languages) is fitted here first. Translations are split across
+//! several messages so nothing is lost; status displays are truncated.
+
+/// Characters allowed in a message's content
+pub const MESSAGE_CONTENT: usize = 2000;
+
+/// Characters allowed in an embed description
+pub const EMBED_DESCRIPTION: usize = 4096;
+
+/// Characters allowed in an embed field value
+pub const EMBED_FIELD_VALUE: usize = 1024;
+
+/// Characters allowed across all of an embed's text
+pub const EMBED_TOTAL: usize = 6000;
+
+/// Characters allowed in a thread name
+pub const THREAD_NAME: usize = 100;
+
+/// Characters allowed in a webhook username
+pub const WEBHOOK_USERNAME: usize = 80;
+
+/// `s` cut to at most `max` characters, ending in "…" if anything was cut
+pub fn truncate(s: &str, max: usize) -> String {
+    if s.chars().count() <= max {
+        return s.to_string();
+    }
+    let mut truncated: String = s.chars().take(max.saturating_sub(1)).collect();
+    truncated.push('…');
+    truncated
+}
+
+/// Split `text` into parts of at most `max` characters.
+///
+/// Parts break at the last newline in the second half of what fits, then at
+/// the last whitespace, and only cut through a word longer than `max`.
+pub fn split(text: &str, max: usize) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut rest = text.trim();
+    while rest.chars().count() > max {
+        let limit = rest
+            .char_indices()
+            .nth(max)
+            .map_or(rest.len(), |(i, _)| i);
+        let window = &rest[..limit];
+        let cut = window
+            .rfind('\n')
+            .filter(|&i| i >= window.len() / 2)
+            .or_else(|| window.rfind(char::is_whitespace))
+            .filter(|&i| i > 0)
+            .unwrap_or(limit);
+        parts.push(rest[..cut].trim_end().to_string());
+        rest = rest[cut..].trim_start();
+    }
+    if !rest.is_empty() {
+        parts.push(rest.to_string());
+    }
+    parts
+}
+
+/// `items` joined with `separator`, dropping trailing items for an
+/// "…and N more" note when the whole list is longer than `max` characters
+pub fn join_fitting(items: &[String], separator: &str, max: usize) -> String {
+    let full = items.join(separator);
+    if full.chars().count() <= max {
+        return full;
+    }
+
+    let separator_len = separator.chars().count();
+    let mut shown = 0;
+    let mut len = 0;
+    for item in items {
+        let item_len = item.chars().count() + if shown == 0 { 0 } else { separator_len };
+        let more = format!("…and {} more", items.len() - shown - 1);
+        if len + item_len + separator_len + more.chars().count() > max {
+            break;
+        }
+        len += item_len;
+        shown += 1;
+    }
+
+    let more = format!("…and {} more", items.len() - shown);
+    let mut parts: Vec<&str> = items[..shown].iter().map(String::as_str).collect();
+    parts.push(&more);
+    truncate(&parts.join(separator), max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("short", 10), "short");
+        assert_eq!(truncate("exactly10!", 10), "exactly10!");
+        assert_eq!(truncate("much too long", 5), "much…");
+        assert_eq!(truncate("日本語のテキスト", 4).chars().count(), 4);
+    }
+
+    #[test]
+    fn test_split_keeps_short_text_whole() {
+        assert_eq!(split("hello world", 2000), vec!["hello world"]);
+        assert!(split("   ", 2000).is_empty());
+    }
+
+    #[test]
+    fn test_split_breaks_at_lines_then_words() {
+        let text = "first line\nsecond line here";
+        assert_eq!(split(text, 15), vec!["first line", "second line", "here"]);
+        // A short heading stays with the text after it
+        let text = format!("**Title**\n{}", "word ".repeat(10));
+        assert_eq!(split(&text, 30)[0], "**Title**\nword word word word");
+
+        let words = "word ".repeat(1000);
+        let parts = split(&words, MESSAGE_CONTENT);
+        assert!(parts.iter().all(|p| p.chars().count() <= MESSAGE_CONTENT));
+        assert!(parts.iter().all(|p| !p.starts_with(' ') && !p.ends_with(' ')));
+        assert_eq!(parts.join(" "), words.trim());
+    }
+
+    #[test]
+    fn test_split_cuts_words_longer_than_limit() {
+        let text = "é".repeat(25);
+        let parts = split(&text, 10);
+        assert_eq!(parts.len(), 3);
+        assert!(parts.iter().all(|p| p.chars().count() <= 10));
+        assert_eq!(parts.concat(), text);
+    }
+
+    #[test]
+    fn test_join_fitting() {
+        let items: Vec<String> = (0..5).map(|i| format!("<#{}>", i)).collect();
+        assert_eq!(join_fitting(&items, ", ", 100), "<#0>, <#1>, <#2>, <#3>, <#4>");
+
+        let fitted = join_fitting(&items, ", ", 25);
+        assert_eq!(fitted, "<#0>, <#1>, …and 3 more");
+        assert!(fitted.chars().count() <= 25);
+    }
+
+    #[test]
+    fn test_join_fitting_long_list_stays_under_field_limit() {
+        let items: Vec<String> = (0..500).map(|i| format!("<#{:018}>", i)).collect();
+        let fitted = join_fitting(&items, "\n", EMBED_FIELD_VALUE);
+        assert!(fitted.chars().count() <= EMBED_FIELD_VALUE);
+        assert!(fitted.ends_with("more"));
+        assert!(fitted.lines().all(|l| l.starts_with("<#") || l.starts_with('…')));
+    }
+}
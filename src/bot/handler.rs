@@ -1,108 +1,459 @@
-use crate::db::{DbPool, GuildRepo, UserPreferenceRepo, NewGuild};
-use crate::translation::{TranslationClient, TranslationResult};
+use crate::bot::message_limits::MessageGuard;
+use crate::bot::output::{self, WebhookCache};
+use crate::bot::progress::Progress;
+use crate::bot::settings_cache::SettingsCache;
+use crate::config::AppConfig;
+use crate::db::{DbPool, GuildRepo, IgnoreList, NewGuild, SubscriptionTier, UserGlobalPreferenceRepo};
+use crate::error::AppError;
+use crate::translation::{Language, TranslationClient, TranslationHints, TranslationResult};
 use crate::web::broadcast::BroadcastManager;
 use poise::serenity_prelude::{self as serenity, Context, Message};
 use std::sync::Arc;
-use tracing::{error, info};
+use std::time::Instant;
+use tracing::{debug, error, info, warn};
 
 /// Handle incoming messages for auto-translation
 pub async fn handle_message(
     ctx: &Context,
     msg: &Message,
-    pool: &DbPool,
+    settings_cache: &SettingsCache,
     translator: &TranslationClient,
     broadcast: &Arc<BroadcastManager>,
+    guard: &MessageGuard,
+    webhooks: &WebhookCache,
 ) {
-    // Ignore bot messages
-    if msg.author.bot {
+    let services = Services {
+        settings_cache,
+        translator,
+        broadcast,
+        guard,
+        webhooks,
+    };
+    translate_guild_message(ctx, msg, &msg.content, msg.channel_id, services).await;
+}
+
+/// What translating a guild message needs besides the message itself
+#[derive(Clone, Copy)]
+struct Services<'a> {
+    settings_cache: &'a SettingsCache,
+    translator: &'a TranslationClient,
+    broadcast: &'a Arc<BroadcastManager>,
+    guard: &'a MessageGuard,
+    webhooks: &'a WebhookCache,
+}
+
+/// Translate `content` of `msg` under the settings of `channel`: the
+/// message's own channel, or the forum a post was made in
+async fn translate_guild_message(
+    ctx: &Context,
+    msg: &Message,
+    content: &str,
+    channel: serenity::ChannelId,
+    services: Services<'_>,
+) {
+    let Services {
+        settings_cache,
+        translator,
+        broadcast,
+        guard,
+        webhooks,
+    } = services;
+
+    // Never translate our own posts, or every translation would be translated again
+    let own_id = ctx.cache.current_user().id;
+    let origin = MessageOrigin::of(msg, own_id, webhooks);
+    if origin == MessageOrigin::Own {
         return;
     }
 
     // Ignore empty messages
-    if msg.content.trim().is_empty() {
+    if content.trim().is_empty() {
         return;
     }
 
     // Get guild ID
     let guild_id = match msg.guild_id {
         Some(id) => id.to_string(),
-        None => return, // DMs not supported
+        None => return, // DMs go through handle_direct_message
     };
 
-    let channel_id = msg.channel_id.to_string();
+    let channel_id = channel.to_string();
     let user_id = msg.author.id.to_string();
 
-    // Check if channel is enabled for translation
-    let is_enabled = match GuildRepo::is_channel_enabled(pool, &guild_id, &channel_id).await {
-        Ok(enabled) => enabled,
+    // Guild settings (cached; one database read covers many messages)
+    let settings = match settings_cache.guild_settings(&guild_id).await {
+        Ok(Some(s)) => s,
+        Ok(None) => return,
+        Err(AppError::DatabaseUnavailable(e)) => {
+            // The health monitor reports the outage; don't log every message
+            debug!("Skipping message {}, database unavailable: {}", msg.id, e);
+            return;
+        }
         Err(e) => {
-            error!("Failed to check channel status: {}", e);
+            error!("Failed to get guild settings: {}", e);
             return;
         }
     };
 
-    if !is_enabled {
+    // Check if channel is enabled for translation
+    if !settings.enabled_channels.contains(&channel_id) {
         return;
     }
 
-    // Get guild settings
-    let settings = match GuildRepo::get_settings(pool, &guild_id).await {
-        Ok(Some(s)) => s,
-        Ok(None) => return,
+    if !origin.is_translated(&settings) {
+        debug!(?origin, "Skipping automated message {}", msg.id);
+        return;
+    }
+
+    // Admins can exclude announcement bots or whole roles
+    match settings_cache.ignore_list(&guild_id).await {
+        Ok(ignores) if is_ignored_author(msg, &ignores) => {
+            debug!("Skipping message {} from an ignored user or role", msg.id);
+            return;
+        }
+        Ok(_) => {}
         Err(e) => {
-            error!("Failed to get guild settings: {}", e);
+            // Translating anyway would go against the list
+            warn!("Skipping message {}, couldn't load ignore list: {}", msg.id, e);
+            return;
+        }
+    }
+
+    // Respect per-message opt-out
+    if is_opted_out(content, &settings) {
+        debug!("Skipping opted-out message {}", msg.id);
+        return;
+    }
+
+    // Channels can be limited to messages matching a pattern
+    match settings_cache.message_filter(&channel_id).await {
+        Ok(filter) if !passes_message_filter(filter.as_ref(), content) => {
+            debug!("Skipping message {} that doesn't match the channel filter", msg.id);
+            return;
+        }
+        Ok(_) => {}
+        Err(e) => {
+            // Translating anyway would go against the filter
+            warn!("Skipping message {}, couldn't load channel filter: {}", msg.id, e);
             return;
         }
+    }
+
+    // Keep oversized pastes and floods away from the inference backend
+    let chars = content.chars().count();
+    let guild_key = msg.guild_id.map(|id| id.get()).unwrap_or_default();
+    if let Err(rejected) = guard.check(guild_key, settings.subscription_tier, chars, Instant::now()) {
+        warn!(guild_id = %guild_id, message_id = %msg.id, ?rejected, "Skipping message");
+        if let Err(e) = msg.react(&ctx.http, rejected.reaction()).await {
+            debug!("Failed to react to skipped message: {}", e);
+        }
+        return;
+    }
+
+    // Get user preference (optional; guild defaults apply without it)
+    let user_pref = match settings_cache.user_preference(&user_id, &guild_id).await {
+        Ok(pref) => pref,
+        Err(AppError::DatabaseUnavailable(_)) => None,
+        Err(e) => {
+            warn!("Failed to load preferences for user {}: {}", user_id, e);
+            None
+        }
     };
 
-    // Get user preference (optional)
-    let user_pref = UserPreferenceRepo::get(pool, &user_id, &guild_id)
-        .await
-        .ok()
-        .flatten();
+    // Determine target languages, within the channel's whitelist if it has one
+    let allowed = match settings_cache.allowed_languages(&channel_id).await {
+        Ok(allowed) => allowed,
+        Err(e) => {
+            // Translating anyway could break the whitelist
+            warn!("Skipping message {}, couldn't load allowed languages: {}", msg.id, e);
+            return;
+        }
+    };
+    let target_langs = channel_target_languages(&settings, &allowed);
 
-    // Determine target languages
-    let target_langs = if settings.target_languages.is_empty() {
-        vec![settings.default_language.clone()]
-    } else {
-        settings.target_languages.clone()
+    // A channel's context hint replaces its guild's
+    let context = match settings_cache.translation_context(&channel_id).await {
+        Ok(context) => context.or_else(|| settings.translation_context.clone()),
+        Err(e) => {
+            warn!("Couldn't load translation context for channel {}: {}", channel_id, e);
+            settings.translation_context.clone()
+        }
     };
 
-    // Translate message
-    let results = translate_message(translator, &msg.content, &target_langs).await;
+    // Translate message, showing that it's underway if the guild wants that
+    let progress = Progress::start(ctx, msg, settings.progress_reaction).await;
+    let hints = TranslationHints {
+        context: context.as_deref(),
+        formality: settings.formality,
+    };
+    let results = translate_message(translator, content, &target_langs, &settings, hints).await;
 
     // Process results
+    let mut translations = Vec::new();
+    let mut failed = 0;
     for result in results {
         match result {
             Ok(translation) => {
                 // Broadcast to web viewers
                 broadcast.send_translation(
+                    &guild_id,
                     &channel_id,
                     &msg.author.name,
                     &msg.author.id.to_string(),
                     &translation,
                 );
-
-                // Send translation as Discord reply (optional, configurable)
-                if should_send_discord_reply(&settings, &user_pref) {
-                    send_translation_reply(ctx, msg, &translation).await;
-                }
+                translations.push(translation);
             }
             Err(e) => {
                 error!("Translation failed: {}", e);
+                failed += 1;
             }
         }
     }
+    progress.finish(ctx, msg, translations.len(), failed).await;
+
+    // Post translations in Discord (optional, configurable)
+    if should_send_discord_reply(&settings, &user_pref) {
+        output::deliver(ctx, msg, &translations, settings.output_mode, webhooks, settings_cache.pool()).await;
+    }
 }
 
-/// Translate message to multiple languages
-async fn translate_message(
+/// How long after its creation a thread still counts as a new post. Discord
+/// also sends `ThreadCreate` when the bot is added to an existing thread.
+const NEW_THREAD_SECS: i64 = 300;
+
+/// Translate the starter post of a new forum thread. Its message is posted
+/// in the thread rather than an enabled channel, so the forum's settings apply.
+pub async fn handle_thread_create(
+    ctx: &Context,
+    thread: &serenity::GuildChannel,
+    settings_cache: &SettingsCache,
+    translator: &TranslationClient,
+    broadcast: &Arc<BroadcastManager>,
+    guard: &MessageGuard,
+    webhooks: &WebhookCache,
+) {
+    let Some(forum) = thread.parent_id else {
+        return;
+    };
+    let parent_kind = channel_kind(ctx, thread.guild_id, forum).await;
+    let created_at = thread
+        .thread_metadata
+        .and_then(|metadata| metadata.create_timestamp)
+        .map(|created| created.unix_timestamp());
+    let now = serenity::Timestamp::now().unix_timestamp();
+    if !is_new_forum_post(thread.kind, parent_kind, created_at, now) {
+        return;
+    }
+
+    // A forum post's starter message shares its thread's id
+    let starter = match thread
+        .id
+        .message(&ctx.http, serenity::MessageId::new(thread.id.get()))
+        .await
+    {
+        Ok(starter) => starter,
+        Err(e) => {
+            debug!("Couldn't fetch starter message of thread {}: {}", thread.id, e);
+            return;
+        }
+    };
+    let services = Services {
+        settings_cache,
+        translator,
+        broadcast,
+        guard,
+        webhooks,
+    };
+    let content = forum_post_text(&thread.name, &starter.content);
+    translate_guild_message(ctx, &starter, &content, forum, services).await;
+}
+
+/// Whether a `ThreadCreate` is a post just made in a forum (or media)
+/// channel, whose starter message would otherwise go untranslated. Threads
+/// in text channels start from a message the channel already translates.
+fn is_new_forum_post(
+    thread_kind: serenity::ChannelType,
+    parent_kind: Option<serenity::ChannelType>,
+    created_at: Option<i64>,
+    now: i64,
+) -> bool {
+    use serenity::ChannelType;
+    // Media channels (type 16) aren't named by serenity yet
+    let in_forum = matches!(parent_kind, Some(ChannelType::Forum | ChannelType::Unknown(16)));
+    let is_new = created_at.is_some_and(|created| now - created <= NEW_THREAD_SECS);
+    thread_kind == ChannelType::PublicThread && in_forum && is_new
+}
+
+/// A forum post's title and body, translated together
+fn forum_post_text(title: &str, body: &str) -> String {
+    if body.trim().is_empty() {
+        title.to_string()
+    } else {
+        format!("{}\n\n{}", title, body)
+    }
+}
+
+/// Kind of a guild channel, from the cache or else from Discord
+async fn channel_kind(
+    ctx: &Context,
+    guild_id: serenity::GuildId,
+    channel_id: serenity::ChannelId,
+) -> Option<serenity::ChannelType> {
+    let cached = ctx
+        .cache
+        .guild(guild_id)
+        .and_then(|guild| guild.channels.get(&channel_id).map(|channel| channel.kind));
+    if cached.is_some() {
+        return cached;
+    }
+    match channel_id.to_channel(ctx).await {
+        Ok(channel) => channel.guild().map(|channel| channel.kind),
+        Err(e) => {
+            debug!("Couldn't look up channel {}: {}", channel_id, e);
+            None
+        }
+    }
+}
+
+/// Translate a direct message into the sender's global preferred language
+/// and reply to them privately
+pub async fn handle_direct_message(
+    ctx: &Context,
+    msg: &Message,
+    pool: &DbPool,
+    translator: &TranslationClient,
+    guard: &MessageGuard,
+) {
+    if msg.author.bot || msg.content.trim().is_empty() {
+        return;
+    }
+    // Text commands sent in DMs are the framework's business
+    if msg.content.starts_with(AppConfig::get().discord.command_prefix.as_str()) {
+        return;
+    }
+
+    // DMs have no guild, so the sender's own ID keys the flood limit
+    let chars = msg.content.chars().count();
+    if let Err(rejected) = guard.check(msg.author.id.get(), SubscriptionTier::Free, chars, Instant::now()) {
+        warn!(user_id = %msg.author.id, ?rejected, "Skipping direct message");
+        if let Err(e) = msg.react(&ctx.http, rejected.reaction()).await {
+            debug!("Failed to react to skipped message: {}", e);
+        }
+        return;
+    }
+
+    let user_id = msg.author.id.to_string();
+    let target = match UserGlobalPreferenceRepo::get(pool, &user_id).await {
+        Ok(Some(pref)) => pref.preferred_language,
+        Ok(None) => {
+            let hint = "Set the language to translate your messages into with `/mylang` here first.";
+            if let Err(e) = msg.reply(&ctx.http, hint).await {
+                debug!("Failed to send DM hint: {}", e);
+            }
+            return;
+        }
+        Err(e) => {
+            warn!("Failed to load global preference for user {}: {}", user_id, e);
+            return;
+        }
+    };
+
+    match translator.translate_auto(&msg.content, &target, None).await {
+        Ok(translation) if translation.source_lang == translation.target_lang => {
+            let note = format!("That's already in {}.", language_name(&target));
+            if let Err(e) = msg.reply(&ctx.http, note).await {
+                debug!("Failed to reply to direct message: {}", e);
+            }
+        }
+        Ok(translation) => {
+            output::send_reply(ctx, msg, &translation).await;
+        }
+        Err(e) => {
+            error!("Direct message translation failed: {}", e);
+            if let Err(e) = msg.reply(&ctx.http, "Translation failed, please try again later.").await {
+                debug!("Failed to reply to direct message: {}", e);
+            }
+        }
+    }
+}
+
+fn language_name(code: &str) -> &str {
+    Language::from_code(code).map_or(code, |lang| lang.name())
+}
+
+/// Who posted a message, as far as translation is concerned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MessageOrigin {
+    /// This bot itself, including translations posted through its webhooks
+    Own,
+    Human,
+    /// Another bot account
+    Bot,
+    /// A webhook, typically relaying people from another chat
+    Webhook,
+}
+
+impl MessageOrigin {
+    pub(crate) fn of(msg: &Message, own_id: serenity::UserId, webhooks: &WebhookCache) -> Self {
+        // Translations posted through our webhooks carry the speaker's name,
+        // not ours, so the webhook ID is what identifies them
+        let is_own = msg.author.id == own_id || msg.webhook_id.is_some_and(|id| webhooks.is_own(id));
+        Self::classify(is_own, msg.author.bot, msg.webhook_id.is_some())
+    }
+
+    fn classify(is_own: bool, is_bot: bool, is_webhook: bool) -> Self {
+        if is_own {
+            Self::Own
+        } else if is_webhook {
+            Self::Webhook
+        } else if is_bot {
+            Self::Bot
+        } else {
+            Self::Human
+        }
+    }
+
+    /// Whether the guild wants messages from this origin translated
+    pub(crate) fn is_translated(self, settings: &crate::db::GuildSettings) -> bool {
+        match self {
+            Self::Own => false,
+            Self::Human => true,
+            Self::Bot => settings.translate_bot_messages,
+            Self::Webhook => settings.translate_webhooks,
+        }
+    }
+}
+
+/// Languages a message in a channel is translated into: the guild's targets
+/// (or its default language), limited to `allowed` when that is non-empty
+pub(crate) fn channel_target_languages(
+    settings: &crate::db::GuildSettings,
+    allowed: &[String],
+) -> Vec<String> {
+    let mut targets = if settings.target_languages.is_empty() {
+        vec![settings.default_language.clone()]
+    } else {
+        settings.target_languages.clone()
+    };
+    if !allowed.is_empty() {
+        targets.retain(|target| allowed.contains(target));
+    }
+    targets
+}
+
+/// Translate message to multiple languages, with `hints` for backends that
+/// accept them
+pub(crate) async fn translate_message(
     translator: &TranslationClient,
     text: &str,
     target_langs: &[String],
+    settings: &crate::db::GuildSettings,
+    hints: TranslationHints<'_>,
 ) -> Vec<Result<TranslationResult, crate::error::AppError>> {
+    let guild_id = settings.guild_id.parse().ok();
     // First detect the source language
-    let source_lang = match translator.detect_language(text).await {
+    let source_lang = match translator.detect_language(text, guild_id).await {
         Ok(detection) => detection.language,
         Err(e) => {
             error!("Language detection failed: {}", e);
@@ -110,19 +461,90 @@ async fn translate_message(
         }
     };
 
-    // Translate to each target language (excluding source)
+    let targets = select_target_languages(&source_lang, target_langs, settings);
+    if targets.is_empty() {
+        debug!("Nothing to translate for source language {}", source_lang);
+        return Vec::new();
+    }
+
+    // Translate to each remaining target language
     let mut results = Vec::new();
-    for target in target_langs {
-        if target == &source_lang {
-            continue;
-        }
-        let result = translator.translate(text, &source_lang, target).await;
+    for target in targets {
+        let result = translator
+            .translate_with_hints(text, &source_lang, target, guild_id, hints)
+            .await;
         results.push(result);
     }
 
     results
 }
 
+/// Pick the target languages a message actually needs translating into.
+///
+/// Targets matching the detected source are always skipped. When the guild has
+/// `translate_only_foreign` set, messages already written in the guild's default
+/// language produce no translations at all.
+fn select_target_languages<'a>(
+    source_lang: &str,
+    target_langs: &'a [String],
+    settings: &crate::db::GuildSettings,
+) -> Vec<&'a String> {
+    if settings.translate_only_foreign && source_lang == settings.default_language {
+        return Vec::new();
+    }
+
+    target_langs
+        .iter()
+        .filter(|target| target.as_str() != source_lang)
+        .collect()
+}
+
+/// Whether the message's author, or one of their roles, is on the guild's
+/// ignore list
+pub(crate) fn is_ignored_author(msg: &Message, ignores: &IgnoreList) -> bool {
+    if ignores.is_empty() {
+        return false;
+    }
+    let roles: Vec<String> = msg
+        .member
+        .as_ref()
+        .map(|member| member.roles.iter().map(|role| role.to_string()).collect())
+        .unwrap_or_default();
+    ignores.ignores(&msg.author.id.to_string(), &roles)
+}
+
+/// Whether the author marked this message as not to be translated
+pub(crate) fn is_opted_out(content: &str, settings: &crate::db::GuildSettings) -> bool {
+    !settings.skip_prefix.is_empty() && content.trim_start().starts_with(&settings.skip_prefix)
+}
+
+/// Longest message filter pattern accepted, in characters
+pub(crate) const MAX_MESSAGE_FILTER_LEN: usize = 200;
+
+/// Compiled size limit for message filters, so one channel's pattern can't
+/// take a lot of memory
+const MESSAGE_FILTER_SIZE_LIMIT: usize = 256 * 1024;
+
+/// Compile a channel's message filter, refusing patterns too long or too
+/// large to run on every message
+pub(crate) fn compile_message_filter(pattern: &str) -> Result<regex::Regex, regex::Error> {
+    if pattern.chars().count() > MAX_MESSAGE_FILTER_LEN {
+        return Err(regex::Error::Syntax(format!(
+            "pattern is longer than {} characters",
+            MAX_MESSAGE_FILTER_LEN
+        )));
+    }
+    regex::RegexBuilder::new(pattern)
+        .size_limit(MESSAGE_FILTER_SIZE_LIMIT)
+        .build()
+}
+
+/// Whether a message passes its channel's filter (every message does
+/// without one)
+pub(crate) fn passes_message_filter(filter: Option<&regex::Regex>, content: &str) -> bool {
+    filter.is_none_or(|filter| filter.is_match(content))
+}
+
 /// Check if we should send a reply in Discord
 fn should_send_discord_reply(
     _settings: &crate::db::GuildSettings,
@@ -133,40 +555,14 @@ fn should_send_discord_reply(
     true
 }
 
-/// Send translation as a Discord reply
-async fn send_translation_reply(
-    ctx: &Context,
-    original_msg: &Message,
-    translation: &TranslationResult,
-) {
-    // Skip if source and target are the same
-    if translation.source_lang == translation.target_lang {
-        return;
-    }
-
-    // Create embed for translation
-    let embed = serenity::CreateEmbed::default()
-        .description(&translation.translated_text)
-        .footer(serenity::CreateEmbedFooter::new(format!(
-            "{} → {}",
-            translation.source_lang.to_uppercase(),
-            translation.target_lang.to_uppercase()
-        )))
-        .color(0x5865F2); // Discord blurple
-
-    let builder = serenity::CreateMessage::default()
-        .embed(embed)
-        .reference_message(original_msg);
-
-    if let Err(e) = original_msg.channel_id.send_message(&ctx.http, builder).await {
-        error!("Failed to send translation reply: {}", e);
-    }
-}
-
-/// Handle guild join event
+/// Handle guild join event. `joined` is set when the bot was just added,
+/// rather than the guild arriving on startup or reconnect.
 pub async fn handle_guild_create(
+    ctx: &Context,
     guild: &serenity::Guild,
+    joined: bool,
     pool: &DbPool,
+    settings_cache: &SettingsCache,
 ) {
     info!("Joined guild: {} ({})", guild.name, guild.id);
 
@@ -178,6 +574,57 @@ pub async fn handle_guild_create(
     if let Err(e) = GuildRepo::upsert(pool, new_guild).await {
         error!("Failed to register guild: {}", e);
     }
+    settings_cache.invalidate_guild(&guild.id.to_string());
+
+    if joined && AppConfig::get().discord.onboarding_message {
+        send_onboarding(ctx, guild, pool).await;
+    }
+}
+
+/// Tell a server's admins how to get started, once per guild: in its system
+/// channel when the bot may post there, else by DM to the owner
+async fn send_onboarding(ctx: &Context, guild: &serenity::Guild, pool: &DbPool) {
+    // Claim the guild first so a quick rejoin can't send it twice
+    match GuildRepo::mark_onboarded(pool, &guild.id.to_string()).await {
+        Ok(true) => {}
+        Ok(false) => return,
+        Err(e) => {
+            warn!(guild_id = %guild.id, error = %e, "Failed to record onboarding, not sending it");
+            return;
+        }
+    }
+
+    let content = onboarding_message(&guild.name, &AppConfig::get().discord.docs_url);
+    let message = serenity::CreateMessage::new().content(content);
+    if let Some(channel_id) = onboarding_channel(ctx, guild) {
+        match channel_id.send_message(&ctx.http, message.clone()).await {
+            Ok(_) => return,
+            Err(e) => warn!(guild_id = %guild.id, error = %e, "Failed to post onboarding message, messaging the owner"),
+        }
+    }
+    if let Err(e) = guild.owner_id.direct_message(ctx, message).await {
+        warn!(guild_id = %guild.id, error = %e, "Failed to send onboarding message to the guild owner");
+    }
+}
+
+/// The guild's system channel, if the bot can see it and post there
+fn onboarding_channel(ctx: &Context, guild: &serenity::Guild) -> Option<serenity::ChannelId> {
+    let channel_id = guild.system_channel_id?;
+    let channel = guild.channels.get(&channel_id)?;
+    let member = guild.members.get(&ctx.cache.current_user().id)?;
+    let permissions = guild.user_permissions_in(channel, member);
+    (permissions.view_channel() && permissions.send_messages()).then_some(channel_id)
+}
+
+/// Greeting for a server that just added the bot
+fn onboarding_message(guild_name: &str, docs_url: &str) -> String {
+    format!(
+        "Thanks for adding LinguaBridge to **{}**!\n\n\
+        An admin can run `/setup init` to get started, then `/setup channel` to \
+        enable translation in a channel and `/setup languages` to choose target languages.\n\
+        Guide: <{}>",
+        guild_name, docs_url
+    )
 }
 
 /// Handle guild leave event
@@ -187,3 +634,225 @@ pub async fn handle_guild_delete(
     info!("Left guild: {}", guild_id);
     // Optionally: clean up guild data
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{GuildSettings, OutputMode, ProgressReaction, SubscriptionTier};
+    use crate::translation::Formality;
+
+    fn settings(default_language: &str, translate_only_foreign: bool) -> GuildSettings {
+        GuildSettings {
+            guild_id: "g1".to_string(),
+            name: "Test".to_string(),
+            default_language: default_language.to_string(),
+            enabled_channels: vec!["ch1".to_string()],
+            target_languages: vec![],
+            subscription_tier: SubscriptionTier::Free,
+            subscription_expires_at: None,
+            translate_only_foreign,
+            skip_prefix: "\\".to_string(),
+            translate_bot_messages: false,
+            translate_webhooks: false,
+            output_mode: OutputMode::Reply,
+            use_nicknames: true,
+            anonymize_speakers: false,
+            max_voice_session_secs: None,
+            translation_context: None,
+            formality: Formality::Default,
+            progress_reaction: ProgressReaction::Off,
+        }
+    }
+
+    #[test]
+    fn test_message_filter_matches_questions_only() {
+        let filter = compile_message_filter(r"\?\s*$").unwrap();
+        assert!(passes_message_filter(Some(&filter), "How do I reset my password?"));
+        assert!(passes_message_filter(Some(&filter), "is it down? "));
+        assert!(!passes_message_filter(Some(&filter), "Thanks, that fixed it"));
+        assert!(!passes_message_filter(Some(&filter), "what? no, it works now"));
+        assert!(passes_message_filter(None, "Thanks, that fixed it"));
+    }
+
+    #[test]
+    fn test_message_filter_rejects_bad_patterns() {
+        assert!(compile_message_filter("(unclosed").is_err());
+        assert!(compile_message_filter(&"a".repeat(MAX_MESSAGE_FILTER_LEN + 1)).is_err());
+        assert!(compile_message_filter(r"\w{1000}{1000}").is_err());
+    }
+
+    #[test]
+    fn test_onboarding_message_points_to_setup() {
+        let text = onboarding_message("Test", "https://docs.example");
+        assert!(text.contains("**Test**"));
+        assert!(text.contains("/setup init"));
+        assert!(text.contains("<https://docs.example>"));
+    }
+
+    fn langs(codes: &[&str]) -> Vec<String> {
+        codes.iter().map(|c| c.to_string()).collect()
+    }
+
+    #[test]
+    fn test_channel_whitelist_filters_disallowed_targets() {
+        let mut guild = settings("en", false);
+        guild.target_languages = langs(&["es", "fr", "de"]);
+
+        assert_eq!(channel_target_languages(&guild, &[]), langs(&["es", "fr", "de"]));
+        assert_eq!(channel_target_languages(&guild, &langs(&["fr", "ja"])), langs(&["fr"]));
+        assert!(channel_target_languages(&guild, &langs(&["ja"])).is_empty());
+
+        // Without guild targets the default language is still subject to the whitelist
+        assert!(channel_target_languages(&settings("en", false), &langs(&["fr"])).is_empty());
+    }
+
+    #[test]
+    fn test_select_targets_skips_source_language() {
+        let targets = langs(&["en", "es", "fr"]);
+        let selected = select_target_languages("en", &targets, &settings("en", false));
+        assert_eq!(selected, vec!["es", "fr"]);
+    }
+
+    #[test]
+    fn test_select_targets_english_in_english_channel_only_foreign() {
+        let targets = langs(&["en"]);
+        let selected = select_target_languages("en", &targets, &settings("en", true));
+        assert!(selected.is_empty());
+    }
+
+    #[test]
+    fn test_select_targets_default_language_suppressed_only_foreign() {
+        // Without the filter an English message would still go out in Spanish
+        let targets = langs(&["en", "es"]);
+        assert_eq!(
+            select_target_languages("en", &targets, &settings("en", false)),
+            vec!["es"]
+        );
+        assert!(select_target_languages("en", &targets, &settings("en", true)).is_empty());
+    }
+
+    #[test]
+    fn test_select_targets_foreign_message_translated_only_foreign() {
+        let targets = langs(&["en", "es"]);
+        let selected = select_target_languages("ja", &targets, &settings("en", true));
+        assert_eq!(selected, vec!["en", "es"]);
+    }
+
+    #[test]
+    fn test_prefixed_message_is_not_translated() {
+        let s = settings("en", false);
+        assert!(is_opted_out("\\git push --force", &s));
+        assert!(is_opted_out("  \\ leading whitespace", &s));
+        assert!(!is_opted_out("hola amigos", &s));
+    }
+
+    #[test]
+    fn test_custom_skip_prefix() {
+        let mut s = settings("en", false);
+        s.skip_prefix = "no-translate".to_string();
+        assert!(is_opted_out("no-translate c'est la vie", &s));
+        assert!(!is_opted_out("\\not the configured marker", &s));
+    }
+
+    #[test]
+    fn test_empty_skip_prefix_disables_opt_out() {
+        let mut s = settings("en", false);
+        s.skip_prefix.clear();
+        assert!(!is_opted_out("\\anything", &s));
+        assert!(!is_opted_out("anything", &s));
+    }
+
+    #[test]
+    fn test_ignored_authors_messages_are_skipped() {
+        let ignores = IgnoreList {
+            users: vec!["111".to_string()],
+            roles: vec!["900".to_string()],
+        };
+        let mut msg = Message::default();
+        msg.author.id = serenity::UserId::new(111);
+        assert!(is_ignored_author(&msg, &ignores));
+
+        msg.author.id = serenity::UserId::new(222);
+        assert!(!is_ignored_author(&msg, &ignores));
+        assert!(!is_ignored_author(&msg, &IgnoreList::default()));
+
+        msg.member = Some(Box::new(
+            serde_json::from_value(serde_json::json!({
+                "roles": ["900"],
+                "joined_at": "2024-01-01T00:00:00Z",
+                "deaf": false,
+                "mute": false,
+                "flags": 0,
+            }))
+            .unwrap(),
+        ));
+        assert!(is_ignored_author(&msg, &ignores));
+    }
+
+    #[test]
+    fn test_message_origin_classification() {
+        assert_eq!(MessageOrigin::classify(false, false, false), MessageOrigin::Human);
+        assert_eq!(MessageOrigin::classify(false, true, false), MessageOrigin::Bot);
+        // Webhook authors are flagged as bots by Discord
+        assert_eq!(MessageOrigin::classify(false, true, true), MessageOrigin::Webhook);
+        assert_eq!(MessageOrigin::classify(true, true, false), MessageOrigin::Own);
+        assert_eq!(MessageOrigin::classify(true, true, true), MessageOrigin::Own);
+    }
+
+    #[test]
+    fn test_default_settings_ignore_bots_and_webhooks() {
+        let s = settings("en", false);
+        assert!(MessageOrigin::Human.is_translated(&s));
+        assert!(!MessageOrigin::Bot.is_translated(&s));
+        assert!(!MessageOrigin::Webhook.is_translated(&s));
+    }
+
+    #[test]
+    fn test_webhooks_translated_while_bots_ignored() {
+        let mut s = settings("en", false);
+        s.translate_webhooks = true;
+        assert!(MessageOrigin::Webhook.is_translated(&s));
+        assert!(!MessageOrigin::Bot.is_translated(&s));
+    }
+
+    #[test]
+    fn test_bot_messages_translated_when_enabled() {
+        let mut s = settings("en", false);
+        s.translate_bot_messages = true;
+        assert!(MessageOrigin::Bot.is_translated(&s));
+        assert!(!MessageOrigin::Webhook.is_translated(&s));
+    }
+
+    #[test]
+    fn test_own_messages_never_translated() {
+        let mut s = settings("en", false);
+        s.translate_bot_messages = true;
+        s.translate_webhooks = true;
+        assert!(!MessageOrigin::Own.is_translated(&s));
+    }
+
+    #[test]
+    fn test_is_new_forum_post() {
+        use poise::serenity_prelude::ChannelType;
+        let now = 1_700_000_000;
+        let forum = Some(ChannelType::Forum);
+        assert!(is_new_forum_post(ChannelType::PublicThread, forum, Some(now - 5), now));
+        // Media channels share the forum post layout
+        let media = Some(ChannelType::Unknown(16));
+        assert!(is_new_forum_post(ChannelType::PublicThread, media, Some(now), now));
+        // Threads in text channels start from an already translated message
+        let text = Some(ChannelType::Text);
+        assert!(!is_new_forum_post(ChannelType::PublicThread, text, Some(now), now));
+        assert!(!is_new_forum_post(ChannelType::PrivateThread, text, Some(now), now));
+        // Joining an old post, or one whose parent couldn't be looked up
+        assert!(!is_new_forum_post(ChannelType::PublicThread, forum, Some(now - 3600), now));
+        assert!(!is_new_forum_post(ChannelType::PublicThread, forum, None, now));
+        assert!(!is_new_forum_post(ChannelType::PublicThread, None, Some(now), now));
+    }
+
+    #[test]
+    fn test_forum_post_text() {
+        assert_eq!(forum_post_text("Hello", "World"), "Hello\n\nWorld");
+        assert_eq!(forum_post_text("Hello", "  "), "Hello");
+    }
+}
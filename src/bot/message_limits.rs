@@ -0,0 +1,200 @@
+//! Length and rate guards for auto-translated messages.
+//!
+//! Every translated message costs one detection plus one inference call per
+//! target language, so a long paste repeated a few times can get expensive
+//! quickly. [`MessageGuard`] rejects messages over the tier's length limit and
+//! caps how many messages per minute each guild gets translated.
+
+use crate::db::SubscriptionTier;
+use dashmap::DashMap;
+use std::time::{Duration, Instant};
+
+/// Length of the per-guild rate window
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Prune finished windows once this many guilds are tracked
+const PRUNE_THRESHOLD: usize = 10_000;
+
+/// Why a message was not translated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageRejected {
+    /// Message has more characters than the tier allows
+    TooLong { chars: usize, limit: usize },
+    /// Guild already used its translations for this minute
+    RateLimited,
+}
+
+impl MessageRejected {
+    /// Reaction left on the skipped message
+    pub fn reaction(&self) -> char {
+        match self {
+            Self::TooLong { .. } => '✂',
+            Self::RateLimited => '⏳',
+        }
+    }
+}
+
+/// Limits for one group of tiers
+#[derive(Debug, Clone, Copy)]
+pub struct TierLimits {
+    pub max_chars: usize,
+    pub messages_per_minute: u32,
+}
+
+/// Per-guild length and rate limits for auto-translation
+#[derive(Debug)]
+pub struct MessageGuard {
+    free: TierLimits,
+    paid: TierLimits,
+    /// Start of the current window and messages counted in it, per guild
+    windows: DashMap<u64, (Instant, u32)>,
+}
+
+impl MessageGuard {
+    pub fn new(free: TierLimits, paid: TierLimits) -> Self {
+        Self {
+            free,
+            paid,
+            windows: DashMap::new(),
+        }
+    }
+
+    /// Limits that apply to guilds on `tier`
+    pub fn limits(&self, tier: SubscriptionTier) -> TierLimits {
+        if tier.is_paid() {
+            self.paid
+        } else {
+            self.free
+        }
+    }
+
+    /// Check whether a message of `chars` characters may be translated, and
+    /// count it against the guild's rate if so. Rejected messages are not
+    /// counted.
+    pub fn check(
+        &self,
+        guild_id: u64,
+        tier: SubscriptionTier,
+        chars: usize,
+        now: Instant,
+    ) -> Result<(), MessageRejected> {
+        let limits = self.limits(tier);
+        if chars > limits.max_chars {
+            return Err(MessageRejected::TooLong {
+                chars,
+                limit: limits.max_chars,
+            });
+        }
+
+        {
+            let mut window = self.windows.entry(guild_id).or_insert((now, 0));
+            let (started, count) = &mut *window;
+            if now.saturating_duration_since(*started) >= RATE_WINDOW {
+                *started = now;
+                *count = 0;
+            }
+            if *count >= limits.messages_per_minute {
+                return Err(MessageRejected::RateLimited);
+            }
+            *count += 1;
+        }
+
+        if self.windows.len() > PRUNE_THRESHOLD {
+            self.prune(now);
+        }
+        Ok(())
+    }
+
+    /// Drop guilds whose window has already ended
+    pub fn prune(&self, now: Instant) {
+        self.windows
+            .retain(|_, (started, _)| now.saturating_duration_since(*started) < RATE_WINDOW);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn guard() -> MessageGuard {
+        MessageGuard::new(
+            TierLimits {
+                max_chars: 2000,
+                messages_per_minute: 3,
+            },
+            TierLimits {
+                max_chars: 8000,
+                messages_per_minute: 10,
+            },
+        )
+    }
+
+    #[test]
+    fn test_long_message_is_skipped() {
+        let guard = guard();
+        let now = Instant::now();
+        assert_eq!(
+            guard.check(1, SubscriptionTier::Free, 10_000, now),
+            Err(MessageRejected::TooLong {
+                chars: 10_000,
+                limit: 2000
+            })
+        );
+        // Paid tiers get more room, but a 10k wall is still too much
+        assert!(guard.check(1, SubscriptionTier::Pro, 5_000, now).is_ok());
+        assert!(guard.check(1, SubscriptionTier::Pro, 10_000, now).is_err());
+    }
+
+    #[test]
+    fn test_guild_rate_limit() {
+        let guard = guard();
+        let start = Instant::now();
+        for _ in 0..3 {
+            assert!(guard.check(1, SubscriptionTier::Free, 10, start).is_ok());
+        }
+        assert_eq!(
+            guard.check(1, SubscriptionTier::Free, 10, start),
+            Err(MessageRejected::RateLimited)
+        );
+        // Other guilds have their own budget
+        assert!(guard.check(2, SubscriptionTier::Free, 10, start).is_ok());
+        // The budget refills once the window has passed
+        assert!(guard
+            .check(1, SubscriptionTier::Free, 10, start + RATE_WINDOW)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_rejected_messages_do_not_use_budget() {
+        let guard = guard();
+        let now = Instant::now();
+        for _ in 0..5 {
+            assert!(guard.check(1, SubscriptionTier::Free, 9_999, now).is_err());
+        }
+        assert!(guard.check(1, SubscriptionTier::Free, 10, now).is_ok());
+    }
+
+    #[test]
+    fn test_paid_tier_has_higher_rate() {
+        let guard = guard();
+        let now = Instant::now();
+        for _ in 0..10 {
+            assert!(guard.check(1, SubscriptionTier::Basic, 10, now).is_ok());
+        }
+        assert!(guard.check(1, SubscriptionTier::Basic, 10, now).is_err());
+    }
+
+    #[test]
+    fn test_prune_drops_finished_windows() {
+        let guard = guard();
+        let start = Instant::now();
+        guard.check(1, SubscriptionTier::Free, 10, start).unwrap();
+        guard
+            .check(2, SubscriptionTier::Free, 10, start + Duration::from_secs(30))
+            .unwrap();
+
+        guard.prune(start + RATE_WINDOW);
+        assert_eq!(guard.windows.len(), 1);
+        assert!(guard.windows.contains_key(&2));
+    }
+}
@@ -1,16 +1,37 @@
+pub mod backfill;
 pub mod commands;
+pub mod cooldown;
+pub mod discord_limits;
+pub mod message_limits;
+pub mod output;
+pub mod prefix;
+pub mod progress;
+pub mod selftest;
+pub mod settings_cache;
+pub mod speaker_names;
+pub mod transcript_threads;
+pub mod voice_session;
 pub mod handler;
 
+use crate::admin::InferenceTokens;
 use crate::config::AppConfig;
-use crate::db::DbPool;
+use crate::db::{DbPool, TtsMode};
 use crate::translation::TranslationClient;
-use crate::voice::{spawn_voice_bridge, QueueFullStrategy, VoiceClientConfig, VoiceManager};
+use crate::voice::{
+    BufferConfig, NoiseGate, PlaybackPolicy, QueueFullStrategy, TtsFormat, VoiceBridge, VoiceClientConfig,
+    VoiceManager, VoicePipeline, VoiceTranscriptionCache,
+};
 use crate::web::broadcast::BroadcastManager;
+use cooldown::{CommandCooldowns, CooldownHit};
+use message_limits::{MessageGuard, TierLimits};
+use output::WebhookCache;
+use prefix::PrefixCache;
+use settings_cache::SettingsCache;
 use poise::serenity_prelude::{self as serenity, FullEvent, GatewayIntents};
 use songbird::SerenityInit;
 use std::sync::Arc;
-use std::time::Duration;
-use tracing::{error, info};
+use std::time::{Duration, Instant};
+use tracing::{debug, error, info, warn};
 
 /// Shared data accessible in all commands
 #[derive(Debug)]
@@ -19,6 +40,11 @@ pub struct Data {
     pub translator: Arc<TranslationClient>,
     pub broadcast: Arc<BroadcastManager>,
     pub voice: Option<Arc<VoiceManager>>,
+    pub cooldowns: Arc<CommandCooldowns>,
+    pub message_guard: Arc<MessageGuard>,
+    pub prefixes: Arc<PrefixCache>,
+    pub webhooks: Arc<WebhookCache>,
+    pub settings: Arc<SettingsCache>,
 }
 
 type Error = Box<dyn std::error::Error + Send + Sync>;
@@ -36,19 +62,52 @@ async fn event_handler(
                 "Bot is ready! Logged in as {}",
                 data_about_bot.user.name
             );
+            restore_voice_sessions(ctx, data);
+        }
+        FullEvent::Resume { .. } => {
+            info!("Gateway session resumed");
+            restore_voice_sessions(ctx, data);
+        }
+        FullEvent::Message { new_message }
+            if new_message.guild_id.is_none() && AppConfig::get().discord.allow_direct_messages =>
+        {
+            handler::handle_direct_message(
+                ctx,
+                new_message,
+                &data.pool,
+                &data.translator,
+                &data.message_guard,
+            )
+            .await;
         }
         FullEvent::Message { new_message } => {
             handler::handle_message(
                 ctx,
                 new_message,
-                &data.pool,
+                &data.settings,
+                &data.translator,
+                &data.broadcast,
+                &data.message_guard,
+                &data.webhooks,
+            )
+            .await;
+        }
+        FullEvent::ThreadCreate { thread } => {
+            handler::handle_thread_create(
+                ctx,
+                thread,
+                &data.settings,
                 &data.translator,
                 &data.broadcast,
+                &data.message_guard,
+                &data.webhooks,
             )
             .await;
         }
-        FullEvent::GuildCreate { guild, is_new: _ } => {
-            handler::handle_guild_create(guild, &data.pool).await;
+        FullEvent::GuildCreate { guild, is_new } => {
+            // Guilds streamed in at startup or on reconnect aren't new
+            let joined = is_new.unwrap_or(false);
+            handler::handle_guild_create(ctx, guild, joined, &data.pool, &data.settings).await;
         }
         FullEvent::GuildDelete { incomplete, full: _ } => {
             handler::handle_guild_delete(incomplete.id).await;
@@ -58,6 +117,21 @@ async fn event_handler(
     Ok(())
 }
 
+/// Rejoin voice channels dropped by a gateway disconnect, in the background
+/// so event handling isn't held up by voice handshakes
+fn restore_voice_sessions(ctx: &serenity::Context, data: &Data) {
+    let Some(voice) = data.voice.clone() else {
+        return;
+    };
+    if !AppConfig::get().voice.rejoin_on_reconnect {
+        return;
+    }
+    let (ctx, pool) = (ctx.clone(), data.pool.clone());
+    tokio::spawn(async move {
+        voice_session::reconcile(&ctx, &pool, &voice).await;
+    });
+}
+
 /// Create and configure the Discord bot framework
 pub async fn create_framework(
     pool: DbPool,
@@ -65,14 +139,43 @@ pub async fn create_framework(
     broadcast: Arc<BroadcastManager>,
     voice: Option<Arc<VoiceManager>>,
 ) -> Result<poise::Framework<Data, Error>, Error> {
-    let _config = AppConfig::get();
+    let config = AppConfig::get();
+    let cooldowns = Arc::new(CommandCooldowns::new(&config.rate_limits.command_cooldowns));
+    let message_guard = Arc::new(MessageGuard::new(
+        TierLimits {
+            max_chars: config.translation.max_message_length,
+            messages_per_minute: config.rate_limits.free_messages_per_minute,
+        },
+        TierLimits {
+            max_chars: config.translation.paid_max_message_length,
+            messages_per_minute: config.rate_limits.paid_messages_per_minute,
+        },
+    ));
+    let prefixes = Arc::new(PrefixCache::new(config.discord.command_prefix.clone()));
+    let webhooks = Arc::new(WebhookCache::new());
+    let settings = Arc::new(SettingsCache::new(pool.clone()));
 
     let framework = poise::Framework::builder()
         .options(poise::FrameworkOptions {
-            commands: commands::all_commands(),
+            commands: commands::enabled_commands(voice.is_some()),
             event_handler: |ctx, event, framework, data| {
                 Box::pin(event_handler(ctx, event, framework, data))
             },
+            command_check: Some(|ctx| {
+                Box::pin(async move {
+                    let command = &ctx.command().qualified_name;
+                    ctx.data()
+                        .cooldowns
+                        .check(ctx.author().id.get(), command, Instant::now())
+                        .map_err(|remaining| {
+                            Box::new(CooldownHit {
+                                command: command.clone(),
+                                remaining,
+                            }) as Error
+                        })?;
+                    Ok(true)
+                })
+            }),
             on_error: |error| {
                 Box::pin(async move {
                     match error {
@@ -80,6 +183,24 @@ pub async fn create_framework(
                             error!("Command error: {}", error);
                             let _ = ctx.say(format!("An error occurred: {}", error)).await;
                         }
+                        poise::FrameworkError::CommandCheckFailed {
+                            error: Some(error),
+                            ctx,
+                            ..
+                        } => {
+                            if let Some(hit) = error.downcast_ref::<CooldownHit>() {
+                                debug!(user = %ctx.author().id, "{}", hit);
+                            } else {
+                                error!("Command check error: {}", error);
+                            }
+                            let _ = ctx
+                                .send(
+                                    poise::CreateReply::default()
+                                        .content(error.to_string())
+                                        .ephemeral(true),
+                                )
+                                .await;
+                        }
                         poise::FrameworkError::Setup { error, .. } => {
                             error!("Setup error: {}", error);
                         }
@@ -90,7 +211,19 @@ pub async fn create_framework(
                 })
             },
             prefix_options: poise::PrefixFrameworkOptions {
-                prefix: Some("!lb ".into()),
+                // Resolved per guild; falls back to `discord.command_prefix`
+                dynamic_prefix: Some(|ctx| {
+                    Box::pin(async move {
+                        let guild_id = ctx.guild_id.map(|id| id.get());
+                        match ctx.data.prefixes.resolve(&ctx.data.pool, guild_id).await {
+                            Ok(prefix) => Ok(Some(prefix)),
+                            Err(e) => {
+                                warn!(error = %e, "Prefix lookup failed, using default");
+                                Ok(Some(ctx.data.prefixes.default_prefix().to_string()))
+                            }
+                        }
+                    })
+                }),
                 ..Default::default()
             },
             ..Default::default()
@@ -99,11 +232,28 @@ pub async fn create_framework(
             Box::pin(async move {
                 poise::builtins::register_globally(ctx, &framework.options().commands).await?;
                 info!("Registered {} slash commands globally", framework.options().commands.len());
+                if let Some(voice) = &voice {
+                    voice.set_speaker_names(Arc::new(speaker_names::GuildMemberNames::new(
+                        ctx.clone(),
+                        settings.clone(),
+                    )));
+                    voice_session::spawn_session_limit_monitor(
+                        ctx.clone(),
+                        pool.clone(),
+                        voice.clone(),
+                        broadcast.clone(),
+                    );
+                }
                 Ok(Data {
                     pool,
                     translator,
                     broadcast,
                     voice,
+                    cooldowns,
+                    message_guard,
+                    prefixes,
+                    webhooks,
+                    settings,
                 })
             })
         })
@@ -119,6 +269,8 @@ pub async fn start_bot_with_token(
     pool: DbPool,
     translator: Arc<TranslationClient>,
     broadcast: Arc<BroadcastManager>,
+    voice_cache: Arc<VoiceTranscriptionCache>,
+    inference_tokens: InferenceTokens,
     token: &str,
 ) -> Result<(), Error> {
     if token.is_empty() {
@@ -127,13 +279,120 @@ pub async fn start_bot_with_token(
 
     let config = AppConfig::get();
 
-    let intents = GatewayIntents::GUILDS
+    let mut intents = GatewayIntents::GUILDS
         | GatewayIntents::GUILD_MESSAGES
         | GatewayIntents::MESSAGE_CONTENT
         | GatewayIntents::GUILD_MEMBERS
         | GatewayIntents::GUILD_VOICE_STATES;
+    if config.discord.allow_direct_messages {
+        intents |= GatewayIntents::DIRECT_MESSAGES;
+    }
+
+    // Voice is optional: a broken voice stack must not take text translation down
+    let (songbird, voice_manager, voice_bridge) = match init_voice(config, voice_cache, inference_tokens) {
+        Ok((songbird, voice_manager)) => {
+            // Spawn voice bridge to forward results to web clients
+            let voice_rx = voice_manager.subscribe_results();
+            let cache = voice_manager.cache(); // Get cache reference for response caching
+            let default_tts_mode = if config.voice.enable_tts_playback {
+                TtsMode::Both
+            } else {
+                TtsMode::WebOnly
+            };
+            let bridge = VoiceBridge::new(voice_rx, broadcast.clone(), cache)
+                .with_connection_state(voice_manager.subscribe_connection_state())
+                .with_min_confidence(config.voice.min_confidence)
+                .with_pool(pool.clone())
+                .with_tts_playback(voice_manager.clone(), default_tts_mode);
+            let bridge = bridge.spawn();
+            info!("Voice bridge started - forwarding transcriptions to web clients");
+            (Some(songbird), Some(voice_manager), Some(bridge))
+        }
+        Err(e) => {
+            warn!(error = %e, "Voice support failed to initialize; voice commands disabled, text translation unaffected");
+            (None, None, None)
+        }
+    };
+    let voice_enabled = voice_manager.is_some();
+
+    let result = async {
+        let framework = create_framework(pool, translator, broadcast, voice_manager).await?;
+
+        let mut builder = serenity::ClientBuilder::new(token, intents).framework(framework);
+        if let Some(songbird) = songbird {
+            builder = builder.register_songbird_with(songbird);
+        }
+        let mut client = builder.await?;
+
+        // Disconnect on Ctrl-C or SIGTERM so `start` returns and the caller's
+        // shutdown steps (saving the translation cache) run
+        let shard_manager = client.shard_manager.clone();
+        tokio::spawn(async move {
+            shutdown_signal().await;
+            info!("Shutdown signal received; disconnecting from Discord");
+            shard_manager.shutdown_all().await;
+        });
+
+        if voice_enabled {
+            info!("Starting Discord bot with voice support...");
+        } else {
+            info!("Starting Discord bot (text only)...");
+        }
+        client.start().await?;
+        Ok::<(), Error>(())
+    }
+    .await;
+
+    // Stop the bridge whether the client exited cleanly or not, so a restart
+    // doesn't leave it forwarding results alongside the new one
+    if let Some(bridge) = voice_bridge {
+        bridge.shutdown().await;
+    }
+
+    result
+}
+
+/// Resolves on Ctrl-C, or on SIGTERM (what `docker stop` sends) on Unix
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            warn!(error = %e, "Failed to listen for Ctrl-C");
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                warn!(error = %e, "Failed to listen for SIGTERM");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
+
+/// Set up Songbird and the voice manager, failing early if the voice stack
+/// can't work (e.g. libopus missing). Inference URLs were already checked by
+/// [`AppConfig::validate_endpoints`] at startup.
+fn init_voice(
+    config: &AppConfig,
+    cache: Arc<VoiceTranscriptionCache>,
+    inference_tokens: InferenceTokens,
+) -> Result<(Arc<songbird::Songbird>, Arc<VoiceManager>), Error> {
+    // Songbird only needs Opus once a call starts; probe it now instead
+    probe_opus().map_err(|e| format!("Opus codec unavailable: {}", e))?;
 
-    // Create Songbird voice manager
     let songbird = songbird::Songbird::serenity();
 
     // Create voice client config from app config
@@ -145,26 +404,79 @@ pub async fn start_bot_with_token(
         ping_interval: Duration::from_secs(10), // Faster dead connection detection
         max_queue_size: 500, // ~10 seconds of audio buffer
         queue_full_strategy: QueueFullStrategy::DropOldest, // Real-time voice
+        max_in_flight: config.voice.max_in_flight,
+        pipeline: VoicePipeline::parse(&config.voice.pipeline).unwrap_or_else(|| {
+            warn!(pipeline = %config.voice.pipeline, "Unknown voice.pipeline, using transcribe_translate_tts");
+            VoicePipeline::TranscribeTranslateTts
+        }),
+        tts_format: TtsFormat::parse(&config.voice.tts_format).unwrap_or_else(|| {
+            warn!(format = %config.voice.tts_format, "Unknown voice.tts_format, requesting wav");
+            TtsFormat::Wav
+        }),
+        tts_url: config.voice.tts_url.clone(),
+        max_frame_size: config.voice.max_frame_size,
+        profile_payloads: config.voice.profile_payloads,
+        inference_tokens,
     };
 
-    // Create voice manager
-    let voice_manager = Arc::new(VoiceManager::new(songbird.clone(), voice_client_config));
-
-    // Spawn voice bridge to forward results to web clients
-    let voice_rx = voice_manager.subscribe_results();
-    let cache = voice_manager.cache(); // Get cache reference for response caching
-    let _bridge_handle = spawn_voice_bridge(voice_rx, broadcast.clone(), cache);
-    info!("Voice bridge started - forwarding transcriptions to web clients");
-
-    let framework = create_framework(pool, translator, broadcast, Some(voice_manager)).await?;
+    let buffer_config = BufferConfig {
+        pre_roll: Duration::from_millis(config.voice.pre_roll_ms),
+        trailing_silence: Duration::from_millis(config.voice.trailing_silence_ms),
+        chunk_overlap: Duration::from_millis(config.voice.chunk_overlap_ms),
+        noise_gate: config.voice.noise_gate.then_some(NoiseGate {
+            max_zero_crossing_rate: config.voice.noise_gate_max_zero_crossing_rate,
+            max_crest_factor: config.voice.noise_gate_max_crest_factor,
+        }),
+        min_segment_rms: config.voice.min_segment_rms,
+    };
+    let playback_policy = PlaybackPolicy::parse(
+        &config.voice.tts_playback,
+        Duration::from_secs(config.voice.tts_stale_after_secs),
+        config.voice.tts_overlap_gain,
+    )
+    .unwrap_or_else(|| {
+        warn!(policy = %config.voice.tts_playback, "Unknown voice.tts_playback, playing sequentially");
+        PlaybackPolicy::Sequential
+    });
+    let voice_manager = Arc::new(
+        VoiceManager::new(songbird.clone(), voice_client_config)
+            .with_buffer_config(buffer_config)
+            .with_cache(cache)
+            .with_max_guilds(config.voice.max_concurrent_guilds)
+            .with_playback_policy(playback_policy),
+    );
+    Ok((songbird, voice_manager))
+}
 
-    let mut client = serenity::ClientBuilder::new(token, intents)
-        .framework(framework)
-        .register_songbird_with(songbird.clone())
-        .await?;
+/// Round-trip one silent 20 ms frame through the Opus bindings Songbird
+/// decodes received voice with. Building a decoder alone doesn't touch the
+/// codec, so a broken libopus would otherwise only fail inside a call.
+fn probe_opus() -> Result<(), String> {
+    use songbird::driver::opus::{
+        coder::{Decoder, Encoder},
+        Application, Channels, SampleRate,
+    };
+    // Samples per channel in 20 ms at 48 kHz
+    const FRAME_SAMPLES: usize = 960;
 
-    info!("Starting Discord bot with voice support...");
-    client.start().await?;
+    let round_trip = || -> Result<usize, songbird::driver::opus::Error> {
+        let encoder = Encoder::new(SampleRate::Hz48000, Channels::Stereo, Application::Voip)?;
+        let mut packet = [0u8; 256];
+        let len = encoder.encode(&[0i16; FRAME_SAMPLES * 2], &mut packet)?;
 
-    Ok(())
+        let mut decoder = Decoder::new(SampleRate::Hz48000, Channels::Stereo)?;
+        let mut pcm = [0i16; FRAME_SAMPLES * 2];
+        decoder.decode(
+            Some((&packet[..len]).try_into()?),
+            (&mut pcm[..]).try_into()?,
+            false,
+        )
+    };
+    match round_trip().map_err(|e| e.to_string())? {
+        FRAME_SAMPLES => Ok(()),
+        decoded => Err(format!(
+            "decoded {} samples from a {}-sample frame",
+            decoded, FRAME_SAMPLES
+        )),
+    }
 }
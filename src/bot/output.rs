@@ -0,0 +1,331 @@
+//! Posting text translations back to Discord.
+//!
+//! Guilds pick an [`OutputMode`]: a bot reply, a thread started from the
+//! original message, or a channel webhook that posts under the author's name
+//! and avatar. Webhooks are created once per channel and cached; their IDs
+//! are also how the message handler recognises our own webhook posts.
+//!
+//! Every posted translation is recorded against the message it translates,
+//! which is how `/feedback` finds the original in modes without a reply.
+
+use crate::bot::discord_limits::{self, truncate};
+use crate::db::{DbPool, NewTranslationMessage, OutputMode, TranslationMessageRepo};
+use crate::translation::TranslationResult;
+use dashmap::DashMap;
+use poise::serenity_prelude::{
+    self as serenity, ChannelId, Context, CreateAllowedMentions, CreateEmbed, CreateEmbedFooter,
+    CreateMessage, CreateThread, CreateWebhook, ExecuteWebhook, Message, Webhook, WebhookId,
+};
+use tracing::{error, warn};
+
+/// Name of the webhook the bot creates in each channel
+const WEBHOOK_NAME: &str = "LinguaBridge";
+
+/// Per-channel webhooks used for [`OutputMode::Webhook`]
+#[derive(Debug, Default)]
+pub struct WebhookCache {
+    by_channel: DashMap<ChannelId, Webhook>,
+}
+
+impl WebhookCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether `id` is one of the webhooks this bot posts through
+    pub fn is_own(&self, id: WebhookId) -> bool {
+        self.by_channel.iter().any(|entry| entry.value().id == id)
+    }
+
+    /// The bot's webhook in `channel`, reusing an existing one before
+    /// creating a new one
+    pub async fn get_or_create(&self, ctx: &Context, channel: ChannelId) -> serenity::Result<Webhook> {
+        if let Some(webhook) = self.by_channel.get(&channel) {
+            return Ok(webhook.clone());
+        }
+
+        let own_id = ctx.cache.current_user().id;
+        let existing = channel.webhooks(&ctx.http).await?.into_iter().find(|w| {
+            w.token.is_some()
+                && w.name.as_deref() == Some(WEBHOOK_NAME)
+                && w.user.as_ref().is_some_and(|u| u.id == own_id)
+        });
+        let webhook = match existing {
+            Some(webhook) => webhook,
+            None => channel.create_webhook(&ctx.http, CreateWebhook::new(WEBHOOK_NAME)).await?,
+        };
+
+        self.by_channel.insert(channel, webhook.clone());
+        Ok(webhook)
+    }
+
+    /// Drop a cached webhook, e.g. after it was deleted in Discord
+    pub fn invalidate(&self, channel: ChannelId) {
+        self.by_channel.remove(&channel);
+    }
+}
+
+/// The mode to actually use for a message posted in a thread or not.
+///
+/// Threads can't contain threads, and webhooks belong to the parent channel,
+/// so messages inside threads always get plain replies.
+pub fn effective_mode(mode: OutputMode, in_thread: bool) -> OutputMode {
+    if in_thread {
+        OutputMode::Reply
+    } else {
+        mode
+    }
+}
+
+/// Post `translations` of `msg` using `mode`, falling back to replies for
+/// any the bot couldn't post in a thread or through a webhook
+pub async fn deliver(
+    ctx: &Context,
+    msg: &Message,
+    translations: &[TranslationResult],
+    mode: OutputMode,
+    webhooks: &WebhookCache,
+    pool: &DbPool,
+) {
+    // Nothing to show for translations into the message's own language
+    let translations: Vec<&TranslationResult> = translations
+        .iter()
+        .filter(|t| t.source_lang != t.target_lang)
+        .collect();
+    if translations.is_empty() {
+        return;
+    }
+
+    let in_thread = mode != OutputMode::Reply
+        && msg
+            .guild(&ctx.cache)
+            .is_some_and(|guild| guild.threads.iter().any(|t| t.id == msg.channel_id));
+
+    let unposted = match effective_mode(mode, in_thread) {
+        OutputMode::Reply => translations,
+        OutputMode::Thread => post_in_thread(ctx, msg, translations, pool).await,
+        OutputMode::Webhook => post_as_author(ctx, msg, translations, webhooks, pool).await,
+    };
+
+    for translation in unposted {
+        for posted in send_reply(ctx, msg, translation).await {
+            record_posted(pool, msg, &posted, translation).await;
+        }
+    }
+}
+
+/// Start a thread on the original message and post every translation in it.
+///
+/// Returns every translation if the thread couldn't be created.
+async fn post_in_thread<'a>(
+    ctx: &Context,
+    msg: &Message,
+    translations: Vec<&'a TranslationResult>,
+    pool: &DbPool,
+) -> Vec<&'a TranslationResult> {
+    let thread = match msg
+        .channel_id
+        .create_thread_from_message(&ctx.http, msg.id, CreateThread::new(thread_name(&msg.content)))
+        .await
+    {
+        Ok(thread) => thread,
+        Err(e) => {
+            warn!(error = %e, channel_id = %msg.channel_id, "Failed to create translation thread, replying instead");
+            return translations;
+        }
+    };
+
+    for translation in translations {
+        for embed in translation_embeds(translation) {
+            let builder = CreateMessage::default().embed(embed);
+            match thread.id.send_message(&ctx.http, builder).await {
+                Ok(posted) => record_posted(pool, msg, &posted, translation).await,
+                Err(e) => error!("Failed to post translation in thread: {}", e),
+            }
+        }
+    }
+    Vec::new()
+}
+
+/// Post every translation through the channel webhook as the original author.
+///
+/// Returns the translations that weren't (fully) posted, starting with the
+/// one a webhook post failed on, so the caller can reply with them instead.
+async fn post_as_author<'a>(
+    ctx: &Context,
+    msg: &Message,
+    translations: Vec<&'a TranslationResult>,
+    webhooks: &WebhookCache,
+    pool: &DbPool,
+) -> Vec<&'a TranslationResult> {
+    let webhook = match webhooks.get_or_create(ctx, msg.channel_id).await {
+        Ok(webhook) => webhook,
+        Err(e) => {
+            warn!(error = %e, channel_id = %msg.channel_id, "No usable webhook, replying instead");
+            return translations;
+        }
+    };
+
+    let author = msg
+        .member
+        .as_ref()
+        .and_then(|m| m.nick.clone())
+        .unwrap_or_else(|| msg.author.display_name().to_string());
+
+    for (i, translation) in translations.iter().enumerate() {
+        let username = webhook_username(&author, &translation.target_lang);
+        for part in discord_limits::split(&translation.translated_text, discord_limits::MESSAGE_CONTENT) {
+            let builder = ExecuteWebhook::new()
+                .username(&username)
+                .avatar_url(msg.author.face())
+                .content(part)
+                // The original already pinged whoever it mentions
+                .allowed_mentions(CreateAllowedMentions::new());
+            // Wait for the message so it can be recorded for `/feedback`
+            match webhook.execute(&ctx.http, true, builder).await {
+                Ok(Some(posted)) => record_posted(pool, msg, &posted, translation).await,
+                Ok(None) => {}
+                Err(e) => {
+                    // Most likely deleted from the channel settings; recreate next time
+                    webhooks.invalidate(msg.channel_id);
+                    warn!(error = %e, channel_id = %msg.channel_id, "Webhook post failed, replying with the rest");
+                    return translations[i..].to_vec();
+                }
+            }
+        }
+    }
+    Vec::new()
+}
+
+/// Send a translation as a Discord reply, continuing in follow-up messages
+/// when it is too long for one embed. Returns the messages sent.
+pub(crate) async fn send_reply(
+    ctx: &Context,
+    original_msg: &Message,
+    translation: &TranslationResult,
+) -> Vec<Message> {
+    let mut sent = Vec::new();
+    for (i, embed) in translation_embeds(translation).into_iter().enumerate() {
+        let mut builder = CreateMessage::default().embed(embed);
+        if i == 0 {
+            builder = builder.reference_message(original_msg);
+        }
+
+        match original_msg.channel_id.send_message(&ctx.http, builder).await {
+            Ok(message) => sent.push(message),
+            Err(e) => {
+                error!("Failed to send translation reply: {}", e);
+                break;
+            }
+        }
+    }
+    sent
+}
+
+/// Link a posted translation message to the message it translates
+async fn record_posted(pool: &DbPool, original: &Message, posted: &Message, translation: &TranslationResult) {
+    let Some(guild_id) = original.guild_id else {
+        return;
+    };
+    let record = NewTranslationMessage {
+        message_id: posted.id.to_string(),
+        guild_id: guild_id.to_string(),
+        channel_id: posted.channel_id.to_string(),
+        original_channel_id: original.channel_id.to_string(),
+        original_message_id: original.id.to_string(),
+        source_lang: translation.source_lang.clone(),
+        target_lang: translation.target_lang.clone(),
+        translated_text: translation.translated_text.clone(),
+    };
+    if let Err(e) = TranslationMessageRepo::record(pool, record).await {
+        warn!(error = %e, message_id = %posted.id, "Failed to record translation message");
+    }
+}
+
+/// One embed per part of the translation that fits in an embed description
+fn translation_embeds(translation: &TranslationResult) -> Vec<CreateEmbed> {
+    let footer = format!(
+        "{} → {}",
+        translation.source_lang.to_uppercase(),
+        translation.target_lang.to_uppercase()
+    );
+    discord_limits::split(&translation.translated_text, discord_limits::EMBED_DESCRIPTION)
+        .into_iter()
+        .map(|part| {
+            CreateEmbed::default()
+                .description(part)
+                .footer(CreateEmbedFooter::new(&footer))
+                .color(0x5865F2) // Discord blurple
+        })
+        .collect()
+}
+
+/// Thread title taken from the start of the original message
+fn thread_name(content: &str) -> String {
+    let first_line = content.lines().next().unwrap_or_default().trim();
+    let name = if first_line.is_empty() { "Translations" } else { first_line };
+    truncate(name, discord_limits::THREAD_NAME)
+}
+
+/// Author name tagged with the translation's language, e.g. "Ana [ES]"
+fn webhook_username(author: &str, target_lang: &str) -> String {
+    let tag = format!(" [{}]", target_lang.to_uppercase());
+    let name_len = discord_limits::WEBHOOK_USERNAME.saturating_sub(tag.chars().count());
+    format!("{}{}", truncate(author, name_len), tag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_effective_mode_outside_threads() {
+        for mode in [OutputMode::Reply, OutputMode::Thread, OutputMode::Webhook] {
+            assert_eq!(effective_mode(mode, false), mode);
+        }
+    }
+
+    #[test]
+    fn test_effective_mode_inside_threads_replies() {
+        for mode in [OutputMode::Reply, OutputMode::Thread, OutputMode::Webhook] {
+            assert_eq!(effective_mode(mode, true), OutputMode::Reply);
+        }
+    }
+
+    #[test]
+    fn test_thread_name() {
+        assert_eq!(thread_name("hola amigos\nsecond line"), "hola amigos");
+        assert_eq!(thread_name("   "), "Translations");
+        let long = "a".repeat(300);
+        assert_eq!(thread_name(&long).chars().count(), discord_limits::THREAD_NAME);
+    }
+
+    #[test]
+    fn test_webhook_username() {
+        assert_eq!(webhook_username("Ana", "es"), "Ana [ES]");
+        let long = webhook_username(&"n".repeat(200), "pt-br");
+        assert_eq!(long.chars().count(), discord_limits::WEBHOOK_USERNAME);
+        assert!(long.ends_with(" [PT-BR]"));
+    }
+
+    #[test]
+    fn test_long_translation_spans_several_embeds() {
+        let mut translation = TranslationResult {
+            original_text: "hola".to_string(),
+            translated_text: "hello".to_string(),
+            source_lang: "es".to_string(),
+            target_lang: "en".to_string(),
+            cached: false,
+        };
+        assert_eq!(translation_embeds(&translation).len(), 1);
+
+        translation.translated_text = "word ".repeat(2000);
+        assert_eq!(translation_embeds(&translation).len(), 3);
+    }
+
+    #[test]
+    fn test_unknown_webhook_is_not_own() {
+        let cache = WebhookCache::new();
+        assert!(!cache.is_own(WebhookId::new(1)));
+    }
+}
@@ -0,0 +1,136 @@
+//! Per-guild text command prefixes.
+//!
+//! Guilds may override the global `discord.command_prefix` with their own
+//! (`guilds.command_prefix`). The prefix is resolved for every message that
+//! could be a command, so lookups are cached for a few minutes.
+
+use crate::db::{DbPool, GuildRepo};
+use crate::error::AppResult;
+use dashmap::DashMap;
+use std::time::{Duration, Instant};
+
+/// How long a looked-up prefix is trusted before reading it again
+const CACHE_TTL: Duration = Duration::from_secs(300);
+
+/// Longest prefix a guild may set
+pub const MAX_PREFIX_LEN: usize = 16;
+
+/// Cached per-guild prefix overrides
+#[derive(Debug)]
+pub struct PrefixCache {
+    default_prefix: String,
+    /// Guild override (None when the guild uses the default) and lookup time
+    entries: DashMap<u64, (Option<String>, Instant)>,
+}
+
+impl PrefixCache {
+    pub fn new(default_prefix: impl Into<String>) -> Self {
+        Self {
+            default_prefix: default_prefix.into(),
+            entries: DashMap::new(),
+        }
+    }
+
+    /// Global prefix used outside guilds and by guilds without an override
+    pub fn default_prefix(&self) -> &str {
+        &self.default_prefix
+    }
+
+    /// Prefix for `guild_id`, reading the database only when the cached
+    /// value is missing or stale
+    pub async fn resolve(&self, pool: &DbPool, guild_id: Option<u64>) -> AppResult<String> {
+        let Some(guild_id) = guild_id else {
+            return Ok(self.default_prefix.clone());
+        };
+
+        let now = Instant::now();
+        if let Some(prefix) = self.cached(guild_id, now) {
+            return Ok(prefix);
+        }
+
+        let custom = GuildRepo::get_command_prefix(pool, &guild_id.to_string()).await?;
+        self.entries.insert(guild_id, (custom.clone(), now));
+        Ok(custom.unwrap_or_else(|| self.default_prefix.clone()))
+    }
+
+    /// Forget a guild's cached prefix after it changes
+    pub fn invalidate(&self, guild_id: u64) {
+        self.entries.remove(&guild_id);
+    }
+
+    fn cached(&self, guild_id: u64, now: Instant) -> Option<String> {
+        let entry = self.entries.get(&guild_id)?;
+        let (custom, fetched) = entry.value();
+        (now.saturating_duration_since(*fetched) < CACHE_TTL)
+            .then(|| custom.clone().unwrap_or_else(|| self.default_prefix.clone()))
+    }
+}
+
+/// Validate a prefix entered by a guild admin
+pub fn validate_prefix(prefix: &str) -> Result<(), String> {
+    if prefix.trim().is_empty() {
+        return Err("The prefix can't be empty.".to_string());
+    }
+    if prefix.chars().count() > MAX_PREFIX_LEN {
+        return Err(format!("The prefix can be at most {} characters.", MAX_PREFIX_LEN));
+    }
+    if prefix.starts_with('/') {
+        return Err("The prefix can't start with `/`, which is used by slash commands.".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{queries::setup_test_db, NewGuild};
+
+    async fn pool_with_guild() -> DbPool {
+        let pool = setup_test_db().await;
+        GuildRepo::upsert(
+            &pool,
+            NewGuild {
+                guild_id: "1".to_string(),
+                name: "Test".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+        pool
+    }
+
+    #[tokio::test]
+    async fn test_guild_prefix_falls_back_to_default() {
+        let pool = pool_with_guild().await;
+        let cache = PrefixCache::new("!lb ");
+        assert_eq!(cache.resolve(&pool, Some(1)).await.unwrap(), "!lb ");
+        assert_eq!(cache.resolve(&pool, Some(2)).await.unwrap(), "!lb ");
+        assert_eq!(cache.resolve(&pool, None).await.unwrap(), "!lb ");
+    }
+
+    #[tokio::test]
+    async fn test_lookup_is_cached_until_invalidated() {
+        let pool = pool_with_guild().await;
+        let cache = PrefixCache::new("!lb ");
+        assert_eq!(cache.resolve(&pool, Some(1)).await.unwrap(), "!lb ");
+
+        GuildRepo::set_command_prefix(&pool, "1", Some("?")).await.unwrap();
+        assert_eq!(cache.resolve(&pool, Some(1)).await.unwrap(), "!lb ");
+
+        cache.invalidate(1);
+        assert_eq!(cache.resolve(&pool, Some(1)).await.unwrap(), "?");
+
+        GuildRepo::set_command_prefix(&pool, "1", None).await.unwrap();
+        cache.invalidate(1);
+        assert_eq!(cache.resolve(&pool, Some(1)).await.unwrap(), "!lb ");
+    }
+
+    #[test]
+    fn test_validate_prefix() {
+        assert!(validate_prefix("?").is_ok());
+        assert!(validate_prefix("lb! ").is_ok());
+        assert!(validate_prefix("  ").is_err());
+        assert!(validate_prefix("/lb").is_err());
+        assert!(validate_prefix(&"x".repeat(MAX_PREFIX_LEN + 1)).is_err());
+    }
+}
@@ -0,0 +1,101 @@
+//! Reactions that show a message is being translated.
+//!
+//! Guilds that opt in (`/setup progress`) get a reaction on each message
+//! while its translations are in flight. It is removed when they are done,
+//! or replaced with ✅ or ❌ in the `outcome` mode. Without the permission
+//! to react the bot translates as usual and shows nothing.
+
+use crate::db::ProgressReaction;
+use poise::serenity_prelude::{self as serenity, Context, Message};
+use tracing::debug;
+
+/// Shown while a message is being translated; ⏳ already marks messages
+/// skipped for the rate limit
+const IN_PROGRESS: char = '🔄';
+
+/// The progress reaction left on one message, if any
+pub struct Progress {
+    mode: ProgressReaction,
+    shown: bool,
+}
+
+impl Progress {
+    /// React to `msg` unless the guild turned progress reactions off or the
+    /// bot can't react in its channel
+    pub async fn start(ctx: &Context, msg: &Message, mode: ProgressReaction) -> Self {
+        let mut progress = Self { mode, shown: false };
+        if mode == ProgressReaction::Off || !can_react(ctx, msg) {
+            return progress;
+        }
+        match msg.react(&ctx.http, IN_PROGRESS).await {
+            Ok(_) => progress.shown = true,
+            Err(e) => debug!("Couldn't add progress reaction to message {}: {}", msg.id, e),
+        }
+        progress
+    }
+
+    /// Take the progress reaction off, leaving the outcome in its place when
+    /// the guild asked for it
+    pub async fn finish(self, ctx: &Context, msg: &Message, translated: usize, failed: usize) {
+        if !self.shown {
+            return;
+        }
+        if let Err(e) = msg.delete_reaction(&ctx.http, None, IN_PROGRESS).await {
+            debug!("Couldn't remove progress reaction from message {}: {}", msg.id, e);
+        }
+        let Some(outcome) = outcome_reaction(self.mode, translated, failed) else {
+            return;
+        };
+        if let Err(e) = msg.react(&ctx.http, outcome).await {
+            debug!("Couldn't add outcome reaction to message {}: {}", msg.id, e);
+        }
+    }
+}
+
+/// Reaction that replaces the progress one: ❌ if any translation failed,
+/// ✅ if some succeeded, nothing when there was nothing to translate
+fn outcome_reaction(mode: ProgressReaction, translated: usize, failed: usize) -> Option<char> {
+    if mode != ProgressReaction::Outcome {
+        return None;
+    }
+    if failed > 0 {
+        Some('❌')
+    } else if translated > 0 {
+        Some('✅')
+    } else {
+        None
+    }
+}
+
+/// Whether the cache says the bot may react in the message's channel.
+/// Anything not cached yet (such as threads) is left for Discord to decide.
+fn can_react(ctx: &Context, msg: &Message) -> bool {
+    let Some(guild) = msg.guild_id.and_then(|id| ctx.cache.guild(id)) else {
+        return true;
+    };
+    let bot_id = ctx.cache.current_user().id;
+    let (Some(channel), Some(member)) = (guild.channels.get(&msg.channel_id), guild.members.get(&bot_id))
+    else {
+        return true;
+    };
+    // Discord also wants Read Message History before it accepts a reaction
+    guild.user_permissions_in(channel, member).contains(
+        serenity::Permissions::ADD_REACTIONS | serenity::Permissions::READ_MESSAGE_HISTORY,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_outcome_reaction() {
+        assert_eq!(outcome_reaction(ProgressReaction::Outcome, 2, 0), Some('✅'));
+        assert_eq!(outcome_reaction(ProgressReaction::Outcome, 1, 1), Some('❌'));
+        assert_eq!(outcome_reaction(ProgressReaction::Outcome, 0, 1), Some('❌'));
+        // Nothing needed translating, e.g. already in every target language
+        assert_eq!(outcome_reaction(ProgressReaction::Outcome, 0, 0), None);
+        // Pending mode only removes the progress reaction
+        assert_eq!(outcome_reaction(ProgressReaction::Pending, 2, 0), None);
+    }
+}
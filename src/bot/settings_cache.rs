@@ -0,0 +1,269 @@
+//! Cached guild settings, ignore lists, channel language whitelists, message
+//! filters and translation contexts, and user preferences.
+//!
+//! Every message in a translated channel needs its guild's settings and
+//! ignore list, the channel's allowed languages, filter and context, and the
+//! author's preferences, so reads go through [`SettingsCache`], which keeps
+//! each row for a minute. Commands that write settings through the repos
+//! invalidate the guild or user they changed; the database stays the source
+//! of truth and the TTL bounds staleness from anything else.
+
+use crate::bot::handler::compile_message_filter;
+use crate::db::{
+    ChannelRepo, DbPool, GuildRepo, GuildSettings, IgnoreList, IgnoreListRepo, UserPreference,
+    UserPreferenceRepo,
+};
+use crate::error::AppResult;
+use dashmap::DashMap;
+use regex::Regex;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// How long a looked-up row is trusted before reading it again
+const CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// Preference entries kept before expired ones are swept
+const MAX_PREFERENCE_ENTRIES: usize = 10_000;
+
+/// Short-lived cache in front of `GuildRepo`, `IgnoreListRepo`,
+/// `ChannelRepo` and `UserPreferenceRepo`
+#[derive(Debug)]
+pub struct SettingsCache {
+    pool: DbPool,
+    /// Guild settings (None for unconfigured guilds) and lookup time
+    guilds: DashMap<String, (Option<GuildSettings>, Instant)>,
+    /// Ignored users and roles by guild and lookup time
+    ignores: DashMap<String, (IgnoreList, Instant)>,
+    /// Allowed target languages by channel and lookup time
+    channels: DashMap<String, (Vec<String>, Instant)>,
+    /// Compiled message filters by channel and lookup time
+    filters: DashMap<String, (Option<Regex>, Instant)>,
+    /// Channel translation context overrides and lookup time
+    contexts: DashMap<String, (Option<String>, Instant)>,
+    /// Preferences by (user, guild) and lookup time
+    preferences: DashMap<(String, String), (Option<UserPreference>, Instant)>,
+}
+
+impl SettingsCache {
+    pub fn new(pool: DbPool) -> Self {
+        Self {
+            pool,
+            guilds: DashMap::new(),
+            ignores: DashMap::new(),
+            channels: DashMap::new(),
+            filters: DashMap::new(),
+            contexts: DashMap::new(),
+            preferences: DashMap::new(),
+        }
+    }
+
+    /// The database behind the cache, for writes it doesn't cover
+    pub fn pool(&self) -> &DbPool {
+        &self.pool
+    }
+
+    /// Settings for `guild_id`, reading the database only when the cached
+    /// value is missing or stale
+    pub async fn guild_settings(&self, guild_id: &str) -> AppResult<Option<GuildSettings>> {
+        let now = Instant::now();
+        if let Some(settings) = fresh(self.guilds.get(guild_id).as_deref(), now) {
+            return Ok(settings);
+        }
+
+        let settings = GuildRepo::get_settings(&self.pool, guild_id).await?;
+        self.guilds.insert(guild_id.to_string(), (settings.clone(), now));
+        Ok(settings)
+    }
+
+    /// Users and roles `guild_id` doesn't translate, cached like guild
+    /// settings
+    pub async fn ignore_list(&self, guild_id: &str) -> AppResult<IgnoreList> {
+        let now = Instant::now();
+        if let Some(list) = fresh(self.ignores.get(guild_id).as_deref(), now) {
+            return Ok(list);
+        }
+
+        let list = IgnoreListRepo::get(&self.pool, guild_id).await?;
+        self.ignores.insert(guild_id.to_string(), (list.clone(), now));
+        Ok(list)
+    }
+
+    /// Languages `channel_id` may be translated into (empty allows all),
+    /// cached like guild settings
+    pub async fn allowed_languages(&self, channel_id: &str) -> AppResult<Vec<String>> {
+        let now = Instant::now();
+        if let Some(allowed) = fresh(self.channels.get(channel_id).as_deref(), now) {
+            return Ok(allowed);
+        }
+
+        let allowed = ChannelRepo::get_allowed_languages(&self.pool, channel_id).await?;
+        self.channels.insert(channel_id.to_string(), (allowed.clone(), now));
+        Ok(allowed)
+    }
+
+    /// The compiled filter messages in `channel_id` must match to be
+    /// translated (None translates all), cached like guild settings
+    pub async fn message_filter(&self, channel_id: &str) -> AppResult<Option<Regex>> {
+        let now = Instant::now();
+        if let Some(filter) = fresh(self.filters.get(channel_id).as_deref(), now) {
+            return Ok(filter);
+        }
+
+        let pattern = ChannelRepo::get_message_filter(&self.pool, channel_id).await?;
+        // Patterns are validated when set, so this only fails if the regex
+        // engine's limits changed underneath a stored one
+        let filter = pattern.and_then(|pattern| match compile_message_filter(&pattern) {
+            Ok(filter) => Some(filter),
+            Err(e) => {
+                warn!(channel_id, error = %e, "Ignoring invalid stored message filter");
+                None
+            }
+        });
+        self.filters.insert(channel_id.to_string(), (filter.clone(), now));
+        Ok(filter)
+    }
+
+    /// The translation context `channel_id` uses instead of its guild's (None
+    /// uses the guild's), cached like guild settings
+    pub async fn translation_context(&self, channel_id: &str) -> AppResult<Option<String>> {
+        let now = Instant::now();
+        if let Some(context) = fresh(self.contexts.get(channel_id).as_deref(), now) {
+            return Ok(context);
+        }
+
+        let context = ChannelRepo::get_translation_context(&self.pool, channel_id).await?;
+        self.contexts.insert(channel_id.to_string(), (context.clone(), now));
+        Ok(context)
+    }
+
+    /// A user's preferences in a guild, cached like guild settings
+    pub async fn user_preference(
+        &self,
+        user_id: &str,
+        guild_id: &str,
+    ) -> AppResult<Option<UserPreference>> {
+        let key = (user_id.to_string(), guild_id.to_string());
+        let now = Instant::now();
+        if let Some(pref) = fresh(self.preferences.get(&key).as_deref(), now) {
+            return Ok(pref);
+        }
+
+        let pref = UserPreferenceRepo::get(&self.pool, user_id, guild_id).await?;
+        if self.preferences.len() >= MAX_PREFERENCE_ENTRIES {
+            self.preferences
+                .retain(|_, (_, fetched)| now.saturating_duration_since(*fetched) < CACHE_TTL);
+        }
+        self.preferences.insert(key, (pref.clone(), now));
+        Ok(pref)
+    }
+
+    /// Forget a guild's cached settings after they change
+    pub fn invalidate_guild(&self, guild_id: &str) {
+        self.guilds.remove(guild_id);
+    }
+
+    /// Forget a guild's cached ignore list after it changes
+    pub fn invalidate_ignore_list(&self, guild_id: &str) {
+        self.ignores.remove(guild_id);
+    }
+
+    /// Forget a channel's cached whitelist, filter and context after they
+    /// change
+    pub fn invalidate_channel(&self, channel_id: &str) {
+        self.channels.remove(channel_id);
+        self.filters.remove(channel_id);
+        self.contexts.remove(channel_id);
+    }
+
+    /// Forget a user's cached preferences after they change
+    pub fn invalidate_user(&self, user_id: &str, guild_id: &str) {
+        self.preferences
+            .remove(&(user_id.to_string(), guild_id.to_string()));
+    }
+
+    /// Forget every cached user preference in a guild
+    pub fn invalidate_guild_users(&self, guild_id: &str) {
+        self.preferences.retain(|(_, guild), _| guild != guild_id);
+    }
+}
+
+fn fresh<T: Clone>(entry: Option<&(T, Instant)>, now: Instant) -> Option<T> {
+    let (value, fetched) = entry?;
+    (now.saturating_duration_since(*fetched) < CACHE_TTL).then(|| value.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{queries::setup_test_db, NewGuild};
+
+    async fn pool_with_guild() -> DbPool {
+        let pool = setup_test_db().await;
+        GuildRepo::upsert(
+            &pool,
+            NewGuild {
+                guild_id: "1".to_string(),
+                name: "Test".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+        pool
+    }
+
+    #[tokio::test]
+    async fn test_rapid_reads_hit_database_once() {
+        let pool = pool_with_guild().await;
+        let cache = SettingsCache::new(pool.clone());
+        let first = cache.guild_settings("1").await.unwrap().unwrap();
+        assert_eq!(first.default_language, "en");
+
+        // Change the row behind the cache's back; the second read must not see it
+        sqlx::query("UPDATE guilds SET default_language = 'fr' WHERE guild_id = '1'")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let second = cache.guild_settings("1").await.unwrap().unwrap();
+        assert_eq!(second.default_language, "en");
+
+        cache.invalidate_guild("1");
+        let third = cache.guild_settings("1").await.unwrap().unwrap();
+        assert_eq!(third.default_language, "fr");
+    }
+
+    #[tokio::test]
+    async fn test_missing_rows_are_cached_too() {
+        let pool = pool_with_guild().await;
+        let cache = SettingsCache::new(pool.clone());
+        assert!(cache.user_preference("u1", "1").await.unwrap().is_none());
+
+        UserPreferenceRepo::set_language(&pool, "u1", "1", "de").await.unwrap();
+        assert!(cache.user_preference("u1", "1").await.unwrap().is_none());
+
+        cache.invalidate_user("u1", "1");
+        let pref = cache.user_preference("u1", "1").await.unwrap().unwrap();
+        assert_eq!(pref.preferred_language, "de");
+    }
+
+    #[tokio::test]
+    async fn test_guilds_cached_separately() {
+        let pool = pool_with_guild().await;
+        let cache = SettingsCache::new(pool);
+        assert!(cache.guild_settings("1").await.unwrap().is_some());
+        assert!(cache.guild_settings("2").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_message_filter_compiled_and_invalidated() {
+        let pool = pool_with_guild().await;
+        let cache = SettingsCache::new(pool.clone());
+        assert!(cache.message_filter("ch1").await.unwrap().is_none());
+
+        ChannelRepo::set_message_filter(&pool, "1", "ch1", Some(r"\?$")).await.unwrap();
+        assert!(cache.message_filter("ch1").await.unwrap().is_none());
+
+        cache.invalidate_channel("ch1");
+        let filter = cache.message_filter("ch1").await.unwrap().unwrap();
+        assert!(filter.is_match("anyone around?"));
+    }
+}
@@ -0,0 +1,127 @@
+//! Names voice speakers go by, for transcripts and the web view.
+//!
+//! Speakers are labelled when they first talk in a session. By default that
+//! is their server nickname (falling back to their display name and then
+//! username); guilds can turn `use_nicknames` off to show usernames only, or
+//! `anonymize_speakers` on to number speakers instead of naming them.
+//! Speakers on the guild's ignore list (`/setup ignore`) aren't transcribed
+//! at all.
+
+use crate::bot::settings_cache::SettingsCache;
+use crate::voice::SpeakerNames;
+use async_trait::async_trait;
+use poise::serenity_prelude as serenity;
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+/// Resolves speakers through the guild member cache, fetching members it
+/// hasn't seen yet
+pub struct GuildMemberNames {
+    ctx: serenity::Context,
+    settings: Arc<SettingsCache>,
+}
+
+impl GuildMemberNames {
+    pub fn new(ctx: serenity::Context, settings: Arc<SettingsCache>) -> Self {
+        Self { ctx, settings }
+    }
+}
+
+#[async_trait]
+impl SpeakerNames for GuildMemberNames {
+    async fn speaker_name(&self, guild_id: u64, user_id: u64) -> Option<String> {
+        let guild_id = serenity::GuildId::new(guild_id);
+        let member = match guild_id.member(&self.ctx, serenity::UserId::new(user_id)).await {
+            Ok(member) => member,
+            Err(e) => {
+                debug!(error = %e, user_id, "Couldn't look up voice speaker");
+                return None;
+            }
+        };
+
+        // Unknown guilds keep the default of showing nicknames
+        let use_nicknames = self
+            .settings
+            .guild_settings(&guild_id.to_string())
+            .await
+            .ok()
+            .flatten()
+            .is_none_or(|s| s.use_nicknames);
+        Some(speaker_label(
+            member.nick.as_deref(),
+            member.user.global_name.as_deref(),
+            &member.user.name,
+            use_nicknames,
+        ))
+    }
+
+    async fn anonymize(&self, guild_id: u64) -> bool {
+        self.settings
+            .guild_settings(&guild_id.to_string())
+            .await
+            .ok()
+            .flatten()
+            .is_some_and(|s| s.anonymize_speakers)
+    }
+
+    async fn is_ignored(&self, guild_id: u64, user_id: u64) -> bool {
+        let ignores = match self.settings.ignore_list(&guild_id.to_string()).await {
+            Ok(ignores) => ignores,
+            Err(e) => {
+                // Speakers are registered as they start talking; failing
+                // closed could silence someone for the rest of the session
+                warn!(error = %e, user_id, "Couldn't load ignore list, transcribing speaker");
+                return false;
+            }
+        };
+        if ignores.is_empty() {
+            return false;
+        }
+
+        // Roles need the member; skip the lookup when no role is ignored
+        let roles = if ignores.roles.is_empty() {
+            Vec::new()
+        } else {
+            match serenity::GuildId::new(guild_id)
+                .member(&self.ctx, serenity::UserId::new(user_id))
+                .await
+            {
+                Ok(member) => member.roles.iter().map(|role| role.to_string()).collect(),
+                Err(e) => {
+                    debug!(error = %e, user_id, "Couldn't look up voice speaker's roles");
+                    Vec::new()
+                }
+            }
+        };
+        ignores.ignores(&user_id.to_string(), &roles)
+    }
+}
+
+/// What to call a speaker: their nickname, else display name, else username;
+/// or just the username when the guild turned nicknames off
+fn speaker_label(
+    nick: Option<&str>,
+    global_name: Option<&str>,
+    username: &str,
+    use_nicknames: bool,
+) -> String {
+    let preferred = nick.or(global_name).filter(|_| use_nicknames);
+    preferred.unwrap_or(username).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_speaker_label_prefers_nickname() {
+        assert_eq!(speaker_label(Some("Nick"), Some("Global"), "user", true), "Nick");
+        assert_eq!(speaker_label(None, Some("Global"), "user", true), "Global");
+        assert_eq!(speaker_label(None, None, "user", true), "user");
+    }
+
+    #[test]
+    fn test_speaker_label_without_nicknames() {
+        assert_eq!(speaker_label(Some("Nick"), Some("Global"), "user", false), "user");
+    }
+}
@@ -0,0 +1,107 @@
+//! Per-language threads that voice transcripts are posted to.
+//!
+//! `/voice transcript configure` opens one thread per language and stores
+//! its id. Anyone with Manage Threads can delete one, leaving a stale id, so
+//! a post that fails because the thread is gone opens a replacement and
+//! carries on there; `/voice transcript repair` does the same up front for
+//! every language.
+
+use crate::db::{DbPool, VoiceTranscriptRepo, VoiceTranscriptSettings};
+use crate::error::AppResult;
+use crate::translation::Language;
+use poise::serenity_prelude::{self as serenity, ChannelId, Http};
+use tracing::info;
+
+/// Discord's error code for a channel or thread that no longer exists
+const UNKNOWN_CHANNEL: isize = 10003;
+
+/// Open the transcript thread for `language` under `text_channel_id`, store
+/// its id and post its welcome message
+pub async fn create_thread(
+    http: &Http,
+    pool: &DbPool,
+    guild_id: &str,
+    voice_channel_id: &str,
+    text_channel_id: &str,
+    language: &str,
+) -> AppResult<ChannelId> {
+    let lang_name = Language::from_code(language)
+        .map(|l| l.name())
+        .unwrap_or(language);
+    let text_channel = text_channel_id
+        .parse()
+        .map(ChannelId::new)
+        .map_err(|_| crate::error::AppError::internal("Invalid transcript text channel id"))?;
+
+    let builder = serenity::CreateThread::new(format!("Voice Translation - {}", lang_name))
+        .kind(serenity::ChannelType::PublicThread)
+        .auto_archive_duration(serenity::AutoArchiveDuration::OneDay);
+    let thread = text_channel.create_thread(http, builder).await?;
+
+    VoiceTranscriptRepo::set_thread_id(pool, guild_id, voice_channel_id, language, &thread.id.to_string())
+        .await?;
+
+    let welcome = format!(
+        "This thread will receive real-time voice transcripts translated to **{}**.\n\nTranscripts from <#{}> will appear here.",
+        lang_name, voice_channel_id
+    );
+    // The thread works without its welcome message
+    let _ = thread
+        .id
+        .send_message(http, serenity::CreateMessage::new().content(welcome))
+        .await;
+    Ok(thread.id)
+}
+
+/// Whether `error` means the channel or thread was deleted
+pub fn is_unknown_channel(error: &serenity::Error) -> bool {
+    match error {
+        serenity::Error::Http(serenity::HttpError::UnsuccessfulRequest(response)) => {
+            response.error.code == UNKNOWN_CHANNEL
+                || response.status_code == serenity::StatusCode::NOT_FOUND
+        }
+        _ => false,
+    }
+}
+
+/// Recreate the threads of every transcript language whose thread is missing
+/// or deleted, returning the languages and their new threads
+pub async fn repair(
+    http: &Http,
+    pool: &DbPool,
+    settings: &VoiceTranscriptSettings,
+) -> AppResult<Vec<(String, ChannelId)>> {
+    let thread_ids = settings.get_thread_ids();
+    let mut recreated = Vec::new();
+    for language in settings.get_languages() {
+        let existing = thread_ids
+            .get(&language)
+            .and_then(|id| id.parse().ok())
+            .map(ChannelId::new);
+        if let Some(thread) = existing {
+            match thread.to_channel(http).await {
+                Ok(_) => continue,
+                Err(e) if is_unknown_channel(&e) => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        let thread = create_thread(
+            http,
+            pool,
+            &settings.guild_id,
+            &settings.voice_channel_id,
+            &settings.text_channel_id,
+            &language,
+        )
+        .await?;
+        info!(
+            guild_id = %settings.guild_id,
+            language = %language,
+            thread_id = %thread,
+            "Recreated transcript thread"
+        );
+        recreated.push((language, thread));
+    }
+    Ok(recreated)
+}
@@ -0,0 +1,601 @@
+//! Joining voice channels and restoring them after gateway reconnects.
+//!
+//! The bot remembers which channel it translates in per guild (the `active`
+//! flag in `voice_channel_settings`). After a `Ready` or `Resume` the stored
+//! and in-memory sessions are compared with what Songbird is actually
+//! connected to, and any dropped channel is joined again.
+//!
+//! Sessions are also capped in length (`voice.max_session_secs`, or the
+//! guild's own limit): once one runs past it the bot sends what it has
+//! buffered, says why in the channel, and leaves as if `/voice leave` was
+//! used.
+
+use crate::config::AppConfig;
+use crate::db::{DbPool, GuildRepo, NewVoiceChannelSettings, TtsMode, VoiceChannelRepo, VoiceTranscriptRepo};
+use crate::error::AppResult;
+use crate::translation::Formality;
+use crate::voice::{VoiceManager, VoiceReceiveHandler};
+use crate::web::broadcast::BroadcastManager;
+use poise::serenity_prelude as serenity;
+use songbird::error::{ConnectionError, JoinError};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+
+/// How often sessions are checked against their length limit
+const SESSION_LIMIT_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+type Error = Box<dyn std::error::Error + Send + Sync>;
+
+/// A voice join that failed for a reason the user can fix
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum JoinFailure {
+    #[error("I lack the {} permission in <#{channel}>", .missing.join(" and "))]
+    MissingPermissions {
+        channel: u64,
+        missing: Vec<&'static str>,
+    },
+    #[error("<#{channel}> is full ({limit} members)")]
+    ChannelFull { channel: u64, limit: u32 },
+    #[error("Timed out connecting to Discord's voice server")]
+    TimedOut,
+}
+
+impl JoinFailure {
+    /// What the user can do about it
+    pub fn fix(&self) -> String {
+        match self {
+            Self::MissingPermissions { missing, .. } => format!(
+                "Ask a server admin to allow me **{}** in that channel (Edit Channel → Permissions), then run `/voice join` again.",
+                missing.join("**, **")
+            ),
+            Self::ChannelFull { .. } => "Wait for a spot to free up, raise the channel's user limit, or give me **Move Members** so I can join full channels.".to_string(),
+            Self::TimedOut => "Discord's voice server for this channel's region didn't answer. Try again in a moment, or set the channel's Region Override to a nearby region.".to_string(),
+        }
+    }
+}
+
+/// Voice permissions the bot needs in a channel that `permissions` lacks
+fn missing_voice_permissions(permissions: serenity::Permissions) -> Vec<&'static str> {
+    [
+        (serenity::Permissions::VIEW_CHANNEL, "View Channel"),
+        (serenity::Permissions::CONNECT, "Connect"),
+        (serenity::Permissions::SPEAK, "Speak"),
+    ]
+    .into_iter()
+    .filter(|(needed, _)| !permissions.contains(*needed))
+    .map(|(_, name)| name)
+    .collect()
+}
+
+/// Whether a channel with `user_limit` (0 for none) has no room for the bot.
+/// Members with Move Members can join full channels anyway.
+fn channel_is_full(user_limit: u32, occupants: usize, can_move_members: bool) -> bool {
+    user_limit > 0 && occupants >= user_limit as usize && !can_move_members
+}
+
+/// Check from the cache that the bot can join `channel_id`, so the common
+/// failures get a clear reason instead of a Discord timeout. Anything not
+/// cached yet is left for Discord to decide.
+fn precheck_join(
+    ctx: &serenity::Context,
+    guild_id: serenity::GuildId,
+    channel_id: serenity::ChannelId,
+) -> Result<(), JoinFailure> {
+    let Some(guild) = ctx.cache.guild(guild_id) else {
+        return Ok(());
+    };
+    let bot_id = ctx.cache.current_user().id;
+    let (Some(channel), Some(member)) = (guild.channels.get(&channel_id), guild.members.get(&bot_id))
+    else {
+        return Ok(());
+    };
+
+    let permissions = guild.user_permissions_in(channel, member);
+    let missing = missing_voice_permissions(permissions);
+    if !missing.is_empty() {
+        return Err(JoinFailure::MissingPermissions {
+            channel: channel_id.get(),
+            missing,
+        });
+    }
+
+    let limit = channel.user_limit.unwrap_or(0);
+    let occupants = guild
+        .voice_states
+        .values()
+        .filter(|state| state.channel_id == Some(channel_id) && state.user_id != bot_id)
+        .count();
+    if channel_is_full(limit, occupants, permissions.move_members()) {
+        return Err(JoinFailure::ChannelFull {
+            channel: channel_id.get(),
+            limit,
+        });
+    }
+    Ok(())
+}
+
+/// Join `channel_id` and start translating it. Returns the channel's fixed
+/// spoken language, or None when it is auto-detected.
+pub async fn connect(
+    ctx: &serenity::Context,
+    pool: &DbPool,
+    voice_manager: &VoiceManager,
+    guild_id: serenity::GuildId,
+    channel_id: serenity::ChannelId,
+) -> Result<Option<String>, Error> {
+    let manager = songbird::get(ctx)
+        .await
+        .ok_or("Voice client not initialized")?;
+
+    // Refuse before touching Discord so a full instance doesn't join and leave
+    voice_manager.check_capacity(guild_id.get())?;
+    precheck_join(ctx, guild_id, channel_id)?;
+
+    // Drop any stale call first so its event handlers aren't registered twice
+    if manager.get(guild_id).is_some() {
+        manager.remove(guild_id).await?;
+    }
+
+    let call = match manager.join(guild_id, channel_id).await {
+        Ok(call) => call,
+        Err(e) => {
+            error!(error = %e, "Failed to join voice channel");
+            // Songbird asks for a leave before retrying a failed connection
+            if e.should_leave_server() {
+                let _ = manager.remove(guild_id).await;
+            }
+            return Err(match e {
+                JoinError::TimedOut | JoinError::Driver(ConnectionError::TimedOut) => {
+                    JoinFailure::TimedOut.into()
+                }
+                e => format!("Failed to join voice channel: {}", e).into(),
+            });
+        }
+    };
+
+    // Handlers are per guild; one left over from another channel must not be reused
+    if voice_manager
+        .handler(guild_id.get())
+        .is_some_and(|h| h.channel_id() != channel_id.get())
+    {
+        voice_manager.remove_handler(guild_id.get());
+    }
+    let handler = match voice_manager.get_or_create_handler(guild_id.get(), channel_id.get()) {
+        Ok(handler) => handler,
+        Err(full) => {
+            // Another guild took the last slot while we were joining
+            manager.remove(guild_id).await?;
+            return Err(full.into());
+        }
+    };
+    let spoken_language = channel_source_language(pool, guild_id.get(), channel_id.get()).await;
+    handler
+        .set_source_language(spoken_language.as_deref().map(Into::into))
+        .await;
+    handler
+        .set_formality(channel_formality(pool, guild_id.get(), channel_id.get()).await)
+        .await;
+    sync_transcript_audio(pool, &handler, guild_id.get(), channel_id.get()).await;
+
+    // Play TTS the bridge queues for this guild until the bot leaves
+    voice_manager.start_playback(guild_id.get(), call.clone());
+
+    {
+        let mut call_lock = call.lock().await;
+
+        // Songbird takes ownership of each event handler
+        call_lock.add_global_event(
+            songbird::CoreEvent::SpeakingStateUpdate.into(),
+            (*handler).clone(),
+        );
+        call_lock.add_global_event(songbird::CoreEvent::VoiceTick.into(), (*handler).clone());
+        call_lock.add_global_event(
+            songbird::CoreEvent::ClientDisconnect.into(),
+            (*handler).clone(),
+        );
+    }
+
+    let (guild, channel) = (guild_id.to_string(), channel_id.to_string());
+    let remembered = match ensure_channel_settings(pool, &guild, &channel).await {
+        Ok(()) => VoiceChannelRepo::set_active_channel(pool, &guild, Some(&channel)).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = remembered {
+        warn!(error = %e, "Failed to remember active voice channel");
+    }
+
+    info!(
+        guild_id = guild_id.get(),
+        channel_id = channel_id.get(),
+        "Joined voice channel for translation"
+    );
+    Ok(spoken_language)
+}
+
+/// Leave the guild's voice channel and forget it, so it isn't rejoined.
+pub async fn disconnect(
+    ctx: &serenity::Context,
+    pool: &DbPool,
+    voice_manager: Option<&VoiceManager>,
+    broadcast: &BroadcastManager,
+    guild_id: serenity::GuildId,
+) -> Result<(), Error> {
+    let manager = songbird::get(ctx)
+        .await
+        .ok_or("Voice client not initialized")?;
+
+    let Some(call) = manager.get(guild_id) else {
+        return Err("Not in a voice channel".into());
+    };
+    let channel = call.lock().await.current_channel();
+
+    manager.remove(guild_id).await?;
+    if let Some(voice_manager) = voice_manager {
+        voice_manager.remove_handler(guild_id.get());
+    }
+    VoiceChannelRepo::set_active_channel(pool, &guild_id.to_string(), None).await?;
+    if let Some(channel) = channel {
+        broadcast.forget_voice_channel(&guild_id.to_string(), &channel.0.to_string());
+    }
+
+    info!(guild_id = guild_id.get(), "Left voice channel");
+    Ok(())
+}
+
+/// Rejoin every channel the bot should be translating in but whose voice
+/// connection is gone.
+pub async fn reconcile(ctx: &serenity::Context, pool: &DbPool, voice_manager: &VoiceManager) {
+    let Some(manager) = songbird::get(ctx).await else {
+        return;
+    };
+
+    let stored = match VoiceChannelRepo::get_active(pool).await {
+        Ok(settings) => settings
+            .iter()
+            .filter_map(|s| Some((s.guild_id.parse().ok()?, s.voice_channel_id.parse().ok()?)))
+            .collect(),
+        Err(e) => {
+            error!(error = %e, "Failed to load active voice channels");
+            Vec::new()
+        }
+    };
+
+    for (guild_id, channel_id) in sessions_to_restore(&stored, &voice_manager.active_channels()) {
+        let guild = serenity::GuildId::new(guild_id);
+        let connected = match manager.get(guild) {
+            Some(call) => {
+                let call = call.lock().await;
+                call.current_connection().is_some()
+                    && call.current_channel().map(|c| c.0.get()) == Some(channel_id)
+            }
+            None => false,
+        };
+        if connected {
+            continue;
+        }
+
+        info!(guild_id, channel_id, "Restoring voice session after reconnect");
+        if let Err(e) = connect(
+            ctx,
+            pool,
+            voice_manager,
+            guild,
+            serenity::ChannelId::new(channel_id),
+        )
+        .await
+        {
+            warn!(guild_id, channel_id, error = %e, "Failed to restore voice session");
+        }
+    }
+}
+
+/// Longest a guild's voice session may run, or None for no limit. The
+/// guild's own limit wins over `default_secs`; zero means no limit.
+pub fn session_limit(default_secs: u64, guild_secs: Option<i64>) -> Option<Duration> {
+    let secs = guild_secs.map_or(default_secs, |secs| secs.max(0) as u64);
+    (secs > 0).then(|| Duration::from_secs(secs))
+}
+
+/// "4 hours", "90 minutes"
+pub fn describe_session_limit(limit: Duration) -> String {
+    let minutes = limit.as_secs() / 60;
+    match minutes {
+        m if m > 0 && m % 60 == 0 => format!("{} hour{}", m / 60, if m == 60 { "" } else { "s" }),
+        1 => "1 minute".to_string(),
+        m => format!("{} minutes", m),
+    }
+}
+
+/// Guilds whose session has run for at least its limit
+fn sessions_over_limit(
+    sessions: &[(u64, Duration)],
+    limit_for: impl Fn(u64) -> Option<Duration>,
+) -> Vec<(u64, Duration)> {
+    sessions
+        .iter()
+        .filter_map(|&(guild_id, age)| {
+            let limit = limit_for(guild_id)?;
+            (age >= limit).then_some((guild_id, limit))
+        })
+        .collect()
+}
+
+/// Leave every voice channel whose session has outlasted its limit, after
+/// sending its buffered speech and saying why in the channel.
+pub async fn end_overlong_sessions(
+    ctx: &serenity::Context,
+    pool: &DbPool,
+    voice_manager: &VoiceManager,
+    broadcast: &BroadcastManager,
+) {
+    let default_secs = AppConfig::get().voice.max_session_secs;
+    let mut sessions = Vec::new();
+    let mut limits = HashMap::new();
+    for (guild_id, _) in voice_manager.active_channels() {
+        let Some(handler) = voice_manager.handler(guild_id) else {
+            continue;
+        };
+        let guild_secs = match GuildRepo::get_settings(pool, &guild_id.to_string()).await {
+            Ok(settings) => settings.and_then(|s| s.max_voice_session_secs),
+            Err(e) => {
+                warn!(guild_id, error = %e, "Failed to load voice session limit, using the default");
+                None
+            }
+        };
+        limits.insert(guild_id, session_limit(default_secs, guild_secs));
+        sessions.push((guild_id, handler.session_age()));
+    }
+
+    for (guild_id, limit) in sessions_over_limit(&sessions, |guild_id| limits.get(&guild_id).copied().flatten()) {
+        let Some(handler) = voice_manager.handler(guild_id) else {
+            continue;
+        };
+        info!(guild_id, limit_secs = limit.as_secs(), "Voice session reached its length limit, leaving");
+        handler.flush().await;
+
+        let guild = serenity::GuildId::new(guild_id);
+        if let Err(e) = disconnect(ctx, pool, Some(voice_manager), broadcast, guild).await {
+            warn!(guild_id, error = %e, "Failed to leave voice channel at its session limit");
+            continue;
+        }
+        let notice = serenity::CreateMessage::new().embed(
+            serenity::CreateEmbed::default()
+                .title("Voice Translation Stopped")
+                .description(format!(
+                    "Left after {}, this server's voice session limit. Use `/voice join` to start again.",
+                    describe_session_limit(limit)
+                ))
+                .color(0xED4245),
+        );
+        // Voice channels have their own text chat
+        if let Err(e) = serenity::ChannelId::new(handler.channel_id()).send_message(ctx, notice).await {
+            warn!(guild_id, error = %e, "Failed to announce leaving at the session limit");
+        }
+    }
+}
+
+/// Check voice sessions against their length limit every minute
+pub fn spawn_session_limit_monitor(
+    ctx: serenity::Context,
+    pool: DbPool,
+    voice_manager: Arc<VoiceManager>,
+    broadcast: Arc<BroadcastManager>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(SESSION_LIMIT_CHECK_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            end_overlong_sessions(&ctx, &pool, &voice_manager, &broadcast).await;
+        }
+    })
+}
+
+/// TTS mode used when a voice channel has no settings of its own
+pub fn default_tts_mode() -> TtsMode {
+    if AppConfig::get().voice.enable_tts_playback {
+        TtsMode::Both
+    } else {
+        TtsMode::WebOnly
+    }
+}
+
+/// Create default settings for a voice channel that has none yet
+pub async fn ensure_channel_settings(
+    pool: &DbPool,
+    guild_id: &str,
+    voice_channel_id: &str,
+) -> AppResult<()> {
+    if VoiceChannelRepo::get_settings(pool, guild_id, voice_channel_id)
+        .await?
+        .is_none()
+    {
+        VoiceChannelRepo::upsert(
+            pool,
+            NewVoiceChannelSettings {
+                guild_id: guild_id.to_string(),
+                voice_channel_id: voice_channel_id.to_string(),
+                target_language: AppConfig::get().voice.default_target_language.clone(),
+                tts_mode: default_tts_mode(),
+            },
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+/// Request TTS with each segment while the transcript thread for the
+/// handler's target language posts audio clips, unless the channel limits
+/// TTS to other languages
+pub async fn sync_transcript_audio(
+    pool: &DbPool,
+    handler: &VoiceReceiveHandler,
+    guild_id: u64,
+    channel_id: u64,
+) {
+    let (guild, channel) = (guild_id.to_string(), channel_id.to_string());
+    let settings = VoiceTranscriptRepo::get_settings(pool, &guild, &channel)
+        .await
+        .ok()
+        .flatten();
+    let channel_settings = VoiceChannelRepo::get_settings(pool, &guild, &channel)
+        .await
+        .ok()
+        .flatten();
+    let target_language = handler.state().read().await.target_language.clone();
+    let wants_audio = settings.is_some_and(|s| s.enabled && s.tts_enabled_for(&target_language))
+        && channel_settings.is_none_or(|s| s.tts_enabled_for(&target_language));
+    handler.set_tts_enabled(wants_audio).await;
+}
+
+/// Fixed spoken language for a voice channel, or None to auto-detect
+pub async fn channel_source_language(
+    pool: &DbPool,
+    guild_id: u64,
+    channel_id: u64,
+) -> Option<String> {
+    VoiceChannelRepo::get_settings(pool, &guild_id.to_string(), &channel_id.to_string())
+        .await
+        .ok()
+        .flatten()
+        .and_then(|settings| settings.fixed_source_language().map(str::to_string))
+}
+
+/// Formality of spoken translations in a voice channel
+pub async fn channel_formality(pool: &DbPool, guild_id: u64, channel_id: u64) -> Formality {
+    VoiceChannelRepo::get_settings(pool, &guild_id.to_string(), &channel_id.to_string())
+        .await
+        .ok()
+        .flatten()
+        .map(|settings| Formality::from(settings.formality.as_str()))
+        .unwrap_or_default()
+}
+
+/// One `(guild, channel)` per guild from stored and in-memory sessions. The
+/// in-memory handler wins, since it reflects the latest join.
+fn sessions_to_restore(stored: &[(u64, u64)], in_memory: &[(u64, u64)]) -> Vec<(u64, u64)> {
+    let mut sessions: HashMap<u64, u64> = stored.iter().copied().collect();
+    sessions.extend(in_memory.iter().copied());
+    let mut sessions: Vec<_> = sessions.into_iter().collect();
+    sessions.sort_unstable();
+    sessions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_voice_permissions_names_each_gap() {
+        use poise::serenity_prelude::Permissions;
+        let all = Permissions::VIEW_CHANNEL | Permissions::CONNECT | Permissions::SPEAK;
+        assert!(missing_voice_permissions(all).is_empty());
+        assert_eq!(
+            missing_voice_permissions(Permissions::VIEW_CHANNEL | Permissions::SPEAK),
+            vec!["Connect"]
+        );
+        assert_eq!(
+            missing_voice_permissions(Permissions::empty()),
+            vec!["View Channel", "Connect", "Speak"]
+        );
+
+        let failure = JoinFailure::MissingPermissions {
+            channel: 5,
+            missing: vec!["Connect", "Speak"],
+        };
+        assert_eq!(failure.to_string(), "I lack the Connect and Speak permission in <#5>");
+        assert!(failure.fix().contains("**Connect**, **Speak**"));
+    }
+
+    #[test]
+    fn test_channel_is_full() {
+        assert!(!channel_is_full(0, 99, false));
+        assert!(!channel_is_full(5, 4, false));
+        assert!(channel_is_full(5, 5, false));
+        // Move Members lets the bot into full channels
+        assert!(!channel_is_full(5, 5, true));
+    }
+
+    #[test]
+    fn test_session_limit_prefers_guild_override() {
+        assert_eq!(session_limit(0, None), None);
+        assert_eq!(session_limit(14400, None), Some(Duration::from_secs(14400)));
+        assert_eq!(session_limit(14400, Some(600)), Some(Duration::from_secs(600)));
+        // A guild can lift the limit, or set one the default doesn't have
+        assert_eq!(session_limit(14400, Some(0)), None);
+        assert_eq!(session_limit(0, Some(3600)), Some(Duration::from_secs(3600)));
+
+        assert_eq!(describe_session_limit(Duration::from_secs(14400)), "4 hours");
+        assert_eq!(describe_session_limit(Duration::from_secs(3600)), "1 hour");
+        assert_eq!(describe_session_limit(Duration::from_secs(5400)), "90 minutes");
+    }
+
+    #[test]
+    fn test_sessions_past_their_limit_are_ended() {
+        let hours = |h: u64| Duration::from_secs(h * 3600);
+        let sessions = [(1, hours(5)), (2, hours(3)), (3, hours(50)), (4, hours(4))];
+        let limits = HashMap::from([(1, Some(hours(4))), (2, Some(hours(4))), (3, None), (4, Some(hours(4)))]);
+
+        let ended = sessions_over_limit(&sessions, |guild| limits[&guild]);
+        assert_eq!(ended, vec![(1, hours(4)), (4, hours(4))]);
+    }
+
+    #[test]
+    fn test_sessions_to_restore_prefers_in_memory_channel() {
+        let stored = [(1, 10), (2, 20)];
+        let in_memory = [(2, 21), (3, 30)];
+        assert_eq!(
+            sessions_to_restore(&stored, &in_memory),
+            vec![(1, 10), (2, 21), (3, 30)]
+        );
+        assert!(sessions_to_restore(&[], &[]).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_only_enabled_languages_request_tts() {
+        use crate::db::NewVoiceTranscriptSettings;
+        use crate::voice::{VoiceClientConfig, VoiceInferenceClient, VoiceTranscriptionCache};
+
+        let pool = crate::db::queries::setup_test_db().await;
+        VoiceChannelRepo::upsert(
+            &pool,
+            NewVoiceChannelSettings {
+                guild_id: "1".to_string(),
+                voice_channel_id: "2".to_string(),
+                target_language: "en".to_string(),
+                tts_mode: TtsMode::Voice,
+            },
+        )
+        .await
+        .unwrap();
+        let languages = ["en", "es", "ja"].map(String::from).to_vec();
+        VoiceTranscriptRepo::upsert(
+            &pool,
+            NewVoiceTranscriptSettings {
+                guild_id: "1".to_string(),
+                voice_channel_id: "2".to_string(),
+                text_channel_id: "3".to_string(),
+                languages: languages.clone(),
+            },
+        )
+        .await
+        .unwrap();
+        for lang in &languages {
+            VoiceTranscriptRepo::set_language_tts(&pool, "1", "2", lang, true).await.unwrap();
+        }
+        VoiceChannelRepo::set_tts_languages(&pool, "1", "2", &["es".to_string(), "ja".to_string()])
+            .await
+            .unwrap();
+
+        let client = Arc::new(VoiceInferenceClient::new(VoiceClientConfig::default()));
+        let handler = VoiceReceiveHandler::new(1, 2, client, Arc::new(VoiceTranscriptionCache::new(10)));
+        for (lang, generate_tts) in [("en", false), ("es", true), ("ja", true)] {
+            handler.update_settings(Arc::from(lang), !generate_tts).await;
+            sync_transcript_audio(&pool, &handler, 1, 2).await;
+            assert_eq!(handler.state().read().await.tts_enabled, generate_tts, "{lang}");
+        }
+    }
+}
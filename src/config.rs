@@ -1,24 +1,59 @@
-use config::{Config, ConfigError, Environment, File};
-use serde::Deserialize;
+//! Typed application configuration.
+//!
+//! Every setting has a default, so a deployment only sets what it changes.
+//! Sources are layered, later ones overriding earlier ones:
+//!
+//! 1. `config/default.toml`, embedded in the binary at build time
+//! 2. `config/default.toml` on disk, if present
+//! 3. `config/local.toml`, if present
+//! 4. `LINGUABRIDGE_`-prefixed environment variables, with `__` between
+//!    section and key: `LINGUABRIDGE_WEB__PORT=8080` sets `web.port`,
+//!    `LINGUABRIDGE_VOICE__TTS_URL=http://tts:8000` sets `voice.tts_url`
+//!
+//! [`AppConfig::print_effective`] logs the result at boot.
+
+use config::{Config, ConfigError, Environment, File, FileFormat};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::OnceLock;
 
 static CONFIG: OnceLock<AppConfig> = OnceLock::new();
 
+/// The default configuration, also used when no config file is deployed
+pub const DEFAULT_CONFIG_TOML: &str = include_str!("../config/default.toml");
+
+/// Prefix of environment variables that override config keys
+pub const ENV_PREFIX: &str = "LINGUABRIDGE";
+
 /// Admin transport configuration (for secure provisioning)
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
 pub struct AdminConfig {
     /// Admin's Ed25519 public key (base64 encoded)
     /// This is the ONLY trust anchor - only the holder of the corresponding
-    /// private key can provision secrets to this bot.
+    /// private key can provision secrets to this bot. Empty refuses to start.
     pub public_key: String,
     /// Port for admin provisioning endpoint
-    #[serde(default = "default_admin_port")]
     pub port: u16,
     /// Host for admin provisioning endpoint
-    #[serde(default = "default_admin_host")]
     pub host: String,
+    /// Log each step of a provision request (lengths and outcomes only,
+    /// never key material or secrets)
+    pub debug_provisioning: bool,
+    /// Path the admin endpoints are served under (e.g. `/bot-admin` for
+    /// `/bot-admin/pubkey`), so they can share a reverse proxy with the web
+    /// server; empty serves them at the root
+    pub path_prefix: String,
 }
 
+/// First path segments the web server routes (`web::create_router` and the
+/// admin cache routes); an admin prefix starting with one would shadow them
+/// behind a shared reverse proxy
+const WEB_PATH_ROOTS: &[&str] = &[
+    "admin", "api", "guild", "health", "healthz", "metrics", "readyz", "static", "text", "view",
+    "voice", "ws",
+];
+
 fn default_admin_port() -> u16 {
     9999
 }
@@ -27,74 +62,322 @@ fn default_admin_host() -> String {
     "0.0.0.0".to_string()
 }
 
+impl Default for AdminConfig {
+    fn default() -> Self {
+        Self {
+            public_key: String::new(),
+            port: default_admin_port(),
+            host: default_admin_host(),
+            debug_provisioning: false,
+            path_prefix: String::new(),
+        }
+    }
+}
+
+impl AdminConfig {
+    /// `path_prefix` with one leading slash and no trailing one (`admin/`
+    /// becomes `/admin`), or empty for the root
+    pub fn mount_path(&self) -> String {
+        let trimmed = self.path_prefix.trim().trim_matches('/');
+        if trimmed.is_empty() {
+            String::new()
+        } else {
+            format!("/{}", trimmed)
+        }
+    }
+
+    /// Check that `path_prefix` doesn't start with a path the web server
+    /// routes, such as `/admin` (its cache endpoints) or `/voice`
+    pub fn validate_path_prefix(&self) -> Result<(), String> {
+        let mount = self.mount_path();
+        let first = mount.trim_start_matches('/').split('/').next().unwrap_or_default();
+        if WEB_PATH_ROOTS.iter().any(|root| root.eq_ignore_ascii_case(first)) {
+            return Err(format!(
+                "admin.path_prefix '{}' overlaps the web server's /{} routes; use another path, e.g. /bot-admin",
+                self.path_prefix, first
+            ));
+        }
+        Ok(())
+    }
+}
+
 /// Discord bot configuration (non-sensitive parts only)
 /// The token is now provided via secure admin provisioning.
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
 pub struct DiscordConfig {
     /// Application ID (optional, for OAuth flows)
-    #[serde(default)]
     pub application_id: Option<String>,
+    /// Text command prefix for guilds that haven't set their own
+    pub command_prefix: String,
+    /// Translate direct messages to the bot into the sender's `/mylang`
+    /// language (requests the direct messages gateway intent)
+    pub allow_direct_messages: bool,
+    /// Greet each server that adds the bot, once, with a pointer to
+    /// `/setup init` (in its system channel, else by DM to the owner)
+    pub onboarding_message: bool,
+    /// Documentation linked from the onboarding message
+    pub docs_url: String,
+}
+
+fn default_command_prefix() -> String {
+    "!lb ".to_string()
+}
+
+fn default_docs_url() -> String {
+    "https://github.com/permissionlessweb/lingua-bridge#readme".to_string()
+}
+
+impl Default for DiscordConfig {
+    fn default() -> Self {
+        Self {
+            application_id: None,
+            command_prefix: default_command_prefix(),
+            allow_direct_messages: false,
+            onboarding_message: true,
+            docs_url: default_docs_url(),
+        }
+    }
 }
 
 /// Inference service configuration
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
 pub struct InferenceConfig {
+    /// Base URL of the Python inference sidecar
     pub url: String,
+    /// TranslateGemma model to use
     pub model: String,
     pub timeout_secs: u64,
     pub max_retries: u32,
+    /// Longest `Retry-After` back-off a request waits out before failing
+    pub max_retry_after_secs: u64,
+    /// Refuse to start without a provisioned Hugging Face token instead of
+    /// running with the features that need it disabled
+    pub require_hf_token: bool,
+    /// How long a translation waits for an inference service that hasn't
+    /// come up since boot before failing with "still starting"
+    pub startup_wait_secs: u64,
+    /// Seconds between inference health checks once the service is up
+    pub health_check_interval_secs: u64,
+}
+
+impl Default for InferenceConfig {
+    fn default() -> Self {
+        Self {
+            url: "http://localhost:8000".to_string(),
+            model: "google/translategemma-4b-it".to_string(),
+            timeout_secs: 30,
+            max_retries: 3,
+            max_retry_after_secs: 30,
+            require_hf_token: false,
+            startup_wait_secs: 10,
+            health_check_interval_secs: default_health_check_interval_secs(),
+        }
+    }
 }
 
 /// Web server configuration
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
 pub struct WebConfig {
     pub host: String,
     pub port: u16,
     pub session_expiry_hours: u64,
+    /// Public URL for generating links
     pub public_url: String,
+    /// Most requests in flight plus open WebSocket viewers each; excess
+    /// ones get a 503 (0 for no limit)
+    pub max_connections: usize,
+    /// Most WebSocket viewers from one address (0 for no limit; use 0
+    /// behind a reverse proxy, where every viewer shares its address)
+    pub max_ws_connections_per_ip: usize,
+}
+
+impl Default for WebConfig {
+    fn default() -> Self {
+        Self {
+            host: "0.0.0.0".to_string(),
+            port: 3000,
+            session_expiry_hours: 24,
+            public_url: "http://localhost:3000".to_string(),
+            max_connections: 1024,
+            max_ws_connections_per_ip: 16,
+        }
+    }
 }
 
 /// Database configuration
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
 pub struct DatabaseConfig {
     pub url: String,
     pub max_connections: u32,
+    /// Seconds between `SELECT 1` probes of the pool
+    pub health_check_interval_secs: u64,
+    /// Seconds to wait for a connection before a query fails as unavailable
+    pub acquire_timeout_secs: u64,
+}
+
+fn default_health_check_interval_secs() -> u64 {
+    30
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            url: "sqlite://linguabridge.db?mode=rwc".to_string(),
+            max_connections: 10,
+            health_check_interval_secs: default_health_check_interval_secs(),
+            acquire_timeout_secs: 5,
+        }
+    }
 }
 
 /// Translation settings
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
 pub struct TranslationConfig {
     pub default_languages: Vec<String>,
+    /// Longest message (in characters) auto-translated for free guilds
     pub max_message_length: usize,
+    /// Longest message auto-translated for paid guilds
+    pub paid_max_message_length: usize,
+    /// Base URL of a second inference service to compare translations
+    /// against; unset disables shadowing
+    pub shadow_provider: Option<String>,
+    /// Fraction of translations (0.0–1.0) also sent to `shadow_provider`
+    pub shadow_sample_rate: f64,
     pub cache_ttl_secs: u64,
     pub cache_max_size: usize,
+    /// Save the translation cache to the database on shutdown and load it
+    /// back at startup
+    pub persist_cache: bool,
+    /// Longest the shutdown save may take before it is abandoned
+    pub cache_flush_timeout_secs: u64,
+}
+
+impl Default for TranslationConfig {
+    fn default() -> Self {
+        Self {
+            default_languages: vec!["en".to_string()],
+            max_message_length: 2000,
+            paid_max_message_length: 4000,
+            shadow_provider: None,
+            shadow_sample_rate: 0.1,
+            cache_ttl_secs: 3600,
+            cache_max_size: 10000,
+            persist_cache: true,
+            cache_flush_timeout_secs: 10,
+        }
+    }
 }
 
 /// Rate limiting settings
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
 pub struct RateLimitsConfig {
+    /// Auto-translated messages per minute per guild (free tier)
     pub free_messages_per_minute: u32,
+    /// Auto-translated messages per minute per guild (paid tiers)
     pub paid_messages_per_minute: u32,
+    /// Per-user cooldown in seconds, keyed by qualified command name (e.g. "voice join")
+    pub command_cooldowns: HashMap<String, u64>,
+}
+
+fn default_command_cooldowns() -> HashMap<String, u64> {
+    HashMap::from([
+        ("translate".to_string(), 3),
+        ("voice join".to_string(), 15),
+        ("voice leave".to_string(), 5),
+    ])
+}
+
+impl Default for RateLimitsConfig {
+    fn default() -> Self {
+        Self {
+            free_messages_per_minute: 10,
+            paid_messages_per_minute: 100,
+            command_cooldowns: default_command_cooldowns(),
+        }
+    }
 }
 
 /// Voice translation settings
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
 pub struct VoiceConfig {
     /// WebSocket URL for voice inference service
-    #[serde(default = "default_voice_url")]
     pub url: String,
-    /// Enable TTS playback in Discord
-    #[serde(default)]
+    /// HTTP URL of a separate TTS service (its `POST /tts`). When unset the
+    /// voice inference service synthesises speech along with each result.
+    pub tts_url: Option<String>,
+    /// Default TTS target for channels without settings: voice and web when
+    /// true, web only when false (per-channel `tts_mode` overrides this)
     pub enable_tts_playback: bool,
     /// Audio buffer size in milliseconds
-    #[serde(default = "default_buffer_ms")]
     pub buffer_ms: u32,
     /// VAD sensitivity threshold (0.0-1.0)
-    #[serde(default = "default_vad_threshold")]
     pub vad_threshold: f32,
     /// Default target language for voice translations
-    #[serde(default = "default_voice_target_lang")]
     pub default_target_language: String,
+    /// Transcriptions below this confidence (0.0-1.0) are flagged in the web view
+    pub low_confidence_threshold: f32,
+    /// Transcriptions below this confidence are dropped entirely (disabled if unset)
+    pub min_confidence: Option<f32>,
+    /// Maximum audio segments awaiting a result from the inference service
+    pub max_in_flight: usize,
+    /// Audio kept from just before speech is detected and prepended to each
+    /// utterance, in milliseconds (0 disables)
+    pub pre_roll_ms: u64,
+    /// Silence kept on the end of each finished utterance, in milliseconds
+    pub trailing_silence_ms: u64,
+    /// Audio repeated from the end of each streaming chunk at the start of
+    /// the next, in milliseconds (0 disables)
+    pub chunk_overlap_ms: u64,
+    /// Drop keyboard clicks and fan noise before voice activity detection
+    pub noise_gate: bool,
+    /// Zero-crossing rate (0.0-1.0) above which the noise gate treats a
+    /// frame as broadband noise
+    pub noise_gate_max_zero_crossing_rate: f32,
+    /// Peak-to-RMS ratio above which the noise gate treats a frame as a click
+    pub noise_gate_max_crest_factor: f32,
+    /// Segments whose overall RMS (0.0-1.0 of full scale) is below this are
+    /// not sent for transcription (0 disables)
+    pub min_segment_rms: f32,
+    /// Leave a voice channel after this many seconds in it, so a forgotten
+    /// session doesn't run for days (0 for no limit; `/voice timelimit`
+    /// overrides it per server)
+    pub max_session_secs: u64,
+    /// Rejoin the voice channels the bot was translating in after a gateway
+    /// reconnect or restart
+    pub rejoin_on_reconnect: bool,
+    /// Most guilds to translate voice in at once; further joins are refused
+    /// (0 for no limit)
+    pub max_concurrent_guilds: usize,
+    /// How far voice segments are taken: transcribe_only,
+    /// transcribe_and_translate or transcribe_translate_tts (TTS still only
+    /// where a channel enables it)
+    pub pipeline: String,
+    /// TTS encoding to request from the inference service: wav, pcm, opus
+    /// or mp3. Whatever the service actually returns is decoded.
+    pub tts_format: String,
+    /// Largest voice WebSocket message in bytes; longer utterances are sent
+    /// in pieces. Keep it within the inference service's `WS_MAX_SIZE`.
+    pub max_frame_size: usize,
+    /// Log each segment's frame size, sample count and round-trip time at
+    /// trace level, keyed by audio hash (never the audio itself)
+    pub profile_payloads: bool,
+    /// How TTS queued faster than it can be spoken is played: sequential,
+    /// drop_stale or overlap
+    pub tts_playback: String,
+    /// Under drop_stale, skip TTS that waited longer than this many seconds
+    pub tts_stale_after_secs: u64,
+    /// Under overlap, gain applied to each clip before mixing (0.0-1.0)
+    pub tts_overlap_gain: f32,
+    /// Save the voice transcription cache to the database on shutdown and
+    /// load it back at startup, within `translation.cache_flush_timeout_secs`
+    pub persist_cache: bool,
 }
 
 fn default_voice_url() -> String {
@@ -117,21 +400,43 @@ impl Default for VoiceConfig {
     fn default() -> Self {
         Self {
             url: default_voice_url(),
+            tts_url: None,
             enable_tts_playback: false,
             buffer_ms: default_buffer_ms(),
             vad_threshold: default_vad_threshold(),
             default_target_language: default_voice_target_lang(),
+            low_confidence_threshold: 0.5,
+            min_confidence: None,
+            max_in_flight: 4,
+            pre_roll_ms: 200,
+            trailing_silence_ms: 300,
+            chunk_overlap_ms: 300,
+            noise_gate: false,
+            noise_gate_max_zero_crossing_rate: 0.25,
+            noise_gate_max_crest_factor: 6.0,
+            min_segment_rms: 0.005,
+            max_session_secs: 0,
+            rejoin_on_reconnect: true,
+            max_concurrent_guilds: 0,
+            pipeline: "transcribe_translate_tts".to_string(),
+            tts_format: "wav".to_string(),
+            max_frame_size: 16 * 1024 * 1024,
+            profile_payloads: false,
+            tts_playback: "sequential".to_string(),
+            tts_stale_after_secs: 10,
+            tts_overlap_gain: 0.6,
+            persist_cache: true,
         }
     }
 }
 
 /// Root application configuration
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+#[serde(default)]
 pub struct AppConfig {
-    /// Admin transport configuration (required)
+    /// Admin transport configuration (`public_key` is required)
     pub admin: AdminConfig,
     /// Discord configuration (non-sensitive)
-    #[serde(default)]
     pub discord: DiscordConfig,
     pub inference: InferenceConfig,
     pub web: WebConfig,
@@ -139,32 +444,31 @@ pub struct AppConfig {
     pub translation: TranslationConfig,
     pub rate_limits: RateLimitsConfig,
     /// Voice translation configuration
-    #[serde(default)]
     pub voice: VoiceConfig,
 }
 
-impl Default for DiscordConfig {
-    fn default() -> Self {
-        Self {
-            application_id: None,
-        }
-    }
-}
-
 impl AppConfig {
-    /// Load configuration from files and environment variables
+    /// Load configuration from the embedded defaults, config files and
+    /// environment variables
     pub fn load() -> Result<Self, ConfigError> {
+        Self::load_with_env(None)
+    }
+
+    /// Load as [`Self::load`] does, taking environment overrides from `env`
+    /// instead of the process environment when given
+    pub fn load_with_env(env: Option<HashMap<String, String>>) -> Result<Self, ConfigError> {
         let config = Config::builder()
-            // Start with default config file
+            .add_source(File::from_str(DEFAULT_CONFIG_TOML, FileFormat::Toml))
+            // Deployed config file, then local overrides
             .add_source(File::with_name("config/default").required(false))
-            // Override with local config if present
             .add_source(File::with_name("config/local").required(false))
-            // Override with environment variables (prefix: LINGUABRIDGE_)
             // e.g., LINGUABRIDGE_ADMIN__PUBLIC_KEY, LINGUABRIDGE_WEB__PORT
             .add_source(
-                Environment::with_prefix("LINGUABRIDGE")
+                Environment::with_prefix(ENV_PREFIX)
+                    .prefix_separator("_")
                     .separator("__")
-                    .try_parsing(true),
+                    .try_parsing(true)
+                    .source(env),
             )
             .build()?;
 
@@ -181,6 +485,76 @@ impl AppConfig {
     pub fn get() -> &'static Self {
         CONFIG.get().expect("Config not initialized. Call AppConfig::init() first.")
     }
+
+    /// The resolved configuration as TOML, with credentials in URLs and
+    /// secret-looking keys redacted
+    pub fn effective_toml(&self) -> String {
+        let mut value = toml::Value::try_from(self).expect("config serializes to TOML");
+        redact(&mut value);
+        toml::to_string_pretty(&value).expect("config serializes to TOML")
+    }
+
+    /// Log the resolved configuration (see [`Self::effective_toml`])
+    pub fn print_effective(&self) {
+        tracing::info!("Effective configuration:\n{}", self.effective_toml());
+    }
+}
+
+/// Keys whose values are never printed
+const SECRET_KEY_PARTS: &[&str] = &["token", "secret", "password"];
+
+const REDACTED: &str = "[redacted]";
+
+/// Blank out secret-looking keys and the credentials of URLs in `value`
+fn redact(value: &mut toml::Value) {
+    match value {
+        toml::Value::Table(table) => {
+            for (key, value) in table.iter_mut() {
+                let key = key.to_lowercase();
+                if SECRET_KEY_PARTS.iter().any(|part| key.contains(part)) {
+                    *value = toml::Value::String(REDACTED.to_string());
+                } else {
+                    redact(value);
+                }
+            }
+        }
+        toml::Value::Array(items) => items.iter_mut().for_each(redact),
+        toml::Value::String(s) => {
+            if let Ok(mut url) = reqwest::Url::parse(s) {
+                if url.password().is_some() {
+                    let _ = url.set_password(Some(REDACTED));
+                    *s = url.to_string();
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+impl AppConfig {
+    /// Check that each inference endpoint is a URL of the kind its client
+    /// speaks: HTTP for text translation and TTS, WebSocket for voice
+    pub fn validate_endpoints(&self) -> Result<(), String> {
+        check_url("inference.url", &self.inference.url, &["http", "https"])?;
+        check_url("voice.url", &self.voice.url, &["ws", "wss"])?;
+        if let Some(tts_url) = &self.voice.tts_url {
+            check_url("voice.tts_url", tts_url, &["http", "https"])?;
+        }
+        Ok(())
+    }
+}
+
+fn check_url(key: &str, url: &str, schemes: &[&str]) -> Result<(), String> {
+    let parsed = reqwest::Url::parse(url).map_err(|e| format!("Invalid {} '{}': {}", key, url, e))?;
+    if !schemes.contains(&parsed.scheme()) {
+        return Err(format!(
+            "Invalid {} '{}': expected a {} URL",
+            key,
+            url,
+            schemes.join(" or ")
+        ));
+    }
+    Ok(())
 }
 
 /// Helper to get inference URL with proper trailing slash handling
@@ -203,6 +577,10 @@ mod tests {
             model: "test".to_string(),
             timeout_secs: 30,
             max_retries: 3,
+            max_retry_after_secs: 30,
+            require_hf_token: false,
+            startup_wait_secs: 10,
+            health_check_interval_secs: 30,
         };
         assert_eq!(config.endpoint("/translate"), "http://localhost:8000/translate");
         assert_eq!(config.endpoint("translate"), "http://localhost:8000/translate");
@@ -215,6 +593,10 @@ mod tests {
             model: "test".to_string(),
             timeout_secs: 30,
             max_retries: 3,
+            max_retry_after_secs: 30,
+            require_hf_token: false,
+            startup_wait_secs: 10,
+            health_check_interval_secs: 30,
         };
         assert_eq!(config.endpoint("/translate"), "http://localhost:8000/translate");
     }
@@ -227,6 +609,60 @@ mod tests {
         assert_eq!(voice.buffer_ms, default_buffer_ms());
         assert_eq!(voice.vad_threshold, default_vad_threshold());
         assert_eq!(voice.default_target_language, default_voice_target_lang());
+        assert_eq!(voice.low_confidence_threshold, 0.5);
+        assert!(voice.min_confidence.is_none());
+    }
+
+    #[test]
+    fn test_validate_endpoints() {
+        let mut config = AppConfig::load().unwrap();
+        assert!(config.validate_endpoints().is_ok());
+
+        config.voice.tts_url = Some("http://tts:8000".to_string());
+        assert!(config.validate_endpoints().is_ok());
+
+        config.voice.tts_url = Some("ws://tts:8000".to_string());
+        let err = config.validate_endpoints().unwrap_err();
+        assert!(err.contains("voice.tts_url"));
+
+        config.voice.tts_url = None;
+        config.voice.url = "http://inference:8000/voice".to_string();
+        assert!(config.validate_endpoints().unwrap_err().contains("voice.url"));
+
+        config.voice.url = default_voice_url();
+        config.inference.url = "not a url".to_string();
+        assert!(config.validate_endpoints().unwrap_err().contains("inference.url"));
+    }
+
+    #[test]
+    fn test_admin_mount_path() {
+        let mount = |prefix: &str| AdminConfig {
+            path_prefix: prefix.to_string(),
+            ..AdminConfig::default()
+        }
+        .mount_path();
+        assert_eq!(mount(""), "");
+        assert_eq!(mount("/"), "");
+        assert_eq!(mount("admin"), "/admin");
+        assert_eq!(mount("/admin/"), "/admin");
+        assert_eq!(mount("/bots/admin"), "/bots/admin");
+    }
+
+    #[test]
+    fn test_admin_path_prefix_must_not_overlap_web_routes() {
+        let check = |prefix: &str| AdminConfig {
+            path_prefix: prefix.to_string(),
+            ..AdminConfig::default()
+        }
+        .validate_path_prefix();
+        assert!(check("").is_ok());
+        assert!(check("/bot-admin").is_ok());
+        assert!(check("/bots/admin").is_ok());
+        assert!(check("/administration").is_ok());
+        assert!(check("/admin").unwrap_err().contains("/admin routes"));
+        assert!(check("admin/").is_err());
+        assert!(check("/Voice/keys").is_err());
+        assert!(check("/static").is_err());
     }
 
     #[test]
@@ -243,6 +679,69 @@ mod tests {
         let config = config.unwrap();
         assert_eq!(config.admin.port, default_admin_port());
         assert_eq!(config.admin.host, default_admin_host());
+        assert_eq!(config.rate_limits.command_cooldowns.get("voice join"), Some(&15));
+    }
+
+    #[test]
+    fn test_missing_optional_fields_fall_back_to_defaults() {
+        let partial = "[web]\nport = 8080\n\n[voice]\nenable_tts_playback = true\n";
+        let config: AppConfig = Config::builder()
+            .add_source(File::from_str(partial, FileFormat::Toml))
+            .build()
+            .unwrap()
+            .try_deserialize()
+            .unwrap();
+
+        assert_eq!(config.web.port, 8080);
+        assert_eq!(config.web.public_url, WebConfig::default().public_url);
+        assert!(config.voice.enable_tts_playback);
+        assert_eq!(config.voice.default_target_language, default_voice_target_lang());
+        assert_eq!(config.database.max_connections, 10);
+        assert_eq!(config.admin.port, default_admin_port());
+    }
+
+    #[test]
+    fn test_embedded_defaults_match_code_defaults() {
+        let embedded: AppConfig = Config::builder()
+            .add_source(File::from_str(DEFAULT_CONFIG_TOML, FileFormat::Toml))
+            .build()
+            .unwrap()
+            .try_deserialize()
+            .unwrap();
+        assert_eq!(
+            toml::Value::try_from(&embedded).unwrap(),
+            toml::Value::try_from(AppConfig::default()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_env_overrides_apply() {
+        let env = HashMap::from([
+            ("LINGUABRIDGE_WEB__PORT".to_string(), "8080".to_string()),
+            ("LINGUABRIDGE_WEB__PUBLIC_URL".to_string(), "https://lb.example".to_string()),
+            ("LINGUABRIDGE_VOICE__ENABLE_TTS_PLAYBACK".to_string(), "true".to_string()),
+            ("LINGUABRIDGE_DATABASE__MAX_CONNECTIONS".to_string(), "2".to_string()),
+        ]);
+        let config = AppConfig::load_with_env(Some(env)).unwrap();
+
+        assert_eq!(config.web.port, 8080);
+        assert_eq!(config.web.public_url, "https://lb.example");
+        assert!(config.voice.enable_tts_playback);
+        assert_eq!(config.database.max_connections, 2);
+        // Untouched keys keep their defaults
+        assert_eq!(config.web.session_expiry_hours, 24);
+    }
+
+    #[test]
+    fn test_effective_toml_redacts_credentials() {
+        let mut config = AppConfig::default();
+        config.database.url = "postgres://bot:hunter2@db:5432/lb".to_string();
+        let effective = config.effective_toml();
+
+        assert!(!effective.contains("hunter2"));
+        assert!(effective.contains("postgres://bot:%5Bredacted%5D@db:5432/lb"));
+        assert!(effective.contains("[voice]"));
+        assert!(effective.contains("default_target_language = \"en\""));
     }
 
     #[test]
@@ -0,0 +1,167 @@
+//! Database health probing.
+//!
+//! The pool is opened once at startup. When the database file goes away
+//! (e.g. a network filesystem hiccup) sqlx drops the broken connections and
+//! opens fresh ones on the next acquire, so recovery needs no intervention —
+//! but nothing would notice the outage. A background task runs `SELECT 1` on
+//! an interval, logs when the database goes down or comes back, and keeps a
+//! status that `/healthz` reports.
+
+use super::DbPool;
+use crate::error::AppResult;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+
+/// Database status for `/healthz`
+#[derive(Debug, Clone, Serialize)]
+pub struct DbHealthSnapshot {
+    pub healthy: bool,
+    /// Probes failed in a row (0 while healthy)
+    pub consecutive_failures: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_checked: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    consecutive_failures: u32,
+    last_error: Option<String>,
+    last_checked: Option<DateTime<Utc>>,
+}
+
+/// Result of the most recent database probes
+#[derive(Debug, Default)]
+pub struct DbHealth {
+    inner: Mutex<Inner>,
+}
+
+impl DbHealth {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        self.lock().consecutive_failures == 0
+    }
+
+    /// Record a successful probe; returns true if the database was down
+    pub fn record_success(&self, now: DateTime<Utc>) -> bool {
+        let mut inner = self.lock();
+        let recovered = inner.consecutive_failures > 0;
+        inner.consecutive_failures = 0;
+        inner.last_error = None;
+        inner.last_checked = Some(now);
+        recovered
+    }
+
+    /// Record a failed probe; returns true if the database was up
+    pub fn record_failure(&self, error: String, now: DateTime<Utc>) -> bool {
+        let mut inner = self.lock();
+        let went_down = inner.consecutive_failures == 0;
+        inner.consecutive_failures += 1;
+        inner.last_error = Some(error);
+        inner.last_checked = Some(now);
+        went_down
+    }
+
+    pub fn snapshot(&self) -> DbHealthSnapshot {
+        let inner = self.lock();
+        DbHealthSnapshot {
+            healthy: inner.consecutive_failures == 0,
+            consecutive_failures: inner.consecutive_failures,
+            last_error: inner.last_error.clone(),
+            last_checked: inner.last_checked,
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Run a trivial query to check the database is reachable
+pub async fn probe(pool: &DbPool) -> AppResult<()> {
+    sqlx::query("SELECT 1").execute(pool).await?;
+    Ok(())
+}
+
+/// Probe once and record the outcome, logging when the status changes
+pub async fn check(pool: &DbPool, health: &DbHealth) {
+    match probe(pool).await {
+        Ok(()) => {
+            if health.record_success(Utc::now()) {
+                info!("Database reachable again");
+            }
+        }
+        Err(e) => {
+            if health.record_failure(e.to_string(), Utc::now()) {
+                error!("Database unavailable: {}", e);
+            } else {
+                warn!("Database still unavailable: {}", e);
+            }
+        }
+    }
+}
+
+/// Probe the database every `interval` until the task is aborted
+pub fn spawn_health_monitor(pool: DbPool, health: Arc<DbHealth>, interval: Duration) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            check(&pool, &health).await;
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::queries::setup_test_db;
+
+    #[tokio::test]
+    async fn test_probe_healthy_pool() {
+        let pool = setup_test_db().await;
+        let health = DbHealth::new();
+        check(&pool, &health).await;
+
+        let snapshot = health.snapshot();
+        assert!(snapshot.healthy);
+        assert!(snapshot.last_checked.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_closed_pool_reported_unavailable_then_recovers() {
+        let pool = setup_test_db().await;
+        let health = DbHealth::new();
+        pool.close().await;
+
+        check(&pool, &health).await;
+        check(&pool, &health).await;
+        let snapshot = health.snapshot();
+        assert!(!snapshot.healthy);
+        assert_eq!(snapshot.consecutive_failures, 2);
+        assert!(snapshot.last_error.unwrap().contains("unavailable"));
+
+        let pool = setup_test_db().await;
+        check(&pool, &health).await;
+        assert!(health.is_healthy());
+        assert!(health.snapshot().last_error.is_none());
+    }
+
+    #[test]
+    fn test_status_transitions_reported_once() {
+        let health = DbHealth::new();
+        let now = Utc::now();
+        assert!(health.record_failure("down".to_string(), now));
+        assert!(!health.record_failure("down".to_string(), now));
+        assert!(health.record_success(now));
+        assert!(!health.record_success(now));
+    }
+}
@@ -1,5 +1,7 @@
+pub mod health;
 pub mod models;
 pub mod queries;
 
+pub use health::{DbHealth, DbHealthSnapshot};
 pub use models::*;
 pub use queries::*;
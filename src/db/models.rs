@@ -1,3 +1,4 @@
+use crate::translation::Formality;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
@@ -14,6 +15,30 @@ pub struct Guild {
     pub target_languages: String, // JSON array of language codes
     pub subscription_tier: String,
     pub subscription_expires_at: Option<DateTime<Utc>>,
+    /// Only post translations for messages not written in the default language
+    pub translate_only_foreign: bool,
+    /// Messages starting with this prefix are never translated (empty disables)
+    pub skip_prefix: String,
+    /// Translate messages posted by other bots
+    pub translate_bot_messages: bool,
+    /// Translate messages posted through webhooks (e.g. bridged chats)
+    pub translate_webhooks: bool,
+    /// Stored [`OutputMode`]
+    pub output_mode: String,
+    /// Label voice speakers with their server nickname (else their username)
+    pub use_nicknames: bool,
+    /// Show voice speakers as "Speaker N" and drop their user ids from the
+    /// web view and stored history
+    pub anonymize_speakers: bool,
+    /// Longest voice session in seconds before the bot leaves on its own
+    /// (0 for no limit); None uses `voice.max_session_secs`
+    pub max_voice_session_secs: Option<i64>,
+    /// Hint passed to the translation model, e.g. "casual gaming chat"
+    pub translation_context: Option<String>,
+    /// Stored [`Formality`] of message translations
+    pub formality: String,
+    /// Stored [`ProgressReaction`]
+    pub progress_reaction: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -26,6 +51,19 @@ pub struct UserPreference {
     pub guild_id: String,
     pub preferred_language: String,
     pub auto_translate: bool,
+    /// Leave this user's speech out of exported voice transcripts
+    pub transcript_opt_out: bool,
+    /// Stored [`Formality`] of this user's `/translate` results
+    pub formality: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A user's preferences outside any guild, e.g. for direct messages
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct UserGlobalPreference {
+    pub user_id: String,
+    pub preferred_language: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -38,6 +76,11 @@ pub struct Channel {
     pub guild_id: String,
     pub enabled: bool,
     pub target_languages: String, // JSON array, overrides guild default
+    pub allowed_target_languages: String, // JSON array, empty allows every target
+    /// Only messages matching this regex are translated (None translates all)
+    pub message_filter: Option<String>,
+    /// Translation hint used here instead of the guild's
+    pub translation_context: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -101,6 +144,11 @@ impl SubscriptionTier {
         }
     }
 
+    /// Whether this is a paying tier (higher message limits)
+    pub fn is_paid(&self) -> bool {
+        !matches!(self, Self::Free)
+    }
+
     /// Whether web view is available
     pub fn has_web_view(&self) -> bool {
         matches!(self, Self::Basic | Self::Pro | Self::Enterprise)
@@ -123,6 +171,17 @@ pub struct GuildSettings {
     pub target_languages: Vec<String>,
     pub subscription_tier: SubscriptionTier,
     pub subscription_expires_at: Option<DateTime<Utc>>,
+    pub translate_only_foreign: bool,
+    pub skip_prefix: String,
+    pub translate_bot_messages: bool,
+    pub translate_webhooks: bool,
+    pub output_mode: OutputMode,
+    pub use_nicknames: bool,
+    pub anonymize_speakers: bool,
+    pub max_voice_session_secs: Option<i64>,
+    pub translation_context: Option<String>,
+    pub formality: Formality,
+    pub progress_reaction: ProgressReaction,
 }
 
 impl From<Guild> for GuildSettings {
@@ -135,6 +194,17 @@ impl From<Guild> for GuildSettings {
             target_languages: serde_json::from_str(&guild.target_languages).unwrap_or_default(),
             subscription_tier: SubscriptionTier::from_str(&guild.subscription_tier),
             subscription_expires_at: guild.subscription_expires_at,
+            translate_only_foreign: guild.translate_only_foreign,
+            skip_prefix: guild.skip_prefix,
+            translate_bot_messages: guild.translate_bot_messages,
+            translate_webhooks: guild.translate_webhooks,
+            output_mode: OutputMode::from(guild.output_mode.as_str()),
+            use_nicknames: guild.use_nicknames,
+            anonymize_speakers: guild.anonymize_speakers,
+            max_voice_session_secs: guild.max_voice_session_secs,
+            translation_context: guild.translation_context,
+            formality: Formality::from(guild.formality.as_str()),
+            progress_reaction: ProgressReaction::from(guild.progress_reaction.as_str()),
         }
     }
 }
@@ -168,6 +238,128 @@ impl NewWebSession {
     }
 }
 
+/// How text translations are posted back to Discord
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OutputMode {
+    /// Bot reply to the original message
+    Reply,
+    /// Posted in a thread started from the original message
+    Thread,
+    /// Posted through a channel webhook under the author's name and avatar
+    Webhook,
+}
+
+impl OutputMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Reply => "reply",
+            Self::Thread => "thread",
+            Self::Webhook => "webhook",
+        }
+    }
+}
+
+impl From<&str> for OutputMode {
+    fn from(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "thread" => Self::Thread,
+            "webhook" => Self::Webhook,
+            _ => Self::Reply,
+        }
+    }
+}
+
+impl std::fmt::Display for OutputMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Reactions showing a message's translation progress
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProgressReaction {
+    Off,
+    /// Shown while translating, then removed
+    Pending,
+    /// Shown while translating, then replaced with ✅ or ❌
+    Outcome,
+}
+
+impl ProgressReaction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::Pending => "pending",
+            Self::Outcome => "outcome",
+        }
+    }
+}
+
+impl From<&str> for ProgressReaction {
+    fn from(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "pending" => Self::Pending,
+            "outcome" => Self::Outcome,
+            _ => Self::Off,
+        }
+    }
+}
+
+impl std::fmt::Display for ProgressReaction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Where synthesized TTS audio for a voice channel is played
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TtsMode {
+    Off,
+    /// Audio goes to web viewers only; the bot stays silent in the channel
+    WebOnly,
+    /// Bot speaks in the voice channel; web viewers get text only
+    Voice,
+    Both,
+}
+
+impl TtsMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::WebOnly => "web_only",
+            Self::Voice => "voice",
+            Self::Both => "both",
+        }
+    }
+
+    /// Whether TTS audio is forwarded to web viewers
+    pub fn streams_to_web(&self) -> bool {
+        matches!(self, Self::WebOnly | Self::Both)
+    }
+
+    /// Whether TTS audio is played by the bot in the voice channel
+    pub fn plays_in_voice(&self) -> bool {
+        matches!(self, Self::Voice | Self::Both)
+    }
+}
+
+impl From<&str> for TtsMode {
+    fn from(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "web_only" | "web" => Self::WebOnly,
+            "voice" => Self::Voice,
+            "both" => Self::Both,
+            _ => Self::Off,
+        }
+    }
+}
+
+impl std::fmt::Display for TtsMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
 /// Voice channel translation settings
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
 pub struct VoiceChannelSettings {
@@ -176,9 +368,42 @@ pub struct VoiceChannelSettings {
     pub voice_channel_id: String,
     pub enabled: bool,
     pub target_language: String,
+    /// Legacy flag, kept in sync with `tts_mode != off`
     pub enable_tts: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Stored [`TtsMode`]
+    pub tts_mode: String,
+    /// Spoken language, or [`AUTO_DETECT_LANGUAGE`] to detect it per segment
+    pub source_language: String,
+    /// Bot is currently translating in this channel; rejoined after reconnects
+    pub active: bool,
+    /// Stored [`Formality`] of spoken translations
+    pub formality: String,
+    /// JSON array of target languages that get TTS; empty means all of them
+    pub tts_languages: String,
+}
+
+/// `source_language` value that lets the inference service detect the
+/// spoken language of every segment
+pub const AUTO_DETECT_LANGUAGE: &str = "auto";
+
+impl VoiceChannelSettings {
+    /// Fixed spoken language, or None when it is auto-detected
+    pub fn fixed_source_language(&self) -> Option<&str> {
+        Some(self.source_language.as_str()).filter(|lang| *lang != AUTO_DETECT_LANGUAGE)
+    }
+
+    /// Target languages limited to TTS, or empty when every language gets it
+    pub fn get_tts_languages(&self) -> Vec<String> {
+        serde_json::from_str(&self.tts_languages).unwrap_or_default()
+    }
+
+    /// Whether translations into `language` may be spoken
+    pub fn tts_enabled_for(&self, language: &str) -> bool {
+        let languages = self.get_tts_languages();
+        languages.is_empty() || languages.iter().any(|lang| lang == language)
+    }
 }
 
 /// New voice channel settings
@@ -187,7 +412,7 @@ pub struct NewVoiceChannelSettings {
     pub guild_id: String,
     pub voice_channel_id: String,
     pub target_language: String,
-    pub enable_tts: bool,
+    pub tts_mode: TtsMode,
 }
 
 /// Voice transcript settings - for posting transcripts to Discord threads
@@ -202,6 +427,8 @@ pub struct VoiceTranscriptSettings {
     pub languages: String,
     /// JSON map of language code to thread ID, e.g., {"en": "123456", "es": "789012"}
     pub thread_ids: String,
+    /// JSON array of languages whose thread posts include a TTS clip (off by default)
+    pub tts_languages: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -225,12 +452,310 @@ impl VoiceTranscriptSettings {
     pub fn get_thread_ids(&self) -> std::collections::HashMap<String, String> {
         serde_json::from_str(&self.thread_ids).unwrap_or_default()
     }
+
+    /// Get languages with audio clips as Vec
+    pub fn get_tts_languages(&self) -> Vec<String> {
+        serde_json::from_str(&self.tts_languages).unwrap_or_default()
+    }
+
+    /// Whether posts to `language`'s thread include a TTS clip
+    pub fn tts_enabled_for(&self, language: &str) -> bool {
+        self.get_tts_languages().iter().any(|lang| lang == language)
+    }
+}
+
+/// A human correction of a machine translation, submitted via `/feedback`
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct TranslationFeedback {
+    pub id: i64,
+    pub guild_id: String,
+    pub channel_id: String,
+    /// The bot's translation message that was corrected
+    pub message_id: String,
+    /// User who submitted the correction
+    pub user_id: String,
+    pub source_lang: String,
+    pub target_lang: String,
+    pub original_text: String,
+    pub machine_translation: String,
+    pub correction: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// New translation feedback
+#[derive(Debug, Clone)]
+pub struct NewTranslationFeedback {
+    pub guild_id: String,
+    pub channel_id: String,
+    pub message_id: String,
+    pub user_id: String,
+    pub source_lang: String,
+    pub target_lang: String,
+    pub original_text: String,
+    pub machine_translation: String,
+    pub correction: String,
+}
+
+/// A message the bot posted with a translation, and the message it
+/// translates. Webhook and thread posts carry no reply reference, so
+/// `/feedback` finds the original through this.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct TranslationMessage {
+    pub message_id: String,
+    pub guild_id: String,
+    pub channel_id: String,
+    pub original_channel_id: String,
+    pub original_message_id: String,
+    pub source_lang: String,
+    pub target_lang: String,
+    /// The whole translation, even when it was split over several messages
+    pub translated_text: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// New translation message record
+#[derive(Debug, Clone)]
+pub struct NewTranslationMessage {
+    pub message_id: String,
+    pub guild_id: String,
+    pub channel_id: String,
+    pub original_channel_id: String,
+    pub original_message_id: String,
+    pub source_lang: String,
+    pub target_lang: String,
+    pub translated_text: String,
+}
+
+/// One stored voice utterance, kept for `/voice transcript export`
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct VoiceTranscriptEntry {
+    pub id: i64,
+    pub guild_id: String,
+    pub voice_channel_id: String,
+    pub user_id: String,
+    pub username: String,
+    pub original_text: String,
+    pub translated_text: String,
+    pub source_language: String,
+    pub target_language: String,
+    /// When the speaker started the utterance
+    pub spoken_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// New voice transcript entry
+#[derive(Debug, Clone)]
+pub struct NewVoiceTranscriptEntry {
+    pub guild_id: String,
+    pub voice_channel_id: String,
+    pub user_id: String,
+    pub username: String,
+    pub original_text: String,
+    pub translated_text: String,
+    pub source_language: String,
+    pub target_language: String,
+    pub spoken_at: DateTime<Utc>,
+}
+
+/// A configured guild with how much of the bot it uses, for fleet overviews
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct GuildFootprint {
+    pub guild_id: String,
+    pub name: String,
+    pub subscription_tier: String,
+    /// Channels with text translation enabled
+    pub enabled_channels: i64,
+    /// Voice channels the bot is currently translating in
+    pub active_voice_sessions: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A guild setting whose changes `/setup undo` can revert
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigSetting {
+    DefaultLanguage,
+    TargetLanguages,
+}
+
+impl ConfigSetting {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::DefaultLanguage => "default_language",
+            Self::TargetLanguages => "target_languages",
+        }
+    }
+
+    /// Column of `guilds` holding the setting
+    pub fn column(&self) -> &'static str {
+        // Stored names are the column names
+        self.as_str()
+    }
+}
+
+impl From<&str> for ConfigSetting {
+    fn from(s: &str) -> Self {
+        match s {
+            "default_language" => Self::DefaultLanguage,
+            _ => Self::TargetLanguages,
+        }
+    }
+}
+
+/// A recorded guild setting change and the value it replaced
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct ConfigChange {
+    pub id: i64,
+    pub guild_id: String,
+    pub setting: String,
+    /// As stored in the `guilds` column (JSON for target languages)
+    pub previous_value: String,
+    pub changed_at: DateTime<Utc>,
+}
+
+impl ConfigChange {
+    pub fn setting(&self) -> ConfigSetting {
+        ConfigSetting::from(self.setting.as_str())
+    }
+}
+
+/// A translation cache entry saved across restarts
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct PersistedTranslation {
+    pub text: String,
+    pub source_lang: String,
+    pub target_lang: String,
+    pub context: Option<String>,
+    pub formality: String,
+    pub translated_text: String,
+    /// When the translation was first cached, so its TTL carries over
+    pub cached_at: DateTime<Utc>,
+}
+
+/// A voice transcription cache entry saved across restarts
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct PersistedVoiceResult {
+    /// Audio hash, stored as SQLite's signed integer
+    pub audio_hash: i64,
+    pub target_language: String,
+    /// The cached `VoiceInferenceResponse` as JSON
+    pub response: String,
+}
+
+/// A production translation paired with a shadow backend's output
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct TranslationShadow {
+    pub id: i64,
+    pub source_lang: String,
+    pub target_lang: String,
+    pub original_text: String,
+    pub primary_text: String,
+    pub primary_latency_ms: i64,
+    /// Base URL of the shadow backend
+    pub shadow_provider: String,
+    /// None when the shadow request failed
+    pub shadow_text: Option<String>,
+    pub shadow_latency_ms: i64,
+    pub shadow_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// New shadow comparison
+#[derive(Debug, Clone)]
+pub struct NewTranslationShadow {
+    pub source_lang: String,
+    pub target_lang: String,
+    pub original_text: String,
+    pub primary_text: String,
+    pub primary_latency_ms: i64,
+    pub shadow_provider: String,
+    pub shadow_text: Option<String>,
+    pub shadow_latency_ms: i64,
+    pub shadow_error: Option<String>,
+}
+
+/// What an ignore-list entry matches
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IgnoredKind {
+    User,
+    Role,
+}
+
+impl IgnoredKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::User => "user",
+            Self::Role => "role",
+        }
+    }
+}
+
+/// Users and roles whose messages and speech a guild doesn't translate
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IgnoreList {
+    pub users: Vec<String>,
+    pub roles: Vec<String>,
+}
+
+impl IgnoreList {
+    pub fn is_empty(&self) -> bool {
+        self.users.is_empty() && self.roles.is_empty()
+    }
+
+    /// Whether `user_id`, holding `role_ids`, is on the list
+    pub fn ignores(&self, user_id: &str, role_ids: &[String]) -> bool {
+        self.users.iter().any(|user| user == user_id)
+            || role_ids.iter().any(|role| self.roles.contains(role))
+    }
+}
+
+/// How much configuration is stored for one guild
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GuildDataSummary {
+    /// Server-wide settings differ from the defaults
+    pub custom_settings: bool,
+    pub user_preferences: i64,
+    /// Text channels with their own translation settings
+    pub channels: i64,
+    pub voice_channels: i64,
+    pub transcript_channels: i64,
+}
+
+impl GuildDataSummary {
+    /// Nothing has been configured for the guild
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// What a guild migration copied to the new guild id
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GuildMigrationReport {
+    /// Server-wide settings were copied
+    pub settings: bool,
+    pub user_preferences: u64,
+    pub channels: u64,
+    pub voice_channels: u64,
+    pub transcript_channels: u64,
+    /// Channel settings left behind because the channel isn't in the new guild
+    pub skipped_channels: i64,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_ignore_list_matches_users_and_roles() {
+        let list = IgnoreList {
+            users: vec!["music-bot".to_string()],
+            roles: vec!["afk".to_string()],
+        };
+        assert!(list.ignores("music-bot", &[]));
+        assert!(list.ignores("someone", &["member".to_string(), "afk".to_string()]));
+        assert!(!list.ignores("someone", &["member".to_string()]));
+        assert!(!IgnoreList::default().ignores("music-bot", &["afk".to_string()]));
+    }
+
     // --- SubscriptionTier tests ---
 
     #[test]
@@ -287,6 +812,16 @@ mod tests {
         assert_eq!(format!("{}", SubscriptionTier::Pro), "pro");
     }
 
+    #[test]
+    fn test_output_mode_round_trip() {
+        for mode in [OutputMode::Reply, OutputMode::Thread, OutputMode::Webhook] {
+            assert_eq!(OutputMode::from(mode.as_str()), mode);
+        }
+        assert_eq!(OutputMode::from("WEBHOOK"), OutputMode::Webhook);
+        // Unknown values fall back to plain replies
+        assert_eq!(OutputMode::from("carrier-pigeon"), OutputMode::Reply);
+    }
+
     // --- GuildSettings from Guild conversion ---
 
     #[test]
@@ -300,6 +835,17 @@ mod tests {
             target_languages: r#"["en","es","fr"]"#.to_string(),
             subscription_tier: "pro".to_string(),
             subscription_expires_at: None,
+            translate_only_foreign: false,
+            skip_prefix: "\\".to_string(),
+            translate_bot_messages: false,
+            translate_webhooks: false,
+            output_mode: "reply".to_string(),
+            use_nicknames: true,
+            anonymize_speakers: false,
+            max_voice_session_secs: None,
+            translation_context: None,
+            formality: "default".to_string(),
+            progress_reaction: "off".to_string(),
             created_at: Utc::now(),
             updated_at: Utc::now(),
         };
@@ -323,6 +869,17 @@ mod tests {
             target_languages: "also invalid".to_string(),
             subscription_tier: "free".to_string(),
             subscription_expires_at: None,
+            translate_only_foreign: false,
+            skip_prefix: "\\".to_string(),
+            translate_bot_messages: false,
+            translate_webhooks: false,
+            output_mode: "reply".to_string(),
+            use_nicknames: true,
+            anonymize_speakers: false,
+            max_voice_session_secs: None,
+            translation_context: None,
+            formality: "default".to_string(),
+            progress_reaction: "off".to_string(),
             created_at: Utc::now(),
             updated_at: Utc::now(),
         };
@@ -361,6 +918,7 @@ mod tests {
             enabled: true,
             languages: r#"["en","es","fr"]"#.to_string(),
             thread_ids: "{}".to_string(),
+            tts_languages: "[]".to_string(),
             created_at: Utc::now(),
             updated_at: Utc::now(),
         };
@@ -379,6 +937,7 @@ mod tests {
             enabled: true,
             languages: r#"["en"]"#.to_string(),
             thread_ids: r#"{"en":"123456","es":"789012"}"#.to_string(),
+            tts_languages: r#"["es"]"#.to_string(),
             created_at: Utc::now(),
             updated_at: Utc::now(),
         };
@@ -386,6 +945,8 @@ mod tests {
         let ids = settings.get_thread_ids();
         assert_eq!(ids.get("en"), Some(&"123456".to_string()));
         assert_eq!(ids.get("es"), Some(&"789012".to_string()));
+        assert!(settings.tts_enabled_for("es"));
+        assert!(!settings.tts_enabled_for("en"));
     }
 
     #[test]
@@ -398,11 +959,13 @@ mod tests {
             enabled: true,
             languages: "invalid".to_string(),
             thread_ids: "invalid".to_string(),
+            tts_languages: "invalid".to_string(),
             created_at: Utc::now(),
             updated_at: Utc::now(),
         };
 
         assert!(settings.get_languages().is_empty());
         assert!(settings.get_thread_ids().is_empty());
+        assert!(settings.get_tts_languages().is_empty());
     }
 }
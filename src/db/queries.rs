@@ -1,6 +1,7 @@
 use crate::db::models::*;
 use crate::error::{AppError, AppResult};
-use chrono::{Duration, Utc};
+use crate::translation::Formality;
+use chrono::{DateTime, Duration, Utc};
 use sqlx::{Pool, Sqlite};
 use tracing::info;
 
@@ -54,14 +55,35 @@ impl GuildRepo {
             .ok_or_else(|| AppError::internal("Failed to retrieve created guild"))
     }
 
-    /// Update guild default language
+    /// Update guild default language, remembering the old one for
+    /// [`ConfigHistoryRepo::undo_last`]
     pub async fn set_default_language(
         pool: &DbPool,
         guild_id: &str,
         language: &str,
     ) -> AppResult<()> {
-        sqlx::query("UPDATE guilds SET default_language = ?, updated_at = ? WHERE guild_id = ?")
-            .bind(language)
+        ConfigHistoryRepo::record_and_set(pool, guild_id, ConfigSetting::DefaultLanguage, language).await
+    }
+
+    /// Update guild target languages, remembering the old ones for
+    /// [`ConfigHistoryRepo::undo_last`]
+    pub async fn set_target_languages(
+        pool: &DbPool,
+        guild_id: &str,
+        languages: &[String],
+    ) -> AppResult<()> {
+        let langs_json = serde_json::to_string(languages).unwrap();
+        ConfigHistoryRepo::record_and_set(pool, guild_id, ConfigSetting::TargetLanguages, &langs_json).await
+    }
+
+    /// Toggle whether only messages outside the default language are translated
+    pub async fn set_translate_only_foreign(
+        pool: &DbPool,
+        guild_id: &str,
+        enabled: bool,
+    ) -> AppResult<()> {
+        sqlx::query("UPDATE guilds SET translate_only_foreign = ?, updated_at = ? WHERE guild_id = ?")
+            .bind(enabled)
             .bind(Utc::now())
             .bind(guild_id)
             .execute(pool)
@@ -69,15 +91,190 @@ impl GuildRepo {
         Ok(())
     }
 
-    /// Update guild target languages
-    pub async fn set_target_languages(
+    /// Choose whether messages from other bots and from webhooks are translated
+    pub async fn set_automated_sources(
         pool: &DbPool,
         guild_id: &str,
-        languages: &[String],
+        bots: bool,
+        webhooks: bool,
     ) -> AppResult<()> {
-        let langs_json = serde_json::to_string(languages).unwrap();
-        sqlx::query("UPDATE guilds SET target_languages = ?, updated_at = ? WHERE guild_id = ?")
-            .bind(langs_json)
+        sqlx::query(
+            "UPDATE guilds SET translate_bot_messages = ?, translate_webhooks = ?, updated_at = ? WHERE guild_id = ?",
+        )
+        .bind(bots)
+        .bind(webhooks)
+        .bind(Utc::now())
+        .bind(guild_id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Label voice speakers with their server nickname rather than their
+    /// username
+    pub async fn set_use_nicknames(pool: &DbPool, guild_id: &str, enabled: bool) -> AppResult<()> {
+        sqlx::query("UPDATE guilds SET use_nicknames = ?, updated_at = ? WHERE guild_id = ?")
+            .bind(enabled)
+            .bind(Utc::now())
+            .bind(guild_id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Replace voice speaker names with per-session pseudonyms and keep
+    /// user ids out of the web view and history
+    pub async fn set_anonymize_speakers(pool: &DbPool, guild_id: &str, enabled: bool) -> AppResult<()> {
+        sqlx::query("UPDATE guilds SET anonymize_speakers = ?, updated_at = ? WHERE guild_id = ?")
+            .bind(enabled)
+            .bind(Utc::now())
+            .bind(guild_id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Override `voice.max_session_secs` for this guild (None restores it)
+    pub async fn set_max_voice_session_secs(pool: &DbPool, guild_id: &str, secs: Option<i64>) -> AppResult<()> {
+        sqlx::query("UPDATE guilds SET max_voice_session_secs = ?, updated_at = ? WHERE guild_id = ?")
+            .bind(secs)
+            .bind(Utc::now())
+            .bind(guild_id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Set the hint passed to the translation model for this guild's
+    /// messages (None removes it)
+    pub async fn set_translation_context(
+        pool: &DbPool,
+        guild_id: &str,
+        context: Option<&str>,
+    ) -> AppResult<()> {
+        sqlx::query("UPDATE guilds SET translation_context = ?, updated_at = ? WHERE guild_id = ?")
+            .bind(context)
+            .bind(Utc::now())
+            .bind(guild_id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Set the formality of this guild's message translations
+    pub async fn set_formality(pool: &DbPool, guild_id: &str, formality: Formality) -> AppResult<()> {
+        sqlx::query("UPDATE guilds SET formality = ?, updated_at = ? WHERE guild_id = ?")
+            .bind(formality.as_str())
+            .bind(Utc::now())
+            .bind(guild_id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Number of configured guilds
+    pub async fn count(pool: &DbPool) -> AppResult<i64> {
+        let count = sqlx::query_scalar("SELECT COUNT(*) FROM guilds")
+            .fetch_one(pool)
+            .await?;
+        Ok(count)
+    }
+
+    /// A page of configured guilds, oldest first, with their enabled channel
+    /// and active voice session counts
+    pub async fn list_footprints(
+        pool: &DbPool,
+        limit: i64,
+        offset: i64,
+    ) -> AppResult<Vec<GuildFootprint>> {
+        let guilds = sqlx::query_as::<_, GuildFootprint>(
+            r#"
+            SELECT g.guild_id, g.name, g.subscription_tier, g.created_at,
+                json_array_length(g.enabled_channels) AS enabled_channels,
+                (SELECT COUNT(*) FROM voice_channel_settings v
+                    WHERE v.guild_id = g.guild_id AND v.active = true AND v.enabled = true) AS active_voice_sessions
+            FROM guilds g
+            ORDER BY g.created_at, g.id
+            LIMIT ? OFFSET ?
+            "#,
+        )
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await?;
+        Ok(guilds)
+    }
+
+    /// Record that the onboarding message went out, returning false if it
+    /// already had (so it's only ever sent once per guild)
+    pub async fn mark_onboarded(pool: &DbPool, guild_id: &str) -> AppResult<bool> {
+        let result = sqlx::query(
+            "UPDATE guilds SET onboarded_at = ? WHERE guild_id = ? AND onboarded_at IS NULL",
+        )
+        .bind(Utc::now())
+        .bind(guild_id)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Set how translations are posted in this guild
+    pub async fn set_output_mode(pool: &DbPool, guild_id: &str, mode: OutputMode) -> AppResult<()> {
+        sqlx::query("UPDATE guilds SET output_mode = ?, updated_at = ? WHERE guild_id = ?")
+            .bind(mode.as_str())
+            .bind(Utc::now())
+            .bind(guild_id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Set which reactions show a message's translation progress
+    pub async fn set_progress_reaction(
+        pool: &DbPool,
+        guild_id: &str,
+        mode: ProgressReaction,
+    ) -> AppResult<()> {
+        sqlx::query("UPDATE guilds SET progress_reaction = ?, updated_at = ? WHERE guild_id = ?")
+            .bind(mode.as_str())
+            .bind(Utc::now())
+            .bind(guild_id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Set the prefix that opts a message out of translation (empty disables)
+    pub async fn set_skip_prefix(pool: &DbPool, guild_id: &str, prefix: &str) -> AppResult<()> {
+        sqlx::query("UPDATE guilds SET skip_prefix = ?, updated_at = ? WHERE guild_id = ?")
+            .bind(prefix)
+            .bind(Utc::now())
+            .bind(guild_id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Text command prefix the guild overrides the global one with
+    pub async fn get_command_prefix(pool: &DbPool, guild_id: &str) -> AppResult<Option<String>> {
+        let prefix = sqlx::query_scalar::<_, Option<String>>(
+            "SELECT command_prefix FROM guilds WHERE guild_id = ?",
+        )
+        .bind(guild_id)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(prefix.flatten())
+    }
+
+    /// Set the guild's text command prefix (`None` restores the global one)
+    pub async fn set_command_prefix(
+        pool: &DbPool,
+        guild_id: &str,
+        prefix: Option<&str>,
+    ) -> AppResult<()> {
+        sqlx::query("UPDATE guilds SET command_prefix = ?, updated_at = ? WHERE guild_id = ?")
+            .bind(prefix)
             .bind(Utc::now())
             .bind(guild_id)
             .execute(pool)
@@ -149,6 +346,129 @@ impl GuildRepo {
     }
 }
 
+/// Database operations for per-channel translation settings
+pub struct ChannelRepo;
+
+impl ChannelRepo {
+    /// Languages a channel may be translated into; empty when any of the
+    /// guild's targets are allowed
+    pub async fn get_allowed_languages(pool: &DbPool, channel_id: &str) -> AppResult<Vec<String>> {
+        let allowed: Option<String> =
+            sqlx::query_scalar("SELECT allowed_target_languages FROM channels WHERE channel_id = ?")
+                .bind(channel_id)
+                .fetch_optional(pool)
+                .await?;
+        Ok(allowed
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default())
+    }
+
+    /// Restrict a channel's translations to `languages` (empty lifts the
+    /// restriction)
+    pub async fn set_allowed_languages(
+        pool: &DbPool,
+        guild_id: &str,
+        channel_id: &str,
+        languages: &[String],
+    ) -> AppResult<()> {
+        let now = Utc::now();
+        let languages_json = serde_json::to_string(languages).unwrap();
+        sqlx::query(
+            r#"
+            INSERT INTO channels (channel_id, guild_id, allowed_target_languages, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?)
+            ON CONFLICT(channel_id) DO UPDATE SET
+                allowed_target_languages = excluded.allowed_target_languages,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(channel_id)
+        .bind(guild_id)
+        .bind(&languages_json)
+        .bind(now)
+        .bind(now)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Pattern a message must match to be translated in a channel, if any
+    pub async fn get_message_filter(pool: &DbPool, channel_id: &str) -> AppResult<Option<String>> {
+        let filter: Option<Option<String>> =
+            sqlx::query_scalar("SELECT message_filter FROM channels WHERE channel_id = ?")
+                .bind(channel_id)
+                .fetch_optional(pool)
+                .await?;
+        Ok(filter.flatten())
+    }
+
+    /// Translation hint a channel uses instead of its guild's, if any
+    pub async fn get_translation_context(pool: &DbPool, channel_id: &str) -> AppResult<Option<String>> {
+        let context: Option<Option<String>> =
+            sqlx::query_scalar("SELECT translation_context FROM channels WHERE channel_id = ?")
+                .bind(channel_id)
+                .fetch_optional(pool)
+                .await?;
+        Ok(context.flatten())
+    }
+
+    /// Give a channel its own translation hint (None falls back to the
+    /// guild's)
+    pub async fn set_translation_context(
+        pool: &DbPool,
+        guild_id: &str,
+        channel_id: &str,
+        context: Option<&str>,
+    ) -> AppResult<()> {
+        let now = Utc::now();
+        sqlx::query(
+            r#"
+            INSERT INTO channels (channel_id, guild_id, translation_context, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?)
+            ON CONFLICT(channel_id) DO UPDATE SET
+                translation_context = excluded.translation_context,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(channel_id)
+        .bind(guild_id)
+        .bind(context)
+        .bind(now)
+        .bind(now)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Only translate messages in a channel that match `pattern` (None
+    /// translates every message). Validate the pattern before storing it.
+    pub async fn set_message_filter(
+        pool: &DbPool,
+        guild_id: &str,
+        channel_id: &str,
+        pattern: Option<&str>,
+    ) -> AppResult<()> {
+        let now = Utc::now();
+        sqlx::query(
+            r#"
+            INSERT INTO channels (channel_id, guild_id, message_filter, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?)
+            ON CONFLICT(channel_id) DO UPDATE SET
+                message_filter = excluded.message_filter,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(channel_id)
+        .bind(guild_id)
+        .bind(pattern)
+        .bind(now)
+        .bind(now)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
+
 /// Database operations for user preferences
 pub struct UserPreferenceRepo;
 
@@ -218,77 +538,241 @@ impl UserPreferenceRepo {
 
         Ok(())
     }
-}
 
-/// Database operations for web sessions
-pub struct WebSessionRepo;
-
-impl WebSessionRepo {
-    /// Create a new web session
-    pub async fn create(
+    /// Opt a user in or out of exported voice transcripts
+    pub async fn set_transcript_opt_out(
         pool: &DbPool,
-        session: NewWebSession,
-        expiry_hours: u64,
-    ) -> AppResult<WebSession> {
-        let session_id = NewWebSession::generate_session_id();
+        user_id: &str,
+        guild_id: &str,
+        opt_out: bool,
+    ) -> AppResult<()> {
         let now = Utc::now();
-        let expires_at = now + Duration::hours(expiry_hours as i64);
 
+        // Users without preferences get the guild's default language
         sqlx::query(
             r#"
-            INSERT INTO web_sessions (session_id, user_id, guild_id, channel_id, expires_at, created_at)
-            VALUES (?, ?, ?, ?, ?, ?)
+            INSERT INTO user_preferences (user_id, guild_id, preferred_language, auto_translate, transcript_opt_out, created_at, updated_at)
+            VALUES (?, ?, COALESCE((SELECT default_language FROM guilds WHERE guild_id = ?), 'en'), true, ?, ?, ?)
+            ON CONFLICT(user_id, guild_id) DO UPDATE SET
+                transcript_opt_out = excluded.transcript_opt_out,
+                updated_at = excluded.updated_at
             "#,
         )
-        .bind(&session_id)
-        .bind(&session.user_id)
-        .bind(&session.guild_id)
-        .bind(&session.channel_id)
-        .bind(expires_at)
+        .bind(user_id)
+        .bind(guild_id)
+        .bind(guild_id)
+        .bind(opt_out)
+        .bind(now)
         .bind(now)
         .execute(pool)
         .await?;
 
-        Self::get_by_session_id(pool, &session_id)
-            .await?
-            .ok_or_else(|| AppError::internal("Failed to create session"))
+        Ok(())
     }
 
-    /// Get session by ID
-    pub async fn get_by_session_id(
+    /// Set the formality of a user's `/translate` results
+    pub async fn set_formality(
         pool: &DbPool,
-        session_id: &str,
-    ) -> AppResult<Option<WebSession>> {
-        let session = sqlx::query_as::<_, WebSession>(
-            "SELECT * FROM web_sessions WHERE session_id = ? AND expires_at > ?",
+        user_id: &str,
+        guild_id: &str,
+        formality: Formality,
+    ) -> AppResult<()> {
+        let now = Utc::now();
+
+        // Users without preferences get the guild's default language
+        sqlx::query(
+            r#"
+            INSERT INTO user_preferences (user_id, guild_id, preferred_language, auto_translate, formality, created_at, updated_at)
+            VALUES (?, ?, COALESCE((SELECT default_language FROM guilds WHERE guild_id = ?), 'en'), true, ?, ?, ?)
+            ON CONFLICT(user_id, guild_id) DO UPDATE SET
+                formality = excluded.formality,
+                updated_at = excluded.updated_at
+            "#,
         )
-        .bind(session_id)
-        .bind(Utc::now())
-        .fetch_optional(pool)
+        .bind(user_id)
+        .bind(guild_id)
+        .bind(guild_id)
+        .bind(formality.as_str())
+        .bind(now)
+        .bind(now)
+        .execute(pool)
         .await?;
 
-        Ok(session)
+        Ok(())
     }
 
-    /// Delete expired sessions
-    pub async fn cleanup_expired(pool: &DbPool) -> AppResult<u64> {
-        let result = sqlx::query("DELETE FROM web_sessions WHERE expires_at <= ?")
-            .bind(Utc::now())
-            .execute(pool)
-            .await?;
+    /// Users of a guild who opted out of exported voice transcripts
+    pub async fn transcript_opt_outs(pool: &DbPool, guild_id: &str) -> AppResult<Vec<String>> {
+        let users = sqlx::query_scalar::<_, String>(
+            "SELECT user_id FROM user_preferences WHERE guild_id = ? AND transcript_opt_out = true",
+        )
+        .bind(guild_id)
+        .fetch_all(pool)
+        .await?;
 
-        Ok(result.rows_affected())
+        Ok(users)
     }
+}
 
-    /// Delete session
-    pub async fn delete(pool: &DbPool, session_id: &str) -> AppResult<()> {
-        sqlx::query("DELETE FROM web_sessions WHERE session_id = ?")
-            .bind(session_id)
-            .execute(pool)
-            .await?;
+/// Database operations for preferences that apply outside any guild
+pub struct UserGlobalPreferenceRepo;
+
+impl UserGlobalPreferenceRepo {
+    /// Get a user's guild-independent preference
+    pub async fn get(pool: &DbPool, user_id: &str) -> AppResult<Option<UserGlobalPreference>> {
+        let pref = sqlx::query_as::<_, UserGlobalPreference>(
+            "SELECT * FROM user_global_preferences WHERE user_id = ?",
+        )
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(pref)
+    }
+
+    /// Set the language direct messages are translated into
+    pub async fn set_language(pool: &DbPool, user_id: &str, language: &str) -> AppResult<()> {
+        let now = Utc::now();
+
+        sqlx::query(
+            r#"
+            INSERT INTO user_global_preferences (user_id, preferred_language, created_at, updated_at)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT(user_id) DO UPDATE SET
+                preferred_language = excluded.preferred_language,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(user_id)
+        .bind(language)
+        .bind(now)
+        .bind(now)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+}
+
+/// Database operations for web sessions
+pub struct WebSessionRepo;
+
+impl WebSessionRepo {
+    /// Create a new web session
+    pub async fn create(
+        pool: &DbPool,
+        session: NewWebSession,
+        expiry_hours: u64,
+    ) -> AppResult<WebSession> {
+        let session_id = NewWebSession::generate_session_id();
+        let now = Utc::now();
+        let expires_at = now + Duration::hours(expiry_hours as i64);
+
+        sqlx::query(
+            r#"
+            INSERT INTO web_sessions (session_id, user_id, guild_id, channel_id, expires_at, created_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&session_id)
+        .bind(&session.user_id)
+        .bind(&session.guild_id)
+        .bind(&session.channel_id)
+        .bind(expires_at)
+        .bind(now)
+        .execute(pool)
+        .await?;
+
+        Self::get_by_session_id(pool, &session_id)
+            .await?
+            .ok_or_else(|| AppError::internal("Failed to create session"))
+    }
+
+    /// Get session by ID
+    pub async fn get_by_session_id(
+        pool: &DbPool,
+        session_id: &str,
+    ) -> AppResult<Option<WebSession>> {
+        let session = sqlx::query_as::<_, WebSession>(
+            "SELECT * FROM web_sessions WHERE session_id = ? AND expires_at > ?",
+        )
+        .bind(session_id)
+        .bind(Utc::now())
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(session)
+    }
+
+    /// Delete expired sessions
+    pub async fn cleanup_expired(pool: &DbPool) -> AppResult<u64> {
+        let result = sqlx::query("DELETE FROM web_sessions WHERE expires_at <= ?")
+            .bind(Utc::now())
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Delete session
+    pub async fn delete(pool: &DbPool, session_id: &str) -> AppResult<()> {
+        sqlx::query("DELETE FROM web_sessions WHERE session_id = ?")
+            .bind(session_id)
+            .execute(pool)
+            .await?;
 
         Ok(())
     }
+
+    /// List unexpired sessions for a guild, newest first
+    pub async fn list_for_guild(pool: &DbPool, guild_id: &str) -> AppResult<Vec<WebSession>> {
+        let sessions = sqlx::query_as::<_, WebSession>(
+            "SELECT * FROM web_sessions WHERE guild_id = ? AND expires_at > ? ORDER BY created_at DESC, id DESC",
+        )
+        .bind(guild_id)
+        .bind(Utc::now())
+        .fetch_all(pool)
+        .await?;
+
+        Ok(sessions)
+    }
+
+    /// Revoke one session of a guild by its row ID. Returns false if the
+    /// guild has no such session.
+    pub async fn revoke(pool: &DbPool, guild_id: &str, id: i64) -> AppResult<bool> {
+        let result = sqlx::query("DELETE FROM web_sessions WHERE guild_id = ? AND id = ?")
+            .bind(guild_id)
+            .bind(id)
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Revoke every session of a guild, or only those of `user_id`
+    pub async fn revoke_for_guild(
+        pool: &DbPool,
+        guild_id: &str,
+        user_id: Option<&str>,
+    ) -> AppResult<u64> {
+        let result = match user_id {
+            Some(user_id) => {
+                sqlx::query("DELETE FROM web_sessions WHERE guild_id = ? AND user_id = ?")
+                    .bind(guild_id)
+                    .bind(user_id)
+                    .execute(pool)
+                    .await?
+            }
+            None => {
+                sqlx::query("DELETE FROM web_sessions WHERE guild_id = ?")
+                    .bind(guild_id)
+                    .execute(pool)
+                    .await?
+            }
+        };
+
+        Ok(result.rows_affected())
+    }
 }
 
 /// Database operations for voice channel settings
@@ -336,18 +820,20 @@ impl VoiceChannelRepo {
 
         sqlx::query(
             r#"
-            INSERT INTO voice_channel_settings (guild_id, voice_channel_id, enabled, target_language, enable_tts, created_at, updated_at)
-            VALUES (?, ?, true, ?, ?, ?, ?)
+            INSERT INTO voice_channel_settings (guild_id, voice_channel_id, enabled, target_language, enable_tts, tts_mode, created_at, updated_at)
+            VALUES (?, ?, true, ?, ?, ?, ?, ?)
             ON CONFLICT(guild_id, voice_channel_id) DO UPDATE SET
                 target_language = excluded.target_language,
                 enable_tts = excluded.enable_tts,
+                tts_mode = excluded.tts_mode,
                 updated_at = excluded.updated_at
             "#,
         )
         .bind(&settings.guild_id)
         .bind(&settings.voice_channel_id)
         .bind(&settings.target_language)
-        .bind(settings.enable_tts)
+        .bind(settings.tts_mode != TtsMode::Off)
+        .bind(settings.tts_mode.as_str())
         .bind(now)
         .bind(now)
         .execute(pool)
@@ -396,17 +882,119 @@ impl VoiceChannelRepo {
         Ok(())
     }
 
-    /// Update TTS setting for a voice channel
+    /// Update TTS setting for a voice channel.
+    ///
+    /// Enabling maps to [`TtsMode::Both`]; use [`Self::set_tts_mode`] for finer control.
     pub async fn set_tts_enabled(
         pool: &DbPool,
         guild_id: &str,
         voice_channel_id: &str,
         enabled: bool,
+    ) -> AppResult<()> {
+        let mode = if enabled { TtsMode::Both } else { TtsMode::Off };
+        Self::set_tts_mode(pool, guild_id, voice_channel_id, mode).await
+    }
+
+    /// Set where TTS audio is played for a voice channel
+    pub async fn set_tts_mode(
+        pool: &DbPool,
+        guild_id: &str,
+        voice_channel_id: &str,
+        mode: TtsMode,
     ) -> AppResult<()> {
         sqlx::query(
-            "UPDATE voice_channel_settings SET enable_tts = ?, updated_at = ? WHERE guild_id = ? AND voice_channel_id = ?",
+            "UPDATE voice_channel_settings SET tts_mode = ?, enable_tts = ?, updated_at = ? WHERE guild_id = ? AND voice_channel_id = ?",
         )
-        .bind(enabled)
+        .bind(mode.as_str())
+        .bind(mode != TtsMode::Off)
+        .bind(Utc::now())
+        .bind(guild_id)
+        .bind(voice_channel_id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Mark the channel the bot is translating in a guild. The bot is in at most
+    /// one voice channel per guild, so every other channel is marked inactive;
+    /// `None` clears the guild.
+    pub async fn set_active_channel(
+        pool: &DbPool,
+        guild_id: &str,
+        voice_channel_id: Option<&str>,
+    ) -> AppResult<()> {
+        sqlx::query(
+            "UPDATE voice_channel_settings SET active = (voice_channel_id IS ?), updated_at = ? WHERE guild_id = ?",
+        )
+        .bind(voice_channel_id)
+        .bind(Utc::now())
+        .bind(guild_id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Channels the bot was translating in, across all guilds
+    pub async fn get_active(pool: &DbPool) -> AppResult<Vec<VoiceChannelSettings>> {
+        let settings = sqlx::query_as::<_, VoiceChannelSettings>(
+            "SELECT * FROM voice_channel_settings WHERE active = true AND enabled = true",
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(settings)
+    }
+
+    /// Set the spoken language for a voice channel (`auto` to detect per segment)
+    pub async fn set_source_language(
+        pool: &DbPool,
+        guild_id: &str,
+        voice_channel_id: &str,
+        language: &str,
+    ) -> AppResult<()> {
+        sqlx::query(
+            "UPDATE voice_channel_settings SET source_language = ?, updated_at = ? WHERE guild_id = ? AND voice_channel_id = ?",
+        )
+        .bind(language)
+        .bind(Utc::now())
+        .bind(guild_id)
+        .bind(voice_channel_id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Set the formality of spoken translations in a voice channel
+    pub async fn set_formality(
+        pool: &DbPool,
+        guild_id: &str,
+        voice_channel_id: &str,
+        formality: Formality,
+    ) -> AppResult<()> {
+        sqlx::query(
+            "UPDATE voice_channel_settings SET formality = ?, updated_at = ? WHERE guild_id = ? AND voice_channel_id = ?",
+        )
+        .bind(formality.as_str())
+        .bind(Utc::now())
+        .bind(guild_id)
+        .bind(voice_channel_id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Limit TTS to these target languages; an empty list allows every language
+    pub async fn set_tts_languages(
+        pool: &DbPool,
+        guild_id: &str,
+        voice_channel_id: &str,
+        languages: &[String],
+    ) -> AppResult<()> {
+        let languages_json = serde_json::to_string(languages).unwrap();
+        sqlx::query(
+            "UPDATE voice_channel_settings SET tts_languages = ?, updated_at = ? WHERE guild_id = ? AND voice_channel_id = ?",
+        )
+        .bind(languages_json)
         .bind(Utc::now())
         .bind(guild_id)
         .bind(voice_channel_id)
@@ -548,6 +1136,37 @@ impl VoiceTranscriptRepo {
         Ok(())
     }
 
+    /// Turn audio clips on or off for one transcript language
+    pub async fn set_language_tts(
+        pool: &DbPool,
+        guild_id: &str,
+        voice_channel_id: &str,
+        language: &str,
+        enabled: bool,
+    ) -> AppResult<()> {
+        let settings = Self::get_settings(pool, guild_id, voice_channel_id)
+            .await?
+            .ok_or_else(|| AppError::internal("Transcript settings not found"))?;
+
+        let mut tts_languages = settings.get_tts_languages();
+        tts_languages.retain(|lang| lang != language);
+        if enabled {
+            tts_languages.push(language.to_string());
+        }
+        let tts_languages_json = serde_json::to_string(&tts_languages).unwrap();
+
+        sqlx::query(
+            "UPDATE voice_transcript_settings SET tts_languages = ?, updated_at = ? WHERE guild_id = ? AND voice_channel_id = ?",
+        )
+        .bind(tts_languages_json)
+        .bind(Utc::now())
+        .bind(guild_id)
+        .bind(voice_channel_id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
     /// Delete transcript settings
     pub async fn delete(pool: &DbPool, guild_id: &str, voice_channel_id: &str) -> AppResult<()> {
         sqlx::query(
@@ -561,49 +1180,694 @@ impl VoiceTranscriptRepo {
     }
 }
 
-#[cfg(test)]
-pub async fn setup_test_db() -> DbPool {
-    use sqlx::sqlite::SqlitePoolOptions;
-    let pool = SqlitePoolOptions::new()
-        .max_connections(1)
-        .connect("sqlite::memory:")
-        .await
-        .expect("Failed to create in-memory database");
-    init_db(&pool).await.expect("Failed to init database");
-    pool
-}
+/// Database operations for translation corrections
+pub struct TranslationFeedbackRepo;
 
-/// Initialize database with migrations
-pub async fn init_db(pool: &DbPool) -> AppResult<()> {
-    info!("Running database migrations");
+impl TranslationFeedbackRepo {
+    /// Store a correction
+    pub async fn create(
+        pool: &DbPool,
+        feedback: NewTranslationFeedback,
+    ) -> AppResult<TranslationFeedback> {
+        let now = Utc::now();
 
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS guilds (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            guild_id TEXT UNIQUE NOT NULL,
-            name TEXT NOT NULL,
-            default_language TEXT NOT NULL DEFAULT 'en',
-            enabled_channels TEXT NOT NULL DEFAULT '[]',
-            target_languages TEXT NOT NULL DEFAULT '["en"]',
-            subscription_tier TEXT NOT NULL DEFAULT 'free',
-            subscription_expires_at DATETIME,
-            created_at DATETIME NOT NULL,
-            updated_at DATETIME NOT NULL
+        let id = sqlx::query(
+            r#"
+            INSERT INTO translation_feedback (guild_id, channel_id, message_id, user_id, source_lang, target_lang, original_text, machine_translation, correction, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
         )
-        "#,
-    )
-    .execute(pool)
-    .await?;
+        .bind(&feedback.guild_id)
+        .bind(&feedback.channel_id)
+        .bind(&feedback.message_id)
+        .bind(&feedback.user_id)
+        .bind(&feedback.source_lang)
+        .bind(&feedback.target_lang)
+        .bind(&feedback.original_text)
+        .bind(&feedback.machine_translation)
+        .bind(&feedback.correction)
+        .bind(now)
+        .execute(pool)
+        .await?
+        .last_insert_rowid();
 
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS user_preferences (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            user_id TEXT NOT NULL,
-            guild_id TEXT NOT NULL,
-            preferred_language TEXT NOT NULL,
-            auto_translate BOOLEAN NOT NULL DEFAULT true,
+        sqlx::query_as::<_, TranslationFeedback>("SELECT * FROM translation_feedback WHERE id = ?")
+            .bind(id)
+            .fetch_one(pool)
+            .await
+            .map_err(Into::into)
+    }
+
+    /// All corrections for a guild, newest first
+    pub async fn get_by_guild(pool: &DbPool, guild_id: &str) -> AppResult<Vec<TranslationFeedback>> {
+        let feedback = sqlx::query_as::<_, TranslationFeedback>(
+            "SELECT * FROM translation_feedback WHERE guild_id = ? ORDER BY created_at DESC, id DESC",
+        )
+        .bind(guild_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(feedback)
+    }
+}
+
+/// How long posted translations stay linked to their originals for `/feedback`
+pub const TRANSLATION_MESSAGE_RETENTION_DAYS: i64 = 30;
+
+/// Database operations for the messages translations were posted as
+pub struct TranslationMessageRepo;
+
+impl TranslationMessageRepo {
+    /// Remember a posted translation message, dropping records past retention
+    pub async fn record(pool: &DbPool, message: NewTranslationMessage) -> AppResult<()> {
+        let now = Utc::now();
+
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO translation_messages (message_id, guild_id, channel_id, original_channel_id, original_message_id, source_lang, target_lang, translated_text, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&message.message_id)
+        .bind(&message.guild_id)
+        .bind(&message.channel_id)
+        .bind(&message.original_channel_id)
+        .bind(&message.original_message_id)
+        .bind(&message.source_lang)
+        .bind(&message.target_lang)
+        .bind(&message.translated_text)
+        .bind(now)
+        .execute(pool)
+        .await?;
+
+        sqlx::query("DELETE FROM translation_messages WHERE created_at < ?")
+            .bind(now - Duration::days(TRANSLATION_MESSAGE_RETENTION_DAYS))
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// The record for a posted translation message, if it is one
+    pub async fn get(pool: &DbPool, message_id: &str) -> AppResult<Option<TranslationMessage>> {
+        let message = sqlx::query_as::<_, TranslationMessage>(
+            "SELECT * FROM translation_messages WHERE message_id = ?",
+        )
+        .bind(message_id)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(message)
+    }
+}
+
+/// Database operations for stored voice transcripts
+pub struct VoiceHistoryRepo;
+
+impl VoiceHistoryRepo {
+    /// Store one utterance
+    pub async fn insert(pool: &DbPool, entry: NewVoiceTranscriptEntry) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO voice_transcripts (guild_id, voice_channel_id, user_id, username, original_text, translated_text, source_language, target_language, spoken_at, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&entry.guild_id)
+        .bind(&entry.voice_channel_id)
+        .bind(&entry.user_id)
+        .bind(&entry.username)
+        .bind(&entry.original_text)
+        .bind(&entry.translated_text)
+        .bind(&entry.source_language)
+        .bind(&entry.target_language)
+        .bind(entry.spoken_at)
+        .bind(Utc::now())
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Utterances in a voice channel spoken within `[since, until]`, oldest first
+    pub async fn list(
+        pool: &DbPool,
+        guild_id: &str,
+        voice_channel_id: &str,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> AppResult<Vec<VoiceTranscriptEntry>> {
+        let entries = sqlx::query_as::<_, VoiceTranscriptEntry>(
+            r#"
+            SELECT * FROM voice_transcripts
+            WHERE guild_id = ? AND voice_channel_id = ? AND spoken_at >= ? AND spoken_at <= ?
+            ORDER BY spoken_at ASC, id ASC
+            "#,
+        )
+        .bind(guild_id)
+        .bind(voice_channel_id)
+        .bind(since)
+        .bind(until)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(entries)
+    }
+}
+
+/// Database operations for shadow translation comparisons
+pub struct TranslationShadowRepo;
+
+impl TranslationShadowRepo {
+    /// Store one comparison
+    pub async fn insert(pool: &DbPool, entry: NewTranslationShadow) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO translation_shadow (source_lang, target_lang, original_text, primary_text, primary_latency_ms, shadow_provider, shadow_text, shadow_latency_ms, shadow_error, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&entry.source_lang)
+        .bind(&entry.target_lang)
+        .bind(&entry.original_text)
+        .bind(&entry.primary_text)
+        .bind(entry.primary_latency_ms)
+        .bind(&entry.shadow_provider)
+        .bind(&entry.shadow_text)
+        .bind(entry.shadow_latency_ms)
+        .bind(&entry.shadow_error)
+        .bind(Utc::now())
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Most recent comparisons, newest first
+    pub async fn recent(pool: &DbPool, limit: i64) -> AppResult<Vec<TranslationShadow>> {
+        let rows = sqlx::query_as::<_, TranslationShadow>(
+            "SELECT * FROM translation_shadow ORDER BY created_at DESC, id DESC LIMIT ?",
+        )
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows)
+    }
+}
+
+/// Changes remembered per guild for `/setup undo`; older ones are dropped
+pub const CONFIG_HISTORY_DEPTH: i64 = 10;
+
+/// Database operations for undoable guild setting changes
+pub struct ConfigHistoryRepo;
+
+impl ConfigHistoryRepo {
+    /// Set `setting` to `value` (as stored in the `guilds` column), recording
+    /// the value it replaces when that differs
+    async fn record_and_set(
+        pool: &DbPool,
+        guild_id: &str,
+        setting: ConfigSetting,
+        value: &str,
+    ) -> AppResult<()> {
+        let now = Utc::now();
+        let mut tx = pool.begin().await?;
+
+        let previous: Option<String> = sqlx::query_scalar(&format!(
+            "SELECT {} FROM guilds WHERE guild_id = ?",
+            setting.column()
+        ))
+        .bind(guild_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        if let Some(previous) = previous.filter(|previous| previous != value) {
+            sqlx::query(
+                "INSERT INTO config_history (guild_id, setting, previous_value, changed_at) VALUES (?, ?, ?, ?)",
+            )
+            .bind(guild_id)
+            .bind(setting.as_str())
+            .bind(previous)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+            sqlx::query(
+                r#"
+                DELETE FROM config_history WHERE guild_id = ? AND id NOT IN (
+                    SELECT id FROM config_history WHERE guild_id = ? ORDER BY id DESC LIMIT ?
+                )
+                "#,
+            )
+            .bind(guild_id)
+            .bind(guild_id)
+            .bind(CONFIG_HISTORY_DEPTH)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        sqlx::query(&format!(
+            "UPDATE guilds SET {} = ?, updated_at = ? WHERE guild_id = ?",
+            setting.column()
+        ))
+        .bind(value)
+        .bind(now)
+        .bind(guild_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Restore the value the guild's most recent recorded change replaced
+    /// and forget that change; None when there is nothing to undo
+    pub async fn undo_last(pool: &DbPool, guild_id: &str) -> AppResult<Option<ConfigChange>> {
+        let mut tx = pool.begin().await?;
+
+        let change = sqlx::query_as::<_, ConfigChange>(
+            "SELECT * FROM config_history WHERE guild_id = ? ORDER BY id DESC LIMIT 1",
+        )
+        .bind(guild_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(change) = change else {
+            return Ok(None);
+        };
+
+        sqlx::query(&format!(
+            "UPDATE guilds SET {} = ?, updated_at = ? WHERE guild_id = ?",
+            change.setting().column()
+        ))
+        .bind(&change.previous_value)
+        .bind(Utc::now())
+        .bind(guild_id)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query("DELETE FROM config_history WHERE id = ?")
+            .bind(change.id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(Some(change))
+    }
+}
+
+/// Database operations for the saved translation cache
+pub struct TranslationCacheRepo;
+
+impl TranslationCacheRepo {
+    /// Replace the saved cache with `entries` in one transaction
+    pub async fn replace_all(pool: &DbPool, entries: &[PersistedTranslation]) -> AppResult<()> {
+        let mut tx = pool.begin().await?;
+        sqlx::query("DELETE FROM translation_cache").execute(&mut *tx).await?;
+        for entry in entries {
+            sqlx::query(
+                r#"
+                INSERT INTO translation_cache (text, source_lang, target_lang, context, formality, translated_text, cached_at)
+                VALUES (?, ?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(&entry.text)
+            .bind(&entry.source_lang)
+            .bind(&entry.target_lang)
+            .bind(&entry.context)
+            .bind(&entry.formality)
+            .bind(&entry.translated_text)
+            .bind(entry.cached_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Every saved entry
+    pub async fn load_all(pool: &DbPool) -> AppResult<Vec<PersistedTranslation>> {
+        let rows = sqlx::query_as::<_, PersistedTranslation>(
+            "SELECT text, source_lang, target_lang, context, formality, translated_text, cached_at FROM translation_cache",
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows)
+    }
+}
+
+/// Database operations for the saved voice transcription cache
+pub struct VoiceCacheRepo;
+
+impl VoiceCacheRepo {
+    /// Replace the saved cache with `entries`, least recently used first, in
+    /// one transaction
+    pub async fn replace_all(pool: &DbPool, entries: &[PersistedVoiceResult]) -> AppResult<()> {
+        let mut tx = pool.begin().await?;
+        sqlx::query("DELETE FROM voice_transcription_cache").execute(&mut *tx).await?;
+        for entry in entries {
+            sqlx::query(
+                "INSERT INTO voice_transcription_cache (audio_hash, target_language, response) VALUES (?, ?, ?)",
+            )
+            .bind(entry.audio_hash)
+            .bind(&entry.target_language)
+            .bind(&entry.response)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Every saved entry, in the order it was saved
+    pub async fn load_all(pool: &DbPool) -> AppResult<Vec<PersistedVoiceResult>> {
+        let rows = sqlx::query_as::<_, PersistedVoiceResult>(
+            "SELECT audio_hash, target_language, response FROM voice_transcription_cache ORDER BY id",
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows)
+    }
+}
+
+/// Database operations for the users and roles a guild doesn't translate
+pub struct IgnoreListRepo;
+
+impl IgnoreListRepo {
+    /// The guild's ignore list (empty when nothing is ignored)
+    pub async fn get(pool: &DbPool, guild_id: &str) -> AppResult<IgnoreList> {
+        let rows = sqlx::query_as::<_, (String, String)>(
+            "SELECT kind, target_id FROM translation_ignores WHERE guild_id = ? ORDER BY created_at, id",
+        )
+        .bind(guild_id)
+        .fetch_all(pool)
+        .await?;
+
+        let mut list = IgnoreList::default();
+        for (kind, target_id) in rows {
+            match kind.as_str() {
+                "role" => list.roles.push(target_id),
+                _ => list.users.push(target_id),
+            }
+        }
+        Ok(list)
+    }
+
+    /// Ignore a user or role; returns false if it already was
+    pub async fn add(
+        pool: &DbPool,
+        guild_id: &str,
+        kind: IgnoredKind,
+        target_id: &str,
+    ) -> AppResult<bool> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO translation_ignores (guild_id, kind, target_id, created_at)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT(guild_id, kind, target_id) DO NOTHING
+            "#,
+        )
+        .bind(guild_id)
+        .bind(kind.as_str())
+        .bind(target_id)
+        .bind(Utc::now())
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Translate a user or role again; returns false if it wasn't ignored
+    pub async fn remove(
+        pool: &DbPool,
+        guild_id: &str,
+        kind: IgnoredKind,
+        target_id: &str,
+    ) -> AppResult<bool> {
+        let result = sqlx::query(
+            "DELETE FROM translation_ignores WHERE guild_id = ? AND kind = ? AND target_id = ?",
+        )
+        .bind(guild_id)
+        .bind(kind.as_str())
+        .bind(target_id)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+/// Moves configuration from a guild id that no longer exists to a new one
+pub struct GuildMigrationRepo;
+
+impl GuildMigrationRepo {
+    /// Count what is stored for a guild
+    pub async fn summarize(pool: &DbPool, guild_id: &str) -> AppResult<GuildDataSummary> {
+        let (custom_settings, user_preferences, channels, voice_channels, transcript_channels) =
+            sqlx::query_as::<_, (bool, i64, i64, i64, i64)>(
+                r#"
+                SELECT
+                    EXISTS(
+                        SELECT 1 FROM guilds WHERE guild_id = ?1 AND (
+                            default_language != 'en'
+                            OR target_languages != '["en"]'
+                            OR enabled_channels != '[]'
+                            OR translate_only_foreign
+                            OR skip_prefix != '\'
+                            OR command_prefix IS NOT NULL
+                            OR translate_bot_messages
+                            OR translate_webhooks
+                            OR output_mode != 'reply'
+                            OR NOT use_nicknames
+                            OR anonymize_speakers
+                            OR max_voice_session_secs IS NOT NULL
+                            OR translation_context IS NOT NULL
+                            OR formality != 'default'
+                            OR progress_reaction != 'off'
+                        )
+                    ),
+                    (SELECT COUNT(*) FROM user_preferences WHERE guild_id = ?1),
+                    (SELECT COUNT(*) FROM channels WHERE guild_id = ?1),
+                    (SELECT COUNT(*) FROM voice_channel_settings WHERE guild_id = ?1),
+                    (SELECT COUNT(*) FROM voice_transcript_settings WHERE guild_id = ?1)
+                "#,
+            )
+            .bind(guild_id)
+            .fetch_one(pool)
+            .await?;
+
+        Ok(GuildDataSummary {
+            custom_settings,
+            user_preferences,
+            channels,
+            voice_channels,
+            transcript_channels,
+        })
+    }
+
+    /// Copy settings and user preferences from `old_guild_id` to
+    /// `new_guild_id`, overwriting what the new guild has, in one transaction.
+    ///
+    /// Channel settings are keyed by channel id, so only those for channels in
+    /// `current_channels` (the new guild's channels) are moved; the rest stay
+    /// with the old guild id and are counted as skipped.
+    pub async fn migrate(
+        pool: &DbPool,
+        old_guild_id: &str,
+        new_guild_id: &str,
+        new_guild_name: &str,
+        current_channels: &[String],
+    ) -> AppResult<GuildMigrationReport> {
+        let now = Utc::now();
+        let current_json = serde_json::to_string(current_channels).unwrap();
+        let mut report = GuildMigrationReport::default();
+        let mut tx = pool.begin().await?;
+
+        let old_guild = sqlx::query_as::<_, Guild>("SELECT * FROM guilds WHERE guild_id = ?")
+            .bind(old_guild_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+        if let Some(old_guild) = old_guild {
+            let enabled: Vec<String> =
+                serde_json::from_str(&old_guild.enabled_channels).unwrap_or_default();
+            let enabled: Vec<&String> = enabled
+                .iter()
+                .filter(|channel| current_channels.contains(channel))
+                .collect();
+
+            sqlx::query(
+                r#"
+                INSERT INTO guilds (guild_id, name, default_language, enabled_channels, target_languages, subscription_tier, subscription_expires_at, translate_only_foreign, skip_prefix, command_prefix, translate_bot_messages, translate_webhooks, output_mode, use_nicknames, anonymize_speakers, max_voice_session_secs, translation_context, formality, progress_reaction, created_at, updated_at)
+                SELECT ?, ?, default_language, ?, target_languages, subscription_tier, subscription_expires_at, translate_only_foreign, skip_prefix, command_prefix, translate_bot_messages, translate_webhooks, output_mode, use_nicknames, anonymize_speakers, max_voice_session_secs, translation_context, formality, progress_reaction, ?, ?
+                FROM guilds WHERE guild_id = ?
+                ON CONFLICT(guild_id) DO UPDATE SET
+                    default_language = excluded.default_language,
+                    enabled_channels = excluded.enabled_channels,
+                    target_languages = excluded.target_languages,
+                    subscription_tier = excluded.subscription_tier,
+                    subscription_expires_at = excluded.subscription_expires_at,
+                    translate_only_foreign = excluded.translate_only_foreign,
+                    skip_prefix = excluded.skip_prefix,
+                    command_prefix = excluded.command_prefix,
+                    translate_bot_messages = excluded.translate_bot_messages,
+                    translate_webhooks = excluded.translate_webhooks,
+                    output_mode = excluded.output_mode,
+                    use_nicknames = excluded.use_nicknames,
+                    anonymize_speakers = excluded.anonymize_speakers,
+                    max_voice_session_secs = excluded.max_voice_session_secs,
+                    translation_context = excluded.translation_context,
+                    formality = excluded.formality,
+                    progress_reaction = excluded.progress_reaction,
+                    updated_at = excluded.updated_at
+                "#,
+            )
+            .bind(new_guild_id)
+            .bind(new_guild_name)
+            .bind(serde_json::to_string(&enabled).unwrap())
+            .bind(now)
+            .bind(now)
+            .bind(old_guild_id)
+            .execute(&mut *tx)
+            .await?;
+            report.settings = true;
+        }
+
+        report.user_preferences = sqlx::query(
+            r#"
+            INSERT INTO user_preferences (user_id, guild_id, preferred_language, auto_translate, transcript_opt_out, formality, created_at, updated_at)
+            SELECT user_id, ?, preferred_language, auto_translate, transcript_opt_out, formality, ?, ?
+            FROM user_preferences WHERE guild_id = ?
+            ON CONFLICT(user_id, guild_id) DO UPDATE SET
+                preferred_language = excluded.preferred_language,
+                auto_translate = excluded.auto_translate,
+                transcript_opt_out = excluded.transcript_opt_out,
+                formality = excluded.formality,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(new_guild_id)
+        .bind(now)
+        .bind(now)
+        .bind(old_guild_id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        report.channels = sqlx::query(
+            "UPDATE channels SET guild_id = ?, updated_at = ? WHERE guild_id = ? AND channel_id IN (SELECT value FROM json_each(?))",
+        )
+        .bind(new_guild_id)
+        .bind(now)
+        .bind(old_guild_id)
+        .bind(&current_json)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        // The bot isn't in any call in the new guild yet
+        report.voice_channels = sqlx::query(
+            "UPDATE OR REPLACE voice_channel_settings SET guild_id = ?, active = false, updated_at = ? WHERE guild_id = ? AND voice_channel_id IN (SELECT value FROM json_each(?))",
+        )
+        .bind(new_guild_id)
+        .bind(now)
+        .bind(old_guild_id)
+        .bind(&current_json)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        report.transcript_channels = sqlx::query(
+            "UPDATE OR REPLACE voice_transcript_settings SET guild_id = ?, updated_at = ? WHERE guild_id = ? AND voice_channel_id IN (SELECT value FROM json_each(?))",
+        )
+        .bind(new_guild_id)
+        .bind(now)
+        .bind(old_guild_id)
+        .bind(&current_json)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        report.skipped_channels = sqlx::query_scalar(
+            r#"
+            SELECT
+                (SELECT COUNT(*) FROM channels WHERE guild_id = ?1)
+                + (SELECT COUNT(*) FROM voice_channel_settings WHERE guild_id = ?1)
+                + (SELECT COUNT(*) FROM voice_transcript_settings WHERE guild_id = ?1)
+            "#,
+        )
+        .bind(old_guild_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        info!(
+            old_guild_id,
+            new_guild_id,
+            user_preferences = report.user_preferences,
+            skipped_channels = report.skipped_channels,
+            "Migrated guild configuration"
+        );
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+pub async fn setup_test_db() -> DbPool {
+    use sqlx::sqlite::SqlitePoolOptions;
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .expect("Failed to create in-memory database");
+    init_db(&pool).await.expect("Failed to init database");
+    pool
+}
+
+/// Initialize database with migrations
+pub async fn init_db(pool: &DbPool) -> AppResult<()> {
+    info!("Running database migrations");
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS guilds (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            guild_id TEXT UNIQUE NOT NULL,
+            name TEXT NOT NULL,
+            default_language TEXT NOT NULL DEFAULT 'en',
+            enabled_channels TEXT NOT NULL DEFAULT '[]',
+            target_languages TEXT NOT NULL DEFAULT '["en"]',
+            subscription_tier TEXT NOT NULL DEFAULT 'free',
+            subscription_expires_at DATETIME,
+            translate_only_foreign BOOLEAN NOT NULL DEFAULT false,
+            skip_prefix TEXT NOT NULL DEFAULT '\',
+            command_prefix TEXT,
+            translate_bot_messages BOOLEAN NOT NULL DEFAULT false,
+            translate_webhooks BOOLEAN NOT NULL DEFAULT false,
+            output_mode TEXT NOT NULL DEFAULT 'reply',
+            use_nicknames BOOLEAN NOT NULL DEFAULT true,
+            anonymize_speakers BOOLEAN NOT NULL DEFAULT false,
+            max_voice_session_secs INTEGER,
+            onboarded_at DATETIME,
+            translation_context TEXT,
+            formality TEXT NOT NULL DEFAULT 'default',
+            progress_reaction TEXT NOT NULL DEFAULT 'off',
+            created_at DATETIME NOT NULL,
+            updated_at DATETIME NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS user_preferences (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            user_id TEXT NOT NULL,
+            guild_id TEXT NOT NULL,
+            preferred_language TEXT NOT NULL,
+            auto_translate BOOLEAN NOT NULL DEFAULT true,
+            transcript_opt_out BOOLEAN NOT NULL DEFAULT false,
+            formality TEXT NOT NULL DEFAULT 'default',
             created_at DATETIME NOT NULL,
             updated_at DATETIME NOT NULL,
             UNIQUE(user_id, guild_id)
@@ -615,14 +1879,150 @@ pub async fn init_db(pool: &DbPool) -> AppResult<()> {
 
     sqlx::query(
         r#"
-        CREATE TABLE IF NOT EXISTS channels (
+        CREATE TABLE IF NOT EXISTS user_global_preferences (
+            user_id TEXT PRIMARY KEY,
+            preferred_language TEXT NOT NULL,
+            created_at DATETIME NOT NULL,
+            updated_at DATETIME NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS channels (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            channel_id TEXT UNIQUE NOT NULL,
+            guild_id TEXT NOT NULL,
+            enabled BOOLEAN NOT NULL DEFAULT true,
+            target_languages TEXT NOT NULL DEFAULT '[]',
+            allowed_target_languages TEXT NOT NULL DEFAULT '[]',
+            message_filter TEXT,
+            translation_context TEXT,
+            created_at DATETIME NOT NULL,
+            updated_at DATETIME NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS web_sessions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            session_id TEXT UNIQUE NOT NULL,
+            user_id TEXT NOT NULL,
+            guild_id TEXT NOT NULL,
+            channel_id TEXT,
+            expires_at DATETIME NOT NULL,
+            created_at DATETIME NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS voice_channel_settings (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            guild_id TEXT NOT NULL,
+            voice_channel_id TEXT NOT NULL,
+            enabled BOOLEAN NOT NULL DEFAULT true,
+            target_language TEXT NOT NULL DEFAULT 'en',
+            enable_tts BOOLEAN NOT NULL DEFAULT false,
+            created_at DATETIME NOT NULL,
+            updated_at DATETIME NOT NULL,
+            tts_mode TEXT NOT NULL DEFAULT 'off',
+            source_language TEXT NOT NULL DEFAULT 'auto',
+            active BOOLEAN NOT NULL DEFAULT false,
+            formality TEXT NOT NULL DEFAULT 'default',
+            tts_languages TEXT NOT NULL DEFAULT '[]',
+            UNIQUE(guild_id, voice_channel_id)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS voice_transcript_settings (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            guild_id TEXT NOT NULL,
+            voice_channel_id TEXT NOT NULL,
+            text_channel_id TEXT NOT NULL,
+            enabled BOOLEAN NOT NULL DEFAULT true,
+            languages TEXT NOT NULL DEFAULT '["en"]',
+            thread_ids TEXT NOT NULL DEFAULT '{}',
+            tts_languages TEXT NOT NULL DEFAULT '[]',
+            created_at DATETIME NOT NULL,
+            updated_at DATETIME NOT NULL,
+            UNIQUE(guild_id, voice_channel_id)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS translation_feedback (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            guild_id TEXT NOT NULL,
+            channel_id TEXT NOT NULL,
+            message_id TEXT NOT NULL,
+            user_id TEXT NOT NULL,
+            source_lang TEXT NOT NULL,
+            target_lang TEXT NOT NULL,
+            original_text TEXT NOT NULL,
+            machine_translation TEXT NOT NULL,
+            correction TEXT NOT NULL,
+            created_at DATETIME NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS voice_transcripts (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            guild_id TEXT NOT NULL,
+            voice_channel_id TEXT NOT NULL,
+            user_id TEXT NOT NULL,
+            username TEXT NOT NULL,
+            original_text TEXT NOT NULL,
+            translated_text TEXT NOT NULL,
+            source_language TEXT NOT NULL,
+            target_language TEXT NOT NULL,
+            spoken_at DATETIME NOT NULL,
+            created_at DATETIME NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_voice_transcripts_channel ON voice_transcripts (guild_id, voice_channel_id, spoken_at)",
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS translation_ignores (
             id INTEGER PRIMARY KEY AUTOINCREMENT,
-            channel_id TEXT UNIQUE NOT NULL,
             guild_id TEXT NOT NULL,
-            enabled BOOLEAN NOT NULL DEFAULT true,
-            target_languages TEXT NOT NULL DEFAULT '[]',
+            kind TEXT NOT NULL,
+            target_id TEXT NOT NULL,
             created_at DATETIME NOT NULL,
-            updated_at DATETIME NOT NULL
+            UNIQUE(guild_id, kind, target_id)
         )
         "#,
     )
@@ -631,13 +2031,17 @@ pub async fn init_db(pool: &DbPool) -> AppResult<()> {
 
     sqlx::query(
         r#"
-        CREATE TABLE IF NOT EXISTS web_sessions (
+        CREATE TABLE IF NOT EXISTS translation_shadow (
             id INTEGER PRIMARY KEY AUTOINCREMENT,
-            session_id TEXT UNIQUE NOT NULL,
-            user_id TEXT NOT NULL,
-            guild_id TEXT NOT NULL,
-            channel_id TEXT,
-            expires_at DATETIME NOT NULL,
+            source_lang TEXT NOT NULL,
+            target_lang TEXT NOT NULL,
+            original_text TEXT NOT NULL,
+            primary_text TEXT NOT NULL,
+            primary_latency_ms INTEGER NOT NULL,
+            shadow_provider TEXT NOT NULL,
+            shadow_text TEXT,
+            shadow_latency_ms INTEGER NOT NULL,
+            shadow_error TEXT,
             created_at DATETIME NOT NULL
         )
         "#,
@@ -647,16 +2051,12 @@ pub async fn init_db(pool: &DbPool) -> AppResult<()> {
 
     sqlx::query(
         r#"
-        CREATE TABLE IF NOT EXISTS voice_channel_settings (
+        CREATE TABLE IF NOT EXISTS config_history (
             id INTEGER PRIMARY KEY AUTOINCREMENT,
             guild_id TEXT NOT NULL,
-            voice_channel_id TEXT NOT NULL,
-            enabled BOOLEAN NOT NULL DEFAULT true,
-            target_language TEXT NOT NULL DEFAULT 'en',
-            enable_tts BOOLEAN NOT NULL DEFAULT false,
-            created_at DATETIME NOT NULL,
-            updated_at DATETIME NOT NULL,
-            UNIQUE(guild_id, voice_channel_id)
+            setting TEXT NOT NULL,
+            previous_value TEXT NOT NULL,
+            changed_at DATETIME NOT NULL
         )
         "#,
     )
@@ -665,23 +2065,156 @@ pub async fn init_db(pool: &DbPool) -> AppResult<()> {
 
     sqlx::query(
         r#"
-        CREATE TABLE IF NOT EXISTS voice_transcript_settings (
+        CREATE TABLE IF NOT EXISTS translation_cache (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            text TEXT NOT NULL,
+            source_lang TEXT NOT NULL,
+            target_lang TEXT NOT NULL,
+            context TEXT,
+            formality TEXT NOT NULL DEFAULT 'default',
+            translated_text TEXT NOT NULL,
+            cached_at DATETIME NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS voice_transcription_cache (
             id INTEGER PRIMARY KEY AUTOINCREMENT,
+            audio_hash INTEGER NOT NULL,
+            target_language TEXT NOT NULL,
+            response TEXT NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS translation_messages (
+            message_id TEXT PRIMARY KEY,
             guild_id TEXT NOT NULL,
-            voice_channel_id TEXT NOT NULL,
-            text_channel_id TEXT NOT NULL,
-            enabled BOOLEAN NOT NULL DEFAULT true,
-            languages TEXT NOT NULL DEFAULT '["en"]',
-            thread_ids TEXT NOT NULL DEFAULT '{}',
-            created_at DATETIME NOT NULL,
-            updated_at DATETIME NOT NULL,
-            UNIQUE(guild_id, voice_channel_id)
+            channel_id TEXT NOT NULL,
+            original_channel_id TEXT NOT NULL,
+            original_message_id TEXT NOT NULL,
+            source_lang TEXT NOT NULL,
+            target_lang TEXT NOT NULL,
+            translated_text TEXT NOT NULL,
+            created_at DATETIME NOT NULL
         )
         "#,
     )
     .execute(pool)
     .await?;
 
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_translation_messages_created ON translation_messages (created_at)",
+    )
+    .execute(pool)
+    .await?;
+
+    // Columns added after the initial schema; existing databases need them backfilled
+    add_column_if_missing(
+        pool,
+        "guilds",
+        "translate_only_foreign",
+        "BOOLEAN NOT NULL DEFAULT false",
+    )
+    .await?;
+    add_column_if_missing(pool, "guilds", "skip_prefix", "TEXT NOT NULL DEFAULT '\\'").await?;
+    add_column_if_missing(pool, "guilds", "command_prefix", "TEXT").await?;
+    add_column_if_missing(
+        pool,
+        "guilds",
+        "translate_bot_messages",
+        "BOOLEAN NOT NULL DEFAULT false",
+    )
+    .await?;
+    add_column_if_missing(
+        pool,
+        "guilds",
+        "translate_webhooks",
+        "BOOLEAN NOT NULL DEFAULT false",
+    )
+    .await?;
+    add_column_if_missing(pool, "guilds", "output_mode", "TEXT NOT NULL DEFAULT 'reply'").await?;
+    add_column_if_missing(pool, "guilds", "use_nicknames", "BOOLEAN NOT NULL DEFAULT true").await?;
+    add_column_if_missing(pool, "guilds", "anonymize_speakers", "BOOLEAN NOT NULL DEFAULT false").await?;
+    add_column_if_missing(pool, "guilds", "max_voice_session_secs", "INTEGER").await?;
+    add_column_if_missing(pool, "guilds", "onboarded_at", "DATETIME").await?;
+    add_column_if_missing(pool, "guilds", "translation_context", "TEXT").await?;
+    add_column_if_missing(pool, "guilds", "formality", "TEXT NOT NULL DEFAULT 'default'").await?;
+    add_column_if_missing(pool, "guilds", "progress_reaction", "TEXT NOT NULL DEFAULT 'off'").await?;
+    add_column_if_missing(pool, "channels", "allowed_target_languages", "TEXT NOT NULL DEFAULT '[]'").await?;
+    add_column_if_missing(pool, "channels", "message_filter", "TEXT").await?;
+    add_column_if_missing(pool, "channels", "translation_context", "TEXT").await?;
+    add_column_if_missing(
+        pool,
+        "user_preferences",
+        "transcript_opt_out",
+        "BOOLEAN NOT NULL DEFAULT false",
+    )
+    .await?;
+    add_column_if_missing(
+        pool,
+        "voice_channel_settings",
+        "tts_mode",
+        "TEXT NOT NULL DEFAULT 'off'",
+    )
+    .await?;
+    add_column_if_missing(
+        pool,
+        "voice_channel_settings",
+        "source_language",
+        "TEXT NOT NULL DEFAULT 'auto'",
+    )
+    .await?;
+    add_column_if_missing(
+        pool,
+        "voice_channel_settings",
+        "active",
+        "BOOLEAN NOT NULL DEFAULT false",
+    )
+    .await?;
+    add_column_if_missing(
+        pool,
+        "voice_channel_settings",
+        "formality",
+        "TEXT NOT NULL DEFAULT 'default'",
+    )
+    .await?;
+    add_column_if_missing(
+        pool,
+        "voice_channel_settings",
+        "tts_languages",
+        "TEXT NOT NULL DEFAULT '[]'",
+    )
+    .await?;
+    add_column_if_missing(
+        pool,
+        "user_preferences",
+        "formality",
+        "TEXT NOT NULL DEFAULT 'default'",
+    )
+    .await?;
+    add_column_if_missing(
+        pool,
+        "voice_transcript_settings",
+        "tts_languages",
+        "TEXT NOT NULL DEFAULT '[]'",
+    )
+    .await?;
+    // Before tts_mode existed, enable_tts meant playback everywhere
+    sqlx::query(
+        "UPDATE voice_channel_settings SET tts_mode = 'both' WHERE enable_tts = true AND tts_mode = 'off'",
+    )
+    .execute(pool)
+    .await?;
+
     // Create indexes
     sqlx::query("CREATE INDEX IF NOT EXISTS idx_guilds_guild_id ON guilds(guild_id)")
         .execute(pool)
@@ -700,57 +2233,346 @@ pub async fn init_db(pool: &DbPool) -> AppResult<()> {
     sqlx::query("CREATE INDEX IF NOT EXISTS idx_voice_transcript_guild ON voice_transcript_settings(guild_id)")
         .execute(pool)
         .await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_config_history_guild ON config_history(guild_id, id)")
+        .execute(pool)
+        .await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_feedback_guild ON translation_feedback(guild_id)")
+        .execute(pool)
+        .await?;
 
     info!("Database migrations complete");
     Ok(())
 }
 
+/// Add a column to an existing table unless it is already present
+async fn add_column_if_missing(
+    pool: &DbPool,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> AppResult<()> {
+    let exists: bool = sqlx::query_scalar(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info(?) WHERE name = ?",
+    )
+    .bind(table)
+    .bind(column)
+    .fetch_one(pool)
+    .await?;
+
+    if !exists {
+        info!("Adding column {}.{}", table, column);
+        sqlx::query(&format!(
+            "ALTER TABLE {} ADD COLUMN {} {}",
+            table, column, definition
+        ))
+        .execute(pool)
+        .await?;
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    // --- GuildRepo tests ---
+    // --- GuildRepo tests ---
+
+    #[tokio::test]
+    async fn test_guild_upsert_creates_new() {
+        let pool = setup_test_db().await;
+        let new_guild = NewGuild {
+            guild_id: "g123".to_string(),
+            name: "Test Guild".to_string(),
+        };
+
+        let guild = GuildRepo::upsert(&pool, new_guild).await.unwrap();
+        assert_eq!(guild.guild_id, "g123");
+        assert_eq!(guild.name, "Test Guild");
+        assert_eq!(guild.default_language, "en");
+    }
+
+    #[tokio::test]
+    async fn test_guild_upsert_updates_existing() {
+        let pool = setup_test_db().await;
+        let new_guild = NewGuild {
+            guild_id: "g123".to_string(),
+            name: "Original Name".to_string(),
+        };
+        GuildRepo::upsert(&pool, new_guild).await.unwrap();
+
+        let updated = NewGuild {
+            guild_id: "g123".to_string(),
+            name: "Updated Name".to_string(),
+        };
+        let guild = GuildRepo::upsert(&pool, updated).await.unwrap();
+        assert_eq!(guild.name, "Updated Name");
+    }
+
+    #[tokio::test]
+    async fn test_guild_mark_onboarded_only_once() {
+        let pool = setup_test_db().await;
+        let new_guild = NewGuild {
+            guild_id: "g123".to_string(),
+            name: "Test".to_string(),
+        };
+        GuildRepo::upsert(&pool, new_guild).await.unwrap();
+
+        assert!(GuildRepo::mark_onboarded(&pool, "g123").await.unwrap());
+        assert!(!GuildRepo::mark_onboarded(&pool, "g123").await.unwrap());
+        assert!(!GuildRepo::mark_onboarded(&pool, "unknown").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_guild_get_nonexistent_returns_none() {
+        let pool = setup_test_db().await;
+        let result = GuildRepo::get_by_guild_id(&pool, "nonexistent").await.unwrap();
+        assert!(result.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_guild_set_default_language() {
+        let pool = setup_test_db().await;
+        let new_guild = NewGuild {
+            guild_id: "g123".to_string(),
+            name: "Test".to_string(),
+        };
+        GuildRepo::upsert(&pool, new_guild).await.unwrap();
+
+        GuildRepo::set_default_language(&pool, "g123", "es").await.unwrap();
+        let guild = GuildRepo::get_by_guild_id(&pool, "g123").await.unwrap().unwrap();
+        assert_eq!(guild.default_language, "es");
+    }
+
+    #[tokio::test]
+    async fn test_guild_set_target_languages() {
+        let pool = setup_test_db().await;
+        let new_guild = NewGuild {
+            guild_id: "g123".to_string(),
+            name: "Test".to_string(),
+        };
+        GuildRepo::upsert(&pool, new_guild).await.unwrap();
+
+        let langs = vec!["en".to_string(), "es".to_string(), "fr".to_string()];
+        GuildRepo::set_target_languages(&pool, "g123", &langs).await.unwrap();
+
+        let guild = GuildRepo::get_by_guild_id(&pool, "g123").await.unwrap().unwrap();
+        let stored_langs: Vec<String> = serde_json::from_str(&guild.target_languages).unwrap();
+        assert_eq!(stored_langs, langs);
+    }
+
+    #[tokio::test]
+    async fn test_list_guild_footprints() {
+        let pool = setup_test_db().await;
+        for (id, name) in [("g1", "One"), ("g2", "Two"), ("g3", "Three")] {
+            let new_guild = NewGuild {
+                guild_id: id.to_string(),
+                name: name.to_string(),
+            };
+            GuildRepo::upsert(&pool, new_guild).await.unwrap();
+        }
+        GuildRepo::enable_channel(&pool, "g2", "c1").await.unwrap();
+        GuildRepo::enable_channel(&pool, "g2", "c2").await.unwrap();
+        let voice = NewVoiceChannelSettings {
+            guild_id: "g2".to_string(),
+            voice_channel_id: "v1".to_string(),
+            target_language: "en".to_string(),
+            tts_mode: TtsMode::Off,
+        };
+        VoiceChannelRepo::upsert(&pool, voice).await.unwrap();
+        VoiceChannelRepo::set_active_channel(&pool, "g2", Some("v1")).await.unwrap();
+
+        assert_eq!(GuildRepo::count(&pool).await.unwrap(), 3);
+        let page = GuildRepo::list_footprints(&pool, 2, 1).await.unwrap();
+        assert_eq!(page.len(), 2);
+        assert_eq!(page[0].guild_id, "g2");
+        assert_eq!(page[0].enabled_channels, 2);
+        assert_eq!(page[0].active_voice_sessions, 1);
+        assert_eq!(page[1].guild_id, "g3");
+        assert_eq!(page[1].enabled_channels, 0);
+        assert_eq!(page[1].active_voice_sessions, 0);
+    }
+
+    #[tokio::test]
+    async fn test_config_history_undo() {
+        let pool = setup_test_db().await;
+        let new_guild = NewGuild {
+            guild_id: "g123".to_string(),
+            name: "Test".to_string(),
+        };
+        GuildRepo::upsert(&pool, new_guild).await.unwrap();
+        assert!(ConfigHistoryRepo::undo_last(&pool, "g123").await.unwrap().is_none());
+
+        let before = GuildRepo::get_by_guild_id(&pool, "g123").await.unwrap().unwrap();
+        GuildRepo::set_target_languages(&pool, "g123", &["de".to_string()]).await.unwrap();
+        GuildRepo::set_default_language(&pool, "g123", "fr").await.unwrap();
+        // Setting the same value again isn't a change to undo
+        GuildRepo::set_default_language(&pool, "g123", "fr").await.unwrap();
+
+        let change = ConfigHistoryRepo::undo_last(&pool, "g123").await.unwrap().unwrap();
+        assert_eq!(change.setting(), ConfigSetting::DefaultLanguage);
+        let guild = GuildRepo::get_by_guild_id(&pool, "g123").await.unwrap().unwrap();
+        assert_eq!(guild.default_language, before.default_language);
+        assert_eq!(guild.target_languages, r#"["de"]"#);
+
+        let change = ConfigHistoryRepo::undo_last(&pool, "g123").await.unwrap().unwrap();
+        assert_eq!(change.setting(), ConfigSetting::TargetLanguages);
+        let guild = GuildRepo::get_by_guild_id(&pool, "g123").await.unwrap().unwrap();
+        assert_eq!(guild.target_languages, before.target_languages);
+        assert!(ConfigHistoryRepo::undo_last(&pool, "g123").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_config_history_depth_is_limited() {
+        let pool = setup_test_db().await;
+        let new_guild = NewGuild {
+            guild_id: "g123".to_string(),
+            name: "Test".to_string(),
+        };
+        GuildRepo::upsert(&pool, new_guild).await.unwrap();
+        for i in 0..CONFIG_HISTORY_DEPTH + 5 {
+            GuildRepo::set_default_language(&pool, "g123", &format!("l{}", i)).await.unwrap();
+        }
+
+        let mut undone = 0;
+        while ConfigHistoryRepo::undo_last(&pool, "g123").await.unwrap().is_some() {
+            undone += 1;
+        }
+        assert_eq!(undone, CONFIG_HISTORY_DEPTH);
+        let guild = GuildRepo::get_by_guild_id(&pool, "g123").await.unwrap().unwrap();
+        assert_eq!(guild.default_language, "l4");
+    }
+
+    #[tokio::test]
+    async fn test_guild_enable_channel() {
+        let pool = setup_test_db().await;
+        let new_guild = NewGuild {
+            guild_id: "g123".to_string(),
+            name: "Test".to_string(),
+        };
+        GuildRepo::upsert(&pool, new_guild).await.unwrap();
+
+        GuildRepo::enable_channel(&pool, "g123", "ch456").await.unwrap();
+        assert!(GuildRepo::is_channel_enabled(&pool, "g123", "ch456").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_guild_disable_channel() {
+        let pool = setup_test_db().await;
+        let new_guild = NewGuild {
+            guild_id: "g123".to_string(),
+            name: "Test".to_string(),
+        };
+        GuildRepo::upsert(&pool, new_guild).await.unwrap();
+
+        GuildRepo::enable_channel(&pool, "g123", "ch456").await.unwrap();
+        GuildRepo::disable_channel(&pool, "g123", "ch456").await.unwrap();
+        assert!(!GuildRepo::is_channel_enabled(&pool, "g123", "ch456").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_guild_enable_channel_idempotent() {
+        let pool = setup_test_db().await;
+        let new_guild = NewGuild {
+            guild_id: "g123".to_string(),
+            name: "Test".to_string(),
+        };
+        GuildRepo::upsert(&pool, new_guild).await.unwrap();
+
+        GuildRepo::enable_channel(&pool, "g123", "ch456").await.unwrap();
+        GuildRepo::enable_channel(&pool, "g123", "ch456").await.unwrap();
+
+        let guild = GuildRepo::get_by_guild_id(&pool, "g123").await.unwrap().unwrap();
+        let channels: Vec<String> = serde_json::from_str(&guild.enabled_channels).unwrap();
+        assert_eq!(channels.len(), 1);
+    }
 
     #[tokio::test]
-    async fn test_guild_upsert_creates_new() {
+    async fn test_guild_channel_not_enabled_by_default() {
         let pool = setup_test_db().await;
         let new_guild = NewGuild {
             guild_id: "g123".to_string(),
-            name: "Test Guild".to_string(),
+            name: "Test".to_string(),
         };
-
-        let guild = GuildRepo::upsert(&pool, new_guild).await.unwrap();
-        assert_eq!(guild.guild_id, "g123");
-        assert_eq!(guild.name, "Test Guild");
-        assert_eq!(guild.default_language, "en");
+        GuildRepo::upsert(&pool, new_guild).await.unwrap();
+        assert!(!GuildRepo::is_channel_enabled(&pool, "g123", "ch456").await.unwrap());
     }
 
     #[tokio::test]
-    async fn test_guild_upsert_updates_existing() {
+    async fn test_guild_get_settings() {
         let pool = setup_test_db().await;
         let new_guild = NewGuild {
             guild_id: "g123".to_string(),
-            name: "Original Name".to_string(),
+            name: "Test".to_string(),
         };
         GuildRepo::upsert(&pool, new_guild).await.unwrap();
 
-        let updated = NewGuild {
+        let settings = GuildRepo::get_settings(&pool, "g123").await.unwrap();
+        assert!(settings.is_some());
+        let s = settings.unwrap();
+        assert_eq!(s.guild_id, "g123");
+        assert_eq!(s.default_language, "en");
+    }
+
+    #[tokio::test]
+    async fn test_guild_translate_only_foreign_toggle() {
+        let pool = setup_test_db().await;
+        let new_guild = NewGuild {
             guild_id: "g123".to_string(),
-            name: "Updated Name".to_string(),
+            name: "Test".to_string(),
         };
-        let guild = GuildRepo::upsert(&pool, updated).await.unwrap();
-        assert_eq!(guild.name, "Updated Name");
+        let guild = GuildRepo::upsert(&pool, new_guild).await.unwrap();
+        assert!(!guild.translate_only_foreign);
+
+        GuildRepo::set_translate_only_foreign(&pool, "g123", true).await.unwrap();
+        let settings = GuildRepo::get_settings(&pool, "g123").await.unwrap().unwrap();
+        assert!(settings.translate_only_foreign);
     }
 
     #[tokio::test]
-    async fn test_guild_get_nonexistent_returns_none() {
-        let pool = setup_test_db().await;
-        let result = GuildRepo::get_by_guild_id(&pool, "nonexistent").await.unwrap();
-        assert!(result.is_none());
+    async fn test_init_db_adds_missing_guild_columns() {
+        use sqlx::sqlite::SqlitePoolOptions;
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        // Guilds table as created before translate_only_foreign existed
+        sqlx::query(
+            r#"
+            CREATE TABLE guilds (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                guild_id TEXT UNIQUE NOT NULL,
+                name TEXT NOT NULL,
+                default_language TEXT NOT NULL DEFAULT 'en',
+                enabled_channels TEXT NOT NULL DEFAULT '[]',
+                target_languages TEXT NOT NULL DEFAULT '["en"]',
+                subscription_tier TEXT NOT NULL DEFAULT 'free',
+                subscription_expires_at DATETIME,
+                created_at DATETIME NOT NULL,
+                updated_at DATETIME NOT NULL
+            )
+            "#,
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        init_db(&pool).await.unwrap();
+        // Running again must be a no-op
+        init_db(&pool).await.unwrap();
+
+        let new_guild = NewGuild {
+            guild_id: "g1".to_string(),
+            name: "Legacy".to_string(),
+        };
+        let guild = GuildRepo::upsert(&pool, new_guild).await.unwrap();
+        assert!(!guild.translate_only_foreign);
+        assert_eq!(guild.skip_prefix, "\\");
     }
 
     #[tokio::test]
-    async fn test_guild_set_default_language() {
+    async fn test_guild_automated_sources() {
         let pool = setup_test_db().await;
         let new_guild = NewGuild {
             guild_id: "g123".to_string(),
@@ -758,97 +2580,168 @@ mod tests {
         };
         GuildRepo::upsert(&pool, new_guild).await.unwrap();
 
-        GuildRepo::set_default_language(&pool, "g123", "es").await.unwrap();
-        let guild = GuildRepo::get_by_guild_id(&pool, "g123").await.unwrap().unwrap();
-        assert_eq!(guild.default_language, "es");
+        let settings = GuildRepo::get_settings(&pool, "g123").await.unwrap().unwrap();
+        assert!(!settings.translate_bot_messages);
+        assert!(!settings.translate_webhooks);
+
+        GuildRepo::set_automated_sources(&pool, "g123", false, true).await.unwrap();
+        let settings = GuildRepo::get_settings(&pool, "g123").await.unwrap().unwrap();
+        assert!(!settings.translate_bot_messages);
+        assert!(settings.translate_webhooks);
     }
 
     #[tokio::test]
-    async fn test_guild_set_target_languages() {
+    async fn test_guild_use_nicknames() {
         let pool = setup_test_db().await;
         let new_guild = NewGuild {
             guild_id: "g123".to_string(),
             name: "Test".to_string(),
         };
         GuildRepo::upsert(&pool, new_guild).await.unwrap();
+        assert!(GuildRepo::get_settings(&pool, "g123").await.unwrap().unwrap().use_nicknames);
 
-        let langs = vec!["en".to_string(), "es".to_string(), "fr".to_string()];
-        GuildRepo::set_target_languages(&pool, "g123", &langs).await.unwrap();
-
-        let guild = GuildRepo::get_by_guild_id(&pool, "g123").await.unwrap().unwrap();
-        let stored_langs: Vec<String> = serde_json::from_str(&guild.target_languages).unwrap();
-        assert_eq!(stored_langs, langs);
+        GuildRepo::set_use_nicknames(&pool, "g123", false).await.unwrap();
+        assert!(!GuildRepo::get_settings(&pool, "g123").await.unwrap().unwrap().use_nicknames);
     }
 
     #[tokio::test]
-    async fn test_guild_enable_channel() {
+    async fn test_guild_anonymize_speakers() {
         let pool = setup_test_db().await;
         let new_guild = NewGuild {
             guild_id: "g123".to_string(),
             name: "Test".to_string(),
         };
         GuildRepo::upsert(&pool, new_guild).await.unwrap();
+        assert!(!GuildRepo::get_settings(&pool, "g123").await.unwrap().unwrap().anonymize_speakers);
 
-        GuildRepo::enable_channel(&pool, "g123", "ch456").await.unwrap();
-        assert!(GuildRepo::is_channel_enabled(&pool, "g123", "ch456").await.unwrap());
+        GuildRepo::set_anonymize_speakers(&pool, "g123", true).await.unwrap();
+        assert!(GuildRepo::get_settings(&pool, "g123").await.unwrap().unwrap().anonymize_speakers);
     }
 
     #[tokio::test]
-    async fn test_guild_disable_channel() {
+    async fn test_guild_max_voice_session_override() {
         let pool = setup_test_db().await;
         let new_guild = NewGuild {
             guild_id: "g123".to_string(),
             name: "Test".to_string(),
         };
         GuildRepo::upsert(&pool, new_guild).await.unwrap();
+        let settings = || async { GuildRepo::get_settings(&pool, "g123").await.unwrap().unwrap() };
+        assert_eq!(settings().await.max_voice_session_secs, None);
 
-        GuildRepo::enable_channel(&pool, "g123", "ch456").await.unwrap();
-        GuildRepo::disable_channel(&pool, "g123", "ch456").await.unwrap();
-        assert!(!GuildRepo::is_channel_enabled(&pool, "g123", "ch456").await.unwrap());
+        GuildRepo::set_max_voice_session_secs(&pool, "g123", Some(7200)).await.unwrap();
+        assert_eq!(settings().await.max_voice_session_secs, Some(7200));
+        GuildRepo::set_max_voice_session_secs(&pool, "g123", None).await.unwrap();
+        assert_eq!(settings().await.max_voice_session_secs, None);
     }
 
     #[tokio::test]
-    async fn test_guild_enable_channel_idempotent() {
+    async fn test_channel_allowed_languages() {
+        let pool = setup_test_db().await;
+        assert!(ChannelRepo::get_allowed_languages(&pool, "ch1").await.unwrap().is_empty());
+
+        let allowed = vec!["en".to_string(), "fr".to_string()];
+        ChannelRepo::set_allowed_languages(&pool, "g1", "ch1", &allowed).await.unwrap();
+        assert_eq!(ChannelRepo::get_allowed_languages(&pool, "ch1").await.unwrap(), allowed);
+
+        ChannelRepo::set_allowed_languages(&pool, "g1", "ch1", &[]).await.unwrap();
+        assert!(ChannelRepo::get_allowed_languages(&pool, "ch1").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_channel_message_filter() {
+        let pool = setup_test_db().await;
+        assert_eq!(ChannelRepo::get_message_filter(&pool, "ch1").await.unwrap(), None);
+
+        ChannelRepo::set_message_filter(&pool, "g1", "ch1", Some(r"\?\s*$")).await.unwrap();
+        assert_eq!(
+            ChannelRepo::get_message_filter(&pool, "ch1").await.unwrap().as_deref(),
+            Some(r"\?\s*$")
+        );
+
+        // Setting one leaves the channel's other settings alone
+        let allowed = vec!["fr".to_string()];
+        ChannelRepo::set_allowed_languages(&pool, "g1", "ch1", &allowed).await.unwrap();
+        assert!(ChannelRepo::get_message_filter(&pool, "ch1").await.unwrap().is_some());
+
+        ChannelRepo::set_message_filter(&pool, "g1", "ch1", None).await.unwrap();
+        assert_eq!(ChannelRepo::get_message_filter(&pool, "ch1").await.unwrap(), None);
+        assert_eq!(ChannelRepo::get_allowed_languages(&pool, "ch1").await.unwrap(), allowed);
+    }
+
+    #[tokio::test]
+    async fn test_translation_context_guild_and_channel() {
         let pool = setup_test_db().await;
         let new_guild = NewGuild {
-            guild_id: "g123".to_string(),
+            guild_id: "g1".to_string(),
             name: "Test".to_string(),
         };
         GuildRepo::upsert(&pool, new_guild).await.unwrap();
+        let settings = || async { GuildRepo::get_settings(&pool, "g1").await.unwrap().unwrap() };
+        assert_eq!(settings().await.translation_context, None);
+
+        GuildRepo::set_translation_context(&pool, "g1", Some("casual gaming chat")).await.unwrap();
+        assert_eq!(settings().await.translation_context.as_deref(), Some("casual gaming chat"));
+
+        ChannelRepo::set_message_filter(&pool, "g1", "ch1", Some("x")).await.unwrap();
+        ChannelRepo::set_translation_context(&pool, "g1", "ch1", Some("support desk")).await.unwrap();
+        assert_eq!(
+            ChannelRepo::get_translation_context(&pool, "ch1").await.unwrap().as_deref(),
+            Some("support desk")
+        );
+        assert!(ChannelRepo::get_message_filter(&pool, "ch1").await.unwrap().is_some());
+
+        GuildRepo::set_translation_context(&pool, "g1", None).await.unwrap();
+        ChannelRepo::set_translation_context(&pool, "g1", "ch1", None).await.unwrap();
+        assert_eq!(settings().await.translation_context, None);
+        assert_eq!(ChannelRepo::get_translation_context(&pool, "ch1").await.unwrap(), None);
+    }
 
-        GuildRepo::enable_channel(&pool, "g123", "ch456").await.unwrap();
-        GuildRepo::enable_channel(&pool, "g123", "ch456").await.unwrap();
+    #[tokio::test]
+    async fn test_guild_output_mode() {
+        let pool = setup_test_db().await;
+        let new_guild = NewGuild {
+            guild_id: "g123".to_string(),
+            name: "Test".to_string(),
+        };
+        GuildRepo::upsert(&pool, new_guild).await.unwrap();
+        let settings = GuildRepo::get_settings(&pool, "g123").await.unwrap().unwrap();
+        assert_eq!(settings.output_mode, OutputMode::Reply);
 
-        let guild = GuildRepo::get_by_guild_id(&pool, "g123").await.unwrap().unwrap();
-        let channels: Vec<String> = serde_json::from_str(&guild.enabled_channels).unwrap();
-        assert_eq!(channels.len(), 1);
+        GuildRepo::set_output_mode(&pool, "g123", OutputMode::Webhook).await.unwrap();
+        let settings = GuildRepo::get_settings(&pool, "g123").await.unwrap().unwrap();
+        assert_eq!(settings.output_mode, OutputMode::Webhook);
     }
 
     #[tokio::test]
-    async fn test_guild_channel_not_enabled_by_default() {
+    async fn test_guild_progress_reaction() {
         let pool = setup_test_db().await;
         let new_guild = NewGuild {
             guild_id: "g123".to_string(),
             name: "Test".to_string(),
         };
         GuildRepo::upsert(&pool, new_guild).await.unwrap();
-        assert!(!GuildRepo::is_channel_enabled(&pool, "g123", "ch456").await.unwrap());
+        let settings = GuildRepo::get_settings(&pool, "g123").await.unwrap().unwrap();
+        assert_eq!(settings.progress_reaction, ProgressReaction::Off);
+
+        GuildRepo::set_progress_reaction(&pool, "g123", ProgressReaction::Outcome).await.unwrap();
+        let settings = GuildRepo::get_settings(&pool, "g123").await.unwrap().unwrap();
+        assert_eq!(settings.progress_reaction, ProgressReaction::Outcome);
     }
 
     #[tokio::test]
-    async fn test_guild_get_settings() {
+    async fn test_guild_set_skip_prefix() {
         let pool = setup_test_db().await;
         let new_guild = NewGuild {
             guild_id: "g123".to_string(),
             name: "Test".to_string(),
         };
-        GuildRepo::upsert(&pool, new_guild).await.unwrap();
+        let guild = GuildRepo::upsert(&pool, new_guild).await.unwrap();
+        assert_eq!(guild.skip_prefix, "\\");
 
-        let settings = GuildRepo::get_settings(&pool, "g123").await.unwrap();
-        assert!(settings.is_some());
-        let s = settings.unwrap();
-        assert_eq!(s.guild_id, "g123");
-        assert_eq!(s.default_language, "en");
+        GuildRepo::set_skip_prefix(&pool, "g123", "no-translate").await.unwrap();
+        let settings = GuildRepo::get_settings(&pool, "g123").await.unwrap().unwrap();
+        assert_eq!(settings.skip_prefix, "no-translate");
     }
 
     // --- UserPreferenceRepo tests ---
@@ -892,6 +2785,81 @@ mod tests {
         assert!(!pref.auto_translate);
     }
 
+    #[tokio::test]
+    async fn test_transcript_opt_out() {
+        let pool = setup_test_db().await;
+        UserPreferenceRepo::set_language(&pool, "u1", "g1", "es").await.unwrap();
+        UserPreferenceRepo::set_transcript_opt_out(&pool, "u1", "g1", true).await.unwrap();
+        // Users without preferences can opt out too
+        UserPreferenceRepo::set_transcript_opt_out(&pool, "u2", "g1", true).await.unwrap();
+        UserPreferenceRepo::set_transcript_opt_out(&pool, "u3", "g2", true).await.unwrap();
+
+        let mut opted_out = UserPreferenceRepo::transcript_opt_outs(&pool, "g1").await.unwrap();
+        opted_out.sort();
+        assert_eq!(opted_out, vec!["u1", "u2"]);
+
+        // Opting out keeps the language preference
+        let pref = UserPreferenceRepo::get(&pool, "u1", "g1").await.unwrap().unwrap();
+        assert_eq!(pref.preferred_language, "es");
+        assert!(pref.transcript_opt_out);
+
+        UserPreferenceRepo::set_transcript_opt_out(&pool, "u1", "g1", false).await.unwrap();
+        assert_eq!(
+            UserPreferenceRepo::transcript_opt_outs(&pool, "g1").await.unwrap(),
+            vec!["u2"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_global_preference_is_independent_of_guilds() {
+        let pool = setup_test_db().await;
+        UserPreferenceRepo::set_language(&pool, "u1", "g1", "es").await.unwrap();
+        assert!(UserGlobalPreferenceRepo::get(&pool, "u1").await.unwrap().is_none());
+
+        UserGlobalPreferenceRepo::set_language(&pool, "u1", "de").await.unwrap();
+        UserGlobalPreferenceRepo::set_language(&pool, "u1", "ja").await.unwrap();
+        let pref = UserGlobalPreferenceRepo::get(&pool, "u1").await.unwrap().unwrap();
+        assert_eq!(pref.preferred_language, "ja");
+
+        let guild_pref = UserPreferenceRepo::get(&pool, "u1", "g1").await.unwrap().unwrap();
+        assert_eq!(guild_pref.preferred_language, "es");
+    }
+
+    // --- VoiceHistoryRepo tests ---
+
+    #[tokio::test]
+    async fn test_voice_history_range() {
+        let pool = setup_test_db().await;
+        let start = Utc::now() - Duration::hours(2);
+        for (i, channel) in ["vc1", "vc1", "vc2", "vc1"].iter().enumerate() {
+            VoiceHistoryRepo::insert(
+                &pool,
+                NewVoiceTranscriptEntry {
+                    guild_id: "g1".to_string(),
+                    voice_channel_id: channel.to_string(),
+                    user_id: "u1".to_string(),
+                    username: "User".to_string(),
+                    original_text: format!("line {}", i),
+                    translated_text: String::new(),
+                    source_language: "en".to_string(),
+                    target_language: "es".to_string(),
+                    spoken_at: start + Duration::minutes(30 * i as i64),
+                },
+            )
+            .await
+            .unwrap();
+        }
+
+        let all = VoiceHistoryRepo::list(&pool, "g1", "vc1", start, Utc::now()).await.unwrap();
+        let texts: Vec<_> = all.iter().map(|e| e.original_text.as_str()).collect();
+        assert_eq!(texts, vec!["line 0", "line 1", "line 3"]);
+
+        let recent = VoiceHistoryRepo::list(&pool, "g1", "vc1", start + Duration::minutes(20), Utc::now())
+            .await
+            .unwrap();
+        assert_eq!(recent.len(), 2);
+    }
+
     // --- WebSessionRepo tests ---
 
     #[tokio::test]
@@ -968,6 +2936,41 @@ mod tests {
         assert_eq!(cleaned, 0);
     }
 
+    #[tokio::test]
+    async fn test_session_list_and_revoke() {
+        let pool = setup_test_db().await;
+        let new_session = |user: &str, guild: &str| NewWebSession {
+            user_id: user.to_string(),
+            guild_id: guild.to_string(),
+            channel_id: None,
+        };
+        let a = WebSessionRepo::create(&pool, new_session("u1", "g1"), 24).await.unwrap();
+        WebSessionRepo::create(&pool, new_session("u1", "g1"), 24).await.unwrap();
+        WebSessionRepo::create(&pool, new_session("u2", "g1"), 24).await.unwrap();
+        let other = WebSessionRepo::create(&pool, new_session("u1", "g2"), 24).await.unwrap();
+
+        assert_eq!(WebSessionRepo::list_for_guild(&pool, "g1").await.unwrap().len(), 3);
+
+        // Sessions of another guild can't be revoked from here
+        assert!(!WebSessionRepo::revoke(&pool, "g1", other.id).await.unwrap());
+        assert!(WebSessionRepo::revoke(&pool, "g1", a.id).await.unwrap());
+        assert!(WebSessionRepo::get_by_session_id(&pool, &a.session_id)
+            .await
+            .unwrap()
+            .is_none());
+
+        assert_eq!(
+            WebSessionRepo::revoke_for_guild(&pool, "g1", Some("u1")).await.unwrap(),
+            1
+        );
+        let remaining = WebSessionRepo::list_for_guild(&pool, "g1").await.unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].user_id, "u2");
+
+        assert_eq!(WebSessionRepo::revoke_for_guild(&pool, "g1", None).await.unwrap(), 1);
+        assert_eq!(WebSessionRepo::list_for_guild(&pool, "g2").await.unwrap().len(), 1);
+    }
+
     // --- VoiceChannelRepo tests ---
 
     #[tokio::test]
@@ -977,13 +2980,14 @@ mod tests {
             guild_id: "g1".to_string(),
             voice_channel_id: "vc1".to_string(),
             target_language: "es".to_string(),
-            enable_tts: true,
+            tts_mode: TtsMode::Both,
         };
 
         let result = VoiceChannelRepo::upsert(&pool, settings).await.unwrap();
         assert_eq!(result.guild_id, "g1");
         assert_eq!(result.target_language, "es");
         assert!(result.enable_tts);
+        assert_eq!(TtsMode::from(result.tts_mode.as_str()), TtsMode::Both);
         assert!(result.enabled);
     }
 
@@ -994,7 +2998,7 @@ mod tests {
             guild_id: "g1".to_string(),
             voice_channel_id: "vc1".to_string(),
             target_language: "fr".to_string(),
-            enable_tts: false,
+            tts_mode: TtsMode::Off,
         };
         VoiceChannelRepo::upsert(&pool, settings).await.unwrap();
 
@@ -1003,6 +3007,40 @@ mod tests {
         assert_eq!(result.unwrap().target_language, "fr");
     }
 
+    #[tokio::test]
+    async fn test_formality_per_guild_user_and_voice_channel() {
+        let pool = setup_test_db().await;
+        let new_guild = NewGuild {
+            guild_id: "g1".to_string(),
+            name: "Test".to_string(),
+        };
+        GuildRepo::upsert(&pool, new_guild).await.unwrap();
+        VoiceChannelRepo::upsert(
+            &pool,
+            NewVoiceChannelSettings {
+                guild_id: "g1".to_string(),
+                voice_channel_id: "vc1".to_string(),
+                target_language: "de".to_string(),
+                tts_mode: TtsMode::Off,
+            },
+        )
+        .await
+        .unwrap();
+        let guild = || async { GuildRepo::get_settings(&pool, "g1").await.unwrap().unwrap() };
+        assert_eq!(guild().await.formality, Formality::Default);
+
+        GuildRepo::set_formality(&pool, "g1", Formality::Formal).await.unwrap();
+        UserPreferenceRepo::set_formality(&pool, "u1", "g1", Formality::Informal).await.unwrap();
+        VoiceChannelRepo::set_formality(&pool, "g1", "vc1", Formality::Formal).await.unwrap();
+
+        assert_eq!(guild().await.formality, Formality::Formal);
+        let pref = UserPreferenceRepo::get(&pool, "u1", "g1").await.unwrap().unwrap();
+        assert_eq!(Formality::from(pref.formality.as_str()), Formality::Informal);
+        assert_eq!(pref.preferred_language, "en");
+        let voice = VoiceChannelRepo::get_settings(&pool, "g1", "vc1").await.unwrap().unwrap();
+        assert_eq!(Formality::from(voice.formality.as_str()), Formality::Formal);
+    }
+
     #[tokio::test]
     async fn test_voice_channel_get_nonexistent() {
         let pool = setup_test_db().await;
@@ -1010,6 +3048,43 @@ mod tests {
         assert!(result.is_none());
     }
 
+    #[tokio::test]
+    async fn test_voice_channel_active_tracking() {
+        let pool = setup_test_db().await;
+        for (guild, channel) in [("g1", "vc1"), ("g1", "vc2"), ("g2", "vc3")] {
+            VoiceChannelRepo::upsert(
+                &pool,
+                NewVoiceChannelSettings {
+                    guild_id: guild.to_string(),
+                    voice_channel_id: channel.to_string(),
+                    target_language: "en".to_string(),
+                    tts_mode: TtsMode::Off,
+                },
+            )
+            .await
+            .unwrap();
+        }
+        assert!(VoiceChannelRepo::get_active(&pool).await.unwrap().is_empty());
+
+        VoiceChannelRepo::set_active_channel(&pool, "g1", Some("vc1")).await.unwrap();
+        VoiceChannelRepo::set_active_channel(&pool, "g2", Some("vc3")).await.unwrap();
+        // Moving within a guild deactivates the previous channel
+        VoiceChannelRepo::set_active_channel(&pool, "g1", Some("vc2")).await.unwrap();
+        let mut active: Vec<_> = VoiceChannelRepo::get_active(&pool)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|s| s.voice_channel_id)
+            .collect();
+        active.sort();
+        assert_eq!(active, vec!["vc2", "vc3"]);
+
+        VoiceChannelRepo::set_active_channel(&pool, "g1", None).await.unwrap();
+        let active = VoiceChannelRepo::get_active(&pool).await.unwrap();
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].guild_id, "g2");
+    }
+
     #[tokio::test]
     async fn test_voice_channel_set_enabled() {
         let pool = setup_test_db().await;
@@ -1017,7 +3092,7 @@ mod tests {
             guild_id: "g1".to_string(),
             voice_channel_id: "vc1".to_string(),
             target_language: "es".to_string(),
-            enable_tts: false,
+            tts_mode: TtsMode::Off,
         };
         VoiceChannelRepo::upsert(&pool, settings).await.unwrap();
 
@@ -1033,7 +3108,7 @@ mod tests {
             guild_id: "g1".to_string(),
             voice_channel_id: "vc1".to_string(),
             target_language: "es".to_string(),
-            enable_tts: false,
+            tts_mode: TtsMode::Off,
         };
         VoiceChannelRepo::upsert(&pool, settings).await.unwrap();
 
@@ -1049,7 +3124,7 @@ mod tests {
             guild_id: "g1".to_string(),
             voice_channel_id: "vc1".to_string(),
             target_language: "es".to_string(),
-            enable_tts: false,
+            tts_mode: TtsMode::Off,
         };
         VoiceChannelRepo::upsert(&pool, settings).await.unwrap();
 
@@ -1058,6 +3133,50 @@ mod tests {
         assert!(result.enable_tts);
     }
 
+    #[tokio::test]
+    async fn test_voice_channel_set_tts_mode() {
+        let pool = setup_test_db().await;
+        let settings = NewVoiceChannelSettings {
+            guild_id: "g1".to_string(),
+            voice_channel_id: "vc1".to_string(),
+            target_language: "es".to_string(),
+            tts_mode: TtsMode::Off,
+        };
+        VoiceChannelRepo::upsert(&pool, settings).await.unwrap();
+
+        VoiceChannelRepo::set_tts_mode(&pool, "g1", "vc1", TtsMode::WebOnly).await.unwrap();
+        let result = VoiceChannelRepo::get_settings(&pool, "g1", "vc1").await.unwrap().unwrap();
+        assert_eq!(TtsMode::from(result.tts_mode.as_str()), TtsMode::WebOnly);
+        assert!(result.enable_tts);
+
+        VoiceChannelRepo::set_tts_mode(&pool, "g1", "vc1", TtsMode::Off).await.unwrap();
+        let result = VoiceChannelRepo::get_settings(&pool, "g1", "vc1").await.unwrap().unwrap();
+        assert!(!result.enable_tts);
+    }
+
+    #[tokio::test]
+    async fn test_voice_channel_set_tts_languages() {
+        let pool = setup_test_db().await;
+        let settings = NewVoiceChannelSettings {
+            guild_id: "g1".to_string(),
+            voice_channel_id: "vc1".to_string(),
+            target_language: "es".to_string(),
+            tts_mode: TtsMode::Voice,
+        };
+        VoiceChannelRepo::upsert(&pool, settings).await.unwrap();
+
+        // No list means every language is spoken
+        let result = VoiceChannelRepo::get_settings(&pool, "g1", "vc1").await.unwrap().unwrap();
+        assert!(result.tts_enabled_for("fr"));
+
+        let languages = vec!["es".to_string(), "ja".to_string()];
+        VoiceChannelRepo::set_tts_languages(&pool, "g1", "vc1", &languages).await.unwrap();
+        let result = VoiceChannelRepo::get_settings(&pool, "g1", "vc1").await.unwrap().unwrap();
+        assert_eq!(result.get_tts_languages(), languages);
+        assert!(result.tts_enabled_for("ja"));
+        assert!(!result.tts_enabled_for("fr"));
+    }
+
     #[tokio::test]
     async fn test_voice_channel_delete() {
         let pool = setup_test_db().await;
@@ -1065,7 +3184,7 @@ mod tests {
             guild_id: "g1".to_string(),
             voice_channel_id: "vc1".to_string(),
             target_language: "es".to_string(),
-            enable_tts: false,
+            tts_mode: TtsMode::Off,
         };
         VoiceChannelRepo::upsert(&pool, settings).await.unwrap();
 
@@ -1082,7 +3201,7 @@ mod tests {
                 guild_id: "g1".to_string(),
                 voice_channel_id: format!("vc{}", i),
                 target_language: "es".to_string(),
-                enable_tts: false,
+                tts_mode: TtsMode::Off,
             };
             VoiceChannelRepo::upsert(&pool, settings).await.unwrap();
         }
@@ -1090,4 +3209,175 @@ mod tests {
         let results = VoiceChannelRepo::get_by_guild(&pool, "g1").await.unwrap();
         assert_eq!(results.len(), 3);
     }
+
+    // --- VoiceTranscriptRepo tests ---
+
+    #[tokio::test]
+    async fn test_transcript_language_tts_toggle() {
+        let pool = setup_test_db().await;
+        let new_settings = NewVoiceTranscriptSettings {
+            guild_id: "g1".to_string(),
+            voice_channel_id: "vc1".to_string(),
+            text_channel_id: "tc1".to_string(),
+            languages: vec!["en".to_string(), "es".to_string()],
+        };
+        let settings = VoiceTranscriptRepo::upsert(&pool, new_settings).await.unwrap();
+        assert!(settings.get_tts_languages().is_empty());
+
+        VoiceTranscriptRepo::set_language_tts(&pool, "g1", "vc1", "es", true).await.unwrap();
+        VoiceTranscriptRepo::set_language_tts(&pool, "g1", "vc1", "es", true).await.unwrap();
+        let settings = VoiceTranscriptRepo::get_settings(&pool, "g1", "vc1").await.unwrap().unwrap();
+        assert_eq!(settings.get_tts_languages(), vec!["es"]);
+        assert!(!settings.tts_enabled_for("en"));
+
+        VoiceTranscriptRepo::set_language_tts(&pool, "g1", "vc1", "es", false).await.unwrap();
+        let settings = VoiceTranscriptRepo::get_settings(&pool, "g1", "vc1").await.unwrap().unwrap();
+        assert!(!settings.tts_enabled_for("es"));
+    }
+
+    // --- TranslationFeedbackRepo tests ---
+
+    fn feedback(guild_id: &str, correction: &str) -> NewTranslationFeedback {
+        NewTranslationFeedback {
+            guild_id: guild_id.to_string(),
+            channel_id: "c1".to_string(),
+            message_id: "m1".to_string(),
+            user_id: "u1".to_string(),
+            source_lang: "en".to_string(),
+            target_lang: "es".to_string(),
+            original_text: "I'm feeling blue".to_string(),
+            machine_translation: "Me siento azul".to_string(),
+            correction: correction.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_feedback_create_stores_all_fields() {
+        let pool = setup_test_db().await;
+        let stored = TranslationFeedbackRepo::create(&pool, feedback("g1", "Estoy triste"))
+            .await
+            .unwrap();
+        assert_eq!(stored.original_text, "I'm feeling blue");
+        assert_eq!(stored.machine_translation, "Me siento azul");
+        assert_eq!(stored.correction, "Estoy triste");
+        assert_eq!((stored.source_lang.as_str(), stored.target_lang.as_str()), ("en", "es"));
+    }
+
+    #[tokio::test]
+    async fn test_feedback_get_by_guild_newest_first() {
+        let pool = setup_test_db().await;
+        TranslationFeedbackRepo::create(&pool, feedback("g1", "first")).await.unwrap();
+        TranslationFeedbackRepo::create(&pool, feedback("g1", "second")).await.unwrap();
+        TranslationFeedbackRepo::create(&pool, feedback("g2", "other")).await.unwrap();
+
+        let results = TranslationFeedbackRepo::get_by_guild(&pool, "g1").await.unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].correction, "second");
+    }
+
+    #[tokio::test]
+    async fn test_translation_message_links_to_original() {
+        let pool = setup_test_db().await;
+        let message = NewTranslationMessage {
+            message_id: "m2".to_string(),
+            guild_id: "g1".to_string(),
+            channel_id: "c1".to_string(),
+            original_channel_id: "c1".to_string(),
+            original_message_id: "m1".to_string(),
+            source_lang: "en".to_string(),
+            target_lang: "es".to_string(),
+            translated_text: "Me siento azul".to_string(),
+        };
+        TranslationMessageRepo::record(&pool, message).await.unwrap();
+
+        let stored = TranslationMessageRepo::get(&pool, "m2").await.unwrap().unwrap();
+        assert_eq!(stored.original_message_id, "m1");
+        assert_eq!(stored.translated_text, "Me siento azul");
+        assert!(TranslationMessageRepo::get(&pool, "m1").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_ignore_list_add_and_remove() {
+        let pool = setup_test_db().await;
+        assert!(IgnoreListRepo::get(&pool, "g1").await.unwrap().is_empty());
+
+        assert!(IgnoreListRepo::add(&pool, "g1", IgnoredKind::User, "u1").await.unwrap());
+        assert!(!IgnoreListRepo::add(&pool, "g1", IgnoredKind::User, "u1").await.unwrap());
+        assert!(IgnoreListRepo::add(&pool, "g1", IgnoredKind::Role, "r1").await.unwrap());
+        IgnoreListRepo::add(&pool, "g2", IgnoredKind::User, "u2").await.unwrap();
+
+        let list = IgnoreListRepo::get(&pool, "g1").await.unwrap();
+        assert_eq!(list.users, vec!["u1"]);
+        assert_eq!(list.roles, vec!["r1"]);
+
+        assert!(IgnoreListRepo::remove(&pool, "g1", IgnoredKind::User, "u1").await.unwrap());
+        assert!(!IgnoreListRepo::remove(&pool, "g1", IgnoredKind::User, "u1").await.unwrap());
+        assert!(IgnoreListRepo::get(&pool, "g1").await.unwrap().users.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_guild_migration_copies_settings_and_preferences() {
+        let pool = setup_test_db().await;
+        for guild_id in ["old", "new"] {
+            GuildRepo::upsert(
+                &pool,
+                NewGuild {
+                    guild_id: guild_id.to_string(),
+                    name: guild_id.to_string(),
+                },
+            )
+            .await
+            .unwrap();
+        }
+        assert!(GuildMigrationRepo::summarize(&pool, "new").await.unwrap().is_empty());
+
+        GuildRepo::set_default_language(&pool, "old", "de").await.unwrap();
+        GuildRepo::enable_channel(&pool, "old", "kept").await.unwrap();
+        GuildRepo::enable_channel(&pool, "old", "gone").await.unwrap();
+        UserPreferenceRepo::set_language(&pool, "u1", "old", "fr").await.unwrap();
+        UserPreferenceRepo::set_language(&pool, "u2", "old", "es").await.unwrap();
+        UserPreferenceRepo::set_language(&pool, "u1", "new", "ja").await.unwrap();
+        for channel in ["vc-kept", "vc-gone"] {
+            VoiceChannelRepo::upsert(
+                &pool,
+                NewVoiceChannelSettings {
+                    guild_id: "old".to_string(),
+                    voice_channel_id: channel.to_string(),
+                    target_language: "it".to_string(),
+                    tts_mode: TtsMode::Off,
+                },
+            )
+            .await
+            .unwrap();
+        }
+        VoiceChannelRepo::set_active_channel(&pool, "old", Some("vc-kept")).await.unwrap();
+
+        let before = GuildMigrationRepo::summarize(&pool, "old").await.unwrap();
+        assert!(before.custom_settings);
+        assert_eq!((before.user_preferences, before.voice_channels), (2, 2));
+
+        let current = vec!["kept".to_string(), "vc-kept".to_string()];
+        let report = GuildMigrationRepo::migrate(&pool, "old", "new", "New Name", &current)
+            .await
+            .unwrap();
+        assert!(report.settings);
+        assert_eq!(report.user_preferences, 2);
+        assert_eq!(report.voice_channels, 1);
+        assert_eq!(report.skipped_channels, 1);
+
+        let settings = GuildRepo::get_settings(&pool, "new").await.unwrap().unwrap();
+        assert_eq!(settings.default_language, "de");
+        assert_eq!(settings.name, "new");
+        assert_eq!(settings.enabled_channels, vec!["kept"]);
+
+        // Existing preferences in the new guild are overwritten
+        let pref = UserPreferenceRepo::get(&pool, "u1", "new").await.unwrap().unwrap();
+        assert_eq!(pref.preferred_language, "fr");
+        assert!(UserPreferenceRepo::get(&pool, "u2", "new").await.unwrap().is_some());
+
+        let voice = VoiceChannelRepo::get_settings(&pool, "new", "vc-kept").await.unwrap().unwrap();
+        assert_eq!(voice.target_language, "it");
+        assert!(!voice.active);
+        assert!(VoiceChannelRepo::get_settings(&pool, "old", "vc-gone").await.unwrap().is_some());
+    }
 }
@@ -7,7 +7,12 @@ pub enum AppError {
     Config(#[from] config::ConfigError),
 
     #[error("Database error: {0}")]
-    Database(#[from] sqlx::Error),
+    Database(sqlx::Error),
+
+    /// The database can't be reached (pool exhausted, file missing or I/O
+    /// failing); callers may carry on without it
+    #[error("Database unavailable: {0}")]
+    DatabaseUnavailable(String),
 
     #[error("HTTP client error: {0}")]
     Http(#[from] reqwest::Error),
@@ -39,6 +44,10 @@ pub enum AppError {
     #[error("Inference service unavailable")]
     InferenceUnavailable,
 
+    /// The inference service hasn't answered a health check since boot
+    #[error("Translation service is still starting, please try again in a few seconds")]
+    InferenceStarting,
+
     #[error("Internal error: {0}")]
     Internal(String),
 }
@@ -53,6 +62,34 @@ impl AppError {
     }
 }
 
+impl From<sqlx::Error> for AppError {
+    fn from(err: sqlx::Error) -> Self {
+        if is_connectivity_error(&err) {
+            Self::DatabaseUnavailable(err.to_string())
+        } else {
+            Self::Database(err)
+        }
+    }
+}
+
+/// Whether a database error means the database itself is unreachable,
+/// rather than a problem with one query
+fn is_connectivity_error(err: &sqlx::Error) -> bool {
+    // Primary SQLite result codes (extended codes keep these in the low byte)
+    const SQLITE_BUSY: i64 = 5;
+    const SQLITE_IOERR: i64 = 10;
+    const SQLITE_CANTOPEN: i64 = 14;
+
+    match err {
+        sqlx::Error::PoolTimedOut | sqlx::Error::PoolClosed | sqlx::Error::Io(_) => true,
+        sqlx::Error::Database(db_err) => db_err
+            .code()
+            .and_then(|code| code.parse::<i64>().ok())
+            .is_some_and(|code| matches!(code & 0xff, SQLITE_BUSY | SQLITE_IOERR | SQLITE_CANTOPEN)),
+        _ => false,
+    }
+}
+
 /// Result type alias using AppError
 pub type AppResult<T> = Result<T, AppError>;
 
@@ -63,6 +100,7 @@ impl AppError {
         match self {
             Self::Config(_) => StatusCode::INTERNAL_SERVER_ERROR,
             Self::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::DatabaseUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
             Self::Http(_) => StatusCode::BAD_GATEWAY,
             Self::Discord(_) => StatusCode::BAD_GATEWAY,
             Self::Translation(_) => StatusCode::UNPROCESSABLE_ENTITY,
@@ -73,6 +111,7 @@ impl AppError {
             Self::InvalidSession => StatusCode::UNAUTHORIZED,
             Self::GuildNotConfigured => StatusCode::NOT_FOUND,
             Self::InferenceUnavailable => StatusCode::SERVICE_UNAVAILABLE,
+            Self::InferenceStarting => StatusCode::SERVICE_UNAVAILABLE,
             Self::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
@@ -148,6 +187,35 @@ mod tests {
         assert_eq!(err.status_code(), StatusCode::SERVICE_UNAVAILABLE);
     }
 
+    #[test]
+    fn test_inference_starting_maps_to_503() {
+        let err = AppError::InferenceStarting;
+        assert_eq!(err.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+        assert!(err.to_string().contains("starting"));
+    }
+
+    #[test]
+    fn test_pool_timeout_is_database_unavailable() {
+        let err = AppError::from(sqlx::Error::PoolTimedOut);
+        assert!(matches!(err, AppError::DatabaseUnavailable(_)));
+        assert_eq!(err.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[test]
+    fn test_query_error_stays_database_error() {
+        let err = AppError::from(sqlx::Error::RowNotFound);
+        assert!(matches!(err, AppError::Database(_)));
+        assert_eq!(err.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[tokio::test]
+    async fn test_unopenable_database_is_unavailable() {
+        let err = sqlx::SqlitePool::connect("sqlite:///nonexistent-dir/linguabridge.db")
+            .await
+            .unwrap_err();
+        assert!(matches!(AppError::from(err), AppError::DatabaseUnavailable(_)));
+    }
+
     #[test]
     fn test_internal_error_maps_to_500() {
         let err = AppError::Internal("something broke".to_string());
@@ -3,6 +3,7 @@ pub mod bot;
 pub mod config;
 pub mod db;
 pub mod error;
+pub mod metrics;
 pub mod translation;
 pub mod voice;
 pub mod web;
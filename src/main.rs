@@ -1,9 +1,11 @@
 use linguabridge::{
     admin::{self, AdminState, SharedSecretStore},
-    bot, config::AppConfig, db, translation::TranslationClient, web,
+    bot, config::AppConfig, db, translation::{self, ShadowTranslator, TranslationClient}, voice::{types::PROTOCOL_VERSION, TtsClient, VoiceTranscriptionCache},
+    web,
 };
 use sqlx::sqlite::SqlitePoolOptions;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::net::TcpListener;
 use tracing::{error, info, warn};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
@@ -23,6 +25,9 @@ async fn main() -> anyhow::Result<()> {
 
     // Load non-sensitive configuration
     let config = AppConfig::init()?;
+    config.print_effective();
+    config.validate_endpoints().map_err(|e| anyhow::anyhow!(e))?;
+    config.admin.validate_path_prefix().map_err(|e| anyhow::anyhow!(e))?;
     info!("Configuration loaded");
 
     // Validate admin public key is configured
@@ -39,17 +44,22 @@ async fn main() -> anyhow::Result<()> {
     // Create admin state for provisioning
     let admin_state = Arc::new(
         AdminState::new(&config.admin.public_key, secret_store.clone())
+            .map(|state| state.with_debug(config.admin.debug_provisioning))
             .map_err(|e| anyhow::anyhow!("Failed to initialize admin transport: {}", e))?,
     );
 
     // Start admin provisioning server
     let admin_addr = format!("{}:{}", config.admin.host, config.admin.port);
+    let admin_mount = config.admin.mount_path();
     let admin_listener = TcpListener::bind(&admin_addr).await?;
-    info!("Admin provisioning server listening on http://{}", admin_addr);
+    info!("Admin provisioning server listening on http://{}{}", admin_addr, admin_mount);
     info!("Waiting for admin to provision secrets...");
-    info!("Use: linguabridge-admin provision --bot-url http://{} --discord-token YOUR_TOKEN", admin_addr);
+    info!(
+        "Use: linguabridge-admin provision --bot-url http://{}{} --discord-token YOUR_TOKEN",
+        admin_addr, admin_mount
+    );
 
-    let admin_router = admin::admin_router(admin_state.clone());
+    let admin_router = admin::admin_router(admin_state.clone(), &admin_mount);
     let admin_handle = tokio::spawn(async move {
         if let Err(e) = axum::serve(admin_listener, admin_router).await {
             error!("Admin server error: {}", e);
@@ -75,8 +85,12 @@ async fn run_main_application(
     secret_store: SharedSecretStore,
 ) -> anyhow::Result<()> {
     // Initialize database
+    // Broken connections are dropped and reopened on the next acquire; the
+    // short acquire timeout turns an outage into prompt DatabaseUnavailable errors
     let pool = SqlitePoolOptions::new()
         .max_connections(config.database.max_connections)
+        .acquire_timeout(Duration::from_secs(config.database.acquire_timeout_secs))
+        .test_before_acquire(true)
         .connect(&config.database.url)
         .await?;
     info!("Database connected: {}", config.database.url);
@@ -84,38 +98,153 @@ async fn run_main_application(
     // Run migrations
     db::init_db(&pool).await?;
 
+    let db_health = Arc::new(db::DbHealth::new());
+    let db_health_handle = db::health::spawn_health_monitor(
+        pool.clone(),
+        db_health.clone(),
+        Duration::from_secs(config.database.health_check_interval_secs.max(1)),
+    );
+
+    // Features that need a Hugging Face token degrade rather than fail opaquely
+    let mut degraded_features = Vec::new();
+    if !secret_store.has_hf_token().await {
+        if config.inference.require_hf_token {
+            anyhow::bail!("No hf_token was provisioned and inference.require_hf_token is set");
+        }
+        warn!(
+            "No hf_token provisioned; running without: {}",
+            admin::HF_TOKEN_FEATURES.join("; ")
+        );
+        degraded_features.extend(
+            admin::HF_TOKEN_FEATURES
+                .iter()
+                .map(|feature| format!("{} (no hf_token provisioned)", feature)),
+        );
+    }
+
+    // Guilds with their own inference tenant authenticate with their own token
+    let inference_tokens = secret_store.inference_tokens().await;
+    if inference_tokens.guild_count() > 0 {
+        info!("Inference tokens provisioned for {} guild(s)", inference_tokens.guild_count());
+    }
+
     // Create translation client
-    let translator = Arc::new(TranslationClient::new(config));
+    let mut translator = TranslationClient::new(config).with_inference_tokens(inference_tokens.clone());
+    if let Some(shadow_url) = config.translation.shadow_provider.as_deref().filter(|u| !u.is_empty()) {
+        translator = translator.with_shadow(ShadowTranslator::new(
+            shadow_url,
+            config.translation.shadow_sample_rate,
+            Duration::from_secs(config.inference.timeout_secs),
+            pool.clone(),
+        ));
+        info!(
+            "Shadow translation enabled: {} ({:.0}% sampled)",
+            shadow_url,
+            config.translation.shadow_sample_rate * 100.0
+        );
+    }
+    let translator = Arc::new(translator);
     info!("Translation client initialized");
 
+    // Pick up where the last graceful shutdown left the cache
+    if config.translation.persist_cache {
+        match translator.load_cache(&pool).await {
+            Ok(loaded) => info!("Loaded {} cached translation(s) from the last shutdown", loaded),
+            Err(e) => warn!("Failed to load saved translation cache: {}", e),
+        }
+    }
+
     // Check inference service health
     match translator.health_check().await {
         Ok(health) => {
             info!(
-                "Inference service healthy: model={}, loaded={}",
-                health.model, health.model_loaded
+                "Inference service healthy: model={}, loaded={}, tts={}, languages={}, max_input={:?}",
+                health.model,
+                health.model_loaded,
+                health.tts_loaded,
+                health.supported_languages.len(),
+                health.max_input_chars
             );
+            if health.hf_token_configured == Some(false) {
+                warn!("Inference service has no HF_TOKEN; speaker diarization is disabled there");
+                degraded_features.push("speaker diarization (inference service has no HF_TOKEN)".to_string());
+            }
+            if health.protocol_version != Some(PROTOCOL_VERSION) {
+                error!(
+                    "Inference service speaks voice protocol {:?} but this bot speaks v{}; \
+                    voice translation will refuse to connect until both are redeployed together",
+                    health.protocol_version,
+                    PROTOCOL_VERSION
+                );
+            }
         }
         Err(e) => {
             warn!(
-                "Inference service not available: {}. \
-                Translation will fail until the service is started.",
-                e
+                "Inference service not available yet: {}. Translations wait up to {}s \
+                for it to start, then fail until it is up.",
+                e,
+                config.inference.startup_wait_secs
             );
         }
     }
+    let inference_health_handle = translation::client::spawn_health_monitor(
+        translator.clone(),
+        Duration::from_secs(config.inference.health_check_interval_secs.max(1)),
+    );
+
+    if let Some(tts_url) = &config.voice.tts_url {
+        let tts = TtsClient::new(tts_url, Duration::from_secs(config.inference.timeout_secs));
+        match tts.health_check().await {
+            Ok(true) => info!("TTS service healthy: {}", tts_url),
+            Ok(false) => {
+                warn!("TTS service at {} has no TTS model loaded", tts_url);
+                degraded_features.push("voice TTS (TTS service has no model loaded)".to_string());
+            }
+            Err(e) => {
+                warn!("TTS service not available: {}. Voice results will have no speech until it starts.", e);
+                degraded_features.push("voice TTS (TTS service unreachable)".to_string());
+            }
+        }
+    }
 
     // Create broadcast manager for real-time updates
-    let broadcast = Arc::new(web::BroadcastManager::new());
+    let broadcast = Arc::new(
+        web::BroadcastManager::new()
+            .with_low_confidence_threshold(config.voice.low_confidence_threshold),
+    );
+    broadcast.spawn_pruner();
 
     // Create web server state
     let web_state = web::AppState {
         pool: pool.clone(),
         broadcast: broadcast.clone(),
+        db_health,
+        degraded_features: Arc::new(degraded_features),
+        inference: translator.readiness(),
+        connections: Arc::new(web::ConnectionLimiter::new(
+            config.web.max_connections,
+            config.web.max_ws_connections_per_ip,
+        )),
+    };
+
+    // Shared with the bot's voice manager so admins can inspect and flush it
+    let voice_cache = Arc::new(VoiceTranscriptionCache::default());
+    if config.voice.persist_cache {
+        match voice_cache.load(&pool).await {
+            Ok(loaded) => info!("Loaded {} cached voice result(s) from the last shutdown", loaded),
+            Err(e) => warn!("Failed to load saved voice cache: {}", e),
+        }
+    }
+    let admin_cache_state = web::AdminCacheState {
+        admin_key: admin::crypto::parse_ed25519_public_key(&config.admin.public_key)?,
+        pool: pool.clone(),
+        translator: translator.clone(),
+        voice_cache: voice_cache.clone(),
+        seen_nonces: Default::default(),
     };
 
     // Create web router
-    let app = web::create_router(web_state, translator.clone());
+    let app = web::create_router(web_state, translator.clone(), admin_cache_state);
 
     // Start web server in background
     let web_addr = format!("{}:{}", config.web.host, config.web.port);
@@ -123,6 +252,8 @@ async fn run_main_application(
     info!("Web server listening on http://{}", web_addr);
 
     let web_handle = tokio::spawn(async move {
+        // Client addresses feed the per-IP WebSocket cap
+        let app = app.into_make_service_with_connect_info::<std::net::SocketAddr>();
         if let Err(e) = axum::serve(listener, app).await {
             error!("Web server error: {}", e);
         }
@@ -136,10 +267,14 @@ async fn run_main_application(
 
     // Start Discord bot
     info!("Starting Discord bot...");
+    let cache_translator = translator.clone();
+    let saved_voice_cache = voice_cache.clone();
     let bot_result = bot::start_bot_with_token(
         pool.clone(),
         translator,
         broadcast,
+        voice_cache,
+        inference_tokens,
         &discord_token,
     )
     .await;
@@ -153,8 +288,28 @@ async fn run_main_application(
         }
     }
 
+    // Save the caches before exiting, giving up rather than hanging
+    // shutdown on a slow disk
+    let timeout = Duration::from_secs(config.translation.cache_flush_timeout_secs);
+    if config.translation.persist_cache {
+        match tokio::time::timeout(timeout, cache_translator.save_cache(&pool)).await {
+            Ok(Ok(saved)) => info!("Saved {} cached translation(s)", saved),
+            Ok(Err(e)) => warn!("Failed to save translation cache: {}", e),
+            Err(_) => warn!("Saving the translation cache took over {}s; skipped", timeout.as_secs()),
+        }
+    }
+    if config.voice.persist_cache {
+        match tokio::time::timeout(timeout, saved_voice_cache.save(&pool)).await {
+            Ok(Ok(saved)) => info!("Saved {} cached voice result(s)", saved),
+            Ok(Err(e)) => warn!("Failed to save voice cache: {}", e),
+            Err(_) => warn!("Saving the voice cache took over {}s; skipped", timeout.as_secs()),
+        }
+    }
+
     // Wait for web server to finish
     web_handle.abort();
+    db_health_handle.abort();
+    inference_health_handle.abort();
 
     Ok(())
 }
@@ -0,0 +1,206 @@
+//! Process-wide metrics reported on `/metrics`.
+//!
+//! Values are kept in atomics and rendered in the Prometheus text format by
+//! the web server, so recording one is cheap enough for the audio path.
+
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Bucket upper bounds (seconds) for end-to-end voice latency: from a short
+/// phrase answered straight away to a backed-up queue
+pub const VOICE_LATENCY_BUCKETS: &[f64] = &[0.25, 0.5, 0.75, 1.0, 1.5, 2.0, 3.0, 5.0, 10.0, 30.0];
+
+/// Time from the end of a captured utterance until its translation is
+/// handed to the web feed, including queueing, network and TTS
+pub fn voice_latency() -> &'static Histogram {
+    static VOICE_LATENCY: OnceLock<Histogram> = OnceLock::new();
+    VOICE_LATENCY.get_or_init(|| Histogram::new(VOICE_LATENCY_BUCKETS))
+}
+
+/// Bucket upper bounds (bytes) for voice frames sent to the inference
+/// service: a short word up to a long utterance near the frame size limit
+pub const VOICE_FRAME_SIZE_BUCKETS: &[f64] = &[
+    16_384.0, 65_536.0, 131_072.0, 262_144.0, 524_288.0, 1_048_576.0, 4_194_304.0, 16_777_216.0,
+];
+
+/// Time from sending a segment to the voice inference service until its
+/// result arrives, excluding time spent in our own queue
+pub fn voice_round_trip() -> &'static Histogram {
+    static VOICE_ROUND_TRIP: OnceLock<Histogram> = OnceLock::new();
+    VOICE_ROUND_TRIP.get_or_init(|| Histogram::new(VOICE_LATENCY_BUCKETS))
+}
+
+/// Size of each binary audio frame sent to the voice inference service
+pub fn voice_frame_bytes() -> &'static Histogram {
+    static VOICE_FRAME_BYTES: OnceLock<Histogram> = OnceLock::new();
+    VOICE_FRAME_BYTES.get_or_init(|| Histogram::new(VOICE_FRAME_SIZE_BUCKETS))
+}
+
+/// Voice segments that never got a result: dropped from a full queue,
+/// failed by the service, or left unanswered
+pub fn voice_dead_letters() -> &'static DeadLetters {
+    static VOICE_DEAD_LETTERS: OnceLock<DeadLetters> = OnceLock::new();
+    VOICE_DEAD_LETTERS.get_or_init(DeadLetters::default)
+}
+
+/// A counter of dead-lettered requests that also keeps the trace id of the
+/// latest one, so an alert can be followed into the logs
+#[derive(Debug, Default)]
+pub struct DeadLetters {
+    count: AtomicU64,
+    last_trace_id: Mutex<Option<Uuid>>,
+}
+
+impl DeadLetters {
+    /// Record one request that will never be answered
+    pub fn record(&self, trace_id: Uuid) {
+        self.count.fetch_add(1, Ordering::Relaxed);
+        *self.last_trace_id.lock().unwrap() = Some(trace_id);
+    }
+
+    /// Number of dead letters so far
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    /// Trace id of the most recent dead letter
+    pub fn last_trace_id(&self) -> Option<Uuid> {
+        *self.last_trace_id.lock().unwrap()
+    }
+
+    /// Append `<name>_total` and, once there is one, `<name>_last_info`
+    /// labelled with the latest trace id to `out` as Prometheus text
+    pub fn render(&self, name: &str, help: &str, out: &mut String) {
+        let _ = writeln!(out, "# HELP {}_total {}", name, help);
+        let _ = writeln!(out, "# TYPE {}_total counter", name);
+        let _ = writeln!(out, "{}_total {}", name, self.count());
+        if let Some(trace_id) = self.last_trace_id() {
+            let _ = writeln!(out, "# HELP {}_last_info Trace id of the latest one", name);
+            let _ = writeln!(out, "# TYPE {}_last_info gauge", name);
+            let _ = writeln!(out, "{}_last_info{{trace_id=\"{}\"}} 1", name, trace_id);
+        }
+    }
+}
+
+/// A Prometheus-style histogram with fixed buckets, of durations or plain
+/// values such as sizes
+#[derive(Debug)]
+pub struct Histogram {
+    bounds: &'static [f64],
+    /// Observations per bucket (not cumulative); the last one is `+Inf`
+    buckets: Vec<AtomicU64>,
+    count: AtomicU64,
+    /// Sum of observations in millionths (microseconds for durations)
+    sum_micros: AtomicU64,
+}
+
+impl Histogram {
+    /// A histogram with the given ascending bucket bounds, in seconds for
+    /// durations
+    pub fn new(bounds: &'static [f64]) -> Self {
+        Self {
+            bounds,
+            buckets: (0..=bounds.len()).map(|_| AtomicU64::new(0)).collect(),
+            count: AtomicU64::new(0),
+            sum_micros: AtomicU64::new(0),
+        }
+    }
+
+    /// Record one duration
+    pub fn observe(&self, value: Duration) {
+        self.observe_value(value.as_secs_f64());
+    }
+
+    /// Record one plain value, in the unit of the bucket bounds
+    pub fn observe_value(&self, value: f64) {
+        let bucket = self
+            .bounds
+            .iter()
+            .position(|&bound| value <= bound)
+            .unwrap_or(self.bounds.len());
+        self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum_micros
+            .fetch_add((value * 1_000_000.0).round() as u64, Ordering::Relaxed);
+    }
+
+    /// Number of observations so far
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    /// Append the histogram to `out` as Prometheus text
+    pub fn render(&self, name: &str, help: &str, out: &mut String) {
+        let _ = writeln!(out, "# HELP {} {}", name, help);
+        let _ = writeln!(out, "# TYPE {} histogram", name);
+        let mut cumulative = 0;
+        for (i, bucket) in self.buckets.iter().enumerate() {
+            cumulative += bucket.load(Ordering::Relaxed);
+            let le = self
+                .bounds
+                .get(i)
+                .map_or_else(|| "+Inf".to_string(), |bound| bound.to_string());
+            let _ = writeln!(out, "{}_bucket{{le=\"{}\"}} {}", name, le, cumulative);
+        }
+        let sum = self.sum_micros.load(Ordering::Relaxed) as f64 / 1_000_000.0;
+        let _ = writeln!(out, "{}_sum {}", name, sum);
+        let _ = writeln!(out, "{}_count {}", name, self.count());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram_renders_cumulative_buckets() {
+        let histogram = Histogram::new(&[0.5, 1.0]);
+        histogram.observe(Duration::from_millis(200));
+        histogram.observe(Duration::from_millis(700));
+        histogram.observe(Duration::from_secs(4));
+
+        let mut out = String::new();
+        histogram.render("test_latency_seconds", "Test latency", &mut out);
+
+        assert!(out.contains("# TYPE test_latency_seconds histogram\n"));
+        assert!(out.contains("test_latency_seconds_bucket{le=\"0.5\"} 1\n"));
+        assert!(out.contains("test_latency_seconds_bucket{le=\"1\"} 2\n"));
+        assert!(out.contains("test_latency_seconds_bucket{le=\"+Inf\"} 3\n"));
+        assert!(out.contains("test_latency_seconds_sum 4.9\n"));
+        assert!(out.contains("test_latency_seconds_count 3\n"));
+    }
+
+    #[test]
+    fn test_histogram_records_plain_values() {
+        let histogram = Histogram::new(&[1024.0, 4096.0]);
+        histogram.observe_value(2048.0);
+        histogram.observe_value(10_000.0);
+
+        let mut out = String::new();
+        histogram.render("test_bytes", "Test sizes", &mut out);
+
+        assert!(out.contains("test_bytes_bucket{le=\"1024\"} 0\n"));
+        assert!(out.contains("test_bytes_bucket{le=\"4096\"} 1\n"));
+        assert!(out.contains("test_bytes_sum 12048\n"));
+    }
+
+    #[test]
+    fn test_dead_letters_render_latest_trace_id() {
+        let dead_letters = DeadLetters::default();
+        let mut out = String::new();
+        dead_letters.render("test_dead_letters", "Test dead letters", &mut out);
+        assert!(out.contains("test_dead_letters_total 0\n"));
+        assert!(!out.contains("_last_info"));
+
+        let (first, second) = (Uuid::new_v4(), Uuid::new_v4());
+        dead_letters.record(first);
+        dead_letters.record(second);
+        let mut out = String::new();
+        dead_letters.render("test_dead_letters", "Test dead letters", &mut out);
+        assert!(out.contains("test_dead_letters_total 2\n"));
+        assert!(out.contains(&format!("test_dead_letters_last_info{{trace_id=\"{}\"}} 1\n", second)));
+    }
+}
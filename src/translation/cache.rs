@@ -1,5 +1,10 @@
+use crate::db::{DbPool, PersistedTranslation, TranslationCacheRepo};
+use crate::error::AppResult;
+use crate::translation::Formality;
+use chrono::Utc;
 use dashmap::DashMap;
 use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{Duration, Instant};
 
 /// Cache key for translations
@@ -8,6 +13,10 @@ pub struct CacheKey {
     pub text: String,
     pub source_lang: String,
     pub target_lang: String,
+    /// Context hint the translation was made with
+    pub context: Option<String>,
+    /// Register the translation was asked for
+    pub formality: Formality,
 }
 
 impl PartialEq for CacheKey {
@@ -15,6 +24,8 @@ impl PartialEq for CacheKey {
         self.text == other.text
             && self.source_lang == other.source_lang
             && self.target_lang == other.target_lang
+            && self.context == other.context
+            && self.formality == other.formality
     }
 }
 
@@ -23,6 +34,8 @@ impl Hash for CacheKey {
         self.text.hash(state);
         self.source_lang.hash(state);
         self.target_lang.hash(state);
+        self.context.hash(state);
+        self.formality.hash(state);
     }
 }
 
@@ -51,6 +64,8 @@ pub struct TranslationCache {
     cache: DashMap<CacheKey, CacheEntry>,
     ttl: Duration,
     max_size: usize,
+    hits: AtomicU64,
+    misses: AtomicU64,
 }
 
 impl TranslationCache {
@@ -59,17 +74,24 @@ impl TranslationCache {
             cache: DashMap::new(),
             ttl: Duration::from_secs(ttl_secs),
             max_size,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
         }
     }
 
     /// Get a cached translation if it exists and is not expired
     pub fn get(&self, key: &CacheKey) -> Option<String> {
-        let entry = self.cache.get(key)?;
+        let Some(entry) = self.cache.get(key) else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+            return None;
+        };
         if entry.is_expired(self.ttl) {
             drop(entry);
             self.cache.remove(key);
+            self.misses.fetch_add(1, Ordering::Relaxed);
             None
         } else {
+            self.hits.fetch_add(1, Ordering::Relaxed);
             Some(entry.translated_text.clone())
         }
     }
@@ -124,6 +146,69 @@ impl TranslationCache {
         self.cache.clear();
     }
 
+    /// Reset the hit and miss counters
+    pub fn reset_stats(&self) {
+        self.hits.store(0, Ordering::Relaxed);
+        self.misses.store(0, Ordering::Relaxed);
+    }
+
+    /// Save every unexpired entry to the database, replacing what was saved
+    /// before, and return how many were written
+    pub async fn save(&self, pool: &DbPool) -> AppResult<usize> {
+        let now = Utc::now();
+        let entries: Vec<_> = self
+            .cache
+            .iter()
+            .filter(|r| !r.value().is_expired(self.ttl))
+            .map(|r| {
+                let (key, entry) = r.pair();
+                let age = chrono::Duration::from_std(entry.created_at.elapsed()).unwrap_or_default();
+                PersistedTranslation {
+                    text: key.text.clone(),
+                    source_lang: key.source_lang.clone(),
+                    target_lang: key.target_lang.clone(),
+                    context: key.context.clone(),
+                    formality: key.formality.as_str().to_string(),
+                    translated_text: entry.translated_text.clone(),
+                    cached_at: now - age,
+                }
+            })
+            .collect();
+
+        TranslationCacheRepo::replace_all(pool, &entries).await?;
+        Ok(entries.len())
+    }
+
+    /// Load entries saved by [`Self::save`], keeping their original age so
+    /// the TTL still applies, and return how many were still fresh
+    pub async fn load(&self, pool: &DbPool) -> AppResult<usize> {
+        let now = Utc::now();
+        let mut loaded = 0;
+        for row in TranslationCacheRepo::load_all(pool).await? {
+            let age = (now - row.cached_at).to_std().unwrap_or_default();
+            if age > self.ttl || self.cache.len() >= self.max_size {
+                continue;
+            }
+            let key = CacheKey {
+                text: row.text,
+                source_lang: row.source_lang,
+                target_lang: row.target_lang,
+                context: row.context,
+                formality: Formality::from(row.formality.as_str()),
+            };
+            let created_at = Instant::now().checked_sub(age).unwrap_or_else(Instant::now);
+            self.cache.insert(
+                key,
+                CacheEntry {
+                    translated_text: row.translated_text,
+                    created_at,
+                },
+            );
+            loaded += 1;
+        }
+        Ok(loaded)
+    }
+
     /// Get cache statistics
     pub fn stats(&self) -> CacheStats {
         let total = self.cache.len();
@@ -131,11 +216,17 @@ impl TranslationCache {
             .iter()
             .filter(|r| r.value().is_expired(self.ttl))
             .count();
+        let hits = self.hits.load(Ordering::Relaxed);
+        let misses = self.misses.load(Ordering::Relaxed);
+        let lookups = hits + misses;
         CacheStats {
             total_entries: total,
             expired_entries: expired,
             max_size: self.max_size,
             ttl_secs: self.ttl.as_secs(),
+            hits,
+            misses,
+            hit_rate: if lookups > 0 { hits as f64 / lookups as f64 } else { 0.0 },
         }
     }
 }
@@ -146,6 +237,10 @@ pub struct CacheStats {
     pub expired_entries: usize,
     pub max_size: usize,
     pub ttl_secs: u64,
+    pub hits: u64,
+    pub misses: u64,
+    /// Fraction of lookups since the last reset that were hits
+    pub hit_rate: f64,
 }
 
 #[cfg(test)]
@@ -159,6 +254,8 @@ mod tests {
             text: "Hello".to_string(),
             source_lang: "en".to_string(),
             target_lang: "es".to_string(),
+            context: None,
+            formality: Formality::Default,
         };
 
         cache.insert(key.clone(), "Hola".to_string());
@@ -172,6 +269,8 @@ mod tests {
             text: "Hello".to_string(),
             source_lang: "en".to_string(),
             target_lang: "es".to_string(),
+            context: None,
+            formality: Formality::Default,
         };
 
         assert_eq!(cache.get(&key), None);
@@ -184,6 +283,8 @@ mod tests {
             text: "Hello".to_string(),
             source_lang: "en".to_string(),
             target_lang: "es".to_string(),
+            context: None,
+            formality: Formality::Default,
         };
 
         cache.insert(key.clone(), "Hola".to_string());
@@ -198,6 +299,8 @@ mod tests {
             text: "Hello".to_string(),
             source_lang: "en".to_string(),
             target_lang: "es".to_string(),
+            context: None,
+            formality: Formality::Default,
         };
 
         cache.insert(key.clone(), "Hola".to_string());
@@ -212,11 +315,15 @@ mod tests {
             text: "Hello".to_string(),
             source_lang: "en".to_string(),
             target_lang: "es".to_string(),
+            context: None,
+            formality: Formality::Default,
         };
         let key2 = CacheKey {
             text: "Hello".to_string(),
             source_lang: "en".to_string(),
             target_lang: "fr".to_string(),
+            context: None,
+            formality: Formality::Default,
         };
 
         cache.insert(key1.clone(), "Hola".to_string());
@@ -232,6 +339,8 @@ mod tests {
             text: "Hello".to_string(),
             source_lang: "en".to_string(),
             target_lang: "es".to_string(),
+            context: None,
+            formality: Formality::Default,
         };
 
         cache.insert(key.clone(), "Hola".to_string());
@@ -248,6 +357,8 @@ mod tests {
             text: "Hello".to_string(),
             source_lang: "en".to_string(),
             target_lang: "es".to_string(),
+            context: None,
+            formality: Formality::Default,
         };
 
         cache.insert(key, "Hola".to_string());
@@ -258,6 +369,29 @@ mod tests {
         assert_eq!(stats.ttl_secs, 3600);
     }
 
+    #[test]
+    fn test_cache_hit_rate() {
+        let cache = TranslationCache::new(3600, 100);
+        let key = CacheKey {
+            text: "Hello".to_string(),
+            source_lang: "en".to_string(),
+            target_lang: "es".to_string(),
+            context: None,
+            formality: Formality::Default,
+        };
+
+        assert_eq!(cache.get(&key), None);
+        cache.insert(key.clone(), "Hola".to_string());
+        cache.get(&key);
+        cache.get(&key);
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses), (2, 1));
+        assert!((stats.hit_rate - 2.0 / 3.0).abs() < 1e-9);
+
+        cache.reset_stats();
+        assert_eq!(cache.stats().hit_rate, 0.0);
+    }
+
     #[test]
     fn test_cache_evict_expired() {
         let cache = TranslationCache::new(0, 1000); // 0 TTL = instant expiry
@@ -265,6 +399,8 @@ mod tests {
             text: "Hello".to_string(),
             source_lang: "en".to_string(),
             target_lang: "es".to_string(),
+            context: None,
+            formality: Formality::Default,
         };
 
         cache.insert(key, "Hola".to_string());
@@ -282,6 +418,8 @@ mod tests {
                 text: format!("text_{}", i),
                 source_lang: "en".to_string(),
                 target_lang: "es".to_string(),
+                context: None,
+                formality: Formality::Default,
             };
             cache.insert(key, format!("translated_{}", i));
         }
@@ -309,6 +447,8 @@ mod proptests {
                 text: text,
                 source_lang: src,
                 target_lang: tgt,
+                context: None,
+                formality: Formality::Default,
             };
             cache.insert(key.clone(), result.clone());
             let retrieved = cache.get(&key);
@@ -330,6 +470,8 @@ mod proptests {
                     text,
                     source_lang: src,
                     target_lang: tgt,
+                    context: None,
+                    formality: Formality::Default,
                 };
                 cache.insert(key, result);
             }
@@ -350,6 +492,8 @@ mod proptests {
                 text,
                 source_lang: src,
                 target_lang: tgt,
+                context: None,
+                formality: Formality::Default,
             };
             cache.insert(key.clone(), result1);
             cache.insert(key.clone(), result2.clone());
@@ -357,4 +501,49 @@ mod proptests {
             prop_assert_eq!(retrieved, Some(result2));
         }
     }
+
+    #[tokio::test]
+    async fn test_entries_survive_save_and_reload() {
+        let pool = crate::db::queries::setup_test_db().await;
+        let cache = TranslationCache::new(3600, 1000);
+        let key = CacheKey {
+            text: "Hello".to_string(),
+            source_lang: "en".to_string(),
+            target_lang: "de".to_string(),
+            context: Some("gaming".to_string()),
+            formality: Formality::Formal,
+        };
+        cache.insert(key.clone(), "Guten Tag".to_string());
+        assert_eq!(cache.save(&pool).await.unwrap(), 1);
+
+        let reopened = TranslationCache::new(3600, 1000);
+        assert_eq!(reopened.load(&pool).await.unwrap(), 1);
+        assert_eq!(reopened.get(&key), Some("Guten Tag".to_string()));
+
+        // A second save replaces the first rather than adding to it
+        cache.clear();
+        assert_eq!(cache.save(&pool).await.unwrap(), 0);
+        let empty = TranslationCache::new(3600, 1000);
+        assert_eq!(empty.load(&pool).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_expired_entries_not_reloaded() {
+        let pool = crate::db::queries::setup_test_db().await;
+        let cache = TranslationCache::new(3600, 1000);
+        let key = CacheKey {
+            text: "Hello".to_string(),
+            source_lang: "en".to_string(),
+            target_lang: "es".to_string(),
+            context: None,
+            formality: Formality::Default,
+        };
+        cache.insert(key.clone(), "Hola".to_string());
+        cache.save(&pool).await.unwrap();
+
+        let short_lived = TranslationCache::new(0, 1000);
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        assert_eq!(short_lived.load(&pool).await.unwrap(), 0);
+        assert_eq!(short_lived.get(&key), None);
+    }
 }
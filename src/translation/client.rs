@@ -1,18 +1,81 @@
+use crate::admin::InferenceTokens;
 use crate::config::AppConfig;
 use crate::error::{AppError, AppResult};
 use crate::translation::cache::{CacheKey, TranslationCache};
-use reqwest::Client;
+use crate::translation::formality::Formality;
+use crate::translation::rate_limit::{RateLimitInfo, RateLimitSnapshot, RateLimitState};
+use crate::translation::readiness::{Readiness, ServiceState};
+use crate::translation::recent_errors::{FailureRecord, RecentErrors};
+use crate::translation::shadow::ShadowTranslator;
+use futures::stream::{self, BoxStream, StreamExt};
+use reqwest::{Client, RequestBuilder};
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
-use std::time::Duration;
+use std::collections::BTreeSet;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
 use tracing::{debug, error, info, warn};
 
+/// How often the health monitor retries while the service is starting
+const STARTUP_RETRY_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Longest translation context hint, in characters
+pub const MAX_CONTEXT_CHARS: usize = 200;
+
 /// Request body for translation
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct TranslateRequest {
     pub text: String,
     pub source_lang: String,
     pub target_lang: String,
+    /// Domain hint for backends that accept one, e.g. "casual gaming chat"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    /// Register for backends that support one for `target_lang`
+    #[serde(skip_serializing_if = "Formality::is_default")]
+    pub formality: Formality,
+}
+
+/// How a translation should read, beyond its languages
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TranslationHints<'a> {
+    /// Domain hint, e.g. "casual gaming chat"
+    pub context: Option<&'a str>,
+    pub formality: Formality,
+}
+
+/// `input` as a translation context hint: whitespace collapsed to single
+/// spaces, None when blank, and an error when over [`MAX_CONTEXT_CHARS`]
+pub fn parse_context(input: &str) -> Result<Option<String>, String> {
+    let context = input.split_whitespace().collect::<Vec<_>>().join(" ");
+    let chars = context.chars().count();
+    if chars > MAX_CONTEXT_CHARS {
+        return Err(format!(
+            "Context is {} characters; the limit is {}",
+            chars, MAX_CONTEXT_CHARS
+        ));
+    }
+    Ok((!context.is_empty()).then_some(context))
+}
+
+/// Request body for a streamed batch translation
+#[derive(Debug, Clone, Serialize)]
+pub struct TranslateBatchRequest {
+    pub texts: Vec<String>,
+    pub source_lang: String,
+    pub target_lang: String,
+}
+
+/// One NDJSON line of a `/translate/stream` response
+#[derive(Debug, Deserialize)]
+struct StreamedTranslation {
+    /// Position of the text in the request
+    index: usize,
+    #[serde(default)]
+    translated_text: Option<String>,
+    /// Why this text failed, when it did
+    #[serde(default)]
+    error: Option<String>,
 }
 
 /// Response from translation service
@@ -23,6 +86,9 @@ pub struct TranslateResponse {
     pub target_lang: String,
     #[serde(default)]
     pub confidence: Option<f32>,
+    /// Model that produced the translation, for services that report it
+    #[serde(default)]
+    pub model: Option<String>,
 }
 
 /// Request for language detection
@@ -45,6 +111,82 @@ pub struct HealthResponse {
     pub model: String,
     #[serde(default)]
     pub model_loaded: bool,
+    #[serde(default)]
+    pub detector_loaded: bool,
+    #[serde(default)]
+    pub stt_loaded: bool,
+    #[serde(default)]
+    pub tts_loaded: bool,
+    #[serde(default)]
+    pub stt_model: Option<String>,
+    #[serde(default)]
+    pub tts_model: Option<String>,
+    /// ISO 639-1 codes the translation model accepts (empty if not reported)
+    #[serde(default)]
+    pub supported_languages: Vec<String>,
+    /// Target languages the model can make formal or informal (empty if
+    /// none, or not reported)
+    #[serde(default)]
+    pub formality_languages: Vec<String>,
+    /// Longest text accepted by /translate and /detect, in characters
+    #[serde(default)]
+    pub max_input_chars: Option<usize>,
+    /// Voice protocol version the service speaks (absent before versioning)
+    #[serde(default)]
+    pub protocol_version: Option<u32>,
+    /// Whether the service has an `HF_TOKEN` (absent on older services)
+    #[serde(default)]
+    pub hf_token_configured: Option<bool>,
+}
+
+/// What the inference backend can do, as reported by its last health check
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Capabilities {
+    pub model: String,
+    pub supported_languages: Vec<String>,
+    pub formality_languages: Vec<String>,
+    pub tts_available: bool,
+    pub stt_available: bool,
+    pub max_input_chars: Option<usize>,
+}
+
+impl Capabilities {
+    /// Whether the backend translates `code`. Older backends don't report
+    /// their languages, in which case every code is assumed supported.
+    pub fn supports_language(&self, code: &str) -> bool {
+        self.supported_languages.is_empty()
+            || self
+                .supported_languages
+                .iter()
+                .any(|lang| lang.eq_ignore_ascii_case(code))
+    }
+
+    /// Whether the backend honours a formality for translations into `code`.
+    /// Backends that don't report it are assumed not to.
+    pub fn supports_formality(&self, code: &str) -> bool {
+        self.formality_languages
+            .iter()
+            .any(|lang| lang.eq_ignore_ascii_case(code))
+    }
+
+    /// Whether `text` fits in a single translation request
+    pub fn accepts_input(&self, text: &str) -> bool {
+        self.max_input_chars
+            .is_none_or(|max| text.chars().count() <= max)
+    }
+}
+
+impl From<&HealthResponse> for Capabilities {
+    fn from(health: &HealthResponse) -> Self {
+        Self {
+            model: health.model.clone(),
+            supported_languages: health.supported_languages.clone(),
+            formality_languages: health.formality_languages.clone(),
+            tts_available: health.tts_loaded,
+            stt_available: health.stt_loaded,
+            max_input_chars: health.max_input_chars,
+        }
+    }
 }
 
 /// Translation result with metadata
@@ -61,8 +203,26 @@ pub struct TranslationResult {
 pub struct TranslationClient {
     http: Client,
     base_url: String,
+    /// Per-request timeout, scaled by item count for streamed batches
+    request_timeout: Duration,
     cache: Arc<TranslationCache>,
     max_retries: u32,
+    /// Capabilities from the last successful health check
+    capabilities: RwLock<Option<Capabilities>>,
+    /// Second backend that sampled translations are compared against
+    shadow: Option<ShadowTranslator>,
+    /// Quota and back-off reported by the service's rate-limit headers
+    rate_limit: RateLimitState,
+    /// Longest back-off to sit out inside a request; longer ones fail fast
+    max_retry_after: Duration,
+    /// Whether the service has come up, from health checks
+    readiness: Arc<Readiness>,
+    /// How long a request waits for a starting service before failing
+    startup_wait: Duration,
+    /// Bearer tokens presented per guild
+    tokens: InferenceTokens,
+    /// Last few failures per guild, for `/admin errors`
+    recent_errors: RecentErrors,
 }
 
 impl std::fmt::Debug for TranslationClient {
@@ -77,8 +237,9 @@ impl std::fmt::Debug for TranslationClient {
 impl TranslationClient {
     /// Create a new translation client from config
     pub fn new(config: &AppConfig) -> Self {
+        let request_timeout = Duration::from_secs(config.inference.timeout_secs);
         let http = Client::builder()
-            .timeout(Duration::from_secs(config.inference.timeout_secs))
+            .timeout(request_timeout)
             .build()
             .expect("Failed to create HTTP client");
 
@@ -90,22 +251,65 @@ impl TranslationClient {
         Self {
             http,
             base_url: config.inference.url.trim_end_matches('/').to_string(),
+            request_timeout,
             cache,
             max_retries: config.inference.max_retries,
+            capabilities: RwLock::new(None),
+            shadow: None,
+            rate_limit: RateLimitState::new(),
+            max_retry_after: Duration::from_secs(config.inference.max_retry_after_secs),
+            readiness: Arc::new(Readiness::new()),
+            startup_wait: Duration::from_secs(config.inference.startup_wait_secs),
+            tokens: InferenceTokens::default(),
+            recent_errors: RecentErrors::new(),
+        }
+    }
+
+    /// Authenticate requests with `tokens`, each guild with its own
+    pub fn with_inference_tokens(mut self, tokens: InferenceTokens) -> Self {
+        self.tokens = tokens;
+        self
+    }
+
+    /// Attach the bearer token for `guild_id`, if one was provisioned
+    fn authorized(&self, request: RequestBuilder, guild_id: Option<u64>) -> RequestBuilder {
+        match self.tokens.for_guild(guild_id) {
+            Some(token) => request.bearer_auth(token),
+            None => request,
         }
     }
 
+    /// Also send sampled translations to `shadow` for comparison
+    pub fn with_shadow(mut self, shadow: ShadowTranslator) -> Self {
+        self.shadow = Some(shadow);
+        self
+    }
+
     /// Check if the inference service is healthy
     pub async fn health_check(&self) -> AppResult<HealthResponse> {
         let url = format!("{}/health", self.base_url);
         debug!("Checking inference service health at {}", url);
 
-        let response = self.http
-            .get(&url)
+        let health = self.fetch_health(&url).await;
+        let (previous, next) = self
+            .readiness
+            .record_check(health.as_ref().ok().map(|h| h.status == "ok"));
+        if previous != next {
+            info!("Inference service {} (was {})", next, previous);
+        }
+
+        let health = health?;
+        *self.capabilities.write().unwrap_or_else(|e| e.into_inner()) = Some((&health).into());
+        Ok(health)
+    }
+
+    async fn fetch_health(&self, url: &str) -> AppResult<HealthResponse> {
+        let response = self
+            .authorized(self.http.get(url), None)
             .send()
             .await
             .map_err(|e| {
-                error!("Health check failed: {}", e);
+                debug!("Health check failed: {}", e);
                 AppError::InferenceUnavailable
             })?;
 
@@ -119,8 +323,85 @@ impl TranslationClient {
         })
     }
 
-    /// Detect the language of a text
-    pub async fn detect_language(&self, text: &str) -> AppResult<DetectResponse> {
+    /// Whether the inference service has come up, as of the last health check
+    pub fn state(&self) -> ServiceState {
+        self.readiness.state()
+    }
+
+    /// Shared handle on the service state, for status endpoints
+    pub fn readiness(&self) -> Arc<Readiness> {
+        Arc::clone(&self.readiness)
+    }
+
+    /// Hold a request briefly while the service is still starting, failing
+    /// with [`AppError::InferenceStarting`] if it doesn't come up in time
+    async fn wait_until_started(&self) -> AppResult<()> {
+        match self.readiness.wait_until_started(self.startup_wait).await {
+            ServiceState::Starting => Err(AppError::InferenceStarting),
+            _ => Ok(()),
+        }
+    }
+
+    /// Backend capabilities cached from the last successful health check,
+    /// or `None` if the service has not answered one yet
+    pub fn capabilities(&self) -> Option<Capabilities> {
+        self.capabilities
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Last rate-limit quota reported by the service
+    pub fn rate_limit(&self) -> RateLimitSnapshot {
+        self.rate_limit.snapshot(Instant::now())
+    }
+
+    /// Wait out a back-off the service asked for, or fail straight away if
+    /// it is longer than `max_retry_after`
+    async fn wait_for_quota(&self) -> AppResult<()> {
+        let Some(wait) = self.rate_limit.wait_time(Instant::now()) else {
+            return Ok(());
+        };
+        if wait > self.max_retry_after {
+            warn!("Inference service rate limited for another {:?}, not waiting", wait);
+            return Err(AppError::RateLimitExceeded);
+        }
+        debug!("Inference service rate limited, waiting {:?}", wait);
+        tokio::time::sleep(wait).await;
+        Ok(())
+    }
+
+    /// Record rate-limit headers; returns whether the response was throttled
+    fn observe_rate_limit(&self, response: &reqwest::Response) -> bool {
+        let info = RateLimitInfo::from_headers(response.headers(), chrono::Utc::now());
+        let status = response.status();
+        let throttled = status == reqwest::StatusCode::TOO_MANY_REQUESTS
+            || (status == reqwest::StatusCode::SERVICE_UNAVAILABLE && info.retry_after.is_some());
+        self.rate_limit.observe(&info, throttled, Instant::now());
+        throttled
+    }
+
+    /// Up to `limit` of the guild's recent translation failures, newest first
+    pub fn recent_errors(&self, guild_id: u64, limit: usize) -> Vec<FailureRecord> {
+        self.recent_errors.recent(guild_id, limit)
+    }
+
+    fn note_failure(&self, guild_id: Option<u64>, text: &str, target_lang: Option<&str>, error: &AppError) {
+        if let Some(guild_id) = guild_id {
+            self.recent_errors.record(guild_id, text, target_lang, error);
+        }
+    }
+
+    /// Detect the language of a text, authenticating as `guild_id`
+    pub async fn detect_language(&self, text: &str, guild_id: Option<u64>) -> AppResult<DetectResponse> {
+        let result = self.request_detection(text, guild_id).await;
+        if let Err(e) = &result {
+            self.note_failure(guild_id, text, None, e);
+        }
+        result
+    }
+
+    async fn request_detection(&self, text: &str, guild_id: Option<u64>) -> AppResult<DetectResponse> {
         let url = format!("{}/detect", self.base_url);
         let request = DetectRequest {
             text: text.to_string(),
@@ -128,8 +409,10 @@ impl TranslationClient {
 
         debug!("Detecting language for text: {}...", &text.chars().take(50).collect::<String>());
 
-        let response = self.http
-            .post(&url)
+        self.wait_until_started().await?;
+        self.wait_for_quota().await?;
+        let response = self
+            .authorized(self.http.post(&url), guild_id)
             .json(&request)
             .send()
             .await
@@ -138,6 +421,10 @@ impl TranslationClient {
                 AppError::InferenceUnavailable
             })?;
 
+        if self.observe_rate_limit(&response) {
+            warn!("Language detection throttled by inference service");
+            return Err(AppError::RateLimitExceeded);
+        }
         if !response.status().is_success() {
             let status = response.status();
             let body = response.text().await.unwrap_or_default();
@@ -151,12 +438,29 @@ impl TranslationClient {
         })
     }
 
-    /// Translate text from source language to target language
+    /// Translate text from source language to target language, authenticating
+    /// as `guild_id`
     pub async fn translate(
         &self,
         text: &str,
         source_lang: &str,
         target_lang: &str,
+        guild_id: Option<u64>,
+    ) -> AppResult<TranslationResult> {
+        self.translate_with_hints(text, source_lang, target_lang, guild_id, TranslationHints::default())
+            .await
+    }
+
+    /// [`translate`](Self::translate) with a hint about the conversation, such
+    /// as "casual gaming chat", and a formality, for backends that accept
+    /// them. Translations are cached per hint.
+    pub async fn translate_with_hints(
+        &self,
+        text: &str,
+        source_lang: &str,
+        target_lang: &str,
+        guild_id: Option<u64>,
+        hints: TranslationHints<'_>,
     ) -> AppResult<TranslationResult> {
         // Skip translation if source and target are the same
         if source_lang == target_lang {
@@ -174,6 +478,8 @@ impl TranslationClient {
             text: text.to_string(),
             source_lang: source_lang.to_string(),
             target_lang: target_lang.to_string(),
+            context: hints.context.map(str::to_string),
+            formality: hints.formality,
         };
 
         if let Some(cached) = self.cache.get(&cache_key) {
@@ -188,7 +494,25 @@ impl TranslationClient {
         }
 
         // Make request with retries
-        let result = self.translate_with_retry(text, source_lang, target_lang).await?;
+        let started = Instant::now();
+        let result = self
+            .translate_with_retry(text, source_lang, target_lang, guild_id, hints)
+            .await
+            .inspect_err(|e| self.note_failure(guild_id, text, Some(target_lang), e))?;
+
+        if let Some(shadow) = &self.shadow {
+            shadow.observe(
+                TranslateRequest {
+                    text: text.to_string(),
+                    source_lang: source_lang.to_string(),
+                    target_lang: target_lang.to_string(),
+                    context: hints.context.map(str::to_string),
+                    formality: hints.formality,
+                },
+                result.translated_text.clone(),
+                started.elapsed(),
+            );
+        }
 
         // Cache the result
         self.cache.insert(cache_key, result.translated_text.clone());
@@ -207,13 +531,14 @@ impl TranslationClient {
         &self,
         text: &str,
         target_lang: &str,
+        guild_id: Option<u64>,
     ) -> AppResult<TranslationResult> {
         // Detect source language
-        let detection = self.detect_language(text).await?;
+        let detection = self.detect_language(text, guild_id).await?;
         info!("Detected language: {} (confidence: {:.2})", detection.language, detection.confidence);
 
         // Translate
-        self.translate(text, &detection.language, target_lang).await
+        self.translate(text, &detection.language, target_lang, guild_id).await
     }
 
     /// Translate to multiple target languages
@@ -222,42 +547,195 @@ impl TranslationClient {
         text: &str,
         source_lang: &str,
         target_langs: &[String],
+        guild_id: Option<u64>,
+        hints: TranslationHints<'_>,
     ) -> Vec<AppResult<TranslationResult>> {
         let futures: Vec<_> = target_langs
             .iter()
-            .map(|target| self.translate(text, source_lang, target))
+            .map(|target| self.translate_with_hints(text, source_lang, target, guild_id, hints))
             .collect();
 
         futures::future::join_all(futures).await
     }
 
+    /// Translate a batch of texts, yielding each as soon as it is ready
+    ///
+    /// Cached texts come first; the rest are sent in one request to the
+    /// service's `/translate/stream` endpoint and yielded as their lines
+    /// arrive, each cached on the way. Items carry their index in `texts`
+    /// and may arrive in any order. Every text is yielded exactly once,
+    /// with an error if the service never answered for it.
+    pub fn translate_stream<'a>(
+        &'a self,
+        texts: &'a [String],
+        source_lang: &'a str,
+        target_lang: &'a str,
+        guild_id: Option<u64>,
+    ) -> BoxStream<'a, (usize, AppResult<TranslationResult>)> {
+        let result = move |index: usize, translated_text: String, cached: bool| TranslationResult {
+            original_text: texts[index].clone(),
+            translated_text,
+            source_lang: source_lang.to_string(),
+            target_lang: target_lang.to_string(),
+            cached,
+        };
+
+        if source_lang == target_lang {
+            return stream::iter(texts.iter().enumerate())
+                .map(move |(index, text)| (index, Ok(result(index, text.clone(), false))))
+                .boxed();
+        }
+
+        let mut ready = Vec::new();
+        let mut missing = Vec::new();
+        for (index, text) in texts.iter().enumerate() {
+            let key = CacheKey {
+                text: text.clone(),
+                source_lang: source_lang.to_string(),
+                target_lang: target_lang.to_string(),
+                context: None,
+                formality: Formality::Default,
+            };
+            match self.cache.get(&key) {
+                Some(cached) => ready.push((index, Ok(result(index, cached, true)))),
+                None => missing.push(index),
+            }
+        }
+        debug!("Batch of {}: {} cached, {} to translate", texts.len(), ready.len(), missing.len());
+
+        let fetched = stream::once(async move {
+            if missing.is_empty() {
+                return stream::empty().boxed();
+            }
+            match self.open_stream(texts, &missing, source_lang, target_lang, guild_id).await {
+                Ok(response) => BatchStream {
+                    client: self,
+                    texts,
+                    source_lang,
+                    target_lang,
+                    guild_id,
+                    bytes: response.bytes_stream().boxed(),
+                    buf: Vec::new(),
+                    indices: missing.clone(),
+                    unanswered: missing.into_iter().collect(),
+                    ended: false,
+                }
+                .into_stream(),
+                Err(e) => {
+                    for &index in &missing {
+                        self.note_failure(guild_id, &texts[index], Some(target_lang), &e);
+                    }
+                    let error = e.to_string();
+                    stream::iter(missing)
+                        .map(move |index| (index, Err(batch_error(&e, &error))))
+                        .boxed()
+                }
+            }
+        })
+        .flatten();
+
+        stream::iter(ready).chain(fetched).boxed()
+    }
+
+    /// Send the uncached texts of a batch, returning the response once the
+    /// service has accepted it
+    async fn open_stream(
+        &self,
+        texts: &[String],
+        missing: &[usize],
+        source_lang: &str,
+        target_lang: &str,
+        guild_id: Option<u64>,
+    ) -> AppResult<reqwest::Response> {
+        let url = format!("{}/translate/stream", self.base_url);
+        let request = TranslateBatchRequest {
+            texts: missing.iter().map(|&i| texts[i].clone()).collect(),
+            source_lang: source_lang.to_string(),
+            target_lang: target_lang.to_string(),
+        };
+
+        self.wait_until_started().await?;
+        self.wait_for_quota().await?;
+
+        // The client-wide timeout covers the whole body, so give a long
+        // batch the time its items would have had one by one
+        let timeout = self.request_timeout * missing.len().min(u32::MAX as usize) as u32;
+        let response = self
+            .authorized(self.http.post(&url), guild_id)
+            .timeout(timeout)
+            .json(&request)
+            .send()
+            .await?;
+
+        if self.observe_rate_limit(&response) {
+            warn!("Batch translation throttled by inference service");
+            return Err(AppError::RateLimitExceeded);
+        }
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            error!("Batch translation failed with status {}: {}", status, body);
+            return Err(AppError::Translation(format!("Service returned {}: {}", status, body)));
+        }
+        Ok(response)
+    }
+
+    /// Base URL of the inference service this client talks to
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Translate straight through the service, skipping the cache and shadow
+    /// comparison (used to verify the backend itself)
+    pub async fn translate_uncached(
+        &self,
+        text: &str,
+        source_lang: &str,
+        target_lang: &str,
+    ) -> AppResult<TranslateResponse> {
+        self.translate_with_retry(text, source_lang, target_lang, None, TranslationHints::default())
+            .await
+    }
+
     /// Internal: translate with retry logic
     async fn translate_with_retry(
         &self,
         text: &str,
         source_lang: &str,
         target_lang: &str,
+        guild_id: Option<u64>,
+        hints: TranslationHints<'_>,
     ) -> AppResult<TranslateResponse> {
         let url = format!("{}/translate", self.base_url);
         let request = TranslateRequest {
             text: text.to_string(),
             source_lang: source_lang.to_string(),
             target_lang: target_lang.to_string(),
+            context: hints.context.map(str::to_string),
+            formality: hints.formality,
         };
 
+        self.wait_until_started().await?;
         let mut last_error = None;
 
         for attempt in 0..=self.max_retries {
-            if attempt > 0 {
+            // A back-off requested by the service replaces the exponential one
+            let throttled = self.rate_limit.wait_time(Instant::now()).is_some();
+            if attempt > 0 && !throttled {
                 let delay = Duration::from_millis(100 * 2u64.pow(attempt));
                 warn!("Retrying translation (attempt {}/{}), waiting {:?}",
                     attempt, self.max_retries, delay);
                 tokio::time::sleep(delay).await;
             }
+            self.wait_for_quota().await?;
 
-            match self.http.post(&url).json(&request).send().await {
+            match self.authorized(self.http.post(&url), guild_id).json(&request).send().await {
                 Ok(response) => {
-                    if response.status().is_success() {
+                    if self.observe_rate_limit(&response) {
+                        warn!("Translation throttled by inference service (attempt {}/{})",
+                            attempt, self.max_retries);
+                        last_error = Some(AppError::RateLimitExceeded);
+                    } else if response.status().is_success() {
                         match response.json::<TranslateResponse>().await {
                             Ok(result) => return Ok(result),
                             Err(e) => {
@@ -290,15 +768,323 @@ impl TranslationClient {
         self.cache.stats()
     }
 
-    /// Clear the translation cache
-    pub fn clear_cache(&self) {
+    /// Save the translation cache to the database, returning how many
+    /// entries were written
+    pub async fn save_cache(&self, pool: &crate::db::DbPool) -> AppResult<usize> {
+        self.cache.save(pool).await
+    }
+
+    /// Fill the translation cache from the last save, returning how many
+    /// entries were still fresh
+    pub async fn load_cache(&self, pool: &crate::db::DbPool) -> AppResult<usize> {
+        self.cache.load(pool).await
+    }
+
+    /// Clear the translation cache and its hit counters, returning how many
+    /// entries were dropped
+    pub fn clear_cache(&self) -> usize {
+        let cleared = self.cache.len();
         self.cache.clear();
+        self.cache.reset_stats();
+        cleared
     }
 }
 
+/// `error` again for another item of a failed batch; `AppError` isn't
+/// `Clone`, so variants that carry a source keep only its message
+fn batch_error(error: &AppError, message: &str) -> AppError {
+    match error {
+        AppError::RateLimitExceeded => AppError::RateLimitExceeded,
+        AppError::InferenceStarting => AppError::InferenceStarting,
+        AppError::InferenceUnavailable => AppError::InferenceUnavailable,
+        _ => AppError::Translation(message.to_string()),
+    }
+}
+
+/// Reads a `/translate/stream` response line by line
+struct BatchStream<'a> {
+    client: &'a TranslationClient,
+    texts: &'a [String],
+    source_lang: &'a str,
+    target_lang: &'a str,
+    guild_id: Option<u64>,
+    bytes: BoxStream<'a, reqwest::Result<bytes::Bytes>>,
+    /// Received bytes not yet split into lines
+    buf: Vec<u8>,
+    /// Positions in `texts` of the items sent, in request order
+    indices: Vec<usize>,
+    /// Positions in `texts` still waiting for a line
+    unanswered: BTreeSet<usize>,
+    ended: bool,
+}
+
+impl<'a> BatchStream<'a> {
+    fn into_stream(self) -> BoxStream<'a, (usize, AppResult<TranslationResult>)> {
+        stream::unfold(self, |mut batch| async move {
+            let item = batch.next_item().await?;
+            Some((item, batch))
+        })
+        .boxed()
+    }
+
+    async fn next_item(&mut self) -> Option<(usize, AppResult<TranslationResult>)> {
+        loop {
+            if let Some(end) = self.buf.iter().position(|&b| b == b'\n') {
+                let line: Vec<u8> = self.buf.drain(..=end).collect();
+                if let Some(item) = self.resolve(&line) {
+                    return Some(item);
+                }
+                continue;
+            }
+
+            if self.ended {
+                if !self.buf.is_empty() {
+                    // A last line without its newline
+                    self.buf.push(b'\n');
+                    continue;
+                }
+                let index = self.unanswered.pop_first()?;
+                let error = AppError::Translation("Stream ended before this text was translated".to_string());
+                self.note_failure(index, &error);
+                return Some((index, Err(error)));
+            }
+
+            match self.bytes.next().await {
+                Some(Ok(chunk)) => self.buf.extend_from_slice(&chunk),
+                Some(Err(e)) => {
+                    error!("Batch translation stream failed: {}", e);
+                    self.ended = true;
+                }
+                None => self.ended = true,
+            }
+        }
+    }
+
+    /// The item a line answers, unless it is blank, malformed or a repeat
+    fn resolve(&mut self, line: &[u8]) -> Option<(usize, AppResult<TranslationResult>)> {
+        if line.trim_ascii().is_empty() {
+            return None;
+        }
+        let streamed: StreamedTranslation = match serde_json::from_slice(line) {
+            Ok(streamed) => streamed,
+            Err(e) => {
+                warn!("Skipping malformed batch translation line: {}", e);
+                return None;
+            }
+        };
+        let index = *self.indices.get(streamed.index)?;
+        if !self.unanswered.remove(&index) {
+            warn!("Ignoring repeated batch translation line for item {}", streamed.index);
+            return None;
+        }
+
+        let Some(translated_text) = streamed.translated_text else {
+            let error = AppError::Translation(
+                streamed.error.unwrap_or_else(|| "Service returned no translation".to_string()),
+            );
+            self.note_failure(index, &error);
+            return Some((index, Err(error)));
+        };
+
+        self.client.cache.insert(
+            CacheKey {
+                text: self.texts[index].clone(),
+                source_lang: self.source_lang.to_string(),
+                target_lang: self.target_lang.to_string(),
+                context: None,
+                formality: Formality::Default,
+            },
+            translated_text.clone(),
+        );
+        Some((
+            index,
+            Ok(TranslationResult {
+                original_text: self.texts[index].clone(),
+                translated_text,
+                source_lang: self.source_lang.to_string(),
+                target_lang: self.target_lang.to_string(),
+                cached: false,
+            }),
+        ))
+    }
+
+    fn note_failure(&self, index: usize, error: &AppError) {
+        self.client
+            .note_failure(self.guild_id, &self.texts[index], Some(self.target_lang), error);
+    }
+}
+
+/// Health-check the inference service until the task is aborted: every
+/// couple of seconds while it is starting, then every `interval`
+pub fn spawn_health_monitor(client: Arc<TranslationClient>, interval: Duration) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            let _ = client.health_check().await;
+            let wait = match client.state() {
+                ServiceState::Starting => STARTUP_RETRY_INTERVAL,
+                _ => interval,
+            };
+            tokio::time::sleep(wait).await;
+        }
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use axum::{http::HeaderMap, routing::post, Json, Router};
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    /// Inference stand-in recording the Authorization header of each request
+    async fn recording_inference() -> (String, Arc<Mutex<Vec<Option<String>>>>) {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let record = seen.clone();
+        let app = Router::new().route(
+            "/translate",
+            post(move |headers: HeaderMap, Json(request): Json<serde_json::Value>| async move {
+                let auth = headers
+                    .get("authorization")
+                    .and_then(|v| v.to_str().ok())
+                    .map(str::to_string);
+                record.lock().unwrap().push(auth);
+                Json(serde_json::json!({
+                    "translated_text": format!("[{}]", request["text"].as_str().unwrap()),
+                    "source_lang": request["source_lang"],
+                    "target_lang": request["target_lang"],
+                }))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (url, seen)
+    }
+
+    #[tokio::test]
+    async fn test_requests_carry_the_guilds_inference_token() {
+        let (url, seen) = recording_inference().await;
+        let mut config = AppConfig::load().unwrap();
+        config.inference.url = url;
+        let no_tokens = TranslationClient::new(&config);
+        let client = TranslationClient::new(&config).with_inference_tokens(
+            InferenceTokens::from_custom_secrets(&HashMap::from([
+                ("inference_token".to_string(), "shared".to_string()),
+                ("inference_token.42".to_string(), "tenant-42".to_string()),
+            ])),
+        );
+
+        for translator in [&client, &no_tokens] {
+            translator.readiness().record_check(Some(true));
+        }
+
+        client.translate("one", "en", "es", Some(42)).await.unwrap();
+        client.translate("two", "en", "es", Some(7)).await.unwrap();
+        client.translate_uncached("three", "en", "es").await.unwrap();
+        no_tokens.translate("four", "en", "es", Some(42)).await.unwrap();
+
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![
+                Some("Bearer tenant-42".to_string()),
+                Some("Bearer shared".to_string()),
+                Some("Bearer shared".to_string()),
+                None,
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failures_are_remembered_per_guild() {
+        let mut config = AppConfig::load().unwrap();
+        config.inference.url = "http://127.0.0.1:1".to_string();
+        config.inference.max_retries = 0;
+        let client = TranslationClient::new(&config);
+        client.readiness().record_check(Some(true));
+
+        assert!(client.translate("hello", "en", "es", Some(5)).await.is_err());
+        assert!(client.detect_language("hello", Some(5)).await.is_err());
+        assert!(client.translate("hello", "en", "es", None).await.is_err());
+
+        let failures = client.recent_errors(5, 10);
+        assert_eq!(failures.len(), 2);
+        assert_eq!(failures[0].target_lang, None);
+        assert_eq!(failures[1].target_lang.as_deref(), Some("es"));
+        assert!(failures.iter().all(|f| f.reason == crate::translation::FailureReason::Unreachable));
+    }
+
+    #[tokio::test]
+    async fn test_batch_stream_yields_before_the_batch_finishes() {
+        // The service answers two texts, then holds the rest until released
+        let (release, held) = tokio::sync::oneshot::channel::<()>();
+        let held = Arc::new(Mutex::new(Some(held)));
+        let app = Router::new().route(
+            "/translate/stream",
+            post(move |Json(request): Json<serde_json::Value>| async move {
+                let held = held.lock().unwrap().take().unwrap();
+                let texts: Vec<String> = serde_json::from_value(request["texts"].clone()).unwrap();
+                let (tx, rx) = tokio::sync::mpsc::unbounded_channel::<String>();
+                tokio::spawn(async move {
+                    for (index, text) in texts.iter().enumerate() {
+                        if index == 2 {
+                            let _ = held.await;
+                            break;
+                        }
+                        tx.send(format!("{{\"index\":{},\"translated_text\":\"[{}]\"}}\n", index, text))
+                            .unwrap();
+                    }
+                    tx.send("{\"index\":3,\"error\":\"Text too long\"}\n".to_string()).unwrap();
+                    // Item 2 is never answered; the last line has no newline
+                    tx.send("{\"index\":4,\"translated_text\":\"[late]\"}".to_string()).unwrap();
+                });
+                let lines = stream::unfold(rx, |mut rx| async move {
+                    let line = rx.recv().await?;
+                    Some((Ok::<_, std::io::Error>(line), rx))
+                });
+                axum::body::Body::from_stream(lines)
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut config = AppConfig::load().unwrap();
+        config.inference.url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        let client = TranslationClient::new(&config);
+        client.readiness().record_check(Some(true));
+        client.cache.insert(
+            CacheKey {
+                text: "cached".to_string(),
+                source_lang: "en".to_string(),
+                target_lang: "es".to_string(),
+                context: None,
+                formality: Formality::Default,
+            },
+            "[from cache]".to_string(),
+        );
+
+        let texts: Vec<String> = ["a", "cached", "b", "c", "d", "late"].map(String::from).to_vec();
+        let mut results = client.translate_stream(&texts, "en", "es", Some(9));
+        let wait = Duration::from_secs(5);
+
+        let (index, first) = tokio::time::timeout(wait, results.next()).await.unwrap().unwrap();
+        assert_eq!((index, first.unwrap().cached), (1, true));
+        for expected in [(0, "[a]"), (2, "[b]")] {
+            let (index, result) = tokio::time::timeout(wait, results.next())
+                .await
+                .expect("streamed before the batch finished")
+                .unwrap();
+            assert_eq!((index, result.unwrap().translated_text.as_str()), expected);
+        }
+
+        release.send(()).unwrap();
+        let rest: Vec<_> = results.collect().await;
+        let outcomes: Vec<_> = rest.iter().map(|(index, result)| (*index, result.is_ok())).collect();
+        assert_eq!(outcomes, vec![(4, false), (5, true), (3, false)]);
+        assert_eq!(client.recent_errors(9, 10).len(), 2);
+
+        // Answered texts were cached as they arrived
+        let again: Vec<_> = client.translate_stream(&texts[..3], "en", "es", None).collect().await;
+        assert!(again.iter().all(|(_, result)| result.as_ref().unwrap().cached));
+    }
 
     #[test]
     fn test_translate_request_serialization() {
@@ -306,10 +1092,121 @@ mod tests {
             text: "Hello".to_string(),
             source_lang: "en".to_string(),
             target_lang: "es".to_string(),
+            context: None,
+            formality: Formality::Default,
         };
         let json = serde_json::to_string(&request).unwrap();
         assert!(json.contains("Hello"));
         assert!(json.contains("en"));
         assert!(json.contains("es"));
+        // Backends without hint support never see the fields
+        assert!(!json.contains("context"));
+        assert!(!json.contains("formality"));
+    }
+
+    #[test]
+    fn test_parse_context() {
+        assert_eq!(
+            parse_context("  casual\n gaming   chat ").unwrap().as_deref(),
+            Some("casual gaming chat")
+        );
+        assert_eq!(parse_context(" \n ").unwrap(), None);
+        assert!(parse_context(&"a".repeat(MAX_CONTEXT_CHARS)).is_ok());
+        assert!(parse_context(&"a".repeat(MAX_CONTEXT_CHARS + 1)).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_hints_are_sent_and_cached_separately() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let record = seen.clone();
+        let app = Router::new().route(
+            "/translate",
+            post(move |Json(request): Json<serde_json::Value>| async move {
+                record
+                    .lock()
+                    .unwrap()
+                    .push((request["context"].clone(), request["formality"].clone()));
+                Json(serde_json::json!({
+                    "translated_text": format!("[{}]", request["text"].as_str().unwrap()),
+                    "source_lang": request["source_lang"],
+                    "target_lang": request["target_lang"],
+                }))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut config = AppConfig::load().unwrap();
+        config.inference.url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        let client = TranslationClient::new(&config);
+        client.readiness().record_check(Some(true));
+
+        let gaming = TranslationHints {
+            context: Some("casual gaming chat"),
+            ..Default::default()
+        };
+        let formal = TranslationHints {
+            formality: Formality::Formal,
+            ..Default::default()
+        };
+        client.translate_with_hints("gg", "en", "de", None, gaming).await.unwrap();
+        let again = client.translate_with_hints("gg", "en", "de", None, gaming).await.unwrap();
+        assert!(again.cached);
+        let sie = client.translate_with_hints("gg", "en", "de", None, formal).await.unwrap();
+        assert!(!sie.cached);
+        let plain = client.translate("gg", "en", "de", None).await.unwrap();
+        assert!(!plain.cached);
+
+        use serde_json::{json, Value};
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![
+                (json!("casual gaming chat"), Value::Null),
+                (Value::Null, json!("formal")),
+                (Value::Null, Value::Null),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_health_response_with_capabilities() {
+        let json = r#"{
+            "status": "ok",
+            "model": "google/translategemma-4b-it",
+            "model_loaded": true,
+            "detector_loaded": true,
+            "stt_loaded": true,
+            "tts_loaded": false,
+            "stt_model": "distil-large-v3",
+            "tts_model": "CosyVoice2-0.5B",
+            "supported_languages": ["en", "es", "fr"],
+            "formality_languages": ["es"],
+            "max_input_chars": 2000
+        }"#;
+        let health: HealthResponse = serde_json::from_str(json).unwrap();
+        let caps = Capabilities::from(&health);
+
+        assert_eq!(caps.model, "google/translategemma-4b-it");
+        assert_eq!(caps.supported_languages, vec!["en", "es", "fr"]);
+        assert!(caps.stt_available);
+        assert!(!caps.tts_available);
+        assert_eq!(caps.max_input_chars, Some(2000));
+        assert!(caps.supports_language("ES"));
+        assert!(!caps.supports_language("ja"));
+        assert!(caps.supports_formality("es"));
+        assert!(!caps.supports_formality("en"));
+        assert!(caps.accepts_input(&"a".repeat(2000)));
+        assert!(!caps.accepts_input(&"a".repeat(2001)));
+    }
+
+    #[test]
+    fn test_health_response_legacy_backend() {
+        let json = r#"{"status": "ok", "model": "m", "model_loaded": true}"#;
+        let health: HealthResponse = serde_json::from_str(json).unwrap();
+        let caps = Capabilities::from(&health);
+
+        // Nothing reported means nothing is restricted
+        assert!(caps.supports_language("ja"));
+        assert!(caps.accepts_input(&"a".repeat(10_000)));
+        assert!(!caps.tts_available);
     }
 }
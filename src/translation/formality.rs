@@ -0,0 +1,63 @@
+use serde::{Deserialize, Serialize};
+
+/// Register a translation is written in, for languages that distinguish one
+/// (e.g. German "Sie" and "du", Japanese keigo)
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, poise::ChoiceParameter,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Formality {
+    /// Whatever the model produces
+    #[default]
+    #[name = "Default"]
+    Default,
+    #[name = "Formal"]
+    Formal,
+    #[name = "Informal"]
+    Informal,
+}
+
+impl Formality {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::Formal => "formal",
+            Self::Informal => "informal",
+        }
+    }
+
+    pub fn is_default(&self) -> bool {
+        *self == Self::Default
+    }
+}
+
+impl From<&str> for Formality {
+    fn from(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "formal" => Self::Formal,
+            "informal" => Self::Informal,
+            _ => Self::Default,
+        }
+    }
+}
+
+impl std::fmt::Display for Formality {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_formality_round_trip() {
+        for formality in [Formality::Default, Formality::Formal, Formality::Informal] {
+            assert_eq!(Formality::from(formality.as_str()), formality);
+        }
+        assert_eq!(Formality::from("FORMAL"), Formality::Formal);
+        assert_eq!(Formality::from("polite-ish"), Formality::Default);
+        assert_eq!(serde_json::to_string(&Formality::Informal).unwrap(), "\"informal\"");
+    }
+}
@@ -1,7 +1,10 @@
 use std::collections::HashMap;
 
 /// Supported language codes based on TranslateGemma's 55 supported languages
-/// Using ISO 639-1 Alpha-2 codes
+///
+/// Codes are BCP 47 tags: an ISO 639-1 code, plus a script or region subtag
+/// for variants the model distinguishes (`zh-Hans`, `zh-Hant`, `pt-BR`).
+/// [`Language::from_code`] also accepts ISO 639-3 (and 639-2) codes.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Language {
     Arabic,
@@ -9,6 +12,8 @@ pub enum Language {
     Bulgarian,
     Catalan,
     Chinese,
+    ChineseSimplified,
+    ChineseTraditional,
     Croatian,
     Czech,
     Danish,
@@ -38,6 +43,7 @@ pub enum Language {
     Persian,
     Polish,
     Portuguese,
+    BrazilianPortuguese,
     Punjabi,
     Romanian,
     Russian,
@@ -56,7 +62,7 @@ pub enum Language {
 }
 
 impl Language {
-    /// Get the ISO 639-1 code for the language
+    /// Get the code for the language (ISO 639-1, with a subtag for variants)
     pub fn code(&self) -> &'static str {
         match self {
             Self::Arabic => "ar",
@@ -64,6 +70,8 @@ impl Language {
             Self::Bulgarian => "bg",
             Self::Catalan => "ca",
             Self::Chinese => "zh",
+            Self::ChineseSimplified => "zh-Hans",
+            Self::ChineseTraditional => "zh-Hant",
             Self::Croatian => "hr",
             Self::Czech => "cs",
             Self::Danish => "da",
@@ -93,6 +101,7 @@ impl Language {
             Self::Persian => "fa",
             Self::Polish => "pl",
             Self::Portuguese => "pt",
+            Self::BrazilianPortuguese => "pt-BR",
             Self::Punjabi => "pa",
             Self::Romanian => "ro",
             Self::Russian => "ru",
@@ -119,6 +128,8 @@ impl Language {
             Self::Bulgarian => "Bulgarian",
             Self::Catalan => "Catalan",
             Self::Chinese => "Chinese",
+            Self::ChineseSimplified => "Chinese (Simplified)",
+            Self::ChineseTraditional => "Chinese (Traditional)",
             Self::Croatian => "Croatian",
             Self::Czech => "Czech",
             Self::Danish => "Danish",
@@ -148,6 +159,7 @@ impl Language {
             Self::Persian => "Persian",
             Self::Polish => "Polish",
             Self::Portuguese => "Portuguese",
+            Self::BrazilianPortuguese => "Portuguese (Brazil)",
             Self::Punjabi => "Punjabi",
             Self::Romanian => "Romanian",
             Self::Russian => "Russian",
@@ -166,78 +178,171 @@ impl Language {
         }
     }
 
+    /// ISO 639-3 code of the base language
+    pub fn iso639_3(&self) -> &'static str {
+        match self {
+            Self::Arabic => "ara",
+            Self::Bengali => "ben",
+            Self::Bulgarian => "bul",
+            Self::Catalan => "cat",
+            Self::Chinese | Self::ChineseSimplified | Self::ChineseTraditional => "zho",
+            Self::Croatian => "hrv",
+            Self::Czech => "ces",
+            Self::Danish => "dan",
+            Self::Dutch => "nld",
+            Self::English => "eng",
+            Self::Estonian => "est",
+            Self::Finnish => "fin",
+            Self::French => "fra",
+            Self::German => "deu",
+            Self::Greek => "ell",
+            Self::Gujarati => "guj",
+            Self::Hebrew => "heb",
+            Self::Hindi => "hin",
+            Self::Hungarian => "hun",
+            Self::Indonesian => "ind",
+            Self::Italian => "ita",
+            Self::Japanese => "jpn",
+            Self::Kannada => "kan",
+            Self::Korean => "kor",
+            Self::Latvian => "lav",
+            Self::Lithuanian => "lit",
+            Self::Macedonian => "mkd",
+            Self::Malay => "msa",
+            Self::Malayalam => "mal",
+            Self::Marathi => "mar",
+            Self::Norwegian => "nor",
+            Self::Persian => "fas",
+            Self::Polish => "pol",
+            Self::Portuguese | Self::BrazilianPortuguese => "por",
+            Self::Punjabi => "pan",
+            Self::Romanian => "ron",
+            Self::Russian => "rus",
+            Self::Serbian => "srp",
+            Self::Slovak => "slk",
+            Self::Slovenian => "slv",
+            Self::Spanish => "spa",
+            Self::Swedish => "swe",
+            Self::Tamil => "tam",
+            Self::Telugu => "tel",
+            Self::Thai => "tha",
+            Self::Turkish => "tur",
+            Self::Ukrainian => "ukr",
+            Self::Urdu => "urd",
+            Self::Vietnamese => "vie",
+        }
+    }
+
     /// Parse a language code string into a Language enum
+    ///
+    /// Accepts ISO 639-1 and 639-3 codes with optional script and region
+    /// subtags (`en`, `eng`, `en_US`, `zh-Hant`, `cmn-TW`, `pt-BR`).
+    /// Subtags pick a variant where one exists and are ignored otherwise.
     pub fn from_code(code: &str) -> Option<Self> {
         let code = code.to_lowercase();
-        // Handle both simple codes (en) and regional variants (en_US, en-GB)
-        let base_code = code.split(|c| c == '_' || c == '-').next()?;
-
-        match base_code {
-            "ar" => Some(Self::Arabic),
-            "bn" => Some(Self::Bengali),
-            "bg" => Some(Self::Bulgarian),
-            "ca" => Some(Self::Catalan),
-            "zh" => Some(Self::Chinese),
-            "hr" => Some(Self::Croatian),
-            "cs" => Some(Self::Czech),
-            "da" => Some(Self::Danish),
-            "nl" => Some(Self::Dutch),
-            "en" => Some(Self::English),
-            "et" => Some(Self::Estonian),
-            "fi" => Some(Self::Finnish),
-            "fr" => Some(Self::French),
-            "de" => Some(Self::German),
-            "el" => Some(Self::Greek),
-            "gu" => Some(Self::Gujarati),
-            "he" | "iw" => Some(Self::Hebrew), // iw is legacy code
-            "hi" => Some(Self::Hindi),
-            "hu" => Some(Self::Hungarian),
-            "id" => Some(Self::Indonesian),
-            "it" => Some(Self::Italian),
-            "ja" => Some(Self::Japanese),
-            "kn" => Some(Self::Kannada),
-            "ko" => Some(Self::Korean),
-            "lv" => Some(Self::Latvian),
-            "lt" => Some(Self::Lithuanian),
-            "mk" => Some(Self::Macedonian),
-            "ms" => Some(Self::Malay),
-            "ml" => Some(Self::Malayalam),
-            "mr" => Some(Self::Marathi),
-            "no" | "nb" | "nn" => Some(Self::Norwegian),
-            "fa" => Some(Self::Persian),
-            "pl" => Some(Self::Polish),
-            "pt" => Some(Self::Portuguese),
-            "pa" => Some(Self::Punjabi),
-            "ro" => Some(Self::Romanian),
-            "ru" => Some(Self::Russian),
-            "sr" => Some(Self::Serbian),
-            "sk" => Some(Self::Slovak),
-            "sl" => Some(Self::Slovenian),
-            "es" => Some(Self::Spanish),
-            "sv" => Some(Self::Swedish),
-            "ta" => Some(Self::Tamil),
-            "te" => Some(Self::Telugu),
-            "th" => Some(Self::Thai),
-            "tr" => Some(Self::Turkish),
-            "uk" => Some(Self::Ukrainian),
-            "ur" => Some(Self::Urdu),
-            "vi" => Some(Self::Vietnamese),
-            _ => None,
-        }
+        let mut subtags = code.split(['_', '-']);
+        let base = Self::from_base_code(subtags.next()?)?;
+        let subtags: Vec<&str> = subtags.collect();
+        let has = |tags: &[&str]| subtags.iter().any(|s| tags.contains(s));
+
+        Some(match base {
+            Self::Chinese if has(&["hans", "cn", "sg"]) => Self::ChineseSimplified,
+            Self::Chinese if has(&["hant", "tw", "hk", "mo"]) => Self::ChineseTraditional,
+            Self::Portuguese if has(&["br"]) => Self::BrazilianPortuguese,
+            base => base,
+        })
+    }
+
+    /// Base language for a primary subtag (639-1, 639-3, or 639-2/B)
+    fn from_base_code(code: &str) -> Option<Self> {
+        let lang = match code {
+            "ar" => Self::Arabic,
+            "bn" => Self::Bengali,
+            "bg" => Self::Bulgarian,
+            "ca" => Self::Catalan,
+            "zh" => Self::Chinese,
+            "hr" => Self::Croatian,
+            "cs" => Self::Czech,
+            "da" => Self::Danish,
+            "nl" => Self::Dutch,
+            "en" => Self::English,
+            "et" => Self::Estonian,
+            "fi" => Self::Finnish,
+            "fr" => Self::French,
+            "de" => Self::German,
+            "el" => Self::Greek,
+            "gu" => Self::Gujarati,
+            "he" | "iw" => Self::Hebrew, // iw is legacy code
+            "hi" => Self::Hindi,
+            "hu" => Self::Hungarian,
+            "id" => Self::Indonesian,
+            "it" => Self::Italian,
+            "ja" => Self::Japanese,
+            "kn" => Self::Kannada,
+            "ko" => Self::Korean,
+            "lv" => Self::Latvian,
+            "lt" => Self::Lithuanian,
+            "mk" => Self::Macedonian,
+            "ms" => Self::Malay,
+            "ml" => Self::Malayalam,
+            "mr" => Self::Marathi,
+            "no" | "nb" | "nn" => Self::Norwegian,
+            "fa" => Self::Persian,
+            "pl" => Self::Polish,
+            "pt" => Self::Portuguese,
+            "pa" => Self::Punjabi,
+            "ro" => Self::Romanian,
+            "ru" => Self::Russian,
+            "sr" => Self::Serbian,
+            "sk" => Self::Slovak,
+            "sl" => Self::Slovenian,
+            "es" => Self::Spanish,
+            "sv" => Self::Swedish,
+            "ta" => Self::Tamil,
+            "te" => Self::Telugu,
+            "th" => Self::Thai,
+            "tr" => Self::Turkish,
+            "uk" => Self::Ukrainian,
+            "ur" => Self::Urdu,
+            "vi" => Self::Vietnamese,
+            // Individual languages and 639-2/B codes for the macrolanguages above
+            "cmn" | "chi" => Self::Chinese,
+            "nob" | "nno" => Self::Norwegian,
+            "pes" | "per" => Self::Persian,
+            "zsm" | "may" => Self::Malay,
+            "cze" => Self::Czech,
+            "dut" => Self::Dutch,
+            "fre" => Self::French,
+            "ger" => Self::German,
+            "gre" => Self::Greek,
+            "mac" => Self::Macedonian,
+            "rum" => Self::Romanian,
+            "slo" => Self::Slovak,
+            code if code.len() == 3 => {
+                return Self::all()
+                    .iter()
+                    .find(|lang| lang.code().len() == 2 && lang.iso639_3() == code)
+                    .copied();
+            }
+            _ => return None,
+        };
+        Some(lang)
     }
 
     /// Get all supported languages
     pub fn all() -> &'static [Language] {
         &[
             Self::Arabic, Self::Bengali, Self::Bulgarian, Self::Catalan,
-            Self::Chinese, Self::Croatian, Self::Czech, Self::Danish,
+            Self::Chinese, Self::ChineseSimplified, Self::ChineseTraditional,
+            Self::Croatian, Self::Czech, Self::Danish,
             Self::Dutch, Self::English, Self::Estonian, Self::Finnish,
             Self::French, Self::German, Self::Greek, Self::Gujarati,
             Self::Hebrew, Self::Hindi, Self::Hungarian, Self::Indonesian,
             Self::Italian, Self::Japanese, Self::Kannada, Self::Korean,
             Self::Latvian, Self::Lithuanian, Self::Macedonian, Self::Malay,
             Self::Malayalam, Self::Marathi, Self::Norwegian, Self::Persian,
-            Self::Polish, Self::Portuguese, Self::Punjabi, Self::Romanian,
+            Self::Polish, Self::Portuguese, Self::BrazilianPortuguese,
+            Self::Punjabi, Self::Romanian,
             Self::Russian, Self::Serbian, Self::Slovak, Self::Slovenian,
             Self::Spanish, Self::Swedish, Self::Tamil, Self::Telugu,
             Self::Thai, Self::Turkish, Self::Ukrainian, Self::Urdu,
@@ -322,7 +427,7 @@ mod tests {
     #[test]
     fn test_all_language_codes_are_2_3_chars() {
         for lang in Language::all() {
-            let code = lang.code();
+            let code = lang.code().split('-').next().unwrap();
             assert!(
                 code.len() >= 2 && code.len() <= 3,
                 "{:?} has code '{}' with length {}",
@@ -350,6 +455,45 @@ mod tests {
         assert_eq!(Language::from_code("nn"), Some(Language::Norwegian));
     }
 
+    #[test]
+    fn test_chinese_scripts() {
+        assert_eq!(Language::from_code("zh-Hans"), Some(Language::ChineseSimplified));
+        assert_eq!(Language::from_code("zh_CN"), Some(Language::ChineseSimplified));
+        assert_eq!(Language::from_code("zh-Hant"), Some(Language::ChineseTraditional));
+        assert_eq!(Language::from_code("zh-Hant-TW"), Some(Language::ChineseTraditional));
+        assert_eq!(Language::from_code("zh-TW"), Some(Language::ChineseTraditional));
+        assert_eq!(Language::from_code("zh"), Some(Language::Chinese));
+        assert_eq!(Language::ChineseSimplified.name(), "Chinese (Simplified)");
+        assert_ne!(Language::ChineseSimplified, Language::ChineseTraditional);
+    }
+
+    #[test]
+    fn test_brazilian_portuguese() {
+        assert_eq!(Language::from_code("pt-BR"), Some(Language::BrazilianPortuguese));
+        assert_eq!(Language::from_code("pt_br"), Some(Language::BrazilianPortuguese));
+        assert_eq!(Language::from_code("pt-PT"), Some(Language::Portuguese));
+        assert_eq!(Language::BrazilianPortuguese.name(), "Portuguese (Brazil)");
+    }
+
+    #[test]
+    fn test_iso639_3_codes() {
+        assert_eq!(Language::from_code("spa"), Some(Language::Spanish));
+        assert_eq!(Language::from_code("eng"), Some(Language::English));
+        assert_eq!(Language::from_code("deu"), Some(Language::German));
+        assert_eq!(Language::from_code("ger"), Some(Language::German));
+        assert_eq!(Language::from_code("cmn-Hant"), Some(Language::ChineseTraditional));
+        assert_eq!(Language::from_code("por-BR"), Some(Language::BrazilianPortuguese));
+        assert_eq!(Language::Spanish.iso639_3(), "spa");
+    }
+
+    #[test]
+    fn test_all_languages_iso639_3_roundtrip() {
+        for lang in Language::all() {
+            let base = Language::from_code(lang.iso639_3()).unwrap();
+            assert_eq!(base.iso639_3(), lang.iso639_3(), "{:?}", lang);
+        }
+    }
+
     #[test]
     fn test_display_impl() {
         assert_eq!(format!("{}", Language::English), "English");
@@ -408,13 +552,13 @@ mod proptests {
 
         #[test]
         fn language_code_length(lang in any_language()) {
-            let code = lang.code();
+            let code = lang.code().split('-').next().unwrap();
             prop_assert!(code.len() >= 2 && code.len() <= 3);
         }
 
         #[test]
         fn language_code_is_lowercase(lang in any_language()) {
-            let code = lang.code();
+            let code = lang.code().split('-').next().unwrap();
             let lowered = code.to_lowercase();
             prop_assert_eq!(code, lowered.as_str());
         }
@@ -1,7 +1,20 @@
 pub mod cache;
 pub mod client;
+pub mod formality;
 pub mod language;
+pub mod rate_limit;
+pub mod readiness;
+pub mod recent_errors;
+pub mod shadow;
 
 pub use cache::{CacheKey, CacheStats, TranslationCache};
-pub use client::{TranslateRequest, TranslateResponse, TranslationClient, TranslationResult};
+pub use client::{
+    parse_context, Capabilities, TranslateRequest, TranslateResponse, TranslationClient, TranslationHints,
+    TranslationResult,
+};
+pub use formality::Formality;
 pub use language::Language;
+pub use rate_limit::RateLimitSnapshot;
+pub use readiness::{Readiness, ServiceState};
+pub use recent_errors::{FailureReason, FailureRecord};
+pub use shadow::ShadowTranslator;
@@ -0,0 +1,257 @@
+//! Rate-limit signals from the inference service.
+//!
+//! Throttled providers answer with `429`/`503` and a `Retry-After` header,
+//! and may report their quota through `X-RateLimit-*` (or the IETF
+//! `RateLimit-*`) headers. [`RateLimitState`] remembers the last values seen
+//! so the client can wait exactly as long as the backend asked, stop sending
+//! requests while it is blocked, and report the quota on `/metrics`.
+
+use chrono::{DateTime, Utc};
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Reset values above this are Unix timestamps rather than seconds from now
+const EPOCH_THRESHOLD: u64 = 1_000_000_000;
+
+/// Rate-limit headers from one response
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RateLimitInfo {
+    /// Requests allowed per window
+    pub limit: Option<u64>,
+    /// Requests left in the current window
+    pub remaining: Option<u64>,
+    /// Time until the window resets
+    pub reset_after: Option<Duration>,
+    /// How long the backend asked us to wait before retrying
+    pub retry_after: Option<Duration>,
+}
+
+impl RateLimitInfo {
+    /// Read rate-limit headers, ignoring any that are missing or malformed
+    pub fn from_headers(headers: &HeaderMap, now: DateTime<Utc>) -> Self {
+        let number = |names: &[&str]| {
+            names
+                .iter()
+                .find_map(|name| headers.get(*name)?.to_str().ok()?.trim().parse::<u64>().ok())
+        };
+
+        let reset_after = number(&["x-ratelimit-reset", "ratelimit-reset"]).map(|reset| {
+            if reset > EPOCH_THRESHOLD {
+                Duration::from_secs(reset.saturating_sub(now.timestamp().max(0) as u64))
+            } else {
+                Duration::from_secs(reset)
+            }
+        });
+
+        let retry_after = headers
+            .get(RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| parse_retry_after(value, now));
+
+        Self {
+            limit: number(&["x-ratelimit-limit", "ratelimit-limit"]),
+            remaining: number(&["x-ratelimit-remaining", "ratelimit-remaining"]),
+            reset_after,
+            retry_after,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Parse `Retry-After` as delay-seconds or an HTTP date
+pub fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = DateTime::parse_from_rfc2822(value).ok()?.with_timezone(&Utc);
+    Some((at - now).to_std().unwrap_or(Duration::ZERO))
+}
+
+/// Last known quota, for `/metrics`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RateLimitSnapshot {
+    pub limit: Option<u64>,
+    pub remaining: Option<u64>,
+    /// Seconds until requests may be sent again (0 when not blocked)
+    pub blocked_for_secs: f64,
+    /// Throttled (429) responses seen since startup
+    pub throttled_responses: u64,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    limit: Option<u64>,
+    remaining: Option<u64>,
+    blocked_until: Option<Instant>,
+    throttled_responses: u64,
+}
+
+/// Rate-limit state shared by every request to one backend
+#[derive(Debug, Default)]
+pub struct RateLimitState {
+    inner: Mutex<Inner>,
+}
+
+impl RateLimitState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the headers of a response. A throttled response, or an
+    /// exhausted quota, blocks requests until the backend's retry time.
+    pub fn observe(&self, info: &RateLimitInfo, throttled: bool, now: Instant) {
+        if info.is_empty() && !throttled {
+            return;
+        }
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        if info.limit.is_some() {
+            inner.limit = info.limit;
+        }
+        if info.remaining.is_some() {
+            inner.remaining = info.remaining;
+        }
+        if throttled {
+            inner.throttled_responses += 1;
+        }
+
+        let wait = if throttled {
+            info.retry_after.or(info.reset_after)
+        } else if info.remaining == Some(0) {
+            info.reset_after
+        } else {
+            info.retry_after
+        };
+        if let Some(wait) = wait {
+            let until = now + wait;
+            inner.blocked_until = Some(inner.blocked_until.map_or(until, |current| current.max(until)));
+        }
+    }
+
+    /// How long callers must wait before sending another request
+    pub fn wait_time(&self, now: Instant) -> Option<Duration> {
+        let inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        inner
+            .blocked_until
+            .and_then(|until| until.checked_duration_since(now))
+            .filter(|wait| !wait.is_zero())
+    }
+
+    pub fn snapshot(&self, now: Instant) -> RateLimitSnapshot {
+        let blocked_for = self.wait_time(now).unwrap_or_default();
+        let inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        RateLimitSnapshot {
+            limit: inner.limit,
+            remaining: inner.remaining,
+            blocked_for_secs: blocked_for.as_secs_f64(),
+            throttled_responses: inner.throttled_responses,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (name, value) in pairs {
+            map.insert(*name, HeaderValue::from_str(value).unwrap());
+        }
+        map
+    }
+
+    #[test]
+    fn test_retry_after_seconds() {
+        assert_eq!(parse_retry_after("12", Utc::now()), Some(Duration::from_secs(12)));
+        assert_eq!(parse_retry_after("soon", Utc::now()), None);
+    }
+
+    #[test]
+    fn test_retry_after_http_date() {
+        let now = DateTime::parse_from_rfc3339("2015-10-21T07:27:30Z").unwrap().with_timezone(&Utc);
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT", now),
+            Some(Duration::from_secs(30))
+        );
+        // Dates in the past mean "retry now"
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:00:00 GMT", now),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn test_quota_headers() {
+        let now = Utc::now();
+        let info = RateLimitInfo::from_headers(
+            &headers(&[
+                ("x-ratelimit-limit", "600"),
+                ("x-ratelimit-remaining", "42"),
+                ("x-ratelimit-reset", "20"),
+            ]),
+            now,
+        );
+        assert_eq!(info.limit, Some(600));
+        assert_eq!(info.remaining, Some(42));
+        assert_eq!(info.reset_after, Some(Duration::from_secs(20)));
+        assert_eq!(info.retry_after, None);
+
+        let epoch = (now.timestamp() + 60).to_string();
+        let info = RateLimitInfo::from_headers(&headers(&[("ratelimit-reset", &epoch)]), now);
+        assert_eq!(info.reset_after, Some(Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn test_throttled_response_blocks_for_retry_after() {
+        let state = RateLimitState::new();
+        let now = Instant::now();
+        let info = RateLimitInfo {
+            retry_after: Some(Duration::from_secs(5)),
+            ..Default::default()
+        };
+        state.observe(&info, true, now);
+
+        assert_eq!(state.wait_time(now), Some(Duration::from_secs(5)));
+        assert_eq!(state.wait_time(now + Duration::from_secs(5)), None);
+        assert_eq!(state.snapshot(now).throttled_responses, 1);
+    }
+
+    #[test]
+    fn test_exhausted_quota_blocks_until_reset() {
+        let state = RateLimitState::new();
+        let now = Instant::now();
+        let info = RateLimitInfo {
+            limit: Some(10),
+            remaining: Some(0),
+            reset_after: Some(Duration::from_secs(3)),
+            retry_after: None,
+        };
+        state.observe(&info, false, now);
+
+        assert_eq!(state.wait_time(now), Some(Duration::from_secs(3)));
+        let snapshot = state.snapshot(now);
+        assert_eq!(snapshot.remaining, Some(0));
+        assert_eq!(snapshot.throttled_responses, 0);
+    }
+
+    #[test]
+    fn test_remaining_quota_does_not_block() {
+        let state = RateLimitState::new();
+        let now = Instant::now();
+        let info = RateLimitInfo {
+            limit: Some(10),
+            remaining: Some(4),
+            reset_after: Some(Duration::from_secs(30)),
+            retry_after: None,
+        };
+        state.observe(&info, false, now);
+        assert_eq!(state.wait_time(now), None);
+    }
+}
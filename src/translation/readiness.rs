@@ -0,0 +1,149 @@
+//! Whether the inference service has come up yet.
+//!
+//! The bot starts before (or alongside) the inference sidecar, which can
+//! take minutes to load its models. Until a health check first succeeds the
+//! client is [`ServiceState::Starting`]: translations wait a few seconds for
+//! it and then fail with a clear "still starting" error instead of a
+//! connection error. Once up it is `Ready`, or `Degraded` while health
+//! checks fail or the service reports missing models; requests are still
+//! attempted then, since the outage may be partial or brief.
+
+use serde::Serialize;
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// Inference service state as seen by the translation client
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ServiceState {
+    /// No health check has succeeded yet
+    Starting,
+    /// Last health check succeeded with all models loaded
+    Ready,
+    /// Service was up but its last health check failed or reported a
+    /// missing model
+    Degraded,
+}
+
+impl ServiceState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Starting => "starting",
+            Self::Ready => "ready",
+            Self::Degraded => "degraded",
+        }
+    }
+
+    /// State after a health check: `healthy` is None if the service didn't
+    /// answer, otherwise whether it reported all models loaded
+    fn after_check(self, healthy: Option<bool>) -> Self {
+        match healthy {
+            Some(true) => Self::Ready,
+            Some(false) => Self::Degraded,
+            None if self == Self::Starting => Self::Starting,
+            None => Self::Degraded,
+        }
+    }
+}
+
+impl std::fmt::Display for ServiceState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Shared, watchable [`ServiceState`]
+#[derive(Debug)]
+pub struct Readiness {
+    state: watch::Sender<ServiceState>,
+}
+
+impl Default for Readiness {
+    fn default() -> Self {
+        Self {
+            state: watch::Sender::new(ServiceState::Starting),
+        }
+    }
+}
+
+impl Readiness {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn state(&self) -> ServiceState {
+        *self.state.borrow()
+    }
+
+    /// Record a health check outcome (see [`ServiceState`]); returns the
+    /// previous and new state
+    pub fn record_check(&self, healthy: Option<bool>) -> (ServiceState, ServiceState) {
+        let mut previous = ServiceState::Starting;
+        let mut next = ServiceState::Starting;
+        self.state.send_if_modified(|state| {
+            previous = *state;
+            next = state.after_check(healthy);
+            *state = next;
+            previous != next
+        });
+        (previous, next)
+    }
+
+    /// Wait up to `timeout` for the service to leave `Starting`, returning
+    /// the state it is in by then
+    pub async fn wait_until_started(&self, timeout: Duration) -> ServiceState {
+        let mut rx = self.state.subscribe();
+        let _ = tokio::time::timeout(
+            timeout,
+            rx.wait_for(|state| *state != ServiceState::Starting),
+        )
+        .await;
+        self.state()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_state_transitions() {
+        let readiness = Readiness::new();
+        assert_eq!(readiness.state(), ServiceState::Starting);
+
+        // Failing checks during startup keep it starting
+        assert_eq!(
+            readiness.record_check(None),
+            (ServiceState::Starting, ServiceState::Starting)
+        );
+        assert_eq!(readiness.record_check(Some(true)).1, ServiceState::Ready);
+        assert_eq!(readiness.record_check(None).1, ServiceState::Degraded);
+        assert_eq!(readiness.record_check(Some(true)).1, ServiceState::Ready);
+        assert_eq!(readiness.record_check(Some(false)).1, ServiceState::Degraded);
+    }
+
+    #[tokio::test]
+    async fn test_wait_until_started_times_out_while_starting() {
+        let readiness = Readiness::new();
+        let state = readiness.wait_until_started(Duration::from_millis(20)).await;
+        assert_eq!(state, ServiceState::Starting);
+    }
+
+    #[tokio::test]
+    async fn test_wait_until_started_wakes_when_ready() {
+        let readiness = Arc::new(Readiness::new());
+        let waiter = {
+            let readiness = Arc::clone(&readiness);
+            tokio::spawn(async move { readiness.wait_until_started(Duration::from_secs(5)).await })
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        readiness.record_check(Some(true));
+        assert_eq!(waiter.await.unwrap(), ServiceState::Ready);
+    }
+
+    #[test]
+    fn test_serializes_lowercase() {
+        assert_eq!(serde_json::to_string(&ServiceState::Degraded).unwrap(), "\"degraded\"");
+    }
+}
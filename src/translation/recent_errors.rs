@@ -0,0 +1,194 @@
+//! The last few translation failures per guild, for `/admin errors`.
+//!
+//! When members report that "the bot stopped translating", admins need to
+//! tell a service outage from throttling or a bad input. Each guild keeps a
+//! small ring of failures with the reason, when it happened and a short hash
+//! of the input so repeats of one message stand out. The text itself is
+//! never kept, and nothing survives a restart: this is a diagnostic aid, not
+//! a log.
+
+use crate::error::AppError;
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use std::collections::{BTreeMap, VecDeque};
+
+/// Failures remembered per guild
+pub const RECENT_ERRORS_PER_GUILD: usize = 20;
+
+/// Why a translation failed, coarse enough to count
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FailureReason {
+    /// The inference service didn't answer in time
+    Timeout,
+    /// The service asked us to back off
+    RateLimited,
+    /// The service couldn't be reached
+    Unreachable,
+    /// The service hasn't finished starting
+    Starting,
+    /// The source language couldn't be detected
+    DetectionFailed,
+    /// The service answered with an error
+    ServiceError,
+    /// Anything else
+    Other,
+}
+
+impl FailureReason {
+    pub fn classify(error: &AppError) -> Self {
+        match error {
+            AppError::Http(e) if e.is_timeout() => Self::Timeout,
+            AppError::Http(_) | AppError::InferenceUnavailable => Self::Unreachable,
+            AppError::RateLimitExceeded => Self::RateLimited,
+            AppError::InferenceStarting => Self::Starting,
+            AppError::LanguageDetection(_) => Self::DetectionFailed,
+            AppError::Translation(_) => Self::ServiceError,
+            _ => Self::Other,
+        }
+    }
+
+    /// Label used when counting several failures, e.g. "3 timeouts"
+    pub fn plural(&self) -> &'static str {
+        match self {
+            Self::Timeout => "timeouts",
+            Self::RateLimited => "rate-limited",
+            Self::Unreachable => "service unreachable",
+            Self::Starting => "service starting",
+            Self::DetectionFailed => "detection failures",
+            Self::ServiceError => "service errors",
+            Self::Other => "other",
+        }
+    }
+}
+
+impl std::fmt::Display for FailureReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let label = match self {
+            Self::Timeout => "timeout",
+            Self::RateLimited => "rate-limited",
+            Self::Unreachable => "service unreachable",
+            Self::Starting => "service starting",
+            Self::DetectionFailed => "detection failed",
+            Self::ServiceError => "service error",
+            Self::Other => "other",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+/// One remembered failure
+#[derive(Debug, Clone)]
+pub struct FailureRecord {
+    pub reason: FailureReason,
+    pub at: DateTime<Utc>,
+    /// Target language, or None for language detection
+    pub target_lang: Option<String>,
+    /// First 8 hex digits of the input's BLAKE3 hash
+    pub input_hash: String,
+    /// The error as reported, for the details
+    pub message: String,
+}
+
+/// Ring of recent failures for each guild
+#[derive(Debug, Default)]
+pub struct RecentErrors {
+    guilds: DashMap<u64, VecDeque<FailureRecord>>,
+}
+
+impl RecentErrors {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remember that translating `text` failed in `guild_id`
+    pub fn record(&self, guild_id: u64, text: &str, target_lang: Option<&str>, error: &AppError) {
+        let mut hash = blake3::hash(text.as_bytes()).to_hex().to_string();
+        hash.truncate(8);
+        let record = FailureRecord {
+            reason: FailureReason::classify(error),
+            at: Utc::now(),
+            target_lang: target_lang.map(str::to_string),
+            input_hash: hash,
+            message: error.to_string(),
+        };
+
+        let mut failures = self.guilds.entry(guild_id).or_default();
+        if failures.len() == RECENT_ERRORS_PER_GUILD {
+            failures.pop_front();
+        }
+        failures.push_back(record);
+    }
+
+    /// Up to `limit` of the guild's failures, newest first
+    pub fn recent(&self, guild_id: u64, limit: usize) -> Vec<FailureRecord> {
+        self.guilds
+            .get(&guild_id)
+            .map(|failures| failures.iter().rev().take(limit).cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+/// "5 failures: 3 timeouts, 2 rate-limited"
+pub fn summarize(failures: &[FailureRecord]) -> String {
+    let mut counts = BTreeMap::new();
+    for failure in failures {
+        *counts.entry(failure.reason).or_insert(0) += 1;
+    }
+    let mut counts: Vec<_> = counts.into_iter().collect();
+    // Most frequent first; ties keep the enum order
+    counts.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
+    let parts: Vec<String> = counts
+        .iter()
+        .map(|(reason, count)| match count {
+            1 => format!("1 {}", reason),
+            _ => format!("{} {}", count, reason.plural()),
+        })
+        .collect();
+    format!(
+        "{} failure{}: {}",
+        failures.len(),
+        if failures.len() == 1 { "" } else { "s" },
+        parts.join(", ")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ring_keeps_newest_per_guild() {
+        let errors = RecentErrors::new();
+        for i in 0..RECENT_ERRORS_PER_GUILD + 3 {
+            errors.record(1, &format!("message {}", i), Some("es"), &AppError::RateLimitExceeded);
+        }
+        errors.record(2, "other guild", None, &AppError::InferenceUnavailable);
+
+        let recent = errors.recent(1, 100);
+        assert_eq!(recent.len(), RECENT_ERRORS_PER_GUILD);
+        assert_eq!(
+            recent[0].input_hash,
+            blake3::hash(format!("message {}", RECENT_ERRORS_PER_GUILD + 2).as_bytes()).to_hex()[..8]
+        );
+        assert_eq!(errors.recent(2, 5)[0].reason, FailureReason::Unreachable);
+        assert!(errors.recent(3, 5).is_empty());
+    }
+
+    #[test]
+    fn test_summary_counts_reasons() {
+        let errors = RecentErrors::new();
+        for error in [
+            AppError::RateLimitExceeded,
+            AppError::Translation("Service returned 500".to_string()),
+            AppError::RateLimitExceeded,
+            AppError::InferenceStarting,
+            AppError::RateLimitExceeded,
+        ] {
+            errors.record(1, "hello", Some("de"), &error);
+        }
+        assert_eq!(
+            summarize(&errors.recent(1, 5)),
+            "5 failures: 3 rate-limited, 1 service starting, 1 service error"
+        );
+    }
+}
@@ -0,0 +1,182 @@
+//! Shadow translation for comparing inference backends.
+//!
+//! When `translation.shadow_provider` is set, a sampled fraction of fresh
+//! production translations is also sent to the shadow backend. Both outputs
+//! and their latencies are stored in `translation_shadow` for offline
+//! comparison. The shadow call runs in the background and never changes what
+//! users see.
+
+use crate::db::{DbPool, NewTranslationShadow, TranslationShadowRepo};
+use crate::translation::{TranslateRequest, TranslateResponse};
+use reqwest::Client;
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+/// Sends sampled translations to a second backend and records the pair
+#[derive(Debug, Clone)]
+pub struct ShadowTranslator {
+    http: Client,
+    base_url: String,
+    sample_rate: f64,
+    pool: DbPool,
+}
+
+impl ShadowTranslator {
+    /// `sample_rate` is the fraction (0.0–1.0) of translations to shadow.
+    pub fn new(base_url: &str, sample_rate: f64, timeout: Duration, pool: DbPool) -> Self {
+        let http = Client::builder()
+            .timeout(timeout)
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self {
+            http,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            sample_rate: sample_rate.clamp(0.0, 1.0),
+            pool,
+        }
+    }
+
+    /// Whether this translation should be shadowed
+    pub fn should_sample(&self) -> bool {
+        self.sample_rate > 0.0 && rand::random::<f64>() < self.sample_rate
+    }
+
+    /// Shadow a production translation in the background if it is sampled
+    pub fn observe(&self, request: TranslateRequest, primary_text: String, primary_latency: Duration) {
+        if !self.should_sample() {
+            return;
+        }
+        let shadow = self.clone();
+        tokio::spawn(async move {
+            shadow.compare(request, primary_text, primary_latency).await;
+        });
+    }
+
+    /// Translate `request` on the shadow backend and store both outputs
+    pub async fn compare(
+        &self,
+        request: TranslateRequest,
+        primary_text: String,
+        primary_latency: Duration,
+    ) {
+        let started = Instant::now();
+        let outcome = self.translate(&request).await;
+        let shadow_latency = started.elapsed();
+
+        let (shadow_text, shadow_error) = match outcome {
+            Ok(response) => (Some(response.translated_text), None),
+            Err(e) => {
+                debug!(error = %e, "Shadow translation failed");
+                (None, Some(e))
+            }
+        };
+
+        let entry = NewTranslationShadow {
+            source_lang: request.source_lang,
+            target_lang: request.target_lang,
+            original_text: request.text,
+            primary_text,
+            primary_latency_ms: primary_latency.as_millis() as i64,
+            shadow_provider: self.base_url.clone(),
+            shadow_text,
+            shadow_latency_ms: shadow_latency.as_millis() as i64,
+            shadow_error,
+        };
+        if let Err(e) = TranslationShadowRepo::insert(&self.pool, entry).await {
+            warn!(error = %e, "Failed to store shadow translation");
+        }
+    }
+
+    async fn translate(&self, request: &TranslateRequest) -> Result<TranslateResponse, String> {
+        let response = self
+            .http
+            .post(format!("{}/translate", self.base_url))
+            .json(request)
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        if !response.status().is_success() {
+            return Err(format!("Service returned {}", response.status()));
+        }
+        response.json().await.map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::queries::setup_test_db;
+    use axum::{routing::post, Json, Router};
+
+    fn request(text: &str) -> TranslateRequest {
+        TranslateRequest {
+            text: text.to_string(),
+            source_lang: "en".to_string(),
+            target_lang: "es".to_string(),
+            context: None,
+            formality: Default::default(),
+        }
+    }
+
+    async fn mock_backend() -> String {
+        let app = Router::new().route(
+            "/translate",
+            post(|Json(req): Json<serde_json::Value>| async move {
+                Json(serde_json::json!({
+                    "translated_text": format!("shadow: {}", req["text"].as_str().unwrap()),
+                    "source_lang": req["source_lang"],
+                    "target_lang": req["target_lang"],
+                }))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{}/", addr)
+    }
+
+    #[tokio::test]
+    async fn test_sample_rate_bounds() {
+        let pool = setup_test_db().await;
+        let never = ShadowTranslator::new("http://x", 0.0, Duration::from_secs(1), pool.clone());
+        let always = ShadowTranslator::new("http://x", 5.0, Duration::from_secs(1), pool);
+        for _ in 0..100 {
+            assert!(!never.should_sample());
+            assert!(always.should_sample());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_compare_stores_both_outputs() {
+        let pool = setup_test_db().await;
+        let shadow = ShadowTranslator::new(&mock_backend().await, 1.0, Duration::from_secs(5), pool.clone());
+
+        shadow
+            .compare(request("hello"), "hola".to_string(), Duration::from_millis(120))
+            .await;
+
+        let rows = TranslationShadowRepo::recent(&pool, 10).await.unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].primary_text, "hola");
+        assert_eq!(rows[0].primary_latency_ms, 120);
+        assert_eq!(rows[0].shadow_text.as_deref(), Some("shadow: hello"));
+        assert!(rows[0].shadow_error.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_shadow_is_recorded_as_error() {
+        let pool = setup_test_db().await;
+        let shadow = ShadowTranslator::new("http://127.0.0.1:1", 1.0, Duration::from_secs(2), pool.clone());
+
+        shadow
+            .compare(request("hello"), "hola".to_string(), Duration::from_millis(80))
+            .await;
+
+        let rows = TranslationShadowRepo::recent(&pool, 10).await.unwrap();
+        assert_eq!(rows.len(), 1);
+        assert!(rows[0].shadow_text.is_none());
+        assert!(rows[0].shadow_error.is_some());
+    }
+}
@@ -3,26 +3,45 @@
 //! Bridges voice inference results to the web broadcast system and
 //! optionally to Discord thread transcripts.
 
-use super::{VoiceInferenceResponse, VoiceTranscriptionCache};
-use crate::db::{DbPool, VoiceTranscriptRepo};
+use super::export;
+use super::playback::parse_tts_audio;
+use super::tts_audio::{self, TtsFormat};
+use super::{ConnectionState, VoiceInferenceResponse, VoiceManager, VoiceTranscriptionCache};
+use crate::db::{
+    DbPool, GuildRepo, NewVoiceTranscriptEntry, TtsMode, VoiceChannelRepo, VoiceHistoryRepo,
+    VoiceTranscriptRepo,
+};
+use crate::bot::discord_limits;
+use crate::bot::selftest::SELF_TEST_GUILD_ID;
+use crate::bot::transcript_threads;
 use crate::web::BroadcastManager;
-use poise::serenity_prelude::{ChannelId, CreateMessage, Http};
+use poise::serenity_prelude::{ChannelId, CreateAttachment, CreateMessage, Http};
+use std::borrow::Cow;
 use std::sync::Arc;
-use tokio::sync::broadcast;
+use tokio::sync::{broadcast, watch};
+use tokio::task::JoinHandle;
 use tracing::{debug, error, info, warn};
 
 /// Bridge that forwards voice inference results to web clients.
 pub struct VoiceBridge {
     /// Receiver for voice inference results
     voice_rx: broadcast::Receiver<VoiceInferenceResponse>,
+    /// Inference connection state changes, relayed to web viewers
+    state_rx: Option<broadcast::Receiver<ConnectionState>>,
     /// Broadcast manager for web clients
     broadcast: Arc<BroadcastManager>,
     /// Voice transcription cache for storing inference results
     cache: Arc<VoiceTranscriptionCache>,
-    /// Optional database pool for transcript settings
+    /// Optional database pool for transcript settings and history
     pool: Option<DbPool>,
     /// Optional HTTP client for posting to Discord threads
     http: Option<Arc<Http>>,
+    /// Results below this transcription confidence are dropped
+    min_confidence: Option<f32>,
+    /// Voice manager whose per-guild playback queues receive TTS audio
+    voice: Option<Arc<VoiceManager>>,
+    /// TTS mode for channels without their own `voice_channel_settings` row
+    default_tts_mode: TtsMode,
 }
 
 impl VoiceBridge {
@@ -34,10 +53,14 @@ impl VoiceBridge {
     ) -> Self {
         Self {
             voice_rx,
+            state_rx: None,
             broadcast,
             cache,
             pool: None,
             http: None,
+            min_confidence: None,
+            voice: None,
+            default_tts_mode: TtsMode::WebOnly,
         }
     }
 
@@ -51,37 +74,120 @@ impl VoiceBridge {
     ) -> Self {
         Self {
             voice_rx,
+            state_rx: None,
             broadcast,
             cache,
             pool: Some(pool),
             http: Some(http),
+            min_confidence: None,
+            voice: None,
+            default_tts_mode: TtsMode::WebOnly,
         }
     }
 
+    /// Relay inference connection state changes from `state_rx` to web
+    /// viewers.
+    pub fn with_connection_state(mut self, state_rx: broadcast::Receiver<ConnectionState>) -> Self {
+        self.state_rx = Some(state_rx);
+        self
+    }
+
+    /// Drop results whose transcription confidence is below `min_confidence`.
+    pub fn with_min_confidence(mut self, min_confidence: Option<f32>) -> Self {
+        self.min_confidence = min_confidence;
+        self
+    }
+
+    /// Look up per-channel TTS modes in `pool`.
+    pub fn with_pool(mut self, pool: DbPool) -> Self {
+        self.pool = Some(pool);
+        self
+    }
+
+    /// Queue TTS audio on `voice`'s playback managers for channels whose mode
+    /// includes voice; `default_mode` applies to channels with no settings.
+    pub fn with_tts_playback(mut self, voice: Arc<VoiceManager>, default_mode: TtsMode) -> Self {
+        self.voice = Some(voice);
+        self.default_tts_mode = default_mode;
+        self
+    }
+
     /// Run the bridge, forwarding voice results to web clients.
     ///
-    /// This should be spawned as a background task.
-    pub async fn run(mut self) {
+    /// Runs until the result channel closes. Prefer [`VoiceBridge::spawn`],
+    /// which can also be stopped.
+    pub async fn run(self) {
+        let (_shutdown_tx, shutdown_rx) = watch::channel(false);
+        self.run_until_shutdown(shutdown_rx).await;
+    }
+
+    /// Spawn the bridge as a background task that stops when the returned
+    /// handle is shut down or dropped.
+    pub fn spawn(self) -> VoiceBridgeHandle {
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let task = tokio::spawn(self.run_until_shutdown(shutdown_rx));
+        VoiceBridgeHandle { shutdown_tx, task }
+    }
+
+    /// Forward results until `shutdown` fires (or its sender is dropped) or
+    /// the result channel closes. Results already queued when shutdown is
+    /// requested are still forwarded before returning.
+    async fn run_until_shutdown(mut self, mut shutdown: watch::Receiver<bool>) {
         info!("Voice bridge started - forwarding results to web clients");
 
         loop {
-            match self.voice_rx.recv().await {
-                Ok(response) => {
-                    self.handle_response(&response).await;
-                }
-                Err(broadcast::error::RecvError::Lagged(n)) => {
-                    warn!(skipped = n, "Voice bridge lagged, skipped messages");
-                }
-                Err(broadcast::error::RecvError::Closed) => {
-                    error!("Voice result channel closed, bridge shutting down");
+            tokio::select! {
+                biased;
+                _ = shutdown.changed() => {
+                    self.flush_pending().await;
                     break;
                 }
+                received = self.voice_rx.recv() => match received {
+                    Ok(response) => {
+                        self.handle_response(&response).await;
+                    }
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        warn!(skipped = n, "Voice bridge lagged, skipped messages");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => {
+                        error!("Voice result channel closed, bridge shutting down");
+                        break;
+                    }
+                },
+                changed = next_state(&mut self.state_rx) => match changed {
+                    Ok(state) => {
+                        info!(?state, "Voice inference connection state changed");
+                        self.broadcast.send_inference_status(state);
+                    }
+                    Err(broadcast::error::RecvError::Lagged(_)) => {}
+                    Err(broadcast::error::RecvError::Closed) => self.state_rx = None,
+                },
             }
         }
 
         info!("Voice bridge stopped");
     }
 
+    /// Forward results that arrived before shutdown without waiting for more
+    async fn flush_pending(&mut self) {
+        let mut flushed = 0usize;
+        loop {
+            match self.voice_rx.try_recv() {
+                Ok(response) => {
+                    self.handle_response(&response).await;
+                    flushed += 1;
+                }
+                Err(broadcast::error::TryRecvError::Lagged(n)) => {
+                    warn!(skipped = n, "Voice bridge lagged while flushing");
+                }
+                Err(_) => break,
+            }
+        }
+        if flushed > 0 {
+            debug!(flushed, "Flushed pending voice results before shutdown");
+        }
+    }
+
     /// Handle a voice inference response.
     async fn handle_response(&self, response: &VoiceInferenceResponse) {
         match response {
@@ -92,16 +198,37 @@ impl VoiceBridge {
                 username,
                 original_text,
                 translated_text,
+                source_language,
                 target_language,
                 audio_hash,
+                started_at,
+                trace_id,
                 ..
             } => {
+                // Synthetic audio from /admin selftest isn't anyone's conversation
+                if guild_id == &SELF_TEST_GUILD_ID.to_string() {
+                    return;
+                }
+
                 // Skip empty transcriptions
                 if original_text.is_empty() {
-                    debug!(user_id, "Skipping empty transcription");
+                    debug!(user_id, trace_id = ?trace_id, "Skipping empty transcription");
                     return;
                 }
 
+                // Skip transcriptions too unreliable to show at all
+                if let Some(min) = self.min_confidence {
+                    if response.is_low_confidence(min) {
+                        debug!(
+                            user_id,
+                            trace_id = ?trace_id,
+                            confidence = ?response.confidence(),
+                            "Dropping low-confidence transcription"
+                        );
+                        return;
+                    }
+                }
+
                 debug!(
                     guild_id,
                     channel_id,
@@ -109,6 +236,7 @@ impl VoiceBridge {
                     username,
                     text = original_text,
                     audio_hash,
+                    trace_id = ?trace_id,
                     "Forwarding voice transcription to web clients"
                 );
 
@@ -122,27 +250,53 @@ impl VoiceBridge {
                     "Cached translation result"
                 );
 
+                // Route TTS audio to the voice channel and/or web viewers
+                let tts_mode = self.tts_mode(guild_id, channel_id).await;
+                let mut web_response = self.route_tts(response, tts_mode).await;
+
+                // Speakers are already labelled "Speaker N"; keep their ids
+                // out of the web view and history too
+                let anonymize = self.anonymize_speakers(guild_id).await;
+                if anonymize {
+                    if let VoiceInferenceResponse::Result { user_id, .. } = web_response.to_mut() {
+                        user_id.clear();
+                    }
+                }
+
                 // Forward to broadcast manager for web clients
-                self.broadcast.send_voice_transcription(response);
+                self.broadcast.send_voice_transcription(&web_response);
+
+                // Keep the utterance for transcript exports
+                if let Some(pool) = &self.pool {
+                    let entry = NewVoiceTranscriptEntry {
+                        guild_id: guild_id.clone(),
+                        voice_channel_id: channel_id.clone(),
+                        user_id: if anonymize {
+                            export::anonymous_user_id(guild_id, user_id)
+                        } else {
+                            user_id.clone()
+                        },
+                        username: username.clone(),
+                        original_text: original_text.clone(),
+                        translated_text: translated_text.clone(),
+                        source_language: source_language.clone(),
+                        target_language: target_language.clone(),
+                        spoken_at: started_at.unwrap_or_else(chrono::Utc::now),
+                    };
+                    if let Err(e) = VoiceHistoryRepo::insert(pool, entry).await {
+                        warn!(error = %e, "Failed to store voice transcript entry");
+                    }
+                }
 
                 // Post to Discord threads if configured
                 if let (Some(pool), Some(http)) = (&self.pool, &self.http) {
-                    self.post_to_threads(
-                        pool,
-                        http,
-                        guild_id,
-                        channel_id,
-                        username,
-                        original_text,
-                        translated_text,
-                        target_language,
-                    )
-                    .await;
+                    self.post_to_threads(pool, http, response).await;
                 }
             }
             VoiceInferenceResponse::Ready {
                 stt_models,
                 tts_models,
+                ..
             } => {
                 info!(
                     stt_models = ?stt_models,
@@ -153,24 +307,99 @@ impl VoiceBridge {
             VoiceInferenceResponse::Pong => {
                 debug!("Received pong from voice inference service");
             }
-            VoiceInferenceResponse::Error { message, code } => {
-                error!(message, code = ?code, "Voice inference error");
+            VoiceInferenceResponse::Error {
+                message,
+                code,
+                trace_id,
+            } => {
+                error!(message, code = ?code, trace_id = ?trace_id, "Voice inference error");
+            }
+        }
+    }
+
+    /// Whether the guild asked for speakers' identities to be left out
+    async fn anonymize_speakers(&self, guild_id: &str) -> bool {
+        let Some(pool) = &self.pool else {
+            return false;
+        };
+        match GuildRepo::get_settings(pool, guild_id).await {
+            Ok(settings) => settings.is_some_and(|s| s.anonymize_speakers),
+            Err(e) => {
+                debug!(error = %e, "Failed to get guild settings");
+                false
             }
         }
     }
 
-    /// Post transcription to Discord threads based on settings.
-    async fn post_to_threads(
+    /// TTS mode for a voice channel, falling back to the bridge default
+    async fn tts_mode(&self, guild_id: &str, channel_id: &str) -> TtsMode {
+        let Some(pool) = &self.pool else {
+            return self.default_tts_mode;
+        };
+        match VoiceChannelRepo::get_settings(pool, guild_id, channel_id).await {
+            Ok(Some(settings)) => TtsMode::from(settings.tts_mode.as_str()),
+            Ok(None) => self.default_tts_mode,
+            Err(e) => {
+                debug!(error = %e, "Failed to get voice channel settings");
+                self.default_tts_mode
+            }
+        }
+    }
+
+    /// Queue TTS audio for voice playback if `mode` calls for it, and return
+    /// the response to broadcast (without audio unless `mode` streams to web).
+    async fn route_tts<'a>(
         &self,
-        pool: &DbPool,
-        http: &Http,
-        guild_id: &str,
-        channel_id: &str,
-        username: &str,
-        original_text: &str,
-        translated_text: &str,
-        target_language: &str,
-    ) {
+        response: &'a VoiceInferenceResponse,
+        mode: TtsMode,
+    ) -> Cow<'a, VoiceInferenceResponse> {
+        let VoiceInferenceResponse::Result {
+            guild_id, tts_audio, ..
+        } = response
+        else {
+            return Cow::Borrowed(response);
+        };
+        if tts_audio.is_none() {
+            return Cow::Borrowed(response);
+        }
+
+        if mode.plays_in_voice() {
+            if let (Some(voice), Ok(guild)) = (&self.voice, guild_id.parse::<u64>()) {
+                if let Some(item) = parse_tts_audio(response) {
+                    voice.get_or_create_playback(guild).queue_tts(item).await;
+                }
+            }
+        }
+
+        if mode.streams_to_web() {
+            Cow::Borrowed(response)
+        } else {
+            let mut stripped = response.clone();
+            if let VoiceInferenceResponse::Result { tts_audio, .. } = &mut stripped {
+                *tts_audio = None;
+            }
+            Cow::Owned(stripped)
+        }
+    }
+
+    /// Post transcription to Discord threads based on settings, with the TTS
+    /// audio attached when the language's thread has clips enabled.
+    async fn post_to_threads(&self, pool: &DbPool, http: &Http, response: &VoiceInferenceResponse) {
+        let VoiceInferenceResponse::Result {
+            guild_id,
+            channel_id,
+            username,
+            original_text,
+            translated_text,
+            target_language,
+            tts_audio,
+            tts_format,
+            ..
+        } = response
+        else {
+            return;
+        };
+
         // Look up transcript settings
         let settings = match VoiceTranscriptRepo::get_settings(pool, guild_id, channel_id).await {
             Ok(Some(s)) if s.enabled => s,
@@ -187,29 +416,125 @@ impl VoiceBridge {
         if let Some(thread_id_str) = thread_ids.get(target_language) {
             if let Ok(thread_id) = thread_id_str.parse::<u64>() {
                 let message = format!("**{}**\n> {}\n{}", username, original_text, translated_text);
+                let mut parts = discord_limits::split(&message, discord_limits::MESSAGE_CONTENT);
+                let last = parts.pop().unwrap_or_default();
+                let mut final_part = CreateMessage::new().content(last);
+                if settings.tts_enabled_for(target_language) {
+                    if let Some(wav) = tts_audio
+                        .as_deref()
+                        .and_then(|audio| transcript_clip(audio, *tts_format))
+                    {
+                        let filename = format!("{}.wav", target_language);
+                        final_part = final_part.add_file(CreateAttachment::bytes(wav, filename));
+                    }
+                }
 
-                let channel = ChannelId::new(thread_id);
-                if let Err(e) = channel
-                    .send_message(http, CreateMessage::new().content(&message))
-                    .await
-                {
-                    debug!(error = %e, thread_id, "Failed to post to transcript thread");
+                // Long utterances continue over several posts, clip on the last
+                let mut channel = ChannelId::new(thread_id);
+                let posts = parts
+                    .into_iter()
+                    .map(|part| CreateMessage::new().content(part))
+                    .chain(std::iter::once(final_part));
+                for create in posts {
+                    let mut sent = channel.send_message(http, create.clone()).await;
+                    // Someone deleted the thread: open a new one and carry on there
+                    if matches!(&sent, Err(e) if transcript_threads::is_unknown_channel(e)) {
+                        match transcript_threads::create_thread(
+                            http,
+                            pool,
+                            guild_id,
+                            channel_id,
+                            &settings.text_channel_id,
+                            target_language,
+                        )
+                        .await
+                        {
+                            Ok(thread) => {
+                                info!(
+                                    guild_id,
+                                    language = %target_language,
+                                    thread_id = %thread,
+                                    "Recreated deleted transcript thread"
+                                );
+                                channel = thread;
+                                sent = channel.send_message(http, create).await;
+                            }
+                            Err(e) => {
+                                warn!(error = %e, thread_id, "Failed to recreate deleted transcript thread");
+                                break;
+                            }
+                        }
+                    }
+                    if let Err(e) = sent {
+                        debug!(error = %e, thread_id, "Failed to post to transcript thread");
+                        break;
+                    }
                 }
             }
         }
     }
 }
 
+/// TTS audio as a WAV file Discord can play inline
+fn transcript_clip(tts_audio: &str, tts_format: Option<TtsFormat>) -> Option<Vec<u8>> {
+    match tts_audio::decode_base64(tts_audio, tts_format) {
+        Ok(decoded) if !decoded.samples.is_empty() => {
+            Some(tts_audio::encode_wav(&decoded.samples, decoded.sample_rate))
+        }
+        Ok(_) => None,
+        Err(e) => {
+            debug!(error = %e, "Failed to decode TTS audio for transcript thread");
+            None
+        }
+    }
+}
+
+/// Next connection state change, or never without a state receiver
+async fn next_state(
+    state_rx: &mut Option<broadcast::Receiver<ConnectionState>>,
+) -> Result<ConnectionState, broadcast::error::RecvError> {
+    match state_rx {
+        Some(rx) => rx.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Handle to a running voice bridge task.
+///
+/// Dropping the handle also stops the bridge, so a restarted voice subsystem
+/// never leaves the old task forwarding results.
+pub struct VoiceBridgeHandle {
+    shutdown_tx: watch::Sender<bool>,
+    task: JoinHandle<()>,
+}
+
+impl VoiceBridgeHandle {
+    /// Stop the bridge and wait for it to flush pending results and exit.
+    pub async fn shutdown(self) {
+        let _ = self.shutdown_tx.send(true);
+        if let Err(e) = self.task.await {
+            warn!(error = %e, "Voice bridge task ended abnormally");
+        }
+    }
+
+    /// Whether the bridge task has exited.
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+}
+
 /// Spawn the voice bridge as a background task.
 ///
-/// Returns a handle to the spawned task.
+/// Returns a handle that stops the task.
 pub fn spawn_voice_bridge(
     voice_rx: broadcast::Receiver<VoiceInferenceResponse>,
     broadcast: Arc<BroadcastManager>,
     cache: Arc<VoiceTranscriptionCache>,
-) -> tokio::task::JoinHandle<()> {
-    let bridge = VoiceBridge::new(voice_rx, broadcast, cache);
-    tokio::spawn(bridge.run())
+    min_confidence: Option<f32>,
+) -> VoiceBridgeHandle {
+    VoiceBridge::new(voice_rx, broadcast, cache)
+        .with_min_confidence(min_confidence)
+        .spawn()
 }
 
 /// Spawn the voice bridge with Discord thread support.
@@ -221,9 +546,11 @@ pub fn spawn_voice_bridge_with_threads(
     cache: Arc<VoiceTranscriptionCache>,
     pool: DbPool,
     http: Arc<Http>,
-) -> tokio::task::JoinHandle<()> {
-    let bridge = VoiceBridge::with_thread_support(voice_rx, broadcast, cache, pool, http);
-    tokio::spawn(bridge.run())
+    min_confidence: Option<f32>,
+) -> VoiceBridgeHandle {
+    VoiceBridge::with_thread_support(voice_rx, broadcast, cache, pool, http)
+        .with_min_confidence(min_confidence)
+        .spawn()
 }
 
 #[cfg(test)]
@@ -254,4 +581,270 @@ mod tests {
         drop(bridge);
         drop(tx);
     }
+
+    fn tts_result() -> VoiceInferenceResponse {
+        VoiceInferenceResponse::Result {
+            guild_id: "42".to_string(),
+            channel_id: "vc1".to_string(),
+            user_id: "1".to_string(),
+            username: "Speaker".to_string(),
+            original_text: "hello".to_string(),
+            translated_text: "hola".to_string(),
+            source_language: "en".to_string(),
+            target_language: "es".to_string(),
+            // Four little-endian PCM samples
+            tts_audio: Some("AQACAAMABAA=".to_string()),
+            latency_ms: 100,
+            audio_hash: 7,
+            confidence: None,
+            started_at: None,
+            tts_format: None,
+            trace_id: None,
+        }
+    }
+
+    fn playback_bridge() -> (VoiceBridge, Arc<VoiceManager>) {
+        let (_tx, rx) = broadcast::channel::<VoiceInferenceResponse>(10);
+        let voice = Arc::new(VoiceManager::new(
+            songbird::Songbird::serenity(),
+            crate::voice::VoiceClientConfig::default(),
+        ));
+        let bridge = VoiceBridge::new(
+            rx,
+            Arc::new(BroadcastManager::new()),
+            Arc::new(VoiceTranscriptionCache::new(100)),
+        )
+        .with_tts_playback(voice.clone(), TtsMode::Off);
+        (bridge, voice)
+    }
+
+    fn has_audio(response: &VoiceInferenceResponse) -> bool {
+        matches!(response, VoiceInferenceResponse::Result { tts_audio: Some(_), .. })
+    }
+
+    #[tokio::test]
+    async fn test_web_only_tts_skips_playback() {
+        let (bridge, voice) = playback_bridge();
+        let response = tts_result();
+
+        let web = bridge.route_tts(&response, TtsMode::WebOnly).await;
+        assert!(has_audio(&web));
+        assert_eq!(voice.get_or_create_playback(42).queue_len().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_voice_tts_queues_playback_without_web_audio() {
+        let (bridge, voice) = playback_bridge();
+        let response = tts_result();
+
+        let web = bridge.route_tts(&response, TtsMode::Voice).await;
+        assert!(!has_audio(&web));
+        assert_eq!(voice.get_or_create_playback(42).queue_len().await, 1);
+
+        let web = bridge.route_tts(&response, TtsMode::Both).await;
+        assert!(has_audio(&web));
+        assert_eq!(voice.get_or_create_playback(42).queue_len().await, 2);
+
+        let web = bridge.route_tts(&response, TtsMode::Off).await;
+        assert!(!has_audio(&web));
+        assert_eq!(voice.get_or_create_playback(42).queue_len().await, 2);
+    }
+
+    #[test]
+    fn test_transcript_clip_is_playable_wav() {
+        let wav = transcript_clip("AQACAAMABAA=", Some(TtsFormat::Pcm)).unwrap();
+        assert_eq!(&wav[..4], b"RIFF");
+        let decoded = tts_audio::decode(TtsFormat::Wav, &wav).unwrap();
+        assert_eq!(decoded.samples, vec![1, 2, 3, 4]);
+
+        assert!(transcript_clip("not base64!", None).is_none());
+        assert!(transcript_clip("", Some(TtsFormat::Pcm)).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_results_are_stored_for_export() {
+        let pool = crate::db::queries::setup_test_db().await;
+        let (_tx, rx) = broadcast::channel::<VoiceInferenceResponse>(10);
+        let bridge = VoiceBridge::new(
+            rx,
+            Arc::new(BroadcastManager::new()),
+            Arc::new(VoiceTranscriptionCache::new(100)),
+        )
+        .with_pool(pool.clone());
+
+        let started = chrono::Utc::now() - chrono::Duration::seconds(5);
+        let mut response = tts_result();
+        if let VoiceInferenceResponse::Result { started_at, .. } = &mut response {
+            *started_at = Some(started);
+        }
+        bridge.handle_response(&response).await;
+
+        let stored = VoiceHistoryRepo::list(
+            &pool,
+            "42",
+            "vc1",
+            started - chrono::Duration::minutes(1),
+            chrono::Utc::now(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].original_text, "hello");
+        assert_eq!(stored[0].translated_text, "hola");
+        assert_eq!(stored[0].spoken_at, started);
+    }
+
+    struct RealNames;
+
+    #[async_trait::async_trait]
+    impl crate::voice::SpeakerNames for RealNames {
+        async fn speaker_name(&self, _guild_id: u64, _user_id: u64) -> Option<String> {
+            Some("Alice".to_string())
+        }
+
+        async fn anonymize(&self, _guild_id: u64) -> bool {
+            true
+        }
+    }
+
+    #[tokio::test]
+    async fn test_anonymized_broadcasts_hide_speaker_identity() {
+        use crate::db::{GuildRepo, NewGuild, UserPreferenceRepo};
+        use crate::voice::{AudioPacket, VoiceClientConfig, VoiceInferenceClient, VoiceReceiveHandler};
+
+        let pool = crate::db::queries::setup_test_db().await;
+        GuildRepo::upsert(&pool, NewGuild { guild_id: "42".to_string(), name: "Test".to_string() })
+            .await
+            .unwrap();
+        GuildRepo::set_anonymize_speakers(&pool, "42", true).await.unwrap();
+
+        // Label a segment the way a live voice connection would
+        let cache = Arc::new(VoiceTranscriptionCache::new(100));
+        let client = Arc::new(VoiceInferenceClient::new(VoiceClientConfig::default()));
+        let handler = VoiceReceiveHandler::new(42, 1, client, cache.clone())
+            .with_speaker_names(Arc::new(RealNames));
+        handler.register_speaker(7, 123456789).await;
+        let loud: Vec<i16> = (0..960).map(|i| ((i as f32 * 0.1).sin() * 10000.0) as i16).collect();
+        handler
+            .buffer_manager()
+            .push_audio(AudioPacket {
+                ssrc: 7,
+                user_id: None,
+                username: None,
+                samples: loud,
+                timestamp: std::time::Instant::now(),
+                sequence: 0,
+            })
+            .await;
+        let segment = handler.buffer_manager().unregister_speaker(7).await.unwrap();
+
+        let mut response = tts_result();
+        if let VoiceInferenceResponse::Result { user_id, username, .. } = &mut response {
+            *user_id = segment.user_id.to_string();
+            *username = segment.username;
+        }
+
+        let (_tx, rx) = broadcast::channel::<VoiceInferenceResponse>(10);
+        let manager = Arc::new(BroadcastManager::new());
+        let mut web_rx = manager.subscribe_voice_channel("42", "vc1");
+        let bridge = VoiceBridge::new(rx, manager, cache).with_pool(pool.clone());
+        bridge.handle_response(&response).await;
+
+        let json = serde_json::to_string(&web_rx.try_recv().unwrap()).unwrap();
+        assert!(json.contains("Speaker 1"));
+        assert!(!json.contains("Alice"));
+        assert!(!json.contains("123456789"));
+        assert!(!json.contains("user_id"));
+
+        let stored = VoiceHistoryRepo::list(
+            &pool,
+            "42",
+            "vc1",
+            chrono::Utc::now() - chrono::Duration::minutes(1),
+            chrono::Utc::now(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(stored[0].username, "Speaker 1");
+        assert!(!stored[0].user_id.contains("123456789"));
+
+        // The speaker's transcript opt-out still reaches anonymized entries
+        UserPreferenceRepo::set_transcript_opt_out(&pool, "123456789", "42", true)
+            .await
+            .unwrap();
+        let opted_out = UserPreferenceRepo::transcript_opt_outs(&pool, "42")
+            .await
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert!(export::without_opted_out(&stored, &opted_out).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_stops_forwarding() {
+        let (tx, rx) = broadcast::channel::<VoiceInferenceResponse>(10);
+        let broadcast = Arc::new(BroadcastManager::new());
+        let mut web_rx = broadcast.subscribe_voice_channel("42", "vc1");
+        let handle = VoiceBridge::new(rx, broadcast.clone(), Arc::new(VoiceTranscriptionCache::new(100)))
+            .spawn();
+
+        tx.send(tts_result()).unwrap();
+        let forwarded = tokio::time::timeout(std::time::Duration::from_secs(2), web_rx.recv()).await;
+        assert!(forwarded.is_ok(), "bridge should forward while running");
+
+        handle.shutdown().await;
+
+        // The bridge no longer holds a receiver, so nothing is listening
+        assert!(tx.send(tts_result()).is_err());
+        assert!(web_rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_flushes_pending_results() {
+        let pool = crate::db::queries::setup_test_db().await;
+        let (tx, rx) = broadcast::channel::<VoiceInferenceResponse>(10);
+        let bridge = VoiceBridge::new(
+            rx,
+            Arc::new(BroadcastManager::new()),
+            Arc::new(VoiceTranscriptionCache::new(100)),
+        )
+        .with_pool(pool.clone());
+
+        // Queue results before the task gets a chance to run
+        tx.send(tts_result()).unwrap();
+        tx.send(tts_result()).unwrap();
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        shutdown_tx.send(true).unwrap();
+        bridge.run_until_shutdown(shutdown_rx).await;
+
+        let stored = VoiceHistoryRepo::list(
+            &pool,
+            "42",
+            "vc1",
+            chrono::Utc::now() - chrono::Duration::minutes(1),
+            chrono::Utc::now(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(stored.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_dropping_handle_stops_bridge() {
+        let (tx, rx) = broadcast::channel::<VoiceInferenceResponse>(10);
+        let handle = VoiceBridge::new(
+            rx,
+            Arc::new(BroadcastManager::new()),
+            Arc::new(VoiceTranscriptionCache::new(100)),
+        )
+        .spawn();
+        let VoiceBridgeHandle { shutdown_tx, task } = handle;
+        drop(shutdown_tx);
+
+        tokio::time::timeout(std::time::Duration::from_secs(2), task)
+            .await
+            .expect("bridge should exit once its handle is dropped")
+            .unwrap();
+        assert_eq!(tx.receiver_count(), 0);
+    }
 }
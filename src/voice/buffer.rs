@@ -1,10 +1,10 @@
 //! Per-user audio ring buffers with voice activity detection.
 
 use super::types::{AudioPacket, AudioSegment, Ssrc, DISCORD_SAMPLE_RATE, SAMPLES_PER_FRAME};
-use std::collections::HashMap;
-use std::sync::Arc;
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use std::collections::VecDeque;
 use std::time::{Duration, Instant};
-use tokio::sync::RwLock;
 use tracing::{debug, trace};
 
 /// Minimum duration of speech to trigger transcription (ms).
@@ -27,6 +27,120 @@ const MIN_CHUNK_SAMPLES: usize = DISCORD_SAMPLE_RATE as usize / 2;
 /// Simple energy-based VAD threshold.
 const VAD_ENERGY_THRESHOLD: f32 = 0.01;
 
+/// Default audio kept from before speech is detected (ms).
+const DEFAULT_PRE_ROLL_MS: u64 = 200;
+
+/// Default silence kept after the last speech frame of an utterance (ms).
+const DEFAULT_TRAILING_SILENCE_MS: u64 = 300;
+
+/// Default audio repeated from the end of one streaming chunk at the start
+/// of the next (ms).
+const DEFAULT_CHUNK_OVERLAP_MS: u64 = 300;
+
+/// Default floor for a flushed segment's overall RMS (0.0-1.0 of full
+/// scale). Half the VAD threshold: a segment this quiet on average is a
+/// click or breath padded out with silence, and transcribes to nothing.
+const DEFAULT_MIN_SEGMENT_RMS: f32 = 0.005;
+
+/// Default share of adjacent samples that may change sign before a frame
+/// counts as broadband noise. Voiced speech at 48kHz stays well below 0.1;
+/// fan hiss and white noise sit near 0.5.
+const DEFAULT_GATE_MAX_ZERO_CROSSING_RATE: f32 = 0.25;
+
+/// Default peak-to-RMS ratio above which a frame counts as a click. A sine
+/// is about 1.4 and voiced speech rarely exceeds 5 over a 20ms frame.
+const DEFAULT_GATE_MAX_CREST_FACTOR: f32 = 6.0;
+
+/// Rejects frames that are loud enough for the VAD but don't look like
+/// speech: impulsive clicks (keyboards, mouse buttons) and broadband hiss
+/// (fans).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NoiseGate {
+    /// Frames whose zero-crossing rate exceeds this are treated as noise
+    pub max_zero_crossing_rate: f32,
+    /// Frames whose peak-to-RMS ratio exceeds this are treated as clicks
+    pub max_crest_factor: f32,
+}
+
+impl Default for NoiseGate {
+    fn default() -> Self {
+        Self {
+            max_zero_crossing_rate: DEFAULT_GATE_MAX_ZERO_CROSSING_RATE,
+            max_crest_factor: DEFAULT_GATE_MAX_CREST_FACTOR,
+        }
+    }
+}
+
+impl NoiseGate {
+    /// Whether `samples` could be speech
+    fn passes(&self, samples: &[i16]) -> bool {
+        if samples.len() < 2 {
+            return true;
+        }
+        let crossings = samples
+            .windows(2)
+            .filter(|pair| (pair[0] < 0) != (pair[1] < 0))
+            .count();
+        let zero_crossing_rate = crossings as f32 / (samples.len() - 1) as f32;
+        if zero_crossing_rate > self.max_zero_crossing_rate {
+            return false;
+        }
+
+        let sum_squares: f64 = samples.iter().map(|&s| (s as f64).powi(2)).sum();
+        let rms = (sum_squares / samples.len() as f64).sqrt();
+        let peak = samples.iter().map(|&s| (s as f64).abs()).fold(0.0, f64::max);
+        rms == 0.0 || peak / rms <= self.max_crest_factor as f64
+    }
+}
+
+/// Segmentation settings shared by every user buffer in a channel.
+#[derive(Debug, Clone)]
+pub struct BufferConfig {
+    /// Audio from just before VAD trips that is prepended to the utterance,
+    /// so quiet onsets ("start" → "art") aren't clipped. Zero disables it.
+    pub pre_roll: Duration,
+    /// Silence after the last speech frame that stays on the end of a
+    /// finished utterance so a fading last word isn't chopped. Anything
+    /// beyond it becomes the next utterance's pre-roll instead.
+    pub trailing_silence: Duration,
+    /// Tail of each streaming chunk that is sent again at the start of the
+    /// next one, so a word cut by the chunk boundary is heard whole. Zero
+    /// disables it.
+    pub chunk_overlap: Duration,
+    /// Checked before the VAD so clicks and hiss don't start utterances.
+    /// Off when `None`.
+    pub noise_gate: Option<NoiseGate>,
+    /// Flushed segments whose overall RMS (0.0-1.0 of full scale) is below
+    /// this are dropped instead of sent for transcription. Zero disables it.
+    pub min_segment_rms: f32,
+}
+
+impl BufferConfig {
+    fn pre_roll_samples(&self) -> usize {
+        duration_samples(self.pre_roll)
+    }
+
+    fn trailing_samples(&self) -> usize {
+        duration_samples(self.trailing_silence)
+    }
+
+    fn overlap_samples(&self) -> usize {
+        duration_samples(self.chunk_overlap)
+    }
+}
+
+impl Default for BufferConfig {
+    fn default() -> Self {
+        Self {
+            pre_roll: Duration::from_millis(DEFAULT_PRE_ROLL_MS),
+            trailing_silence: Duration::from_millis(DEFAULT_TRAILING_SILENCE_MS),
+            chunk_overlap: Duration::from_millis(DEFAULT_CHUNK_OVERLAP_MS),
+            noise_gate: None,
+            min_segment_rms: DEFAULT_MIN_SEGMENT_RMS,
+        }
+    }
+}
+
 /// Per-user audio buffer.
 #[derive(Debug)]
 struct UserBuffer {
@@ -40,8 +154,26 @@ struct UserBuffer {
     channel_id: u64,
     /// Accumulated audio samples
     samples: Vec<i16>,
+    /// Most recent non-speech samples, prepended when speech starts
+    pre_roll: VecDeque<i16>,
+    /// Capacity of `pre_roll` in samples
+    pre_roll_capacity: usize,
+    /// Length of `samples` up to the end of the last speech frame
+    speech_end: usize,
+    /// Silence samples kept after `speech_end` when an utterance ends
+    trailing_capacity: usize,
+    /// Samples carried from the end of a streaming chunk into the next
+    overlap_capacity: usize,
+    /// Leading samples of `samples` that were already sent in the previous chunk
+    overlap: usize,
+    /// Filter applied ahead of the VAD
+    noise_gate: Option<NoiseGate>,
+    /// Quietest segment worth sending
+    min_segment_rms: f32,
     /// When this utterance started
     speech_start: Option<Instant>,
+    /// Wall-clock time of the first sample currently in `samples`
+    samples_started_at: Option<DateTime<Utc>>,
     /// Last time we received audio
     last_audio_time: Instant,
     /// Last time we sent a streaming chunk
@@ -51,14 +183,35 @@ struct UserBuffer {
 }
 
 impl UserBuffer {
+    #[cfg(test)]
     fn new(user_id: u64, username: String, guild_id: u64, channel_id: u64) -> Self {
+        Self::with_config(user_id, username, guild_id, channel_id, &BufferConfig::default())
+    }
+
+    fn with_config(
+        user_id: u64,
+        username: String,
+        guild_id: u64,
+        channel_id: u64,
+        config: &BufferConfig,
+    ) -> Self {
+        let pre_roll_capacity = config.pre_roll_samples();
         Self {
             user_id,
             username,
             guild_id,
             channel_id,
             samples: Vec::with_capacity(SAMPLES_PER_FRAME * 50), // ~1 second initial capacity
+            pre_roll: VecDeque::with_capacity(pre_roll_capacity),
+            pre_roll_capacity,
+            speech_end: 0,
+            trailing_capacity: config.trailing_samples(),
+            overlap_capacity: config.overlap_samples(),
+            overlap: 0,
+            noise_gate: config.noise_gate,
+            min_segment_rms: config.min_segment_rms,
             speech_start: None,
+            samples_started_at: None,
             last_audio_time: Instant::now(),
             last_chunk_sent: None,
             is_speaking: false,
@@ -68,7 +221,14 @@ impl UserBuffer {
     /// Add audio samples to buffer.
     fn push_audio(&mut self, samples: &[i16]) {
         let now = Instant::now();
-        let has_speech = detect_speech(samples);
+        let has_speech = detect_speech(samples)
+            && self.noise_gate.is_none_or(|gate| gate.passes(samples));
+
+        if (has_speech || self.is_speaking) && self.samples.is_empty() {
+            // Each streaming chunk is stamped with its own first sample,
+            // not the start of the whole utterance
+            self.samples_started_at = Some(Utc::now());
+        }
 
         if has_speech {
             if !self.is_speaking {
@@ -76,19 +236,47 @@ impl UserBuffer {
                 self.is_speaking = true;
                 self.speech_start = Some(now);
                 trace!(user_id = self.user_id, "Speech started");
+
+                // The onset is usually quieter than the VAD threshold
+                if !self.pre_roll.is_empty() {
+                    let pre_roll_ms = self.pre_roll.len() as i64 * 1000 / DISCORD_SAMPLE_RATE as i64;
+                    self.samples_started_at = self
+                        .samples_started_at
+                        .map(|t| t - chrono::Duration::milliseconds(pre_roll_ms));
+                    self.samples.extend(self.pre_roll.drain(..));
+                }
             }
             self.samples.extend_from_slice(samples);
+            self.speech_end = self.samples.len();
             self.last_audio_time = now;
         } else if self.is_speaking {
             // Still include some silence for natural speech boundaries
             self.samples.extend_from_slice(samples);
+        } else {
+            self.remember_pre_roll(samples);
+        }
+    }
+
+    /// Keep the last `pre_roll_capacity` samples heard while not speaking.
+    fn remember_pre_roll(&mut self, samples: &[i16]) {
+        if self.pre_roll_capacity == 0 {
+            return;
         }
+        let samples = &samples[samples.len().saturating_sub(self.pre_roll_capacity)..];
+        let overflow = (self.pre_roll.len() + samples.len()).saturating_sub(self.pre_roll_capacity);
+        self.pre_roll.drain(..overflow);
+        self.pre_roll.extend(samples);
+    }
+
+    /// Samples heard since the last chunk, not counting the carried overlap.
+    fn new_samples(&self) -> usize {
+        self.samples.len() - self.overlap
     }
 
     /// Check if we should flush this buffer.
     /// Supports both streaming (timer-based chunks) and silence detection.
     fn should_flush(&self) -> bool {
-        if !self.is_speaking || self.samples.is_empty() {
+        if !self.is_speaking || self.new_samples() == 0 {
             return false;
         }
 
@@ -99,7 +287,7 @@ impl UserBuffer {
 
         // STREAMING: Send chunks periodically while speaking for lower latency
         // This enables partial results instead of waiting for complete utterances
-        if self.samples.len() >= MIN_CHUNK_SAMPLES {
+        if self.new_samples() >= MIN_CHUNK_SAMPLES {
             let time_since_last_chunk = self.last_chunk_sent
                 .map(|t| now.duration_since(t))
                 .unwrap_or(speech_duration);
@@ -111,7 +299,7 @@ impl UserBuffer {
 
         // SILENCE DETECTION: Flush if silence timeout reached (legacy behavior)
         if silence_duration >= Duration::from_millis(SILENCE_TIMEOUT_MS) {
-            let total_duration = self.samples.len() as f64 / DISCORD_SAMPLE_RATE as f64;
+            let total_duration = self.new_samples() as f64 / DISCORD_SAMPLE_RATE as f64;
             if total_duration >= MIN_SPEECH_DURATION_MS as f64 / 1000.0 {
                 return true;
             }
@@ -127,35 +315,65 @@ impl UserBuffer {
 
     /// Flush buffer and return audio segment.
     fn flush(&mut self) -> Option<AudioSegment> {
+        let now = Instant::now();
+        // Silence detected: this flush ends the utterance
+        let silence_duration = now.duration_since(self.last_audio_time);
+        self.take_segment(now, silence_duration >= Duration::from_millis(SILENCE_TIMEOUT_MS))
+    }
+
+    /// Force flush due to timeout, ending the current utterance.
+    fn force_flush(&mut self) -> Option<AudioSegment> {
+        self.take_segment(Instant::now(), true)
+    }
+
+    fn take_segment(&mut self, now: Instant, ends_utterance: bool) -> Option<AudioSegment> {
         if self.samples.is_empty() {
             return None;
         }
 
-        let now = Instant::now();
+        let start_time = self.speech_start.unwrap_or(now);
+        let mut samples = std::mem::take(&mut self.samples);
+        let overlap_samples = std::mem::take(&mut self.overlap);
+        let started_at = self.samples_started_at.take().unwrap_or_else(Utc::now);
+        self.samples = Vec::with_capacity(SAMPLES_PER_FRAME * 50);
+        if ends_utterance {
+            // Keep a short tail for the last word; later silence leads into
+            // the next utterance, so it goes to the pre-roll, never both
+            let cut = (self.speech_end + self.trailing_capacity).min(samples.len());
+            let rest = samples.split_off(cut);
+            self.remember_pre_roll(&rest);
+
+            self.speech_start = None;
+            self.is_speaking = false;
+            self.last_chunk_sent = None;
+            self.speech_end = 0;
+        } else {
+            // Keep speaking state (streaming mode)
+            self.last_chunk_sent = Some(now);
+            self.carry_overlap(&samples, started_at);
+        }
+
+        // Nothing heard since the last chunk beyond what it already sent
+        if samples.len() <= overlap_samples {
+            return None;
+        }
+        // A lone click can trip the VAD; the silence around it can't carry words
+        if rms(&samples) < self.min_segment_rms as f64 {
+            debug!(user_id = self.user_id, samples = samples.len(), "Dropped near-silent segment");
+            return None;
+        }
         let segment = AudioSegment {
             user_id: self.user_id,
             username: self.username.clone(),
             guild_id: self.guild_id,
             channel_id: self.channel_id,
-            samples: std::mem::take(&mut self.samples),
-            start_time: self.speech_start.unwrap_or(now),
+            samples,
+            start_time,
             end_time: now,
+            started_at,
+            overlap_samples,
         };
 
-        // Update streaming state
-        self.last_chunk_sent = Some(now);
-
-        // If silence detected, reset speaking state
-        let silence_duration = now.duration_since(self.last_audio_time);
-        if silence_duration >= Duration::from_millis(SILENCE_TIMEOUT_MS) {
-            self.speech_start = None;
-            self.is_speaking = false;
-            self.last_chunk_sent = None;
-        }
-        // Otherwise keep speaking state (streaming mode)
-
-        self.samples = Vec::with_capacity(SAMPLES_PER_FRAME * 50);
-
         debug!(
             user_id = self.user_id,
             duration_ms = segment.duration().as_millis(),
@@ -166,86 +384,108 @@ impl UserBuffer {
         Some(segment)
     }
 
-    /// Force flush due to timeout.
-    fn force_flush(&mut self) -> Option<AudioSegment> {
-        if self.samples.is_empty() {
-            return None;
+    /// Start the next streaming chunk with the tail of the one just taken.
+    fn carry_overlap(&mut self, sent: &[i16], sent_started_at: DateTime<Utc>) {
+        let keep = self.overlap_capacity.min(sent.len());
+        let skipped = sent.len() - keep;
+        self.samples.extend_from_slice(&sent[skipped..]);
+        self.overlap = keep;
+        self.speech_end = self.speech_end.saturating_sub(skipped);
+        if keep > 0 {
+            let skipped_ms = skipped as i64 * 1000 / DISCORD_SAMPLE_RATE as i64;
+            self.samples_started_at =
+                Some(sent_started_at + chrono::Duration::milliseconds(skipped_ms));
         }
-        self.flush()
     }
 }
 
+/// Number of samples covering `duration` at the Discord sample rate.
+fn duration_samples(duration: Duration) -> usize {
+    (duration.as_millis() as u64 * DISCORD_SAMPLE_RATE as u64 / 1000) as usize
+}
+
 /// Simple energy-based voice activity detection.
 fn detect_speech(samples: &[i16]) -> bool {
     if samples.is_empty() {
         return false;
     }
 
-    // Calculate RMS energy
-    let sum_squares: f64 = samples.iter().map(|&s| (s as f64).powi(2)).sum();
-    let rms = (sum_squares / samples.len() as f64).sqrt() / 32768.0;
+    rms(samples) > VAD_ENERGY_THRESHOLD as f64
+}
 
-    rms > VAD_ENERGY_THRESHOLD as f64
+/// RMS energy of `samples` as a fraction of full scale.
+fn rms(samples: &[i16]) -> f64 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    let sum_squares: f64 = samples.iter().map(|&s| (s as f64).powi(2)).sum();
+    (sum_squares / samples.len() as f64).sqrt() / 32768.0
 }
 
 /// Manages audio buffers for all users in a voice channel.
+///
+/// Buffers live in a sharded map, so speakers whose SSRCs land in different
+/// shards are buffered in parallel rather than queueing on one lock.
 pub struct AudioBufferManager {
     /// SSRC -> user buffer mapping
-    buffers: Arc<RwLock<HashMap<Ssrc, UserBuffer>>>,
+    buffers: DashMap<Ssrc, UserBuffer>,
     /// SSRC -> User ID mapping (populated from SpeakingStateUpdate)
-    ssrc_map: Arc<RwLock<HashMap<Ssrc, (u64, String)>>>,
+    ssrc_map: DashMap<Ssrc, (u64, String)>,
     /// Guild ID
     guild_id: u64,
     /// Channel ID
     channel_id: u64,
+    /// Segmentation settings for new user buffers
+    config: BufferConfig,
 }
 
 impl AudioBufferManager {
     /// Create a new buffer manager for a voice channel.
     pub fn new(guild_id: u64, channel_id: u64) -> Self {
+        Self::with_config(guild_id, channel_id, BufferConfig::default())
+    }
+
+    /// Create a buffer manager with custom segmentation settings.
+    pub fn with_config(guild_id: u64, channel_id: u64, config: BufferConfig) -> Self {
         Self {
-            buffers: Arc::new(RwLock::new(HashMap::new())),
-            ssrc_map: Arc::new(RwLock::new(HashMap::new())),
+            buffers: DashMap::new(),
+            ssrc_map: DashMap::new(),
             guild_id,
             channel_id,
+            config,
         }
     }
 
+    fn new_buffer(&self, user_id: u64, username: String) -> UserBuffer {
+        UserBuffer::with_config(user_id, username, self.guild_id, self.channel_id, &self.config)
+    }
+
     /// Register SSRC to user ID mapping.
     pub async fn register_speaker(&self, ssrc: Ssrc, user_id: u64, username: String) {
-        let mut ssrc_map = self.ssrc_map.write().await;
-        ssrc_map.insert(ssrc, (user_id, username.clone()));
-
-        let mut buffers = self.buffers.write().await;
-        buffers
+        self.ssrc_map.insert(ssrc, (user_id, username.clone()));
+        self.buffers
             .entry(ssrc)
-            .or_insert_with(|| UserBuffer::new(user_id, username, self.guild_id, self.channel_id));
+            .or_insert_with(|| self.new_buffer(user_id, username));
 
         debug!(ssrc, user_id, "Registered speaker");
     }
 
     /// Remove speaker from tracking.
     pub async fn unregister_speaker(&self, ssrc: Ssrc) -> Option<AudioSegment> {
-        let mut ssrc_map = self.ssrc_map.write().await;
-        ssrc_map.remove(&ssrc);
-
-        let mut buffers = self.buffers.write().await;
-        if let Some(mut buffer) = buffers.remove(&ssrc) {
-            return buffer.force_flush();
-        }
-        None
+        self.ssrc_map.remove(&ssrc);
+        let (_, mut buffer) = self.buffers.remove(&ssrc)?;
+        buffer.force_flush()
     }
 
     /// Process incoming audio packet.
     pub async fn push_audio(&self, packet: AudioPacket) -> Option<AudioSegment> {
-        let ssrc_map = self.ssrc_map.read().await;
-        let (user_id, username) = ssrc_map.get(&packet.ssrc)?.clone();
-        drop(ssrc_map);
+        let (user_id, username) = self.ssrc_map.get(&packet.ssrc)?.clone();
 
-        let mut buffers = self.buffers.write().await;
-        let buffer = buffers
+        // Only this speaker's shard is locked while the packet is buffered
+        let mut buffer = self
+            .buffers
             .entry(packet.ssrc)
-            .or_insert_with(|| UserBuffer::new(user_id, username, self.guild_id, self.channel_id));
+            .or_insert_with(|| self.new_buffer(user_id, username));
 
         buffer.push_audio(&packet.samples);
 
@@ -258,37 +498,24 @@ impl AudioBufferManager {
 
     /// Check all buffers for timeout and flush if needed.
     pub async fn check_timeouts(&self) -> Vec<AudioSegment> {
-        let mut segments = Vec::new();
-        let mut buffers = self.buffers.write().await;
-
-        for buffer in buffers.values_mut() {
-            if buffer.should_flush() {
-                if let Some(segment) = buffer.flush() {
-                    segments.push(segment);
-                }
-            }
-        }
-
-        segments
+        // Shards are locked one at a time, so ingestion elsewhere continues
+        self.buffers
+            .iter_mut()
+            .filter_map(|mut buffer| buffer.should_flush().then(|| buffer.flush()).flatten())
+            .collect()
     }
 
     /// Flush all buffers (e.g., when leaving channel).
     pub async fn flush_all(&self) -> Vec<AudioSegment> {
-        let mut segments = Vec::new();
-        let mut buffers = self.buffers.write().await;
-
-        for buffer in buffers.values_mut() {
-            if let Some(segment) = buffer.force_flush() {
-                segments.push(segment);
-            }
-        }
-
-        segments
+        self.buffers
+            .iter_mut()
+            .filter_map(|mut buffer| buffer.force_flush())
+            .collect()
     }
 
     /// Get number of active speakers.
     pub async fn speaker_count(&self) -> usize {
-        self.ssrc_map.read().await.len()
+        self.ssrc_map.len()
     }
 }
 
@@ -304,6 +531,7 @@ impl std::fmt::Debug for AudioBufferManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::Arc;
 
     #[test]
     fn test_detect_speech_silence() {
@@ -338,6 +566,104 @@ mod tests {
         assert!(detect_speech(&loud));
     }
 
+    /// 20ms of a 150Hz voice with a few harmonics
+    fn tonal_speech() -> Vec<i16> {
+        (0..960)
+            .map(|i| {
+                let t = i as f32 / DISCORD_SAMPLE_RATE as f32;
+                let voice: f32 = (1..=4)
+                    .map(|h| (2.0 * std::f32::consts::PI * 150.0 * h as f32 * t).sin() / h as f32)
+                    .sum();
+                (voice * 6000.0) as i16
+            })
+            .collect()
+    }
+
+    /// 20ms frame holding one key click: a short ringing burst, then nothing
+    fn key_click() -> Vec<i16> {
+        let mut frame = vec![0i16; 960];
+        for (i, sample) in frame.iter_mut().take(12).enumerate() {
+            *sample = if i % 2 == 0 { 30000 } else { -30000 };
+        }
+        frame
+    }
+
+    /// 20ms of fan-like hiss from a fixed pseudo-random sequence
+    fn hiss() -> Vec<i16> {
+        let mut state = 0x2545_f491u32;
+        (0..960)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                (state >> 16) as i16 / 4
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_noise_gate_passes_speech_and_rejects_noise() {
+        let gate = NoiseGate::default();
+        assert!(gate.passes(&tonal_speech()));
+        // All three are loud enough for the VAD on its own
+        for noise in [key_click(), hiss()] {
+            assert!(detect_speech(&noise));
+            assert!(!gate.passes(&noise));
+        }
+        assert!(gate.passes(&[0i16; 960]));
+    }
+
+    #[test]
+    fn test_noise_gate_keeps_clicks_out_of_segments() {
+        let gated = BufferConfig {
+            noise_gate: Some(NoiseGate::default()),
+            ..BufferConfig::default()
+        };
+        let mut buf = UserBuffer::with_config(1, "User".to_string(), 2, 3, &gated);
+        for _ in 0..50 {
+            buf.push_audio(&key_click());
+            buf.push_audio(&hiss());
+        }
+        assert!(!buf.is_speaking);
+        assert!(buf.force_flush().is_none());
+
+        buf.push_audio(&tonal_speech());
+        assert!(buf.is_speaking);
+
+        // Off by default: the same clicks start an utterance
+        let mut ungated = UserBuffer::new(1, "User".to_string(), 2, 3);
+        ungated.push_audio(&key_click());
+        assert!(ungated.is_speaking);
+    }
+
+    #[test]
+    fn test_near_silent_segments_are_dropped() {
+        // One soft frame just over the VAD threshold, then silence
+        let soft: Vec<i16> = (0..960).map(|i| ((i as f32 * 0.1).sin() * 550.0) as i16).collect();
+        assert!(detect_speech(&soft));
+        let blip = |config: &BufferConfig| {
+            let mut buf = UserBuffer::with_config(1, "User".to_string(), 2, 3, config);
+            buf.push_audio(&soft);
+            for _ in 0..40 {
+                buf.push_audio(&[0i16; 960]);
+            }
+            buf.force_flush()
+        };
+        assert!(blip(&BufferConfig::default()).is_none());
+        let unfiltered = BufferConfig { min_segment_rms: 0.0, ..BufferConfig::default() };
+        assert!(blip(&unfiltered).is_some());
+
+        // Speech followed by the same silence still goes through
+        let mut buf = UserBuffer::new(1, "User".to_string(), 2, 3);
+        for _ in 0..25 {
+            buf.push_audio(&tonal_speech());
+        }
+        for _ in 0..40 {
+            buf.push_audio(&[0i16; 960]);
+        }
+        assert!(buf.force_flush().is_some());
+    }
+
     #[test]
     fn test_user_buffer_new() {
         let buf = UserBuffer::new(123, "TestUser".to_string(), 456, 789);
@@ -391,6 +717,136 @@ mod tests {
         assert_eq!(seg.samples.len(), 960);
     }
 
+    #[test]
+    fn test_streaming_chunks_carry_own_start_time() {
+        let mut buf = UserBuffer::new(1, "User".to_string(), 2, 3);
+        let loud: Vec<i16> = (0..960)
+            .map(|i| ((i as f32 * 0.1).sin() * 10000.0) as i16)
+            .collect();
+
+        let before = Utc::now();
+        // Longer than the overlap, so the next chunk starts part-way in
+        for _ in 0..20 {
+            buf.push_audio(&loud);
+        }
+        let first = buf.flush().unwrap();
+        assert!(first.started_at >= before);
+
+        // Still speaking: the next chunk starts when its first sample arrives
+        std::thread::sleep(Duration::from_millis(5));
+        buf.push_audio(&loud);
+        let second = buf.flush().unwrap();
+        assert!(second.started_at > first.started_at);
+        assert_eq!(second.start_time, first.start_time);
+    }
+
+    #[test]
+    fn test_segment_includes_pre_onset_samples() {
+        let mut buf = UserBuffer::new(1, "User".to_string(), 2, 3);
+        // A quiet onset below the VAD threshold, longer than the pre-roll
+        let onset = vec![50i16; 960];
+        for _ in 0..20 {
+            buf.push_audio(&onset);
+        }
+        assert!(buf.samples.is_empty());
+
+        let before = Utc::now();
+        buf.push_audio(&[20000i16; 960]);
+        let segment = buf.force_flush().unwrap();
+
+        let pre_roll = BufferConfig::default().pre_roll_samples();
+        assert_eq!(pre_roll, 9600);
+        assert_eq!(segment.samples.len(), pre_roll + 960);
+        assert!(segment.samples[..pre_roll].iter().all(|&s| s == 50));
+        assert!(segment.samples[pre_roll..].iter().all(|&s| s == 20000));
+        // The segment is stamped from its first (pre-roll) sample
+        assert!(segment.started_at <= before - chrono::Duration::milliseconds(190));
+    }
+
+    #[test]
+    fn test_pre_roll_can_be_disabled() {
+        let config = BufferConfig {
+            pre_roll: Duration::ZERO,
+            ..BufferConfig::default()
+        };
+        let mut buf = UserBuffer::with_config(1, "User".to_string(), 2, 3, &config);
+        buf.push_audio(&[50i16; 960]);
+        buf.push_audio(&[20000i16; 960]);
+        assert_eq!(buf.force_flush().unwrap().samples.len(), 960);
+    }
+
+    #[test]
+    fn test_finished_segment_keeps_trailing_silence() {
+        let mut buf = UserBuffer::new(1, "User".to_string(), 2, 3);
+        buf.push_audio(&[20000i16; 960]);
+        // A fading tail below the VAD threshold, then a long silence
+        for _ in 0..40 {
+            buf.push_audio(&[60i16; 960]);
+        }
+        let segment = buf.force_flush().unwrap();
+
+        let trailing = BufferConfig::default().trailing_samples();
+        assert_eq!(trailing, 14400);
+        assert_eq!(segment.samples.len(), 960 + trailing);
+        assert!(segment.samples[960..].iter().all(|&s| s == 60));
+        assert!(!buf.is_speaking);
+
+        // Silence past the tail leads into the next utterance exactly once
+        let pre_roll = BufferConfig::default().pre_roll_samples();
+        assert_eq!(buf.pre_roll.len(), pre_roll);
+        buf.push_audio(&[20000i16; 960]);
+        let next = buf.force_flush().unwrap();
+        assert_eq!(next.samples.len(), pre_roll + 960);
+    }
+
+    #[test]
+    fn test_streaming_chunk_keeps_mid_utterance_pauses() {
+        let config = BufferConfig {
+            pre_roll: Duration::ZERO,
+            trailing_silence: Duration::ZERO,
+            chunk_overlap: Duration::ZERO,
+            noise_gate: None,
+            min_segment_rms: 0.0,
+        };
+        let mut buf = UserBuffer::with_config(1, "User".to_string(), 2, 3, &config);
+        buf.push_audio(&[20000i16; 960]);
+        buf.push_audio(&[0i16; 960]);
+        // Not an utterance end: the pause stays in the chunk
+        assert_eq!(buf.flush().unwrap().samples.len(), 1920);
+        assert!(buf.is_speaking);
+    }
+
+    #[test]
+    fn test_streaming_chunks_share_overlap() {
+        let mut buf = UserBuffer::new(1, "User".to_string(), 2, 3);
+        let frame = |i: i16| vec![5000 + i; 960];
+        for i in 0..20 {
+            buf.push_audio(&frame(i));
+        }
+        let first = buf.flush().unwrap();
+        assert_eq!(first.overlap_samples, 0);
+
+        for i in 20..30 {
+            buf.push_audio(&frame(i));
+        }
+        let second = buf.flush().unwrap();
+
+        let overlap = BufferConfig::default().overlap_samples();
+        assert_eq!(overlap, 14400);
+        assert_eq!(second.overlap_samples, overlap);
+        assert_eq!(second.samples.len(), overlap + 10 * 960);
+        assert_eq!(
+            second.samples[..overlap],
+            first.samples[first.samples.len() - overlap..]
+        );
+        assert!(second.samples[overlap..].iter().all(|&s| s >= 5020));
+
+        // A new utterance doesn't repeat the end of the last one
+        buf.force_flush();
+        buf.push_audio(&frame(0));
+        assert_eq!(buf.force_flush().unwrap().overlap_samples, 0);
+    }
+
     #[tokio::test]
     async fn test_buffer_manager() {
         let manager = AudioBufferManager::new(123, 456);
@@ -448,6 +904,80 @@ mod tests {
         assert_eq!(segments.len(), 1);
         assert_eq!(segments[0].user_id, 789);
     }
+
+    fn speech_packet(ssrc: Ssrc, sequence: u16) -> AudioPacket {
+        AudioPacket {
+            ssrc,
+            user_id: None,
+            username: None,
+            samples: vec![10000i16; 960],
+            timestamp: Instant::now(),
+            sequence,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_busy_speaker_does_not_block_others() {
+        let manager = AudioBufferManager::new(123, 456);
+        for ssrc in 1..=64 {
+            manager.register_speaker(ssrc, ssrc as u64, format!("User{}", ssrc)).await;
+        }
+
+        // Hold one speaker's buffer as if its packet were mid-processing
+        let busy = manager.buffers.get_mut(&1).unwrap();
+        let unblocked: Vec<Ssrc> = (2..=64)
+            .filter(|ssrc| !manager.buffers.try_get_mut(ssrc).is_locked())
+            .collect();
+        assert!(!unblocked.is_empty(), "every speaker shares one lock");
+
+        for &ssrc in &unblocked {
+            let pushed = tokio::time::timeout(
+                Duration::from_secs(1),
+                manager.push_audio(speech_packet(ssrc, 0)),
+            )
+            .await;
+            assert!(pushed.is_ok(), "speaker {} waited on speaker 1", ssrc);
+        }
+        drop(busy);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_speakers_across_shards() {
+        const SPEAKERS: u32 = 25;
+        const FRAMES: u16 = 40;
+        let manager = Arc::new(AudioBufferManager::new(123, 456));
+        for ssrc in 0..SPEAKERS {
+            manager.register_speaker(ssrc, ssrc as u64, format!("User{}", ssrc)).await;
+        }
+
+        let tasks: Vec<_> = (0..SPEAKERS)
+            .map(|ssrc| {
+                let manager = manager.clone();
+                tokio::spawn(async move {
+                    let mut segments = Vec::new();
+                    for sequence in 0..FRAMES {
+                        segments.extend(manager.push_audio(speech_packet(ssrc, sequence)).await);
+                    }
+                    segments
+                })
+            })
+            .collect();
+        let mut segments = Vec::new();
+        for task in tasks {
+            segments.extend(task.await.unwrap());
+        }
+        segments.extend(manager.flush_all().await);
+
+        // Every speaker's audio ends up in that speaker's own segments
+        for ssrc in 0..SPEAKERS {
+            let samples: usize = segments
+                .iter()
+                .filter(|s| s.user_id == ssrc as u64)
+                .map(|s| s.samples.len() - s.overlap_samples)
+                .sum();
+            assert_eq!(samples, FRAMES as usize * 960, "speaker {}", ssrc);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -4,13 +4,15 @@
 //! we can cache the transcription/translation results keyed by audio hash + target language.
 //! This can reduce inference latency by 10-100x for repeated phrases.
 
+use crate::db::{DbPool, PersistedVoiceResult, VoiceCacheRepo};
+use crate::error::AppResult;
 use blake3::Hasher as Blake3Hasher;
 use lru::LruCache;
 use std::num::NonZeroUsize;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use tokio::sync::Mutex;
-use tracing::{debug, trace};
+use tracing::{debug, trace, warn};
 
 use super::types::VoiceInferenceResponse;
 
@@ -22,6 +24,24 @@ pub struct CachedTranslation {
     pub cached_at: std::time::Instant,
 }
 
+/// Computes the cache key for a segment's samples.
+///
+/// Production uses [`VoiceTranscriptionCache::hash_audio`]; tests can supply
+/// their own (any `Fn(&[i16]) -> u64` works) to force collisions or pick
+/// which entries get evicted.
+pub trait AudioHasher: Send + Sync {
+    fn hash(&self, samples: &[i16]) -> u64;
+}
+
+impl<F> AudioHasher for F
+where
+    F: Fn(&[i16]) -> u64 + Send + Sync,
+{
+    fn hash(&self, samples: &[i16]) -> u64 {
+        self(samples)
+    }
+}
+
 /// LRU cache for voice transcription/translation results.
 ///
 /// Key: (audio_hash, target_language)
@@ -30,6 +50,7 @@ pub struct CachedTranslation {
 /// This prevents reprocessing identical audio segments (common for repeated phrases).
 pub struct VoiceTranscriptionCache {
     cache: Arc<Mutex<LruCache<(u64, Arc<str>), CachedTranslation>>>,
+    hasher: Arc<dyn AudioHasher>,
     hits: Arc<AtomicU64>,
     misses: Arc<AtomicU64>,
 }
@@ -41,15 +62,27 @@ impl VoiceTranscriptionCache {
     /// - 1000 entries ≈ 10-50 MB memory (depends on text length)
     /// - Covers most common phrases in typical voice calls
     pub fn new(capacity: usize) -> Self {
+        Self::with_hasher(capacity, Self::hash_audio)
+    }
+
+    /// Create a cache that keys segments with `hasher` instead of
+    /// [`Self::hash_audio`].
+    pub fn with_hasher(capacity: usize, hasher: impl AudioHasher + 'static) -> Self {
         Self {
             cache: Arc::new(Mutex::new(LruCache::new(
                 NonZeroUsize::new(capacity).expect("Capacity must be non-zero"),
             ))),
+            hasher: Arc::new(hasher),
             hits: Arc::new(AtomicU64::new(0)),
             misses: Arc::new(AtomicU64::new(0)),
         }
     }
 
+    /// Cache key for `samples` under this cache's hasher.
+    pub fn hash(&self, samples: &[i16]) -> u64 {
+        self.hasher.hash(samples)
+    }
+
     /// Hash audio samples for cache key.
     ///
     /// Uses blake3 which is extremely fast (faster than SipHash) and provides
@@ -135,6 +168,49 @@ impl VoiceTranscriptionCache {
         cache.is_empty()
     }
 
+    /// Save every entry to the database, replacing what was saved before,
+    /// and return how many were written.
+    pub async fn save(&self, pool: &DbPool) -> AppResult<usize> {
+        let entries: Vec<PersistedVoiceResult> = {
+            let cache = self.cache.lock().await;
+            // Least recently used first, so reloading keeps the LRU order
+            cache
+                .iter()
+                .rev()
+                .filter_map(|((audio_hash, target_language), cached)| {
+                    let response = serde_json::to_string(&cached.response).ok()?;
+                    Some(PersistedVoiceResult {
+                        audio_hash: *audio_hash as i64,
+                        target_language: target_language.to_string(),
+                        response,
+                    })
+                })
+                .collect()
+        };
+
+        VoiceCacheRepo::replace_all(pool, &entries).await?;
+        Ok(entries.len())
+    }
+
+    /// Load entries saved by [`Self::save`] and return how many were read.
+    /// Beyond capacity, the least recently used are dropped as usual.
+    pub async fn load(&self, pool: &DbPool) -> AppResult<usize> {
+        let mut loaded = 0;
+        for row in VoiceCacheRepo::load_all(pool).await? {
+            let response = match serde_json::from_str(&row.response) {
+                Ok(response) => response,
+                Err(e) => {
+                    warn!(error = %e, "Skipping unreadable saved voice result");
+                    continue;
+                }
+            };
+            self.put(row.audio_hash as u64, Arc::from(row.target_language), response)
+                .await;
+            loaded += 1;
+        }
+        Ok(loaded)
+    }
+
     /// Get cache statistics.
     pub fn stats(&self) -> CacheStats {
         let hits = self.hits.load(Ordering::Relaxed);
@@ -169,7 +245,7 @@ impl Default for VoiceTranscriptionCache {
 }
 
 /// Cache statistics.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, serde::Serialize)]
 pub struct CacheStats {
     pub hits: u64,
     pub misses: u64,
@@ -250,6 +326,10 @@ mod tests {
             tts_audio: None,
             latency_ms: 100,
             audio_hash: 0,
+            confidence: None,
+            started_at: None,
+            tts_format: None,
+            trace_id: None,
         };
 
         cache.put(audio_hash, Arc::clone(&target_lang), response.clone()).await;
@@ -300,6 +380,10 @@ mod tests {
             tts_audio: None,
             latency_ms: 100,
             audio_hash: 0,
+            confidence: None,
+            started_at: None,
+            tts_format: None,
+            trace_id: None,
         };
 
         let response_es = VoiceInferenceResponse::Result {
@@ -314,6 +398,10 @@ mod tests {
             tts_audio: None,
             latency_ms: 100,
             audio_hash: 0,
+            confidence: None,
+            started_at: None,
+            tts_format: None,
+            trace_id: None,
         };
 
         cache.put(audio_hash, Arc::clone(&lang_en), response_en).await;
@@ -350,6 +438,10 @@ mod tests {
             tts_audio: None,
             latency_ms: 100,
             audio_hash: 0,
+            confidence: None,
+            started_at: None,
+            tts_format: None,
+            trace_id: None,
         };
 
         cache.put(1, Arc::clone(&lang), make_response("One")).await;
@@ -380,6 +472,10 @@ mod tests {
             tts_audio: None,
             latency_ms: 100,
             audio_hash: 0,
+            confidence: None,
+            started_at: None,
+            tts_format: None,
+            trace_id: None,
         };
 
         cache.put(123, Arc::clone(&lang), response).await;
@@ -390,6 +486,86 @@ mod tests {
         assert!(cache.is_empty().await);
     }
 
+    #[test]
+    fn test_default_hasher_is_hash_audio() {
+        let cache = VoiceTranscriptionCache::new(10);
+        let samples = [1000i16, -2000, 3000];
+        assert_eq!(cache.hash(&samples), VoiceTranscriptionCache::hash_audio(&samples));
+    }
+
+    #[tokio::test]
+    async fn test_injected_hasher_forces_collision() {
+        use super::super::types::VoiceInferenceResponse;
+
+        // Every segment hashes to the same key
+        let cache = VoiceTranscriptionCache::with_hasher(10, |_: &[i16]| 7);
+        let lang: Arc<str> = Arc::from("en");
+        let (a, b) = ([1i16, 2, 3], [4i16, 5, 6]);
+        assert_eq!(cache.hash(&a), cache.hash(&b));
+
+        let response = VoiceInferenceResponse::Result {
+            guild_id: "123".to_string(),
+            channel_id: "456".to_string(),
+            user_id: "789".to_string(),
+            username: "TestUser".to_string(),
+            original_text: "first".to_string(),
+            translated_text: "first".to_string(),
+            source_language: "en".to_string(),
+            target_language: "en".to_string(),
+            tts_audio: None,
+            latency_ms: 100,
+            audio_hash: 7,
+            confidence: None,
+            started_at: None,
+            tts_format: None,
+            trace_id: None,
+        };
+        cache.put(cache.hash(&a), Arc::clone(&lang), response).await;
+
+        // Different audio with a colliding hash is served the first entry
+        assert!(cache.get(cache.hash(&b), &lang).await.is_some());
+        assert_eq!(cache.len().await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_injected_hasher_controls_eviction() {
+        use super::super::types::VoiceInferenceResponse;
+
+        // Key each segment by its first sample so eviction order is predictable
+        let cache = VoiceTranscriptionCache::with_hasher(2, |s: &[i16]| s[0] as u64);
+        let lang: Arc<str> = Arc::from("en");
+        let response = |hash: u64| VoiceInferenceResponse::Result {
+            guild_id: "123".to_string(),
+            channel_id: "456".to_string(),
+            user_id: "789".to_string(),
+            username: "TestUser".to_string(),
+            original_text: "text".to_string(),
+            translated_text: "text".to_string(),
+            source_language: "en".to_string(),
+            target_language: "en".to_string(),
+            tts_audio: None,
+            latency_ms: 100,
+            audio_hash: hash,
+            confidence: None,
+            started_at: None,
+            tts_format: None,
+            trace_id: None,
+        };
+
+        for samples in [[1i16, 9], [2, 9]] {
+            let hash = cache.hash(&samples);
+            cache.put(hash, Arc::clone(&lang), response(hash)).await;
+        }
+        // Touch 1 so 2 is least recently used
+        assert!(cache.contains(1, &lang).await);
+        assert!(cache.get(1, &lang).await.is_some());
+        cache.put(cache.hash(&[3, 9]), Arc::clone(&lang), response(3)).await;
+
+        assert!(cache.contains(1, &lang).await);
+        assert!(!cache.contains(2, &lang).await);
+        assert!(cache.contains(3, &lang).await);
+    }
+
     #[tokio::test]
     async fn test_cache_stats_reset() {
         let cache = VoiceTranscriptionCache::new(10);
@@ -408,4 +584,59 @@ mod tests {
         assert_eq!(stats.hits, 0);
         assert_eq!(stats.misses, 0);
     }
+
+    #[tokio::test]
+    async fn test_entries_survive_save_and_reload() {
+        use super::super::types::VoiceInferenceResponse;
+
+        let pool = crate::db::queries::setup_test_db().await;
+        let result = |text: &str| VoiceInferenceResponse::Result {
+            guild_id: "123".to_string(),
+            channel_id: "456".to_string(),
+            user_id: "789".to_string(),
+            username: "TestUser".to_string(),
+            original_text: text.to_string(),
+            translated_text: text.to_string(),
+            source_language: "en".to_string(),
+            target_language: "en".to_string(),
+            tts_audio: Some("UklGRg==".to_string()),
+            latency_ms: 100,
+            audio_hash: 0,
+            confidence: None,
+            started_at: None,
+            tts_format: Some(super::super::tts_audio::TtsFormat::Wav),
+            trace_id: None,
+        };
+        let lang: Arc<str> = Arc::from("en");
+        let cache = VoiceTranscriptionCache::new(10);
+        cache.put(1, Arc::clone(&lang), result("one")).await;
+        cache.put(u64::MAX, Arc::clone(&lang), result("max")).await;
+        cache.put(3, Arc::clone(&lang), result("three")).await;
+        // Touch the oldest so it is no longer the least recently used
+        cache.get(1, &lang).await;
+        assert_eq!(cache.save(&pool).await.unwrap(), 3);
+
+        // A smaller cache keeps the most recently used
+        let reopened = VoiceTranscriptionCache::new(2);
+        assert_eq!(reopened.load(&pool).await.unwrap(), 3);
+        assert!(!reopened.contains(u64::MAX, &lang).await);
+        assert!(reopened.contains(3, &lang).await);
+        match reopened.get(1, &lang).await {
+            Some(VoiceInferenceResponse::Result { original_text, tts_audio, .. }) => {
+                assert_eq!(original_text, "one");
+                assert_eq!(tts_audio.as_deref(), Some("UklGRg=="));
+            }
+            other => panic!("expected the saved result, got {:?}", other),
+        }
+
+        // Hashes with the top bit set survive SQLite's signed integers
+        let roomy = VoiceTranscriptionCache::new(10);
+        roomy.load(&pool).await.unwrap();
+        assert!(roomy.contains(u64::MAX, &lang).await);
+
+        // A second save replaces the first rather than adding to it
+        cache.clear().await;
+        assert_eq!(cache.save(&pool).await.unwrap(), 0);
+        assert_eq!(VoiceTranscriptionCache::new(10).load(&pool).await.unwrap(), 0);
+    }
 }
@@ -1,22 +1,51 @@
 //! WebSocket client for voice inference service.
 
-use super::types::{AudioSegment, VoiceInferenceRequest, VoiceInferenceResponse};
+use super::cache::VoiceTranscriptionCache;
+use crate::admin::InferenceTokens;
+use crate::translation::Formality;
+use super::types::{
+    AudioSegment, PipelineStage, VoiceInferenceRequest, VoiceInferenceResponse, VoicePipeline,
+    PROTOCOL_VERSION,
+};
+use super::tts_audio::TtsFormat;
+use super::tts_client::TtsClient;
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
-use futures::{SinkExt, StreamExt};
-use std::sync::Arc;
-use std::time::Duration;
-use tokio::sync::{broadcast, mpsc, RwLock};
-use tokio_tungstenite::{connect_async, tungstenite::Message};
-use tracing::{debug, error, info, warn};
+use futures::{Sink, SinkExt, Stream, StreamExt};
+use serde::Serialize;
+use std::collections::{HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, mpsc, OwnedSemaphorePermit, RwLock, Semaphore};
+use tokio_tungstenite::{
+    connect_async_with_config, tungstenite,
+    tungstenite::{client::IntoClientRequest, http::HeaderValue, protocol::WebSocketConfig, Message},
+};
+use uuid::Uuid;
+
+/// Error code the inference service sends when it rejects our protocol version
+pub const PROTOCOL_MISMATCH_CODE: &str = "PROTOCOL_MISMATCH";
+
+/// Largest WebSocket message sent or accepted by default: 16 MiB, the most
+/// the inference service's server (uvicorn) accepts unless told otherwise
+pub const DEFAULT_MAX_FRAME_SIZE: usize = 16 * 1024 * 1024;
+
+/// Room left in each audio frame for the length prefix and JSON header
+const FRAME_HEADER_ALLOWANCE: usize = 4096;
+use tracing::{debug, error, info, trace, warn};
 
 /// Audio segment bundled with translation config for sending to inference.
 #[derive(Debug, Clone)]
 struct AudioRequest {
     segment: AudioSegment,
+    /// None lets the inference service detect the spoken language
+    source_language: Option<String>,
     target_language: String,
+    formality: Formality,
     generate_tts: bool,
     /// Audio hash for cache correlation (computed from samples)
     audio_hash: u64,
+    /// Logged with the segment by both services and echoed in its response
+    trace_id: Uuid,
 }
 
 /// Strategy for handling full audio queue (backpressure).
@@ -31,12 +60,15 @@ pub enum QueueFullStrategy {
 }
 
 /// Connection state.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ConnectionState {
     Disconnected,
     Connecting,
     Connected,
     Reconnecting,
+    /// Server speaks a different protocol version; the client has stopped
+    Incompatible,
 }
 
 /// Voice inference client configuration.
@@ -56,6 +88,26 @@ pub struct VoiceClientConfig {
     pub max_queue_size: usize,
     /// Strategy for handling full queue
     pub queue_full_strategy: QueueFullStrategy,
+    /// Maximum segments sent but not yet answered; further audio waits in
+    /// the queue until the service catches up
+    pub max_in_flight: usize,
+    /// Deepest pipeline segments go through; stages beyond it are never
+    /// requested
+    pub pipeline: VoicePipeline,
+    /// TTS encoding requested from the service
+    pub tts_format: TtsFormat,
+    /// Separate TTS service to synthesise speech with instead of the voice
+    /// inference service
+    pub tts_url: Option<String>,
+    /// Largest WebSocket message, in bytes. Segments too long to fit are
+    /// split before sending, and larger incoming messages are refused.
+    pub max_frame_size: usize,
+    /// Log each segment's frame size, sample count and round-trip time at
+    /// trace level, keyed by `audio_hash`. Never logs the audio itself.
+    pub profile_payloads: bool,
+    /// Bearer tokens: the shared one authenticates the connection, and each
+    /// segment carries its guild's own
+    pub inference_tokens: InferenceTokens,
 }
 
 impl Default for VoiceClientConfig {
@@ -71,6 +123,200 @@ impl Default for VoiceClientConfig {
             max_queue_size: 500,
             // Drop newest for real-time voice (old audio is already stale)
             queue_full_strategy: QueueFullStrategy::DropNewest,
+            // One segment decoding plus a few queued server-side
+            max_in_flight: 4,
+            pipeline: VoicePipeline::default(),
+            tts_format: TtsFormat::Wav,
+            tts_url: None,
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+            profile_payloads: false,
+            inference_tokens: InferenceTokens::default(),
+        }
+    }
+}
+
+/// Most samples one binary audio frame can carry within `max_frame_size`
+pub fn max_segment_samples(max_frame_size: usize) -> usize {
+    (max_frame_size.saturating_sub(FRAME_HEADER_ALLOWANCE) / 2).max(1)
+}
+
+/// Segments sent to the inference service that haven't been answered yet.
+///
+/// Each one holds a semaphore permit until its `Result` (matched by
+/// `audio_hash`) or an `Error` arrives, or it times out. Segments that fail
+/// or go unanswered are logged as dead letters with their trace id.
+#[derive(Debug)]
+struct InFlightRequests {
+    permits: Arc<Semaphore>,
+    pending: Mutex<VecDeque<PendingRequest>>,
+    /// Requests whose speech comes from the separate TTS service
+    needs_speech: Mutex<HashSet<u64>>,
+}
+
+#[derive(Debug)]
+struct PendingRequest {
+    audio_hash: u64,
+    trace_id: Uuid,
+    sent_at: Instant,
+    /// When the buffer flushed the segment, for end-to-end latency
+    flushed_at: Instant,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl InFlightRequests {
+    fn new(max_in_flight: usize) -> Self {
+        Self {
+            permits: Arc::new(Semaphore::new(max_in_flight.max(1))),
+            pending: Mutex::new(VecDeque::new()),
+            needs_speech: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Wait until another segment may be sent
+    async fn reserve(&self) -> OwnedSemaphorePermit {
+        Arc::clone(&self.permits)
+            .acquire_owned()
+            .await
+            .expect("in-flight semaphore is never closed")
+    }
+
+    /// Hold `permit` until the response for `audio_hash` arrives
+    fn sent(
+        &self,
+        audio_hash: u64,
+        trace_id: Uuid,
+        flushed_at: Instant,
+        permit: OwnedSemaphorePermit,
+    ) {
+        self.pending.lock().unwrap().push_back(PendingRequest {
+            audio_hash,
+            trace_id,
+            sent_at: Instant::now(),
+            flushed_at,
+            _permit: permit,
+        });
+    }
+
+    /// When the segment answered by `response` was flushed, if it's pending
+    fn flushed_at(&self, response: &VoiceInferenceResponse) -> Option<Instant> {
+        self.pending_for(response, |p| p.flushed_at)
+    }
+
+    /// When the segment answered by `response` was sent, if it's pending
+    fn sent_at(&self, response: &VoiceInferenceResponse) -> Option<Instant> {
+        self.pending_for(response, |p| p.sent_at)
+    }
+
+    fn pending_for<T>(
+        &self,
+        response: &VoiceInferenceResponse,
+        f: impl FnOnce(&PendingRequest) -> T,
+    ) -> Option<T> {
+        let VoiceInferenceResponse::Result { audio_hash, .. } = response else {
+            return None;
+        };
+        self.pending
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|p| p.audio_hash == *audio_hash)
+            .map(f)
+    }
+
+    /// Mark the request for `audio_hash` as wanting speech from the TTS service
+    fn expect_speech(&self, audio_hash: u64) {
+        self.needs_speech.lock().unwrap().insert(audio_hash);
+    }
+
+    /// Free the slot of the request `response` answers, returning whether
+    /// its speech still has to be synthesised
+    fn answered(&self, response: &VoiceInferenceResponse) -> bool {
+        let mut pending = self.pending.lock().unwrap();
+        let mut needs_speech = self.needs_speech.lock().unwrap();
+        match response {
+            VoiceInferenceResponse::Result { audio_hash, .. } => {
+                if let Some(pos) = pending.iter().position(|p| p.audio_hash == *audio_hash) {
+                    pending.remove(pos);
+                }
+                needs_speech.remove(audio_hash)
+            }
+            // Older services don't say which segment failed; they answer
+            // in order, so it's the oldest one
+            VoiceInferenceResponse::Error { trace_id, message, .. } => {
+                let pos = match trace_id {
+                    Some(id) => pending.iter().position(|p| p.trace_id == *id),
+                    None => (!pending.is_empty()).then_some(0),
+                };
+                if let Some(p) = pos.and_then(|pos| pending.remove(pos)) {
+                    needs_speech.remove(&p.audio_hash);
+                    dead_letter(p.trace_id, p.audio_hash, message);
+                }
+                false
+            }
+            _ => false,
+        }
+    }
+
+    /// Free slots of requests that went unanswered for longer than `timeout`
+    fn expire(&self, timeout: Duration) {
+        let mut pending = self.pending.lock().unwrap();
+        pending.retain(|p| {
+            let waiting = p.sent_at.elapsed() < timeout;
+            if !waiting {
+                dead_letter(p.trace_id, p.audio_hash, "no response before timeout");
+            }
+            waiting
+        });
+        self.needs_speech
+            .lock()
+            .unwrap()
+            .retain(|hash| pending.iter().any(|p| p.audio_hash == *hash));
+    }
+
+    /// Forget every outstanding request (their connection is gone)
+    fn clear(&self) {
+        for p in self.pending.lock().unwrap().drain(..) {
+            dead_letter(p.trace_id, p.audio_hash, "connection lost");
+        }
+        self.needs_speech.lock().unwrap().clear();
+    }
+
+    fn outstanding(&self) -> usize {
+        self.pending.lock().unwrap().len()
+    }
+}
+
+/// Log a segment that will never get a result and count it on `/metrics`
+fn dead_letter(trace_id: Uuid, audio_hash: u64, reason: &str) {
+    warn!(%trace_id, audio_hash, reason, "Voice segment dead-lettered");
+    crate::metrics::voice_dead_letters().record(trace_id);
+}
+
+/// Current connection state, announcing each change to subscribers
+struct StateTracker {
+    current: RwLock<ConnectionState>,
+    changes: broadcast::Sender<ConnectionState>,
+}
+
+impl StateTracker {
+    fn new() -> Self {
+        let (changes, _) = broadcast::channel(16);
+        Self {
+            current: RwLock::new(ConnectionState::Disconnected),
+            changes,
+        }
+    }
+
+    async fn get(&self) -> ConnectionState {
+        *self.current.read().await
+    }
+
+    async fn set(&self, state: ConnectionState) {
+        let mut current = self.current.write().await;
+        if *current != state {
+            *current = state;
+            // Nobody listening is fine
+            let _ = self.changes.send(state);
         }
     }
 }
@@ -78,7 +324,7 @@ impl Default for VoiceClientConfig {
 /// WebSocket client for voice inference.
 pub struct VoiceInferenceClient {
     config: VoiceClientConfig,
-    state: Arc<RwLock<ConnectionState>>,
+    state: Arc<StateTracker>,
     /// Channel to send audio requests (segment + config) for processing
     audio_tx: mpsc::Sender<AudioRequest>,
     /// Channel to receive transcription results
@@ -96,7 +342,7 @@ impl VoiceInferenceClient {
 
         let client = Self {
             config: config.clone(),
-            state: Arc::new(RwLock::new(ConnectionState::Disconnected)),
+            state: Arc::new(StateTracker::new()),
             audio_tx,
             _result_rx,
             result_tx: result_tx.clone(),
@@ -111,12 +357,20 @@ impl VoiceInferenceClient {
 
     /// Get current connection state.
     pub async fn state(&self) -> ConnectionState {
-        *self.state.read().await
+        self.state.get().await
+    }
+
+    /// Subscribe to connection state changes (connecting, reconnecting, ...).
+    ///
+    /// Only changes after subscribing are delivered; read [`Self::state`]
+    /// for the current one.
+    pub fn subscribe_state(&self) -> broadcast::Receiver<ConnectionState> {
+        self.state.changes.subscribe()
     }
 
     /// Check if connected.
     pub async fn is_connected(&self) -> bool {
-        *self.state.read().await == ConnectionState::Connected
+        self.state.get().await == ConnectionState::Connected
     }
 
     /// Send audio segment for processing.
@@ -124,10 +378,15 @@ impl VoiceInferenceClient {
     /// Handles backpressure according to the configured strategy.
     ///
     /// The audio_hash is used to correlate responses with requests for caching.
+    /// Segments too long for one frame are sent in pieces, each answered
+    /// separately under the hash of its own audio. Each segment or piece gets
+    /// a fresh trace id, which the service logs and echoes back.
     pub async fn send_audio(
         &self,
         segment: AudioSegment,
+        source_language: Option<&str>,
         target_language: &str,
+        formality: Formality,
         generate_tts: bool,
         audio_hash: u64,
     ) -> Result<(), VoiceClientError> {
@@ -135,14 +394,46 @@ impl VoiceInferenceClient {
             return Err(VoiceClientError::NotConnected);
         }
 
-        // Package segment with config and audio hash for cache correlation
-        let req = AudioRequest {
-            segment,
-            target_language: target_language.to_string(),
-            generate_tts,
-            audio_hash,
-        };
+        let max_samples = max_segment_samples(self.config.max_frame_size);
+        if segment.samples.len() <= max_samples {
+            return self
+                .enqueue(AudioRequest {
+                    segment,
+                    source_language: source_language.map(str::to_string),
+                    target_language: target_language.to_string(),
+                    formality,
+                    generate_tts,
+                    audio_hash,
+                    trace_id: Uuid::new_v4(),
+                })
+                .await;
+        }
 
+        let pieces = segment.split(max_samples);
+        info!(
+            pieces = pieces.len(),
+            max_frame_size = self.config.max_frame_size,
+            "Audio segment too large for one frame, sending in pieces"
+        );
+        for piece in pieces {
+            let audio_hash = VoiceTranscriptionCache::hash_audio(&piece.samples);
+            self.enqueue(AudioRequest {
+                segment: piece,
+                source_language: source_language.map(str::to_string),
+                target_language: target_language.to_string(),
+                formality,
+                generate_tts,
+                audio_hash,
+                trace_id: Uuid::new_v4(),
+            })
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Queue a request for the connection task, applying the backpressure
+    /// strategy when the queue is full
+    async fn enqueue(&self, req: AudioRequest) -> Result<(), VoiceClientError> {
         // Try non-blocking send first
         match self.audio_tx.try_send(req) {
             Ok(()) => Ok(()),
@@ -154,6 +445,7 @@ impl VoiceInferenceClient {
                             queue_size = self.config.max_queue_size,
                             "Audio queue full, dropping newest segment (backpressure)"
                         );
+                        dead_letter(req.trace_id, req.audio_hash, "audio queue full");
                         Err(VoiceClientError::QueueFull)
                     }
                     QueueFullStrategy::DropOldest => {
@@ -164,6 +456,7 @@ impl VoiceInferenceClient {
                             queue_size = self.config.max_queue_size,
                             "Audio queue full, dropping segment (backpressure)"
                         );
+                        dead_letter(req.trace_id, req.audio_hash, "audio queue full");
                         Err(VoiceClientError::QueueFull)
                     }
                     QueueFullStrategy::Block => {
@@ -209,120 +502,247 @@ async fn connection_handler(
     config: VoiceClientConfig,
     mut audio_rx: mpsc::Receiver<AudioRequest>,
     result_tx: broadcast::Sender<VoiceInferenceResponse>,
-    state: Arc<RwLock<ConnectionState>>,
+    state: Arc<StateTracker>,
 ) {
     let mut reconnect_attempts = 0;
+    let in_flight = Arc::new(InFlightRequests::new(config.max_in_flight));
+    let tts = config
+        .tts_url
+        .as_deref()
+        .map(|url| TtsClient::new(url, config.request_timeout));
 
     loop {
-        *state.write().await = ConnectionState::Connecting;
+        state.set(ConnectionState::Connecting).await;
         info!(url = %config.url, "Connecting to voice inference service");
 
-        match connect_async(&config.url).await {
+        let ws_config = WebSocketConfig {
+            max_message_size: Some(config.max_frame_size),
+            max_frame_size: Some(config.max_frame_size),
+            ..Default::default()
+        };
+        let connection = match config.url.as_str().into_client_request() {
+            Ok(mut request) => {
+                authorize(&mut request, &config.inference_tokens);
+                connect_async_with_config(request, Some(ws_config), false).await
+            }
+            Err(e) => Err(e),
+        };
+        match connection {
             Ok((ws_stream, _response)) => {
-                *state.write().await = ConnectionState::Connected;
-                reconnect_attempts = 0;
-                info!("Connected to voice inference service");
-
                 let (mut write, mut read) = ws_stream.split();
 
-                // Spawn reader task
-                let result_tx_clone = result_tx.clone();
-                let reader_handle = tokio::spawn(async move {
-                    while let Some(msg) = read.next().await {
-                        match msg {
-                            Ok(Message::Text(text)) => {
-                                match serde_json::from_str::<VoiceInferenceResponse>(&text) {
-                                    Ok(response) => {
-                                        debug!(?response, "Received voice inference response");
-                                        let _ = result_tx_clone.send(response);
+                match handshake(&mut write, &mut read, config.request_timeout).await {
+                    Ok(()) => {
+                        state.set(ConnectionState::Connected).await;
+                        reconnect_attempts = 0;
+                        info!("Connected to voice inference service");
+
+                        // Spawn reader task
+                        let result_tx_clone = result_tx.clone();
+                        let reader_in_flight = Arc::clone(&in_flight);
+                        let reader_tts = tts.clone();
+                        let tts_format = config.tts_format;
+                        let profile_payloads = config.profile_payloads;
+                        let mut reader_handle = tokio::spawn(async move {
+                            while let Some(msg) = read.next().await {
+                                match msg {
+                                    Ok(Message::Text(text)) => {
+                                        match serde_json::from_str::<VoiceInferenceResponse>(&text) {
+                                            Ok(mut response) => {
+                                                debug!(?response, "Received voice inference response");
+                                                let flushed_at = reader_in_flight.flushed_at(&response);
+                                                // Measured before any separate TTS call, so it's the service alone
+                                                if let Some(sent_at) = reader_in_flight.sent_at(&response) {
+                                                    let round_trip = sent_at.elapsed();
+                                                    crate::metrics::voice_round_trip().observe(round_trip);
+                                                    if profile_payloads {
+                                                        if let VoiceInferenceResponse::Result { audio_hash, latency_ms, trace_id, .. } = &response {
+                                                            trace!(
+                                                                audio_hash,
+                                                                trace_id = ?trace_id,
+                                                                round_trip_ms = round_trip.as_millis() as u64,
+                                                                service_latency_ms = latency_ms,
+                                                                "Voice inference result received"
+                                                            );
+                                                        }
+                                                    }
+                                                }
+                                                let needs_speech = reader_in_flight.answered(&response);
+                                                match reader_tts.clone().filter(|_| needs_speech) {
+                                                    // Synthesize off the read loop so results behind
+                                                    // this one aren't held up by the TTS service
+                                                    Some(tts) => {
+                                                        let result_tx = result_tx_clone.clone();
+                                                        tokio::spawn(async move {
+                                                            tts.add_speech(&mut response, tts_format).await;
+                                                            if let Some(flushed_at) = flushed_at {
+                                                                crate::metrics::voice_latency().observe(flushed_at.elapsed());
+                                                            }
+                                                            let _ = result_tx.send(response);
+                                                        });
+                                                    }
+                                                    None => {
+                                                        if let Some(flushed_at) = flushed_at {
+                                                            crate::metrics::voice_latency().observe(flushed_at.elapsed());
+                                                        }
+                                                        let _ = result_tx_clone.send(response);
+                                                    }
+                                                }
+                                            }
+                                            Err(e) => {
+                                                warn!(error = %e, "Failed to parse voice response");
+                                            }
+                                        }
+                                    }
+                                    Ok(Message::Close(_)) => {
+                                        info!("Voice inference connection closed");
+                                        break;
+                                    }
+                                    Ok(Message::Ping(_data)) => {
+                                        debug!("Received ping");
+                                        // Pong is handled automatically by tungstenite
                                     }
                                     Err(e) => {
-                                        warn!(error = %e, "Failed to parse voice response");
+                                        error!(error = %e, "WebSocket read error");
+                                        break;
                                     }
+                                    _ => {}
                                 }
                             }
-                            Ok(Message::Close(_)) => {
-                                info!("Voice inference connection closed");
-                                break;
-                            }
-                            Ok(Message::Ping(_data)) => {
-                                debug!("Received ping");
-                                // Pong is handled automatically by tungstenite
-                            }
-                            Err(e) => {
-                                error!(error = %e, "WebSocket read error");
-                                break;
-                            }
-                            _ => {}
-                        }
-                    }
-                });
-
-                // Process outgoing audio
-                let mut ping_interval = tokio::time::interval(config.ping_interval);
-
-                loop {
-                    tokio::select! {
-                        Some(req) = audio_rx.recv() => {
-                            let segment = &req.segment;
-
-                            // Use binary WebSocket frames instead of base64 text
-                            // Format: JSON header + raw PCM data
-                            let header = VoiceInferenceRequest::Audio {
-                                guild_id: segment.guild_id.to_string(),
-                                channel_id: segment.channel_id.to_string(),
-                                user_id: segment.user_id.to_string(),
-                                username: segment.username.clone(),
-                                audio_base64: String::new(), // Placeholder, will send binary
-                                sample_rate: super::types::DISCORD_SAMPLE_RATE,
-                                target_language: req.target_language.clone(),
-                                generate_tts: req.generate_tts,
-                                audio_hash: req.audio_hash, // For cache correlation
-                            };
-
-                            // Serialize header as JSON
-                            let header_json = serde_json::to_string(&header)
-                                .expect("Failed to serialize request");
-                            let header_bytes = header_json.as_bytes();
-
-                            // Build binary message: [4-byte header length][header JSON][raw PCM i16 samples]
-                            let header_len = header_bytes.len() as u32;
-                            let mut binary_msg = Vec::with_capacity(
-                                4 + header_bytes.len() + segment.samples.len() * 2
-                            );
-                            binary_msg.extend_from_slice(&header_len.to_le_bytes());
-                            binary_msg.extend_from_slice(header_bytes);
-                            // Raw PCM samples (no base64 encoding = 33% bandwidth savings)
-                            for sample in &segment.samples {
-                                binary_msg.extend_from_slice(&sample.to_le_bytes());
-                            }
+                        });
+
+                        // Process outgoing audio
+                        let mut ping_interval = tokio::time::interval(config.ping_interval);
+
+                        loop {
+                            tokio::select! {
+                                // Only take audio off the queue once the service has room for it
+                                Some((req, permit)) = async {
+                                    let permit = in_flight.reserve().await;
+                                    audio_rx.recv().await.map(|req| (req, permit))
+                                } => {
+                                    let segment = &req.segment;
+                                    let mut stages = config.pipeline.stages(
+                                        req.source_language.as_deref(),
+                                        &req.target_language,
+                                        req.generate_tts,
+                                    );
+                                    let wants_tts = stages.contains(&PipelineStage::Tts);
+                                    // With a separate TTS service the voice service only transcribes and translates
+                                    let inline_tts = wants_tts && tts.is_none();
+                                    if !inline_tts {
+                                        stages.retain(|stage| *stage != PipelineStage::Tts);
+                                    }
 
-                            if let Err(e) = write.send(Message::Binary(binary_msg)).await {
-                                error!(error = %e, "Failed to send audio to inference");
-                                break;
-                            }
+                                    // Use binary WebSocket frames instead of base64 text
+                                    // Format: JSON header + raw PCM data
+                                    let header = VoiceInferenceRequest::Audio {
+                                        guild_id: segment.guild_id.to_string(),
+                                        channel_id: segment.channel_id.to_string(),
+                                        user_id: segment.user_id.to_string(),
+                                        username: segment.username.clone(),
+                                        audio_base64: String::new(), // Placeholder, will send binary
+                                        sample_rate: super::types::DISCORD_SAMPLE_RATE,
+                                        source_language: req.source_language.clone(),
+                                        target_language: req.target_language.clone(),
+                                        generate_tts: inline_tts,
+                                        formality: req.formality,
+                                        stages,
+                                        audio_hash: req.audio_hash, // For cache correlation
+                                        started_at: Some(segment.started_at),
+                                        overlap_samples: segment.overlap_samples as u32,
+                                        tts_format: inline_tts.then_some(config.tts_format),
+                                        inference_token: config
+                                            .inference_tokens
+                                            .for_guild(Some(segment.guild_id))
+                                            .map(str::to_string),
+                                        trace_id: Some(req.trace_id),
+                                    };
+
+                                    // Serialize header as JSON
+                                    let header_json = serde_json::to_string(&header)
+                                        .expect("Failed to serialize request");
+                                    let header_bytes = header_json.as_bytes();
+
+                                    // Build binary message: [4-byte header length][header JSON][raw PCM i16 samples]
+                                    let header_len = header_bytes.len() as u32;
+                                    let mut binary_msg = Vec::with_capacity(
+                                        4 + header_bytes.len() + segment.samples.len() * 2
+                                    );
+                                    binary_msg.extend_from_slice(&header_len.to_le_bytes());
+                                    binary_msg.extend_from_slice(header_bytes);
+                                    // Raw PCM samples (no base64 encoding = 33% bandwidth savings)
+                                    for sample in &segment.samples {
+                                        binary_msg.extend_from_slice(&sample.to_le_bytes());
+                                    }
 
-                            debug!(
-                                user_id = segment.user_id,
-                                duration_ms = segment.duration().as_millis(),
-                                samples = segment.samples.len(),
-                                "Sent audio to inference service (binary)"
-                            );
-                        }
+                                    let frame_bytes = binary_msg.len();
+                                    if let Err(e) = write.send(Message::Binary(binary_msg)).await {
+                                        error!(error = %e, "Failed to send audio to inference");
+                                        dead_letter(req.trace_id, req.audio_hash, "send failed");
+                                        break;
+                                    }
+                                    crate::metrics::voice_frame_bytes().observe_value(frame_bytes as f64);
+                                    if config.profile_payloads {
+                                        trace!(
+                                            audio_hash = req.audio_hash,
+                                            trace_id = %req.trace_id,
+                                            frame_bytes,
+                                            header_bytes = header_bytes.len(),
+                                            samples = segment.samples.len(),
+                                            "Voice audio frame sent"
+                                        );
+                                    }
+                                    in_flight.sent(req.audio_hash, req.trace_id, segment.end_time, permit);
+                                    if wants_tts && !inline_tts {
+                                        in_flight.expect_speech(req.audio_hash);
+                                    }
+
+                                    debug!(
+                                        user_id = segment.user_id,
+                                        trace_id = %req.trace_id,
+                                        duration_ms = segment.duration().as_millis(),
+                                        samples = segment.samples.len(),
+                                        in_flight = in_flight.outstanding(),
+                                        "Sent audio to inference service (binary)"
+                                    );
+                                }
+
+                                // The service hung up; don't wait for a write to fail
+                                _ = &mut reader_handle => {
+                                    warn!("Voice inference service closed the connection");
+                                    break;
+                                }
+
+                                _ = ping_interval.tick() => {
+                                    in_flight.expire(config.request_timeout);
 
-                        _ = ping_interval.tick() => {
-                            let ping = serde_json::to_string(&VoiceInferenceRequest::Ping)
-                                .expect("Failed to serialize ping");
-                            if let Err(e) = write.send(Message::Text(ping.into())).await {
-                                warn!(error = %e, "Failed to send ping");
-                                break;
+                                    let ping = serde_json::to_string(&VoiceInferenceRequest::Ping)
+                                        .expect("Failed to serialize ping");
+                                    if let Err(e) = write.send(Message::Text(ping.into())).await {
+                                        warn!(error = %e, "Failed to send ping");
+                                        break;
+                                    }
+                                }
                             }
                         }
+
+                        // Connection lost, abort reader; unanswered segments won't be answered now
+                        reader_handle.abort();
+                        in_flight.clear();
+                    }
+                    Err(e @ VoiceClientError::ProtocolMismatch { .. }) => {
+                        error!(
+                            error = %e,
+                            "Refusing to use voice inference service; deploy matching bot and inference versions"
+                        );
+                        state.set(ConnectionState::Incompatible).await;
+                        return;
+                    }
+                    Err(e) => {
+                        error!(error = %e, "Voice inference handshake failed");
                     }
                 }
-
-                // Connection lost, abort reader
-                reader_handle.abort();
             }
             Err(e) => {
                 error!(error = %e, "Failed to connect to voice inference service");
@@ -330,7 +750,7 @@ async fn connection_handler(
         }
 
         // Reconnection logic
-        *state.write().await = ConnectionState::Reconnecting;
+        state.set(ConnectionState::Reconnecting).await;
         reconnect_attempts += 1;
 
         if reconnect_attempts >= config.max_reconnect_attempts {
@@ -338,7 +758,7 @@ async fn connection_handler(
                 attempts = reconnect_attempts,
                 "Max reconnection attempts reached, giving up"
             );
-            *state.write().await = ConnectionState::Disconnected;
+            state.set(ConnectionState::Disconnected).await;
             break;
         }
 
@@ -352,6 +772,78 @@ async fn connection_handler(
     }
 }
 
+/// Present the shared inference token, if one was provisioned, when
+/// opening the connection.
+fn authorize(request: &mut tungstenite::handshake::client::Request, tokens: &InferenceTokens) {
+    let Some(token) = tokens.for_guild(None) else {
+        return;
+    };
+    match HeaderValue::from_str(&format!("Bearer {}", token)) {
+        Ok(value) => {
+            request.headers_mut().insert("Authorization", value);
+        }
+        Err(_) => warn!("Inference token isn't a valid header value; connecting without it"),
+    }
+}
+
+/// Announce our protocol version and wait for the server's `Ready`, failing
+/// with [`VoiceClientError::ProtocolMismatch`] if the versions differ.
+async fn handshake<W, R>(
+    write: &mut W,
+    read: &mut R,
+    timeout: Duration,
+) -> Result<(), VoiceClientError>
+where
+    W: Sink<Message, Error = tungstenite::Error> + Unpin,
+    R: Stream<Item = Result<Message, tungstenite::Error>> + Unpin,
+{
+    let hello = serde_json::to_string(&VoiceInferenceRequest::Hello {
+        protocol_version: PROTOCOL_VERSION,
+    })?;
+    write.send(Message::Text(hello)).await?;
+
+    let wait_for_ready = async {
+        while let Some(msg) = read.next().await {
+            let Message::Text(text) = msg? else {
+                continue;
+            };
+            match serde_json::from_str::<VoiceInferenceResponse>(&text) {
+                Ok(VoiceInferenceResponse::Ready {
+                    protocol_version, ..
+                }) => {
+                    return match protocol_mismatch(protocol_version) {
+                        Some(mismatch) => Err(mismatch),
+                        None => Ok(()),
+                    };
+                }
+                Ok(VoiceInferenceResponse::Error {
+                    code: Some(code), ..
+                }) if code == PROTOCOL_MISMATCH_CODE => {
+                    return Err(VoiceClientError::ProtocolMismatch {
+                        client: PROTOCOL_VERSION,
+                        server: None,
+                    });
+                }
+                _ => debug!("Ignoring message received before Ready"),
+            }
+        }
+        Err(VoiceClientError::ChannelClosed)
+    };
+
+    tokio::time::timeout(timeout, wait_for_ready)
+        .await
+        .map_err(|_| VoiceClientError::Timeout)?
+}
+
+/// Mismatch error for the protocol version a server reported in its `Ready`
+/// message, or `None` if it matches ours
+fn protocol_mismatch(server: Option<u32>) -> Option<VoiceClientError> {
+    (server != Some(PROTOCOL_VERSION)).then_some(VoiceClientError::ProtocolMismatch {
+        client: PROTOCOL_VERSION,
+        server,
+    })
+}
+
 /// Voice client errors.
 #[derive(Debug, thiserror::Error)]
 pub enum VoiceClientError {
@@ -370,6 +862,12 @@ pub enum VoiceClientError {
     #[error("Failed to broadcast cached result")]
     BroadcastFailed,
 
+    #[error(
+        "Voice protocol mismatch: bot speaks v{client}, inference service speaks {}",
+        .server.map_or("an unversioned protocol".to_string(), |v| format!("v{}", v))
+    )]
+    ProtocolMismatch { client: u32, server: Option<u32> },
+
     #[error("WebSocket error: {0}")]
     WebSocket(#[from] tokio_tungstenite::tungstenite::Error),
 
@@ -395,4 +893,125 @@ mod tests {
         assert_eq!(config.url, "ws://localhost:8001/voice");
         assert_eq!(config.max_reconnect_attempts, 10);
     }
+
+    fn result_for(audio_hash: u64) -> VoiceInferenceResponse {
+        VoiceInferenceResponse::Result {
+            guild_id: "1".to_string(),
+            channel_id: "2".to_string(),
+            user_id: "3".to_string(),
+            username: "U".to_string(),
+            original_text: "hi".to_string(),
+            translated_text: "hola".to_string(),
+            source_language: "en".to_string(),
+            target_language: "es".to_string(),
+            tts_audio: None,
+            latency_ms: 10,
+            audio_hash,
+            confidence: None,
+            started_at: None,
+            tts_format: None,
+            trace_id: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_in_flight_limit_caps_outstanding_segments() {
+        let in_flight = InFlightRequests::new(2);
+        for hash in [1, 2] {
+            let permit = in_flight.reserve().await;
+            in_flight.sent(hash, Uuid::nil(), Instant::now(), permit);
+        }
+        assert_eq!(in_flight.outstanding(), 2);
+
+        // A third segment must wait for an answer
+        let blocked = tokio::time::timeout(Duration::from_millis(50), in_flight.reserve()).await;
+        assert!(blocked.is_err());
+
+        in_flight.answered(&result_for(2));
+        assert_eq!(in_flight.outstanding(), 1);
+        let permit = tokio::time::timeout(Duration::from_millis(50), in_flight.reserve())
+            .await
+            .expect("answer should free a slot");
+        in_flight.sent(3, Uuid::nil(), Instant::now(), permit);
+
+        // Errors free the oldest request; unknown hashes free nothing
+        in_flight.answered(&result_for(99));
+        assert_eq!(in_flight.outstanding(), 2);
+        in_flight.answered(&VoiceInferenceResponse::Error {
+            message: "boom".to_string(),
+            code: None,
+            trace_id: None,
+        });
+        assert_eq!(in_flight.outstanding(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_in_flight_expire_and_clear_free_slots() {
+        let in_flight = InFlightRequests::new(1);
+        let permit = in_flight.reserve().await;
+        in_flight.sent(1, Uuid::nil(), Instant::now(), permit);
+
+        in_flight.expire(Duration::from_secs(60));
+        assert_eq!(in_flight.outstanding(), 1);
+        in_flight.expire(Duration::ZERO);
+        assert_eq!(in_flight.outstanding(), 0);
+
+        let permit = in_flight.reserve().await;
+        in_flight.sent(2, Uuid::nil(), Instant::now(), permit);
+        in_flight.clear();
+        assert_eq!(in_flight.permits.available_permits(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_in_flight_tracks_requests_needing_speech() {
+        let in_flight = InFlightRequests::new(3);
+        for hash in [1, 2, 3] {
+            let permit = in_flight.reserve().await;
+            in_flight.sent(hash, Uuid::nil(), Instant::now(), permit);
+        }
+        in_flight.expect_speech(1);
+        in_flight.expect_speech(3);
+
+        assert!(!in_flight.answered(&result_for(2)));
+        assert!(in_flight.answered(&result_for(3)));
+        assert!(!in_flight.answered(&result_for(3)));
+
+        // An error answers the oldest request, which then needs no speech
+        let error = VoiceInferenceResponse::Error {
+            message: "failed".to_string(),
+            code: None,
+            trace_id: None,
+        };
+        assert!(!in_flight.answered(&error));
+        assert!(in_flight.needs_speech.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_in_flight_remembers_flush_time() {
+        let in_flight = InFlightRequests::new(2);
+        let flushed_at = Instant::now() - Duration::from_millis(800);
+        let permit = in_flight.reserve().await;
+        in_flight.sent(7, Uuid::nil(), flushed_at, permit);
+
+        assert_eq!(in_flight.flushed_at(&result_for(7)), Some(flushed_at));
+        assert!(in_flight.sent_at(&result_for(7)).unwrap() >= flushed_at);
+        assert_eq!(in_flight.flushed_at(&result_for(8)), None);
+        assert_eq!(in_flight.sent_at(&result_for(8)), None);
+        in_flight.answered(&result_for(7));
+        assert_eq!(in_flight.flushed_at(&result_for(7)), None);
+    }
+
+    #[test]
+    fn test_protocol_mismatch() {
+        assert!(protocol_mismatch(Some(PROTOCOL_VERSION)).is_none());
+
+        let err = protocol_mismatch(Some(PROTOCOL_VERSION + 1)).unwrap();
+        assert!(matches!(
+            err,
+            VoiceClientError::ProtocolMismatch { server: Some(_), .. }
+        ));
+
+        let err = protocol_mismatch(None).unwrap();
+        assert!(err.to_string().contains("unversioned"));
+    }
 }
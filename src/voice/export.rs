@@ -0,0 +1,234 @@
+//! Transcript export.
+//!
+//! Turns stored voice utterances into plain-text or SRT files for
+//! `/voice transcript export`.
+
+use crate::db::VoiceTranscriptEntry;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashSet;
+
+/// Silence longer than this separates two sessions in the same channel
+pub const SESSION_GAP: Duration = Duration::minutes(30);
+
+/// Longest a subtitle cue stays on screen
+const MAX_CUE: Duration = Duration::seconds(8);
+
+/// Output file format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum ExportFormat {
+    #[name = "Plain text"]
+    Txt,
+    #[name = "Subtitles (SRT)"]
+    Srt,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Txt => "txt",
+            Self::Srt => "srt",
+        }
+    }
+}
+
+/// The most recent run of entries with no silence longer than `gap`
+pub fn latest_session(entries: &[VoiceTranscriptEntry], gap: Duration) -> &[VoiceTranscriptEntry] {
+    let start = entries
+        .windows(2)
+        .rposition(|pair| pair[1].spoken_at - pair[0].spoken_at > gap)
+        .map_or(0, |i| i + 1);
+    &entries[start..]
+}
+
+/// User id stored in place of the real one when the guild anonymizes
+/// speakers: stable per guild, so transcript opt-outs still apply
+pub fn anonymous_user_id(guild_id: &str, user_id: &str) -> String {
+    let hash = blake3::hash(format!("{}:{}", guild_id, user_id).as_bytes());
+    format!("anon:{}", &hash.to_hex()[..16])
+}
+
+/// Drop entries from users who opted out of transcripts, whether they were
+/// stored under their own id or an anonymous one
+pub fn without_opted_out(
+    entries: &[VoiceTranscriptEntry],
+    opted_out: &HashSet<String>,
+) -> Vec<VoiceTranscriptEntry> {
+    entries
+        .iter()
+        .filter(|e| {
+            !opted_out.contains(&e.user_id)
+                && !opted_out
+                    .iter()
+                    .any(|user| anonymous_user_id(&e.guild_id, user) == e.user_id)
+        })
+        .cloned()
+        .collect()
+}
+
+/// Render entries in `format`
+pub fn render(entries: &[VoiceTranscriptEntry], format: ExportFormat) -> String {
+    match format {
+        ExportFormat::Txt => render_txt(entries),
+        ExportFormat::Srt => render_srt(entries),
+    }
+}
+
+/// One line per utterance: `[HH:MM:SS] name: original (→ translation)`
+pub fn render_txt(entries: &[VoiceTranscriptEntry]) -> String {
+    entries
+        .iter()
+        .map(|e| {
+            let mut line = format!(
+                "[{}] {}: {}",
+                e.spoken_at.format("%H:%M:%S"),
+                e.username,
+                e.original_text
+            );
+            if e.translated_text != e.original_text && !e.translated_text.is_empty() {
+                line.push_str(&format!(
+                    " (→ {}: {})",
+                    e.target_language, e.translated_text
+                ));
+            }
+            line.push('\n');
+            line
+        })
+        .collect()
+}
+
+/// SubRip subtitles timed from the first utterance. Each cue lasts until the
+/// next one starts, at most [`MAX_CUE`].
+pub fn render_srt(entries: &[VoiceTranscriptEntry]) -> String {
+    let Some(origin) = entries.first().map(|e| e.spoken_at) else {
+        return String::new();
+    };
+
+    let mut out = String::new();
+    for (i, entry) in entries.iter().enumerate() {
+        let start = entry.spoken_at - origin;
+        let end = entries
+            .get(i + 1)
+            .map(|next| next.spoken_at - origin)
+            .unwrap_or(start + MAX_CUE)
+            .min(start + MAX_CUE);
+        let text = if entry.translated_text.is_empty() {
+            &entry.original_text
+        } else {
+            &entry.translated_text
+        };
+        out.push_str(&format!(
+            "{}\n{} --> {}\n{}: {}\n\n",
+            i + 1,
+            srt_timestamp(start),
+            srt_timestamp(end),
+            entry.username,
+            text
+        ));
+    }
+    out
+}
+
+/// File name for an export of `voice_channel_id` starting at `start`
+pub fn file_name(voice_channel_id: &str, start: DateTime<Utc>, format: ExportFormat) -> String {
+    format!(
+        "transcript-{}-{}.{}",
+        voice_channel_id,
+        start.format("%Y%m%d-%H%M"),
+        format.extension()
+    )
+}
+
+fn srt_timestamp(offset: Duration) -> String {
+    let ms = offset.num_milliseconds().max(0);
+    format!(
+        "{:02}:{:02}:{:02},{:03}",
+        ms / 3_600_000,
+        ms / 60_000 % 60,
+        ms / 1000 % 60,
+        ms % 1000
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn entry(user: &str, secs: i64, text: &str) -> VoiceTranscriptEntry {
+        let at = Utc.with_ymd_and_hms(2026, 3, 1, 18, 0, 0).unwrap() + Duration::seconds(secs);
+        VoiceTranscriptEntry {
+            id: secs,
+            guild_id: "g1".to_string(),
+            voice_channel_id: "vc1".to_string(),
+            user_id: user.to_string(),
+            username: user.to_uppercase(),
+            original_text: text.to_string(),
+            translated_text: format!("{} (en)", text),
+            source_language: "es".to_string(),
+            target_language: "en".to_string(),
+            spoken_at: at,
+            created_at: at,
+        }
+    }
+
+    #[test]
+    fn test_latest_session_splits_on_long_gap() {
+        let entries = vec![
+            entry("a", 0, "ayer"),
+            entry("a", 3600, "hola"),
+            entry("b", 3610, "buenas"),
+        ];
+        let session = latest_session(&entries, SESSION_GAP);
+        assert_eq!(session.len(), 2);
+        assert_eq!(session[0].original_text, "hola");
+
+        assert_eq!(latest_session(&entries[1..], SESSION_GAP).len(), 2);
+        assert!(latest_session(&[], SESSION_GAP).is_empty());
+    }
+
+    #[test]
+    fn test_opted_out_users_are_left_out() {
+        let entries = vec![entry("a", 0, "hola"), entry("b", 5, "secreto")];
+        let opted_out = HashSet::from(["b".to_string()]);
+        let kept = without_opted_out(&entries, &opted_out);
+        assert_eq!(kept.len(), 1);
+        assert!(!render_txt(&kept).contains("secreto"));
+    }
+
+    #[test]
+    fn test_opted_out_users_are_left_out_when_anonymized() {
+        let entries = vec![
+            entry(&anonymous_user_id("g1", "a"), 0, "hola"),
+            entry(&anonymous_user_id("g1", "b"), 5, "secreto"),
+        ];
+        let opted_out = HashSet::from(["b".to_string()]);
+        let kept = without_opted_out(&entries, &opted_out);
+        assert_eq!(kept.len(), 1);
+        assert!(!render_txt(&kept).contains("secreto"));
+
+        // Stable within a guild, different across guilds
+        assert_eq!(anonymous_user_id("g1", "a"), anonymous_user_id("g1", "a"));
+        assert_ne!(anonymous_user_id("g1", "a"), anonymous_user_id("g2", "a"));
+        assert!(!anonymous_user_id("g1", "123456789").contains("123456789"));
+    }
+
+    #[test]
+    fn test_txt_lines() {
+        let txt = render_txt(&[entry("a", 0, "hola"), entry("b", 65, "adiós")]);
+        assert_eq!(
+            txt,
+            "[18:00:00] A: hola (→ en: hola (en))\n[18:01:05] B: adiós (→ en: adiós (en))\n"
+        );
+    }
+
+    #[test]
+    fn test_srt_cues_are_timed_from_first_utterance() {
+        let srt = render_srt(&[entry("a", 0, "hola"), entry("b", 3, "qué tal"), entry("a", 60, "adiós")]);
+        let cues: Vec<&str> = srt.split("\n\n").filter(|c| !c.is_empty()).collect();
+        assert_eq!(cues.len(), 3);
+        assert_eq!(cues[0], "1\n00:00:00,000 --> 00:00:03,000\nA: hola (en)");
+        // Long pauses don't keep the previous cue on screen
+        assert_eq!(cues[1], "2\n00:00:03,000 --> 00:00:11,000\nB: qué tal (en)");
+        assert_eq!(cues[2], "3\n00:01:00,000 --> 00:01:08,000\nA: adiós (en)");
+    }
+}
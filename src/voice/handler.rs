@@ -1,19 +1,42 @@
 //! Songbird event handler for Discord voice audio reception.
 
-use super::buffer::AudioBufferManager;
+use super::buffer::{AudioBufferManager, BufferConfig};
 use super::cache::VoiceTranscriptionCache;
 use super::client::VoiceInferenceClient;
-use super::types::{AudioPacket, AudioSegment, VoiceChannelState};
+use super::types::{AudioPacket, AudioSegment, Ssrc, VoiceChannelState, VoiceInferenceResponse};
+use crate::translation::Formality;
 use async_trait::async_trait;
+use dashmap::DashMap;
 use songbird::{
     events::context_data::VoiceTick,
     model::payload::{ClientDisconnect, Speaking},
     Event, EventContext, EventHandler,
 };
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 use tracing::{debug, info, warn};
 
+/// Looks up the name a speaker goes by in a guild, for transcripts.
+#[async_trait]
+pub trait SpeakerNames: Send + Sync {
+    /// Name to label `user_id`'s speech with, or None if they can't be found
+    async fn speaker_name(&self, guild_id: u64, user_id: u64) -> Option<String>;
+
+    /// Whether the guild wants speakers labelled "Speaker N" instead of by
+    /// name
+    async fn anonymize(&self, _guild_id: u64) -> bool {
+        false
+    }
+
+    /// Whether the guild excluded this speaker (or one of their roles) from
+    /// translation, so their audio is never buffered
+    async fn is_ignored(&self, _guild_id: u64, _user_id: u64) -> bool {
+        false
+    }
+}
+
 /// Voice receive handler for a single guild's voice connection.
 #[derive(Clone)]
 pub struct VoiceReceiveHandler {
@@ -29,6 +52,14 @@ pub struct VoiceReceiveHandler {
     state: Arc<RwLock<VoiceChannelState>>,
     /// Voice transcription cache (shared across guilds)
     cache: Arc<VoiceTranscriptionCache>,
+    /// Resolves speakers' names (placeholder names without one)
+    speaker_names: Option<Arc<dyn SpeakerNames>>,
+    /// Names already resolved this session, by user ID
+    resolved_names: Arc<DashMap<u64, String>>,
+    /// Pseudonyms handed out this session, when the guild anonymizes speakers
+    anonymous_speakers: Arc<AtomicUsize>,
+    /// When the bot joined the channel
+    started_at: Instant,
 }
 
 impl VoiceReceiveHandler {
@@ -50,7 +81,78 @@ impl VoiceReceiveHandler {
             inference_client,
             state: Arc::new(RwLock::new(state)),
             cache,
+            speaker_names: None,
+            resolved_names: Arc::new(DashMap::new()),
+            anonymous_speakers: Arc::new(AtomicUsize::new(0)),
+            started_at: Instant::now(),
+        }
+    }
+
+    /// Label speakers with names from `speaker_names`.
+    pub fn with_speaker_names(mut self, speaker_names: Arc<dyn SpeakerNames>) -> Self {
+        self.speaker_names = Some(speaker_names);
+        self
+    }
+
+    /// Look speakers' names up again the next time they talk (after the
+    /// guild changes how speakers are labelled).
+    pub fn forget_speaker_names(&self) {
+        self.resolved_names.clear();
+        self.anonymous_speakers.store(0, Ordering::Relaxed);
+    }
+
+    /// Name for `user_id`, resolved once per session.
+    async fn speaker_name(&self, user_id: u64) -> String {
+        if let Some(name) = self.resolved_names.get(&user_id) {
+            return name.clone();
         }
+        let resolved = match &self.speaker_names {
+            Some(names) if names.anonymize(self.guild_id).await => {
+                // Numbered under the entry lock so each user keeps one label
+                let label = self.resolved_names.entry(user_id).or_insert_with(|| {
+                    let n = self.anonymous_speakers.fetch_add(1, Ordering::Relaxed) + 1;
+                    format!("Speaker {}", n)
+                });
+                return label.clone();
+            }
+            Some(names) => names.speaker_name(self.guild_id, user_id).await,
+            None => None,
+        };
+        let name = resolved.unwrap_or_else(|| format!("User-{}", user_id));
+        self.resolved_names.insert(user_id, name.clone());
+        name
+    }
+
+    /// Map `ssrc` to the user speaking on it, unless the guild ignores them.
+    pub async fn register_speaker(&self, ssrc: Ssrc, user_id: u64) {
+        if let Some(names) = &self.speaker_names {
+            if names.is_ignored(self.guild_id, user_id).await {
+                // Packets on an unmapped SSRC are dropped; this also covers
+                // speakers added to the list mid-session
+                self.buffer_manager.unregister_speaker(ssrc).await;
+                debug!(ssrc, user_id, "Ignoring speaker");
+                return;
+            }
+        }
+        let username = self.speaker_name(user_id).await;
+        self.buffer_manager
+            .register_speaker(ssrc, user_id, username)
+            .await;
+    }
+
+    /// Segment audio with `config` instead of the default buffer settings.
+    pub fn with_buffer_config(mut self, config: BufferConfig) -> Self {
+        self.buffer_manager = Arc::new(AudioBufferManager::with_config(
+            self.guild_id,
+            self.channel_id,
+            config,
+        ));
+        self
+    }
+
+    /// Voice channel this handler listens to.
+    pub fn channel_id(&self) -> u64 {
+        self.channel_id
     }
 
     /// Get reference to the buffer manager.
@@ -70,15 +172,55 @@ impl VoiceReceiveHandler {
         state.tts_enabled = tts_enabled;
     }
 
+    /// Ask for formal or informal translations where the target language
+    /// has both.
+    pub async fn set_formality(&self, formality: Formality) {
+        self.state.write().await.formality = formality;
+    }
+
+    /// Ask the inference service for TTS audio with each segment, or stop.
+    pub async fn set_tts_enabled(&self, tts_enabled: bool) {
+        self.state.write().await.tts_enabled = tts_enabled;
+    }
+
+    /// Set the spoken language, or None to auto-detect it per segment.
+    pub async fn set_source_language(&self, source_language: Option<Arc<str>>) {
+        self.state.write().await.source_language = source_language;
+    }
+
+    /// How long the bot has been listening in this channel.
+    pub fn session_age(&self) -> Duration {
+        self.started_at.elapsed()
+    }
+
+    /// Send whatever speech is still buffered, e.g. before leaving.
+    pub async fn flush(&self) {
+        let segments = self.buffer_manager.flush_all().await;
+        if segments.is_empty() {
+            return;
+        }
+        let state = self.state.read().await;
+        let source_lang = state.source_language.clone();
+        let target_lang = Arc::clone(&state.target_language);
+        let tts_enabled = state.tts_enabled;
+        drop(state);
+
+        for segment in segments {
+            self.process_segment(segment, source_lang.clone(), Arc::clone(&target_lang), tts_enabled)
+                .await;
+        }
+    }
+
     /// Process audio segment: check cache first, send to inference if miss.
     async fn process_segment(
         &self,
         segment: AudioSegment,
+        source_lang: Option<Arc<str>>,
         target_lang: Arc<str>,
         tts_enabled: bool,
     ) {
         // Check cache first (hash audio samples)
-        let audio_hash = VoiceTranscriptionCache::hash_audio(&segment.samples);
+        let audio_hash = self.cache.hash(&segment.samples);
 
         if let Some(cached_response) = self.cache.get(audio_hash, &target_lang).await {
             // Cache hit! No need to call inference service
@@ -91,6 +233,11 @@ impl VoiceReceiveHandler {
 
             // Re-broadcast cached response to inference result channel
             // This allows the bridge to forward it to web clients and Discord threads
+            // Stamp it with this utterance's start, not the one that was cached
+            let mut cached_response = cached_response;
+            if let VoiceInferenceResponse::Result { started_at, .. } = &mut cached_response {
+                *started_at = Some(segment.started_at);
+            }
             if let Err(e) = self.inference_client.broadcast_cached_result(cached_response).await {
                 warn!(error = %e, "Failed to broadcast cached result");
             }
@@ -99,9 +246,17 @@ impl VoiceReceiveHandler {
         }
 
         // Cache miss - send to inference (pass audio_hash for response correlation)
+        let formality = self.state.read().await.formality;
         if let Err(e) = self
             .inference_client
-            .send_audio(segment, &target_lang, tts_enabled, audio_hash)
+            .send_audio(
+                segment,
+                source_lang.as_deref(),
+                &target_lang,
+                formality,
+                tts_enabled,
+                audio_hash,
+            )
             .await
         {
             warn!(error = %e, "Failed to send audio to inference");
@@ -125,8 +280,6 @@ impl EventHandler for VoiceReceiveHandler {
                 // Map SSRC to user ID when a user starts speaking
                 if let Some(user_id) = user_id {
                     let user_id_u64: u64 = user_id.0;
-                    // We don't have username here, will get from guild cache later
-                    let username = format!("User-{}", user_id_u64);
 
                     info!(
                         ssrc = ssrc,
@@ -135,9 +288,7 @@ impl EventHandler for VoiceReceiveHandler {
                         "Speaking state update"
                     );
 
-                    self.buffer_manager
-                        .register_speaker(*ssrc, user_id_u64, username)
-                        .await;
+                    self.register_speaker(*ssrc, user_id_u64).await;
                 }
             }
 
@@ -171,12 +322,14 @@ impl EventHandler for VoiceReceiveHandler {
                         if let Some(segment) = self.buffer_manager.push_audio(packet).await {
                             // Read config (Arc clone is cheap - just atomic increment)
                             let state = self.state.read().await;
+                            let source_lang = state.source_language.clone();
                             let target_lang = Arc::clone(&state.target_language);
                             let tts_enabled = state.tts_enabled;
                             // Lock released here automatically
 
                             // Process segment (checks cache, sends to inference if needed)
-                            self.process_segment(segment, target_lang, tts_enabled).await;
+                            self.process_segment(segment, source_lang, target_lang, tts_enabled)
+                                .await;
                         }
                     }
                 }
@@ -187,13 +340,20 @@ impl EventHandler for VoiceReceiveHandler {
                 if !segments.is_empty() {
                     // Read config once (Arc clone is cheap)
                     let state = self.state.read().await;
+                    let source_lang = state.source_language.clone();
                     let target_lang = Arc::clone(&state.target_language);
                     let tts_enabled = state.tts_enabled;
                     // Lock released here automatically
 
                     // Process all timeout segments (checks cache, sends to inference if needed)
                     for segment in segments {
-                        self.process_segment(segment, Arc::clone(&target_lang), tts_enabled).await;
+                        self.process_segment(
+                            segment,
+                            source_lang.clone(),
+                            Arc::clone(&target_lang),
+                            tts_enabled,
+                        )
+                        .await;
                     }
                 }
             }
@@ -239,6 +399,98 @@ mod tests {
         assert_eq!(handler.channel_id, 789012);
     }
 
+    struct FixedNames;
+
+    #[async_trait]
+    impl SpeakerNames for FixedNames {
+        async fn speaker_name(&self, _guild_id: u64, user_id: u64) -> Option<String> {
+            (user_id == 42).then(|| "Nickname".to_string())
+        }
+
+        async fn is_ignored(&self, _guild_id: u64, user_id: u64) -> bool {
+            user_id == 44
+        }
+    }
+
+    #[tokio::test]
+    async fn test_segments_carry_resolved_speaker_name() {
+        let client = Arc::new(VoiceInferenceClient::new(VoiceClientConfig::default()));
+        let cache = Arc::new(VoiceTranscriptionCache::new(100));
+        let handler =
+            VoiceReceiveHandler::new(1, 2, client, cache).with_speaker_names(Arc::new(FixedNames));
+
+        let loud: Vec<i16> = (0..960)
+            .map(|i| ((i as f32 * 0.1).sin() * 10000.0) as i16)
+            .collect();
+        let mut names = Vec::new();
+        for (ssrc, user_id) in [(7, 42), (8, 43)] {
+            handler.register_speaker(ssrc, user_id).await;
+            let packet = AudioPacket {
+                ssrc,
+                user_id: None,
+                username: None,
+                samples: loud.clone(),
+                timestamp: std::time::Instant::now(),
+                sequence: 0,
+            };
+            handler.buffer_manager.push_audio(packet).await;
+            let segment = handler.buffer_manager.unregister_speaker(ssrc).await.unwrap();
+            names.push(segment.username);
+        }
+
+        // Falls back to a placeholder for users the resolver can't find
+        assert_eq!(names, vec!["Nickname", "User-43"]);
+    }
+
+    #[tokio::test]
+    async fn test_ignored_speakers_are_not_buffered() {
+        let client = Arc::new(VoiceInferenceClient::new(VoiceClientConfig::default()));
+        let cache = Arc::new(VoiceTranscriptionCache::new(100));
+        let handler =
+            VoiceReceiveHandler::new(1, 2, client, cache).with_speaker_names(Arc::new(FixedNames));
+
+        handler.register_speaker(9, 44).await;
+        let packet = AudioPacket {
+            ssrc: 9,
+            user_id: None,
+            username: None,
+            samples: vec![20000; 960],
+            timestamp: std::time::Instant::now(),
+            sequence: 0,
+        };
+        assert!(handler.buffer_manager.push_audio(packet).await.is_none());
+        assert!(handler.buffer_manager.flush_all().await.is_empty());
+    }
+
+    struct AnonymousNames;
+
+    #[async_trait]
+    impl SpeakerNames for AnonymousNames {
+        async fn speaker_name(&self, _guild_id: u64, _user_id: u64) -> Option<String> {
+            Some("Nickname".to_string())
+        }
+
+        async fn anonymize(&self, _guild_id: u64) -> bool {
+            true
+        }
+    }
+
+    #[tokio::test]
+    async fn test_anonymized_speakers_get_stable_pseudonyms() {
+        let client = Arc::new(VoiceInferenceClient::new(VoiceClientConfig::default()));
+        let cache = Arc::new(VoiceTranscriptionCache::new(100));
+        let handler = VoiceReceiveHandler::new(1, 2, client, cache)
+            .with_speaker_names(Arc::new(AnonymousNames));
+
+        assert_eq!(handler.speaker_name(42).await, "Speaker 1");
+        assert_eq!(handler.speaker_name(43).await, "Speaker 2");
+        assert_eq!(handler.speaker_name(42).await, "Speaker 1");
+
+        // Numbering restarts once the guild changes how speakers are labelled
+        handler.forget_speaker_names();
+        assert_eq!(handler.speaker_name(43).await, "Speaker 1");
+    }
+
     #[tokio::test]
     async fn test_update_settings() {
         let config = VoiceClientConfig::default();
@@ -281,6 +533,8 @@ mod tests {
             samples: vec![100, 200, 300],
             start_time: now,
             end_time: now + std::time::Duration::from_millis(100),
+            started_at: chrono::Utc::now(),
+            overlap_samples: 0,
         };
 
         let audio_hash = VoiceTranscriptionCache::hash_audio(&segment.samples);
@@ -290,7 +544,7 @@ mod tests {
 
         // Process segment (will try to send to non-existent server, but won't panic)
         handler
-            .process_segment(segment, Arc::from("en"), false)
+            .process_segment(segment, None, Arc::from("en"), false)
             .await;
 
         // Verify cache still empty (response never came back)
@@ -328,6 +582,10 @@ mod tests {
             tts_audio: None,
             latency_ms: 100,
             audio_hash,
+            confidence: None,
+            started_at: None,
+            tts_format: None,
+            trace_id: None,
         };
 
         cache
@@ -347,11 +605,13 @@ mod tests {
             samples: samples.clone(),
             start_time: now,
             end_time: now + std::time::Duration::from_millis(100),
+            started_at: chrono::Utc::now(),
+            overlap_samples: 0,
         };
 
         // Process segment (should hit cache, not send to inference)
         handler
-            .process_segment(segment, Arc::clone(&target_lang), false)
+            .process_segment(segment, None, Arc::clone(&target_lang), false)
             .await;
 
         // Verify cache hit (one more from process_segment)
@@ -360,6 +620,53 @@ mod tests {
         assert_eq!(stats.misses, 0);
     }
 
+    #[tokio::test]
+    async fn test_process_segment_keys_cache_with_injected_hasher() {
+        let config = VoiceClientConfig::default();
+        let client = Arc::new(VoiceInferenceClient::new(config));
+        let cache = Arc::new(VoiceTranscriptionCache::with_hasher(100, |_: &[i16]| 42));
+
+        let handler = VoiceReceiveHandler::new(666, 777, client, cache.clone());
+        let target_lang: Arc<str> = Arc::from("es");
+        let cached_response = VoiceInferenceResponse::Result {
+            guild_id: "666".to_string(),
+            channel_id: "777".to_string(),
+            user_id: "888".to_string(),
+            username: "CachedUser".to_string(),
+            original_text: "hello".to_string(),
+            translated_text: "hola".to_string(),
+            source_language: "en".to_string(),
+            target_language: "es".to_string(),
+            tts_audio: None,
+            latency_ms: 100,
+            audio_hash: 42,
+            confidence: None,
+            started_at: None,
+            tts_format: None,
+            trace_id: None,
+        };
+        cache.put(42, Arc::clone(&target_lang), cached_response).await;
+
+        // Unrelated audio collides with the cached entry under the forced hash
+        let now = std::time::Instant::now();
+        let segment = AudioSegment {
+            user_id: 888,
+            username: "TestUser".to_string(),
+            guild_id: 666,
+            channel_id: 777,
+            samples: vec![-1, -2, -3],
+            start_time: now,
+            end_time: now + std::time::Duration::from_millis(100),
+            started_at: chrono::Utc::now(),
+            overlap_samples: 0,
+        };
+        handler.process_segment(segment, None, target_lang, false).await;
+
+        let stats = cache.stats();
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 0);
+    }
+
     #[tokio::test]
     async fn test_buffer_manager_access() {
         let config = VoiceClientConfig::default();
@@ -37,30 +37,45 @@ pub mod bridge;
 pub mod buffer;
 pub mod cache;
 pub mod client;
+pub mod export;
 pub mod handler;
 pub mod playback;
+pub mod tts_audio;
+pub mod tts_client;
 pub mod types;
 
-pub use bridge::{spawn_voice_bridge, spawn_voice_bridge_with_threads, VoiceBridge};
-pub use buffer::AudioBufferManager;
-pub use cache::{CachedTranslation, CacheStats, VoiceTranscriptionCache};
+pub use bridge::{spawn_voice_bridge, spawn_voice_bridge_with_threads, VoiceBridge, VoiceBridgeHandle};
+pub use buffer::{AudioBufferManager, BufferConfig, NoiseGate};
+pub use cache::{AudioHasher, CachedTranslation, CacheStats, VoiceTranscriptionCache};
 pub use client::{
     ConnectionState, QueueFullStrategy, VoiceClientConfig, VoiceClientError,
     VoiceInferenceClient,
 };
-pub use handler::VoiceReceiveHandler;
-pub use playback::{PlaybackManager, TTSPlaybackItem};
+pub use handler::{SpeakerNames, VoiceReceiveHandler};
+pub use playback::{PlaybackManager, PlaybackPolicy, TTSPlaybackItem};
+pub use tts_audio::{DecodedAudio, TtsFormat};
+pub use tts_client::TtsClient;
 pub use types::{
-    AudioPacket, AudioSegment, SpeakerInfo, TranscriptionResult, TranscriptionSegment,
-    VoiceChannelState, VoiceInferenceRequest, VoiceInferenceResponse, VoiceTranslationResult,
+    AudioPacket, AudioSegment, PipelineStage, SpeakerInfo, TranscriptionResult,
+    TranscriptionSegment, VoiceChannelState, VoiceInferenceRequest, VoiceInferenceResponse,
+    VoicePipeline, VoiceTranslationResult,
     DISCORD_SAMPLE_RATE, OPUS_FRAME_MS, SAMPLES_PER_FRAME,
 };
 
 use dashmap::DashMap;
-use songbird::Songbird;
-use std::sync::Arc;
+use playback::run_playback_loop;
+use songbird::{Call, Songbird};
+use std::sync::{Arc, Mutex, OnceLock};
+use tokio::task::JoinHandle;
 use tracing::info;
 
+/// The bot is already translating in as many guilds as it is allowed to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("Voice translation is at capacity ({max} servers at once). Please try again later.")]
+pub struct AtCapacity {
+    pub max: usize,
+}
+
 /// Voice translation manager for the entire bot.
 pub struct VoiceManager {
     /// Songbird voice manager
@@ -71,8 +86,20 @@ pub struct VoiceManager {
     handlers: DashMap<u64, Arc<VoiceReceiveHandler>>,
     /// Per-guild playback managers
     playback: DashMap<u64, Arc<PlaybackManager>>,
+    /// Per-guild playback loops; one at a time so rejoins don't race
+    playback_loops: DashMap<u64, JoinHandle<()>>,
     /// Voice transcription result cache (shared across all guilds)
     cache: Arc<VoiceTranscriptionCache>,
+    /// Segmentation settings for new handlers
+    buffer_config: BufferConfig,
+    /// How new playback managers handle a TTS backlog
+    playback_policy: PlaybackPolicy,
+    /// Most guilds translated at once (None for no limit)
+    max_guilds: Option<usize>,
+    /// Makes the capacity check and handler creation one step
+    create_lock: Mutex<()>,
+    /// Speaker name lookup for new handlers, set once the bot is connected
+    speaker_names: OnceLock<Arc<dyn SpeakerNames>>,
 }
 
 impl VoiceManager {
@@ -87,47 +114,140 @@ impl VoiceManager {
             inference_client,
             handlers: DashMap::new(),
             playback: DashMap::new(),
+            playback_loops: DashMap::new(),
             cache,
+            buffer_config: BufferConfig::default(),
+            playback_policy: PlaybackPolicy::default(),
+            max_guilds: None,
+            create_lock: Mutex::new(()),
+            speaker_names: OnceLock::new(),
         }
     }
 
+    /// Share `cache` instead of a private one, so it can be inspected and
+    /// cleared from outside the bot.
+    pub fn with_cache(mut self, cache: Arc<VoiceTranscriptionCache>) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Label speakers in handlers created from now on with names from
+    /// `speaker_names`. Only the first call has an effect.
+    pub fn set_speaker_names(&self, speaker_names: Arc<dyn SpeakerNames>) {
+        let _ = self.speaker_names.set(speaker_names);
+    }
+
+    /// Translate in at most `max` guilds at once (0 means no limit).
+    pub fn with_max_guilds(mut self, max: usize) -> Self {
+        self.max_guilds = (max > 0).then_some(max);
+        self
+    }
+
+    /// Most guilds translated at once, if limited.
+    pub fn max_guilds(&self) -> Option<usize> {
+        self.max_guilds
+    }
+
+    /// Number of guilds currently being translated.
+    pub fn active_guild_count(&self) -> usize {
+        self.handlers.len()
+    }
+
+    /// Whether `guild_id` may start voice translation. Guilds that already
+    /// have a handler always may.
+    pub fn check_capacity(&self, guild_id: u64) -> Result<(), AtCapacity> {
+        match self.max_guilds {
+            Some(max) if !self.handlers.contains_key(&guild_id) && self.handlers.len() >= max => {
+                Err(AtCapacity { max })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Use `config` for the audio buffers of handlers created from now on.
+    pub fn with_buffer_config(mut self, config: BufferConfig) -> Self {
+        self.buffer_config = config;
+        self
+    }
+
+    /// Play TTS with `policy` in guilds whose playback starts from now on.
+    pub fn with_playback_policy(mut self, policy: PlaybackPolicy) -> Self {
+        self.playback_policy = policy;
+        self
+    }
+
     /// Get the Songbird instance.
     pub fn songbird(&self) -> Arc<Songbird> {
         self.songbird.clone()
     }
 
-    /// Get or create handler for a guild/channel.
+    /// Get or create handler for a guild/channel, refusing new guilds once
+    /// `max_guilds` are active.
     pub fn get_or_create_handler(
         &self,
         guild_id: u64,
         channel_id: u64,
-    ) -> Arc<VoiceReceiveHandler> {
-        self.handlers
+    ) -> Result<Arc<VoiceReceiveHandler>, AtCapacity> {
+        let _creating = self.create_lock.lock().unwrap_or_else(|e| e.into_inner());
+        self.check_capacity(guild_id)?;
+
+        Ok(self
+            .handlers
             .entry(guild_id)
             .or_insert_with(|| {
                 info!(guild_id, channel_id, "Creating voice handler");
-                Arc::new(VoiceReceiveHandler::new(
+                let mut handler = VoiceReceiveHandler::new(
                     guild_id,
                     channel_id,
                     self.inference_client.clone(),
                     self.cache.clone(),
-                ))
+                )
+                .with_buffer_config(self.buffer_config.clone());
+                if let Some(names) = self.speaker_names.get() {
+                    handler = handler.with_speaker_names(names.clone());
+                }
+                Arc::new(handler)
             })
-            .clone()
+            .clone())
+    }
+
+    /// `(guild, channel)` of every handler the bot is listening with.
+    pub fn active_channels(&self) -> Vec<(u64, u64)> {
+        self.handlers
+            .iter()
+            .map(|entry| (*entry.key(), entry.value().channel_id()))
+            .collect()
+    }
+
+    /// Handler for a guild, if the bot is listening there.
+    pub fn handler(&self, guild_id: u64) -> Option<Arc<VoiceReceiveHandler>> {
+        self.handlers.get(&guild_id).map(|h| h.clone())
     }
 
     /// Remove handler for a guild (when leaving voice).
     pub fn remove_handler(&self, guild_id: u64) {
         self.handlers.remove(&guild_id);
         self.playback.remove(&guild_id);
+        if let Some((_, playback_loop)) = self.playback_loops.remove(&guild_id) {
+            playback_loop.abort();
+        }
         info!(guild_id, "Removed voice handler");
     }
 
+    /// Play TTS queued for `guild_id` through `call` until it leaves its
+    /// channel, replacing any loop already playing for the guild.
+    pub fn start_playback(&self, guild_id: u64, call: Arc<tokio::sync::Mutex<Call>>) {
+        let playback_loop = tokio::spawn(run_playback_loop(call, self.get_or_create_playback(guild_id)));
+        if let Some(previous) = self.playback_loops.insert(guild_id, playback_loop) {
+            previous.abort();
+        }
+    }
+
     /// Get playback manager for a guild.
     pub fn get_or_create_playback(&self, guild_id: u64) -> Arc<PlaybackManager> {
         self.playback
             .entry(guild_id)
-            .or_insert_with(|| Arc::new(PlaybackManager::new()))
+            .or_insert_with(|| Arc::new(PlaybackManager::new().with_policy(self.playback_policy)))
             .clone()
     }
 
@@ -143,6 +263,13 @@ impl VoiceManager {
         self.inference_client.clone()
     }
 
+    /// Subscribe to the inference service connection state.
+    pub fn subscribe_connection_state(
+        &self,
+    ) -> tokio::sync::broadcast::Receiver<ConnectionState> {
+        self.inference_client.subscribe_state()
+    }
+
     /// Subscribe to voice inference results.
     pub fn subscribe_results(
         &self,
@@ -160,6 +287,7 @@ impl std::fmt::Debug for VoiceManager {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("VoiceManager")
             .field("active_guilds", &self.handlers.len())
+            .field("max_guilds", &self.max_guilds)
             .finish()
     }
 }
@@ -194,11 +322,11 @@ mod tests {
         let channel_id = 789012;
 
         // First call should create handler
-        let handler1 = manager.get_or_create_handler(guild_id, channel_id);
+        let handler1 = manager.get_or_create_handler(guild_id, channel_id).unwrap();
         assert_eq!(manager.handlers.len(), 1);
 
         // Second call should return same handler
-        let handler2 = manager.get_or_create_handler(guild_id, channel_id);
+        let handler2 = manager.get_or_create_handler(guild_id, channel_id).unwrap();
         assert_eq!(manager.handlers.len(), 1);
 
         // Should be same Arc reference
@@ -215,7 +343,7 @@ mod tests {
         let channel_id = 333444;
 
         // Create handler
-        let _handler = manager.get_or_create_handler(guild_id, channel_id);
+        let _handler = manager.get_or_create_handler(guild_id, channel_id).unwrap();
         assert_eq!(manager.handlers.len(), 1);
 
         // Remove handler
@@ -223,6 +351,36 @@ mod tests {
         assert_eq!(manager.handlers.len(), 0);
     }
 
+    #[tokio::test]
+    async fn test_voice_manager_max_guilds() {
+        let manager = VoiceManager::new(Songbird::serenity(), VoiceClientConfig::default())
+            .with_max_guilds(2);
+
+        manager.get_or_create_handler(1, 10).unwrap();
+        manager.get_or_create_handler(2, 20).unwrap();
+        assert_eq!(manager.active_guild_count(), 2);
+
+        // A third guild is refused, but guilds already in voice are not
+        assert_eq!(manager.check_capacity(3), Err(AtCapacity { max: 2 }));
+        assert!(manager.get_or_create_handler(3, 30).is_err());
+        assert!(manager.get_or_create_handler(1, 10).is_ok());
+        assert_eq!(manager.active_guild_count(), 2);
+
+        // Leaving frees a slot
+        manager.remove_handler(2);
+        assert!(manager.get_or_create_handler(3, 30).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_voice_manager_unlimited_by_default() {
+        let manager = VoiceManager::new(Songbird::serenity(), VoiceClientConfig::default())
+            .with_max_guilds(0);
+        for guild in 0..50 {
+            manager.get_or_create_handler(guild, guild).unwrap();
+        }
+        assert_eq!(manager.max_guilds(), None);
+    }
+
     #[tokio::test]
     async fn test_voice_manager_get_or_create_playback() {
         let songbird = Songbird::serenity();
@@ -1,23 +1,57 @@
 //! TTS audio playback to Discord voice channel.
 
+use super::tts_audio;
 use super::types::VoiceInferenceResponse;
-use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
 use songbird::{
     input::{Input, RawAdapter},
     tracks::TrackHandle,
     Call,
 };
+use std::collections::VecDeque;
 use std::io::Cursor;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use tokio::sync::{broadcast, RwLock};
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::time::Instant;
 use tracing::{debug, error, info, warn};
 
+/// Most TTS clips waiting per guild; when speech arrives faster than it can
+/// be played, the oldest waiting clip is dropped
+pub const MAX_TTS_QUEUE: usize = 32;
+
+/// How queued TTS is played when it arrives faster than it can be spoken.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum PlaybackPolicy {
+    /// Play every item in order, however far behind that gets
+    #[default]
+    Sequential,
+    /// Skip items that waited longer than `max_age`
+    DropStale { max_age: Duration },
+    /// Mix everything queued into one clip, each scaled by `gain`
+    Overlap { gain: f32 },
+}
+
+impl PlaybackPolicy {
+    /// Policy for a `voice.tts_playback` name (sequential, drop_stale or
+    /// overlap), or None if unknown
+    pub fn parse(s: &str, max_age: Duration, gain: f32) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "sequential" => Some(Self::Sequential),
+            "drop_stale" => Some(Self::DropStale { max_age }),
+            "overlap" => Some(Self::Overlap { gain: gain.clamp(0.0, 1.0) }),
+            _ => None,
+        }
+    }
+}
+
 /// Playback manager for TTS audio.
 pub struct PlaybackManager {
-    /// Queue of pending TTS audio to play
-    queue: Arc<RwLock<Vec<TTSPlaybackItem>>>,
+    /// Queue of pending TTS audio to play, with when each was queued
+    queue: Arc<RwLock<VecDeque<(Instant, TTSPlaybackItem)>>>,
+    policy: PlaybackPolicy,
     /// Whether playback is currently active
-    playing: Arc<RwLock<bool>>,
+    playing: AtomicBool,
     /// Current track handle if playing
     _current_track: Arc<RwLock<Option<TrackHandle>>>,
 }
@@ -41,37 +75,71 @@ impl PlaybackManager {
     /// Create a new playback manager.
     pub fn new() -> Self {
         Self {
-            queue: Arc::new(RwLock::new(Vec::new())),
-            playing: Arc::new(RwLock::new(false)),
+            queue: Arc::new(RwLock::new(VecDeque::new())),
+            policy: PlaybackPolicy::default(),
+            playing: AtomicBool::new(false),
             _current_track: Arc::new(RwLock::new(None)),
         }
     }
 
-    /// Queue TTS audio for playback.
+    /// Play queued items according to `policy`.
+    pub fn with_policy(mut self, policy: PlaybackPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Queue TTS audio for playback, dropping the oldest waiting item once
+    /// [`MAX_TTS_QUEUE`] are queued.
     pub async fn queue_tts(&self, item: TTSPlaybackItem) {
         let mut queue = self.queue.write().await;
-        queue.push(item);
+        if queue.len() >= MAX_TTS_QUEUE {
+            if let Some((_, dropped)) = queue.pop_front() {
+                debug!(user = dropped.username, "TTS queue full, dropped the oldest item");
+            }
+        }
+        queue.push_back((Instant::now(), item));
         debug!(queue_len = queue.len(), "Queued TTS for playback");
     }
 
-    /// Get the next item from the queue.
+    /// Get the next clip to play under the manager's policy: the oldest
+    /// item, the oldest one still fresh enough, or everything queued mixed
+    /// together.
     pub async fn next(&self) -> Option<TTSPlaybackItem> {
         let mut queue = self.queue.write().await;
-        if queue.is_empty() {
-            None
-        } else {
-            Some(queue.remove(0))
+        match self.policy {
+            PlaybackPolicy::Sequential => queue.pop_front().map(|(_, item)| item),
+            PlaybackPolicy::DropStale { max_age } => {
+                let before = queue.len();
+                queue.retain(|(queued_at, _)| queued_at.elapsed() <= max_age);
+                let dropped = before - queue.len();
+                if dropped > 0 {
+                    debug!(dropped, "Skipped stale TTS items");
+                }
+                queue.pop_front().map(|(_, item)| item)
+            }
+            PlaybackPolicy::Overlap { gain } => {
+                let items: Vec<_> = queue.drain(..).map(|(_, item)| item).collect();
+                match items.len() {
+                    0 => None,
+                    1 => items.into_iter().next(),
+                    _ => Some(mix_items(&items, gain)),
+                }
+            }
         }
     }
 
     /// Check if currently playing.
     pub async fn is_playing(&self) -> bool {
-        *self.playing.read().await
+        self.playing.load(Ordering::Acquire)
     }
 
-    /// Set playing state.
-    pub async fn set_playing(&self, playing: bool) {
-        *self.playing.write().await = playing;
+    /// Mark playback as started, unless it already is. Playback counts as
+    /// active until the returned guard is dropped.
+    pub fn try_start_playing(&self) -> Option<PlayingGuard<'_>> {
+        self.playing
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .ok()
+            .map(|_| PlayingGuard(&self.playing))
     }
 
     /// Get queue length.
@@ -85,13 +153,62 @@ impl PlaybackManager {
     }
 }
 
+/// Playback in progress; ends when dropped, even if the playing task is
+/// aborted mid-clip.
+#[must_use]
+pub struct PlayingGuard<'a>(&'a AtomicBool);
+
+impl Drop for PlayingGuard<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Release);
+    }
+}
+
 impl Default for PlaybackManager {
     fn default() -> Self {
         Self::new()
     }
 }
 
-/// Parse TTS audio from inference response.
+/// Mix `items` into one 48kHz clip as long as the longest, each scaled by
+/// `gain` so overlapping speech doesn't clip.
+fn mix_items(items: &[TTSPlaybackItem], gain: f32) -> TTSPlaybackItem {
+    const MIX_SAMPLE_RATE: u32 = 48000;
+
+    let tracks: Vec<Vec<i16>> = items
+        .iter()
+        .map(|item| resample_audio(&item.audio, item.sample_rate, MIX_SAMPLE_RATE))
+        .collect();
+    let len = tracks.iter().map(Vec::len).max().unwrap_or(0);
+    let mut mixed = vec![0f32; len];
+    for track in &tracks {
+        for (out, &sample) in mixed.iter_mut().zip(track) {
+            *out += sample as f32 * gain;
+        }
+    }
+
+    TTSPlaybackItem {
+        user_id: items[0].user_id,
+        username: items
+            .iter()
+            .map(|item| item.username.as_str())
+            .collect::<Vec<_>>()
+            .join(", "),
+        text: items
+            .iter()
+            .map(|item| item.text.as_str())
+            .collect::<Vec<_>>()
+            .join(" / "),
+        audio: mixed
+            .into_iter()
+            .map(|sample| sample.clamp(i16::MIN as f32, i16::MAX as f32) as i16)
+            .collect(),
+        sample_rate: MIX_SAMPLE_RATE,
+    }
+}
+
+/// Parse TTS audio from inference response, decoding whichever format the
+/// service produced.
 pub fn parse_tts_audio(response: &VoiceInferenceResponse) -> Option<TTSPlaybackItem> {
     match response {
         VoiceInferenceResponse::Result {
@@ -99,20 +216,20 @@ pub fn parse_tts_audio(response: &VoiceInferenceResponse) -> Option<TTSPlaybackI
             username,
             translated_text,
             tts_audio,
+            tts_format,
             ..
         } => {
             let audio_base64 = tts_audio.as_ref()?;
 
-            // Decode base64 audio
-            let audio_bytes = BASE64.decode(audio_base64).ok()?;
-
-            // Convert bytes to i16 samples (assuming little-endian PCM)
-            let samples: Vec<i16> = audio_bytes
-                .chunks_exact(2)
-                .map(|chunk| i16::from_le_bytes([chunk[0], chunk[1]]))
-                .collect();
+            let decoded = match tts_audio::decode_base64(audio_base64, *tts_format) {
+                Ok(decoded) => decoded,
+                Err(e) => {
+                    warn!(error = %e, format = ?tts_format, "Failed to decode TTS audio");
+                    return None;
+                }
+            };
 
-            if samples.is_empty() {
+            if decoded.samples.is_empty() {
                 return None;
             }
 
@@ -120,8 +237,8 @@ pub fn parse_tts_audio(response: &VoiceInferenceResponse) -> Option<TTSPlaybackI
                 user_id: user_id.parse().unwrap_or(0),
                 username: username.clone(),
                 text: translated_text.clone(),
-                audio: samples,
-                sample_rate: 24000, // CosyVoice typically outputs 24kHz
+                audio: decoded.samples,
+                sample_rate: decoded.sample_rate,
             })
         }
         _ => None,
@@ -129,44 +246,42 @@ pub fn parse_tts_audio(response: &VoiceInferenceResponse) -> Option<TTSPlaybackI
 }
 
 /// Start the TTS playback loop for a voice channel.
+///
+/// Plays items queued on `playback_manager` (the voice bridge enqueues them
+/// for channels whose TTS mode includes voice) until the call leaves its channel.
 pub async fn run_playback_loop(
     call: Arc<tokio::sync::Mutex<Call>>,
     playback_manager: Arc<PlaybackManager>,
-    mut result_rx: broadcast::Receiver<VoiceInferenceResponse>,
 ) {
     info!("Starting TTS playback loop");
 
     loop {
-        tokio::select! {
-            Ok(response) = result_rx.recv() => {
-                if let Some(item) = parse_tts_audio(&response) {
-                    debug!(
-                        user = item.username,
-                        text_len = item.text.len(),
-                        audio_samples = item.audio.len(),
-                        "Received TTS audio"
-                    );
-                    playback_manager.queue_tts(item).await;
-                }
-            }
-
-            _ = tokio::time::sleep(std::time::Duration::from_millis(100)) => {
-                // Check if we should start playing
-                if !playback_manager.is_playing().await {
-                    if let Some(item) = playback_manager.next().await {
-                        playback_manager.set_playing(true).await;
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
 
-                        // Play the TTS audio
-                        if let Err(e) = play_tts_audio(&call, &item).await {
-                            error!(error = %e, "Failed to play TTS audio");
-                        }
+        if call.lock().await.current_channel().is_none() {
+            break;
+        }
 
-                        playback_manager.set_playing(false).await;
-                    }
+        // Check if we should start playing
+        if let Some(_playing) = playback_manager.try_start_playing() {
+            if let Some(item) = playback_manager.next().await {
+                debug!(
+                    user = item.username,
+                    text_len = item.text.len(),
+                    audio_samples = item.audio.len(),
+                    "Playing queued TTS audio"
+                );
+
+                // Play the TTS audio
+                if let Err(e) = play_tts_audio(&call, &item).await {
+                    error!(error = %e, "Failed to play TTS audio");
                 }
             }
         }
     }
+
+    playback_manager.clear().await;
+    info!("TTS playback loop stopped");
 }
 
 /// Play TTS audio through the voice connection.
@@ -408,6 +523,124 @@ mod tests {
         assert_eq!(manager.queue_len().await, 0);
     }
 
+    fn item(text: &str, audio: Vec<i16>, sample_rate: u32) -> TTSPlaybackItem {
+        TTSPlaybackItem {
+            user_id: 1,
+            username: format!("{}-speaker", text),
+            text: text.to_string(),
+            audio,
+            sample_rate,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_full_queue_drops_oldest() {
+        let manager = PlaybackManager::new();
+        for i in 0..MAX_TTS_QUEUE + 2 {
+            manager.queue_tts(item(&format!("clip {}", i), vec![1; 10], 48000)).await;
+        }
+
+        assert_eq!(manager.queue_len().await, MAX_TTS_QUEUE);
+        assert_eq!(manager.next().await.unwrap().text, "clip 2");
+    }
+
+    #[tokio::test]
+    async fn test_only_one_playback_at_a_time() {
+        let manager = PlaybackManager::new();
+        let playing = manager.try_start_playing().expect("idle manager starts");
+        assert!(manager.is_playing().await);
+        assert!(manager.try_start_playing().is_none());
+
+        drop(playing);
+        assert!(!manager.is_playing().await);
+        assert!(manager.try_start_playing().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_drop_stale_skips_old_items() {
+        let manager = PlaybackManager::new().with_policy(PlaybackPolicy::DropStale {
+            max_age: Duration::from_millis(50),
+        });
+        manager.queue_tts(item("old", vec![1; 10], 48000)).await;
+        manager.queue_tts(item("older", vec![1; 10], 48000)).await;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        manager.queue_tts(item("fresh", vec![1; 10], 48000)).await;
+
+        // Playback fell behind: the two old items are skipped
+        assert_eq!(manager.next().await.unwrap().text, "fresh");
+        assert!(manager.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_sequential_keeps_old_items() {
+        let manager = PlaybackManager::new();
+        manager.queue_tts(item("old", vec![1; 10], 48000)).await;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        manager.queue_tts(item("fresh", vec![1; 10], 48000)).await;
+
+        assert_eq!(manager.next().await.unwrap().text, "old");
+        assert_eq!(manager.next().await.unwrap().text, "fresh");
+    }
+
+    #[tokio::test]
+    async fn test_overlap_mixes_queued_items() {
+        let manager = PlaybackManager::new().with_policy(PlaybackPolicy::Overlap { gain: 0.5 });
+        manager.queue_tts(item("a", vec![1000; 4], 48000)).await;
+        manager.queue_tts(item("b", vec![30000; 2], 24000)).await;
+
+        let mixed = manager.next().await.unwrap();
+        assert_eq!(mixed.text, "a / b");
+        assert_eq!(mixed.sample_rate, 48000);
+        // b is resampled to 4 samples; both are halved and summed
+        assert_eq!(mixed.audio.len(), 4);
+        assert_eq!(mixed.audio[0], 500 + 15000);
+        assert_eq!(manager.queue_len().await, 0);
+    }
+
+    #[test]
+    fn test_playback_policy_parse() {
+        let age = Duration::from_secs(10);
+        assert_eq!(PlaybackPolicy::parse("Sequential", age, 0.5), Some(PlaybackPolicy::Sequential));
+        assert_eq!(
+            PlaybackPolicy::parse("drop_stale", age, 0.5),
+            Some(PlaybackPolicy::DropStale { max_age: age })
+        );
+        assert_eq!(
+            PlaybackPolicy::parse("overlap", age, 3.0),
+            Some(PlaybackPolicy::Overlap { gain: 1.0 })
+        );
+        assert_eq!(PlaybackPolicy::parse("shuffle", age, 0.5), None);
+    }
+
+    #[test]
+    fn test_parse_tts_audio_uses_wav_sample_rate() {
+        use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+
+        let wav = tts_audio::encode_wav(&[10, -10, 20, -20], 22050);
+        let response = VoiceInferenceResponse::Result {
+            guild_id: "1".to_string(),
+            channel_id: "2".to_string(),
+            user_id: "3".to_string(),
+            username: "Speaker".to_string(),
+            original_text: "hello".to_string(),
+            translated_text: "hola".to_string(),
+            source_language: "en".to_string(),
+            target_language: "es".to_string(),
+            tts_audio: Some(BASE64.encode(wav)),
+            latency_ms: 10,
+            audio_hash: 1,
+            confidence: None,
+            started_at: None,
+            tts_format: None,
+            trace_id: None,
+        };
+
+        let item = parse_tts_audio(&response).unwrap();
+        // The WAV header isn't played as samples
+        assert_eq!(item.audio, vec![10, -10, 20, -20]);
+        assert_eq!(item.sample_rate, 22050);
+    }
+
     #[test]
     fn test_resample_audio() {
         // Test 2x upsampling (24kHz -> 48kHz)
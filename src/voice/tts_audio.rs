@@ -0,0 +1,445 @@
+//! TTS audio formats and decoding.
+//!
+//! The bot asks the inference service for a TTS encoding (`voice.tts_format`)
+//! and the service reports the one it actually produced in each result.
+//! Songbird needs raw PCM, so whatever arrives is decoded here into mono
+//! 16-bit samples; the web view plays the encoded audio directly.
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use serde::{Deserialize, Serialize};
+use std::io::Cursor;
+
+/// Sample rate assumed for headerless PCM (CosyVoice's native rate)
+pub const DEFAULT_PCM_SAMPLE_RATE: u32 = 24000;
+
+/// Opus always decodes at 48 kHz
+const OPUS_SAMPLE_RATE: u32 = 48000;
+
+/// Longest Opus frame is 120 ms; 48 kHz stereo needs this many samples
+const MAX_OPUS_FRAME_SAMPLES: usize = 5760 * 2;
+
+/// Encoding of TTS audio in a voice result
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TtsFormat {
+    /// 16-bit PCM WAV
+    #[default]
+    Wav,
+    /// Headerless 16-bit little-endian mono PCM at 24 kHz
+    Pcm,
+    /// Opus in an Ogg container
+    Opus,
+    Mp3,
+}
+
+impl TtsFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Wav => "wav",
+            Self::Pcm => "pcm",
+            Self::Opus => "opus",
+            Self::Mp3 => "mp3",
+        }
+    }
+
+    /// MIME type for a browser `data:` URI
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            Self::Wav | Self::Pcm => "audio/wav",
+            Self::Opus => "audio/ogg",
+            Self::Mp3 => "audio/mpeg",
+        }
+    }
+
+    /// Parse a format name, or None if unknown
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "wav" | "wave" => Some(Self::Wav),
+            "pcm" | "raw" => Some(Self::Pcm),
+            "opus" | "ogg" => Some(Self::Opus),
+            "mp3" | "mpeg" => Some(Self::Mp3),
+            _ => None,
+        }
+    }
+
+    /// Guess the format of untagged audio from its leading bytes (results
+    /// from services that predate `tts_format`)
+    pub fn sniff(bytes: &[u8]) -> Self {
+        if bytes.starts_with(b"RIFF") {
+            Self::Wav
+        } else if bytes.starts_with(b"OggS") {
+            Self::Opus
+        } else if bytes.starts_with(b"ID3") || (bytes.len() > 1 && bytes[0] == 0xFF && bytes[1] & 0xE0 == 0xE0) {
+            Self::Mp3
+        } else {
+            Self::Pcm
+        }
+    }
+
+    /// Format of a result's audio: the declared one, else sniffed
+    pub fn resolve(declared: Option<Self>, bytes: &[u8]) -> Self {
+        declared.unwrap_or_else(|| Self::sniff(bytes))
+    }
+}
+
+/// Deserialize an optional format, treating names this build doesn't know
+/// as absent so the audio is sniffed instead of the message being rejected
+pub(crate) fn deserialize_lenient<'de, D>(deserializer: D) -> Result<Option<TtsFormat>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let name = Option::<String>::deserialize(deserializer)?;
+    Ok(name.as_deref().and_then(TtsFormat::parse))
+}
+
+impl std::fmt::Display for TtsFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Decoded mono audio
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedAudio {
+    pub samples: Vec<i16>,
+    pub sample_rate: u32,
+}
+
+/// TTS audio decoding errors
+#[derive(Debug, thiserror::Error)]
+pub enum TtsDecodeError {
+    #[error("Invalid base64 audio")]
+    Base64,
+
+    #[error("Invalid WAV audio: {0}")]
+    Wav(&'static str),
+
+    #[error("Invalid Ogg Opus audio: {0}")]
+    Opus(String),
+
+    #[error("Invalid MP3 audio: {0}")]
+    Mp3(String),
+}
+
+/// Decode base64 TTS audio of the given (or sniffed) format
+pub fn decode_base64(data: &str, declared: Option<TtsFormat>) -> Result<DecodedAudio, TtsDecodeError> {
+    let bytes = BASE64.decode(data).map_err(|_| TtsDecodeError::Base64)?;
+    decode(TtsFormat::resolve(declared, &bytes), &bytes)
+}
+
+/// Decode audio bytes into mono 16-bit samples
+pub fn decode(format: TtsFormat, bytes: &[u8]) -> Result<DecodedAudio, TtsDecodeError> {
+    match format {
+        TtsFormat::Wav => decode_wav(bytes),
+        TtsFormat::Pcm => Ok(DecodedAudio {
+            samples: pcm_samples(bytes),
+            sample_rate: DEFAULT_PCM_SAMPLE_RATE,
+        }),
+        TtsFormat::Opus => decode_ogg_opus(bytes),
+        TtsFormat::Mp3 => decode_mp3(bytes),
+    }
+}
+
+/// Wrap mono 16-bit samples in a WAV container
+pub fn encode_wav(samples: &[i16], sample_rate: u32) -> Vec<u8> {
+    let data_len = (samples.len() * 2) as u32;
+    let mut wav = Vec::with_capacity(44 + data_len as usize);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    wav.extend_from_slice(&1u16.to_le_bytes()); // mono
+    wav.extend_from_slice(&sample_rate.to_le_bytes());
+    wav.extend_from_slice(&(sample_rate * 2).to_le_bytes());
+    wav.extend_from_slice(&2u16.to_le_bytes());
+    wav.extend_from_slice(&16u16.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+        wav.extend_from_slice(&sample.to_le_bytes());
+    }
+    wav
+}
+
+fn pcm_samples(bytes: &[u8]) -> Vec<i16> {
+    bytes
+        .chunks_exact(2)
+        .map(|chunk| i16::from_le_bytes([chunk[0], chunk[1]]))
+        .collect()
+}
+
+/// Average interleaved channels down to mono
+fn downmix(interleaved: &[i16], channels: usize) -> Vec<i16> {
+    if channels <= 1 {
+        return interleaved.to_vec();
+    }
+    interleaved
+        .chunks_exact(channels)
+        .map(|frame| (frame.iter().map(|&s| s as i32).sum::<i32>() / channels as i32) as i16)
+        .collect()
+}
+
+fn decode_wav(bytes: &[u8]) -> Result<DecodedAudio, TtsDecodeError> {
+    if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return Err(TtsDecodeError::Wav("missing RIFF/WAVE header"));
+    }
+
+    let mut format = None;
+    let mut pos = 12;
+    while pos + 8 <= bytes.len() {
+        let id = &bytes[pos..pos + 4];
+        let len = u32::from_le_bytes([bytes[pos + 4], bytes[pos + 5], bytes[pos + 6], bytes[pos + 7]]) as usize;
+        let body = &bytes[pos + 8..(pos + 8 + len).min(bytes.len())];
+
+        match id {
+            b"fmt " if body.len() >= 16 => {
+                let audio_format = u16::from_le_bytes([body[0], body[1]]);
+                let channels = u16::from_le_bytes([body[2], body[3]]);
+                let sample_rate = u32::from_le_bytes([body[4], body[5], body[6], body[7]]);
+                let bits = u16::from_le_bytes([body[14], body[15]]);
+                // 0xFFFE (extensible) carries plain PCM for 16-bit output
+                if !(audio_format == 1 || audio_format == 0xFFFE) || bits != 16 {
+                    return Err(TtsDecodeError::Wav("only 16-bit PCM is supported"));
+                }
+                if channels == 0 {
+                    return Err(TtsDecodeError::Wav("no channels"));
+                }
+                format = Some((channels as usize, sample_rate));
+            }
+            b"data" => {
+                let (channels, sample_rate) = format.ok_or(TtsDecodeError::Wav("data before fmt chunk"))?;
+                return Ok(DecodedAudio {
+                    samples: downmix(&pcm_samples(body), channels),
+                    sample_rate,
+                });
+            }
+            _ => {}
+        }
+        // Chunks are padded to even lengths
+        pos += 8 + len + (len & 1);
+    }
+
+    Err(TtsDecodeError::Wav("no data chunk"))
+}
+
+fn decode_ogg_opus(bytes: &[u8]) -> Result<DecodedAudio, TtsDecodeError> {
+    use audiopus::{coder::Decoder, Channels, SampleRate};
+
+    let opus_err = |e: &dyn std::fmt::Display| TtsDecodeError::Opus(e.to_string());
+    let mut reader = ogg::PacketReader::new(Cursor::new(bytes));
+
+    // OpusHead: magic, version, channel count, pre-skip
+    let head = reader
+        .read_packet()
+        .map_err(|e| opus_err(&e))?
+        .ok_or_else(|| TtsDecodeError::Opus("empty stream".to_string()))?;
+    if head.data.len() < 19 || !head.data.starts_with(b"OpusHead") {
+        return Err(TtsDecodeError::Opus("missing OpusHead".to_string()));
+    }
+    let channel_count = head.data[9] as usize;
+    let pre_skip = u16::from_le_bytes([head.data[10], head.data[11]]) as usize;
+    let channels = match channel_count {
+        1 => Channels::Mono,
+        2 => Channels::Stereo,
+        n => return Err(TtsDecodeError::Opus(format!("unsupported channel count {}", n))),
+    };
+
+    let mut decoder = Decoder::new(SampleRate::Hz48000, channels).map_err(|e| opus_err(&e))?;
+    let mut frame = vec![0i16; MAX_OPUS_FRAME_SAMPLES];
+    let mut interleaved = Vec::new();
+
+    while let Some(packet) = reader.read_packet().map_err(|e| opus_err(&e))? {
+        // OpusTags carries metadata only
+        if packet.data.starts_with(b"OpusTags") {
+            continue;
+        }
+        let per_channel = decoder
+            .decode(Some(&packet.data[..]), &mut frame[..], false)
+            .map_err(|e| opus_err(&e))?;
+        interleaved.extend_from_slice(&frame[..per_channel * channel_count]);
+    }
+
+    let mut samples = downmix(&interleaved, channel_count);
+    samples.drain(..pre_skip.min(samples.len()));
+    Ok(DecodedAudio {
+        samples,
+        sample_rate: OPUS_SAMPLE_RATE,
+    })
+}
+
+fn decode_mp3(bytes: &[u8]) -> Result<DecodedAudio, TtsDecodeError> {
+    use symphonia::core::audio::SampleBuffer;
+    use symphonia::core::codecs::DecoderOptions;
+    use symphonia::core::errors::Error as SymphoniaError;
+    use symphonia::core::formats::FormatOptions;
+    use symphonia::core::io::MediaSourceStream;
+    use symphonia::core::meta::MetadataOptions;
+    use symphonia::core::probe::Hint;
+
+    let mp3_err = |e: SymphoniaError| TtsDecodeError::Mp3(e.to_string());
+    let source = MediaSourceStream::new(Box::new(Cursor::new(bytes.to_vec())), Default::default());
+    let mut hint = Hint::new();
+    hint.with_extension("mp3");
+
+    let mut format = symphonia::default::get_probe()
+        .format(&hint, source, &FormatOptions::default(), &MetadataOptions::default())
+        .map_err(mp3_err)?
+        .format;
+    let track = format
+        .default_track()
+        .ok_or_else(|| TtsDecodeError::Mp3("no audio track".to_string()))?;
+    let track_id = track.id;
+    let mut sample_rate = track.codec_params.sample_rate.unwrap_or(DEFAULT_PCM_SAMPLE_RATE);
+    let mut decoder = symphonia::default::get_codecs()
+        .make(&track.codec_params, &DecoderOptions::default())
+        .map_err(mp3_err)?;
+
+    let mut samples = Vec::new();
+    loop {
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            Err(SymphoniaError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(mp3_err(e)),
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+        let decoded = match decoder.decode(&packet) {
+            Ok(decoded) => decoded,
+            // A corrupt frame shouldn't lose the whole clip
+            Err(SymphoniaError::DecodeError(_)) => continue,
+            Err(e) => return Err(mp3_err(e)),
+        };
+        let spec = *decoded.spec();
+        sample_rate = spec.rate;
+        let mut buffer = SampleBuffer::<i16>::new(decoded.capacity() as u64, spec);
+        buffer.copy_interleaved_ref(decoded);
+        samples.extend(downmix(buffer.samples(), spec.channels.count()));
+    }
+
+    Ok(DecodedAudio {
+        samples,
+        sample_rate,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(len: usize) -> Vec<i16> {
+        (0..len).map(|i| ((i as f32 / 10.0).sin() * 8000.0) as i16).collect()
+    }
+
+    #[test]
+    fn test_format_names() {
+        for format in [TtsFormat::Wav, TtsFormat::Pcm, TtsFormat::Opus, TtsFormat::Mp3] {
+            assert_eq!(TtsFormat::parse(format.as_str()), Some(format));
+        }
+        assert_eq!(TtsFormat::parse("flac"), None);
+        assert_eq!(TtsFormat::Opus.mime_type(), "audio/ogg");
+        assert_eq!(TtsFormat::Mp3.mime_type(), "audio/mpeg");
+    }
+
+    #[test]
+    fn test_sniff_untagged_audio() {
+        assert_eq!(TtsFormat::sniff(&encode_wav(&[1, 2], 24000)), TtsFormat::Wav);
+        assert_eq!(TtsFormat::sniff(b"OggS\0\x02"), TtsFormat::Opus);
+        assert_eq!(TtsFormat::sniff(b"ID3\x04"), TtsFormat::Mp3);
+        assert_eq!(TtsFormat::sniff(&[0xFF, 0xFB, 0x90]), TtsFormat::Mp3);
+        assert_eq!(TtsFormat::sniff(&[1, 0, 2, 0]), TtsFormat::Pcm);
+        // A declared format wins over sniffing
+        assert_eq!(TtsFormat::resolve(Some(TtsFormat::Pcm), b"RIFF"), TtsFormat::Pcm);
+    }
+
+    #[test]
+    fn test_unknown_format_name_is_ignored() {
+        #[derive(Deserialize)]
+        struct Tagged {
+            #[serde(default, deserialize_with = "deserialize_lenient")]
+            format: Option<TtsFormat>,
+        }
+
+        let parse = |json: &str| serde_json::from_str::<Tagged>(json).unwrap().format;
+        assert_eq!(parse(r#"{"format":"opus"}"#), Some(TtsFormat::Opus));
+        assert_eq!(parse(r#"{"format":"flac"}"#), None);
+        assert_eq!(parse(r#"{"format":null}"#), None);
+        assert_eq!(parse("{}"), None);
+    }
+
+    #[test]
+    fn test_wav_round_trip() {
+        let samples = tone(480);
+        let decoded = decode(TtsFormat::Wav, &encode_wav(&samples, 22050)).unwrap();
+        assert_eq!(decoded.sample_rate, 22050);
+        assert_eq!(decoded.samples, samples);
+    }
+
+    #[test]
+    fn test_stereo_wav_is_downmixed() {
+        let mut wav = encode_wav(&[100, 300, -50, 50], 48000);
+        // Patch the header to two channels (block align 4, byte rate doubled)
+        wav[22..24].copy_from_slice(&2u16.to_le_bytes());
+        wav[28..32].copy_from_slice(&(48000u32 * 4).to_le_bytes());
+        wav[32..34].copy_from_slice(&4u16.to_le_bytes());
+
+        let decoded = decode(TtsFormat::Wav, &wav).unwrap();
+        assert_eq!(decoded.samples, vec![200, 0]);
+    }
+
+    #[test]
+    fn test_invalid_wav_rejected() {
+        assert!(decode(TtsFormat::Wav, b"RIFF....WAVE").is_err());
+        assert!(decode(TtsFormat::Wav, &[1, 2, 3]).is_err());
+    }
+
+    #[test]
+    fn test_pcm_assumes_default_rate() {
+        let decoded = decode_base64("AQACAAMABAA=", Some(TtsFormat::Pcm)).unwrap();
+        assert_eq!(decoded.samples, vec![1, 2, 3, 4]);
+        assert_eq!(decoded.sample_rate, DEFAULT_PCM_SAMPLE_RATE);
+    }
+
+    #[test]
+    fn test_ogg_opus_decodes() {
+        use audiopus::{coder::Encoder, Application, Channels, SampleRate};
+
+        // 100 ms of tone as five 20 ms Opus frames
+        let encoder = Encoder::new(SampleRate::Hz48000, Channels::Mono, Application::Voip).unwrap();
+        let pcm = tone(4800);
+        let mut writer = ogg::PacketWriter::new(Vec::new());
+        let mut head = b"OpusHead".to_vec();
+        head.extend_from_slice(&[1, 1]); // version, channels
+        head.extend_from_slice(&312u16.to_le_bytes()); // pre-skip
+        head.extend_from_slice(&48000u32.to_le_bytes());
+        head.extend_from_slice(&[0, 0, 0]); // gain, mapping family
+        writer.write_packet(head.into_boxed_slice(), 1, ogg::PacketWriteEndInfo::EndPage, 0).unwrap();
+        writer
+            .write_packet(b"OpusTags\0\0\0\0\0\0\0\0".to_vec().into_boxed_slice(), 1, ogg::PacketWriteEndInfo::EndPage, 0)
+            .unwrap();
+        for (i, frame) in pcm.chunks(960).enumerate() {
+            let mut out = vec![0u8; 4000];
+            let len = encoder.encode(frame, &mut out).unwrap();
+            out.truncate(len);
+            let end = if i == 4 {
+                ogg::PacketWriteEndInfo::EndStream
+            } else {
+                ogg::PacketWriteEndInfo::NormalPacket
+            };
+            writer.write_packet(out.into_boxed_slice(), 1, end, ((i + 1) * 960) as u64).unwrap();
+        }
+        let ogg_bytes = writer.into_inner();
+
+        assert_eq!(TtsFormat::sniff(&ogg_bytes), TtsFormat::Opus);
+        let decoded = decode(TtsFormat::Opus, &ogg_bytes).unwrap();
+        assert_eq!(decoded.sample_rate, 48000);
+        assert_eq!(decoded.samples.len(), 4800 - 312);
+        assert!(decoded.samples.iter().any(|&s| s.abs() > 1000));
+    }
+
+    #[test]
+    fn test_garbage_mp3_rejected() {
+        assert!(decode(TtsFormat::Mp3, &[0u8; 64]).is_err());
+    }
+}
@@ -0,0 +1,141 @@
+//! HTTP client for a TTS service running apart from voice inference.
+//!
+//! With `voice.tts_url` set, voice segments are sent without asking for
+//! speech and each result's translation is synthesised here instead, so TTS
+//! can be scaled on its own. The service is the inference sidecar's
+//! `POST /tts` endpoint, typically on a replica that only loads TTS.
+
+use super::tts_audio::{deserialize_lenient, TtsFormat};
+use super::types::VoiceInferenceResponse;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tracing::warn;
+
+#[derive(Debug, Serialize)]
+struct TtsRequest<'a> {
+    text: &'a str,
+    language: &'a str,
+    format: TtsFormat,
+}
+
+/// Speech synthesised by the TTS service
+#[derive(Debug, Deserialize)]
+pub struct TtsResponse {
+    /// Base64 audio
+    pub audio: String,
+    /// Encoding the service actually produced; sniffed from the bytes when
+    /// absent or unknown
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    pub format: Option<TtsFormat>,
+}
+
+/// Client for a standalone TTS service
+#[derive(Debug, Clone)]
+pub struct TtsClient {
+    http: reqwest::Client,
+    base_url: String,
+}
+
+impl TtsClient {
+    pub fn new(base_url: &str, timeout: Duration) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .expect("Failed to create HTTP client");
+        Self {
+            http,
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Synthesise `text` spoken in `language`
+    pub async fn synthesize(
+        &self,
+        text: &str,
+        language: &str,
+        format: TtsFormat,
+    ) -> reqwest::Result<TtsResponse> {
+        self.http
+            .post(format!("{}/tts", self.base_url))
+            .json(&TtsRequest { text, language, format })
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+
+    /// Whether the service is up and has its TTS model loaded
+    pub async fn health_check(&self) -> reqwest::Result<bool> {
+        #[derive(Deserialize)]
+        struct Health {
+            tts_loaded: bool,
+        }
+
+        let health: Health = self
+            .http
+            .get(format!("{}/health", self.base_url))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(health.tts_loaded)
+    }
+
+    /// Fill in the speech for a voice result, leaving it without audio if
+    /// synthesis fails (the transcript is still worth delivering)
+    pub async fn add_speech(&self, response: &mut VoiceInferenceResponse, format: TtsFormat) {
+        let VoiceInferenceResponse::Result {
+            translated_text,
+            target_language,
+            tts_audio,
+            tts_format,
+            ..
+        } = response
+        else {
+            return;
+        };
+        if translated_text.trim().is_empty() {
+            return;
+        }
+
+        match self.synthesize(translated_text, target_language, format).await {
+            Ok(speech) => {
+                *tts_audio = Some(speech.audio);
+                *tts_format = speech.format;
+            }
+            Err(e) => warn!(error = %e, language = %target_language, "TTS service request failed"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_body() {
+        let body = serde_json::to_value(TtsRequest {
+            text: "hola",
+            language: "es",
+            format: TtsFormat::Opus,
+        })
+        .unwrap();
+        assert_eq!(body, serde_json::json!({"text": "hola", "language": "es", "format": "opus"}));
+    }
+
+    #[test]
+    fn test_response_with_unknown_format() {
+        let speech: TtsResponse =
+            serde_json::from_str(r#"{"audio": "AAAA", "format": "flac", "duration": 1.5}"#).unwrap();
+        assert_eq!(speech.audio, "AAAA");
+        assert_eq!(speech.format, None);
+    }
+
+    #[test]
+    fn test_base_url_trailing_slash() {
+        let client = TtsClient::new("http://tts:8000/", Duration::from_secs(5));
+        assert_eq!(client.base_url, "http://tts:8000");
+    }
+}
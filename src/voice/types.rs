@@ -1,9 +1,13 @@
 //! Shared types for voice translation pipeline.
 
+use super::tts_audio::TtsFormat;
+use crate::translation::Formality;
 use bytes::Bytes;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
+use uuid::Uuid;
 
 /// Discord SSRC (Synchronization Source) identifier for a speaker.
 pub type Ssrc = u32;
@@ -17,6 +21,11 @@ pub const OPUS_FRAME_MS: u32 = 20;
 /// Samples per Opus frame at 48kHz.
 pub const SAMPLES_PER_FRAME: usize = (DISCORD_SAMPLE_RATE * OPUS_FRAME_MS / 1000) as usize;
 
+/// Version of the Rust ↔ Python voice protocol (binary frame layout and
+/// message shapes). Bump this, and `PROTOCOL_VERSION` in
+/// `inference/voice_protocol.py`, whenever either changes incompatibly.
+pub const PROTOCOL_VERSION: u32 = 1;
+
 /// Raw audio packet from Discord voice.
 #[derive(Debug, Clone)]
 pub struct AudioPacket {
@@ -60,6 +69,14 @@ pub struct AudioSegment {
     pub start_time: Instant,
     /// End timestamp
     pub end_time: Instant,
+    /// Wall-clock time of the first sample in `samples`, for aligning
+    /// transcripts and subtitle cues. `start_time`/`end_time` stay
+    /// monotonic for duration math.
+    pub started_at: DateTime<Utc>,
+    /// Leading samples repeated from the end of the previous streamed chunk
+    /// of the same utterance, so words on the seam are heard whole. The
+    /// inference service drops the words it already transcribed there.
+    pub overlap_samples: usize,
 }
 
 impl AudioSegment {
@@ -81,6 +98,45 @@ impl AudioSegment {
         }
         Bytes::from(bytes)
     }
+
+    /// Split into consecutive pieces of at most `max_samples` samples, each
+    /// timed from where it starts in the original utterance.
+    pub fn split(self, max_samples: usize) -> Vec<AudioSegment> {
+        let max_samples = max_samples.max(1);
+        if self.samples.len() <= max_samples {
+            return vec![self];
+        }
+
+        let offset = |samples: usize| {
+            Duration::from_secs_f64(samples as f64 / DISCORD_SAMPLE_RATE as f64)
+        };
+        let chunks = self.samples.chunks(max_samples);
+        let last = chunks.len() - 1;
+        chunks
+            .enumerate()
+            .map(|(i, chunk)| {
+                let start = offset(i * max_samples);
+                let start_time = self.start_time + start;
+                let end_time = if i == last {
+                    self.end_time
+                } else {
+                    start_time + offset(chunk.len())
+                };
+                AudioSegment {
+                    user_id: self.user_id,
+                    username: self.username.clone(),
+                    guild_id: self.guild_id,
+                    channel_id: self.channel_id,
+                    samples: chunk.to_vec(),
+                    start_time,
+                    end_time,
+                    started_at: self.started_at
+                        + chrono::Duration::from_std(start).unwrap_or_default(),
+                    overlap_samples: if i == 0 { self.overlap_samples } else { 0 },
+                }
+            })
+            .collect()
+    }
 }
 
 /// Speaker information for diarization.
@@ -127,6 +183,9 @@ pub struct TranscriptionResult {
     pub audio_duration: f64,
     /// Processing time in milliseconds
     pub processing_time_ms: u64,
+    /// Overall transcription confidence (0.0-1.0), if reported
+    #[serde(default)]
+    pub confidence: Option<f32>,
 }
 
 /// Translated transcription with TTS audio.
@@ -138,13 +197,66 @@ pub struct VoiceTranslationResult {
     pub translated_text: String,
     /// Target language
     pub target_language: String,
-    /// TTS audio (base64 encoded, WAV format)
+    /// TTS audio (base64 encoded, see `tts_format`)
     pub tts_audio: Option<String>,
     /// Total pipeline latency in milliseconds
     pub total_latency_ms: u64,
 }
 
+/// One step the inference service runs on a voice segment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PipelineStage {
+    Transcribe,
+    Translate,
+    Tts,
+}
+
+/// How far the bot takes voice segments: transcripts only, translations, or
+/// translations read aloud.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum VoicePipeline {
+    TranscribeOnly,
+    TranscribeAndTranslate,
+    #[default]
+    TranscribeTranslateTts,
+}
+
+impl VoicePipeline {
+    /// Parse a pipeline name, or None if unknown
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "transcribe_only" => Some(Self::TranscribeOnly),
+            "transcribe_and_translate" => Some(Self::TranscribeAndTranslate),
+            "transcribe_translate_tts" => Some(Self::TranscribeTranslateTts),
+            _ => None,
+        }
+    }
+
+    /// Stages to request for one segment. Translation is left out when the
+    /// speaker's language is known to be the target already, and speech
+    /// unless the channel wants it; TTS then reads the transcript.
+    pub fn stages(
+        &self,
+        source_language: Option<&str>,
+        target_language: &str,
+        tts_enabled: bool,
+    ) -> Vec<PipelineStage> {
+        let mut stages = vec![PipelineStage::Transcribe];
+        let same_language = source_language.is_some_and(|source| source == target_language);
+        if *self >= Self::TranscribeAndTranslate && !same_language {
+            stages.push(PipelineStage::Translate);
+        }
+        if *self == Self::TranscribeTranslateTts && tts_enabled {
+            stages.push(PipelineStage::Tts);
+        }
+        stages
+    }
+}
+
 /// WebSocket message from Rust bot to voice inference.
+// Built once per message and sent straight on; boxing wouldn't save anything
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum VoiceInferenceRequest {
@@ -162,12 +274,51 @@ pub enum VoiceInferenceRequest {
         audio_base64: String,
         /// Sample rate
         sample_rate: u32,
+        /// Spoken language (ISO 639-1), or None to auto-detect per segment
+        #[serde(default)]
+        source_language: Option<String>,
         /// Target language for translation
         target_language: String,
         /// Whether to generate TTS audio
         generate_tts: bool,
+        /// Register of the translation, for services that support one
+        #[serde(default, skip_serializing_if = "Formality::is_default")]
+        formality: Formality,
+        /// Stages to run, in order; services that predate this run them all
+        /// and go by `generate_tts`
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        stages: Vec<PipelineStage>,
         /// Audio hash for cache correlation (Python must echo this back)
         audio_hash: u64,
+        /// Wall-clock time the segment's first sample was captured (echoed back)
+        #[serde(default)]
+        started_at: Option<DateTime<Utc>>,
+        /// Leading samples that repeat the end of the previous chunk; the
+        /// service drops the words it already transcribed there
+        #[serde(default)]
+        overlap_samples: u32,
+
+        /// Preferred TTS encoding; the service may fall back to WAV
+        #[serde(
+            default,
+            skip_serializing_if = "Option::is_none",
+            deserialize_with = "super::tts_audio::deserialize_lenient"
+        )]
+        tts_format: Option<TtsFormat>,
+
+        /// Token of the tenant this guild authenticates as, for inference
+        /// services that serve several
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        inference_token: Option<String>,
+
+        /// Per-segment id both sides log, to follow one utterance across
+        /// services (echoed back)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        trace_id: Option<Uuid>,
+    },
+    /// First message on connect, announcing the client's protocol version
+    Hello {
+        protocol_version: u32,
     },
     /// Ping to keep connection alive
     Ping,
@@ -180,6 +331,8 @@ pub enum VoiceInferenceRequest {
 }
 
 /// WebSocket message from voice inference to Rust bot.
+// Built once per message and sent straight on; boxing wouldn't save anything
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum VoiceInferenceResponse {
@@ -207,6 +360,20 @@ pub enum VoiceInferenceResponse {
         latency_ms: u64,
         /// Audio hash echoed back for cache correlation
         audio_hash: u64,
+        /// Transcription confidence (0.0-1.0), if the service reports it
+        #[serde(default)]
+        confidence: Option<f32>,
+        /// Wall-clock time the speech started, echoed from the request
+        #[serde(default)]
+        started_at: Option<DateTime<Utc>>,
+
+        /// Encoding of `tts_audio`; sniffed from the bytes when absent
+        #[serde(default, deserialize_with = "super::tts_audio::deserialize_lenient")]
+        tts_format: Option<TtsFormat>,
+
+        /// Trace id echoed from the request; absent on older services
+        #[serde(default)]
+        trace_id: Option<Uuid>,
     },
     /// Pong response
     Pong,
@@ -216,6 +383,9 @@ pub enum VoiceInferenceResponse {
         message: String,
         /// Error code
         code: Option<String>,
+        /// Trace id of the segment that failed, when the error is about one
+        #[serde(default)]
+        trace_id: Option<Uuid>,
     },
     /// Service ready notification
     Ready {
@@ -223,9 +393,36 @@ pub enum VoiceInferenceResponse {
         stt_models: Vec<String>,
         /// Available TTS models
         tts_models: Vec<String>,
+        /// Server protocol version; absent on servers that predate versioning
+        #[serde(default)]
+        protocol_version: Option<u32>,
     },
 }
 
+impl VoiceInferenceResponse {
+    /// Transcription confidence of a `Result`, if the service reported one
+    pub fn confidence(&self) -> Option<f32> {
+        match self {
+            Self::Result { confidence, .. } => *confidence,
+            _ => None,
+        }
+    }
+
+    /// Trace id of the segment this answers, if the service echoed one
+    pub fn trace_id(&self) -> Option<Uuid> {
+        match self {
+            Self::Result { trace_id, .. } | Self::Error { trace_id, .. } => *trace_id,
+            _ => None,
+        }
+    }
+
+    /// Whether this is a `Result` whose confidence is below `threshold`.
+    /// Results without a confidence score are never considered low.
+    pub fn is_low_confidence(&self, threshold: f32) -> bool {
+        self.confidence().is_some_and(|c| c < threshold)
+    }
+}
+
 /// Voice channel state.
 #[derive(Debug, Clone)]
 pub struct VoiceChannelState {
@@ -235,10 +432,15 @@ pub struct VoiceChannelState {
     pub channel_id: u64,
     /// Whether translation is enabled
     pub translation_enabled: bool,
+    /// Spoken language, or None to let the inference service detect it
+    /// for every segment (mixed-language channels)
+    pub source_language: Option<Arc<str>>,
     /// Target language for translations (Arc so cloning is cheap)
     pub target_language: Arc<str>,
     /// Whether TTS playback is enabled
     pub tts_enabled: bool,
+    /// Register of translations into `target_language`
+    pub formality: Formality,
     /// Active speakers (SSRC -> user mapping)
     pub speakers: std::collections::HashMap<Ssrc, SpeakerInfo>,
 }
@@ -249,8 +451,10 @@ impl Default for VoiceChannelState {
             guild_id: 0,
             channel_id: 0,
             translation_enabled: true,
+            source_language: None,
             target_language: Arc::from("en"),
             tts_enabled: false,
+            formality: Formality::Default,
             speakers: std::collections::HashMap::new(),
         }
     }
@@ -273,6 +477,8 @@ mod tests {
             samples: vec![1, 2, 3],
             start_time: start,
             end_time: end,
+            started_at: chrono::Utc::now(),
+            overlap_samples: 0,
         };
 
         let duration = segment.duration();
@@ -289,6 +495,8 @@ mod tests {
             samples: vec![0, 16384, -16384, 32767, -32768],
             start_time: Instant::now(),
             end_time: Instant::now(),
+            started_at: chrono::Utc::now(),
+            overlap_samples: 0,
         };
 
         let f32_samples = segment.samples_f32();
@@ -310,6 +518,8 @@ mod tests {
             samples: vec![256, 512],
             start_time: Instant::now(),
             end_time: Instant::now(),
+            started_at: chrono::Utc::now(),
+            overlap_samples: 0,
         };
 
         let bytes = segment.samples_bytes();
@@ -323,6 +533,42 @@ mod tests {
         assert_eq!(reconstructed, vec![256, 512]);
     }
 
+    #[test]
+    fn test_audio_segment_split_keeps_audio_and_timing() {
+        let rate = DISCORD_SAMPLE_RATE as usize;
+        let start = Instant::now();
+        let started_at = chrono::Utc::now();
+        let segment = AudioSegment {
+            user_id: 1,
+            username: "Test".to_string(),
+            guild_id: 2,
+            channel_id: 3,
+            samples: (0..rate * 5 / 2).map(|i| i as i16).collect(),
+            start_time: start,
+            end_time: start + Duration::from_millis(2500),
+            started_at,
+            overlap_samples: 100,
+        };
+
+        let pieces = segment.clone().split(rate);
+        assert_eq!(
+            pieces.iter().map(|p| p.samples.len()).collect::<Vec<_>>(),
+            vec![rate, rate, rate / 2]
+        );
+        let rejoined: Vec<i16> = pieces.iter().flat_map(|p| p.samples.clone()).collect();
+        assert_eq!(rejoined, segment.samples);
+
+        assert_eq!(pieces[1].start_time, start + Duration::from_secs(1));
+        assert_eq!(pieces[1].started_at, started_at + chrono::Duration::seconds(1));
+        assert_eq!(pieces[2].end_time, segment.end_time);
+        // Only the first piece starts with audio from the previous chunk
+        assert_eq!(pieces[0].overlap_samples, 100);
+        assert_eq!(pieces[1].overlap_samples, 0);
+
+        // Short segments come back whole
+        assert_eq!(segment.split(rate * 10).len(), 1);
+    }
+
     #[test]
     fn test_voice_inference_request_audio() {
         let request = VoiceInferenceRequest::Audio {
@@ -332,9 +578,17 @@ mod tests {
             username: "TestUser".to_string(),
             audio_base64: "dGVzdA==".to_string(),
             sample_rate: 48000,
+            source_language: None,
             target_language: "es".to_string(),
             generate_tts: true,
+            formality: Formality::Default,
+            stages: Vec::new(),
             audio_hash: 12345,
+            started_at: None,
+            overlap_samples: 0,
+            tts_format: None,
+            trace_id: None,
+            inference_token: None,
         };
 
         match request {
@@ -345,6 +599,63 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_auto_detect_request_sends_null_source() {
+        let request = VoiceInferenceRequest::Audio {
+            guild_id: "123".to_string(),
+            channel_id: "456".to_string(),
+            user_id: "789".to_string(),
+            username: "TestUser".to_string(),
+            audio_base64: String::new(),
+            sample_rate: 48000,
+            source_language: None,
+            target_language: "en".to_string(),
+            generate_tts: false,
+            formality: Formality::Default,
+            stages: Vec::new(),
+            audio_hash: 1,
+            started_at: None,
+            overlap_samples: 0,
+            tts_format: None,
+            trace_id: None,
+            inference_token: None,
+        };
+        let json = serde_json::to_value(&request).unwrap();
+        assert!(json["source_language"].is_null());
+        assert!(json.get("formality").is_none());
+
+        // Older payloads without the field still parse as auto-detect
+        let legacy = r#"{"type":"Audio","guild_id":"1","channel_id":"2","user_id":"3",
+            "username":"U","audio_base64":"","sample_rate":48000,"target_language":"en",
+            "generate_tts":false,"audio_hash":1}"#;
+        match serde_json::from_str(legacy).unwrap() {
+            VoiceInferenceRequest::Audio {
+                source_language,
+                formality,
+                ..
+            } => {
+                assert!(source_language.is_none());
+                assert_eq!(formality, Formality::Default);
+            }
+            _ => panic!("Wrong variant"),
+        }
+    }
+
+    #[test]
+    fn test_pipeline_stages() {
+        use PipelineStage::*;
+        let full = VoicePipeline::TranscribeTranslateTts;
+        assert_eq!(full.stages(None, "en", true), vec![Transcribe, Translate, Tts]);
+        assert_eq!(full.stages(Some("de"), "en", false), vec![Transcribe, Translate]);
+        // Already in the target language: speech without translation
+        assert_eq!(full.stages(Some("en"), "en", true), vec![Transcribe, Tts]);
+
+        assert_eq!(VoicePipeline::TranscribeAndTranslate.stages(None, "en", true), vec![Transcribe, Translate]);
+        assert_eq!(VoicePipeline::TranscribeOnly.stages(Some("de"), "en", true), vec![Transcribe]);
+        assert_eq!(VoicePipeline::parse(" Transcribe_Only "), Some(VoicePipeline::TranscribeOnly));
+        assert_eq!(VoicePipeline::parse("tts"), None);
+    }
+
     #[test]
     fn test_voice_inference_response_result() {
         let response = VoiceInferenceResponse::Result {
@@ -359,6 +670,10 @@ mod tests {
             tts_audio: None,
             latency_ms: 150,
             audio_hash: 67890,
+            confidence: None,
+            started_at: None,
+            tts_format: None,
+            trace_id: None,
         };
 
         match response {
@@ -401,6 +716,40 @@ mod tests {
         assert_eq!(duration.as_millis(), 20);
     }
 
+    #[test]
+    fn test_voice_inference_response_confidence() {
+        let json = r#"{"type":"Result","guild_id":"1","channel_id":"2","user_id":"3",
+            "username":"U","original_text":"hm","translated_text":"hm",
+            "source_language":"en","target_language":"es","tts_audio":null,
+            "latency_ms":80,"audio_hash":1,"confidence":0.3}"#;
+        let response: VoiceInferenceResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(response.confidence(), Some(0.3));
+        assert!(response.is_low_confidence(0.5));
+        assert!(!response.is_low_confidence(0.2));
+
+        // Older services omit the field; such results are never flagged
+        let legacy: VoiceInferenceResponse =
+            serde_json::from_str(&json.replace(r#","confidence":0.3"#, "")).unwrap();
+        assert_eq!(legacy.confidence(), None);
+        assert!(!legacy.is_low_confidence(0.5));
+    }
+
+    #[test]
+    fn test_voice_inference_response_trace_id() {
+        let trace_id = Uuid::new_v4();
+        let json = format!(
+            r#"{{"type":"Error","message":"boom","code":"PROCESSING_ERROR","trace_id":"{}"}}"#,
+            trace_id
+        );
+        let response: VoiceInferenceResponse = serde_json::from_str(&json).unwrap();
+        assert_eq!(response.trace_id(), Some(trace_id));
+
+        // Older services don't echo one
+        let legacy: VoiceInferenceResponse =
+            serde_json::from_str(r#"{"type":"Error","message":"boom","code":null}"#).unwrap();
+        assert_eq!(legacy.trace_id(), None);
+    }
+
     #[test]
     fn test_discord_sample_rate_constant() {
         assert_eq!(DISCORD_SAMPLE_RATE, 48000);
@@ -0,0 +1,354 @@
+//! Admin web routes for inspecting and flushing the caches, and listing the
+//! guilds this instance serves.
+//!
+//! Requests are signed with the same Ed25519 admin key that provisions the
+//! bot's secrets: `X-Admin-Timestamp` carries the Unix time in seconds,
+//! `X-Admin-Nonce` a random string unique to the request, and
+//! `X-Admin-Signature` a base64 signature over
+//! `"{METHOD} {path_and_query} {timestamp} {nonce} {body_sha256_hex}"`.
+//! Signatures older than [`MAX_SIGNATURE_AGE_SECS`] are refused, and each
+//! nonce is accepted once within that window, so a captured request can't be
+//! replayed.
+
+use crate::admin::crypto::{parse_signature, verify_signature};
+use crate::db::{DbPool, GuildFootprint, GuildRepo};
+use crate::error::AppError;
+use crate::translation::TranslationClient;
+use crate::voice::VoiceTranscriptionCache;
+use axum::{
+    body::Body,
+    extract::{Query, Request, State},
+    http::HeaderMap,
+    middleware::{self, Next},
+    response::{Json, Response},
+    routing::get,
+    Router,
+};
+use ed25519_dalek::VerifyingKey;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tracing::{info, warn};
+
+/// How far a request's timestamp may be from the bot's clock
+pub const MAX_SIGNATURE_AGE_SECS: i64 = 300;
+
+/// Guilds listed per page when the request doesn't say
+const DEFAULT_GUILDS_PAGE: i64 = 100;
+
+/// Most guilds listed per page
+const MAX_GUILDS_PAGE: i64 = 500;
+
+/// Largest request body the signature check reads
+const MAX_ADMIN_BODY_BYTES: usize = 64 * 1024;
+
+/// Accepted nonce lengths; long enough to be unguessable, short enough to
+/// keep the replay ledger small
+const MIN_NONCE_LEN: usize = 16;
+const MAX_NONCE_LEN: usize = 128;
+
+const TIMESTAMP_HEADER: &str = "x-admin-timestamp";
+const NONCE_HEADER: &str = "x-admin-nonce";
+const SIGNATURE_HEADER: &str = "x-admin-signature";
+
+/// State for the admin cache and guild routes
+#[derive(Clone)]
+pub struct AdminCacheState {
+    pub admin_key: VerifyingKey,
+    pub pool: DbPool,
+    pub translator: Arc<TranslationClient>,
+    pub voice_cache: Arc<VoiceTranscriptionCache>,
+    pub seen_nonces: Arc<SeenNonces>,
+}
+
+/// Nonces of accepted admin requests, each kept until its timestamp falls
+/// out of the signature window
+#[derive(Debug, Default)]
+pub struct SeenNonces {
+    nonces: Mutex<HashMap<String, i64>>,
+}
+
+impl SeenNonces {
+    /// Record `nonce`, signed at `timestamp`; false if it was already used
+    fn insert(&self, nonce: &str, timestamp: i64, now: i64) -> bool {
+        let mut nonces = self.nonces.lock().unwrap();
+        nonces.retain(|_, signed_at| now - *signed_at <= MAX_SIGNATURE_AGE_SECS);
+        nonces.insert(nonce.to_string(), timestamp).is_none()
+    }
+}
+
+/// Response to a cache clear
+#[derive(Debug, Serialize)]
+pub struct ClearResponse {
+    /// Entries dropped from the cache
+    pub cleared: usize,
+}
+
+/// Query parameters for the guild list
+#[derive(Debug, Deserialize)]
+pub struct GuildsQuery {
+    /// Guilds per page (default 100, at most 500)
+    pub limit: Option<i64>,
+    /// Guilds to skip
+    pub offset: Option<i64>,
+}
+
+/// One page of the guilds this instance serves
+#[derive(Debug, Serialize)]
+pub struct GuildsPage {
+    /// Configured guilds in total
+    pub total: i64,
+    pub offset: i64,
+    pub limit: i64,
+    pub guilds: Vec<GuildFootprint>,
+}
+
+/// The message an admin signs for a request. `path_and_query` is the path
+/// plus `?query` when there is one, exactly as sent.
+pub fn request_signature_message(
+    method: &str,
+    path_and_query: &str,
+    timestamp: i64,
+    nonce: &str,
+    body: &[u8],
+) -> String {
+    let body_hash: String = Sha256::digest(body)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    format!("{} {} {} {} {}", method, path_and_query, timestamp, nonce, body_hash)
+}
+
+/// Whether a request carries a valid, current signature from `admin_key`
+/// with a nonce not seen before, checked at Unix time `now`
+fn verify_request(
+    admin_key: &VerifyingKey,
+    seen_nonces: &SeenNonces,
+    method: &str,
+    path_and_query: &str,
+    headers: &HeaderMap,
+    body: &[u8],
+    now: i64,
+) -> bool {
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+    let Some(timestamp) = header(TIMESTAMP_HEADER).and_then(|t| t.parse::<i64>().ok()) else {
+        return false;
+    };
+    if (now - timestamp).abs() > MAX_SIGNATURE_AGE_SECS {
+        return false;
+    }
+    let Some(nonce) = header(NONCE_HEADER)
+        .filter(|n| (MIN_NONCE_LEN..=MAX_NONCE_LEN).contains(&n.len()) && !n.contains(' '))
+    else {
+        return false;
+    };
+    let Some(signature) = header(SIGNATURE_HEADER).and_then(|s| parse_signature(s).ok()) else {
+        return false;
+    };
+
+    let message = request_signature_message(method, path_and_query, timestamp, nonce, body);
+    // Only signed requests reach the ledger, so it can't be flooded
+    verify_signature(admin_key, message.as_bytes(), &signature).is_ok()
+        && seen_nonces.insert(nonce, timestamp, now)
+}
+
+/// Middleware refusing requests without a valid admin signature
+async fn require_admin_signature(
+    State(state): State<AdminCacheState>,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let path = request
+        .uri()
+        .path_and_query()
+        .map_or_else(|| request.uri().path().to_string(), |pq| pq.as_str().to_string());
+    let method = request.method().as_str().to_string();
+    let (parts, body) = request.into_parts();
+    let Ok(body) = axum::body::to_bytes(body, MAX_ADMIN_BODY_BYTES).await else {
+        warn!(%method, %path, "Rejected admin request with an unreadable or oversized body");
+        return Err(AppError::AuthRequired);
+    };
+    let now = chrono::Utc::now().timestamp();
+    if !verify_request(
+        &state.admin_key,
+        &state.seen_nonces,
+        &method,
+        &path,
+        &parts.headers,
+        &body,
+        now,
+    ) {
+        warn!(%method, %path, "Rejected unsigned, badly signed or replayed admin request");
+        return Err(AppError::AuthRequired);
+    }
+    Ok(next.run(Request::from_parts(parts, Body::from(body))).await)
+}
+
+/// Handler: GET /admin/cache/voice
+async fn voice_cache_stats(
+    State(state): State<AdminCacheState>,
+) -> Json<crate::voice::CacheStats> {
+    Json(state.voice_cache.stats())
+}
+
+/// Handler: POST /admin/cache/voice
+///
+/// Drops every entry and resets the hit counters, so hit rates afterwards
+/// describe the fresh cache.
+async fn clear_voice_cache(State(state): State<AdminCacheState>) -> Json<ClearResponse> {
+    let cleared = state.voice_cache.len().await;
+    state.voice_cache.clear().await;
+    state.voice_cache.reset_stats();
+    info!(cleared, "Voice cache cleared by admin");
+    Json(ClearResponse { cleared })
+}
+
+/// Handler: GET /admin/cache/translation
+async fn translation_cache_stats(
+    State(state): State<AdminCacheState>,
+) -> Json<crate::translation::CacheStats> {
+    Json(state.translator.cache_stats())
+}
+
+/// Handler: POST /admin/cache/translation
+async fn clear_translation_cache(State(state): State<AdminCacheState>) -> Json<ClearResponse> {
+    let cleared = state.translator.clear_cache();
+    info!(cleared, "Translation cache cleared by admin");
+    Json(ClearResponse { cleared })
+}
+
+/// Handler: GET /admin/guilds
+///
+/// Guilds that ran `/setup init`, oldest first, with their enabled channel
+/// and active voice session counts.
+async fn list_guilds(
+    State(state): State<AdminCacheState>,
+    Query(query): Query<GuildsQuery>,
+) -> Result<Json<GuildsPage>, AppError> {
+    let limit = query.limit.unwrap_or(DEFAULT_GUILDS_PAGE).clamp(1, MAX_GUILDS_PAGE);
+    let offset = query.offset.unwrap_or(0).max(0);
+    let total = GuildRepo::count(&state.pool).await?;
+    let guilds = GuildRepo::list_footprints(&state.pool, limit, offset).await?;
+    Ok(Json(GuildsPage {
+        total,
+        offset,
+        limit,
+        guilds,
+    }))
+}
+
+/// Routes for the admin cache and guild endpoints, all behind the admin
+/// signature
+pub fn admin_cache_router(state: AdminCacheState) -> Router {
+    Router::new()
+        .route("/admin/guilds", get(list_guilds))
+        .route(
+            "/admin/cache/voice",
+            get(voice_cache_stats).post(clear_voice_cache),
+        )
+        .route(
+            "/admin/cache/translation",
+            get(translation_cache_stats).post(clear_translation_cache),
+        )
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            require_admin_signature,
+        ))
+        .with_state(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+    use ed25519_dalek::{Signer, SigningKey};
+
+    const NONCE: &str = "0123456789abcdef";
+
+    fn signed_headers(
+        key: &SigningKey,
+        method: &str,
+        path: &str,
+        timestamp: i64,
+        nonce: &str,
+        body: &[u8],
+    ) -> HeaderMap {
+        let message = request_signature_message(method, path, timestamp, nonce, body);
+        let signature = BASE64.encode(key.sign(message.as_bytes()).to_bytes());
+        let mut headers = HeaderMap::new();
+        headers.insert(TIMESTAMP_HEADER, timestamp.to_string().parse().unwrap());
+        headers.insert(NONCE_HEADER, nonce.parse().unwrap());
+        headers.insert(SIGNATURE_HEADER, signature.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_verify_request_accepts_valid_signature() {
+        let key = SigningKey::generate(&mut rand::rngs::OsRng);
+        let headers = signed_headers(&key, "POST", "/admin/cache/voice", 1_000, NONCE, b"");
+        let admin = key.verifying_key();
+        let seen = SeenNonces::default();
+        assert!(verify_request(&admin, &seen, "POST", "/admin/cache/voice", &headers, b"", 1_010));
+    }
+
+    #[test]
+    fn test_verify_request_rejects_other_request_or_key() {
+        let key = SigningKey::generate(&mut rand::rngs::OsRng);
+        let headers = signed_headers(&key, "GET", "/admin/guilds?limit=10", 1_000, NONCE, b"");
+        let seen = SeenNonces::default();
+
+        // A list signature can't be reused to clear, for another page or
+        // route, or with a different body
+        let admin = key.verifying_key();
+        assert!(!verify_request(&admin, &seen, "POST", "/admin/guilds?limit=10", &headers, b"", 1_000));
+        assert!(!verify_request(&admin, &seen, "GET", "/admin/guilds?limit=500", &headers, b"", 1_000));
+        assert!(!verify_request(&admin, &seen, "GET", "/admin/guilds", &headers, b"", 1_000));
+        assert!(!verify_request(&admin, &seen, "GET", "/admin/guilds?limit=10", &headers, b"{}", 1_000));
+
+        let other = SigningKey::generate(&mut rand::rngs::OsRng).verifying_key();
+        assert!(!verify_request(&other, &seen, "GET", "/admin/guilds?limit=10", &headers, b"", 1_000));
+    }
+
+    #[test]
+    fn test_verify_request_rejects_stale_or_missing_headers() {
+        let key = SigningKey::generate(&mut rand::rngs::OsRng);
+        let admin = key.verifying_key();
+        let seen = SeenNonces::default();
+        let headers = signed_headers(&key, "GET", "/admin/cache/voice", 1_000, NONCE, b"");
+        let late = 1_000 + MAX_SIGNATURE_AGE_SECS + 1;
+        assert!(!verify_request(&admin, &seen, "GET", "/admin/cache/voice", &headers, b"", late));
+
+        let mut no_nonce = headers.clone();
+        no_nonce.remove(NONCE_HEADER);
+        assert!(!verify_request(&admin, &seen, "GET", "/admin/cache/voice", &no_nonce, b"", 1_000));
+        let short = signed_headers(&key, "GET", "/admin/cache/voice", 1_000, "abc", b"");
+        assert!(!verify_request(&admin, &seen, "GET", "/admin/cache/voice", &short, b"", 1_000));
+
+        assert!(!verify_request(&admin, &seen, "GET", "/admin/cache/voice", &HeaderMap::new(), b"", 1_000));
+    }
+
+    #[test]
+    fn test_verify_request_rejects_replayed_nonce() {
+        let key = SigningKey::generate(&mut rand::rngs::OsRng);
+        let admin = key.verifying_key();
+        let seen = SeenNonces::default();
+        let headers = signed_headers(&key, "POST", "/admin/cache/voice", 1_000, NONCE, b"");
+        assert!(verify_request(&admin, &seen, "POST", "/admin/cache/voice", &headers, b"", 1_000));
+        assert!(!verify_request(&admin, &seen, "POST", "/admin/cache/voice", &headers, b"", 1_001));
+
+        // A fresh nonce in the same second is a different request
+        let next = signed_headers(&key, "POST", "/admin/cache/voice", 1_000, "fedcba9876543210", b"");
+        assert!(verify_request(&admin, &seen, "POST", "/admin/cache/voice", &next, b"", 1_001));
+    }
+
+    #[test]
+    fn test_seen_nonces_forget_expired_entries() {
+        let seen = SeenNonces::default();
+        assert!(seen.insert(NONCE, 1_000, 1_000));
+        assert!(!seen.insert(NONCE, 1_000, 1_000 + MAX_SIGNATURE_AGE_SECS));
+        // By now the old signature is refused on age alone
+        assert!(seen.insert(NONCE, 2_000, 2_000));
+        assert_eq!(seen.nonces.lock().unwrap().len(), 1);
+    }
+}
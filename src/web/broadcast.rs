@@ -1,8 +1,27 @@
 use crate::translation::TranslationResult;
-use crate::voice::VoiceInferenceResponse;
+use crate::voice::tts_audio::{self, TtsFormat};
+use crate::voice::{ConnectionState, VoiceInferenceResponse};
 use dashmap::DashMap;
 use serde::Serialize;
+use std::collections::{HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+/// Number of recent messages kept per channel for replay on (re)subscribe
+pub const REPLAY_HISTORY_SIZE: usize = 20;
+
+/// How long an unwatched channel's history outlives its last message or
+/// viewer, so a viewer reconnecting after a dropped connection still gets
+/// the replay
+pub const REPLAY_GRACE: Duration = Duration::from_secs(120);
+
+/// How often idle history and viewerless channels are swept
+pub const HISTORY_PRUNE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Default confidence below which voice transcriptions are flagged
+pub const DEFAULT_LOW_CONFIDENCE_THRESHOLD: f32 = 0.5;
 
 /// Message sent to web clients via WebSocket
 #[derive(Debug, Clone, Serialize)]
@@ -14,11 +33,15 @@ pub enum WebMessage {
     /// Voice channel transcription/translation
     #[serde(rename = "voice_transcription")]
     VoiceTranscription(VoiceTranscriptionMessage),
+    /// Connection to the voice inference service changed
+    #[serde(rename = "inference_status")]
+    InferenceStatus(InferenceStatusMessage),
 }
 
 /// Text translation message (from text channels)
 #[derive(Debug, Clone, Serialize)]
 pub struct TextTranslationMessage {
+    pub guild_id: String,
     pub channel_id: String,
     pub author_name: String,
     pub author_id: String,
@@ -27,6 +50,9 @@ pub struct TextTranslationMessage {
     pub source_lang: String,
     pub target_lang: String,
     pub timestamp: i64,
+    /// Set when the message is a replay of history sent on (re)connect
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub replay: bool,
 }
 
 /// Voice transcription message (from voice channels)
@@ -34,6 +60,8 @@ pub struct TextTranslationMessage {
 pub struct VoiceTranscriptionMessage {
     pub guild_id: String,
     pub channel_id: String,
+    /// Empty (and omitted) when the guild anonymizes speakers
+    #[serde(skip_serializing_if = "String::is_empty")]
     pub user_id: String,
     pub username: String,
     pub original_text: String,
@@ -42,19 +70,65 @@ pub struct VoiceTranscriptionMessage {
     pub target_lang: String,
     pub latency_ms: u64,
     pub timestamp: i64,
-    /// Base64-encoded TTS audio (WAV format, 24kHz) if available
+    /// When the speaker started talking (ms since epoch); `timestamp` is when
+    /// the result arrived. Use this for subtitle cue times.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<i64>,
+    /// Base64-encoded TTS audio if available
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tts_audio: Option<String>,
+    /// Encoding of `tts_audio` (wav, opus, mp3); headerless PCM is wrapped
+    /// as WAV so browsers can play it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tts_format: Option<TtsFormat>,
+    /// Transcription confidence (0.0-1.0) if the inference service reported it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
+    /// Set when the transcription is likely garbled; rendered de-emphasised
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub low_confidence: bool,
+    /// Set when the message is a replay of history sent on (re)connect
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub replay: bool,
+}
+
+/// Voice inference connection state, so voice viewers can tell a quiet
+/// channel from a lost inference service
+#[derive(Debug, Clone, Serialize)]
+pub struct InferenceStatusMessage {
+    pub state: ConnectionState,
+    pub timestamp: i64,
+}
+
+/// TTS audio in a form browsers can play, with its format
+fn web_tts_audio(audio: &str, declared: Option<TtsFormat>) -> (Option<String>, Option<TtsFormat>) {
+    use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+
+    let Ok(bytes) = BASE64.decode(audio) else {
+        return (None, None);
+    };
+    match TtsFormat::resolve(declared, &bytes) {
+        TtsFormat::Pcm => {
+            let Ok(decoded) = tts_audio::decode(TtsFormat::Pcm, &bytes) else {
+                return (None, None);
+            };
+            let wav = tts_audio::encode_wav(&decoded.samples, decoded.sample_rate);
+            (Some(BASE64.encode(wav)), Some(TtsFormat::Wav))
+        }
+        format => (Some(audio.to_string()), Some(format)),
+    }
 }
 
 impl WebMessage {
     pub fn from_translation(
+        guild_id: &str,
         channel_id: &str,
         author_name: &str,
         author_id: &str,
         translation: &TranslationResult,
     ) -> Self {
         Self::Translation(TextTranslationMessage {
+            guild_id: guild_id.to_string(),
             channel_id: channel_id.to_string(),
             author_name: author_name.to_string(),
             author_id: author_id.to_string(),
@@ -63,10 +137,16 @@ impl WebMessage {
             source_lang: translation.source_lang.clone(),
             target_lang: translation.target_lang.clone(),
             timestamp: chrono::Utc::now().timestamp_millis(),
+            replay: false,
         })
     }
 
-    pub fn from_voice_transcription(response: &VoiceInferenceResponse) -> Option<Self> {
+    /// Web message for a voice result, flagged when its confidence is below
+    /// `low_confidence_threshold`
+    pub fn from_voice_transcription(
+        response: &VoiceInferenceResponse,
+        low_confidence_threshold: f32,
+    ) -> Option<Self> {
         match response {
             VoiceInferenceResponse::Result {
                 guild_id,
@@ -80,11 +160,19 @@ impl WebMessage {
                 tts_audio,
                 latency_ms,
                 audio_hash: _, // Ignore audio_hash for broadcast
+                confidence,
+                started_at,
+                tts_format,
+                trace_id: _,
             } => {
                 // Skip empty transcriptions
                 if original_text.is_empty() {
                     return None;
                 }
+                let (tts_audio, tts_format) = match tts_audio {
+                    Some(audio) => web_tts_audio(audio, *tts_format),
+                    None => (None, None),
+                };
 
                 Some(Self::VoiceTranscription(VoiceTranscriptionMessage {
                     guild_id: guild_id.clone(),
@@ -97,12 +185,53 @@ impl WebMessage {
                     target_lang: target_language.clone(),
                     latency_ms: *latency_ms,
                     timestamp: chrono::Utc::now().timestamp_millis(),
-                    tts_audio: tts_audio.clone(),
+                    started_at: started_at.map(|t| t.timestamp_millis()),
+                    tts_audio,
+                    tts_format,
+                    confidence: *confidence,
+                    low_confidence: response.is_low_confidence(low_confidence_threshold),
+                    replay: false,
                 }))
             }
             _ => None,
         }
     }
+
+    /// Mark this message as replayed history.
+    ///
+    /// TTS audio is dropped so reconnecting viewers don't hear old speech again.
+    pub fn into_replay(self) -> Self {
+        match self {
+            Self::Translation(mut msg) => {
+                msg.replay = true;
+                Self::Translation(msg)
+            }
+            Self::VoiceTranscription(mut msg) => {
+                msg.replay = true;
+                msg.tts_audio = None;
+                Self::VoiceTranscription(msg)
+            }
+            status @ Self::InferenceStatus(_) => status,
+        }
+    }
+}
+
+/// Live stats for one voice channel's transcription feed
+#[derive(Debug, Clone, Serialize)]
+pub struct VoiceChannelStats {
+    pub channel_id: String,
+    /// Web viewers currently connected
+    pub subscribers: usize,
+    /// Timestamp (ms) of the most recent transcription, if any
+    pub last_activity: Option<i64>,
+}
+
+/// Recent messages of one channel and when it was last active
+#[derive(Debug)]
+struct ChannelHistory {
+    messages: VecDeque<WebMessage>,
+    /// Last message or viewer leaving, whichever is later
+    touched: Instant,
 }
 
 /// Manages broadcast channels for real-time web updates
@@ -111,6 +240,12 @@ pub struct BroadcastManager {
     global_tx: broadcast::Sender<WebMessage>,
     /// Per-channel broadcast channels
     channel_txs: DashMap<String, broadcast::Sender<WebMessage>>,
+    /// Recent messages per channel, replayed to clients when they (re)subscribe
+    history: DashMap<String, ChannelHistory>,
+    /// Voice transcriptions below this confidence are flagged for viewers
+    low_confidence_threshold: f32,
+    /// Latest inference connection state, sent to voice viewers as they join
+    inference_status: Mutex<Option<InferenceStatusMessage>>,
 }
 
 impl std::fmt::Debug for BroadcastManager {
@@ -118,6 +253,7 @@ impl std::fmt::Debug for BroadcastManager {
         f.debug_struct("BroadcastManager")
             .field("global_subscribers", &self.global_tx.receiver_count())
             .field("channel_count", &self.channel_txs.len())
+            .field("history_channels", &self.history.len())
             .finish()
     }
 }
@@ -128,9 +264,18 @@ impl BroadcastManager {
         Self {
             global_tx,
             channel_txs: DashMap::new(),
+            history: DashMap::new(),
+            low_confidence_threshold: DEFAULT_LOW_CONFIDENCE_THRESHOLD,
+            inference_status: Mutex::new(None),
         }
     }
 
+    /// Override the confidence below which voice transcriptions are flagged
+    pub fn with_low_confidence_threshold(mut self, threshold: f32) -> Self {
+        self.low_confidence_threshold = threshold;
+        self
+    }
+
     /// Subscribe to all translations
     pub fn subscribe_global(&self) -> broadcast::Receiver<WebMessage> {
         self.global_tx.subscribe()
@@ -145,21 +290,43 @@ impl BroadcastManager {
         tx.subscribe()
     }
 
+    /// Subscribe to a text channel's translations, scoped to its guild.
+    ///
+    /// Uses text:guild_id:channel_id as the key, like voice channels.
+    pub fn subscribe_text_channel(
+        &self,
+        guild_id: &str,
+        channel_id: &str,
+    ) -> broadcast::Receiver<WebMessage> {
+        let key = format!("text:{}:{}", guild_id, channel_id);
+        let tx = self.channel_txs.entry(key).or_insert_with(|| {
+            let (tx, _) = broadcast::channel(100);
+            tx
+        });
+        tx.subscribe()
+    }
+
     /// Send a translation to subscribers
     pub fn send_translation(
         &self,
+        guild_id: &str,
         channel_id: &str,
         author_name: &str,
         author_id: &str,
         translation: &TranslationResult,
     ) {
-        let msg = WebMessage::from_translation(channel_id, author_name, author_id, translation);
+        let msg = WebMessage::from_translation(guild_id, channel_id, author_name, author_id, translation);
 
         // Send to global subscribers
         let _ = self.global_tx.send(msg.clone());
 
-        // Send to channel-specific subscribers
+        self.record_history(channel_id, &msg);
+
+        // Send to channel-specific subscribers, by session and by guild
         if let Some(tx) = self.channel_txs.get(channel_id) {
+            let _ = tx.send(msg.clone());
+        }
+        if let Some(tx) = self.channel_txs.get(&format!("text:{}:{}", guild_id, channel_id)) {
             let _ = tx.send(msg);
         }
     }
@@ -182,7 +349,7 @@ impl BroadcastManager {
 
     /// Send a voice transcription to subscribers
     pub fn send_voice_transcription(&self, response: &VoiceInferenceResponse) {
-        if let Some(msg) = WebMessage::from_voice_transcription(response) {
+        if let Some(msg) = WebMessage::from_voice_transcription(response, self.low_confidence_threshold) {
             // Send to global subscribers
             let _ = self.global_tx.send(msg.clone());
 
@@ -194,6 +361,7 @@ impl BroadcastManager {
             } = response
             {
                 let key = format!("voice:{}:{}", guild_id, channel_id);
+                self.record_history(&key, &msg);
                 // Create channel if subscribers exist, otherwise just try to send
                 if let Some(tx) = self.channel_txs.get(&key) {
                     let _ = tx.send(msg);
@@ -202,6 +370,168 @@ impl BroadcastManager {
         }
     }
 
+    /// Tell global and voice channel subscribers the inference connection
+    /// state changed
+    pub fn send_inference_status(&self, state: ConnectionState) {
+        let status = InferenceStatusMessage {
+            state,
+            timestamp: chrono::Utc::now().timestamp_millis(),
+        };
+        *self.inference_status.lock().unwrap() = Some(status.clone());
+
+        let msg = WebMessage::InferenceStatus(status);
+        let _ = self.global_tx.send(msg.clone());
+        for entry in self.channel_txs.iter() {
+            if entry.key().starts_with("voice:") {
+                let _ = entry.value().send(msg.clone());
+            }
+        }
+    }
+
+    /// Latest inference connection state, if it has been reported
+    pub fn inference_status(&self) -> Option<WebMessage> {
+        self.inference_status
+            .lock()
+            .unwrap()
+            .clone()
+            .map(WebMessage::InferenceStatus)
+    }
+
+    /// Recent messages for a text channel, tagged for replay (oldest first)
+    pub fn recent_channel_messages(&self, channel_id: &str) -> Vec<WebMessage> {
+        self.recent_messages(channel_id)
+    }
+
+    /// Recent messages for a text channel in `guild_id`, tagged for replay
+    /// (oldest first)
+    pub fn recent_text_messages(&self, guild_id: &str, channel_id: &str) -> Vec<WebMessage> {
+        let mut messages = self.recent_messages(channel_id);
+        messages.retain(|msg| matches!(msg, WebMessage::Translation(t) if t.guild_id == guild_id));
+        messages
+    }
+
+    /// Recent messages for a voice channel, tagged for replay (oldest first)
+    pub fn recent_voice_messages(&self, guild_id: &str, channel_id: &str) -> Vec<WebMessage> {
+        self.recent_messages(&format!("voice:{}:{}", guild_id, channel_id))
+    }
+
+    fn recent_messages(&self, key: &str) -> Vec<WebMessage> {
+        self.history
+            .get(key)
+            .map(|history| history.messages.iter().cloned().map(WebMessage::into_replay).collect())
+            .unwrap_or_default()
+    }
+
+    fn record_history(&self, key: &str, msg: &WebMessage) {
+        let mut history = self.history.entry(key.to_string()).or_insert_with(|| ChannelHistory {
+            messages: VecDeque::new(),
+            touched: Instant::now(),
+        });
+        if history.messages.len() >= REPLAY_HISTORY_SIZE {
+            history.messages.pop_front();
+        }
+        history.messages.push_back(msg.clone());
+        history.touched = Instant::now();
+    }
+
+    /// A text channel viewer disconnected. The channel's history is kept
+    /// for the replay grace period even if nobody else is watching.
+    pub fn release_text_channel(&self, channel_id: &str) {
+        self.touch_history(channel_id);
+    }
+
+    /// A voice channel viewer disconnected. The channel's history is kept
+    /// for the replay grace period even if nobody else is watching.
+    pub fn release_voice_channel(&self, guild_id: &str, channel_id: &str) {
+        self.touch_history(&format!("voice:{}:{}", guild_id, channel_id));
+    }
+
+    /// Translation was disabled in a text channel; drop its history now
+    pub fn forget_text_channel(&self, channel_id: &str) {
+        self.history.remove(channel_id);
+    }
+
+    /// The bot left a voice channel; drop its history now
+    pub fn forget_voice_channel(&self, guild_id: &str, channel_id: &str) {
+        self.history.remove(&format!("voice:{}:{}", guild_id, channel_id));
+    }
+
+    fn touch_history(&self, key: &str) {
+        if let Some(mut history) = self.history.get_mut(key) {
+            history.touched = Instant::now();
+        }
+    }
+
+    /// Drop channels nobody listens to, and the history of every channel
+    /// that has no viewer and hasn't been touched within the replay grace
+    /// period
+    pub fn prune_idle(&self, now: Instant) {
+        self.cleanup_empty_channels();
+
+        // History keys of channels someone is watching, by session
+        // (channel ID), by guild (text:guild:channel) or by voice channel
+        let watched: HashSet<String> = self
+            .channel_txs
+            .iter()
+            .map(|entry| match entry.key().strip_prefix("text:") {
+                Some(scoped) => scoped.rsplit(':').next().unwrap_or(scoped).to_string(),
+                None => entry.key().clone(),
+            })
+            .collect();
+        self.history.retain(|key, history| {
+            watched.contains(key) || now.saturating_duration_since(history.touched) < REPLAY_GRACE
+        });
+    }
+
+    /// Sweep idle history every [`HISTORY_PRUNE_INTERVAL`] in the background
+    pub fn spawn_pruner(self: &Arc<Self>) -> JoinHandle<()> {
+        let manager = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(HISTORY_PRUNE_INTERVAL);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                manager.prune_idle(Instant::now());
+            }
+        })
+    }
+
+    /// Voice channels in a guild with viewers or recent transcriptions, by channel ID
+    pub fn voice_channel_stats(&self, guild_id: &str) -> Vec<VoiceChannelStats> {
+        let prefix = format!("voice:{}:", guild_id);
+        let mut stats: std::collections::BTreeMap<String, VoiceChannelStats> =
+            std::collections::BTreeMap::new();
+        let idle = |channel_id: &str| VoiceChannelStats {
+            channel_id: channel_id.to_string(),
+            subscribers: 0,
+            last_activity: None,
+        };
+
+        for entry in self.channel_txs.iter() {
+            if let Some(channel_id) = entry.key().strip_prefix(&prefix) {
+                stats
+                    .entry(channel_id.to_string())
+                    .or_insert_with(|| idle(channel_id))
+                    .subscribers = entry.value().receiver_count();
+            }
+        }
+
+        for entry in self.history.iter() {
+            if let Some(channel_id) = entry.key().strip_prefix(&prefix) {
+                let last_activity = entry.value().messages.back().and_then(|msg| match msg {
+                    WebMessage::VoiceTranscription(v) => Some(v.timestamp),
+                    _ => None,
+                });
+                stats
+                    .entry(channel_id.to_string())
+                    .or_insert_with(|| idle(channel_id))
+                    .last_activity = last_activity;
+            }
+        }
+
+        stats.into_values().collect()
+    }
+
     /// Get number of global subscribers
     pub fn global_subscriber_count(&self) -> usize {
         self.global_tx.receiver_count()
@@ -242,7 +572,7 @@ mod tests {
             cached: false,
         };
 
-        manager.send_translation("123", "TestUser", "456", &translation);
+        manager.send_translation("g1", "123", "TestUser", "456", &translation);
 
         let msg = rx.try_recv().unwrap();
         match msg {
@@ -253,4 +583,307 @@ mod tests {
             _ => panic!("Expected Translation message"),
         }
     }
+
+    #[tokio::test]
+    async fn test_inference_status_reaches_voice_viewers_only() {
+        let manager = BroadcastManager::new();
+        assert!(manager.inference_status().is_none());
+        let mut voice_rx = manager.subscribe_voice_channel("g1", "vc1");
+        let mut text_rx = manager.subscribe_text_channel("g1", "ch1");
+
+        manager.send_inference_status(ConnectionState::Reconnecting);
+
+        let json = serde_json::to_value(voice_rx.try_recv().unwrap()).unwrap();
+        assert_eq!(json["type"], "inference_status");
+        assert_eq!(json["state"], "reconnecting");
+        assert!(text_rx.try_recv().is_err());
+        // Viewers joining later get the current state on connect
+        assert!(matches!(
+            manager.inference_status(),
+            Some(WebMessage::InferenceStatus(InferenceStatusMessage {
+                state: ConnectionState::Reconnecting,
+                ..
+            }))
+        ));
+    }
+
+    fn voice_result(text: &str) -> VoiceInferenceResponse {
+        VoiceInferenceResponse::Result {
+            guild_id: "g1".to_string(),
+            channel_id: "vc1".to_string(),
+            user_id: "u1".to_string(),
+            username: "Speaker".to_string(),
+            original_text: text.to_string(),
+            translated_text: text.to_uppercase(),
+            source_language: "en".to_string(),
+            target_language: "es".to_string(),
+            tts_audio: Some("UklGRg==".to_string()),
+            latency_ms: 120,
+            audio_hash: 0,
+            confidence: None,
+            started_at: None,
+            tts_format: None,
+            trace_id: None,
+        }
+    }
+
+    #[test]
+    fn test_voice_history_replayed_after_resubscribe() {
+        let manager = BroadcastManager::new();
+        {
+            let _rx = manager.subscribe_voice_channel("g1", "vc1");
+            manager.send_voice_transcription(&voice_result("hello"));
+        }
+        // Viewer dropped; channel senders are cleaned up but history remains
+        manager.cleanup_empty_channels();
+        manager.send_voice_transcription(&voice_result("world"));
+
+        let replay = manager.recent_voice_messages("g1", "vc1");
+        assert_eq!(replay.len(), 2);
+        match &replay[0] {
+            WebMessage::VoiceTranscription(v) => {
+                assert_eq!(v.original_text, "hello");
+                assert!(v.replay);
+                assert!(v.tts_audio.is_none());
+            }
+            _ => panic!("Expected VoiceTranscription message"),
+        }
+
+        let json = serde_json::to_value(&replay[1]).unwrap();
+        assert_eq!(json["replay"], true);
+    }
+
+    #[test]
+    fn test_detected_source_language_is_rendered_per_utterance() {
+        let manager = BroadcastManager::new();
+        let mut rx = manager.subscribe_voice_channel("g1", "vc1");
+
+        // Auto-detect channel: consecutive utterances in different languages
+        for lang in ["fr", "de"] {
+            let mut result = voice_result("bonjour");
+            if let VoiceInferenceResponse::Result { source_language, .. } = &mut result {
+                *source_language = lang.to_string();
+            }
+            manager.send_voice_transcription(&result);
+            let json = serde_json::to_value(rx.try_recv().unwrap()).unwrap();
+            assert_eq!(json["source_lang"], lang);
+        }
+    }
+
+    fn hello_translation() -> TranslationResult {
+        TranslationResult {
+            original_text: "Hello".to_string(),
+            translated_text: "Hola".to_string(),
+            source_lang: "en".to_string(),
+            target_lang: "es".to_string(),
+            cached: false,
+        }
+    }
+
+    #[test]
+    fn test_history_pruned_once_unwatched_and_idle() {
+        let manager = BroadcastManager::new();
+        let viewer = manager.subscribe_voice_channel("g1", "vc1");
+        manager.send_voice_transcription(&voice_result("hello"));
+        let later = Instant::now() + REPLAY_GRACE;
+
+        // Kept however quiet the channel is while someone watches
+        manager.prune_idle(later);
+        assert_eq!(manager.recent_voice_messages("g1", "vc1").len(), 1);
+
+        drop(viewer);
+        manager.release_voice_channel("g1", "vc1");
+        manager.prune_idle(Instant::now());
+        assert_eq!(manager.recent_voice_messages("g1", "vc1").len(), 1);
+        assert!(manager.channel_txs.is_empty());
+
+        manager.prune_idle(later + REPLAY_GRACE);
+        assert!(manager.history.is_empty());
+    }
+
+    #[test]
+    fn test_unwatched_channel_history_expires() {
+        let manager = BroadcastManager::new();
+        // Nobody has ever watched c1
+        manager.send_translation("g1", "c1", "TestUser", "456", &hello_translation());
+
+        manager.prune_idle(Instant::now());
+        assert_eq!(manager.recent_channel_messages("c1").len(), 1);
+        manager.prune_idle(Instant::now() + REPLAY_GRACE);
+        assert!(manager.history.is_empty());
+    }
+
+    #[test]
+    fn test_text_history_kept_while_either_view_is_open() {
+        let manager = BroadcastManager::new();
+        let session_viewer = manager.subscribe_channel("c1");
+        let guild_viewer = manager.subscribe_text_channel("g1", "c1");
+        manager.send_translation("g1", "c1", "TestUser", "456", &hello_translation());
+        let later = Instant::now() + REPLAY_GRACE;
+
+        drop(session_viewer);
+        manager.prune_idle(later);
+        assert_eq!(manager.recent_channel_messages("c1").len(), 1);
+
+        drop(guild_viewer);
+        manager.prune_idle(later);
+        assert!(manager.recent_channel_messages("c1").is_empty());
+    }
+
+    #[test]
+    fn test_disabled_channels_forget_history() {
+        let manager = BroadcastManager::new();
+        let _viewer = manager.subscribe_voice_channel("g1", "vc1");
+        manager.send_voice_transcription(&voice_result("hello"));
+        manager.send_translation("g1", "c1", "TestUser", "456", &hello_translation());
+
+        manager.forget_voice_channel("g1", "vc1");
+        manager.forget_text_channel("c1");
+        assert!(manager.history.is_empty());
+    }
+
+    #[test]
+    fn test_history_is_bounded() {
+        let manager = BroadcastManager::new();
+        for i in 0..REPLAY_HISTORY_SIZE + 5 {
+            manager.send_voice_transcription(&voice_result(&format!("line {}", i)));
+        }
+
+        let replay = manager.recent_voice_messages("g1", "vc1");
+        assert_eq!(replay.len(), REPLAY_HISTORY_SIZE);
+        match &replay[0] {
+            WebMessage::VoiceTranscription(v) => assert_eq!(v.original_text, "line 5"),
+            _ => panic!("Expected VoiceTranscription message"),
+        }
+    }
+
+    #[test]
+    fn test_low_confidence_voice_result_is_flagged() {
+        let manager = BroadcastManager::new().with_low_confidence_threshold(0.6);
+        let mut rx = manager.subscribe_voice_channel("g1", "vc1");
+
+        let mut garbled = voice_result("mmhm");
+        if let VoiceInferenceResponse::Result { confidence, .. } = &mut garbled {
+            *confidence = Some(0.4);
+        }
+        manager.send_voice_transcription(&garbled);
+        let json = serde_json::to_value(rx.try_recv().unwrap()).unwrap();
+        assert_eq!(json["low_confidence"], true);
+        assert!((json["confidence"].as_f64().unwrap() - 0.4).abs() < 1e-6);
+
+        manager.send_voice_transcription(&voice_result("clear speech"));
+        let json = serde_json::to_value(rx.try_recv().unwrap()).unwrap();
+        assert!(json.get("low_confidence").is_none());
+        assert!(json.get("confidence").is_none());
+    }
+
+    #[test]
+    fn test_voice_result_carries_speech_start() {
+        let manager = BroadcastManager::new();
+        let mut rx = manager.subscribe_voice_channel("g1", "vc1");
+
+        let started = chrono::DateTime::from_timestamp_millis(1_700_000_000_250).unwrap();
+        let mut result = voice_result("hello");
+        if let VoiceInferenceResponse::Result { started_at, .. } = &mut result {
+            *started_at = Some(started);
+        }
+        manager.send_voice_transcription(&result);
+        let json = serde_json::to_value(rx.try_recv().unwrap()).unwrap();
+        assert_eq!(json["started_at"], 1_700_000_000_250i64);
+
+        manager.send_voice_transcription(&voice_result("no start"));
+        let json = serde_json::to_value(rx.try_recv().unwrap()).unwrap();
+        assert!(json.get("started_at").is_none());
+    }
+
+    #[test]
+    fn test_voice_result_tts_format_for_browsers() {
+        use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+
+        let manager = BroadcastManager::new();
+        let mut rx = manager.subscribe_voice_channel("g1", "vc1");
+
+        // Untagged WAV is identified from its header
+        manager.send_voice_transcription(&voice_result("wav"));
+        let json = serde_json::to_value(rx.try_recv().unwrap()).unwrap();
+        assert_eq!(json["tts_format"], "wav");
+        assert_eq!(json["tts_audio"], "UklGRg==");
+
+        // Headerless PCM is wrapped in WAV so the browser can play it
+        let mut result = voice_result("pcm");
+        if let VoiceInferenceResponse::Result { tts_audio, tts_format, .. } = &mut result {
+            *tts_audio = Some("AQACAA==".to_string());
+            *tts_format = Some(TtsFormat::Pcm);
+        }
+        manager.send_voice_transcription(&result);
+        let json = serde_json::to_value(rx.try_recv().unwrap()).unwrap();
+        assert_eq!(json["tts_format"], "wav");
+        let wav = BASE64.decode(json["tts_audio"].as_str().unwrap()).unwrap();
+        assert_eq!(&wav[..4], b"RIFF");
+        assert_eq!(&wav[44..], &[1, 0, 2, 0]);
+    }
+
+    #[test]
+    fn test_live_messages_omit_replay_flag() {
+        let manager = BroadcastManager::new();
+        let mut rx = manager.subscribe_channel("123");
+        let translation = TranslationResult {
+            original_text: "Hello".to_string(),
+            translated_text: "Hola".to_string(),
+            source_lang: "en".to_string(),
+            target_lang: "es".to_string(),
+            cached: false,
+        };
+        manager.send_translation("g1", "123", "TestUser", "456", &translation);
+
+        let json = serde_json::to_value(rx.try_recv().unwrap()).unwrap();
+        assert!(json.get("replay").is_none());
+        assert_eq!(manager.recent_channel_messages("123").len(), 1);
+    }
+
+    #[test]
+    fn test_text_channel_subscription_is_guild_scoped() {
+        let manager = BroadcastManager::new();
+        let mut viewer = manager.subscribe_text_channel("g1", "123");
+        let mut wrong_guild = manager.subscribe_text_channel("g2", "123");
+        let translation = TranslationResult {
+            original_text: "Hello".to_string(),
+            translated_text: "Hola".to_string(),
+            source_lang: "en".to_string(),
+            target_lang: "es".to_string(),
+            cached: false,
+        };
+        manager.send_translation("g1", "123", "TestUser", "456", &translation);
+
+        match viewer.try_recv().unwrap() {
+            WebMessage::Translation(t) => assert_eq!(t.guild_id, "g1"),
+            _ => panic!("Expected Translation message"),
+        }
+        assert!(wrong_guild.try_recv().is_err());
+        assert_eq!(manager.recent_text_messages("g1", "123").len(), 1);
+        assert!(manager.recent_text_messages("g2", "123").is_empty());
+    }
+
+    #[test]
+    fn test_voice_channel_stats_per_guild() {
+        let manager = BroadcastManager::new();
+        let _viewer1 = manager.subscribe_voice_channel("g1", "vc1");
+        let _viewer2 = manager.subscribe_voice_channel("g1", "vc1");
+        let _other_guild = manager.subscribe_voice_channel("g2", "vc9");
+        // vc2 has had speech but nobody is watching
+        let mut result = voice_result("hi");
+        if let VoiceInferenceResponse::Result { channel_id, .. } = &mut result {
+            *channel_id = "vc2".to_string();
+        }
+        manager.send_voice_transcription(&result);
+
+        let stats = manager.voice_channel_stats("g1");
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].channel_id, "vc1");
+        assert_eq!(stats[0].subscribers, 2);
+        assert!(stats[0].last_activity.is_none());
+        assert_eq!(stats[1].channel_id, "vc2");
+        assert_eq!(stats[1].subscribers, 0);
+        assert!(stats[1].last_activity.is_some());
+    }
 }
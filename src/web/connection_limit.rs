@@ -0,0 +1,165 @@
+//! Caps on open web connections.
+//!
+//! Plain requests are bounded by a `tower` concurrency layer in
+//! [`create_router`](super::create_router). WebSocket viewers stay connected
+//! long after their upgrade request has finished, so each one also holds a
+//! [`ConnectionPermit`] for as long as its socket is open, bounded overall
+//! and per client address. Connections over either cap get a 503.
+
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+
+/// Open WebSocket connections, overall and per client address
+#[derive(Debug, Default)]
+pub struct ConnectionLimiter {
+    /// Most connections at once (0 for no limit)
+    max_total: usize,
+    /// Most connections from one address at once (0 for no limit)
+    max_per_ip: usize,
+    open: Mutex<OpenConnections>,
+}
+
+#[derive(Debug, Default)]
+struct OpenConnections {
+    total: usize,
+    per_ip: HashMap<IpAddr, usize>,
+}
+
+/// Why a connection was refused
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionRejected {
+    /// The server is at `web.max_connections`
+    ServerFull,
+    /// This address is at `web.max_ws_connections_per_ip`
+    TooManyFromAddress,
+}
+
+impl ConnectionLimiter {
+    pub fn new(max_total: usize, max_per_ip: usize) -> Self {
+        Self {
+            max_total,
+            max_per_ip,
+            open: Mutex::default(),
+        }
+    }
+
+    /// The overall cap, also applied to in-flight plain requests (0 for no
+    /// limit)
+    pub fn max_connections(&self) -> usize {
+        self.max_total
+    }
+
+    /// Reserve a slot for a connection from `ip`, held until the permit is
+    /// dropped
+    pub fn acquire(self: &Arc<Self>, ip: IpAddr) -> Result<ConnectionPermit, ConnectionRejected> {
+        let mut open = self.open.lock().unwrap();
+        if self.max_total > 0 && open.total >= self.max_total {
+            return Err(ConnectionRejected::ServerFull);
+        }
+        let from_ip = open.per_ip.get(&ip).copied().unwrap_or(0);
+        if self.max_per_ip > 0 && from_ip >= self.max_per_ip {
+            return Err(ConnectionRejected::TooManyFromAddress);
+        }
+        open.total += 1;
+        open.per_ip.insert(ip, from_ip + 1);
+        Ok(ConnectionPermit {
+            limiter: Arc::clone(self),
+            ip,
+        })
+    }
+
+    /// Connections currently open
+    pub fn open(&self) -> usize {
+        self.open.lock().unwrap().total
+    }
+
+    fn release(&self, ip: IpAddr) {
+        let mut open = self.open.lock().unwrap();
+        open.total = open.total.saturating_sub(1);
+        if let Some(count) = open.per_ip.get_mut(&ip) {
+            *count -= 1;
+            if *count == 0 {
+                open.per_ip.remove(&ip);
+            }
+        }
+    }
+}
+
+/// A connection's slot in its [`ConnectionLimiter`], freed on drop
+#[derive(Debug)]
+pub struct ConnectionPermit {
+    limiter: Arc<ConnectionLimiter>,
+    ip: IpAddr,
+}
+
+impl Drop for ConnectionPermit {
+    fn drop(&mut self) {
+        self.limiter.release(self.ip);
+    }
+}
+
+impl ConnectionRejected {
+    pub fn message(self) -> &'static str {
+        match self {
+            Self::ServerFull => "Too many viewers are connected right now; try again shortly",
+            Self::TooManyFromAddress => {
+                "Too many connections from your address; close other tabs and try again"
+            }
+        }
+    }
+}
+
+impl IntoResponse for ConnectionRejected {
+    fn into_response(self) -> Response {
+        (StatusCode::SERVICE_UNAVAILABLE, self.message()).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(last: u8) -> IpAddr {
+        IpAddr::from([10, 0, 0, last])
+    }
+
+    #[test]
+    fn test_total_and_per_ip_caps() {
+        let limiter = Arc::new(ConnectionLimiter::new(3, 2));
+        let a1 = limiter.acquire(ip(1)).unwrap();
+        let _a2 = limiter.acquire(ip(1)).unwrap();
+        assert_eq!(
+            limiter.acquire(ip(1)).unwrap_err(),
+            ConnectionRejected::TooManyFromAddress
+        );
+
+        let _b1 = limiter.acquire(ip(2)).unwrap();
+        assert_eq!(limiter.acquire(ip(3)).unwrap_err(), ConnectionRejected::ServerFull);
+
+        // Closing a connection frees its slot for anyone
+        drop(a1);
+        assert_eq!(limiter.open(), 2);
+        assert!(limiter.acquire(ip(3)).is_ok());
+    }
+
+    #[test]
+    fn test_zero_means_unlimited() {
+        let limiter = Arc::new(ConnectionLimiter::new(0, 0));
+        let permits: Vec<_> = (0..100).map(|_| limiter.acquire(ip(1)).unwrap()).collect();
+        assert_eq!(limiter.open(), 100);
+        drop(permits);
+        assert_eq!(limiter.open(), 0);
+        assert!(limiter.open.lock().unwrap().per_ip.is_empty());
+    }
+
+    #[test]
+    fn test_rejection_is_503() {
+        let response = ConnectionRejected::ServerFull.into_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+}
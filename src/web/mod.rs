@@ -1,9 +1,16 @@
+pub mod admin_routes;
 pub mod broadcast;
+pub mod connection_limit;
 pub mod routes;
+pub mod text_routes;
 pub mod voice_routes;
+pub mod webhook_signing;
 pub mod websocket;
 
+pub use admin_routes::AdminCacheState;
 pub use broadcast::BroadcastManager;
+pub use connection_limit::ConnectionLimiter;
 pub use routes::create_router;
 pub use voice_routes::VoiceAppState;
+pub use webhook_signing::WebhookSigner;
 pub use websocket::AppState;
@@ -1,17 +1,24 @@
 use crate::config::AppConfig;
-use crate::db::WebSessionRepo;
-use crate::translation::TranslationClient;
+use crate::db::{DbHealthSnapshot, DbPool, VoiceChannelRepo, WebSession, WebSessionRepo};
+use crate::web::admin_routes::{admin_cache_router, AdminCacheState};
+use crate::web::broadcast::VoiceChannelStats;
+use crate::translation::{ServiceState, TranslationClient};
+use crate::web::text_routes::{text_view, text_ws_handler};
 use crate::web::voice_routes::{voice_view, voice_ws_handler, VoiceAppState};
 use crate::web::websocket::AppState;
 use askama::Template;
 use axum::{
-    extract::{Path, State},
+    error_handling::HandleErrorLayer,
+    extract::{Path, Query, State},
+    http::StatusCode,
     response::{Html, IntoResponse, Json, Response},
     routing::get,
-    Router,
+    BoxError, Router,
 };
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use tower::limit::GlobalConcurrencyLimitLayer;
+use tower::ServiceBuilder;
 use tower_http::cors::{Any, CorsLayer};
 use tower_http::services::ServeDir;
 
@@ -30,6 +37,64 @@ pub async fn health() -> Json<HealthResponse> {
     })
 }
 
+/// Health check with the status of each dependency
+#[derive(Serialize)]
+pub struct HealthzResponse {
+    pub status: String,
+    pub version: String,
+    pub database: DbHealthSnapshot,
+}
+
+/// Dependency health endpoint; 503 while the database is unreachable
+pub async fn healthz(State(state): State<AppState>) -> (StatusCode, Json<HealthzResponse>) {
+    let database = state.db_health.snapshot();
+    let (code, status) = if database.healthy {
+        (StatusCode::OK, "ok")
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, "degraded")
+    };
+    (
+        code,
+        Json(HealthzResponse {
+            status: status.to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            database,
+        }),
+    )
+}
+
+/// Readiness response
+#[derive(Serialize)]
+pub struct ReadyzResponse {
+    pub ready: bool,
+    /// Inference service state: starting, ready or degraded
+    pub inference: ServiceState,
+    /// Features disabled at startup, with the reason
+    pub degraded_features: Vec<String>,
+}
+
+/// Readiness endpoint; 503 while the database is unreachable or the
+/// inference service hasn't come up since boot. A degraded inference
+/// service and features the bot started without (e.g. no Hugging Face
+/// token) are reported but don't make it unready.
+pub async fn readyz(State(state): State<AppState>) -> (StatusCode, Json<ReadyzResponse>) {
+    let inference = state.inference.state();
+    let ready = state.db_health.is_healthy() && inference != ServiceState::Starting;
+    let code = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (
+        code,
+        Json(ReadyzResponse {
+            ready,
+            inference,
+            degraded_features: state.degraded_features.to_vec(),
+        }),
+    )
+}
+
 /// Session info response
 #[derive(Serialize)]
 pub struct SessionInfo {
@@ -67,12 +132,98 @@ pub async fn cache_stats(
     Json(translator.cache_stats())
 }
 
+/// Prometheus metrics for the inference backend and voice pipeline
+pub async fn metrics(State(translator): State<Arc<TranslationClient>>) -> impl IntoResponse {
+    (
+        [(axum::http::header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        render_metrics(
+            &translator.rate_limit(),
+            &translator.cache_stats(),
+            crate::metrics::voice_latency(),
+            crate::metrics::voice_round_trip(),
+            crate::metrics::voice_frame_bytes(),
+            crate::metrics::voice_dead_letters(),
+        ),
+    )
+}
+
+fn render_metrics(
+    rate_limit: &crate::translation::RateLimitSnapshot,
+    cache: &crate::translation::CacheStats,
+    voice_latency: &crate::metrics::Histogram,
+    voice_round_trip: &crate::metrics::Histogram,
+    voice_frame_bytes: &crate::metrics::Histogram,
+    voice_dead_letters: &crate::metrics::DeadLetters,
+) -> String {
+    use std::fmt::Write;
+
+    let mut out = String::new();
+    let mut metric = |name: &str, help: &str, kind: &str, value: Option<f64>| {
+        let Some(value) = value else { return };
+        let _ = writeln!(out, "# HELP {} {}", name, help);
+        let _ = writeln!(out, "# TYPE {} {}", name, kind);
+        let _ = writeln!(out, "{} {}", name, value);
+    };
+    metric(
+        "linguabridge_inference_rate_limit",
+        "Requests per window allowed by the inference service",
+        "gauge",
+        rate_limit.limit.map(|v| v as f64),
+    );
+    metric(
+        "linguabridge_inference_rate_limit_remaining",
+        "Requests left in the inference service's current window",
+        "gauge",
+        rate_limit.remaining.map(|v| v as f64),
+    );
+    metric(
+        "linguabridge_inference_rate_limit_blocked_seconds",
+        "Seconds until the inference service accepts requests again",
+        "gauge",
+        Some(rate_limit.blocked_for_secs),
+    );
+    metric(
+        "linguabridge_inference_throttled_total",
+        "Throttled responses from the inference service",
+        "counter",
+        Some(rate_limit.throttled_responses as f64),
+    );
+    metric(
+        "linguabridge_translation_cache_entries",
+        "Entries in the translation cache",
+        "gauge",
+        Some(cache.total_entries as f64),
+    );
+    voice_latency.render(
+        "linguabridge_voice_end_to_end_latency_seconds",
+        "Seconds from the end of an utterance until its translation reaches the web feed",
+        &mut out,
+    );
+    voice_round_trip.render(
+        "linguabridge_voice_inference_round_trip_seconds",
+        "Seconds from sending a voice segment to the inference service until its result arrives",
+        &mut out,
+    );
+    voice_frame_bytes.render(
+        "linguabridge_voice_frame_bytes",
+        "Size in bytes of audio frames sent to the voice inference service",
+        &mut out,
+    );
+    voice_dead_letters.render(
+        "linguabridge_voice_dead_letters",
+        "Voice segments that never got a result (dropped, failed or timed out)",
+        &mut out,
+    );
+    out
+}
+
 /// Askama template for the web view
 #[derive(Template)]
 #[template(path = "web_view.html")]
-struct WebViewTemplate {
-    session_id: String,
-    ws_url: String,
+pub(crate) struct WebViewTemplate {
+    pub(crate) ws_url: String,
+    /// Feed path appended to `ws_url`
+    pub(crate) ws_path: String,
 }
 
 /// Serve the web view HTML
@@ -84,7 +235,112 @@ pub async fn web_view(Path(session_id): Path<String>) -> Response {
         .replace("http://", "ws://")
         .replace("https://", "wss://");
 
-    let template = WebViewTemplate { session_id, ws_url };
+    let template = WebViewTemplate {
+        ws_path: format!("/ws/{}", session_id),
+        ws_url,
+    };
+    Html(template.render().unwrap_or_default()).into_response()
+}
+
+/// A voice channel counts as live if it had speech within this window
+const LIVE_WINDOW_MS: i64 = 5 * 60 * 1000;
+
+/// One voice channel row on the guild dashboard
+struct GuildChannelRow {
+    channel_id: String,
+    url: String,
+    subscribers: usize,
+    live: bool,
+    last_activity: String,
+}
+
+/// Askama template for the guild voice dashboard
+#[derive(Template)]
+#[template(path = "guild_view.html")]
+struct GuildViewTemplate {
+    live_count: usize,
+    channels: Vec<GuildChannelRow>,
+}
+
+/// Query parameters for the guild dashboard
+#[derive(Deserialize)]
+pub struct GuildViewQuery {
+    /// Web session issued by `/webview`; must belong to the guild
+    pub session: Option<String>,
+}
+
+/// The web session `session_id`, if it was issued for `guild_id`; otherwise
+/// the response refusing the request
+pub(crate) async fn guild_session(
+    pool: &DbPool,
+    guild_id: &str,
+    session_id: Option<&str>,
+) -> Result<WebSession, Response> {
+    let Some(session_id) = session_id else {
+        return Err((StatusCode::UNAUTHORIZED, "Missing session").into_response());
+    };
+    match WebSessionRepo::get_by_session_id(pool, session_id).await {
+        Ok(Some(session)) if session.guild_id == guild_id => Ok(session),
+        Ok(Some(_)) => {
+            Err((StatusCode::FORBIDDEN, "Session is not valid for this server").into_response())
+        }
+        Ok(None) => Err((StatusCode::UNAUTHORIZED, "Invalid or expired session").into_response()),
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to look up web session");
+            Err(StatusCode::INTERNAL_SERVER_ERROR.into_response())
+        }
+    }
+}
+
+/// List a guild's voice channels with transcription, linking to each live view
+pub async fn guild_view(
+    Path(guild_id): Path<String>,
+    Query(query): Query<GuildViewQuery>,
+    State(state): State<AppState>,
+) -> Response {
+    if let Err(refusal) = guild_session(&state.pool, &guild_id, query.session.as_deref()).await {
+        return refusal;
+    }
+
+    let mut stats = state.broadcast.voice_channel_stats(&guild_id);
+    // Channels configured for translation show up even before anyone speaks
+    match VoiceChannelRepo::get_by_guild(&state.pool, &guild_id).await {
+        Ok(settings) => {
+            for setting in settings.into_iter().filter(|s| s.enabled) {
+                if !stats.iter().any(|s| s.channel_id == setting.voice_channel_id) {
+                    stats.push(VoiceChannelStats {
+                        channel_id: setting.voice_channel_id,
+                        subscribers: 0,
+                        last_activity: None,
+                    });
+                }
+            }
+        }
+        Err(e) => tracing::warn!(error = %e, "Failed to load voice channel settings"),
+    }
+
+    let now = chrono::Utc::now().timestamp_millis();
+    let mut channels: Vec<GuildChannelRow> = stats
+        .into_iter()
+        .map(|s| GuildChannelRow {
+            url: format!("/voice/{}/{}", guild_id, s.channel_id),
+            live: s.subscribers > 0
+                || s.last_activity.is_some_and(|t| now - t < LIVE_WINDOW_MS),
+            last_activity: match s.last_activity {
+                Some(t) => format!("Last speech {}m ago", (now - t).max(0) / 60_000),
+                None => "No speech yet".to_string(),
+            },
+            subscribers: s.subscribers,
+            channel_id: s.channel_id,
+        })
+        .collect();
+    // Live channels first
+    channels.sort_by_key(|c| !c.live);
+
+    let template = GuildViewTemplate {
+        live_count: channels.iter().filter(|c| c.live).count(),
+        channels,
+    };
     Html(template.render().unwrap_or_default()).into_response()
 }
 
@@ -93,6 +349,7 @@ mod tests {
     use super::*;
     use crate::web::broadcast::BroadcastManager;
     use crate::db::queries::setup_test_db;
+    use crate::db::DbHealth;
 
     #[tokio::test]
     async fn test_health_returns_ok() {
@@ -107,6 +364,71 @@ mod tests {
         assert_eq!(resp.0.version, env!("CARGO_PKG_VERSION"));
     }
 
+    #[tokio::test]
+    async fn test_healthz_reports_database_outage() {
+        let state = AppState {
+            pool: setup_test_db().await,
+            broadcast: Arc::new(BroadcastManager::new()),
+            db_health: Arc::new(DbHealth::new()),
+            degraded_features: Arc::default(),
+            inference: Arc::default(),
+            connections: Arc::default(),
+        };
+        let (code, resp) = healthz(State(state.clone())).await;
+        assert_eq!(code, StatusCode::OK);
+        assert!(resp.0.database.healthy);
+
+        state.db_health.record_failure("pool timed out".to_string(), chrono::Utc::now());
+        let (code, resp) = healthz(State(state)).await;
+        assert_eq!(code, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(resp.0.status, "degraded");
+        assert_eq!(resp.0.database.last_error.as_deref(), Some("pool timed out"));
+    }
+
+    #[tokio::test]
+    async fn test_readyz_lists_degraded_features_while_ready() {
+        let state = AppState {
+            pool: setup_test_db().await,
+            broadcast: Arc::new(BroadcastManager::new()),
+            db_health: Arc::new(DbHealth::new()),
+            degraded_features: Arc::new(vec!["speaker diarization (no hf_token)".to_string()]),
+            inference: Arc::default(),
+            connections: Arc::default(),
+        };
+        state.inference.record_check(Some(true));
+        let (code, resp) = readyz(State(state.clone())).await;
+        assert_eq!(code, StatusCode::OK);
+        assert!(resp.0.ready);
+        assert_eq!(resp.0.degraded_features.len(), 1);
+
+        state.db_health.record_failure("closed".to_string(), chrono::Utc::now());
+        let (code, resp) = readyz(State(state)).await;
+        assert_eq!(code, StatusCode::SERVICE_UNAVAILABLE);
+        assert!(!resp.0.ready);
+    }
+
+    #[tokio::test]
+    async fn test_readyz_waits_for_inference_to_start() {
+        let state = AppState {
+            pool: setup_test_db().await,
+            broadcast: Arc::new(BroadcastManager::new()),
+            db_health: Arc::new(DbHealth::new()),
+            degraded_features: Arc::default(),
+            inference: Arc::default(),
+            connections: Arc::default(),
+        };
+        let (code, resp) = readyz(State(state.clone())).await;
+        assert_eq!(code, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(resp.0.inference, ServiceState::Starting);
+
+        // Once up, a failing inference service is reported but not unready
+        state.inference.record_check(Some(true));
+        state.inference.record_check(None);
+        let (code, resp) = readyz(State(state)).await;
+        assert_eq!(code, StatusCode::OK);
+        assert_eq!(resp.0.inference, ServiceState::Degraded);
+    }
+
     #[tokio::test]
     async fn test_get_session_info_valid() {
         let pool = setup_test_db().await;
@@ -114,6 +436,10 @@ mod tests {
         let state = AppState {
             pool: pool.clone(),
             broadcast,
+            db_health: Arc::new(DbHealth::new()),
+            degraded_features: Arc::default(),
+            inference: Arc::default(),
+            connections: Arc::default(),
         };
 
         // Create a session first
@@ -146,6 +472,10 @@ mod tests {
         let state = AppState {
             pool,
             broadcast,
+            db_health: Arc::new(DbHealth::new()),
+            degraded_features: Arc::default(),
+            inference: Arc::default(),
+            connections: Arc::default(),
         };
 
         let resp = get_session_info(
@@ -160,6 +490,85 @@ mod tests {
         assert!(resp.0.expires_at.is_none());
     }
 
+    async fn guild_view_body(state: AppState, guild_id: &str, session: Option<String>) -> (StatusCode, String) {
+        let resp = guild_view(
+            Path(guild_id.to_string()),
+            Query(GuildViewQuery { session }),
+            State(state),
+        )
+        .await;
+        let status = resp.status();
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_guild_view_lists_live_voice_channels() {
+        let pool = setup_test_db().await;
+        let broadcast = Arc::new(BroadcastManager::new());
+        let _viewer_a = broadcast.subscribe_voice_channel("g1", "vc1");
+        let _viewer_b = broadcast.subscribe_voice_channel("g1", "vc2");
+        let _elsewhere = broadcast.subscribe_voice_channel("g2", "vc3");
+        let state = AppState {
+            pool: pool.clone(),
+            broadcast,
+            db_health: Arc::new(DbHealth::new()),
+            degraded_features: Arc::default(),
+            inference: Arc::default(),
+            connections: Arc::default(),
+        };
+
+        let session = crate::db::WebSessionRepo::create(
+            &pool,
+            crate::db::models::NewWebSession {
+                user_id: "u1".to_string(),
+                guild_id: "g1".to_string(),
+                channel_id: None,
+            },
+            24,
+        )
+        .await
+        .unwrap();
+
+        let (status, body) = guild_view_body(state, "g1", Some(session.session_id)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.contains("2 channels live"));
+        assert!(body.contains("href=\"/voice/g1/vc1\""));
+        assert!(body.contains("href=\"/voice/g1/vc2\""));
+        assert!(!body.contains("vc3"));
+    }
+
+    #[tokio::test]
+    async fn test_guild_view_requires_session_for_guild() {
+        let pool = setup_test_db().await;
+        let state = AppState {
+            pool: pool.clone(),
+            broadcast: Arc::new(BroadcastManager::new()),
+            db_health: Arc::new(DbHealth::new()),
+            degraded_features: Arc::default(),
+            inference: Arc::default(),
+            connections: Arc::default(),
+        };
+        let session = crate::db::WebSessionRepo::create(
+            &pool,
+            crate::db::models::NewWebSession {
+                user_id: "u1".to_string(),
+                guild_id: "other".to_string(),
+                channel_id: None,
+            },
+            24,
+        )
+        .await
+        .unwrap();
+
+        let (status, _) = guild_view_body(state.clone(), "g1", None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, _) = guild_view_body(state.clone(), "g1", Some("bogus".to_string())).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, _) = guild_view_body(state, "g1", Some(session.session_id)).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+
     #[test]
     fn test_health_response_serialize() {
         let resp = HealthResponse {
@@ -184,6 +593,53 @@ mod tests {
         assert!(json.contains("g123"));
     }
 
+    #[test]
+    fn test_metrics_report_rate_limit_quota() {
+        let rate_limit = crate::translation::RateLimitSnapshot {
+            limit: None,
+            remaining: Some(7),
+            blocked_for_secs: 2.5,
+            throttled_responses: 3,
+        };
+        let cache = crate::translation::CacheStats {
+            total_entries: 10,
+            expired_entries: 0,
+            max_size: 100,
+            ttl_secs: 60,
+            hits: 0,
+            misses: 0,
+            hit_rate: 0.0,
+        };
+        let voice_latency = crate::metrics::Histogram::new(crate::metrics::VOICE_LATENCY_BUCKETS);
+        voice_latency.observe(std::time::Duration::from_millis(1200));
+        let voice_round_trip = crate::metrics::Histogram::new(crate::metrics::VOICE_LATENCY_BUCKETS);
+        let voice_frame_bytes =
+            crate::metrics::Histogram::new(crate::metrics::VOICE_FRAME_SIZE_BUCKETS);
+        voice_frame_bytes.observe_value(96_000.0);
+        let voice_dead_letters = crate::metrics::DeadLetters::default();
+        let trace_id = uuid::Uuid::new_v4();
+        voice_dead_letters.record(trace_id);
+        let text = render_metrics(
+            &rate_limit,
+            &cache,
+            &voice_latency,
+            &voice_round_trip,
+            &voice_frame_bytes,
+            &voice_dead_letters,
+        );
+
+        assert!(text.contains("linguabridge_inference_rate_limit_remaining 7\n"));
+        assert!(text.contains("linguabridge_inference_rate_limit_blocked_seconds 2.5\n"));
+        assert!(text.contains("# TYPE linguabridge_inference_throttled_total counter"));
+        // Unknown values are left out rather than reported as zero
+        assert!(!text.contains("linguabridge_inference_rate_limit "));
+        assert!(text.contains("linguabridge_voice_end_to_end_latency_seconds_bucket{le=\"1.5\"} 1\n"));
+        assert!(text.contains("linguabridge_voice_inference_round_trip_seconds_count 0\n"));
+        assert!(text.contains("linguabridge_voice_frame_bytes_bucket{le=\"131072\"} 1\n"));
+        assert!(text.contains("linguabridge_voice_dead_letters_total 1\n"));
+        assert!(text.contains(&format!("trace_id=\"{}\"", trace_id)));
+    }
+
     #[test]
     fn test_session_info_serialize_invalid() {
         let info = SessionInfo {
@@ -199,7 +655,11 @@ mod tests {
 }
 
 /// Create the web router
-pub fn create_router(state: AppState, translator: Arc<TranslationClient>) -> Router {
+pub fn create_router(
+    state: AppState,
+    translator: Arc<TranslationClient>,
+    admin: AdminCacheState,
+) -> Router {
     let cors = CorsLayer::new()
         .allow_origin(Any)
         .allow_methods(Any)
@@ -208,14 +668,22 @@ pub fn create_router(state: AppState, translator: Arc<TranslationClient>) -> Rou
     // Voice routes state
     let voice_state = VoiceAppState {
         broadcast: state.broadcast.clone(),
+        connections: state.connections.clone(),
     };
+    let max_connections = state.connections.max_connections();
 
-    Router::new()
+    let router = Router::new()
         .route("/health", get(health))
+        .route("/healthz", get(healthz))
+        .route("/readyz", get(readyz))
         // Text channel translation routes (session-based)
         .route("/view/{session_id}", get(web_view))
         .route("/ws/{session_id}", get(crate::web::websocket::ws_handler))
         .route("/api/session/{session_id}", get(get_session_info))
+        .route("/guild/{guild_id}", get(guild_view))
+        // Text channel routes (guild session)
+        .route("/text/{guild_id}/{channel_id}", get(text_view))
+        .route("/text/{guild_id}/{channel_id}/ws", get(text_ws_handler))
         .with_state(state)
         // Voice channel routes (public)
         .route("/voice/{guild_id}/{channel_id}", get(voice_view))
@@ -225,8 +693,26 @@ pub fn create_router(state: AppState, translator: Arc<TranslationClient>) -> Rou
         )
         .route(
             "/api/cache/stats",
-            get(cache_stats).with_state(translator),
+            get(cache_stats).with_state(translator.clone()),
         )
+        .route("/metrics", get(metrics).with_state(translator))
+        .merge(admin_cache_router(admin))
         .nest_service("/static", ServeDir::new("static"))
-        .layer(cors)
+        .layer(cors);
+
+    if max_connections == 0 {
+        return router;
+    }
+    // Requests beyond the limit are refused straight away rather than queued
+    router.layer(
+        ServiceBuilder::new()
+            .layer(HandleErrorLayer::new(|_: BoxError| async {
+                (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    "The server is handling too many requests; try again shortly",
+                )
+            }))
+            .load_shed()
+            .layer(GlobalConcurrencyLimitLayer::new(max_connections)),
+    )
 }
@@ -0,0 +1,231 @@
+//! Text channel web routes.
+//!
+//! Live view of a text channel's translations, for anyone holding a web
+//! session for its guild (issued by `/webview`).
+//! Format: /text/{guild_id}/{channel_id}?session={session_id}
+
+use crate::config::AppConfig;
+use crate::db::{WebSession, WebSessionRepo};
+use crate::web::routes::{guild_session, GuildViewQuery, WebViewTemplate};
+use crate::web::websocket::AppState;
+use askama::Template;
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        ConnectInfo, Path, Query, State,
+    },
+    http::StatusCode,
+    response::{Html, IntoResponse, Response},
+};
+use futures::{SinkExt, StreamExt};
+use std::net::SocketAddr;
+use tokio::time::{interval, Duration};
+use tracing::{debug, info, warn};
+
+/// How often an open feed re-checks that its session is still valid
+const SESSION_RECHECK_INTERVAL: Duration = Duration::from_secs(15);
+
+/// The session for `guild_id`, refused if it is scoped to another channel
+async fn channel_session(
+    state: &AppState,
+    guild_id: &str,
+    channel_id: &str,
+    session_id: Option<&str>,
+) -> Result<WebSession, Response> {
+    let session = guild_session(&state.pool, guild_id, session_id).await?;
+    match session.channel_id.as_deref() {
+        Some(scoped) if scoped != channel_id => {
+            Err((StatusCode::FORBIDDEN, "Session is not valid for this channel").into_response())
+        }
+        _ => Ok(session),
+    }
+}
+
+/// Serve the text channel web view
+pub async fn text_view(
+    Path((guild_id, channel_id)): Path<(String, String)>,
+    Query(query): Query<GuildViewQuery>,
+    State(state): State<AppState>,
+) -> Response {
+    let session = match channel_session(&state, &guild_id, &channel_id, query.session.as_deref()).await {
+        Ok(session) => session,
+        Err(refusal) => return refusal,
+    };
+
+    let ws_url = AppConfig::get()
+        .web
+        .public_url
+        .replace("http://", "ws://")
+        .replace("https://", "wss://");
+    let template = WebViewTemplate {
+        ws_url,
+        ws_path: format!(
+            "/text/{}/{}/ws?session={}",
+            guild_id, channel_id, session.session_id
+        ),
+    };
+    Html(template.render().unwrap_or_default()).into_response()
+}
+
+/// WebSocket handler for text channel translations
+pub async fn text_ws_handler(
+    ws: WebSocketUpgrade,
+    Path((guild_id, channel_id)): Path<(String, String)>,
+    Query(query): Query<GuildViewQuery>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(state): State<AppState>,
+) -> Response {
+    let session = match channel_session(&state, &guild_id, &channel_id, query.session.as_deref()).await {
+        Ok(session) => session,
+        Err(refusal) => return refusal,
+    };
+    let permit = match state.connections.acquire(addr.ip()) {
+        Ok(permit) => permit,
+        Err(rejected) => return rejected.into_response(),
+    };
+    ws.on_upgrade(move |socket| async move {
+        handle_text_socket(socket, guild_id, channel_id, session, state).await;
+        drop(permit);
+    })
+}
+
+/// Forward a text channel's translations until the client leaves or the
+/// session ends
+async fn handle_text_socket(
+    socket: WebSocket,
+    guild_id: String,
+    channel_id: String,
+    session: WebSession,
+    state: AppState,
+) {
+    info!(guild_id, channel_id, "Text WebSocket client connected");
+
+    let (mut sender, mut receiver) = socket.split();
+    let mut broadcast_rx = state.broadcast.subscribe_text_channel(&guild_id, &channel_id);
+
+    let welcome = serde_json::json!({
+        "type": "connected",
+        "guild_id": guild_id,
+        "channel_id": channel_id,
+    });
+    if sender.send(Message::Text(welcome.to_string().into())).await.is_err() {
+        state.broadcast.release_text_channel(&channel_id);
+        return;
+    }
+
+    // Replay recent history so a reconnecting viewer doesn't see a silent gap
+    for msg in state.broadcast.recent_text_messages(&guild_id, &channel_id) {
+        if let Ok(json) = serde_json::to_string(&msg) {
+            if sender.send(Message::Text(json.into())).await.is_err() {
+                state.broadcast.release_text_channel(&channel_id);
+                return;
+            }
+        }
+    }
+
+    let mut ping_interval = interval(Duration::from_secs(30));
+    let mut recheck = interval(SESSION_RECHECK_INTERVAL);
+    recheck.tick().await;
+
+    loop {
+        tokio::select! {
+            result = broadcast_rx.recv() => {
+                match result {
+                    Ok(msg) => match serde_json::to_string(&msg) {
+                        Ok(json) => {
+                            if sender.send(Message::Text(json.into())).await.is_err() {
+                                debug!("Failed to send message, client disconnected");
+                                break;
+                            }
+                        }
+                        Err(e) => warn!(error = %e, "Failed to serialize message"),
+                    },
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
+                        warn!(skipped = n, "Client lagged, skipped messages");
+                    }
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                }
+            }
+
+            msg = receiver.next() => {
+                match msg {
+                    Some(Ok(Message::Text(text))) if text.as_str() == "ping" => {
+                        let _ = sender.send(Message::Text("pong".into())).await;
+                    }
+                    Some(Ok(Message::Ping(data))) => {
+                        let _ = sender.send(Message::Pong(data)).await;
+                    }
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    _ => {}
+                }
+            }
+
+            _ = recheck.tick() => {
+                if let Ok(None) = WebSessionRepo::get_by_session_id(&state.pool, &session.session_id).await {
+                    let ended = serde_json::json!({"type": "error", "message": "Session revoked or expired"});
+                    let _ = sender.send(Message::Text(ended.to_string().into())).await;
+                    let _ = sender.send(Message::Close(None)).await;
+                    break;
+                }
+            }
+
+            _ = ping_interval.tick() => {
+                if sender.send(Message::Ping(vec![].into())).await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+
+    state.broadcast.release_text_channel(&channel_id);
+    info!(guild_id, channel_id, "Text WebSocket client disconnected");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{queries::setup_test_db, DbHealth, NewWebSession};
+    use crate::web::BroadcastManager;
+    use std::sync::Arc;
+
+    async fn state_with_session(channel_id: Option<&str>) -> (AppState, String) {
+        let pool = setup_test_db().await;
+        let new_session = NewWebSession {
+            user_id: "u1".to_string(),
+            guild_id: "g1".to_string(),
+            channel_id: channel_id.map(str::to_string),
+        };
+        let session = WebSessionRepo::create(&pool, new_session, 1).await.unwrap();
+        let state = AppState {
+            pool,
+            broadcast: Arc::new(BroadcastManager::new()),
+            db_health: Arc::new(DbHealth::new()),
+            degraded_features: Arc::default(),
+            inference: Arc::default(),
+            connections: Arc::default(),
+        };
+        (state, session.session_id)
+    }
+
+    #[tokio::test]
+    async fn test_text_view_requires_guild_session() {
+        let (state, session_id) = state_with_session(None).await;
+
+        let ok = channel_session(&state, "g1", "c1", Some(&session_id)).await;
+        assert!(ok.is_ok());
+
+        let missing = channel_session(&state, "g1", "c1", None).await.unwrap_err();
+        assert_eq!(missing.status(), StatusCode::UNAUTHORIZED);
+        let other_guild = channel_session(&state, "g2", "c1", Some(&session_id)).await.unwrap_err();
+        assert_eq!(other_guild.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_channel_scoped_session_limited_to_its_channel() {
+        let (state, session_id) = state_with_session(Some("c1")).await;
+
+        assert!(channel_session(&state, "g1", "c1", Some(&session_id)).await.is_ok());
+        let other = channel_session(&state, "g1", "c2", Some(&session_id)).await.unwrap_err();
+        assert_eq!(other.status(), StatusCode::FORBIDDEN);
+    }
+}
@@ -5,15 +5,17 @@
 
 use crate::config::AppConfig;
 use crate::web::broadcast::BroadcastManager;
+use crate::web::connection_limit::ConnectionLimiter;
 use askama::Template;
 use axum::{
     extract::{
         ws::{Message, WebSocket, WebSocketUpgrade},
-        Path, State,
+        ConnectInfo, Path, State,
     },
     response::{Html, IntoResponse, Response},
 };
 use futures::{SinkExt, StreamExt};
+use std::net::SocketAddr;
 use std::sync::Arc;
 use tokio::time::{interval, Duration};
 use tracing::{debug, error, info, warn};
@@ -22,6 +24,8 @@ use tracing::{debug, error, info, warn};
 #[derive(Clone)]
 pub struct VoiceAppState {
     pub broadcast: Arc<BroadcastManager>,
+    /// Shared with the text feeds, so viewers of both count together
+    pub connections: Arc<ConnectionLimiter>,
 }
 
 /// Askama template for the voice view
@@ -54,9 +58,17 @@ pub async fn voice_view(Path((guild_id, channel_id)): Path<(String, String)>) ->
 pub async fn voice_ws_handler(
     ws: WebSocketUpgrade,
     Path((guild_id, channel_id)): Path<(String, String)>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
     State(state): State<VoiceAppState>,
 ) -> Response {
-    ws.on_upgrade(move |socket| handle_voice_socket(socket, guild_id, channel_id, state))
+    let permit = match state.connections.acquire(addr.ip()) {
+        Ok(permit) => permit,
+        Err(rejected) => return rejected.into_response(),
+    };
+    ws.on_upgrade(move |socket| async move {
+        handle_voice_socket(socket, guild_id, channel_id, state).await;
+        drop(permit);
+    })
 }
 
 /// Handle a voice channel WebSocket connection
@@ -84,9 +96,32 @@ async fn handle_voice_socket(
     });
     if let Err(e) = sender.send(Message::Text(welcome.to_string().into())).await {
         error!(error = %e, "Failed to send welcome message");
+        state.broadcast.release_voice_channel(&guild_id, &channel_id);
         return;
     }
 
+    // Let the viewer know straight away if inference is down
+    if let Some(status) = state.broadcast.inference_status() {
+        if let Ok(json) = serde_json::to_string(&status) {
+            if let Err(e) = sender.send(Message::Text(json.into())).await {
+                debug!(error = %e, "Failed to send inference status, client disconnected");
+                state.broadcast.release_voice_channel(&guild_id, &channel_id);
+                return;
+            }
+        }
+    }
+
+    // Replay recent history so a reconnecting viewer doesn't see a silent gap
+    for msg in state.broadcast.recent_voice_messages(&guild_id, &channel_id) {
+        if let Ok(json) = serde_json::to_string(&msg) {
+            if let Err(e) = sender.send(Message::Text(json.into())).await {
+                debug!(error = %e, "Failed to send replay, client disconnected");
+                state.broadcast.release_voice_channel(&guild_id, &channel_id);
+                return;
+            }
+        }
+    }
+
     // Ping interval for keepalive
     let mut ping_interval = interval(Duration::from_secs(30));
 
@@ -156,6 +191,7 @@ async fn handle_voice_socket(
         }
     }
 
+    state.broadcast.release_voice_channel(&guild_id, &channel_id);
     info!(guild_id, channel_id, "Voice WebSocket client disconnected");
 }
 
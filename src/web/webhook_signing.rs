@@ -0,0 +1,122 @@
+//! HMAC signatures for translations forwarded to outside webhooks.
+//!
+//! A receiver shares a secret with the bot and checks each POST before
+//! trusting it. Two headers are sent:
+//!
+//! - `X-LinguaBridge-Timestamp`: Unix time in seconds when the payload was signed
+//! - `X-LinguaBridge-Signature`: `v1=` followed by the lowercase hex
+//!   HMAC-SHA256 of `"{timestamp}.{body}"` under the shared secret
+//!
+//! To verify, recompute the HMAC over the timestamp header, a `.` and the raw
+//! request body (before any JSON parsing), compare it in constant time, and
+//! reject timestamps more than [`MAX_SIGNATURE_AGE_SECS`] from your clock.
+//! The timestamp is part of the signed content, so a captured request can't
+//! be replayed later with a fresh one.
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+pub use super::admin_routes::MAX_SIGNATURE_AGE_SECS;
+
+pub const TIMESTAMP_HEADER: &str = "x-linguabridge-timestamp";
+pub const SIGNATURE_HEADER: &str = "x-linguabridge-signature";
+
+/// Version tag of the signing scheme, prefixed to each signature
+const SCHEME: &str = "v1=";
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Signs forwarded payloads with a shared secret
+#[derive(Clone)]
+pub struct WebhookSigner {
+    secret: Vec<u8>,
+}
+
+impl std::fmt::Debug for WebhookSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebhookSigner").finish_non_exhaustive()
+    }
+}
+
+impl WebhookSigner {
+    pub fn new(secret: impl Into<Vec<u8>>) -> Self {
+        Self { secret: secret.into() }
+    }
+
+    fn mac(&self, timestamp: i64, body: &[u8]) -> HmacSha256 {
+        // HMAC accepts keys of any length
+        let mut mac = HmacSha256::new_from_slice(&self.secret).expect("HMAC key of any size");
+        mac.update(timestamp.to_string().as_bytes());
+        mac.update(b".");
+        mac.update(body);
+        mac
+    }
+
+    /// Value of the signature header for `body` sent at Unix time `timestamp`
+    pub fn sign(&self, timestamp: i64, body: &[u8]) -> String {
+        let digest = self.mac(timestamp, body).finalize().into_bytes();
+        let hex: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+        format!("{}{}", SCHEME, hex)
+    }
+
+    /// Both headers for `body`, signed at Unix time `now`
+    pub fn headers(&self, now: i64, body: &[u8]) -> [(&'static str, String); 2] {
+        [
+            (TIMESTAMP_HEADER, now.to_string()),
+            (SIGNATURE_HEADER, self.sign(now, body)),
+        ]
+    }
+
+    /// Whether `signature` is this secret's signature of `body` at
+    /// `timestamp`, and the timestamp is within the allowed age of `now`
+    pub fn verify(&self, timestamp: i64, body: &[u8], signature: &str, now: i64) -> bool {
+        if (now - timestamp).abs() > MAX_SIGNATURE_AGE_SECS {
+            return false;
+        }
+        let Some(digest) = signature.strip_prefix(SCHEME).and_then(decode_hex) else {
+            return false;
+        };
+        self.mac(timestamp, body).verify_slice(&digest).is_ok()
+    }
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BODY: &[u8] = br#"{"guild_id":"1","translated_text":"Hola"}"#;
+
+    #[test]
+    fn test_signature_matches_known_vector() {
+        let signer = WebhookSigner::new("whsec_test");
+        assert_eq!(
+            signer.sign(1_760_000_000, BODY),
+            "v1=27e9d9de959499cc65c30942386fb225dd28590120bae689a555cd56a8f42898"
+        );
+    }
+
+    #[test]
+    fn test_verify_rejects_tampering_and_replays() {
+        let signer = WebhookSigner::new("whsec_test");
+        let now = 1_760_000_000;
+        let [(_, timestamp), (_, signature)] = signer.headers(now, BODY);
+        assert_eq!(timestamp, now.to_string());
+        assert!(signer.verify(now, BODY, &signature, now + 10));
+
+        assert!(!signer.verify(now, br#"{"guild_id":"2"}"#, &signature, now));
+        assert!(!signer.verify(now + 1, BODY, &signature, now));
+        assert!(!WebhookSigner::new("other").verify(now, BODY, &signature, now));
+        assert!(!signer.verify(now, BODY, &signature, now + MAX_SIGNATURE_AGE_SECS + 1));
+        assert!(!signer.verify(now, BODY, "v1=zz", now));
+    }
+}
@@ -1,14 +1,18 @@
-use crate::db::{DbPool, WebSessionRepo};
+use crate::db::{DbHealth, DbPool, WebSession, WebSessionRepo};
+use crate::translation::Readiness;
 use crate::web::broadcast::BroadcastManager;
+use crate::web::connection_limit::ConnectionLimiter;
 use axum::{
     extract::{
         ws::{Message, WebSocket, WebSocketUpgrade},
-        Path, State,
+        ConnectInfo, Path, State,
     },
-    response::Response,
+    response::{IntoResponse, Response},
 };
 use futures::{SinkExt, StreamExt};
+use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::broadcast::error::RecvError;
 use tracing::{debug, error, info, warn};
 
@@ -17,15 +21,34 @@ use tracing::{debug, error, info, warn};
 pub struct AppState {
     pub pool: DbPool,
     pub broadcast: Arc<BroadcastManager>,
+    pub db_health: Arc<DbHealth>,
+    /// Features running disabled because a secret or dependency is missing
+    pub degraded_features: Arc<Vec<String>>,
+    /// Whether the inference service has come up
+    pub inference: Arc<Readiness>,
+    /// Open WebSocket viewers, capped overall and per address
+    pub connections: Arc<ConnectionLimiter>,
 }
 
+/// How often an open connection re-checks that its session is still valid,
+/// so revoked or expired viewers are disconnected
+const SESSION_RECHECK_INTERVAL: Duration = Duration::from_secs(15);
+
 /// WebSocket upgrade handler
 pub async fn ws_handler(
     ws: WebSocketUpgrade,
     Path(session_id): Path<String>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
     State(state): State<AppState>,
 ) -> Response {
-    ws.on_upgrade(move |socket| handle_socket(socket, session_id, state))
+    let permit = match state.connections.acquire(addr.ip()) {
+        Ok(permit) => permit,
+        Err(rejected) => return rejected.into_response(),
+    };
+    ws.on_upgrade(move |socket| async move {
+        handle_socket(socket, session_id, state).await;
+        drop(permit);
+    })
 }
 
 /// Handle WebSocket connection
@@ -81,13 +104,48 @@ async fn handle_socket(socket: WebSocket, session_id: String, state: AppState) {
         .await
         .is_err()
     {
+        release_session_channel(&state, &session);
         return;
     }
 
+    // Replay recent history for channel-scoped sessions
+    if let Some(ref channel_id) = session.channel_id {
+        for msg in state.broadcast.recent_channel_messages(channel_id) {
+            if let Ok(json) = serde_json::to_string(&msg) {
+                if sender.send(Message::Text(json.into())).await.is_err() {
+                    release_session_channel(&state, &session);
+                    return;
+                }
+            }
+        }
+    }
+
     // Spawn task to receive broadcast messages and forward to client
-    let send_task = tokio::spawn(async move {
+    let pool = state.pool.clone();
+    let session_id = session.session_id.clone();
+    let mut send_task = tokio::spawn(async move {
+        let mut recheck = tokio::time::interval(SESSION_RECHECK_INTERVAL);
+        recheck.tick().await;
         loop {
-            match rx.recv().await {
+            let received = tokio::select! {
+                received = rx.recv() => received,
+                _ = recheck.tick() => {
+                    if let Ok(None) = WebSessionRepo::get_by_session_id(&pool, &session_id).await {
+                        info!("Session ended, closing WebSocket: {}", &session_id[..8]);
+                        let _ = sender
+                            .send(Message::Text(
+                                serde_json::json!({"type": "error", "message": "Session revoked or expired"})
+                                    .to_string()
+                                    .into(),
+                            ))
+                            .await;
+                        let _ = sender.send(Message::Close(None)).await;
+                        break;
+                    }
+                    continue;
+                }
+            };
+            match received {
                 Ok(msg) => {
                     let json = match serde_json::to_string(&msg) {
                         Ok(j) => j,
@@ -112,7 +170,7 @@ async fn handle_socket(socket: WebSocket, session_id: String, state: AppState) {
     });
 
     // Spawn task to receive client messages (heartbeats, etc.)
-    let recv_task = tokio::spawn(async move {
+    let mut recv_task = tokio::spawn(async move {
         while let Some(result) = receiver.next().await {
             match result {
                 Ok(Message::Text(text)) => {
@@ -138,18 +196,30 @@ async fn handle_socket(socket: WebSocket, session_id: String, state: AppState) {
         }
     });
 
-    // Wait for either task to complete
+    // Wait for either task to complete, then stop the other so it (and its
+    // broadcast receiver) doesn't outlive the connection
     tokio::select! {
-        _ = send_task => {
+        _ = &mut send_task => {
             debug!("Send task completed");
+            recv_task.abort();
         }
-        _ = recv_task => {
+        _ = &mut recv_task => {
             debug!("Receive task completed");
+            send_task.abort();
         }
     }
+    let _ = send_task.await;
+    release_session_channel(&state, &session);
 
     info!(
         "WebSocket disconnected: session={}",
         &session.session_id[..8]
     );
 }
+
+/// Restart the replay grace period of a channel-scoped session's history
+fn release_session_channel(state: &AppState, session: &WebSession) {
+    if let Some(ref channel_id) = session.channel_id {
+        state.broadcast.release_text_channel(channel_id);
+    }
+}
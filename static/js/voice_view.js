@@ -11,9 +11,29 @@
     const volumeLabel = document.getElementById('volumeLabel');
     const queueStatus = document.getElementById('queueStatus');
 
+    // MIME type for each TTS format the server may send
+    const AUDIO_MIME_TYPES = {
+        wav: 'audio/wav',
+        opus: 'audio/ogg',
+        mp3: 'audio/mpeg',
+    };
+
     let audioQueue = [];
     let isPlaying = false;
     let currentAudio = null;
+    // Newest message timestamp rendered, used to skip replayed lines we already show
+    let lastTimestamp = 0;
+    let socketConnected = false;
+    // Bot's connection to the inference service, as last reported
+    let inferenceState = 'connected';
+
+    // What to show while the feed is up but inference is not
+    const INFERENCE_STATUS_TEXT = {
+        connecting: 'Connecting to inference…',
+        reconnecting: 'Reconnecting to inference…',
+        disconnected: 'Inference unavailable',
+        incompatible: 'Inference unavailable (version mismatch)',
+    };
 
     // Volume control
     volumeSlider.addEventListener('input', () => {
@@ -56,8 +76,9 @@
         isPlaying = true;
         queueStatus.textContent = 'Queue: ' + audioQueue.length;
 
-        const audioData = audioQueue.shift();
-        const audio = new Audio('data:audio/wav;base64,' + audioData);
+        const clip = audioQueue.shift();
+        const mimeType = AUDIO_MIME_TYPES[clip.format] || 'audio/wav';
+        const audio = new Audio('data:' + mimeType + ';base64,' + clip.data);
         audio.volume = volumeSlider.value / 100;
         currentAudio = audio;
 
@@ -78,10 +99,10 @@
         });
     }
 
-    function queueAudio(base64Audio) {
+    function queueAudio(base64Audio, format) {
         if (!ttsEnabled.checked || !base64Audio) return;
 
-        audioQueue.push(base64Audio);
+        audioQueue.push({ data: base64Audio, format: format });
         queueStatus.textContent = 'Queue: ' + audioQueue.length;
 
         if (!isPlaying) {
@@ -103,22 +124,31 @@
         return username.split(' ').map(n => n[0]).join('').substring(0, 2).toUpperCase();
     }
 
-    function onStatusChange(connected) {
-        if (connected) {
-            statusDot.classList.add('connected');
-            statusText.textContent = 'Live';
-        } else {
-            statusDot.classList.remove('connected');
+    function renderStatus() {
+        const live = socketConnected && inferenceState === 'connected';
+        statusDot.classList.toggle('connected', live);
+        if (!socketConnected) {
             statusText.textContent = 'Disconnected';
+        } else {
+            statusText.textContent = INFERENCE_STATUS_TEXT[inferenceState] || 'Live';
         }
     }
 
+    function onStatusChange(connected) {
+        socketConnected = connected;
+        renderStatus();
+    }
+
     function onMessage(data) {
         if (data.type === 'voice_transcription') {
+            if (data.replay && data.timestamp <= lastTimestamp) return;
             addMessage(data);
-            if (data.tts_audio) {
-                queueAudio(data.tts_audio);
+            if (data.tts_audio && !data.replay) {
+                queueAudio(data.tts_audio, data.tts_format);
             }
+        } else if (data.type === 'inference_status') {
+            inferenceState = data.state;
+            renderStatus();
         } else if (data.type === 'welcome') {
             console.log('Connected:', data.message);
         } else if (data.type === 'error') {
@@ -130,9 +160,14 @@
         emptyState.style.display = 'none';
 
         const messageEl = document.createElement('div');
-        messageEl.className = 'message';
+        messageEl.className = data.replay ? 'message replay' : 'message';
+        if (data.low_confidence) {
+            messageEl.classList.add('low-confidence');
+            messageEl.title = `Low confidence (${Math.round(data.confidence * 100)}%)`;
+        }
+        lastTimestamp = Math.max(lastTimestamp, data.timestamp);
 
-        const speakerColor = getSpeakerColor(data.user_id);
+        const speakerColor = getSpeakerColor(data.user_id || data.username);
         const initials = getInitials(data.username);
         const relativeTime = formatRelativeTime(data.timestamp);
 
@@ -1,12 +1,15 @@
 (function () {
     const config = window.__CONFIG;
-    const wsUrl = config.wsUrl + '/ws/' + config.sessionId;
+    const wsUrl = config.wsUrl;
 
     const messagesEl = document.getElementById('messages');
     const emptyState = document.getElementById('emptyState');
     const statusDot = document.getElementById('statusDot');
     const statusText = document.getElementById('statusText');
 
+    // Newest message timestamp rendered, used to skip replayed lines we already show
+    let lastTimestamp = 0;
+
     function onStatusChange(connected) {
         if (connected) {
             statusDot.classList.add('connected');
@@ -19,6 +22,7 @@
 
     function onMessage(data) {
         if (data.type === 'translation') {
+            if (data.replay && data.timestamp <= lastTimestamp) return;
             addMessage(data);
         } else if (data.type === 'error') {
             statusText.textContent = data.message;
@@ -29,7 +33,8 @@
         emptyState.style.display = 'none';
 
         const messageEl = document.createElement('div');
-        messageEl.className = 'message';
+        messageEl.className = data.replay ? 'message replay' : 'message';
+        lastTimestamp = Math.max(lastTimestamp, data.timestamp);
 
         const time = new Date(data.timestamp).toLocaleTimeString();
 
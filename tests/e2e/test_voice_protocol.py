@@ -23,6 +23,7 @@ from voice_protocol import (
     create_result_response,
     create_error_response,
     create_pong_response,
+    strip_overlap,
     VoiceProtocolError,
 )
 
@@ -319,6 +320,33 @@ class TestAudioHashCorrelation:
         # Verify hash matches
         assert returned_hash == audio_hash_from_rust
 
+    def test_trace_id_roundtrip(self):
+        """Test that the per-segment trace_id comes back on results and errors."""
+        trace_id = "3f2b8c1e-7d4a-4e8b-9c0f-1a2b3c4d5e6f"
+        header = {'type': 'Audio', 'audio_hash': 1, 'trace_id': trace_id}
+        header_bytes = json.dumps(header).encode('utf-8')
+        binary_frame = struct.pack('<I', len(header_bytes)) + header_bytes
+        parsed_header, _ = parse_binary_frame(binary_frame)
+
+        response = json.loads(create_result_response(
+            guild_id="1",
+            channel_id="2",
+            user_id="3",
+            username="TraceUser",
+            original_text="hi",
+            translated_text="hola",
+            source_language="en",
+            target_language="es",
+            tts_audio=None,
+            latency_ms=50,
+            audio_hash=parsed_header['audio_hash'],
+            trace_id=parsed_header['trace_id'],
+        ))
+        assert response['trace_id'] == trace_id
+
+        error = json.loads(create_error_response("boom", "PROCESSING_ERROR", trace_id))
+        assert error['trace_id'] == trace_id
+
 
 class TestProtocolCompatibility:
     """Test backward compatibility with legacy text protocol."""
@@ -340,5 +368,23 @@ class TestProtocolCompatibility:
         assert parsed_text['type'] == 'Ping'
 
 
+class TestChunkOverlap:
+    """Test dropping words repeated by streamed chunk overlap."""
+
+    def test_repeated_words_are_dropped(self):
+        assert strip_overlap("I think we should", "Should go to the park") == "go to the park"
+        assert strip_overlap("see you at the", "at the station.") == "station."
+
+    def test_longest_repeat_wins(self):
+        assert strip_overlap("it is what it is", "what it is now") == "now"
+
+    def test_unrelated_text_is_kept(self):
+        assert strip_overlap("hello there", "general Kenobi") == "general Kenobi"
+        assert strip_overlap("", "hello") == "hello"
+
+    def test_chunk_of_only_overlap_becomes_empty(self):
+        assert strip_overlap("we should go", "go") == ""
+
+
 if __name__ == '__main__':
     pytest.main([__file__, '-v'])
@@ -7,22 +7,29 @@
 
 use futures::{SinkExt, StreamExt};
 use serde_json::json;
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::net::TcpListener;
 use tokio::sync::Mutex;
-use tokio_tungstenite::{accept_async, tungstenite::Message};
+use tokio_tungstenite::{
+    accept_async_with_config,
+    tungstenite::{protocol::WebSocketConfig, Message},
+};
 
 // Import from the actual voice module
+use linguabridge::admin::InferenceTokens;
+use linguabridge::translation::Formality;
 use linguabridge::voice::cache::VoiceTranscriptionCache;
 use linguabridge::voice::client::{
     ConnectionState, QueueFullStrategy, VoiceClientConfig, VoiceInferenceClient,
 };
-use linguabridge::voice::types::{AudioSegment, VoiceInferenceResponse};
+use linguabridge::voice::types::{AudioSegment, VoiceInferenceResponse, PROTOCOL_VERSION};
 
 /// Mock Python inference server for testing.
 ///
 /// Mimics the behavior of the actual Python WebSocket server:
+/// - Announces its protocol version in a Ready message on connect
 /// - Receives binary frames (Rust format)
 /// - Parses them correctly
 /// - Sends back responses with matching audio_hash
@@ -40,6 +47,31 @@ struct MockPythonServer {
 impl MockPythonServer {
     /// Start mock server on random port
     async fn start() -> Self {
+        Self::start_with_protocol(Some(PROTOCOL_VERSION)).await
+    }
+
+    /// Start mock server that reports `protocol_version` in its Ready message
+    async fn start_with_protocol(protocol_version: Option<u32>) -> Self {
+        Self::start_with(protocol_version, None, false).await
+    }
+
+    /// Start mock server that drops the connection on messages larger than
+    /// `max_message_size` bytes, like uvicorn's `ws_max_size`
+    async fn start_with_max_message_size(max_message_size: usize) -> Self {
+        Self::start_with(Some(PROTOCOL_VERSION), Some(max_message_size), false).await
+    }
+
+    /// Start mock server that closes every connection right after its Ready
+    /// message, like a service restarting
+    async fn start_hanging_up() -> Self {
+        Self::start_with(Some(PROTOCOL_VERSION), None, true).await
+    }
+
+    async fn start_with(
+        protocol_version: Option<u32>,
+        max_message_size: Option<usize>,
+        hang_up: bool,
+    ) -> Self {
         let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
         let addr = listener.local_addr().unwrap();
         let url = format!("ws://{}", addr);
@@ -54,13 +86,36 @@ impl MockPythonServer {
                         let frames = Arc::clone(&received_frames_clone);
 
                         tokio::spawn(async move {
-                            let ws = match accept_async(stream).await {
+                            let ws_config = WebSocketConfig {
+                                max_message_size,
+                                max_frame_size: max_message_size,
+                                ..Default::default()
+                            };
+                            let ws = match accept_async_with_config(stream, Some(ws_config)).await {
                                 Ok(ws) => ws,
                                 Err(_) => return,
                             };
 
                             let (mut write, mut read) = ws.split();
 
+                            let ready = json!({
+                                "type": "Ready",
+                                "stt_models": [],
+                                "tts_models": [],
+                                "protocol_version": protocol_version,
+                            });
+                            if write
+                                .send(Message::Text(serde_json::to_string(&ready).unwrap()))
+                                .await
+                                .is_err()
+                            {
+                                return;
+                            }
+                            if hang_up {
+                                let _ = write.send(Message::Close(None)).await;
+                                return;
+                            }
+
                             while let Some(Ok(msg)) = read.next().await {
                                 match msg {
                                     Message::Binary(data) => {
@@ -94,11 +149,15 @@ impl MockPythonServer {
                                                             "username": header["username"],
                                                             "original_text": "test audio",
                                                             "translated_text": "audio de prueba",
-                                                            "source_language": "en",
+                                                            // "Detect" English unless the client fixed the language
+                                                            "source_language": header["source_language"].as_str().unwrap_or("en"),
                                                             "target_language": header["target_language"],
                                                             "tts_audio": null,
                                                             "latency_ms": 100,
                                                             "audio_hash": header["audio_hash"], // CRITICAL: Echo back for cache
+                                                            "confidence": 0.82,
+                                                            "started_at": header["started_at"],
+                                                            "trace_id": header["trace_id"],
                                                         });
 
                                                         let response_str =
@@ -166,6 +225,8 @@ fn create_test_audio_segment(user_id: u64, samples: Vec<i16>) -> AudioSegment {
         samples,
         start_time: now,
         end_time: now + Duration::from_millis(1500),
+        started_at: chrono::Utc::now(),
+        overlap_samples: 0,
     }
 }
 
@@ -220,7 +281,7 @@ async fn test_binary_protocol_end_to_end() {
 
     // Send audio to mock server
     client
-        .send_audio(segment.clone(), "en", false, audio_hash)
+        .send_audio(segment.clone(), None, "en", Formality::Default, false, audio_hash)
         .await
         .expect("Should send audio successfully");
 
@@ -260,8 +321,13 @@ async fn test_binary_protocol_end_to_end() {
     assert_eq!(header["type"], "Audio");
     assert_eq!(header["audio_hash"], audio_hash);
     assert_eq!(header["target_language"], "en");
+    assert!(header["source_language"].is_null(), "auto-detect sends no source language");
     assert_eq!(header["user_id"], "123");
     assert_eq!(header["username"], "TestUser");
+    let trace_id: uuid::Uuid = header["trace_id"]
+        .as_str()
+        .and_then(|id| id.parse().ok())
+        .expect("every segment carries a UUID trace id");
 
     // Verify PCM data
     let pcm_bytes = &frame[4 + header_len..];
@@ -288,16 +354,32 @@ async fn test_binary_protocol_end_to_end() {
         VoiceInferenceResponse::Result {
             audio_hash: resp_hash,
             translated_text,
+            source_language,
+            confidence,
+            started_at,
+            trace_id: resp_trace_id,
             ..
         } => {
+            assert_eq!(source_language, "en", "detected language must reach the client");
+            assert_eq!(
+                resp_trace_id,
+                Some(trace_id),
+                "trace_id must roundtrip so both services' logs can be correlated"
+            );
             assert_eq!(
                 resp_hash, audio_hash,
                 "audio_hash must roundtrip correctly for cache"
             );
+            assert_eq!(
+                started_at,
+                Some(segment.started_at),
+                "speech start time must roundtrip for transcript alignment"
+            );
             assert_eq!(
                 translated_text, "audio de prueba",
                 "Should receive correct translation"
             );
+            assert_eq!(confidence, Some(0.82), "confidence must reach the client");
         }
         _ => panic!("Expected Result response, got {:?}", response),
     }
@@ -361,7 +443,7 @@ async fn test_cache_correlation_through_full_cycle() {
 
     // First request (cache miss)
     client
-        .send_audio(segment.clone(), &target_lang, false, audio_hash)
+        .send_audio(segment.clone(), None, &target_lang, Formality::Default, false, audio_hash)
         .await
         .expect("Should send audio");
 
@@ -481,7 +563,7 @@ async fn test_websocket_disconnect_recovery() {
     let hash1 = VoiceTranscriptionCache::hash_audio(&segment1.samples);
 
     client
-        .send_audio(segment1, "en", false, hash1)
+        .send_audio(segment1, None, "en", Formality::Default, false, hash1)
         .await
         .expect("First send should succeed");
 
@@ -496,7 +578,9 @@ async fn test_websocket_disconnect_recovery() {
         let hash = VoiceTranscriptionCache::hash_audio(&segment.samples);
 
         // THIS SHOULD NOT PANIC - that's what we're testing
-        let _result = client.send_audio(segment, "en", false, hash).await;
+        let _result = client
+            .send_audio(segment, None, "en", Formality::Default, false, hash)
+            .await;
 
         // Brief delay between attempts
         tokio::time::sleep(Duration::from_millis(100)).await;
@@ -507,6 +591,250 @@ async fn test_websocket_disconnect_recovery() {
     println!("✅ WebSocket disconnect recovery test passed - no panic on disconnect");
 }
 
+#[tokio::test]
+async fn test_segments_carry_their_guilds_inference_token() {
+    //! Test: each guild authenticates to the inference service as its own tenant
+    //!
+    //! Verifies:
+    //! 1. A guild with its own token sends that token
+    //! 2. Other guilds send the shared token
+
+    let server = MockPythonServer::start().await;
+    let tokens = InferenceTokens::from_custom_secrets(&HashMap::from([
+        ("inference_token".to_string(), "shared".to_string()),
+        ("inference_token.123456789".to_string(), "tenant-a".to_string()),
+    ]));
+    let client = VoiceInferenceClient::new(VoiceClientConfig {
+        url: server.url.clone(),
+        reconnect_delay: Duration::from_millis(100),
+        inference_tokens: tokens,
+        ..Default::default()
+    });
+    tokio::time::timeout(Duration::from_secs(2), async {
+        while !client.is_connected().await {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    })
+    .await
+    .expect("Client should connect to mock server");
+
+    let own_tenant = create_test_audio_segment(1, vec![100; 10]);
+    let mut shared_tenant = create_test_audio_segment(2, vec![200; 10]);
+    shared_tenant.guild_id = 42;
+    for segment in [own_tenant, shared_tenant] {
+        let hash = VoiceTranscriptionCache::hash_audio(&segment.samples);
+        client.send_audio(segment, None, "en", Formality::Default, false, hash).await.unwrap();
+    }
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let tokens: Vec<serde_json::Value> = server
+        .get_received_frames()
+        .await
+        .iter()
+        .map(|frame| {
+            let header_len = u32::from_le_bytes([frame[0], frame[1], frame[2], frame[3]]) as usize;
+            let header: serde_json::Value = serde_json::from_slice(&frame[4..4 + header_len]).unwrap();
+            header["inference_token"].clone()
+        })
+        .collect();
+    assert_eq!(tokens, vec![json!("tenant-a"), json!("shared")]);
+}
+
+#[tokio::test]
+async fn test_same_language_segments_skip_translation() {
+    //! Test: speakers already using the target language aren't translated
+    //!
+    //! Verifies:
+    //! 1. A segment whose spoken language is the target asks for TTS but not MT
+    //! 2. A foreign-language segment still asks for translation
+
+    let server = MockPythonServer::start().await;
+    let client = VoiceInferenceClient::new(VoiceClientConfig {
+        url: server.url.clone(),
+        reconnect_delay: Duration::from_millis(100),
+        ..Default::default()
+    });
+    tokio::time::timeout(Duration::from_secs(2), async {
+        while !client.is_connected().await {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    })
+    .await
+    .expect("Client should connect to mock server");
+
+    for (user_id, source) in [(1, "en"), (2, "de")] {
+        let segment = create_test_audio_segment(user_id, vec![100 * user_id as i16; 10]);
+        let hash = VoiceTranscriptionCache::hash_audio(&segment.samples);
+        client
+            .send_audio(segment, Some(source), "en", Formality::Default, true, hash)
+            .await
+            .unwrap();
+    }
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let stages: Vec<serde_json::Value> = server
+        .get_received_frames()
+        .await
+        .iter()
+        .map(|frame| {
+            let header_len = u32::from_le_bytes([frame[0], frame[1], frame[2], frame[3]]) as usize;
+            let header: serde_json::Value = serde_json::from_slice(&frame[4..4 + header_len]).unwrap();
+            header["stages"].clone()
+        })
+        .collect();
+    assert_eq!(
+        stages,
+        vec![json!(["transcribe", "tts"]), json!(["transcribe", "translate", "tts"])]
+    );
+}
+
+#[tokio::test]
+async fn test_slow_tts_does_not_hold_up_later_results() {
+    //! Test: speech from a separate TTS service is added off the read loop
+    //!
+    //! Verifies:
+    //! 1. A result waiting on slow synthesis doesn't delay the next one
+    //! 2. The slow result still arrives, with its audio
+
+    // TTS service that takes its time over Spanish
+    let tts = axum::Router::new().route(
+        "/tts",
+        axum::routing::post(|axum::Json(body): axum::Json<serde_json::Value>| async move {
+            if body["language"] == "es" {
+                tokio::time::sleep(Duration::from_millis(1500)).await;
+            }
+            axum::Json(json!({"audio": "UklGRg==", "format": "wav"}))
+        }),
+    );
+    let tts_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let tts_url = format!("http://{}", tts_listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(tts_listener, tts).await.unwrap() });
+
+    let server = MockPythonServer::start().await;
+    let client = VoiceInferenceClient::new(VoiceClientConfig {
+        url: server.url.clone(),
+        tts_url: Some(tts_url),
+        reconnect_delay: Duration::from_millis(100),
+        ..Default::default()
+    });
+    let mut results = client.subscribe();
+    tokio::time::timeout(Duration::from_secs(2), async {
+        while !client.is_connected().await {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    })
+    .await
+    .expect("Client should connect to mock server");
+
+    for (user_id, target) in [(1, "es"), (2, "de")] {
+        let segment = create_test_audio_segment(user_id, vec![100 * user_id as i16; 10]);
+        let hash = VoiceTranscriptionCache::hash_audio(&segment.samples);
+        client
+            .send_audio(segment, Some("en"), target, Formality::Default, true, hash)
+            .await
+            .unwrap();
+    }
+
+    let mut arrived = Vec::new();
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while arrived.len() < 2 {
+            if let Ok(VoiceInferenceResponse::Result { target_language, tts_audio, .. }) = results.recv().await {
+                arrived.push((target_language, tts_audio));
+            }
+        }
+    })
+    .await
+    .expect("Both results should arrive");
+    assert_eq!(
+        arrived,
+        vec![
+            ("de".to_string(), Some("UklGRg==".to_string())),
+            ("es".to_string(), Some("UklGRg==".to_string())),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_state_changes_are_published() {
+    //! Test: subscribers see connection state transitions
+    //!
+    //! Verifies:
+    //! 1. Connecting and Connected are announced on connect
+    //! 2. A server hanging up is noticed without any audio being sent
+    //! 3. Reconnecting is announced after the disconnect
+
+    let server = MockPythonServer::start_hanging_up().await;
+    let config = VoiceClientConfig {
+        url: server.url.clone(),
+        reconnect_delay: Duration::from_millis(50),
+        ..Default::default()
+    };
+    let client = VoiceInferenceClient::new(config);
+    let mut states = client.subscribe_state();
+
+    let mut seen = Vec::new();
+    tokio::time::timeout(Duration::from_secs(2), async {
+        while !seen.contains(&ConnectionState::Reconnecting) {
+            seen.push(states.recv().await.unwrap());
+        }
+    })
+    .await
+    .unwrap_or_else(|_| panic!("expected a reconnect, saw {:?}", seen));
+
+    assert_eq!(
+        seen,
+        vec![
+            ConnectionState::Connecting,
+            ConnectionState::Connected,
+            ConnectionState::Reconnecting,
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_protocol_version_mismatch_refuses_to_operate() {
+    //! Test: a server speaking another protocol version is rejected
+    //!
+    //! Verifies:
+    //! 1. The client ends in the distinct Incompatible state, not Connected
+    //! 2. It does not keep reconnecting
+    //! 3. Audio is refused instead of being sent as garbage
+
+    for server_version in [Some(PROTOCOL_VERSION + 1), None] {
+        let server = MockPythonServer::start_with_protocol(server_version).await;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let config = VoiceClientConfig {
+            url: server.url.clone(),
+            reconnect_delay: Duration::from_millis(50),
+            ..Default::default()
+        };
+        let client = VoiceInferenceClient::new(config);
+
+        tokio::time::timeout(Duration::from_secs(2), async {
+            while client.state().await != ConnectionState::Incompatible {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        })
+        .await
+        .unwrap_or_else(|_| panic!("server version {:?} should be rejected", server_version));
+
+        // Stays incompatible rather than retrying
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(client.state().await, ConnectionState::Incompatible);
+
+        let segment = create_test_audio_segment(1, vec![1, 2, 3]);
+        let hash = VoiceTranscriptionCache::hash_audio(&segment.samples);
+        assert!(client
+            .send_audio(segment, None, "en", Formality::Default, false, hash)
+            .await
+            .is_err());
+        assert!(server.get_received_frames().await.is_empty());
+    }
+
+    println!("✅ Protocol mismatch test passed - incompatible server refused");
+}
+
 #[tokio::test]
 async fn test_backpressure_queue_full() {
     //! Test 4: Backpressure when queue is full
@@ -552,7 +880,7 @@ async fn test_backpressure_queue_full() {
         let segment = create_test_audio_segment(i, vec![i as i16; 100]);
         let hash = VoiceTranscriptionCache::hash_audio(&segment.samples);
 
-        match client.send_audio(segment, "en", false, hash).await {
+        match client.send_audio(segment, None, "en", Formality::Default, false, hash).await {
             Ok(_) => success_count += 1,
             Err(_) => dropped_count += 1,
         }
@@ -636,7 +964,7 @@ async fn test_concurrent_requests_no_deadlock() {
                 if cache.get(audio_hash, &target_lang).await.is_none() {
                     // Cache miss - send to inference
                     let _ = client
-                        .send_audio(segment, &target_lang, false, audio_hash)
+                        .send_audio(segment, None, &target_lang, Formality::Default, false, audio_hash)
                         .await;
                 }
 
@@ -691,3 +1019,64 @@ async fn test_audio_hash_stability() {
 
     println!("✅ Audio hash stability test passed");
 }
+
+#[tokio::test]
+async fn test_long_segment_is_split_to_fit_frame_limit() {
+    //! A long utterance must not exceed the server's message limit and kill
+    //! the connection; it goes out in pieces that are each answered.
+
+    let max_frame_size = 256 * 1024;
+    let server = MockPythonServer::start_with_max_message_size(max_frame_size).await;
+    let config = VoiceClientConfig {
+        url: server.url.clone(),
+        reconnect_delay: Duration::from_millis(100),
+        max_frame_size,
+        ..Default::default()
+    };
+    let client = VoiceInferenceClient::new(config);
+
+    tokio::time::timeout(Duration::from_secs(2), async {
+        while !client.is_connected().await {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    })
+    .await
+    .expect("Client should connect to mock server");
+
+    // Ten seconds at 48kHz: ~940 KiB of PCM, far over the limit
+    let samples: Vec<i16> = (0..480_000).map(|i| (i % 1000) as i16).collect();
+    let segment = create_test_audio_segment(123, samples.clone());
+    let audio_hash = VoiceTranscriptionCache::hash_audio(&segment.samples);
+
+    let mut result_rx = client.subscribe();
+    client
+        .send_audio(segment, None, "es", Formality::Default, false, audio_hash)
+        .await
+        .expect("Long segment should be accepted");
+
+    let frames = tokio::time::timeout(Duration::from_secs(5), async {
+        let mut answered = 0;
+        loop {
+            if let Ok(VoiceInferenceResponse::Result { .. }) = result_rx.recv().await {
+                answered += 1;
+            }
+            let frames = server.get_received_frames().await;
+            if answered == frames.len() && answered >= 4 {
+                return frames;
+            }
+        }
+    })
+    .await
+    .expect("Every piece should be answered");
+
+    assert!(frames.iter().all(|f| f.len() <= max_frame_size));
+    let pcm_bytes: usize = frames
+        .iter()
+        .map(|f| {
+            let header_len = u32::from_le_bytes([f[0], f[1], f[2], f[3]]) as usize;
+            f.len() - 4 - header_len
+        })
+        .sum();
+    assert_eq!(pcm_bytes, samples.len() * 2, "No audio should be lost");
+    assert!(client.is_connected().await, "Connection should survive");
+}